@@ -1,5 +1,5 @@
 import { expect } from "chai";
-import { keccak256 } from "ethers";
+import { concat, hexlify, keccak256, toUtf8Bytes, zeroPadValue } from "ethers";
 import { Block, Bytes, TransactionReceipt } from "web3-types";
 
 import { ALICE, BOB } from "../helpers/account";
@@ -39,6 +39,9 @@ describe("JSON-RPC", () => {
         it("eth_chainId", async () => {
             (await sendExpect("eth_chainId")).eq(CHAIN_ID);
         });
+        it("eth_syncing", async () => {
+            (await sendExpect("eth_syncing")).eq(false);
+        });
         it("net_listening", async () => {
             (await sendExpect("net_listening")).eq(true);
         });
@@ -48,7 +51,7 @@ describe("JSON-RPC", () => {
         it("web3_clientVersion", async () => {
             let client = await sendExpect("web3_clientVersion");
             if (isStratus) {
-                client.eq("stratus");
+                client.match(/^stratus\/v/);
             }
         });
     });
@@ -62,6 +65,20 @@ describe("JSON-RPC", () => {
                 gasPrice.not.eq(ZERO);
             }
         });
+        it("eth_maxPriorityFeePerGas", async () => {
+            let fee = await sendExpect("eth_maxPriorityFeePerGas");
+            if (isStratus) {
+                fee.eq(ZERO);
+            }
+        });
+        it("eth_blobBaseFee", async () => {
+            let fee = await sendExpect("eth_blobBaseFee");
+            if (isStratus) {
+                fee.eq(ZERO);
+            } else {
+                fee.not.eq(ZERO);
+            }
+        });
         it("eth_estimateGas", async () => {
             let tx = { from: ALICE.address, to: BOB.address, value: "0x1" };
             let gas = await send("eth_estimateGas", [tx]);
@@ -82,6 +99,10 @@ describe("JSON-RPC", () => {
             (await sendExpect("eth_getBalance", [ALICE])).eq(TEST_BALANCE);
             (await sendExpect("eth_getBalance", [ALICE, "latest"])).eq(TEST_BALANCE);
         });
+        it("eth_getBalance at unknown block returns header not found", async () => {
+            expect((await sendAndGetError("eth_getBalance", [ALICE, HASH_ZERO])).message).eq("header not found");
+            expect((await sendAndGetError("eth_getBalance", [ALICE, "0xffffffff"])).message).eq("header not found");
+        });
         describe("eth_getCode", () => {
             it("contract code is available in the block it was deployed", async () => {
                 await sendReset();
@@ -93,6 +114,35 @@ describe("JSON-RPC", () => {
                 (await sendExpect("eth_getCode", [ALICE.address, "latest"])).eq("0x");
             });
         });
+        describe("stratus_computeMappingSlot", () => {
+            it("computes slot of value type key", async () => {
+                const expected = keccak256(concat([zeroPadValue(ALICE.address, 32), zeroPadValue("0x01", 32)]));
+                (await sendExpect("stratus_computeMappingSlot", ["0x1", ALICE.address])).eq(expected);
+            });
+            it("computes slot of dynamic key", async () => {
+                const key = toUtf8Bytes("stratus");
+                const expected = keccak256(concat([key, zeroPadValue("0x01", 32)]));
+                (await sendExpect("stratus_computeMappingSlot", ["0x1", hexlify(key), true])).eq(expected);
+            });
+        });
+        it("stratus_getPendingBlock", async () => {
+            await sendReset();
+            const pending = await send("stratus_getPendingBlock");
+            expect(pending.number).eq(toHex(1));
+            expect(pending.transactions).to.be.empty;
+            expect(pending.deferredTransactions).to.be.empty;
+            expect(pending.temporaryStorage.pendingTransactions).eq(0);
+        });
+        it("stratus_getTopContracts", async () => {
+            expect(await send("stratus_getTopContracts", ["5m"])).to.be.an("array");
+            expect((await sendAndGetError("stratus_getTopContracts", ["2h"])).code).exist;
+        });
+        it("stratus_getComparisonStats", async () => {
+            const stats = await send("stratus_getComparisonStats", ["5m"]);
+            expect(stats.totals.matched).eq(0);
+            expect(stats.recentMismatches).to.be.empty;
+            expect((await sendAndGetError("stratus_getComparisonStats", ["2h"])).code).exist;
+        });
     });
 
     describe("Block", () => {
@@ -111,6 +161,13 @@ describe("JSON-RPC", () => {
                 hash = block.hash as Bytes; // get the genesis hash to use on the next test
                 expect(block.transactions.length).eq(0);
             });
+            it("includes post-merge fields", async () => {
+                const block = await send("eth_getBlockByNumber", [ZERO, false]);
+                expect(block.withdrawals).to.deep.eq([]);
+                expect(block.withdrawalsRoot).eq("0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421");
+                expect(block.prevRandao).eq(block.mixHash);
+                expect(block.difficulty).eq("0x0");
+            });
             it("returns null if block does not exist", async () => {
                 const NON_EXISTANT_BLOCK = "0xfffffff";
                 let block = await send("eth_getBlockByNumber", [NON_EXISTANT_BLOCK, true]);
@@ -128,6 +185,48 @@ describe("JSON-RPC", () => {
                 expect(block).to.be.null;
             });
         });
+        describe("eth_getBlockReceipts", () => {
+            it("returns all receipts of a block", async () => {
+                const contract = await deployTestContractBalances();
+                const txResponse = await contract.connect(ALICE.signer()).add(ALICE.address, 10);
+                const txReceipt = await ETHERJS.getTransactionReceipt(txResponse.hash);
+                expect(txReceipt).exist;
+
+                const receipts: TransactionReceipt[] = await send("eth_getBlockReceipts", [toHex(txReceipt?.blockNumber ?? 0)]);
+                expect(receipts).length(1);
+                expect(receipts[0].transactionHash).eq(txResponse.hash);
+                expect(receipts[0].logs).length(1);
+            });
+            it("returns null if block does not exist", async () => {
+                (await sendExpect("eth_getBlockReceipts", ["0xfffffff"])).eq(null);
+            });
+        });
+        describe("stratus_getBlockStateDiff", () => {
+            it("returns accounts and slots modified by a block", async function () {
+                if (!isStratus) {
+                    this.skip();
+                }
+                const contract = await deployTestContractBalances();
+                const txResponse = await contract.connect(ALICE.signer()).add(ALICE.address, 10);
+                const txReceipt = await ETHERJS.getTransactionReceipt(txResponse.hash);
+                expect(txReceipt).exist;
+
+                const diff = await send("stratus_getBlockStateDiff", [toHex(txReceipt?.blockNumber ?? 0)]);
+                expect(diff.blockHash).eq(txReceipt?.blockHash);
+
+                const contractAddress = (await contract.getAddress()).toLowerCase();
+                const sender = diff.accounts.find((account: any) => account.address === ALICE.address.toLowerCase());
+                const target = diff.accounts.find((account: any) => account.address === contractAddress);
+                expect(sender.account).exist;
+                expect(target.slots).length.gte(1);
+            });
+            it("returns null if block does not exist", async function () {
+                if (!isStratus) {
+                    this.skip();
+                }
+                (await sendExpect("stratus_getBlockStateDiff", ["0xfffffff"])).eq(null);
+            });
+        });
         it("eth_getUncleByBlockHashAndIndex", async function () {
             if (isStratus) {
                 (await sendExpect("eth_getUncleByBlockHashAndIndex", [ZERO, ZERO])).eq(null);
@@ -155,6 +254,41 @@ describe("JSON-RPC", () => {
         });
     });
 
+    describe("Filters", () => {
+        it("eth_newFilter / eth_getFilterLogs", async () => {
+            // mine a test transaction
+            const contract = await deployTestContractBalances();
+            const txResponse = await contract.connect(ALICE.signer()).add(ALICE.address, 10);
+            const txReceipt = await ETHERJS.getTransactionReceipt(txResponse.hash);
+            expect(txReceipt?.status).eq(1);
+
+            // install a filter and read all logs matching it
+            const txBlockNumber = txReceipt?.blockNumber ?? 0;
+            const filterId = await send("eth_newFilter", [{ address: contract.target, fromBlock: toHex(txBlockNumber) }]);
+            expect(filterId).match(HEX_PATTERN);
+            expect(await send("eth_getFilterLogs", [filterId])).length(1);
+        });
+        it("eth_newBlockFilter / eth_getFilterChanges", async () => {
+            const filterId = await send("eth_newBlockFilter");
+            expect(await send("eth_getFilterChanges", [filterId])).length(0);
+
+            await sendEvmMine();
+            await new Promise((resolve) => setTimeout(resolve, 100));
+            const changes = await send("eth_getFilterChanges", [filterId]);
+            expect(changes).length(1);
+            expect(changes[0]).match(HEX_PATTERN);
+
+            // changes are cleared after being polled
+            expect(await send("eth_getFilterChanges", [filterId])).length(0);
+        });
+        it("eth_uninstallFilter", async () => {
+            const filterId = await send("eth_newPendingTransactionFilter");
+            expect(await send("eth_uninstallFilter", [filterId])).eq(true);
+            expect(await send("eth_uninstallFilter", [filterId])).eq(false);
+            expect((await sendAndGetError("eth_getFilterChanges", [filterId])).code).exist;
+        });
+    });
+
     describe("Transaction", () => {
         describe("eth_sendRawTransaction", () => {
             it("Returns an expected result when a contract transaction fails", async () => {
@@ -178,6 +312,25 @@ describe("JSON-RPC", () => {
                 expect(actualTxHash).eq(expectedTxHash);
             });
         });
+        describe("stratus_replayTransaction", () => {
+            it("replays a mined transaction without differences", async () => {
+                const contract = await deployTestContractBalances();
+                const signedTx = await prepareSignedTx({
+                    contract,
+                    account: ALICE,
+                    methodName: "add",
+                    methodParameters: [ALICE.address, 1],
+                });
+                const txHash = await sendRawTransaction(signedTx);
+
+                const replay = await send("stratus_replayTransaction", [txHash]);
+                expect(replay.txHash).eq(txHash);
+                expect(replay.differences).to.be.empty;
+            });
+            it("returns null for unknown transactions", async () => {
+                expect(await send("stratus_replayTransaction", [HASH_ZERO])).eq(null);
+            });
+        });
     });
 
     describe("Call", () => {
@@ -214,6 +367,43 @@ describe("JSON-RPC", () => {
                 expect(currentAliceBalance).eq(expectedAliceBalance);
             });
         });
+
+        describe("eth_callMany", () => {
+            it("Carries state changes between calls of the same bundle", async () => {
+                // deploy
+                const contract = await deployTestContractBalances();
+
+                const add = { to: contract.target, data: contract.interface.encodeFunctionData("add", [ALICE.address, 5]) };
+                const get = { to: contract.target, data: contract.interface.encodeFunctionData("get", [ALICE.address]) };
+                const results = await send("eth_callMany", [[add, get], "latest"]);
+
+                // validate bundle sees its own changes
+                expect(results).length(2);
+                expect(results[1].value).eq(toPaddedHex(5, 32));
+
+                // validate nothing was persisted
+                expect(await send("eth_call", [get, "latest"])).eq(toPaddedHex(0, 32));
+            });
+        });
+
+        describe("eth_createAccessList", () => {
+            it("Returns the accessed storage slots and the gas used", async () => {
+                const contract = await deployTestContractBalances();
+
+                const add = {
+                    from: ALICE.address,
+                    to: contract.target,
+                    data: contract.interface.encodeFunctionData("add", [ALICE.address, 5]),
+                };
+                const result = await send("eth_createAccessList", [add, "latest"]);
+
+                expect(result.accessList).length(1);
+                expect(result.accessList[0].address).eq((contract.target as string).toLowerCase());
+                expect(result.accessList[0].storageKeys).length(1);
+                expect(parseInt(result.gasUsed, 16)).gt(0);
+                expect(result.error).undefined;
+            });
+        });
     });
 
     describe("Evm", () => {
@@ -228,6 +418,28 @@ describe("JSON-RPC", () => {
             expect((await latest()).block_number).eq(prev_number + 1);
         });
 
+        it("evm_setAutomine", async () => {
+            const contract = await deployTestContractBalances();
+            const signedTx = await prepareSignedTx({
+                contract,
+                account: ALICE,
+                methodName: "add",
+                methodParameters: [ALICE.address, 1],
+            });
+
+            // transactions stay pending until a block is mined on demand
+            (await sendExpect("evm_setAutomine", [false])).eq(true);
+            const prev_number = (await latest()).block_number;
+            const txHash = await sendRawTransaction(signedTx);
+            expect((await latest()).block_number).eq(prev_number);
+
+            await sendEvmMine();
+            expect((await latest()).block_number).eq(prev_number + 1);
+            expect((await ETHERJS.getTransactionReceipt(txHash))?.blockNumber).eq(prev_number + 1);
+
+            (await sendExpect("evm_setAutomine", [true])).eq(true);
+        });
+
         describe("evm_setNextBlockTimestamp", () => {
             let target = Math.floor(Date.now() / 1000) + 10;
             it("sets the next block timestamp", async () => {
@@ -267,6 +479,20 @@ describe("JSON-RPC", () => {
                 await send("evm_setNextBlockTimestamp", [0]);
             });
         });
+
+        describe("evm_increaseTime", () => {
+            it("shifts the timestamp of subsequent blocks", async () => {
+                const before = Math.floor(Date.now() / 1000);
+                (await sendExpect("evm_increaseTime", [3600])).gte(3600);
+                await sendEvmMine();
+                expect((await latest()).timestamp).gte(before + 3600);
+
+                await sendEvmMine();
+                expect((await latest()).timestamp).gte(before + 3600);
+
+                await send("evm_setNextBlockTimestamp", [0]);
+            });
+        });
     });
 
     describe("Subscription", () => {
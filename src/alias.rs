@@ -12,6 +12,7 @@ pub type JsonValue = serde_json::Value;
 // -----------------------------------------------------------------------------
 // Ethers
 // -----------------------------------------------------------------------------
+pub type EthersAccessList = ethers_core::types::transaction::eip2930::AccessList;
 pub type EthersBlockVoid = ethers_core::types::Block<()>;
 pub type EthersBlockEthersTransaction = ethers_core::types::Block<ethers_core::types::Transaction>;
 pub type EthersBlockExternalTransaction = ethers_core::types::Block<ExternalTransaction>;
@@ -21,6 +22,68 @@ pub type EthersLog = ethers_core::types::Log;
 pub type EthersReceipt = ethers_core::types::TransactionReceipt;
 pub type EthersTransaction = ethers_core::types::Transaction;
 
+// -----------------------------------------------------------------------------
+// Alloy
+// -----------------------------------------------------------------------------
+#[cfg(feature = "alloy")]
+pub type AlloyAccessList = alloy_rpc_types_eth::AccessList;
+#[cfg(feature = "alloy")]
+pub type AlloyAccessListItem = alloy_rpc_types_eth::AccessListItem;
+#[cfg(feature = "alloy")]
+pub type AlloyB64 = alloy_primitives::B64;
+#[cfg(feature = "alloy")]
+pub type AlloyBlockAlloyTransaction = alloy_rpc_types_eth::Block<alloy_rpc_types_eth::Transaction>;
+#[cfg(feature = "alloy")]
+pub type AlloyBlockB256 = alloy_rpc_types_eth::Block<alloy_primitives::B256>;
+#[cfg(feature = "alloy")]
+pub type AlloyBloom = alloy_primitives::Bloom;
+#[cfg(feature = "alloy")]
+pub type AlloyHeader = alloy_rpc_types_eth::Header;
+#[cfg(feature = "alloy")]
+pub type AlloyLog = alloy_rpc_types_eth::Log;
+#[cfg(feature = "alloy")]
+pub type AlloyReceipt = alloy_rpc_types_eth::TransactionReceipt<alloy_rpc_types_eth::ReceiptEnvelope<AlloyLog>>;
+#[cfg(feature = "alloy")]
+pub type AlloySignature = alloy_rpc_types_eth::Signature;
+#[cfg(feature = "alloy")]
+pub type AlloyTransaction = alloy_rpc_types_eth::Transaction;
+
+// -----------------------------------------------------------------------------
+// JSON-RPC
+//
+// External types used to serialize primitives in JSON-RPC responses.
+// Ethers types are used by default and alloy types are used when the `alloy` feature is enabled.
+// -----------------------------------------------------------------------------
+#[cfg(not(feature = "alloy"))]
+pub type JsonRpcBlockWithTransactions = EthersBlockEthersTransaction;
+#[cfg(feature = "alloy")]
+pub type JsonRpcBlockWithTransactions = AlloyBlockAlloyTransaction;
+
+#[cfg(not(feature = "alloy"))]
+pub type JsonRpcBlockWithHashes = EthersBlockH256;
+#[cfg(feature = "alloy")]
+pub type JsonRpcBlockWithHashes = AlloyBlockB256;
+
+#[cfg(not(feature = "alloy"))]
+pub type JsonRpcHeader = EthersBlockVoid;
+#[cfg(feature = "alloy")]
+pub type JsonRpcHeader = AlloyHeader;
+
+#[cfg(not(feature = "alloy"))]
+pub type JsonRpcLog = EthersLog;
+#[cfg(feature = "alloy")]
+pub type JsonRpcLog = AlloyLog;
+
+#[cfg(not(feature = "alloy"))]
+pub type JsonRpcReceipt = EthersReceipt;
+#[cfg(feature = "alloy")]
+pub type JsonRpcReceipt = AlloyReceipt;
+
+#[cfg(not(feature = "alloy"))]
+pub type JsonRpcTransaction = EthersTransaction;
+#[cfg(feature = "alloy")]
+pub type JsonRpcTransaction = AlloyTransaction;
+
 // -----------------------------------------------------------------------------
 // REVM
 // -----------------------------------------------------------------------------
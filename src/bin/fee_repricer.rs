@@ -0,0 +1,115 @@
+//! Fee-repricer binary.
+//!
+//! Prices a range of mined blocks again under a different fee configuration (the miner base fee flags) and, optionally, a different
+//! gas schedule (the executor EVM spec), and reports the original and hypothetical fees totalized by account and contract. Nothing is
+//! written to the storage. See [`FeeRepricer`] for how fees are calculated.
+
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use anyhow::Context;
+use stratus::config::FeeRepricerConfig;
+use stratus::eth::executor::Executor;
+use stratus::eth::miner::FeeRepricer;
+use stratus::eth::miner::MinerMode;
+use stratus::eth::primitives::BlockFilter;
+use stratus::eth::primitives::BlockNumber;
+use stratus::eth::primitives::Wei;
+use stratus::eth::storage::StratusStorage;
+use stratus::ext::to_json_string_pretty;
+use stratus::utils::DropTimer;
+use stratus::GlobalServices;
+use stratus::GlobalState;
+#[cfg(all(not(target_env = "msvc"), any(feature = "jemalloc", feature = "jeprof")))]
+use tikv_jemallocator::Jemalloc;
+use tokio::task::block_in_place;
+
+#[cfg(all(not(target_env = "msvc"), any(feature = "jemalloc", feature = "jeprof")))]
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
+
+const TASK_NAME: &str = "fee-repricer";
+
+fn main() -> anyhow::Result<()> {
+    let global_services = GlobalServices::<FeeRepricerConfig>::init();
+    global_services.runtime.block_on(run(global_services.config))
+}
+
+async fn run(config: FeeRepricerConfig) -> anyhow::Result<()> {
+    let _timer = DropTimer::start("fee-repricer");
+
+    let storage = config.storage.init()?;
+    let base_fee = config.miner.base_fee.build(config.miner.block_gas_limit)?;
+    let mut repricer = FeeRepricer::new(base_fee, config.priority_fee.map(Wei::from));
+
+    // executor is used only to execute transactions again, so the miner never mines
+    let executor = if config.reexecute {
+        let miner = config.miner.init_with_mode(MinerMode::External, Arc::clone(&storage)).await?;
+        Some(config.executor.init(Arc::clone(&storage), Some(miner)))
+    } else {
+        None
+    };
+
+    let block_start = BlockNumber::from(config.block_start);
+    let block_end = match config.block_end {
+        Some(end) => BlockNumber::from(end),
+        None => storage.read_mined_block_number()?,
+    };
+    block_in_place(|| reprice_blocks(&storage, executor.as_deref(), &mut repricer, block_start, block_end))?;
+
+    // write report
+    let report = to_json_string_pretty(&repricer.finish());
+    match config.output {
+        Some(ref path) => fs::write(path, report).with_context(|| format!("failed to write report to {}", path.display()))?,
+        None => println!("{}", report),
+    }
+
+    // Explicitly block the `main` thread to drop the storage.
+    drop(storage);
+
+    Ok(())
+}
+
+fn reprice_blocks(
+    storage: &StratusStorage,
+    executor: Option<&dyn Executor>,
+    repricer: &mut FeeRepricer,
+    block_start: BlockNumber,
+    block_end: BlockNumber,
+) -> anyhow::Result<()> {
+    if block_start > block_end {
+        return Err(anyhow!("block start {} is greater than block end {}", block_start, block_end));
+    }
+    tracing::info!(%block_start, %block_end, reexecute = %executor.is_some(), "repricing blocks");
+
+    let mut number = block_start;
+    while number <= block_end {
+        if GlobalState::is_shutdown_warn(TASK_NAME) {
+            return Ok(());
+        }
+
+        let Some(block) = storage.read_block(&BlockFilter::Number(number))? else {
+            return Err(anyhow!("block {} not found in the permanent storage", number));
+        };
+
+        // execute again with the configured gas schedule
+        let repriced_gas = match executor {
+            Some(executor) => executor
+                .trace_block(&block, None)?
+                .into_iter()
+                .map(|(hash, execution)| (hash, execution.gas))
+                .collect(),
+            None => HashMap::new(),
+        };
+
+        repricer.add_block(&block, &repriced_gas);
+        if number.as_u64() % 1000 == 0 {
+            tracing::info!(block_number = %number, "repriced blocks");
+        }
+        number = number.next_block_number();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,172 @@
+//! Import-State binary.
+//!
+//! Loads accounts and slots of an existing chain from a CSV state file into the permanent storage, so the chain can be migrated to Stratus
+//! without replaying its blocks. See [`StateImportReader`] for the file format.
+//!
+//! The file is read in batches that are written by parallel writers, and the progress is reported periodically.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::anyhow;
+use anyhow::Context;
+use stratus::config::ImportStateConfig;
+use stratus::eth::primitives::BlockNumber;
+use stratus::eth::storage::StateImportBatch;
+use stratus::eth::storage::StateImportReader;
+use stratus::eth::storage::StratusStorage;
+use stratus::ext::MutexExt;
+use stratus::utils::calculate_tps;
+use stratus::utils::DropTimer;
+use stratus::GlobalServices;
+use stratus::GlobalState;
+#[cfg(all(not(target_env = "msvc"), any(feature = "jemalloc", feature = "jeprof")))]
+use tikv_jemallocator::Jemalloc;
+
+#[cfg(all(not(target_env = "msvc"), any(feature = "jemalloc", feature = "jeprof")))]
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
+
+/// Number of batches read ahead of the writers.
+const BACKLOG_SIZE: usize = 10;
+
+/// Interval between progress reports.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+const TASK_NAME: &str = "import-state";
+
+fn main() -> anyhow::Result<()> {
+    let global_services = GlobalServices::<ImportStateConfig>::init();
+    let _runtime_guard = global_services.runtime.enter();
+    run(global_services.config)
+}
+
+fn run(config: ImportStateConfig) -> anyhow::Result<()> {
+    let _timer = DropTimer::start("import-state");
+
+    if config.file.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("parquet")) {
+        return Err(anyhow!("parquet state files are not supported, convert the file to csv before importing"));
+    }
+
+    // init services
+    let storage = config.storage.init()?;
+
+    // only empty storages can be bootstrapped from a state file
+    let mined_number = storage.read_mined_block_number()?;
+    if mined_number != BlockNumber::ZERO {
+        return Err(anyhow!(
+            "cannot import state into a storage with mined blocks (mined block number is {})",
+            mined_number
+        ));
+    }
+
+    // open file
+    let file = File::open(&config.file).with_context(|| format!("failed to open state file {}", config.file.display()))?;
+    let reader = StateImportReader::new(BufReader::new(file), config.batch_size);
+    tracing::info!(file = %config.file.display(), batch_size = %config.batch_size, paralellism = %config.paralellism, "importing state");
+
+    // read batches and dispatch them to writers
+    let progress = ImportProgress::default();
+    let (batch_tx, batch_rx) = mpsc::sync_channel::<StateImportBatch>(BACKLOG_SIZE);
+    let batch_rx = Mutex::new(batch_rx);
+    let import_result = thread::scope(|s| {
+        let writers = (0..config.paralellism.max(1))
+            .map(|_| s.spawn(|| write_batches(&storage, &batch_rx, &progress)))
+            .collect::<Vec<_>>();
+
+        let read_result = read_batches(reader, batch_tx, &progress);
+        let write_result = writers
+            .into_iter()
+            .map(|writer| writer.join().unwrap_or_else(|_| Err(anyhow!("state writer panicked"))))
+            .collect::<anyhow::Result<()>>();
+        write_result.and(read_result)
+    });
+    import_result?;
+
+    // mining continues from the block after the imported state
+    let block_number = BlockNumber::from(config.block_number);
+    storage.set_mined_block_number(block_number)?;
+    storage.set_pending_block_number_as_next()?;
+
+    progress.report();
+    tracing::info!(%block_number, "imported state");
+    Ok(())
+}
+
+/// Reads batches from the state file and sends them to the writers.
+fn read_batches(reader: StateImportReader<BufReader<File>>, batch_tx: mpsc::SyncSender<StateImportBatch>, progress: &ImportProgress) -> anyhow::Result<()> {
+    let mut last_report = Instant::now();
+    for batch in reader {
+        if GlobalState::is_shutdown_warn(TASK_NAME) {
+            return Err(anyhow!("state import interrupted by shutdown"));
+        }
+
+        let batch = batch?;
+        progress.read.fetch_add(batch.len(), Ordering::Relaxed);
+        if batch_tx.send(batch).is_err() {
+            // writers stop receiving only when one of them fails, and its error is reported when joined
+            return Ok(());
+        }
+
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            progress.report();
+            last_report = Instant::now();
+        }
+    }
+    Ok(())
+}
+
+/// Saves batches received from the reader until the file is fully read.
+fn write_batches(storage: &StratusStorage, batch_rx: &Mutex<mpsc::Receiver<StateImportBatch>>, progress: &ImportProgress) -> anyhow::Result<()> {
+    loop {
+        let Ok(batch) = batch_rx.lock_or_clear("state import receiver lock was poisoned").recv() else {
+            return Ok(());
+        };
+
+        let (accounts, slots) = (batch.accounts.len(), batch.slots.len());
+        storage.import_state_batch(batch).inspect_err(|_| {
+            GlobalState::shutdown_from(TASK_NAME, "failed to save state batch");
+        })?;
+        progress.accounts.fetch_add(accounts, Ordering::Relaxed);
+        progress.slots.fetch_add(slots, Ordering::Relaxed);
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Progress
+// -----------------------------------------------------------------------------
+
+struct ImportProgress {
+    start: Instant,
+    read: AtomicUsize,
+    accounts: AtomicUsize,
+    slots: AtomicUsize,
+}
+
+impl Default for ImportProgress {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+            read: AtomicUsize::new(0),
+            accounts: AtomicUsize::new(0),
+            slots: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl ImportProgress {
+    fn report(&self) {
+        let read = self.read.load(Ordering::Relaxed);
+        let accounts = self.accounts.load(Ordering::Relaxed);
+        let slots = self.slots.load(Ordering::Relaxed);
+        let entries_per_second = calculate_tps(self.start.elapsed(), accounts + slots);
+        tracing::info!(%read, %accounts, %slots, entries_per_second = format!("{:.2}", entries_per_second), "state import progress");
+    }
+}
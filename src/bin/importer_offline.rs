@@ -56,7 +56,7 @@ async fn run(config: ImporterOfflineConfig) -> anyhow::Result<()> {
     let rpc_storage = config.rpc_storage.init().await?;
     let storage = config.storage.init()?;
     let miner = config.miner.init_with_mode(MinerMode::External, Arc::clone(&storage)).await?;
-    let executor = config.executor.init(Arc::clone(&storage), Arc::clone(&miner));
+    let executor = config.executor.init(Arc::clone(&storage), Some(Arc::clone(&miner)));
 
     // init block range
     let block_start = match config.block_start {
@@ -103,7 +103,7 @@ async fn run(config: ImporterOfflineConfig) -> anyhow::Result<()> {
 // -----------------------------------------------------------------------------
 fn execute_block_importer(
     // services
-    executor: Arc<Executor>,
+    executor: Arc<dyn Executor>,
     miner: Arc<Miner>,
     // data
     mut backlog_rx: mpsc::Receiver<BacklogTask>,
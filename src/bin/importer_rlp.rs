@@ -0,0 +1,170 @@
+//! Importer-RLP binary.
+//!
+//! Exports a range of blocks from the permanent storage to RLP files, or re-executes and imports blocks from RLP files, similar to
+//! `geth export` and `geth import`. Allows moving chain history between environments without an external RPC. See [`BlockRlpRecord`] for
+//! the file format.
+//!
+//! Blocks already present in the importing storage are skipped, so an interrupted import can be resumed with the same files.
+
+use std::fs;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use anyhow::Context;
+use stratus::config::ImporterRlpConfig;
+use stratus::eth::executor::Executor;
+use stratus::eth::miner::Miner;
+use stratus::eth::miner::MinerMode;
+use stratus::eth::primitives::BlockFilter;
+use stratus::eth::primitives::BlockNumber;
+use stratus::eth::primitives::ExternalReceipts;
+use stratus::eth::primitives::ExternalTransaction;
+use stratus::eth::storage::BlockRlpRecord;
+use stratus::eth::storage::StratusStorage;
+use stratus::utils::DropTimer;
+use stratus::GlobalServices;
+use stratus::GlobalState;
+#[cfg(all(not(target_env = "msvc"), any(feature = "jemalloc", feature = "jeprof")))]
+use tikv_jemallocator::Jemalloc;
+use tokio::task::block_in_place;
+
+#[cfg(all(not(target_env = "msvc"), any(feature = "jemalloc", feature = "jeprof")))]
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
+
+/// Extension of block files.
+const FILE_EXTENSION: &str = "rlp";
+
+const TASK_NAME: &str = "importer-rlp";
+
+fn main() -> anyhow::Result<()> {
+    let global_services = GlobalServices::<ImporterRlpConfig>::init();
+    global_services.runtime.block_on(run(global_services.config))
+}
+
+async fn run(config: ImporterRlpConfig) -> anyhow::Result<()> {
+    let _timer = DropTimer::start("importer-rlp");
+
+    let storage = config.storage.init()?;
+    match (&config.export_dir, &config.import_dir) {
+        (Some(dir), _) => {
+            let block_start = BlockNumber::from(config.block_start);
+            let block_end = match config.block_end {
+                Some(end) => BlockNumber::from(end),
+                None => storage.read_mined_block_number()?,
+            };
+            block_in_place(|| export_blocks(&storage, dir, block_start, block_end, config.blocks_per_file))?;
+        }
+        (None, Some(dir)) => {
+            let miner = config.miner.init_with_mode(MinerMode::External, Arc::clone(&storage)).await?;
+            let executor = config.executor.init(Arc::clone(&storage), Some(Arc::clone(&miner)));
+            block_in_place(|| import_blocks(&storage, &executor, &miner, dir))?;
+        }
+        (None, None) => return Err(anyhow!("either an export or an import directory must be specified")),
+    }
+
+    // Explicitly block the `main` thread to drop the storage.
+    drop(storage);
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Export
+// -----------------------------------------------------------------------------
+
+/// Exports blocks to files with at most `blocks_per_file` blocks, named after the range of blocks they contain.
+fn export_blocks(storage: &StratusStorage, dir: &Path, block_start: BlockNumber, block_end: BlockNumber, blocks_per_file: u64) -> anyhow::Result<()> {
+    if block_start > block_end {
+        return Err(anyhow!("block start {} is greater than block end {}", block_start, block_end));
+    }
+    fs::create_dir_all(dir).with_context(|| format!("failed to create export directory {}", dir.display()))?;
+    tracing::info!(dir = %dir.display(), %block_start, %block_end, %blocks_per_file, "exporting blocks");
+
+    let blocks_per_file = blocks_per_file.max(1);
+    let mut file_start = block_start;
+    while file_start <= block_end {
+        if GlobalState::is_shutdown_warn(TASK_NAME) {
+            return Ok(());
+        }
+
+        let file_end = BlockNumber::from(file_start.as_u64().saturating_add(blocks_per_file - 1)).min(block_end);
+        let path = dir.join(format!("blocks-{:0>12}-{:0>12}.{}", file_start.as_u64(), file_end.as_u64(), FILE_EXTENSION));
+        let file = File::create(&path).with_context(|| format!("failed to create block file {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+
+        let mut number = file_start;
+        while number <= file_end {
+            let Some(block) = storage.read_block(&BlockFilter::Number(number))? else {
+                return Err(anyhow!("block {} not found in the permanent storage", number));
+            };
+            writer.write_all(&BlockRlpRecord::encode(&block))?;
+            number = number.next_block_number();
+        }
+        writer.flush()?;
+        tracing::info!(file = %path.display(), block_start = %file_start, block_end = %file_end, "exported blocks");
+
+        file_start = file_end.next_block_number();
+    }
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Import
+// -----------------------------------------------------------------------------
+
+/// Imports blocks from all block files in the directory, in the order of their names.
+fn import_blocks(storage: &StratusStorage, executor: &dyn Executor, miner: &Miner, dir: &Path) -> anyhow::Result<()> {
+    let mut paths = fs::read_dir(dir)
+        .with_context(|| format!("failed to read import directory {}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<PathBuf>, _>>()?;
+    paths.retain(|path| path.extension().is_some_and(|extension| extension == FILE_EXTENSION));
+    paths.sort();
+    tracing::info!(dir = %dir.display(), files = %paths.len(), "importing blocks");
+
+    for path in paths {
+        let bytes = fs::read(&path).with_context(|| format!("failed to read block file {}", path.display()))?;
+        let records = BlockRlpRecord::decode_all(&bytes).with_context(|| format!("invalid block file {}", path.display()))?;
+
+        let mut imported = 0;
+        for record in records {
+            if GlobalState::is_shutdown_warn(TASK_NAME) {
+                return Ok(());
+            }
+
+            // skip blocks imported before and ensure there are no gaps
+            let block_number = record.number();
+            let next_number = storage.read_block_number_to_resume_import()?;
+            if block_number < next_number {
+                tracing::info!(%block_number, "skipping block already imported");
+                continue;
+            }
+            if block_number != next_number {
+                return Err(anyhow!(
+                    "block {} found in {} but block {} was expected",
+                    block_number,
+                    path.display(),
+                    next_number
+                ));
+            }
+
+            // re-execute, mine and save block
+            let BlockRlpRecord { mut block, receipts } = record;
+            block.transactions.iter_mut().for_each(ExternalTransaction::fill_missing_transaction_type);
+            let mut receipts = ExternalReceipts::from(receipts);
+            executor.execute_external_block(block, &mut receipts)?;
+            miner.mine_external_and_commit()?;
+            imported += 1;
+        }
+        tracing::info!(file = %path.display(), %imported, "imported blocks");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+//! Replay-transaction binary.
+//!
+//! Executes a mined transaction again against the state it was originally executed on and reports the replayed execution, its calls
+//! and the fields that differ from the stored execution. Nothing is written to the storage. The same report is available through the
+//! `stratus_replayTransaction` RPC method.
+
+use std::fs;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use anyhow::Context;
+use stratus::config::ReplayTransactionConfig;
+use stratus::eth::executor::replay_transaction;
+use stratus::eth::miner::MinerMode;
+use stratus::ext::to_json_string_pretty;
+use stratus::utils::DropTimer;
+use stratus::GlobalServices;
+#[cfg(all(not(target_env = "msvc"), any(feature = "jemalloc", feature = "jeprof")))]
+use tikv_jemallocator::Jemalloc;
+use tokio::task::block_in_place;
+
+#[cfg(all(not(target_env = "msvc"), any(feature = "jemalloc", feature = "jeprof")))]
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
+
+fn main() -> anyhow::Result<()> {
+    let global_services = GlobalServices::<ReplayTransactionConfig>::init();
+    global_services.runtime.block_on(run(global_services.config))
+}
+
+async fn run(config: ReplayTransactionConfig) -> anyhow::Result<()> {
+    let _timer = DropTimer::start("replay-transaction");
+
+    // executor is used only to execute the transaction again, so the miner never mines
+    let storage = config.storage.init()?;
+    let miner = config.miner.init_with_mode(MinerMode::External, Arc::clone(&storage)).await?;
+    let executor = config.executor.init(Arc::clone(&storage), Some(miner));
+
+    let Some(replay) = block_in_place(|| replay_transaction(&storage, executor.as_ref(), config.tx_hash, None))? else {
+        return Err(anyhow!("transaction {} is not mined", config.tx_hash));
+    };
+
+    // write report
+    let report = to_json_string_pretty(&replay);
+    match config.output {
+        Some(ref path) => fs::write(path, report).with_context(|| format!("failed to write report to {}", path.display()))?,
+        None => println!("{}", report),
+    }
+
+    // Explicitly block the `main` thread to drop the storage.
+    drop(storage);
+
+    Ok(())
+}
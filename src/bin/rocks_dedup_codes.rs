@@ -0,0 +1,50 @@
+//! Deduplicate account codes binary.
+//!
+//! Loads the Rocks database and moves bytecodes stored inline in current and historical accounts to the
+//! column family keyed by code hash, so each distinct bytecode is stored only once.
+//!
+//! New writes already store bytecodes deduplicated, this is only needed for databases created before that.
+
+use std::time::Duration;
+
+use stratus::config::RocksDedupCodesConfig;
+use stratus::eth::storage::RocksPermanentStorage;
+use stratus::utils::DropTimer;
+use stratus::GlobalServices;
+#[cfg(all(not(target_env = "msvc"), any(feature = "jemalloc", feature = "jeprof")))]
+use tikv_jemallocator::Jemalloc;
+
+#[cfg(all(not(target_env = "msvc"), any(feature = "jemalloc", feature = "jeprof")))]
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
+fn main() -> anyhow::Result<()> {
+    let global_services = GlobalServices::<RocksDedupCodesConfig>::init();
+    run(global_services.config)
+}
+
+fn run(config: RocksDedupCodesConfig) -> anyhow::Result<()> {
+    let _timer = DropTimer::start("rocks-dedup-codes");
+
+    let rocks = RocksPermanentStorage::new(config.rocks_path_prefix, Duration::from_secs(30))?;
+
+    let report = match rocks.migrate_account_codes() {
+        Ok(report) => report,
+        Err(err) => {
+            tracing::error!(reason = ?err, "failed to deduplicate account codes");
+            return Err(err);
+        }
+    };
+
+    tracing::info!(
+        accounts = report.accounts,
+        history_accounts = report.history_accounts,
+        codes = report.codes,
+        unique_codes = report.unique_codes,
+        bytes_before = report.bytes_before,
+        bytes_after = report.bytes_after,
+        bytes_saved = report.bytes_saved(),
+        "deduplicated account codes, space is reclaimed after compaction"
+    );
+
+    Ok(())
+}
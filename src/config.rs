@@ -1,6 +1,7 @@
 //! Application configuration.
 
 use std::env;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
@@ -16,10 +17,14 @@ use tokio::runtime::Runtime;
 
 use crate::eth::executor::ExecutorConfig;
 use crate::eth::follower::importer::ImporterConfig;
+use crate::eth::miner::BlockWatchdogConfig;
 use crate::eth::miner::MinerConfig;
 use crate::eth::primitives::Address;
+use crate::eth::primitives::Hash;
+use crate::eth::publisher::PublisherConfig;
 use crate::eth::rpc::RpcServerConfig;
 use crate::eth::storage::ExternalRpcStorageConfig;
+use crate::eth::storage::RetentionConfig;
 use crate::eth::storage::StratusStorageConfig;
 use crate::ext::parse_duration;
 use crate::infra::build_info;
@@ -173,20 +178,27 @@ impl CommonConfig {
 
 /// Configuration for main Stratus service.
 #[derive(DebugAsJson, Clone, Parser, derive_more::Deref, serde::Serialize)]
-#[clap(group = ArgGroup::new("mode").required(true).args(&["leader", "follower"]))]
+#[clap(group = ArgGroup::new("mode").required(true).args(&["leader", "follower", "archive"]))]
 pub struct StratusConfig {
-    #[arg(long = "leader", env = "LEADER", conflicts_with("follower"))]
+    #[arg(long = "leader", env = "LEADER", conflicts_with_all(["follower", "archive"]))]
     pub leader: bool,
 
-    #[arg(long = "follower", env = "FOLLOWER", conflicts_with("leader"))]
+    #[arg(long = "follower", env = "FOLLOWER", conflicts_with_all(["leader", "archive"]))]
     pub follower: bool,
 
+    /// Serves only historical read RPCs from the permanent storage, without mining, importing or executing transactions.
+    #[arg(long = "archive", env = "ARCHIVE", conflicts_with_all(["leader", "follower"]))]
+    pub archive: bool,
+
     #[clap(flatten)]
     pub rpc_server: RpcServerConfig,
 
     #[clap(flatten)]
     pub storage: StratusStorageConfig,
 
+    #[clap(flatten)]
+    pub retention: RetentionConfig,
+
     #[clap(flatten)]
     pub executor: ExecutorConfig,
 
@@ -196,6 +208,16 @@ pub struct StratusConfig {
     #[clap(flatten)]
     pub importer: Option<ImporterConfig>,
 
+    #[clap(flatten)]
+    pub watchdog: BlockWatchdogConfig,
+
+    #[clap(flatten)]
+    pub publisher: PublisherConfig,
+
+    /// Max time spent in each step of draining importer, executor and miner when shutting down.
+    #[arg(long = "shutdown-drain-timeout", value_parser=parse_duration, env = "SHUTDOWN_DRAIN_TIMEOUT", default_value = "10s")]
+    pub shutdown_drain_timeout: Duration,
+
     #[deref]
     #[clap(flatten)]
     pub common: CommonConfig,
@@ -294,6 +316,54 @@ impl WithCommonConfig for ImporterOfflineConfig {
     }
 }
 
+// -----------------------------------------------------------------------------
+// Config: ImporterRlp
+// -----------------------------------------------------------------------------
+
+/// Configuration for `importer-rlp` binary.
+#[derive(Parser, DebugAsJson, derive_more::Deref, serde::Serialize)]
+#[clap(group = ArgGroup::new("importer_rlp_mode").required(true).args(&["export_dir", "import_dir"]))]
+pub struct ImporterRlpConfig {
+    /// Exports blocks from the permanent storage to RLP files created in this directory.
+    #[arg(long = "export", env = "IMPORTER_RLP_EXPORT")]
+    pub export_dir: Option<PathBuf>,
+
+    /// Re-executes and imports blocks from the RLP files found in this directory.
+    #[arg(long = "import", env = "IMPORTER_RLP_IMPORT")]
+    pub import_dir: Option<PathBuf>,
+
+    /// Initial block number to be exported.
+    #[arg(long = "block-start", env = "BLOCK_START", default_value = "0")]
+    pub block_start: u64,
+
+    /// Final block number to be exported. Defaults to the last mined block.
+    #[arg(long = "block-end", env = "BLOCK_END")]
+    pub block_end: Option<u64>,
+
+    /// Max number of blocks in each exported file.
+    #[arg(long = "blocks-per-file", env = "BLOCKS_PER_FILE", default_value = "10000")]
+    pub blocks_per_file: u64,
+
+    #[clap(flatten)]
+    pub executor: ExecutorConfig,
+
+    #[clap(flatten)]
+    pub miner: MinerConfig,
+
+    #[clap(flatten)]
+    pub storage: StratusStorageConfig,
+
+    #[deref]
+    #[clap(flatten)]
+    pub common: CommonConfig,
+}
+
+impl WithCommonConfig for ImporterRlpConfig {
+    fn common(&self) -> &CommonConfig {
+        &self.common
+    }
+}
+
 // -----------------------------------------------------------------------------
 // Config: RocksRevertToBlockConfig
 // -----------------------------------------------------------------------------
@@ -317,6 +387,147 @@ impl WithCommonConfig for RocksRevertToBlockConfig {
     }
 }
 
+// -----------------------------------------------------------------------------
+// Config: RocksDedupCodesConfig
+// -----------------------------------------------------------------------------
+
+#[derive(DebugAsJson, Clone, Parser, serde::Serialize)]
+pub struct RocksDedupCodesConfig {
+    #[arg(long = "rocks-path-prefix", env = "ROCKS_PATH_PREFIX")]
+    pub rocks_path_prefix: Option<String>,
+
+    #[clap(flatten)]
+    pub common: CommonConfig,
+}
+
+impl WithCommonConfig for RocksDedupCodesConfig {
+    fn common(&self) -> &CommonConfig {
+        &self.common
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Config: ImportState
+// -----------------------------------------------------------------------------
+
+/// Configuration for `import-state` binary.
+#[derive(DebugAsJson, Clone, Parser, derive_more::Deref, serde::Serialize)]
+pub struct ImportStateConfig {
+    /// CSV file with the accounts and slots to be imported.
+    #[arg(env = "IMPORT_STATE_FILE")]
+    pub file: PathBuf,
+
+    /// Block number the imported state belongs to. Mining continues from the next block.
+    #[arg(long = "block-number", env = "IMPORT_STATE_BLOCK_NUMBER", default_value = "0")]
+    pub block_number: u64,
+
+    /// Number of accounts and slots saved in each write.
+    #[arg(short = 'b', long = "batch-size", env = "IMPORT_STATE_BATCH_SIZE", default_value = "10000")]
+    pub batch_size: usize,
+
+    /// Number of parallel writes.
+    #[arg(short = 'p', long = "paralellism", env = "PARALELLISM", default_value = "4")]
+    pub paralellism: usize,
+
+    #[clap(flatten)]
+    pub storage: StratusStorageConfig,
+
+    #[deref]
+    #[clap(flatten)]
+    pub common: CommonConfig,
+}
+
+impl WithCommonConfig for ImportStateConfig {
+    fn common(&self) -> &CommonConfig {
+        &self.common
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Config: FeeRepricer
+// -----------------------------------------------------------------------------
+
+/// Configuration for `fee-repricer` binary.
+///
+/// The hypothetical fee configuration is the base fee configuration of the miner, and the hypothetical gas schedule is the EVM spec of
+/// the executor.
+#[derive(Parser, DebugAsJson, derive_more::Deref, serde::Serialize)]
+pub struct FeeRepricerConfig {
+    /// Initial block number to be repriced.
+    #[arg(long = "block-start", env = "BLOCK_START", default_value = "1")]
+    pub block_start: u64,
+
+    /// Final block number to be repriced. Defaults to the last mined block.
+    #[arg(long = "block-end", env = "BLOCK_END")]
+    pub block_end: Option<u64>,
+
+    /// Executes transactions again with the configured EVM spec instead of using the gas they used when mined.
+    #[arg(long = "reexecute", env = "FEE_REPRICER_REEXECUTE", default_value = "false")]
+    pub reexecute: bool,
+
+    /// Priority fee paid by all transactions. Defaults to the priority fee each transaction originally paid.
+    #[arg(long = "priority-fee", env = "FEE_REPRICER_PRIORITY_FEE")]
+    pub priority_fee: Option<u64>,
+
+    /// File where the JSON report is written. Defaults to the standard output.
+    #[arg(long = "output", env = "FEE_REPRICER_OUTPUT")]
+    pub output: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub executor: ExecutorConfig,
+
+    #[clap(flatten)]
+    pub miner: MinerConfig,
+
+    #[clap(flatten)]
+    pub storage: StratusStorageConfig,
+
+    #[deref]
+    #[clap(flatten)]
+    pub common: CommonConfig,
+}
+
+impl WithCommonConfig for FeeRepricerConfig {
+    fn common(&self) -> &CommonConfig {
+        &self.common
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Config: ReplayTransaction
+// -----------------------------------------------------------------------------
+
+/// Configuration for `replay-transaction` binary.
+#[derive(Parser, DebugAsJson, derive_more::Deref, serde::Serialize)]
+pub struct ReplayTransactionConfig {
+    /// Hash of the mined transaction to be replayed.
+    #[arg(long = "tx-hash", env = "REPLAY_TX_HASH")]
+    pub tx_hash: Hash,
+
+    /// File where the JSON report is written. Defaults to the standard output.
+    #[arg(long = "output", env = "REPLAY_OUTPUT")]
+    pub output: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub executor: ExecutorConfig,
+
+    #[clap(flatten)]
+    pub miner: MinerConfig,
+
+    #[clap(flatten)]
+    pub storage: StratusStorageConfig,
+
+    #[deref]
+    #[clap(flatten)]
+    pub common: CommonConfig,
+}
+
+impl WithCommonConfig for ReplayTransactionConfig {
+    fn common(&self) -> &CommonConfig {
+        &self.common
+    }
+}
+
 // -----------------------------------------------------------------------------
 // Config: Test
 // -----------------------------------------------------------------------------
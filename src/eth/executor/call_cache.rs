@@ -0,0 +1,210 @@
+//! Cache of read-only local call results.
+//!
+//! `eth_call` traffic is heavily repetitive, so results of calls executed against mined state are kept in a LRU cache keyed by the call
+//! input, the point-in-time and the latest mined block number. Entries are valid only for the mined state version they were computed
+//! against, and the whole cache is discarded when the version changes.
+
+use std::sync::Mutex;
+
+use ethers_core::utils::keccak256;
+use indexmap::IndexMap;
+
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::CallInput;
+use crate::eth::primitives::EvmExecution;
+use crate::eth::primitives::Hash;
+use crate::eth::storage::StoragePointInTime;
+use crate::ext::not;
+use crate::ext::to_json_string;
+use crate::ext::MutexExt;
+
+/// Identifies a cached call result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CallCacheKey {
+    input_hash: Hash,
+    point_in_time: StoragePointInTime,
+    block_number: BlockNumber,
+}
+
+impl CallCacheKey {
+    /// Creates a key for a call if its result can be cached.
+    ///
+    /// Calls against the pending block are not cached because its state changes with every transaction.
+    pub fn new(call_input: &CallInput, point_in_time: StoragePointInTime, block_number: BlockNumber) -> Option<Self> {
+        if point_in_time.is_pending() {
+            return None;
+        }
+        Some(Self {
+            input_hash: Hash::new(keccak256(to_json_string(call_input))),
+            point_in_time,
+            block_number,
+        })
+    }
+}
+
+/// LRU cache of call results computed against a mined state version.
+///
+/// Calls against the latest mined block reuse the block timestamp of the cached execution until a new block is mined.
+pub struct CallCache {
+    capacity: usize,
+    state: Mutex<CallCacheState>,
+}
+
+#[derive(Default)]
+struct CallCacheState {
+    /// Mined state version the entries were computed against.
+    version: u64,
+
+    /// Cached results ordered from the least to the most recently used.
+    entries: IndexMap<CallCacheKey, EvmExecution>,
+}
+
+impl CallCache {
+    /// Creates a cache holding at most `capacity` results. A capacity of zero disables the cache.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Retrieves a cached result computed against the specified mined state version.
+    pub fn get(&self, key: &CallCacheKey, version: u64) -> Option<EvmExecution> {
+        if self.capacity == 0 {
+            return None;
+        }
+
+        let mut state = self.state.lock_or_clear("call cache lock was poisoned");
+        if version > state.version {
+            state.invalidate(version);
+        }
+        if version != state.version {
+            return None;
+        }
+
+        let index = state.entries.get_index_of(key)?;
+        let last = state.entries.len() - 1;
+        state.entries.move_index(index, last);
+        state.entries.get_index(last).map(|(_, execution)| execution.clone())
+    }
+
+    /// Checks if a result computed against the specified mined state version is cached, without changing its recency.
+    pub fn contains(&self, key: &CallCacheKey, version: u64) -> bool {
+        if self.capacity == 0 {
+            return false;
+        }
+        let state = self.state.lock_or_clear("call cache lock was poisoned");
+        state.version == version && state.entries.contains_key(key)
+    }
+
+    /// Caches a result computed against the specified mined state version, evicting the least recently used result if full.
+    ///
+    /// Results computed against an outdated version are ignored.
+    pub fn insert(&self, key: CallCacheKey, version: u64, execution: EvmExecution) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut state = self.state.lock_or_clear("call cache lock was poisoned");
+        if version < state.version {
+            return;
+        }
+        if version > state.version {
+            state.invalidate(version);
+        }
+
+        if state.entries.len() >= self.capacity && not(state.entries.contains_key(&key)) {
+            state.entries.shift_remove_index(0);
+        }
+        state.entries.insert(key, execution);
+    }
+
+    /// Number of cached results.
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.state.lock_or_clear("call cache lock was poisoned").entries.len()
+    }
+}
+
+impl CallCacheState {
+    fn invalidate(&mut self, version: u64) {
+        self.version = version;
+        self.entries.clear();
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use fake::Fake;
+    use fake::Faker;
+
+    use super::*;
+
+    fn key(block_number: u64) -> CallCacheKey {
+        let call_input: CallInput = Faker.fake();
+        CallCacheKey::new(&call_input, StoragePointInTime::Mined, block_number.into()).unwrap()
+    }
+
+    #[test]
+    fn call_cache_hit_and_miss() {
+        let cache = CallCache::new(10);
+        let (key, execution): (_, EvmExecution) = (key(1), Faker.fake());
+
+        assert!(cache.get(&key, 0).is_none());
+        assert!(not(cache.contains(&key, 0)));
+        cache.insert(key, 0, execution.clone());
+        assert!(cache.contains(&key, 0));
+        assert!(not(cache.contains(&key, 1)));
+        assert_eq!(cache.get(&key, 0), Some(execution));
+        assert!(cache.get(&self::key(2), 0).is_none());
+    }
+
+    #[test]
+    fn call_cache_does_not_cache_pending_calls() {
+        let call_input: CallInput = Faker.fake();
+        assert!(CallCacheKey::new(&call_input, StoragePointInTime::Pending, BlockNumber::ONE).is_none());
+    }
+
+    #[test]
+    fn call_cache_evicts_least_recently_used() {
+        let cache = CallCache::new(2);
+        let (key1, key2, key3) = (key(1), key(2), key(3));
+        cache.insert(key1, 0, Faker.fake());
+        cache.insert(key2, 0, Faker.fake());
+
+        // key1 becomes the most recently used, so key2 is evicted
+        assert!(cache.get(&key1, 0).is_some());
+        cache.insert(key3, 0, Faker.fake());
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&key1, 0).is_some());
+        assert!(cache.get(&key2, 0).is_none());
+        assert!(cache.get(&key3, 0).is_some());
+    }
+
+    #[test]
+    fn call_cache_is_invalidated_when_version_changes() {
+        let cache = CallCache::new(10);
+        let key = key(1);
+        cache.insert(key, 0, Faker.fake());
+
+        // newer version discards entries
+        assert!(cache.get(&key, 1).is_none());
+        assert_eq!(cache.len(), 0);
+
+        // results computed against an outdated version are ignored
+        cache.insert(key, 0, Faker.fake());
+        assert!(cache.get(&key, 1).is_none());
+    }
+
+    #[test]
+    fn call_cache_disabled() {
+        let cache = CallCache::new(0);
+        let key = key(1);
+        cache.insert(key, 0, Faker.fake());
+        assert!(cache.get(&key, 0).is_none());
+    }
+}
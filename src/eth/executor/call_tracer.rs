@@ -0,0 +1,209 @@
+//! Flat call tracing of transactions.
+//!
+//! The tracer is a revm inspector that records every call, contract creation and self-destruct as a flat list of traces, in the order
+//! they started, as expected by the Parity `trace_*` methods. Inspecting slows down every instruction, so it is only registered in EVMs
+//! created for tracing.
+
+use revm::interpreter::CallInputs;
+use revm::interpreter::CallOutcome;
+use revm::interpreter::CallScheme;
+use revm::interpreter::CreateInputs;
+use revm::interpreter::CreateOutcome;
+use revm::interpreter::CreateScheme;
+use revm::interpreter::InterpreterResult;
+use revm::Database;
+use revm::EvmContext;
+use revm::Inspector;
+
+use crate::alias::RevmAddress;
+use crate::alias::RevmU256;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::CallTrace;
+use crate::eth::primitives::CallTraceKind;
+use crate::eth::primitives::Gas;
+use crate::ext::not;
+
+/// Records calls, contract creations and self-destructs of a transaction.
+#[derive(Debug, Default)]
+pub struct CallTracer {
+    traces: Vec<CallTrace>,
+
+    /// Indexes of the traces of calls not finished yet, from the outermost to the innermost.
+    open: Vec<usize>,
+}
+
+impl CallTracer {
+    /// Discards traces of the previous transaction.
+    pub fn reset(&mut self) {
+        self.traces.clear();
+        self.open.clear();
+    }
+
+    /// Takes the traces of the last executed transaction.
+    pub fn take_traces(&mut self) -> Vec<CallTrace> {
+        self.open.clear();
+        std::mem::take(&mut self.traces)
+    }
+
+    /// Records a trace as a child of the innermost open call.
+    fn push(&mut self, mut trace: CallTrace) -> usize {
+        if let Some(parent) = self.open.last().map(|index| &mut self.traces[*index]) {
+            trace.trace_address = parent.trace_address.clone();
+            trace.trace_address.push(parent.subtraces);
+            parent.subtraces += 1;
+        }
+        self.traces.push(trace);
+        self.traces.len() - 1
+    }
+
+    /// Records the start of a call or contract creation.
+    fn enter(&mut self, trace: CallTrace) {
+        let index = self.push(trace);
+        self.open.push(index);
+    }
+
+    /// Records the result of the innermost open call or contract creation.
+    fn exit(&mut self, result: &InterpreterResult, created: Option<RevmAddress>) {
+        let Some(trace) = self.open.pop().map(|index| &mut self.traces[index]) else {
+            return;
+        };
+        trace.gas_used = result.gas.spent().into();
+        trace.output = result.output.clone().into();
+        if let Some(created) = created {
+            trace.to = Some(created.into());
+        }
+        if result.result.is_revert() {
+            trace.error = Some("Reverted".to_owned());
+        } else if not(result.result.is_ok()) {
+            trace.error = Some(format!("{:?}", result.result));
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for CallTracer {
+    fn call(&mut self, _: &mut EvmContext<DB>, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        let kind = match inputs.scheme {
+            CallScheme::Call => CallTraceKind::Call,
+            CallScheme::CallCode => CallTraceKind::CallCode,
+            CallScheme::DelegateCall => CallTraceKind::DelegateCall,
+            CallScheme::StaticCall => CallTraceKind::StaticCall,
+        };
+        self.enter(CallTrace {
+            kind,
+            from: inputs.caller.into(),
+            to: Some(inputs.target_address.into()),
+            value: inputs.call_value().into(),
+            gas: inputs.gas_limit.into(),
+            gas_used: Gas::ZERO,
+            input: inputs.input.clone().into(),
+            output: Bytes::default(),
+            error: None,
+            trace_address: Vec::new(),
+            subtraces: 0,
+        });
+        None
+    }
+
+    fn call_end(&mut self, _: &mut EvmContext<DB>, _: &CallInputs, outcome: CallOutcome) -> CallOutcome {
+        self.exit(&outcome.result, None);
+        outcome
+    }
+
+    fn create(&mut self, _: &mut EvmContext<DB>, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        let kind = match inputs.scheme {
+            CreateScheme::Create => CallTraceKind::Create,
+            CreateScheme::Create2 { .. } => CallTraceKind::Create2,
+        };
+        self.enter(CallTrace {
+            kind,
+            from: inputs.caller.into(),
+            to: None,
+            value: inputs.value.into(),
+            gas: inputs.gas_limit.into(),
+            gas_used: Gas::ZERO,
+            input: inputs.init_code.clone().into(),
+            output: Bytes::default(),
+            error: None,
+            trace_address: Vec::new(),
+            subtraces: 0,
+        });
+        None
+    }
+
+    fn create_end(&mut self, _: &mut EvmContext<DB>, _: &CreateInputs, outcome: CreateOutcome) -> CreateOutcome {
+        let created = outcome.address.filter(|_| outcome.result.result.is_ok());
+        self.exit(&outcome.result, created);
+        outcome
+    }
+
+    fn selfdestruct(&mut self, contract: RevmAddress, target: RevmAddress, value: RevmU256) {
+        self.push(CallTrace {
+            kind: CallTraceKind::Suicide,
+            from: contract.into(),
+            to: Some(target.into()),
+            value: value.into(),
+            gas: Gas::ZERO,
+            gas_used: Gas::ZERO,
+            input: Bytes::default(),
+            output: Bytes::default(),
+            error: None,
+            trace_address: Vec::new(),
+            subtraces: 0,
+        });
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use revm::interpreter::Gas as RevmGas;
+    use revm::interpreter::InstructionResult;
+
+    use super::*;
+    use crate::eth::primitives::Address;
+
+    fn trace(kind: CallTraceKind) -> CallTrace {
+        CallTrace {
+            kind,
+            from: Address::ZERO,
+            to: None,
+            value: 0u64.into(),
+            gas: Gas::ZERO,
+            gas_used: Gas::ZERO,
+            input: Bytes::default(),
+            output: Bytes::default(),
+            error: None,
+            trace_address: Vec::new(),
+            subtraces: 0,
+        }
+    }
+
+    fn result(result: InstructionResult) -> InterpreterResult {
+        InterpreterResult::new(result, Default::default(), RevmGas::new(0))
+    }
+
+    #[test]
+    fn call_tracer_builds_trace_addresses() {
+        let mut tracer = CallTracer::default();
+
+        // call -> (call -> selfdestruct, create reverted)
+        tracer.enter(trace(CallTraceKind::Call));
+        tracer.enter(trace(CallTraceKind::StaticCall));
+        tracer.push(trace(CallTraceKind::Suicide));
+        tracer.exit(&result(InstructionResult::Stop), None);
+        tracer.enter(trace(CallTraceKind::Create));
+        tracer.exit(&result(InstructionResult::Revert), None);
+        tracer.exit(&result(InstructionResult::Return), None);
+
+        let traces = tracer.take_traces();
+        let addresses = traces.iter().map(|trace| trace.trace_address.clone()).collect::<Vec<_>>();
+        assert_eq!(addresses, vec![vec![], vec![0], vec![0, 0], vec![1]]);
+        let subtraces = traces.iter().map(|trace| trace.subtraces).collect::<Vec<_>>();
+        assert_eq!(subtraces, vec![2, 1, 0, 0]);
+        assert_eq!(traces[3].error.as_deref(), Some("Reverted"));
+        assert!(traces[0].error.is_none());
+    }
+}
@@ -0,0 +1,119 @@
+//! Runtime guards against nondeterminism during EVM execution.
+//!
+//! Followers replay the blocks produced by the leader and must reach exactly the same state, so an execution can depend only on its
+//! input and on the storage state. The guards are enabled in tests and with the `determinism` feature, and panic when:
+//!
+//! * The wall-clock is read while a transaction is executing.
+//! * A result computed by iterating a hash map changes when the map is iterated in a different order.
+//!
+//! When disabled, the checks compile to no-ops.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+/// Indicates if the guards are enabled in the current build.
+pub const DETERMINISM_GUARDS_ENABLED: bool = cfg!(any(test, feature = "determinism"));
+
+thread_local! {
+    /// Indicates if the current thread is executing a transaction.
+    static EXECUTING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Marks the current thread as executing a transaction until dropped.
+pub struct ExecutionScope {
+    previous: bool,
+}
+
+impl ExecutionScope {
+    pub fn enter() -> Self {
+        let previous = EXECUTING.replace(true);
+        Self { previous }
+    }
+}
+
+impl Drop for ExecutionScope {
+    fn drop(&mut self) {
+        EXECUTING.set(self.previous);
+    }
+}
+
+/// Panics if the wall-clock is read by `source` while the current thread is executing a transaction.
+#[track_caller]
+pub fn assert_no_wall_clock_read(source: &str) {
+    if DETERMINISM_GUARDS_ENABLED && EXECUTING.get() {
+        panic!("nondeterminism detected: {} read the wall-clock during EVM execution", source);
+    }
+}
+
+/// Computes a result by iterating a hash map.
+///
+/// When the guards are enabled, the result is computed again iterating the map in reverse order and the function panics if the results
+/// differ.
+#[track_caller]
+pub fn order_independent<K, V, R, F>(map: &HashMap<K, V>, f: F) -> R
+where
+    R: PartialEq + Debug,
+    F: Fn(&mut dyn Iterator<Item = (&K, &V)>) -> R,
+{
+    let result = f(&mut map.iter());
+    if DETERMINISM_GUARDS_ENABLED && map.len() > 1 {
+        let entries = map.iter().collect::<Vec<_>>();
+        let reversed_result = f(&mut entries.into_iter().rev());
+        if result != reversed_result {
+            panic!(
+                "nondeterminism detected: result depends on hash map iteration order ({:?} != {:?})",
+                result, reversed_result
+            );
+        }
+    }
+    result
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::primitives::UnixTime;
+
+    #[test]
+    fn wall_clock_read_outside_execution() {
+        UnixTime::now();
+    }
+
+    #[test]
+    #[should_panic(expected = "read the wall-clock during EVM execution")]
+    fn wall_clock_read_during_execution() {
+        let _scope = ExecutionScope::enter();
+        UnixTime::now();
+    }
+
+    #[test]
+    fn execution_scope_is_restored() {
+        {
+            let _outer = ExecutionScope::enter();
+            {
+                let _inner = ExecutionScope::enter();
+            }
+            assert!(EXECUTING.get());
+        }
+        assert!(!EXECUTING.get());
+    }
+
+    #[test]
+    fn order_independent_result() {
+        let map = (0u64..100).map(|i| (i, i * 2)).collect::<HashMap<_, _>>();
+        let sum = order_independent(&map, |entries| entries.map(|(_, v)| *v).sum::<u64>());
+        assert_eq!(sum, 9900);
+    }
+
+    #[test]
+    #[should_panic(expected = "result depends on hash map iteration order")]
+    fn order_dependent_result() {
+        let map = (0u64..100).map(|i| (i, i * 2)).collect::<HashMap<_, _>>();
+        order_independent(&map, |entries| entries.map(|(k, _)| *k).last());
+    }
+}
@@ -1,16 +1,21 @@
 use std::cmp::min;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::anyhow;
 use itertools::Itertools;
+use revm::inspector_handle_register;
+use revm::interpreter::instructions::control;
+use revm::interpreter::opcode;
+use revm::interpreter::InstructionResult;
+use revm::interpreter::Interpreter;
 use revm::primitives::AccountInfo;
 use revm::primitives::AnalysisKind;
 use revm::primitives::EVMError;
 use revm::primitives::ExecutionResult as RevmExecutionResult;
 use revm::primitives::InvalidTransaction;
 use revm::primitives::ResultAndState as RevmResultAndState;
-use revm::primitives::SpecId;
 use revm::primitives::State as RevmState;
 use revm::primitives::TransactTo;
 use revm::primitives::B256;
@@ -21,12 +26,15 @@ use revm::Handler;
 
 use crate::alias::RevmAddress;
 use crate::alias::RevmBytecode;
+use crate::eth::executor::CallTracer;
+use crate::eth::executor::EvmConfig;
 use crate::eth::executor::EvmExecutionResult;
 use crate::eth::executor::EvmInput;
-use crate::eth::executor::ExecutorConfig;
+use crate::eth::executor::ExecutionScope;
 use crate::eth::primitives::Account;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::Bytes;
+use crate::eth::primitives::CallTrace;
 use crate::eth::primitives::EvmExecution;
 use crate::eth::primitives::EvmExecutionMetrics;
 use crate::eth::primitives::ExecutionAccountChanges;
@@ -38,28 +46,49 @@ use crate::eth::primitives::Log;
 use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
 use crate::eth::primitives::StratusError;
+use crate::eth::storage::StoragePointInTime;
 use crate::eth::storage::StratusStorage;
 use crate::ext::not;
 use crate::ext::OptionExt;
 #[cfg(feature = "metrics")]
 use crate::infra::metrics;
+use crate::infra::profiling::profile_scope;
 
 /// Maximum gas limit allowed for a transaction. Prevents a transaction from consuming too many resources.
 const GAS_MAX_LIMIT: u64 = 1_000_000_000;
 
+/// Number of jumps executed between checks of the execution timeout, because reading the clock is much slower than a jump.
+const TIMEOUT_CHECK_JUMPS: u64 = 1024;
+
+type RevmEvmInstance = RevmEvm<'static, CallTracer, RevmSession>;
+
 /// Implementation of EVM using [`revm`](https://crates.io/crates/revm).
 pub struct Evm {
-    evm: RevmEvm<'static, (), RevmSession>,
+    evm: RevmEvmInstance,
 }
 
 impl Evm {
+    /// Maximum gas limit allowed for a single transaction.
+    pub const TX_GAS_LIMIT: u64 = GAS_MAX_LIMIT;
+
     /// Creates a new instance of the Evm.
+    pub fn new(storage: Arc<StratusStorage>, config: EvmConfig) -> Self {
+        Self::build(storage, config, false)
+    }
+
+    /// Creates a new instance of the Evm that records the calls performed by executed transactions.
+    ///
+    /// Recorded calls must be taken with [`Evm::take_call_traces`] after each execution.
+    pub fn new_call_tracer(storage: Arc<StratusStorage>, config: EvmConfig) -> Self {
+        Self::build(storage, config, true)
+    }
+
     #[allow(clippy::arc_with_non_send_sync)]
-    pub fn new(storage: Arc<StratusStorage>, config: ExecutorConfig) -> Self {
-        tracing::info!(?config, "creating revm");
+    fn build(storage: Arc<StratusStorage>, config: EvmConfig, trace_calls: bool) -> Self {
+        tracing::info!(?config, %trace_calls, "creating revm");
 
         // configure handler
-        let mut handler = Handler::mainnet_with_spec(SpecId::LONDON);
+        let mut handler = Handler::mainnet_with_spec(config.spec);
 
         // handler custom validators
         let validate_tx_against_state = handler.validation.tx_against_state;
@@ -72,31 +101,36 @@ impl Evm {
         });
 
         // handler custom instructions
-        let instructions = handler.take_instruction_table();
+        // loops are made of jumps, so checking the timeout in jumps is enough to cancel executions that never end
+        let mut instructions = handler.take_instruction_table();
+        instructions.insert(opcode::JUMP, jump_with_timeout);
+        instructions.insert(opcode::JUMPI, jumpi_with_timeout);
         handler.set_instruction_table(instructions);
 
+        // handler call tracing
+        if trace_calls {
+            handler.append_handler_register_plain(inspector_handle_register);
+        }
+
         // configure revm
-        let chain_id = config.executor_chain_id;
+        let cfg = config.clone();
         let mut evm = RevmEvm::builder()
-            .with_external_context(())
+            .with_external_context(CallTracer::default())
             .with_db(RevmSession::new(storage, config))
             .with_handler(handler)
             .build();
 
         // global general config
         let cfg_env = evm.cfg_mut();
-        cfg_env.chain_id = chain_id;
-        cfg_env.limit_contract_code_size = Some(usize::MAX);
+        cfg_env.chain_id = cfg.chain_id.into();
+        cfg_env.limit_contract_code_size = Some(cfg.contract_size_limit.unwrap_or(usize::MAX));
         cfg_env.perf_analyse_created_bytecodes = AnalysisKind::Raw;
+        cfg_env.disable_base_fee = cfg.disable_gas_price_check;
 
         // global block config
         let block_env = evm.block_mut();
         block_env.coinbase = Address::COINBASE.into();
 
-        // global tx config
-        let tx_env = evm.tx_mut();
-        tx_env.gas_priority_fee = None;
-
         Self { evm }
     }
 
@@ -104,14 +138,25 @@ impl Evm {
     pub fn execute(&mut self, input: EvmInput) -> Result<EvmExecutionResult, StratusError> {
         #[cfg(feature = "metrics")]
         let start = metrics::now();
+        let _profile_scope = profile_scope("evm::execute");
+
+        // execution must depend only on its input and on the storage state
+        let _execution_scope = ExecutionScope::enter();
 
         // configure session
         let evm = &mut self.evm;
         evm.db_mut().reset(input.clone());
+        evm.context.external.reset();
+
+        // read slots declared upfront in a single batch
+        if let Err(e) = evm.db_mut().prefetch_slots() {
+            tracing::warn!(reason = ?e, "evm storage error");
+            return Err(e);
+        }
 
         // configure block params
         let block_env = evm.block_mut();
-        block_env.basefee = U256::ZERO;
+        block_env.basefee = input.base_fee.into();
         block_env.timestamp = input.block_timestamp.into();
         block_env.number = input.block_number.into();
         let block_env_log = block_env.clone();
@@ -125,6 +170,7 @@ impl Evm {
         };
         tx_env.gas_limit = min(input.gas_limit.into(), GAS_MAX_LIMIT);
         tx_env.gas_price = input.gas_price.into();
+        tx_env.gas_priority_fee = input.max_priority_fee_per_gas.map_into();
         tx_env.chain_id = input.chain_id.map_into();
         tx_env.nonce = input.nonce.map_into();
         tx_env.data = input.data.into();
@@ -133,7 +179,10 @@ impl Evm {
 
         // execute transaction
         tracing::info!(block_env = ?block_env_log, tx_env = ?tx_env_log, "executing transaction in revm");
-        let evm_result = evm.transact();
+        let evm_result = {
+            let _profile_scope = profile_scope("evm::transact");
+            evm.transact()
+        };
 
         // extract results
         let session = evm.db_mut();
@@ -149,15 +198,41 @@ impl Evm {
             Ok(result) => Ok(parse_revm_execution(result, session_input, session_storage_changes)),
 
             // nonce errors
-            Err(EVMError::Transaction(InvalidTransaction::NonceTooHigh { tx, state })) => Err(StratusError::TransactionNonce {
+            Err(EVMError::Transaction(InvalidTransaction::NonceTooHigh { tx, state })) => Err(StratusError::TransactionNonceTooHigh {
                 transaction: tx.into(),
                 account: state.into(),
             }),
-            Err(EVMError::Transaction(InvalidTransaction::NonceTooLow { tx, state })) => Err(StratusError::TransactionNonce {
+            Err(EVMError::Transaction(InvalidTransaction::NonceTooLow { tx, state })) => Err(StratusError::TransactionNonceTooLow {
                 transaction: tx.into(),
                 account: state.into(),
             }),
 
+            // balance errors
+            Err(EVMError::Transaction(InvalidTransaction::LackOfFundForMaxFee { fee, balance })) => Err(StratusError::TransactionInsufficientFunds {
+                balance: (*balance).into(),
+                cost: (*fee).into(),
+            }),
+
+            // fee errors
+            Err(EVMError::Transaction(InvalidTransaction::GasPriceLessThanBasefee)) => Err(StratusError::TransactionGasPriceBelowBaseFee {
+                gas_price: session_input.gas_price,
+                base_fee: session_input.base_fee,
+            }),
+
+            // replay protection errors
+            Err(EVMError::Transaction(InvalidTransaction::InvalidChainId)) => Err(StratusError::TransactionInvalidChainId {
+                transaction: session_input.chain_id.unwrap_or_default(),
+                expected: evm.cfg().chain_id.into(),
+            }),
+
+            // timeout
+            Err(EVMError::Database(e @ StratusError::TransactionEvmTimeout { .. })) => {
+                tracing::warn!(reason = ?e, "evm execution cancelled by timeout");
+                #[cfg(feature = "metrics")]
+                metrics::inc_evm_execution_timeouts(&session_point_in_time);
+                Err(e)
+            }
+
             // storage error
             Err(EVMError::Database(e)) => {
                 tracing::warn!(reason = ?e, "evm storage error");
@@ -176,6 +251,7 @@ impl Evm {
         {
             metrics::inc_evm_execution(start.elapsed(), &session_point_in_time, execution.is_ok());
             metrics::inc_evm_execution_account_reads(session_metrics.account_reads);
+            metrics::inc_evm_execution_slot_prefetch_hits(session_metrics.slot_prefetch_hits);
         }
 
         execution.map(|execution| EvmExecutionResult {
@@ -183,6 +259,51 @@ impl Evm {
             metrics: session_metrics,
         })
     }
+
+    /// Takes the calls recorded during the last execution.
+    ///
+    /// Always empty if the Evm was not created with [`Evm::new_call_tracer`].
+    pub fn take_call_traces(&mut self) -> Vec<CallTrace> {
+        self.evm.context.external.take_traces()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Instructions
+// -----------------------------------------------------------------------------
+
+fn jump_with_timeout(interpreter: &mut Interpreter, host: &mut RevmEvmInstance) {
+    if check_timeout_on_jump(interpreter, host) {
+        control::jump(interpreter, host);
+    }
+}
+
+fn jumpi_with_timeout(interpreter: &mut Interpreter, host: &mut RevmEvmInstance) {
+    if check_timeout_on_jump(interpreter, host) {
+        control::jumpi(interpreter, host);
+    }
+}
+
+/// Checks the execution timeout every [`TIMEOUT_CHECK_JUMPS`] jumps, halting the execution with the timeout error if it was exceeded.
+///
+/// The error is reported like a storage error, so revm stops all call frames and returns it from `transact`.
+fn check_timeout_on_jump(interpreter: &mut Interpreter, host: &mut RevmEvmInstance) -> bool {
+    let session = host.db_mut();
+    if session.deadline.is_none() {
+        return true;
+    }
+    session.jumps += 1;
+    if session.jumps % TIMEOUT_CHECK_JUMPS != 0 {
+        return true;
+    }
+    match session.check_timeout() {
+        Ok(()) => true,
+        Err(e) => {
+            host.context.evm.inner.error = Err(EVMError::Database(e));
+            interpreter.instruction_result = InstructionResult::FatalExternalError;
+            false
+        }
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -191,8 +312,8 @@ impl Evm {
 
 /// Contextual data that is read or set durint the execution of a transaction in the EVM.
 struct RevmSession {
-    /// Executor configuration.
-    config: ExecutorConfig,
+    /// EVM configuration.
+    config: EvmConfig,
 
     /// Service to communicate with the storage.
     storage: Arc<StratusStorage>,
@@ -203,28 +324,84 @@ struct RevmSession {
     /// Changes made to the storage during the execution of the transaction.
     storage_changes: ExecutionChanges,
 
+    /// Slots read before the execution of the transaction that were not read by the EVM yet.
+    prefetched_slots: HashMap<(Address, SlotIndex), Slot>,
+
     /// Metrics collected during EVM execution.
     metrics: EvmExecutionMetrics,
+
+    /// Instant after which the execution is cancelled, if it has a timeout.
+    deadline: Option<Instant>,
+
+    /// Number of jumps executed, used to check the timeout periodically.
+    jumps: u64,
 }
 
 impl RevmSession {
     /// Creates the base session to be used with REVM.
-    pub fn new(storage: Arc<StratusStorage>, config: ExecutorConfig) -> Self {
+    pub fn new(storage: Arc<StratusStorage>, config: EvmConfig) -> Self {
         Self {
             config,
             storage,
             input: EvmInput::default(),
             storage_changes: HashMap::default(),
+            prefetched_slots: HashMap::default(),
             metrics: EvmExecutionMetrics::default(),
+            deadline: None,
+            jumps: 0,
         }
     }
 
     /// Resets the session to be used with a new transaction.
     pub fn reset(&mut self, input: EvmInput) {
+        self.deadline = input.execution_timeout.map(|timeout| Instant::now() + timeout);
+        self.jumps = 0;
         self.input = input;
         self.storage_changes = HashMap::default();
+        self.prefetched_slots = HashMap::default();
         self.metrics = EvmExecutionMetrics::default();
     }
+
+    /// Fails if the execution exceeded its timeout.
+    fn check_timeout(&self) -> Result<(), StratusError> {
+        match (self.deadline, self.input.execution_timeout) {
+            (Some(deadline), Some(timeout)) if Instant::now() >= deadline => {
+                tracing::warn!(?timeout, "cancelling execution because it exceeded the execution timeout");
+                Err(StratusError::TransactionEvmTimeout {
+                    timeout_millis: timeout.as_millis(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Reads the slots to be prefetched by the input in a single batch, so the EVM does not read them one at a time.
+    pub fn prefetch_slots(&mut self) -> Result<(), StratusError> {
+        let slots = std::mem::take(&mut self.input.prefetch_slots);
+        if slots.is_empty() {
+            return Ok(());
+        }
+        let _profile_scope = profile_scope("evm::prefetch_slots");
+        self.prefetched_slots = self.read_storage(|storage, point_in_time| storage.read_slots(&slots, point_in_time))?;
+        Ok(())
+    }
+
+    /// Reads from the storage tracking the time spent, failing if the execution exceeded its storage latency budget.
+    fn read_storage<T>(&mut self, read: impl FnOnce(&StratusStorage, &StoragePointInTime) -> Result<T, StratusError>) -> Result<T, StratusError> {
+        self.check_timeout()?;
+
+        let start = Instant::now();
+        let result = read(&self.storage, &self.input.point_in_time);
+        self.metrics.storage_read_time += start.elapsed();
+
+        if let Some(budget) = self.input.storage_latency_budget {
+            if self.metrics.storage_read_time > budget {
+                tracing::warn!(?budget, storage_read_time = ?self.metrics.storage_read_time, "aborting execution because storage latency budget was exceeded");
+                return Err(StratusError::StratusDegraded);
+            }
+        }
+        result
+    }
 }
 
 impl Database for RevmSession {
@@ -235,12 +412,15 @@ impl Database for RevmSession {
 
         // retrieve account
         let address: Address = revm_address.into();
-        let account = self.storage.read_account(&address, &self.input.point_in_time)?;
+        let account = match self.input.overlay.as_ref().and_then(|overlay| overlay.read_account(&address)) {
+            Some(account) => account,
+            None => self.read_storage(|storage, point_in_time| storage.read_account(&address, point_in_time))?,
+        };
 
         // warn if the loaded account is the `to` account and it does not have a bytecode
         if let Some(ref to_address) = self.input.to {
             if account.bytecode.is_none() && &address == to_address && self.input.is_contract_call() {
-                if self.config.executor_reject_not_contract {
+                if self.config.reject_not_contract {
                     return Err(StratusError::TransactionAccountNotContract { address: *to_address });
                 } else {
                     tracing::warn!(%address, "evm to_account is not a contract because does not have bytecode");
@@ -271,8 +451,17 @@ impl Database for RevmSession {
         let address: Address = revm_address.into();
         let index: SlotIndex = revm_index.into();
 
-        // load slot from storage
-        let slot = self.storage.read_slot(&address, &index, &self.input.point_in_time)?;
+        // load slot from overlay, prefetched slots or storage
+        let slot = match self.input.overlay.as_ref().and_then(|overlay| overlay.read_slot(&address, &index)) {
+            Some(slot) => slot,
+            None => match self.prefetched_slots.remove(&(address, index)) {
+                Some(slot) => {
+                    self.metrics.slot_prefetch_hits += 1;
+                    slot
+                }
+                None => self.read_storage(|storage, point_in_time| storage.read_slot(&address, &index, point_in_time))?,
+            },
+        };
 
         // track original value, except if ignored address
         if not(address.is_ignored()) {
@@ -303,16 +492,10 @@ impl Database for RevmSession {
 // -----------------------------------------------------------------------------
 
 fn parse_revm_execution(revm_result: RevmResultAndState, input: EvmInput, execution_changes: ExecutionChanges) -> EvmExecution {
-    let (result, tx_output, logs, gas) = parse_revm_result(revm_result.result);
+    let (result, tx_output, logs, gas, deployed_contract_address) = parse_revm_result(revm_result.result);
     let changes = parse_revm_state(revm_result.state, execution_changes);
 
     tracing::info!(?result, %gas, tx_output_len = %tx_output.len(), %tx_output, "evm executed");
-    let mut deployed_contract_address = None;
-    for changes in changes.values() {
-        if changes.bytecode.is_modified() {
-            deployed_contract_address = Some(changes.address);
-        }
-    }
 
     EvmExecution {
         block_timestamp: input.block_timestamp,
@@ -326,26 +509,28 @@ fn parse_revm_execution(revm_result: RevmResultAndState, input: EvmInput, execut
     }
 }
 
-fn parse_revm_result(result: RevmExecutionResult) -> (ExecutionResult, Bytes, Vec<Log>, Gas) {
+fn parse_revm_result(result: RevmExecutionResult) -> (ExecutionResult, Bytes, Vec<Log>, Gas, Option<Address>) {
     match result {
         RevmExecutionResult::Success { output, gas_used, logs, .. } => {
             let result = ExecutionResult::Success;
+            // only the contract created by the transaction itself, not the ones created by inner calls
+            let deployed_contract_address = output.address().map(|address| Address::from(*address));
             let output = Bytes::from(output);
             let logs = logs.into_iter().map_into().collect();
             let gas = Gas::from(gas_used);
-            (result, output, logs, gas)
+            (result, output, logs, gas, deployed_contract_address)
         }
         RevmExecutionResult::Revert { output, gas_used } => {
             let result = ExecutionResult::Reverted;
             let output = Bytes::from(output);
             let gas = Gas::from(gas_used);
-            (result, output, Vec::new(), gas)
+            (result, output, Vec::new(), gas, None)
         }
         RevmExecutionResult::Halt { reason, gas_used } => {
             let result = ExecutionResult::new_halted(format!("{:?}", reason));
             let output = Bytes::default();
             let gas = Gas::from(gas_used);
-            (result, output, Vec::new(), gas)
+            (result, output, Vec::new(), gas, None)
         }
     }
 }
@@ -393,3 +578,122 @@ fn parse_revm_state(revm_state: RevmState, mut execution_changes: ExecutionChang
     }
     execution_changes
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use revm::primitives::SpecId;
+
+    use super::*;
+    use crate::eth::primitives::Account;
+    use crate::eth::primitives::Gas;
+    use crate::eth::primitives::TransactionInput;
+    use crate::eth::primitives::UnixTime;
+    use crate::eth::primitives::Wei;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::InMemoryTemporaryStorage;
+
+    const SENDER_BALANCE: u64 = 1_000_000_000_000;
+
+    // BASEFEE PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    const RETURN_BASE_FEE: [u8; 9] = [0x48, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+
+    fn funded_evm(disable_gas_price_check: bool) -> Evm {
+        let storage = StratusStorage::new(Box::<InMemoryTemporaryStorage>::default(), Box::<InMemoryPermanentStorage>::default()).unwrap();
+        storage
+            .save_accounts(vec![Account::new_with_balance(Address::new([1; 20]), Wei::from(SENDER_BALANCE))])
+            .unwrap();
+        let config = EvmConfig {
+            chain_id: 2008u64.into(),
+            spec: SpecId::LONDON,
+            disable_gas_price_check,
+            contract_size_limit: None,
+            reject_not_contract: false,
+        };
+        Evm::new(Arc::new(storage), config)
+    }
+
+    fn block_deploy_input(init_code: &[u8], gas_price: u64, base_fee: u64) -> EvmInput {
+        let tx = TransactionInput {
+            signer: Address::new([1; 20]),
+            from: Address::new([1; 20]),
+            input: init_code.to_vec().into(),
+            gas_limit: Gas::from(100_000u64),
+            gas_price: Wei::from(gas_price),
+            ..TransactionInput::default()
+        };
+        EvmInput::builder_from_transaction(&tx)
+            .block_gas(&tx, Wei::from(base_fee))
+            .block_env(1u64.into(), UnixTime::from(1702568764u64), StoragePointInTime::Pending)
+            .build()
+    }
+
+    fn deploy_input(init_code: &[u8], execution_timeout: Option<Duration>) -> EvmInput {
+        let mut input = EvmInput::builder(Address::new([1; 20]), None, Wei::ZERO, init_code.to_vec().into())
+            .without_nonce()
+            .unmetered_gas()
+            .block_env(1u64.into(), UnixTime::from(1702568764u64), StoragePointInTime::Pending)
+            .build();
+        input.execution_timeout = execution_timeout;
+        input
+    }
+
+    #[test]
+    fn evm_cancels_execution_after_timeout() {
+        let storage = StratusStorage::new(Box::<InMemoryTemporaryStorage>::default(), Box::<InMemoryPermanentStorage>::default()).unwrap();
+        let config = EvmConfig {
+            chain_id: 2008u64.into(),
+            spec: SpecId::LONDON,
+            disable_gas_price_check: true,
+            contract_size_limit: None,
+            reject_not_contract: false,
+        };
+        let mut evm = Evm::new(Arc::new(storage), config);
+
+        // JUMPDEST PUSH1 0 JUMP
+        let infinite_loop = [0x5b, 0x60, 0x00, 0x56];
+        let start = Instant::now();
+        let result = evm.execute(deploy_input(&infinite_loop, Some(Duration::from_millis(50))));
+        assert!(matches!(result, Err(StratusError::TransactionEvmTimeout { timeout_millis: 50 })));
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // the same evm keeps executing after a cancelled execution
+        let stop = [0x00];
+        let execution = evm.execute(deploy_input(&stop, Some(Duration::from_millis(50)))).unwrap().execution;
+        assert!(execution.is_success());
+    }
+
+    #[test]
+    fn evm_charges_gas_with_base_fee_of_block() {
+        let mut evm = funded_evm(false);
+
+        let execution = evm.execute(block_deploy_input(&RETURN_BASE_FEE, 10, 7)).unwrap().execution;
+        assert!(execution.is_success());
+        let mut base_fee_word = [0u8; 32];
+        base_fee_word[31] = 7;
+        assert_eq!(execution.output.0, base_fee_word.to_vec());
+
+        // without priority fee, the whole gas price is paid
+        let sender = execution.changes.get(&Address::new([1; 20])).unwrap();
+        let charged = SENDER_BALANCE - sender.balance.take_ref().unwrap().0.as_u64();
+        assert_eq!(charged, execution.gas.as_u64() * 10);
+    }
+
+    #[test]
+    fn evm_checks_gas_price_against_base_fee_unless_disabled() {
+        // rejected when the gas price does not cover the base fee
+        let mut evm = funded_evm(false);
+        let result = evm.execute(block_deploy_input(&RETURN_BASE_FEE, 1, 7));
+        assert!(matches!(result, Err(StratusError::TransactionGasPriceBelowBaseFee { .. })));
+
+        // accepted when the check is disabled
+        let mut evm = funded_evm(true);
+        let execution = evm.execute(block_deploy_input(&RETURN_BASE_FEE, 1, 7)).unwrap().execution;
+        assert!(execution.is_success());
+    }
+}
@@ -0,0 +1,62 @@
+use anyhow::anyhow;
+use revm::primitives::SpecId;
+
+use crate::eth::primitives::ChainId;
+
+/// Configuration applied to all executions of an [`Evm`](crate::eth::executor::Evm) instance.
+#[derive(Debug, Clone)]
+pub struct EvmConfig {
+    /// Chain ID set in the EVM environment and expected in transactions with replay protection.
+    pub chain_id: ChainId,
+
+    /// Hardfork rules applied to all executions.
+    pub spec: SpecId,
+
+    /// Skips the check that the transaction gas price covers the block base fee.
+    pub disable_gas_price_check: bool,
+
+    /// Maximum size of deployed contracts in bytes. Unlimited if not set.
+    pub contract_size_limit: Option<usize>,
+
+    /// Rejects contract transactions and calls to accounts that are not contracts.
+    pub reject_not_contract: bool,
+}
+
+/// Hardforks that can be selected, in activation order.
+pub const EVM_SPECS: [(&str, SpecId); 12] = [
+    ("frontier", SpecId::FRONTIER),
+    ("homestead", SpecId::HOMESTEAD),
+    ("tangerine", SpecId::TANGERINE),
+    ("spurious_dragon", SpecId::SPURIOUS_DRAGON),
+    ("byzantium", SpecId::BYZANTIUM),
+    ("constantinople", SpecId::CONSTANTINOPLE),
+    ("petersburg", SpecId::PETERSBURG),
+    ("istanbul", SpecId::ISTANBUL),
+    ("berlin", SpecId::BERLIN),
+    ("london", SpecId::LONDON),
+    ("shanghai", SpecId::SHANGHAI),
+    ("cancun", SpecId::CANCUN),
+];
+
+/// Parses a hardfork name into the EVM spec.
+pub fn parse_evm_spec(s: &str) -> anyhow::Result<SpecId> {
+    let name = s.trim().to_lowercase();
+    match EVM_SPECS.iter().find(|(spec_name, _)| *spec_name == name) {
+        Some((_, spec)) => Ok(*spec),
+        None => Err(anyhow!("unknown evm spec: {}", s)),
+    }
+}
+
+/// Formats the EVM spec with the same name accepted by [`parse_evm_spec`].
+pub fn evm_spec_name(spec: SpecId) -> &'static str {
+    EVM_SPECS
+        .iter()
+        .find(|(_, candidate)| *candidate == spec)
+        .map(|(name, _)| *name)
+        .unwrap_or("unknown")
+}
+
+/// Serializes the EVM spec by name in configuration dumps.
+pub fn serialize_evm_spec<S: serde::Serializer>(spec: &SpecId, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(evm_spec_name(*spec))
+}
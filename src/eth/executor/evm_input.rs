@@ -1,5 +1,11 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+
 use display_json::DebugAsJson;
 
+use crate::eth::executor::EvmOverlay;
+use crate::eth::primitives::AccessList;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockNumber;
@@ -10,13 +16,13 @@ use crate::eth::primitives::ExternalReceipt;
 use crate::eth::primitives::ExternalTransaction;
 use crate::eth::primitives::Gas;
 use crate::eth::primitives::Nonce;
+use crate::eth::primitives::SlotIndex;
 use crate::eth::primitives::TransactionInput;
 use crate::eth::primitives::UnixTime;
 use crate::eth::primitives::Wei;
 use crate::eth::storage::StoragePointInTime;
 use crate::ext::not;
 use crate::ext::OptionExt;
-use crate::if_else;
 use crate::log_and_err;
 
 /// EVM input data. Usually derived from a transaction or call.
@@ -64,6 +70,16 @@ pub struct EvmInput {
     /// Gas price paid by each unit of gas consumed by the transaction.
     pub gas_price: Wei,
 
+    /// Part of the gas price paid above the base fee by EIP-1559 transactions.
+    ///
+    /// If not specified, the whole gas price is paid.
+    pub max_priority_fee_per_gas: Option<Wei>,
+
+    /// Base fee of the block where the transaction will be or was included.
+    ///
+    /// Zero in unmetered executions, because they cannot pay it.
+    pub base_fee: Wei,
+
     /// Number of the block where the transaction will be or was included.
     pub block_number: BlockNumber,
 
@@ -77,77 +93,81 @@ pub struct EvmInput {
     ///
     /// If not specified, it will not be validated.
     pub chain_id: Option<ChainId>,
+
+    /// State modified by previous calls of the same bundle that must be read before the storage.
+    ///
+    /// Present only when executing a sequence of calls with `eth_callMany`.
+    #[serde(skip)]
+    pub overlay: Option<Arc<EvmOverlay>>,
+
+    /// Slots read from the storage in a single batch before the execution, instead of one at a time when the EVM needs them.
+    ///
+    /// Usually the slots declared in the access list of the transaction.
+    #[serde(skip)]
+    pub prefetch_slots: Vec<(Address, SlotIndex)>,
+
+    /// Max total time the execution can spend reading from the storage before being aborted.
+    ///
+    /// Present only when executing local transactions with a storage latency budget.
+    #[serde(skip)]
+    pub storage_latency_budget: Option<Duration>,
+
+    /// Max time the execution can run in the EVM before being cancelled.
+    ///
+    /// Present only when executing local transactions and calls with an execution timeout.
+    #[serde(skip)]
+    pub execution_timeout: Option<Duration>,
 }
 
 impl EvmInput {
-    /// Creates from a transaction that was sent directly to Stratus with `eth_sendRawTransaction`.
-    pub fn from_eth_transaction(input: TransactionInput, pending_block_number: BlockNumber) -> Self {
-        Self {
-            from: input.signer,
-            to: input.to,
-            value: input.value,
-            data: input.input,
-            gas_limit: Gas::MAX,
-            gas_price: Wei::ZERO,
-            nonce: Some(input.nonce),
-            block_number: pending_block_number,
-            block_timestamp: UnixTime::now(), // TODO: this should come from the pending block
-            point_in_time: StoragePointInTime::Pending,
-            chain_id: input.chain_id,
+    /// Starts building an input for the operation of `from` with `to`.
+    ///
+    /// Gas, nonce and block environment must be set before the input can be built.
+    pub fn builder(from: Address, to: Option<Address>, value: Wei, data: Bytes) -> EvmInputBuilder<Missing, Missing, Missing> {
+        EvmInputBuilder {
+            input: Self {
+                from,
+                to,
+                value,
+                data,
+                ..Self::default()
+            },
+            state: PhantomData,
         }
     }
 
-    /// Creates from a call that was sent directly to Stratus with `eth_call` or `eth_estimateGas`.
-    pub fn from_eth_call(
-        input: CallInput,
-        point_in_time: StoragePointInTime,
-        pending_block_number: BlockNumber,
-        mined_block: Option<Block>,
-    ) -> anyhow::Result<Self> {
-        Ok(Self {
-            from: input.from.unwrap_or(Address::ZERO),
-            to: input.to.map_into(),
-            value: input.value,
-            data: input.data,
-            gas_limit: Gas::MAX,
-            gas_price: Wei::ZERO,
-            nonce: None,
-            block_number: match point_in_time {
-                StoragePointInTime::Mined | StoragePointInTime::Pending => pending_block_number,
-                StoragePointInTime::MinedPast(number) => number,
-            },
-            block_timestamp: match point_in_time {
-                StoragePointInTime::Mined | StoragePointInTime::Pending => UnixTime::now(),
-                StoragePointInTime::MinedPast(_) => match mined_block {
-                    Some(block) => block.header.timestamp,
-                    None => return log_and_err!("failed to create EvmInput because cannot determine mined block timestamp"),
-                },
-            },
-            point_in_time,
-            chain_id: None,
-        })
+    /// Starts building from a transaction sent directly to Stratus with `eth_sendRawTransaction` or already mined in a block.
+    pub fn builder_from_transaction(input: &TransactionInput) -> EvmInputBuilder<Missing, Present, Missing> {
+        Self::builder(input.signer, input.to, input.value, input.input.clone())
+            .nonce(input.nonce)
+            .chain_id(input.chain_id)
+            .prefetch_slots(input.access_list.iter().flat_map(AccessList::slots))
+    }
+
+    /// Starts building from a call sent directly to Stratus with `eth_call` or `eth_estimateGas`.
+    pub fn builder_from_call(input: CallInput) -> EvmInputBuilder<Missing, Present, Missing> {
+        Self::builder(input.from.unwrap_or(Address::ZERO), input.to.map_into(), input.value, input.data).without_nonce()
     }
 
-    /// Creates a transaction that was executed in an external blockchain and imported to Stratus.
+    /// Starts building from a transaction that was executed in an external blockchain and imported to Stratus.
     ///
     /// Successful external transactions executes with max gas and zero gas price to ensure we will have the same execution result.
-    pub fn from_external(tx: &ExternalTransaction, receipt: &ExternalReceipt, block_number: BlockNumber, block_timestamp: UnixTime) -> anyhow::Result<Self> {
-        Ok(Self {
-            from: tx.0.from.into(),
-            to: tx.0.to.map_into(),
-            value: tx.0.value.into(),
-            data: tx.0.input.clone().into(),
-            nonce: Some(tx.0.nonce.try_into()?),
-            gas_limit: if_else!(receipt.is_success(), Gas::MAX, tx.0.gas.try_into()?),
-            gas_price: if_else!(receipt.is_success(), Wei::ZERO, tx.0.gas_price.map_into().unwrap_or(Wei::ZERO)),
-            point_in_time: StoragePointInTime::Pending,
-            block_number,
-            block_timestamp,
-            chain_id: match tx.0.chain_id {
-                Some(chain_id) => Some(chain_id.try_into()?),
-                None => None,
-            },
-        })
+    pub fn builder_from_external(tx: &ExternalTransaction, receipt: &ExternalReceipt) -> anyhow::Result<EvmInputBuilder<Present, Present, Missing>> {
+        let chain_id = match tx.0.chain_id {
+            Some(chain_id) => Some(chain_id.try_into()?),
+            None => None,
+        };
+        let access_list = tx.0.access_list.clone().map(AccessList::from);
+        let builder = Self::builder(tx.0.from.into(), tx.0.to.map_into(), tx.0.value.into(), tx.0.input.clone().into())
+            .nonce(tx.0.nonce.try_into()?)
+            .chain_id(chain_id)
+            .prefetch_slots(access_list.iter().flat_map(AccessList::slots));
+
+        if receipt.is_success() {
+            Ok(builder.unmetered_gas())
+        } else {
+            Ok(builder.gas(tx.0.gas.try_into()?, tx.0.gas_price.map_into().unwrap_or(Wei::ZERO)))
+        }
     }
 
     /// Checks if the input is a contract call.
@@ -157,3 +177,116 @@ impl EvmInput {
         self.to.is_some() && not(self.data.is_empty())
     }
 }
+
+// -----------------------------------------------------------------------------
+// Builder
+// -----------------------------------------------------------------------------
+
+/// Typestate of a group of [`EvmInput`] fields that was not set yet.
+pub struct Missing;
+
+/// Typestate of a group of [`EvmInput`] fields that was already set.
+pub struct Present;
+
+/// Builds an [`EvmInput`] checking at compile time that gas (`G`), nonce (`N`) and block environment (`E`) were set.
+///
+/// [`EvmInputBuilder::build`] is available only when all groups are [`Present`], so an execution path cannot forget one of them.
+#[must_use]
+pub struct EvmInputBuilder<G, N, E> {
+    input: EvmInput,
+    state: PhantomData<(G, N, E)>,
+}
+
+impl<G, N, E> EvmInputBuilder<G, N, E> {
+    /// Sets the chain ID to be validated. If not set, it will not be validated.
+    pub fn chain_id(mut self, chain_id: Option<ChainId>) -> Self {
+        self.input.chain_id = chain_id;
+        self
+    }
+
+    /// Sets the slots to be read in a single batch before the execution.
+    pub fn prefetch_slots(mut self, slots: impl IntoIterator<Item = (Address, SlotIndex)>) -> Self {
+        self.input.prefetch_slots = slots.into_iter().collect();
+        self
+    }
+
+    fn into_state<G2, N2, E2>(self) -> EvmInputBuilder<G2, N2, E2> {
+        EvmInputBuilder {
+            input: self.input,
+            state: PhantomData,
+        }
+    }
+}
+
+impl<N, E> EvmInputBuilder<Missing, N, E> {
+    pub fn gas(mut self, gas_limit: Gas, gas_price: Wei) -> EvmInputBuilder<Present, N, E> {
+        self.input.gas_limit = gas_limit;
+        self.input.gas_price = gas_price;
+        self.into_state()
+    }
+
+    /// Sets max gas and zero gas price, which is how Stratus executes its own transactions and calls.
+    pub fn unmetered_gas(self) -> EvmInputBuilder<Present, N, E> {
+        self.gas(Gas::MAX, Wei::ZERO)
+    }
+
+    /// Sets the gas of a transaction included in a block with the specified base fee.
+    ///
+    /// Transactions pay for their gas only in blocks with a base fee, otherwise they execute unmetered.
+    pub fn block_gas(self, input: &TransactionInput, base_fee: Wei) -> EvmInputBuilder<Present, N, E> {
+        if base_fee.is_zero() {
+            return self.unmetered_gas();
+        }
+        let mut builder = self.gas(input.gas_limit, input.max_fee_per_gas.unwrap_or(input.gas_price));
+        builder.input.max_priority_fee_per_gas = input.max_priority_fee_per_gas;
+        builder.input.base_fee = base_fee;
+        builder
+    }
+}
+
+impl<G, E> EvmInputBuilder<G, Missing, E> {
+    pub fn nonce(mut self, nonce: Nonce) -> EvmInputBuilder<G, Present, E> {
+        self.input.nonce = Some(nonce);
+        self.into_state()
+    }
+
+    /// Sets that the nonce is not validated, which is the case of calls.
+    pub fn without_nonce(mut self) -> EvmInputBuilder<G, Present, E> {
+        self.input.nonce = None;
+        self.into_state()
+    }
+}
+
+impl<G, N> EvmInputBuilder<G, N, Missing> {
+    pub fn block_env(mut self, block_number: BlockNumber, block_timestamp: UnixTime, point_in_time: StoragePointInTime) -> EvmInputBuilder<G, N, Present> {
+        self.input.block_number = block_number;
+        self.input.block_timestamp = block_timestamp;
+        self.input.point_in_time = point_in_time;
+        self.into_state()
+    }
+
+    /// Sets the block environment of a call executed at `point_in_time`.
+    ///
+    /// Calls to the present execute in the pending block, and calls to the past need the mined block to determine its timestamp.
+    pub fn call_block_env(
+        self,
+        point_in_time: StoragePointInTime,
+        pending_block_number: BlockNumber,
+        mined_block: Option<Block>,
+    ) -> anyhow::Result<EvmInputBuilder<G, N, Present>> {
+        let (block_number, block_timestamp) = match point_in_time {
+            StoragePointInTime::Mined | StoragePointInTime::Pending => (pending_block_number, UnixTime::now()),
+            StoragePointInTime::MinedPast(number) => match mined_block {
+                Some(block) => (number, block.header.timestamp),
+                None => return log_and_err!("failed to create EvmInput because cannot determine mined block timestamp"),
+            },
+        };
+        Ok(self.block_env(block_number, block_timestamp, point_in_time))
+    }
+}
+
+impl EvmInputBuilder<Present, Present, Present> {
+    pub fn build(self) -> EvmInput {
+        self.input
+    }
+}
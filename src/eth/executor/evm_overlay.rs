@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+use crate::eth::primitives::Account;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::EvmExecution;
+use crate::eth::primitives::Slot;
+use crate::eth::primitives::SlotIndex;
+
+/// Ephemeral state layered on top of the storage when executing a sequence of calls.
+///
+/// Accounts and slots modified by previous calls are read from the overlay before falling back to the storage, so each call sees the
+/// effects of the previous ones without anything being persisted.
+#[derive(Debug, Clone, Default)]
+pub struct EvmOverlay {
+    accounts: HashMap<Address, Account>,
+    slots: HashMap<(Address, SlotIndex), Slot>,
+}
+
+impl EvmOverlay {
+    /// Applies the changes of an execution to the overlay.
+    pub fn apply(&mut self, execution: &EvmExecution) {
+        for change in execution.changes.values() {
+            // account basic info (changes are populated with original values, so it is safe to use them as fallback)
+            let account = self.accounts.entry(change.address).or_insert_with(|| Account::new_empty(change.address));
+            if let Some(nonce) = change.nonce.take_ref() {
+                account.nonce = *nonce;
+            }
+            if let Some(balance) = change.balance.take_ref() {
+                account.balance = *balance;
+            }
+            if let Some(bytecode) = change.bytecode.take_ref() {
+                account.bytecode = bytecode.clone();
+                account.code_hash = change.code_hash;
+            }
+
+            // slots
+            for slot in change.slots.values() {
+                if let Some(slot) = slot.take_ref() {
+                    self.slots.insert((change.address, slot.index), *slot);
+                }
+            }
+        }
+    }
+
+    /// Reads an account modified by a previous execution.
+    pub fn read_account(&self, address: &Address) -> Option<Account> {
+        self.accounts.get(address).cloned()
+    }
+
+    /// Reads a slot modified by a previous execution.
+    pub fn read_slot(&self, address: &Address, index: &SlotIndex) -> Option<Slot> {
+        self.slots.get(&(*address, *index)).copied()
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::sync::Condvar;
+use std::sync::Mutex;
+
+use anyhow::anyhow;
+
+use crate::eth::primitives::Address;
+use crate::ext::MutexExt;
+
+/// Strategy used to order tasks waiting to be executed by EVMs.
+#[derive(Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum EvmQueueStrategy {
+    /// Tasks are executed in the order they are received.
+    #[serde(rename = "fifo")]
+    Fifo,
+
+    /// Tasks are interleaved across distinct senders.
+    #[serde(rename = "fair")]
+    Fair,
+}
+
+impl FromStr for EvmQueueStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fifo" => Ok(Self::Fifo),
+            "fair" => Ok(Self::Fair),
+            s => Err(anyhow!("unknown evm queue strategy: {}", s)),
+        }
+    }
+}
+
+/// Reason a task was not accepted by the queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvmQueueRejection {
+    /// Queue no longer accepts tasks.
+    Closed,
+
+    /// Queue reached its capacity.
+    Full,
+}
+
+/// Blocking multi-consumer queue of tasks waiting to be executed by EVMs.
+///
+/// When using the fair strategy, tasks are grouped by sender and senders are served in round-robin, each one dequeuing up to
+/// `quantum` tasks before yielding its turn. Tasks from the same sender are always dequeued in the order they were received.
+///
+/// When the queue is bounded, tasks pushed while it is full are rejected immediately instead of waiting for space, so callers can fail
+/// fast when EVMs are overloaded.
+pub struct EvmQueue<T> {
+    strategy: EvmQueueStrategy,
+    quantum: usize,
+
+    /// Max number of queued tasks. Unbounded if zero.
+    capacity: usize,
+
+    state: Mutex<EvmQueueState<T>>,
+    available: Condvar,
+}
+
+struct EvmQueueState<T> {
+    /// Tasks grouped by sender.
+    tasks: HashMap<Address, VecDeque<T>>,
+
+    /// Senders with queued tasks in the order they will be served.
+    turns: VecDeque<Address>,
+
+    /// Number of tasks the sender in the front of `turns` can still dequeue before yielding its turn.
+    credits: usize,
+
+    /// Total number of queued tasks.
+    len: usize,
+
+    /// Indicates that no new tasks are accepted.
+    closed: bool,
+
+    /// Number of consumers that must stop consuming tasks, used to shrink the pool of EVMs.
+    retiring: usize,
+}
+
+impl<T> EvmQueue<T> {
+    pub fn new(strategy: EvmQueueStrategy, quantum: usize, capacity: usize) -> Self {
+        Self {
+            strategy,
+            quantum: quantum.max(1),
+            capacity,
+            state: Mutex::new(EvmQueueState {
+                tasks: HashMap::new(),
+                turns: VecDeque::new(),
+                credits: 0,
+                len: 0,
+                closed: false,
+                retiring: 0,
+            }),
+            available: Condvar::new(),
+        }
+    }
+
+    /// Enqueues a task. Returns the task back if the queue is closed or full.
+    pub fn push(&self, sender: Address, task: T) -> Result<(), (EvmQueueRejection, T)> {
+        let sender = match self.strategy {
+            EvmQueueStrategy::Fifo => Address::ZERO,
+            EvmQueueStrategy::Fair => sender,
+        };
+
+        let mut state = self.state.lock_or_clear("evm queue lock was poisoned");
+        if state.closed {
+            return Err((EvmQueueRejection::Closed, task));
+        }
+        if self.capacity > 0 && state.len >= self.capacity {
+            return Err((EvmQueueRejection::Full, task));
+        }
+
+        // new senders wait for their turn after all others
+        let sender_tasks = state.tasks.entry(sender).or_default();
+        let is_new_sender = sender_tasks.is_empty();
+        sender_tasks.push_back(task);
+        if is_new_sender {
+            if state.turns.is_empty() {
+                state.credits = self.quantum;
+            }
+            state.turns.push_back(sender);
+        }
+        state.len += 1;
+        drop(state);
+
+        self.available.notify_one();
+        Ok(())
+    }
+
+    /// Dequeues the next task, blocking until one is available.
+    ///
+    /// Returns `None` if the queue is closed and empty, or if the consumer must retire.
+    pub fn pop(&self) -> Option<T> {
+        let mut state = self.state.lock_or_clear("evm queue lock was poisoned");
+        loop {
+            if state.retiring > 0 {
+                state.retiring -= 1;
+                return None;
+            }
+            if let Some(task) = state.pop_next(self.quantum) {
+                return Some(task);
+            }
+            if state.closed {
+                return None;
+            }
+            state = self.available.wait(state).unwrap_or_else(|poison_err| {
+                tracing::error!("Fatal: failed to wait evm queue lock, evm queue lock was poisoned");
+                self.state.clear_poison();
+                poison_err.into_inner()
+            });
+        }
+    }
+
+    /// Number of queued tasks.
+    pub fn len(&self) -> usize {
+        self.state.lock_or_clear("evm queue lock was poisoned").len
+    }
+
+    /// Checks if there are no queued tasks.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Max number of queued tasks. Unbounded if zero.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Checks if the queue no longer accepts tasks.
+    pub fn is_closed(&self) -> bool {
+        self.state.lock_or_clear("evm queue lock was poisoned").closed
+    }
+
+    /// Makes the specified number of consumers stop consuming tasks after finishing their current task.
+    pub fn retire(&self, consumers: usize) {
+        self.state.lock_or_clear("evm queue lock was poisoned").retiring += consumers;
+        self.available.notify_all();
+    }
+
+    /// Stops accepting new tasks and wakes up all consumers once the queue is drained.
+    pub fn close(&self) {
+        self.state.lock_or_clear("evm queue lock was poisoned").closed = true;
+        self.available.notify_all();
+    }
+}
+
+impl<T> EvmQueueState<T> {
+    fn pop_next(&mut self, quantum: usize) -> Option<T> {
+        let sender = *self.turns.front()?;
+        let sender_tasks = self.tasks.get_mut(&sender)?;
+        let task = sender_tasks.pop_front()?;
+        self.len -= 1;
+        self.credits = self.credits.saturating_sub(1);
+
+        // sender has no more tasks: remove it and give the turn to the next one
+        if sender_tasks.is_empty() {
+            self.tasks.remove(&sender);
+            self.turns.pop_front();
+            self.credits = quantum;
+        }
+        // sender used all its credits: move it to the end of the line
+        else if self.credits == 0 {
+            self.turns.rotate_left(1);
+            self.credits = quantum;
+        }
+
+        Some(task)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(queue: &EvmQueue<u32>) -> Vec<u32> {
+        queue.close();
+        std::iter::from_fn(|| queue.pop()).collect()
+    }
+
+    #[test]
+    fn fifo_keeps_arrival_order() {
+        let queue = EvmQueue::new(EvmQueueStrategy::Fifo, 1, 0);
+        let (a, b) = (Address::new([1; 20]), Address::new([2; 20]));
+        for (sender, task) in [(a, 1), (a, 2), (a, 3), (b, 4)] {
+            queue.push(sender, task).unwrap();
+        }
+        assert_eq!(drain(&queue), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn fair_interleaves_senders() {
+        let queue = EvmQueue::new(EvmQueueStrategy::Fair, 1, 0);
+        let (a, b, c) = (Address::new([1; 20]), Address::new([2; 20]), Address::new([3; 20]));
+        for (sender, task) in [(a, 1), (a, 2), (a, 3), (b, 4), (b, 5), (c, 6)] {
+            queue.push(sender, task).unwrap();
+        }
+        assert_eq!(drain(&queue), vec![1, 4, 6, 2, 5, 3]);
+    }
+
+    #[test]
+    fn fair_respects_quantum() {
+        let queue = EvmQueue::new(EvmQueueStrategy::Fair, 2, 0);
+        let (a, b) = (Address::new([1; 20]), Address::new([2; 20]));
+        for (sender, task) in [(a, 1), (a, 2), (a, 3), (b, 4), (b, 5), (b, 6)] {
+            queue.push(sender, task).unwrap();
+        }
+        assert_eq!(drain(&queue), vec![1, 2, 4, 5, 3, 6]);
+    }
+
+    #[test]
+    fn closed_queue_rejects_tasks() {
+        let queue = EvmQueue::new(EvmQueueStrategy::Fair, 1, 0);
+        queue.close();
+        assert_eq!(queue.push(Address::ZERO, 1), Err((EvmQueueRejection::Closed, 1)));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn bounded_queue_rejects_tasks_when_full() {
+        let queue = EvmQueue::new(EvmQueueStrategy::Fifo, 1, 2);
+        queue.push(Address::ZERO, 1).unwrap();
+        queue.push(Address::ZERO, 2).unwrap();
+        assert_eq!(queue.push(Address::ZERO, 3), Err((EvmQueueRejection::Full, 3)));
+        assert_eq!(queue.pop(), Some(1));
+        queue.push(Address::ZERO, 3).unwrap();
+        assert_eq!(drain(&queue), vec![2, 3]);
+    }
+
+    #[test]
+    fn retired_consumers_stop_before_queue_is_closed() {
+        let queue = EvmQueue::new(EvmQueueStrategy::Fifo, 1, 0);
+        queue.push(Address::ZERO, 1).unwrap();
+        queue.retire(1);
+        assert_eq!(queue.pop(), None);
+        assert_eq!(queue.pop(), Some(1));
+    }
+}
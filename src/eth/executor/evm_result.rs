@@ -4,8 +4,8 @@ use crate::eth::primitives::EvmExecution;
 use crate::eth::primitives::EvmExecutionMetrics;
 
 /// Evm execution result.
-#[derive(DebugAsJson, Clone, serde::Serialize)]
-#[cfg_attr(test, derive(serde::Deserialize, fake::Dummy, PartialEq))]
+#[derive(DebugAsJson, Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(test, derive(fake::Dummy, PartialEq))]
 pub struct EvmExecutionResult {
     pub execution: EvmExecution,
     pub metrics: EvmExecutionMetrics,
@@ -0,0 +1,142 @@
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use crate::eth::primitives::Block;
+use crate::eth::primitives::ExecutionConflicts;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TransactionExecution;
+use crate::eth::primitives::TransactionInput;
+use crate::ext::MutexExt;
+
+/// Callbacks invoked by the executor and miner so embedding applications can attach custom logic (billing, alerting, auditing) without
+/// forking the executor.
+///
+/// Hooks are called synchronously in the execution path, so implementations must be fast and must offload heavy work to other threads or
+/// tasks. Panics inside a hook are caught and logged, and never affect the execution.
+pub trait ExecutionHook: Send + Sync {
+    /// Name used to identify the hook in logs.
+    fn name(&self) -> &str;
+
+    /// Called after a transaction execution is saved to the pending block.
+    fn on_transaction_executed(&self, _tx: &TransactionExecution) {}
+
+    /// Called after a block is persisted to the permanent storage.
+    fn on_block_committed(&self, _block: &Block) {}
+
+    /// Called when a local transaction execution conflicts with the pending block state and will be retried or rejected.
+    fn on_conflict(&self, _tx: &TransactionExecution, _conflicts: &ExecutionConflicts) {}
+
+    /// Called when a local transaction is evicted, with the error sent to its submitter.
+    ///
+    /// Transactions are evicted when they are parked and the sender state invalidates them, or when they expire before being executed
+    /// because of the configured TTL or their `validUntilBlock`.
+    fn on_transaction_evicted(&self, _tx: &TransactionInput, _reason: &StratusError) {}
+}
+
+/// Registry of [`ExecutionHook`] called in the order they were registered.
+#[derive(Default)]
+pub struct ExecutionHooks {
+    hooks: Mutex<Vec<Arc<dyn ExecutionHook>>>,
+}
+
+impl ExecutionHooks {
+    /// Registers a new hook.
+    pub fn register(&self, hook: Arc<dyn ExecutionHook>) {
+        tracing::info!(hook = %hook.name(), "registering execution hook");
+        self.hooks.lock_or_clear("execution hooks lock was poisoned").push(hook);
+    }
+
+    /// Number of registered hooks.
+    pub fn count(&self) -> usize {
+        self.hooks.lock_or_clear("execution hooks lock was poisoned").len()
+    }
+
+    /// Calls [`ExecutionHook::on_transaction_executed`] in all registered hooks.
+    pub fn transaction_executed(&self, tx: &TransactionExecution) {
+        self.dispatch("on_transaction_executed", |hook| hook.on_transaction_executed(tx));
+    }
+
+    /// Calls [`ExecutionHook::on_block_committed`] in all registered hooks.
+    pub fn block_committed(&self, block: &Block) {
+        self.dispatch("on_block_committed", |hook| hook.on_block_committed(block));
+    }
+
+    /// Calls [`ExecutionHook::on_conflict`] in all registered hooks.
+    pub fn conflict(&self, tx: &TransactionExecution, conflicts: &ExecutionConflicts) {
+        self.dispatch("on_conflict", |hook| hook.on_conflict(tx, conflicts));
+    }
+
+    /// Calls [`ExecutionHook::on_transaction_evicted`] in all registered hooks.
+    pub fn transaction_evicted(&self, tx: &TransactionInput, reason: &StratusError) {
+        self.dispatch("on_transaction_evicted", |hook| hook.on_transaction_evicted(tx, reason));
+    }
+
+    fn dispatch(&self, event: &'static str, call: impl Fn(&dyn ExecutionHook)) {
+        // clone hooks so a hook can register other hooks without deadlocking
+        let hooks = self.hooks.lock_or_clear("execution hooks lock was poisoned").clone();
+        for hook in hooks {
+            if let Err(e) = panic::catch_unwind(AssertUnwindSafe(|| call(hook.as_ref()))) {
+                let message = e
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| e.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                tracing::error!(hook = %hook.name(), %event, %message, "execution hook panicked");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    use super::*;
+    use crate::eth::primitives::BlockNumber;
+    use crate::eth::primitives::UnixTime;
+
+    #[derive(Default)]
+    struct CountingHook {
+        blocks: AtomicUsize,
+    }
+
+    impl ExecutionHook for CountingHook {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        fn on_block_committed(&self, _block: &Block) {
+            self.blocks.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    struct PanickingHook;
+
+    impl ExecutionHook for PanickingHook {
+        fn name(&self) -> &str {
+            "panicking"
+        }
+
+        fn on_block_committed(&self, _block: &Block) {
+            panic!("hook failure");
+        }
+    }
+
+    #[test]
+    fn test_panicking_hook_does_not_stop_dispatch() {
+        let hooks = ExecutionHooks::default();
+        let counting = Arc::new(CountingHook::default());
+        hooks.register(Arc::new(PanickingHook));
+        hooks.register(Arc::clone(&counting) as Arc<dyn ExecutionHook>);
+        assert_eq!(hooks.count(), 2);
+
+        let block = Block::new(BlockNumber::ZERO, UnixTime::from(1));
+        hooks.block_committed(&block);
+        hooks.block_committed(&block);
+
+        assert_eq!(counting.blocks.load(Ordering::Relaxed), 2);
+    }
+}
@@ -1,8 +1,14 @@
 use std::cmp::max;
 use std::mem;
+use std::panic;
+use std::panic::AssertUnwindSafe;
 use std::str::FromStr;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
 
 use anyhow::anyhow;
 use cfg_if::cfg_if;
@@ -11,14 +17,33 @@ use tracing::Span;
 
 #[cfg(feature = "metrics")]
 use crate::eth::codegen;
+use crate::eth::executor::CallCache;
+use crate::eth::executor::CallCacheKey;
+use crate::eth::executor::ComparisonStats;
 use crate::eth::executor::Evm;
+use crate::eth::executor::EvmConfig;
 use crate::eth::executor::EvmExecutionResult;
 use crate::eth::executor::EvmInput;
+use crate::eth::executor::EvmOverlay;
+use crate::eth::executor::EvmQueue;
+use crate::eth::executor::EvmQueueRejection;
+use crate::eth::executor::ExecutionHook;
 use crate::eth::executor::ExecutorConfig;
+use crate::eth::executor::ExternalComparisons;
+#[cfg(feature = "dev")]
+use crate::eth::executor::Impersonation;
+use crate::eth::executor::NonceParking;
 use crate::eth::miner::Miner;
+use crate::eth::miner::QuarantineReason;
+use crate::eth::primitives::AccessList;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::CallInput;
+use crate::eth::primitives::CallTrace;
+use crate::eth::primitives::ChainId;
+use crate::eth::primitives::ComparisonOutcome;
 use crate::eth::primitives::EvmExecution;
 use crate::eth::primitives::EvmExecutionMetrics;
 use crate::eth::primitives::ExternalBlock;
@@ -26,17 +51,23 @@ use crate::eth::primitives::ExternalReceipt;
 use crate::eth::primitives::ExternalReceipts;
 use crate::eth::primitives::ExternalTransaction;
 use crate::eth::primitives::ExternalTransactionExecution;
+use crate::eth::primitives::Gas;
+use crate::eth::primitives::Hash;
 use crate::eth::primitives::StratusError;
 use crate::eth::primitives::TransactionExecution;
 use crate::eth::primitives::TransactionInput;
+use crate::eth::primitives::TransactionOptions;
 use crate::eth::primitives::UnixTime;
+use crate::eth::primitives::Wei;
 use crate::eth::storage::StoragePointInTime;
 use crate::eth::storage::StratusStorage;
+use crate::ext::not;
 use crate::ext::spawn_thread;
 use crate::ext::to_json_string;
 use crate::ext::MutexExt;
 #[cfg(feature = "metrics")]
 use crate::infra::metrics;
+use crate::infra::operations::Operation;
 use crate::infra::tracing::warn_task_tx_closed;
 use crate::infra::tracing::SpanExt;
 use crate::GlobalState;
@@ -50,6 +81,9 @@ pub struct EvmTask {
     pub span: Span,
     pub input: EvmInput,
     pub response_tx: oneshot::Sender<Result<EvmExecutionResult, StratusError>>,
+
+    /// Instant after which the task must be discarded instead of executed.
+    pub expiry: Option<EvmTaskExpiry>,
 }
 
 impl EvmTask {
@@ -58,6 +92,36 @@ impl EvmTask {
             span: Span::current(),
             input,
             response_tx,
+            expiry: None,
+        }
+    }
+}
+
+/// Deadline of a task waiting in an EVM queue.
+#[derive(Debug, Clone, Copy)]
+pub struct EvmTaskExpiry {
+    pub deadline: Instant,
+    pub ttl: Duration,
+}
+
+impl EvmTaskExpiry {
+    /// Creates an expiry starting now.
+    pub fn starting_now(ttl: Duration) -> Self {
+        Self {
+            deadline: Instant::now() + ttl,
+            ttl,
+        }
+    }
+
+    /// Checks if the deadline was reached.
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.deadline
+    }
+
+    /// Error returned for tasks discarded because of this expiry.
+    pub fn to_error(self) -> StratusError {
+        StratusError::TransactionExpired {
+            ttl_millis: self.ttl.as_millis(),
         }
     }
 }
@@ -66,104 +130,268 @@ impl EvmTask {
 // Evm communication channels
 // -----------------------------------------------------------------------------
 
-/// Manages EVM pool and communication channels.
-struct Evms {
-    /// Pool for parallel execution of transactions received via `eth_sendRawTransaction`. Usually contains multiple EVMs.
-    pub tx_parallel: crossbeam_channel::Sender<EvmTask>,
+/// Pool of EVMs consuming tasks from the same queue.
+struct EvmPool {
+    /// Prefix of the name of EVM threads.
+    name: &'static str,
 
-    /// Pool for serial execution of transactions received via `eth_sendRawTransaction`. Usually contains a single EVM.
-    pub tx_serial: crossbeam_channel::Sender<EvmTask>,
+    /// Queue that feeds the EVMs.
+    queue: Arc<EvmQueue<EvmTask>>,
 
-    /// Pool for serial execution of external transactions received via `importer-online` or `importer-offline`. Usually contains a single EVM.
-    pub tx_external: crossbeam_channel::Sender<EvmTask>,
+    /// Number of EVMs consuming tasks.
+    size: Mutex<usize>,
 
-    /// Pool for parallel execution of calls (eth_call and eth_estimateGas) reading from current state. Usually contains multiple EVMs.
-    pub call_present: crossbeam_channel::Sender<EvmTask>,
+    /// Number of EVMs spawned since the pool was created, used to name new EVM threads.
+    spawned: AtomicUsize,
 
-    /// Pool for parallel execution of calls (eth_call and eth_estimateGas) reading from past state. Usually contains multiple EVMs.
-    pub call_past: crossbeam_channel::Sender<EvmTask>,
+    storage: Arc<StratusStorage>,
+    config: EvmConfig,
 }
 
-impl Evms {
-    /// Spawns EVM tasks in background.
-    fn spawn(storage: Arc<StratusStorage>, config: &ExecutorConfig) -> Self {
-        // function executed by evm threads
-        fn evm_loop(task_name: &str, storage: Arc<StratusStorage>, config: ExecutorConfig, task_rx: crossbeam_channel::Receiver<EvmTask>) {
-            let mut evm = Evm::new(storage, config);
-
-            // keep executing transactions until the channel is closed
-            while let Ok(task) = task_rx.recv() {
-                if GlobalState::is_shutdown_warn(task_name) {
-                    return;
-                }
-
-                // execute
-                let _enter = task.span.enter();
-                let result = evm.execute(task.input);
-                if let Err(e) = task.response_tx.send(result) {
-                    tracing::error!(reason = ?e, "failed to send evm task execution result");
-                }
-            }
-
-            warn_task_tx_closed(task_name);
-        }
+impl EvmPool {
+    /// Creates a pool and spawns its EVMs in background.
+    fn spawn(name: &'static str, size: usize, capacity: usize, storage: Arc<StratusStorage>, config: &ExecutorConfig) -> Self {
+        let pool = Self {
+            name,
+            queue: Arc::new(EvmQueue::new(config.executor_queue, config.executor_queue_quantum, capacity)),
+            size: Mutex::new(0),
+            spawned: AtomicUsize::new(0),
+            storage,
+            config: config.evm_config(),
+        };
+        pool.resize(size);
+        pool
+    }
 
-        // function that spawn evm threads
-        let spawn_evms = |task_name: &str, num_evms: usize| {
-            let (evm_tx, evm_rx) = crossbeam_channel::unbounded::<EvmTask>();
+    /// Number of EVMs consuming tasks.
+    fn size(&self) -> usize {
+        *self.size.lock_or_clear("evm pool size lock was poisoned")
+    }
 
-            for evm_index in 1..=num_evms {
-                let evm_task_name = format!("{}-{}", task_name, evm_index);
-                let evm_storage = Arc::clone(&storage);
-                let evm_config = config.clone();
-                let evm_rx = evm_rx.clone();
+    /// Spawns or retires EVMs until the pool has the specified size.
+    ///
+    /// Retired EVMs finish the task they are executing before stopping.
+    fn resize(&self, new_size: usize) {
+        let mut size = self.size.lock_or_clear("evm pool size lock was poisoned");
+        if new_size > *size {
+            for _ in *size..new_size {
+                let evm_index = self.spawned.fetch_add(1, Ordering::Relaxed) + 1;
+                let evm_task_name = format!("{}-{}", self.name, evm_index);
+                let evm_storage = Arc::clone(&self.storage);
+                let evm_config = self.config.clone();
+                let evm_rx = Arc::clone(&self.queue);
                 let thread_name = evm_task_name.clone();
                 spawn_thread(&thread_name, move || {
                     evm_loop(&evm_task_name, evm_storage, evm_config, evm_rx);
                 });
             }
-            evm_tx
+        } else if new_size < *size {
+            self.queue.retire(*size - new_size);
+        }
+        *size = new_size;
+    }
+}
+
+/// Function executed by EVM threads.
+fn evm_loop(task_name: &str, storage: Arc<StratusStorage>, config: EvmConfig, task_rx: Arc<EvmQueue<EvmTask>>) {
+    let mut evm = Evm::new(Arc::clone(&storage), config.clone());
+
+    // keep executing transactions until the queue is closed or the evm is retired
+    // shutdown is not checked here, so transactions accepted before the shutdown are drained instead of failing
+    while let Some(task) = task_rx.pop() {
+        // discard expired tasks without executing them
+        let _enter = task.span.enter();
+        if let Some(expiry) = task.expiry {
+            if expiry.is_expired() {
+                tracing::warn!(task_name, ttl = ?expiry.ttl, "discarding expired evm task");
+                if let Err(e) = task.response_tx.send(Err(expiry.to_error())) {
+                    tracing::error!(reason = ?e, "failed to send evm task expiration result");
+                }
+                continue;
+            }
+        }
+
+        // execute
+        // panics are caught to keep the EVM thread alive, and the EVM is recreated because its state may be inconsistent
+        let result = match panic::catch_unwind(AssertUnwindSafe(|| evm.execute(task.input))) {
+            Ok(result) => result,
+            Err(panic) => {
+                let message = panic_message(panic);
+                tracing::error!(task_name, %message, "evm panicked while executing task");
+                evm = Evm::new(Arc::clone(&storage), config.clone());
+                Err(StratusError::TransactionEvmPanicked { message })
+            }
         };
+        if let Err(e) = task.response_tx.send(result) {
+            tracing::error!(reason = ?e, "failed to send evm task execution result");
+        }
+    }
+
+    if task_rx.is_closed() {
+        warn_task_tx_closed(task_name);
+    } else {
+        tracing::info!(task_name, "evm retired");
+    }
+}
+
+/// Extracts the message from a panic payload.
+fn panic_message(panic: Box<dyn std::any::Any + Send>) -> String {
+    match panic.downcast::<String>() {
+        Ok(message) => *message,
+        Err(panic) => match panic.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_owned(),
+        },
+    }
+}
+
+/// Manages EVM pools and communication channels.
+struct Evms {
+    /// Pool for parallel execution of transactions received via `eth_sendRawTransaction`. Usually contains multiple EVMs.
+    pub tx_parallel: EvmPool,
 
+    /// Pool for serial execution of transactions received via `eth_sendRawTransaction`. Usually contains a single EVM.
+    pub tx_serial: EvmPool,
+
+    /// Pool for serial execution of external transactions received via `importer-online` or `importer-offline`. Usually contains a single EVM.
+    ///
+    /// Its queue is unbounded because importers must never drop blocks.
+    pub tx_external: EvmPool,
+
+    /// Pool for parallel execution of calls (eth_call and eth_estimateGas) reading from current state. Usually contains multiple EVMs.
+    pub call_present: EvmPool,
+
+    /// Pool for parallel execution of calls (eth_call and eth_estimateGas) reading from past state. Usually contains multiple EVMs.
+    pub call_past: EvmPool,
+}
+
+impl Evms {
+    /// Spawns EVM tasks in background.
+    fn spawn(storage: Arc<StratusStorage>, config: &ExecutorConfig) -> Self {
+        let capacity = config.executor_queue_capacity;
         let tx_parallel = match config.executor_strategy {
-            ExecutorStrategy::Serial => spawn_evms("evm-tx-unused", 1), // should not really be used if strategy is serial, but keep 1 for fallback
-            ExecutorStrategy::Paralell => spawn_evms("evm-tx-parallel", config.executor_evms),
+            ExecutorStrategy::Serial => EvmPool::spawn("evm-tx-unused", 1, capacity, Arc::clone(&storage), config), // should not really be used if strategy is serial, but keep 1 for fallback
+            ExecutorStrategy::Paralell => EvmPool::spawn("evm-tx-parallel", config.executor_evms, capacity, Arc::clone(&storage), config),
         };
-        let tx_serial = spawn_evms("evm-tx-serial", 1);
-        let tx_external = spawn_evms("evm-tx-external", 1);
-        let call_present = spawn_evms("evm-call-present", max(config.executor_evms / 2, 1));
-        let call_past = spawn_evms("evm-call-past", max(config.executor_evms / 4, 1));
+        let tx_serial = EvmPool::spawn("evm-tx-serial", 1, capacity, Arc::clone(&storage), config);
+        let tx_external = EvmPool::spawn("evm-tx-external", 1, 0, Arc::clone(&storage), config);
+        let call_present = EvmPool::spawn("evm-call-present", max(config.executor_evms / 2, 1), capacity, Arc::clone(&storage), config);
+        let call_past = EvmPool::spawn("evm-call-past", max(config.executor_evms / 4, 1), capacity, storage, config);
 
-        Evms {
+        let evms = Evms {
             tx_parallel,
             tx_serial,
             tx_external,
             call_present,
             call_past,
+        };
+        #[cfg(feature = "metrics")]
+        for route in [
+            EvmRoute::Parallel,
+            EvmRoute::Serial,
+            EvmRoute::External,
+            EvmRoute::CallPresent,
+            EvmRoute::CallPast,
+        ] {
+            metrics::set_executor_evm_pool_size(evms.pool(route).size() as u64, route.to_string());
         }
+        evms
     }
 
     /// Executes a transaction in the specified route.
     fn execute(&self, evm_input: EvmInput, route: EvmRoute) -> Result<EvmExecutionResult, StratusError> {
+        self.execute_with_expiry(evm_input, route, None)
+    }
+
+    /// Executes a transaction in the specified route, discarding it if still queued after the expiry.
+    ///
+    /// Fails immediately if the queue of the route is full.
+    fn execute_with_expiry(&self, evm_input: EvmInput, route: EvmRoute, expiry: Option<EvmTaskExpiry>) -> Result<EvmExecutionResult, StratusError> {
         let (execution_tx, execution_rx) = oneshot::channel::<Result<EvmExecutionResult, StratusError>>();
 
-        let task = EvmTask::new(evm_input, execution_tx);
-        let _ = match route {
-            EvmRoute::Parallel => self.tx_parallel.send(task),
-            EvmRoute::Serial => self.tx_serial.send(task),
-            EvmRoute::External => self.tx_external.send(task),
-            EvmRoute::CallPresent => self.call_present.send(task),
-            EvmRoute::CallPast => self.call_past.send(task),
-        };
+        let sender = evm_input.from;
+        let mut task = EvmTask::new(evm_input, execution_tx);
+        task.expiry = expiry;
+        let queue = &self.pool(route).queue;
+        match queue.push(sender, task) {
+            Ok(()) => {}
+            Err((EvmQueueRejection::Full, _)) => {
+                #[cfg(feature = "metrics")]
+                metrics::inc_executor_evm_queue_rejected(route.to_string());
+                tracing::warn!(%route, capacity = queue.capacity(), "rejecting evm task because queue is full");
+                return Err(StratusError::StratusOverloaded {
+                    route: route.to_string(),
+                    capacity: queue.capacity(),
+                });
+            }
+            Err((EvmQueueRejection::Closed, _)) => return Err(StratusError::UnexpectedChannelClosed { channel: "evm" }),
+        }
+        #[cfg(feature = "metrics")]
+        metrics::set_executor_evm_queue_depth(queue.len() as u64, route.to_string());
 
-        match execution_rx.recv() {
+        let result = match execution_rx.recv() {
             Ok(result) => result,
             Err(_) => Err(StratusError::UnexpectedChannelClosed { channel: "evm" }),
+        };
+
+        #[cfg(feature = "metrics")]
+        metrics::set_executor_evm_queue_depth(queue.len() as u64, route.to_string());
+
+        result
+    }
+
+    /// Pool that executes the tasks of the specified route.
+    fn pool(&self, route: EvmRoute) -> &EvmPool {
+        match route {
+            EvmRoute::Parallel => &self.tx_parallel,
+            EvmRoute::Serial => &self.tx_serial,
+            EvmRoute::External => &self.tx_external,
+            EvmRoute::CallPresent => &self.call_present,
+            EvmRoute::CallPast => &self.call_past,
         }
     }
+
+    /// Changes the number of EVMs of the specified route.
+    ///
+    /// Only routes that execute tasks in parallel can be resized, because serial routes rely on having a single EVM.
+    fn resize(&self, route: EvmRoute, size: usize) -> Result<(), StratusError> {
+        let resizable = matches!(route, EvmRoute::Parallel | EvmRoute::CallPresent | EvmRoute::CallPast);
+        if not(resizable) || size == 0 {
+            return Err(StratusError::RpcEvmPoolResizeInvalid {
+                route: route.to_string(),
+                size,
+            });
+        }
+
+        let pool = self.pool(route);
+        tracing::info!(%route, from = pool.size(), to = size, "resizing evm pool");
+        pool.resize(size);
+
+        #[cfg(feature = "metrics")]
+        metrics::set_executor_evm_pool_size(size as u64, route.to_string());
+
+        Ok(())
+    }
+
+    /// Number of local transactions waiting in EVM queues.
+    fn queued_transactions(&self) -> usize {
+        self.tx_parallel.queue.len() + self.tx_serial.queue.len()
+    }
+}
+
+impl Drop for Evms {
+    fn drop(&mut self) {
+        // wakes up EVM threads so they can finish after executing all queued tasks
+        self.tx_parallel.queue.close();
+        self.tx_serial.queue.close();
+        self.tx_external.queue.close();
+        self.call_present.queue.close();
+        self.call_past.queue.close();
+    }
 }
 
-#[derive(Debug, Clone, Copy, strum::Display)]
+/// Route of EVM tasks, each one executed by a different pool of EVMs.
+#[derive(Debug, Clone, Copy, strum::Display, strum::EnumString)]
 pub enum EvmRoute {
     #[strum(to_string = "parallel")]
     Parallel,
@@ -182,7 +410,102 @@ pub enum EvmRoute {
 }
 
 // -----------------------------------------------------------------------------
-// Executor
+// Executor interface
+// -----------------------------------------------------------------------------
+
+/// Executes, imports and simulates transactions.
+///
+/// Used by the RPC server, importers and background services, so alternative coordination strategies can be swapped in by
+/// [`ExecutorConfig::init`] without changing them.
+pub trait Executor: Send + Sync + 'static {
+    // -------------------------------------------------------------------------
+    // Import
+    // -------------------------------------------------------------------------
+
+    /// Reexecutes an external block locally and imports it to the temporary storage.
+    fn execute_external_block(&self, block: ExternalBlock, receipts: &mut ExternalReceipts) -> Result<(), StratusError>;
+
+    // -------------------------------------------------------------------------
+    // Transact
+    // -------------------------------------------------------------------------
+
+    /// Executes a transaction persisting state changes.
+    fn execute_local_transaction(&self, tx: TransactionInput, tx_options: TransactionOptions) -> Result<TransactionExecution, StratusError>;
+
+    // -------------------------------------------------------------------------
+    // Call
+    // -------------------------------------------------------------------------
+
+    /// Executes a transaction without persisting state changes.
+    fn execute_local_call(&self, call_input: CallInput, point_in_time: StoragePointInTime) -> Result<EvmExecution, StratusError>;
+
+    /// Checks if the result of a local call is cached, so it can be served without executing the call.
+    fn is_local_call_cached(&self, call_input: &CallInput, point_in_time: StoragePointInTime) -> bool;
+
+    /// Executes a transaction without persisting state changes and returns the accounts and slots it accessed as an EIP-2930 access list.
+    fn create_access_list(&self, call_input: CallInput, point_in_time: StoragePointInTime) -> Result<(AccessList, EvmExecution), StratusError>;
+
+    /// Executes a sequence of transactions at the same point-in-time without persisting state changes.
+    fn execute_local_call_many(
+        &self,
+        calls: Vec<CallInput>,
+        point_in_time: StoragePointInTime,
+        operation: Option<&Operation>,
+    ) -> Result<Vec<EvmExecution>, StratusError>;
+
+    /// Executes again all transactions of a mined block against the state before the block without persisting state changes.
+    fn trace_block(&self, block: &Block, operation: Option<&Operation>) -> Result<Vec<(Hash, EvmExecution)>, StratusError>;
+
+    /// Executes again all transactions of a mined block recording the calls, contract creations and self-destructs they performed.
+    fn trace_block_calls(&self, block: &Block, operation: Option<&Operation>) -> Result<Vec<(Hash, Vec<CallTrace>)>, StratusError>;
+
+    // -------------------------------------------------------------------------
+    // Subscriptions
+    // -------------------------------------------------------------------------
+
+    /// Registers a callback hook invoked when transactions are executed, blocks are committed or conflicts are detected.
+    fn register_hook(&self, hook: Arc<dyn ExecutionHook>);
+
+    // -------------------------------------------------------------------------
+    // Impersonation
+    // -------------------------------------------------------------------------
+
+    /// Accounts allowed to send transactions without signature, scoped by client application.
+    #[cfg(feature = "dev")]
+    fn impersonation(&self) -> &Impersonation;
+
+    /// Checks if an account is allowed to send transactions without signature by any client.
+    fn is_impersonated(&self, address: &Address) -> bool;
+
+    // -------------------------------------------------------------------------
+    // Status
+    // -------------------------------------------------------------------------
+
+    /// Configuration used by all EVM instances.
+    fn evm_config(&self) -> EvmConfig;
+
+    /// Number of local transactions waiting to be executed.
+    fn queued_transactions(&self) -> usize;
+
+    /// Number of local transactions discarded because they expired before being executed.
+    fn expired_transactions(&self) -> usize;
+
+    /// Number of local transactions being executed right now.
+    fn in_flight_transactions(&self) -> usize;
+
+    /// Outcomes of comparing re-executed external transactions with their receipts in the window ending now.
+    fn external_comparison_stats(&self, window: Duration) -> ComparisonStats;
+
+    // -------------------------------------------------------------------------
+    // Administration
+    // -------------------------------------------------------------------------
+
+    /// Changes the number of EVMs executing the tasks of a route.
+    fn resize_evms(&self, route: EvmRoute, size: usize) -> Result<(), StratusError>;
+}
+
+// -----------------------------------------------------------------------------
+// EVM executor
 // -----------------------------------------------------------------------------
 
 /// Locks used for local execution.
@@ -191,29 +514,56 @@ pub struct ExecutorLocks {
     serial: Mutex<()>,
 }
 
-pub struct Executor {
+/// Executor that runs transactions in a pool of background EVMs.
+pub struct EvmExecutor {
     /// Executor inner locks.
     locks: ExecutorLocks,
 
+    /// Accounts allowed to send transactions without signature.
+    #[cfg(feature = "dev")]
+    impersonation: Impersonation,
+
+    /// Number of local transactions discarded because they expired.
+    expired_transactions: AtomicUsize,
+
+    /// Number of local transactions being executed right now.
+    in_flight_transactions: AtomicUsize,
+
+    /// Local transactions waiting for a nonce gap to be filled.
+    nonce_parking: NonceParking,
+
+    /// Outcomes of re-executed external transactions.
+    external_comparisons: ExternalComparisons,
+
+    /// Results of local calls executed against mined state.
+    call_cache: CallCache,
+
     /// Executor configuration.
     config: ExecutorConfig,
 
     /// Channels to send transactions to background EVMs.
     evms: Evms,
 
-    /// Mutex-wrapped miner for creating new blockchain blocks.
-    miner: Arc<Miner>,
+    /// Mutex-wrapped miner for creating new blockchain blocks. Archive nodes do not have one, so they execute only calls and traces.
+    miner: Option<Arc<Miner>>,
 
     /// Shared storage backend for persisting blockchain state.
     storage: Arc<StratusStorage>,
 }
 
-impl Executor {
-    pub fn new(storage: Arc<StratusStorage>, miner: Arc<Miner>, config: ExecutorConfig) -> Self {
+impl EvmExecutor {
+    pub fn new(storage: Arc<StratusStorage>, miner: Option<Arc<Miner>>, config: ExecutorConfig) -> Self {
         tracing::info!(?config, "creating executor");
         let evms = Evms::spawn(Arc::clone(&storage), &config);
         Self {
             locks: ExecutorLocks::default(),
+            #[cfg(feature = "dev")]
+            impersonation: Impersonation::default(),
+            expired_transactions: AtomicUsize::new(0),
+            in_flight_transactions: AtomicUsize::new(0),
+            nonce_parking: NonceParking::default(),
+            external_comparisons: ExternalComparisons::default(),
+            call_cache: CallCache::new(config.executor_call_cache_size),
             config,
             evms,
             miner,
@@ -228,7 +578,7 @@ impl Executor {
     /// Reexecutes an external block locally and imports it to the temporary storage.
     ///
     /// Returns the remaining receipts that were not consumed by the execution.
-    pub fn execute_external_block(&self, mut block: ExternalBlock, receipts: &mut ExternalReceipts) -> anyhow::Result<()> {
+    pub fn execute_external_block(&self, mut block: ExternalBlock, receipts: &mut ExternalReceipts) -> Result<(), StratusError> {
         // track
         #[cfg(feature = "metrics")]
         let (start, mut block_metrics) = (metrics::now(), EvmExecutionMetrics::default());
@@ -237,6 +587,9 @@ impl Executor {
         let _span = info_span!("executor::external_block", block_number = %block.number()).entered();
         tracing::info!(block_number = %block.number(), "reexecuting external block");
 
+        // reject blocks whose contents do not match their hash
+        block.validate_hash()?;
+
         // track pending block
         let block_number = block.number();
         let block_timestamp = block.timestamp();
@@ -279,7 +632,7 @@ impl Executor {
         block_number: BlockNumber,
         block_timestamp: UnixTime,
         #[cfg(feature = "metrics")] block_metrics: &mut EvmExecutionMetrics,
-    ) -> anyhow::Result<()> {
+    ) -> Result<(), StratusError> {
         // track
         #[cfg(feature = "metrics")]
         let (start, tx_function) = (metrics::now(), codegen::function_sig_for_o11y(&tx.0.input));
@@ -294,7 +647,9 @@ impl Executor {
             // successful external transaction, re-execute locally
             true => {
                 // re-execute transaction
-                let evm_input = EvmInput::from_external(&tx, &receipt, block_number, block_timestamp)?;
+                let evm_input = EvmInput::builder_from_external(&tx, &receipt)?
+                    .block_env(block_number, block_timestamp, StoragePointInTime::Pending)
+                    .build();
                 let evm_execution = self.evms.execute(evm_input, EvmRoute::External);
 
                 // handle re-execution result
@@ -304,11 +659,12 @@ impl Executor {
                         let json_tx = to_json_string(&tx);
                         let json_receipt = to_json_string(&receipt);
                         tracing::error!(reason = ?e, %block_number, tx_hash = %tx.hash(), %json_tx, %json_receipt, "failed to reexecute external transaction");
-                        return Err(e.into());
+                        return Err(e);
                     }
                 };
 
                 // update execution with receipt
+                let execution_gas = evm_execution.execution.gas;
                 evm_execution.execution.apply_receipt(&receipt)?;
 
                 // ensure it matches receipt before saving
@@ -317,9 +673,27 @@ impl Executor {
                     let json_receipt = to_json_string(&receipt);
                     let json_execution_logs = to_json_string(&evm_execution.execution.logs);
                     tracing::error!(reason = ?e, %block_number, tx_hash = %tx.hash(), %json_tx, %json_receipt, %json_execution_logs, "failed to reexecute external transaction");
-                    return Err(e);
+                    self.external_comparisons
+                        .record(block_number, tx.hash(), e.outcome, Some(e.reason.clone()), UnixTime::now());
+                    return Err(anyhow::Error::from(e).into());
                 };
 
+                // gas differences do not prevent the import because the receipt gas is kept
+                if execution_gas != evm_execution.execution.gas {
+                    let reason = format!(
+                        "gas mismatch | hash={} execution={} receipt={}",
+                        tx.hash(),
+                        execution_gas,
+                        evm_execution.execution.gas
+                    );
+                    tracing::warn!(%block_number, tx_hash = %tx.hash(), %reason, "reexecuted external transaction used different gas");
+                    self.external_comparisons
+                        .record(block_number, tx.hash(), ComparisonOutcome::GasMismatch, Some(reason), UnixTime::now());
+                } else {
+                    self.external_comparisons
+                        .record(block_number, tx.hash(), ComparisonOutcome::Matched, None, UnixTime::now());
+                }
+
                 ExternalTransactionExecution::new(tx, receipt, evm_execution)
             }
             //
@@ -345,7 +719,14 @@ impl Executor {
 
         // persist state
         let tx_execution = TransactionExecution::External(tx_execution);
-        self.miner.save_execution(tx_execution, false)?;
+        let miner = self.miner()?;
+        let hooked_execution = if miner.hooks.count() > 0 { Some(tx_execution.clone()) } else { None };
+        miner.save_execution(tx_execution, false)?;
+        #[cfg(feature = "metrics")]
+        metrics::inc_executor_transactions_total("external");
+        if let Some(hooked_execution) = hooked_execution {
+            miner.hooks.transaction_executed(&hooked_execution);
+        }
 
         // track metrics
         #[cfg(feature = "metrics")]
@@ -361,18 +742,42 @@ impl Executor {
         Ok(())
     }
 
+    // -------------------------------------------------------------------------
+    // Hooks
+    // -------------------------------------------------------------------------
+
+    /// Registers a callback hook invoked when transactions are executed, blocks are committed or conflicts are detected.
+    pub fn register_hook(&self, hook: Arc<dyn ExecutionHook>) {
+        if let Some(ref miner) = self.miner {
+            miner.hooks.register(hook);
+        }
+    }
+
     // -------------------------------------------------------------------------
     // Local transactions
     // -------------------------------------------------------------------------
 
     /// Executes a transaction persisting state changes.
     #[tracing::instrument(name = "executor::local_transaction", skip_all, fields(tx_hash, tx_from, tx_to, tx_nonce))]
-    pub fn execute_local_transaction(&self, tx: TransactionInput) -> Result<TransactionExecution, StratusError> {
+    pub fn execute_local_transaction(&self, tx: TransactionInput, tx_options: TransactionOptions) -> Result<TransactionExecution, StratusError> {
         #[cfg(feature = "metrics")]
         let start = metrics::now();
 
+        // transaction expiry starts counting when it is received, so time waiting for locks is also considered
+        let expiry = self.config.executor_tx_ttl.map(EvmTaskExpiry::starting_now);
+        let started_at = Instant::now();
+
         tracing::info!(tx_hash = %tx.hash, "executing local transaction");
 
+        // archive nodes do not have a miner, so they cannot execute transactions
+        let miner = self.miner()?;
+
+        // reject new transactions while shutting down, so the ones already accepted can be drained
+        if GlobalState::is_shutdown_warn("executor::local_transaction") {
+            return Err(StratusError::StratusShutdown);
+        }
+        let _in_flight = InFlightGuard::enter(&self.in_flight_transactions);
+
         // track
         Span::with(|s| {
             s.rec_str("tx_hash", &tx.hash);
@@ -381,6 +786,22 @@ impl Executor {
             s.rec_str("tx_nonce", &tx.nonce);
         });
 
+        // reject transactions signed for another chain (EIP-155 replay protection)
+        // unprotected transactions are not validated
+        if let Some(tx_chain_id) = tx.chain_id {
+            let expected: ChainId = self.config.executor_chain_id.into();
+            if tx_chain_id != expected {
+                tracing::warn!(tx_hash = %tx.hash, %tx_chain_id, %expected, "rejecting local transaction with invalid chain id");
+                return Err(StratusError::TransactionInvalidChainId {
+                    transaction: tx_chain_id,
+                    expected,
+                });
+            }
+        }
+
+        // validate nonce before acquiring locks, so parked transactions do not block the ones that fill the nonce gap
+        self.validate_nonce(&tx, expiry)?;
+
         // execute according to the strategy
         const INFINITE_ATTEMPTS: usize = usize::MAX;
 
@@ -396,8 +817,8 @@ impl Executor {
                 // WORKAROUND: prevents interval miner mining blocks while a transaction is being executed.
                 // this can be removed when we implement conflict detection for block number
                 let _miner_lock = {
-                    if self.miner.mode().is_interval() {
-                        let miner_lock = Some(self.miner.locks.mine_and_commit.lock_or_clear("miner mine_and_commit lock was poisoned"));
+                    if miner.mode().is_interval() {
+                        let miner_lock = Some(miner.locks.mine_and_commit.lock_or_clear("miner mine_and_commit lock was poisoned"));
                         miner_lock
                     } else {
                         None
@@ -405,18 +826,18 @@ impl Executor {
                 };
 
                 // execute transaction
-                self.execute_local_transaction_attempts(tx.clone(), EvmRoute::Serial, INFINITE_ATTEMPTS)
+                self.execute_local_transaction_attempts(tx.clone(), tx_options, expiry, started_at, EvmRoute::Serial, INFINITE_ATTEMPTS)
             }
 
             // Executes transactions in parallel mode:
             // * Conflict detection prevents data corruption.
             ExecutorStrategy::Paralell => {
-                let parallel_attempt = self.execute_local_transaction_attempts(tx.clone(), EvmRoute::Parallel, 1);
+                let parallel_attempt = self.execute_local_transaction_attempts(tx.clone(), tx_options, expiry, started_at, EvmRoute::Parallel, 1);
                 match parallel_attempt {
                     Ok(tx_execution) => Ok(tx_execution),
                     Err(e) =>
                         if let StratusError::TransactionConflict(_) = e {
-                            self.execute_local_transaction_attempts(tx.clone(), EvmRoute::Serial, INFINITE_ATTEMPTS)
+                            self.execute_local_transaction_attempts(tx.clone(), tx_options, expiry, started_at, EvmRoute::Serial, INFINITE_ATTEMPTS)
                         } else {
                             Err(e)
                         },
@@ -441,23 +862,122 @@ impl Executor {
             }
         }
 
+        // track expired transactions and notify their eviction like parked ones
+        if let Err(ref e) = tx_execution {
+            let reason = match e {
+                StratusError::TransactionExpired { .. } => Some("ttl"),
+                StratusError::TransactionValidUntilBlockExpired { .. } => Some("valid_until_block"),
+                _ => None,
+            };
+            if let Some(reason) = reason {
+                self.expired_transactions.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!(tx_hash = %tx.hash, reason, "local transaction expired and was discarded");
+                miner.hooks.transaction_evicted(&tx, e);
+
+                #[cfg(feature = "metrics")]
+                metrics::inc_executor_local_transaction_expired(reason);
+            }
+        }
+
         tx_execution
     }
 
-    /// Executes a transaction until it reaches the max number of attempts.
+    /// Validates the transaction nonce against the sender account.
+    ///
+    /// Stale nonces are rejected immediately, while future nonces are parked until the nonce gap is filled by other transactions of the
+    /// same sender or the configured timeout is reached.
+    ///
+    /// Parked transactions are checked again every time a local transaction is executed, and are evicted as soon as the sender state
+    /// invalidates them: the nonce was consumed by another transaction or the balance no longer covers the transferred value.
+    fn validate_nonce(&self, tx: &TransactionInput, expiry: Option<EvmTaskExpiry>) -> Result<(), StratusError> {
+        let mut deadline = Instant::now() + self.config.executor_nonce_gap_timeout;
+        if let Some(expiry) = expiry {
+            deadline = deadline.min(expiry.deadline);
+        }
+
+        let mut parked = None;
+        loop {
+            let account = self.storage.read_account(&tx.signer, &StoragePointInTime::Pending)?;
+            if tx.nonce < account.nonce {
+                // resubmissions of executed transactions are reported as already known, so clients can handle them as accepted
+                if self.storage.read_transaction(&tx.hash)?.is_some() {
+                    return Err(StratusError::TransactionAlreadyKnown { hash: tx.hash });
+                }
+                let e = StratusError::TransactionNonceTooLow {
+                    transaction: tx.nonce,
+                    account: account.nonce,
+                };
+                if parked.is_some() {
+                    return Err(self.evict_parked(tx, "nonce", e));
+                }
+                return Err(e);
+            }
+            if tx.nonce == account.nonce {
+                return Ok(());
+            }
+
+            // local transactions execute with zero gas price in blocks without base fee, so only the transferred value is checked
+            if parked.is_some() && account.balance < tx.value {
+                let e = StratusError::TransactionInsufficientFunds {
+                    balance: account.balance,
+                    cost: tx.value,
+                };
+                return Err(self.evict_parked(tx, "balance", e));
+            }
+
+            // future nonce: wait for the gap to be filled
+            if Instant::now() >= deadline {
+                tracing::warn!(tx_hash = %tx.hash, tx_nonce = %tx.nonce, account_nonce = %account.nonce, "rejecting local transaction because nonce gap was not filled");
+                return Err(StratusError::TransactionNonceTooHigh {
+                    transaction: tx.nonce,
+                    account: account.nonce,
+                });
+            }
+            if parked.is_none() {
+                tracing::info!(tx_hash = %tx.hash, tx_nonce = %tx.nonce, account_nonce = %account.nonce, "parking local transaction until nonce gap is filled");
+                parked = Some(self.nonce_parking.enter(tx)?);
+            }
+            self.nonce_parking.wait(deadline);
+        }
+    }
+
+    /// Notifies that a parked transaction was evicted, returning the error sent to its submitter.
+    fn evict_parked(&self, tx: &TransactionInput, reason: &'static str, e: StratusError) -> StratusError {
+        tracing::warn!(tx_hash = %tx.hash, tx_nonce = %tx.nonce, reason, "evicting parked local transaction invalidated by the sender state");
+        #[cfg(feature = "metrics")]
+        metrics::inc_executor_nonce_parking_evictions(reason);
+        if let Some(ref miner) = self.miner {
+            miner.hooks.transaction_evicted(tx, &e);
+        }
+        e
+    }
+
+    /// Executes a transaction until it reaches the max number of attempts or the conflict retry policy is exhausted.
     fn execute_local_transaction_attempts(
         &self,
         tx_input: TransactionInput,
+        tx_options: TransactionOptions,
+        expiry: Option<EvmTaskExpiry>,
+        started_at: Instant,
         evm_route: EvmRoute,
         max_attempts: usize,
     ) -> Result<TransactionExecution, StratusError> {
+        let miner = self.miner()?;
+
         // validate
         if tx_input.signer.is_zero() {
             return Err(StratusError::TransactionFromZeroAddress);
         }
+        if tx_input.is_unsigned() && not(self.is_impersonated(&tx_input.signer)) {
+            return Err(StratusError::TransactionSignatureMissing { from: tx_input.signer });
+        }
+        if miner.quarantine.is_quarantined(&tx_input.hash) {
+            return Err(StratusError::TransactionQuarantined { hash: tx_input.hash });
+        }
 
         // executes transaction until no more conflicts
         let mut attempt = 0;
+        let mut storage_read_time = Duration::ZERO;
         loop {
             attempt += 1;
 
@@ -476,8 +996,29 @@ impl Executor {
             });
 
             // prepare evm input
-            let pending_block_number = self.storage.read_pending_block_number()?.unwrap_or_default();
-            let evm_input = EvmInput::from_eth_transaction(tx_input.clone(), pending_block_number);
+            let (pending_block_number, base_fee) = miner.pending_base_fee()?;
+            let mut evm_input = EvmInput::builder_from_transaction(&tx_input)
+                .block_gas(&tx_input, base_fee)
+                .block_env(pending_block_number, UnixTime::now(), StoragePointInTime::Pending) // TODO: timestamp should come from the pending block
+                .build();
+            evm_input.storage_latency_budget = self
+                .config
+                .executor_storage_latency_budget
+                .map(|budget| budget.saturating_sub(storage_read_time));
+            evm_input.execution_timeout = self.config.executor_execution_timeout;
+
+            // discard transaction if it expired while waiting for a previous attempt or lock
+            if let Some(expiry) = expiry {
+                if expiry.is_expired() {
+                    return Err(expiry.to_error());
+                }
+            }
+            if tx_options.is_expired_at(pending_block_number) {
+                return Err(StratusError::TransactionValidUntilBlockExpired {
+                    valid_until_block: tx_options.valid_until_block.unwrap_or_default(),
+                    pending_block: pending_block_number,
+                });
+            }
 
             // execute transaction in evm (retry only in case of conflict, but do not retry on other failures)
             tracing::info!(
@@ -493,23 +1034,68 @@ impl Executor {
                 "executing local transaction attempt"
             );
 
-            let evm_result = match self.evms.execute(evm_input, evm_route) {
+            let evm_result = match self.evms.execute_with_expiry(evm_input, evm_route, expiry) {
                 Ok(evm_result) => evm_result,
+                // abort before saving the execution, so slow storage reads do not delay block production
+                Err(StratusError::StratusDegraded) => {
+                    #[cfg(feature = "metrics")]
+                    metrics::inc_executor_storage_latency_budget_exceeded();
+                    return Err(StratusError::StratusDegraded);
+                }
+                Err(StratusError::TransactionEvmPanicked { message }) => {
+                    let quarantined = miner
+                        .quarantine
+                        .record_failure(tx_input.hash, tx_input.signer, QuarantineReason::EvmPanic, message.clone());
+                    if quarantined {
+                        return Err(StratusError::TransactionQuarantined { hash: tx_input.hash });
+                    }
+                    return Err(StratusError::TransactionEvmPanicked { message });
+                }
                 Err(e) => return Err(e),
             };
 
+            storage_read_time += evm_result.metrics.storage_read_time;
+
             // save execution to temporary storage
             // in case of failure, retry if conflict or abandon if unexpected error
             let tx_execution = TransactionExecution::new_local(tx_input.clone(), evm_result.clone());
-            match self.miner.save_execution(tx_execution.clone(), true) {
+            match miner.save_execution(tx_execution.clone(), true) {
                 Ok(_) => {
+                    #[cfg(feature = "metrics")]
+                    metrics::inc_executor_transactions_total("local");
+                    self.nonce_parking.notify_executed();
+                    miner.hooks.transaction_executed(&tx_execution);
                     return Ok(tx_execution);
                 }
                 Err(e) =>
-                    if let StratusError::TransactionConflict(ref conflicts) = e {
+                    if let StratusError::TransactionConflict(conflicts) = e {
                         tracing::warn!(%attempt, ?conflicts, "temporary storage conflict detected when saving execution");
+                        #[cfg(feature = "metrics")]
+                        for address in conflicts.addresses() {
+                            metrics::inc_executor_transaction_conflicts(address.to_string());
+                        }
+                        miner.hooks.conflict(&tx_execution, &conflicts);
+                        if let Some(ref gas_target) = miner.gas_target {
+                            gas_target.record_conflict();
+                        }
+                        miner
+                            .discarded_attempts
+                            .record_conflict(tx_input.hash, attempt, tx_execution.execution().gas, &conflicts);
                         if attempt >= max_attempts {
-                            return Err(e);
+                            return Err(StratusError::TransactionConflict(conflicts));
+                        }
+
+                        // give up if conflicts persist beyond the configured retry policy
+                        let elapsed = started_at.elapsed();
+                        let retries_exhausted = self.config.executor_conflict_max_retries.is_some_and(|max_retries| attempt > max_retries);
+                        let timeout_exhausted = self.config.executor_conflict_timeout.is_some_and(|timeout| elapsed >= timeout);
+                        if retries_exhausted || timeout_exhausted {
+                            tracing::error!(%attempt, elapsed = ?elapsed, "giving up transaction after exhausting conflict retry policy");
+                            return Err(StratusError::TransactionRetryExhausted {
+                                attempts: attempt,
+                                elapsed_millis: elapsed.as_millis(),
+                                conflicts,
+                            });
                         }
                         continue;
                     } else {
@@ -522,6 +1108,131 @@ impl Executor {
     /// Executes a transaction without persisting state changes.
     #[tracing::instrument(name = "executor::local_call", skip_all, fields(from, to))]
     pub fn execute_local_call(&self, call_input: CallInput, point_in_time: StoragePointInTime) -> Result<EvmExecution, StratusError> {
+        self.do_execute_local_call(call_input, point_in_time, None)
+    }
+
+    /// Checks if the result of a local call is cached, so it can be served without executing the call.
+    pub fn is_local_call_cached(&self, call_input: &CallInput, point_in_time: StoragePointInTime) -> bool {
+        let Ok(block_number) = self.storage.read_mined_block_number() else {
+            return false;
+        };
+        match CallCacheKey::new(call_input, point_in_time, block_number) {
+            Some(cache_key) => self.call_cache.contains(&cache_key, self.storage.read_mined_state_version()),
+            None => false,
+        }
+    }
+
+    /// Executes a transaction without persisting state changes and returns the accounts and slots it accessed as an EIP-2930 access list.
+    #[tracing::instrument(name = "executor::create_access_list", skip_all, fields(from, to))]
+    pub fn create_access_list(&self, call_input: CallInput, point_in_time: StoragePointInTime) -> Result<(AccessList, EvmExecution), StratusError> {
+        let (from, to) = (call_input.from, call_input.to);
+        let execution = self.do_execute_local_call(call_input, point_in_time, None)?;
+        let access_list = AccessList::from_execution(&execution, from, to);
+        Ok((access_list, execution))
+    }
+
+    /// Executes a sequence of transactions at the same point-in-time without persisting state changes.
+    ///
+    /// Changes of each transaction are kept in an ephemeral overlay, so each one sees the state left by the previous ones.
+    ///
+    /// When an operation is provided, the bundle is aborted between calls if the operation is cancelled.
+    #[tracing::instrument(name = "executor::local_call_many", skip_all, fields(calls))]
+    pub fn execute_local_call_many(
+        &self,
+        calls: Vec<CallInput>,
+        point_in_time: StoragePointInTime,
+        operation: Option<&Operation>,
+    ) -> Result<Vec<EvmExecution>, StratusError> {
+        Span::with(|s| s.rec_str("calls", &calls.len()));
+        tracing::info!(calls = calls.len(), %point_in_time, "executing read-only local transaction bundle");
+
+        let mut overlay = Arc::new(EvmOverlay::default());
+        let mut executions = Vec::with_capacity(calls.len());
+        for call_input in calls {
+            if let Some(operation) = operation {
+                operation.check()?;
+            }
+            let execution = self.do_execute_local_call(call_input, point_in_time, Some(Arc::clone(&overlay)))?;
+            Arc::make_mut(&mut overlay).apply(&execution);
+            executions.push(execution);
+        }
+        Ok(executions)
+    }
+
+    /// Executes again all transactions of a mined block, in order, against the state before the block without persisting state changes.
+    ///
+    /// Changes of each transaction are kept in an ephemeral overlay, so each one sees the state left by the previous ones.
+    #[tracing::instrument(name = "executor::trace_block", skip_all, fields(block_number))]
+    pub fn trace_block(&self, block: &Block, operation: Option<&Operation>) -> Result<Vec<(Hash, EvmExecution)>, StratusError> {
+        let block_number = block.number();
+        Span::with(|s| s.rec_str("block_number", &block_number));
+        tracing::info!(%block_number, transactions = block.transactions.len(), "tracing mined block");
+
+        // genesis block has no transactions and no previous state
+        let Some(previous_block_number) = block_number.prev() else {
+            return Ok(Vec::new());
+        };
+        let point_in_time = StoragePointInTime::MinedPast(previous_block_number);
+
+        let mut overlay = Arc::new(EvmOverlay::default());
+        let mut traces = Vec::with_capacity(block.transactions.len());
+        for tx in &block.transactions {
+            if let Some(operation) = operation {
+                operation.check()?;
+            }
+            let mut evm_input = EvmInput::builder_from_transaction(&tx.input)
+                .block_gas(&tx.input, block.header.base_fee_per_gas)
+                .block_env(tx.block_number, block.header.timestamp, point_in_time)
+                .build();
+            evm_input.overlay = Some(Arc::clone(&overlay));
+            let execution = self.evms.execute(evm_input, EvmRoute::CallPast)?.execution;
+            Arc::make_mut(&mut overlay).apply(&execution);
+            traces.push((tx.input.hash, execution));
+        }
+        Ok(traces)
+    }
+
+    /// Executes again all transactions of a mined block, in order, recording the calls, contract creations and self-destructs they
+    /// performed.
+    ///
+    /// Transactions are executed in a dedicated EVM because recording calls slows down the execution of every instruction.
+    #[tracing::instrument(name = "executor::trace_block_calls", skip_all, fields(block_number))]
+    pub fn trace_block_calls(&self, block: &Block, operation: Option<&Operation>) -> Result<Vec<(Hash, Vec<CallTrace>)>, StratusError> {
+        let block_number = block.number();
+        Span::with(|s| s.rec_str("block_number", &block_number));
+        tracing::info!(%block_number, transactions = block.transactions.len(), "tracing calls of mined block");
+
+        // genesis block has no transactions and no previous state
+        let Some(previous_block_number) = block_number.prev() else {
+            return Ok(Vec::new());
+        };
+        let point_in_time = StoragePointInTime::MinedPast(previous_block_number);
+
+        let mut evm = Evm::new_call_tracer(Arc::clone(&self.storage), self.config.evm_config());
+        let mut overlay = Arc::new(EvmOverlay::default());
+        let mut traces = Vec::with_capacity(block.transactions.len());
+        for tx in &block.transactions {
+            if let Some(operation) = operation {
+                operation.check()?;
+            }
+            let mut evm_input = EvmInput::builder_from_transaction(&tx.input)
+                .block_gas(&tx.input, block.header.base_fee_per_gas)
+                .block_env(tx.block_number, block.header.timestamp, point_in_time)
+                .build();
+            evm_input.overlay = Some(Arc::clone(&overlay));
+            let execution = evm.execute(evm_input)?.execution;
+            Arc::make_mut(&mut overlay).apply(&execution);
+            traces.push((tx.input.hash, evm.take_call_traces()));
+        }
+        Ok(traces)
+    }
+
+    fn do_execute_local_call(
+        &self,
+        call_input: CallInput,
+        point_in_time: StoragePointInTime,
+        overlay: Option<Arc<EvmOverlay>>,
+    ) -> Result<EvmExecution, StratusError> {
         #[cfg(feature = "metrics")]
         let start = metrics::now();
 
@@ -538,6 +1249,23 @@ impl Executor {
             "executing read-only local transaction"
         );
 
+        // retrieve cached result
+        // calls with an overlay depend on previous calls of the bundle, so they are never cached
+        let state_version = self.storage.read_mined_state_version();
+        let cache_key = match overlay {
+            Some(_) => None,
+            None => CallCacheKey::new(&call_input, point_in_time, self.storage.read_mined_block_number()?),
+        };
+        if let Some(ref cache_key) = cache_key {
+            let cached = self.call_cache.get(cache_key, state_version);
+            #[cfg(feature = "metrics")]
+            metrics::inc_executor_local_call_cache(cached.is_some());
+            if let Some(execution) = cached {
+                tracing::debug!("local call served from cache");
+                return Ok(execution);
+            }
+        }
+
         // retrieve block info
         let pending_block_number = self.storage.read_pending_block_number()?.unwrap_or_default();
         let mined_block = match point_in_time {
@@ -546,7 +1274,14 @@ impl Executor {
         };
 
         // execute
-        let evm_input = EvmInput::from_eth_call(call_input.clone(), point_in_time, pending_block_number, mined_block)?;
+        let evm_input = EvmInput::builder_from_call(call_input.clone());
+        let evm_input = match self.config.executor_call_gas_limit {
+            Some(gas_limit) => evm_input.gas(Gas::from(gas_limit), Wei::ZERO),
+            None => evm_input.unmetered_gas(),
+        };
+        let mut evm_input = evm_input.call_block_env(point_in_time, pending_block_number, mined_block)?.build();
+        evm_input.overlay = overlay;
+        evm_input.execution_timeout = self.config.executor_execution_timeout;
         let evm_route = match point_in_time {
             StoragePointInTime::Mined | StoragePointInTime::Pending => EvmRoute::CallPresent,
             StoragePointInTime::MinedPast(_) => EvmRoute::CallPast,
@@ -571,8 +1306,179 @@ impl Executor {
         }
 
         let execution = evm_result?.execution;
+
+        // cache result only if the mined state did not change during the execution
+        if let Some(cache_key) = cache_key {
+            if self.storage.read_mined_state_version() == state_version {
+                self.call_cache.insert(cache_key, state_version, execution.clone());
+            }
+        }
+
         Ok(execution)
     }
+
+    // -------------------------------------------------------------------------
+    // Impersonation
+    // -------------------------------------------------------------------------
+
+    /// Accounts allowed to send transactions without signature, scoped by client application.
+    #[cfg(feature = "dev")]
+    pub fn impersonation(&self) -> &Impersonation {
+        &self.impersonation
+    }
+
+    /// Checks if an account is allowed to send transactions without signature by any client.
+    #[cfg(feature = "dev")]
+    pub fn is_impersonated(&self, address: &Address) -> bool {
+        self.impersonation.is_impersonated(address)
+    }
+
+    /// Checks if an account is allowed to send transactions without signature.
+    ///
+    /// Impersonation is only available in dev mode.
+    #[cfg(not(feature = "dev"))]
+    pub fn is_impersonated(&self, _: &Address) -> bool {
+        false
+    }
+
+    // -------------------------------------------------------------------------
+    // Configuration
+    // -------------------------------------------------------------------------
+
+    /// Configuration used by all EVM instances.
+    pub fn evm_config(&self) -> EvmConfig {
+        self.config.evm_config()
+    }
+
+    /// Retrieves the miner, failing in archive nodes because they do not have one.
+    fn miner(&self) -> Result<&Arc<Miner>, StratusError> {
+        self.miner.as_ref().ok_or(StratusError::StratusArchiveReadOnly)
+    }
+
+    // -------------------------------------------------------------------------
+    // Transaction pool
+    // -------------------------------------------------------------------------
+
+    /// Number of local transactions waiting to be executed, including the ones parked because of nonce gaps.
+    pub fn queued_transactions(&self) -> usize {
+        self.evms.queued_transactions() + self.nonce_parking.count()
+    }
+
+    /// Number of local transactions discarded because they expired before being executed.
+    pub fn expired_transactions(&self) -> usize {
+        self.expired_transactions.load(Ordering::Relaxed)
+    }
+
+    /// Number of local transactions being executed right now, including the queued and parked ones.
+    pub fn in_flight_transactions(&self) -> usize {
+        self.in_flight_transactions.load(Ordering::Relaxed)
+    }
+
+    /// Outcomes of comparing re-executed external transactions with their receipts in the window ending now.
+    pub fn external_comparison_stats(&self, window: Duration) -> ComparisonStats {
+        self.external_comparisons.stats(window, UnixTime::now())
+    }
+
+    // -------------------------------------------------------------------------
+    // Administration
+    // -------------------------------------------------------------------------
+
+    /// Changes the number of EVMs executing the tasks of a route.
+    pub fn resize_evms(&self, route: EvmRoute, size: usize) -> Result<(), StratusError> {
+        self.evms.resize(route, size)
+    }
+}
+
+impl Executor for EvmExecutor {
+    fn execute_external_block(&self, block: ExternalBlock, receipts: &mut ExternalReceipts) -> Result<(), StratusError> {
+        EvmExecutor::execute_external_block(self, block, receipts)
+    }
+
+    fn execute_local_transaction(&self, tx: TransactionInput, tx_options: TransactionOptions) -> Result<TransactionExecution, StratusError> {
+        EvmExecutor::execute_local_transaction(self, tx, tx_options)
+    }
+
+    fn execute_local_call(&self, call_input: CallInput, point_in_time: StoragePointInTime) -> Result<EvmExecution, StratusError> {
+        EvmExecutor::execute_local_call(self, call_input, point_in_time)
+    }
+
+    fn is_local_call_cached(&self, call_input: &CallInput, point_in_time: StoragePointInTime) -> bool {
+        EvmExecutor::is_local_call_cached(self, call_input, point_in_time)
+    }
+
+    fn create_access_list(&self, call_input: CallInput, point_in_time: StoragePointInTime) -> Result<(AccessList, EvmExecution), StratusError> {
+        EvmExecutor::create_access_list(self, call_input, point_in_time)
+    }
+
+    fn execute_local_call_many(
+        &self,
+        calls: Vec<CallInput>,
+        point_in_time: StoragePointInTime,
+        operation: Option<&Operation>,
+    ) -> Result<Vec<EvmExecution>, StratusError> {
+        EvmExecutor::execute_local_call_many(self, calls, point_in_time, operation)
+    }
+
+    fn trace_block(&self, block: &Block, operation: Option<&Operation>) -> Result<Vec<(Hash, EvmExecution)>, StratusError> {
+        EvmExecutor::trace_block(self, block, operation)
+    }
+
+    fn trace_block_calls(&self, block: &Block, operation: Option<&Operation>) -> Result<Vec<(Hash, Vec<CallTrace>)>, StratusError> {
+        EvmExecutor::trace_block_calls(self, block, operation)
+    }
+
+    fn register_hook(&self, hook: Arc<dyn ExecutionHook>) {
+        EvmExecutor::register_hook(self, hook);
+    }
+
+    #[cfg(feature = "dev")]
+    fn impersonation(&self) -> &Impersonation {
+        EvmExecutor::impersonation(self)
+    }
+
+    fn is_impersonated(&self, address: &Address) -> bool {
+        EvmExecutor::is_impersonated(self, address)
+    }
+
+    fn evm_config(&self) -> EvmConfig {
+        EvmExecutor::evm_config(self)
+    }
+
+    fn queued_transactions(&self) -> usize {
+        EvmExecutor::queued_transactions(self)
+    }
+
+    fn expired_transactions(&self) -> usize {
+        EvmExecutor::expired_transactions(self)
+    }
+
+    fn in_flight_transactions(&self) -> usize {
+        EvmExecutor::in_flight_transactions(self)
+    }
+
+    fn external_comparison_stats(&self, window: Duration) -> ComparisonStats {
+        EvmExecutor::external_comparison_stats(self, window)
+    }
+
+    fn resize_evms(&self, route: EvmRoute, size: usize) -> Result<(), StratusError> {
+        EvmExecutor::resize_evms(self, route, size)
+    }
+}
+
+/// Keeps a local transaction counted as in-flight while alive.
+struct InFlightGuard<'a>(&'a AtomicUsize);
+
+impl<'a> InFlightGuard<'a> {
+    fn enter(counter: &'a AtomicUsize) -> Self {
+        counter.fetch_add(1, Ordering::Relaxed);
+        Self(counter)
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
 }
 
 #[derive(Clone, Copy, serde::Serialize)]
@@ -595,3 +1501,62 @@ impl FromStr for ExecutorStrategy {
         }
     }
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use fake::Fake;
+    use fake::Faker;
+
+    use super::*;
+    use crate::eth::miner::MinerMode;
+    use crate::eth::primitives::Nonce;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::InMemoryTemporaryStorage;
+
+    #[derive(Default)]
+    struct EvictionHook {
+        evicted: Mutex<Vec<Hash>>,
+    }
+
+    impl ExecutionHook for EvictionHook {
+        fn name(&self) -> &str {
+            "eviction"
+        }
+
+        fn on_transaction_evicted(&self, tx: &TransactionInput, _reason: &StratusError) {
+            self.evicted.lock().unwrap().push(tx.hash);
+        }
+    }
+
+    #[tokio::test]
+    async fn executor_notifies_eviction_of_expired_transactions() {
+        let storage = Arc::new(StratusStorage::new(Box::<InMemoryTemporaryStorage>::default(), Box::<InMemoryPermanentStorage>::default()).unwrap());
+        let miner = Arc::new(Miner::new(Arc::clone(&storage), MinerMode::External, Gas::from(1_000_000u64), 3));
+        let hook = Arc::new(EvictionHook::default());
+        miner.hooks.register(Arc::clone(&hook) as Arc<dyn ExecutionHook>);
+
+        let config = ExecutorConfig::parse_from(["stratus", "--executor-chain-id", "2008", "--executor-evms", "1"]);
+        let executor = config.init(Arc::clone(&storage), Some(miner));
+
+        // transaction valid only until a block before the pending block
+        let tx = TransactionInput {
+            chain_id: None,
+            nonce: Nonce::ZERO,
+            signer: Address::new([1; 20]),
+            ..Faker.fake()
+        };
+        let tx_options = TransactionOptions {
+            valid_until_block: Some(BlockNumber::ZERO),
+        };
+
+        let result = executor.execute_local_transaction(tx.clone(), tx_options);
+        assert!(matches!(result, Err(StratusError::TransactionValidUntilBlockExpired { .. })));
+        assert_eq!(executor.expired_transactions(), 1);
+        assert_eq!(*hook.evicted.lock().unwrap(), vec![tx.hash]);
+    }
+}
@@ -1,13 +1,21 @@
 use std::cmp::max;
 use std::sync::Arc;
+use std::time::Duration;
 
 use clap::Parser;
 use display_json::DebugAsJson;
+use revm::primitives::SpecId;
 
+use crate::eth::executor::parse_evm_spec;
+use crate::eth::executor::serialize_evm_spec;
+use crate::eth::executor::EvmConfig;
+use crate::eth::executor::EvmExecutor;
+use crate::eth::executor::EvmQueueStrategy;
 use crate::eth::executor::Executor;
 use crate::eth::executor::ExecutorStrategy;
 use crate::eth::miner::Miner;
 use crate::eth::storage::StratusStorage;
+use crate::ext::parse_duration;
 
 #[derive(Parser, DebugAsJson, Clone, serde::Serialize)]
 pub struct ExecutorConfig {
@@ -15,6 +23,19 @@ pub struct ExecutorConfig {
     #[arg(long = "executor-chain-id", alias = "chain-id", env = "EXECUTOR_CHAIN_ID")]
     pub executor_chain_id: u64,
 
+    /// EVM hardfork rules applied to all executions.
+    #[arg(long = "executor-evm-spec", alias = "hardfork", env = "EXECUTOR_EVM_SPEC", value_parser = parse_evm_spec, default_value = "london")]
+    #[serde(serialize_with = "serialize_evm_spec")]
+    pub executor_evm_spec: SpecId,
+
+    /// Should skip the check that the transaction gas price covers the block base fee?
+    #[arg(long = "executor-disable-gas-price-check", env = "EXECUTOR_DISABLE_GAS_PRICE_CHECK", default_value = "false")]
+    pub executor_disable_gas_price_check: bool,
+
+    /// Maximum size of deployed contracts in bytes. Unlimited if not set.
+    #[arg(long = "executor-contract-size-limit", env = "EXECUTOR_CONTRACT_SIZE_LIMIT")]
+    pub executor_contract_size_limit: Option<usize>,
+
     /// Number of EVM instances to run.
     ///
     /// TODO: should be configured for each kind of EvmRoute instead of being a single value.
@@ -25,6 +46,19 @@ pub struct ExecutorConfig {
     #[arg(long = "executor-strategy", alias = "strategy", env = "EXECUTOR_STRATEGY", default_value = "serial")]
     pub executor_strategy: ExecutorStrategy,
 
+    /// Strategy used to order tasks waiting to be executed by EVMs. Tasks are executed in the order they are received unless `fair` is
+    /// specified.
+    #[arg(long = "executor-queue", env = "EXECUTOR_QUEUE", default_value = "fifo")]
+    pub executor_queue: EvmQueueStrategy,
+
+    /// Number of consecutive tasks a sender can execute before yielding to the next sender when using the fair queue.
+    #[arg(long = "executor-queue-quantum", env = "EXECUTOR_QUEUE_QUANTUM", default_value = "1")]
+    pub executor_queue_quantum: usize,
+
+    /// Max number of tasks waiting in each EVM queue of local transactions and calls before new tasks are rejected. Unbounded if zero.
+    #[arg(long = "executor-queue-capacity", env = "EXECUTOR_QUEUE_CAPACITY", default_value = "10000")]
+    pub executor_queue_capacity: usize,
+
     /// Should reject contract transactions and calls to accounts that are not contracts?
     #[arg(
         long = "executor-reject-not-contract",
@@ -33,18 +67,67 @@ pub struct ExecutorConfig {
         default_value = "true"
     )]
     pub executor_reject_not_contract: bool,
+
+    /// Max time a local transaction can wait to be executed before being discarded.
+    #[arg(long = "executor-tx-ttl", alias = "tx-ttl", value_parser=parse_duration, env = "EXECUTOR_TX_TTL")]
+    pub executor_tx_ttl: Option<Duration>,
+
+    /// Max time a local transaction with a nonce ahead of the sender account nonce waits for the nonce gap to be filled before being rejected.
+    #[arg(long = "executor-nonce-gap-timeout", value_parser=parse_duration, env = "EXECUTOR_NONCE_GAP_TIMEOUT", default_value = "2s")]
+    pub executor_nonce_gap_timeout: Duration,
+
+    /// Max number of times a local transaction is re-executed because of conflicts. Unlimited if not set.
+    #[arg(long = "executor-conflict-max-retries", env = "EXECUTOR_CONFLICT_MAX_RETRIES")]
+    pub executor_conflict_max_retries: Option<usize>,
+
+    /// Max total time a local transaction can spend being re-executed because of conflicts. Unlimited if not set.
+    #[arg(long = "executor-conflict-timeout", value_parser=parse_duration, env = "EXECUTOR_CONFLICT_TIMEOUT")]
+    pub executor_conflict_timeout: Option<Duration>,
+
+    /// Max total time a local transaction can spend reading from the storage, across all its attempts, before being aborted. Unlimited if not set.
+    #[arg(long = "executor-storage-latency-budget", value_parser=parse_duration, env = "EXECUTOR_STORAGE_LATENCY_BUDGET")]
+    pub executor_storage_latency_budget: Option<Duration>,
+
+    /// Max time a local transaction or call can run in the EVM before being cancelled. Unlimited if not set.
+    #[arg(long = "executor-execution-timeout", value_parser=parse_duration, env = "EXECUTOR_EXECUTION_TIMEOUT")]
+    pub executor_execution_timeout: Option<Duration>,
+
+    /// Max gas consumed by calls received with `eth_call`, `eth_estimateGas` and similar methods. Limited only by the transaction gas
+    /// limit if not set.
+    #[arg(long = "executor-call-gas-limit", env = "EXECUTOR_CALL_GAS_LIMIT")]
+    pub executor_call_gas_limit: Option<u64>,
+
+    /// Max number of results of calls against mined state kept in cache. Cache is disabled if zero.
+    #[arg(long = "executor-call-cache-size", env = "EXECUTOR_CALL_CACHE_SIZE", default_value = "1000")]
+    pub executor_call_cache_size: usize,
 }
 
 impl ExecutorConfig {
-    /// Initializes Executor.
+    /// Initializes the executor implementation.
+    ///
+    /// Archive nodes do not have a miner, so their executor executes only calls and traces.
     ///
     /// Note: Should be called only after async runtime is initialized.
-    pub fn init(&self, storage: Arc<StratusStorage>, miner: Arc<Miner>) -> Arc<Executor> {
+    pub fn init(&self, storage: Arc<StratusStorage>, miner: Option<Arc<Miner>>) -> Arc<dyn Executor> {
         let mut config = self.clone();
         config.executor_evms = max(config.executor_evms, 1);
         tracing::info!(?config, "creating executor");
 
-        let executor = Executor::new(storage, miner, config);
-        Arc::new(executor)
+        let executor: Arc<dyn Executor> = Arc::new(EvmExecutor::new(storage, miner.clone(), config));
+        if let Some(miner) = miner {
+            miner.set_reexecutor(&executor);
+        }
+        executor
+    }
+
+    /// Configuration passed to each EVM instance.
+    pub fn evm_config(&self) -> EvmConfig {
+        EvmConfig {
+            chain_id: self.executor_chain_id.into(),
+            spec: self.executor_evm_spec,
+            disable_gas_price_check: self.executor_disable_gas_price_check,
+            contract_size_limit: self.executor_contract_size_limit,
+            reject_not_contract: self.executor_reject_not_contract,
+        }
     }
 }
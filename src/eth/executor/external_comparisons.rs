@@ -0,0 +1,243 @@
+//! Outcomes of comparing re-executed external transactions with their receipts.
+//!
+//! Followers and importers execute external transactions again and compare the results with the receipts of the leader or of the
+//! external chain. Outcomes are counted in one-minute buckets and the most recent mismatches are kept with their reasons, so divergences
+//! can be queried instead of searched in the logs. Only the most recent buckets are kept.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use display_json::DebugAsJson;
+
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::ComparisonOutcome;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::UnixTime;
+use crate::ext::MutexExt;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
+
+/// Max window that can be queried, which is also the number of one-minute buckets kept.
+pub const EXTERNAL_COMPARISONS_MAX_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Number of recent mismatches kept.
+const RECENT_MISMATCHES: usize = 100;
+
+const SECONDS_PER_MINUTE: u64 = 60;
+
+/// Number of comparisons by outcome.
+#[derive(DebugAsJson, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparisonCounters {
+    pub matched: u64,
+    pub gas_mismatch: u64,
+    pub logs_mismatch: u64,
+    pub status_mismatch: u64,
+}
+
+impl ComparisonCounters {
+    fn inc(&mut self, outcome: ComparisonOutcome) {
+        match outcome {
+            ComparisonOutcome::Matched => self.matched += 1,
+            ComparisonOutcome::GasMismatch => self.gas_mismatch += 1,
+            ComparisonOutcome::LogsMismatch => self.logs_mismatch += 1,
+            ComparisonOutcome::StatusMismatch => self.status_mismatch += 1,
+        }
+    }
+
+    fn add(&mut self, other: &ComparisonCounters) {
+        self.matched += other.matched;
+        self.gas_mismatch += other.gas_mismatch;
+        self.logs_mismatch += other.logs_mismatch;
+        self.status_mismatch += other.status_mismatch;
+    }
+}
+
+/// Re-executed transaction that did not match its receipt.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparisonMismatch {
+    pub block_number: BlockNumber,
+    pub tx_hash: Hash,
+    pub outcome: ComparisonOutcome,
+    pub reason: String,
+    pub timestamp: UnixTime,
+}
+
+/// Comparison outcomes in a window, totalized and by minute.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparisonStats {
+    pub window_seconds: u64,
+    pub totals: ComparisonCounters,
+
+    /// Counters of each minute with comparisons, from the oldest to the newest.
+    pub minutes: Vec<ComparisonMinute>,
+
+    /// Most recent mismatches in the window, from the newest to the oldest.
+    pub recent_mismatches: Vec<ComparisonMismatch>,
+}
+
+#[derive(DebugAsJson, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparisonMinute {
+    pub timestamp: UnixTime,
+
+    #[serde(flatten)]
+    pub counters: ComparisonCounters,
+}
+
+#[derive(Debug)]
+struct ComparisonBucket {
+    /// Minutes since the Unix epoch.
+    minute: u64,
+    counters: ComparisonCounters,
+}
+
+#[derive(Debug, Default)]
+struct ComparisonHistory {
+    /// Buckets from the oldest to the newest minute.
+    buckets: VecDeque<ComparisonBucket>,
+
+    /// Mismatches from the oldest to the newest.
+    mismatches: VecDeque<ComparisonMismatch>,
+}
+
+/// Outcomes of recently re-executed external transactions.
+#[derive(Debug, Default)]
+pub struct ExternalComparisons {
+    history: Mutex<ComparisonHistory>,
+}
+
+impl ExternalComparisons {
+    /// Records the outcome of a transaction compared at `now`. Mismatches must have a reason.
+    pub fn record(&self, block_number: BlockNumber, tx_hash: Hash, outcome: ComparisonOutcome, reason: Option<String>, now: UnixTime) {
+        #[cfg(feature = "metrics")]
+        metrics::inc_executor_external_comparisons(<&'static str>::from(outcome));
+
+        let minute = now.as_u64() / SECONDS_PER_MINUTE;
+        let mut history = self.history.lock_or_clear("external comparisons lock was poisoned");
+        if history.buckets.back().map(|bucket| bucket.minute) != Some(minute) {
+            history.buckets.push_back(ComparisonBucket {
+                minute,
+                counters: ComparisonCounters::default(),
+            });
+        }
+        let oldest_minute = minute.saturating_sub(max_window_minutes() - 1);
+        while history.buckets.front().is_some_and(|bucket| bucket.minute < oldest_minute) {
+            history.buckets.pop_front();
+        }
+        history.buckets.back_mut().expect("bucket was just pushed").counters.inc(outcome);
+
+        if outcome.is_mismatch() {
+            if history.mismatches.len() == RECENT_MISMATCHES {
+                history.mismatches.pop_front();
+            }
+            history.mismatches.push_back(ComparisonMismatch {
+                block_number,
+                tx_hash,
+                outcome,
+                reason: reason.unwrap_or_default(),
+                timestamp: now,
+            });
+        }
+    }
+
+    /// Totalizes the outcomes in the window ending at `now`.
+    pub fn stats(&self, window: Duration, now: UnixTime) -> ComparisonStats {
+        let window_minutes = window.as_secs().div_ceil(SECONDS_PER_MINUTE).clamp(1, max_window_minutes());
+        let oldest_minute = (now.as_u64() / SECONDS_PER_MINUTE).saturating_sub(window_minutes - 1);
+
+        let history = self.history.lock_or_clear("external comparisons lock was poisoned");
+        let mut totals = ComparisonCounters::default();
+        let mut minutes = Vec::new();
+        for bucket in history.buckets.iter().filter(|bucket| bucket.minute >= oldest_minute) {
+            totals.add(&bucket.counters);
+            minutes.push(ComparisonMinute {
+                timestamp: UnixTime::from(bucket.minute * SECONDS_PER_MINUTE),
+                counters: bucket.counters,
+            });
+        }
+
+        let recent_mismatches = history
+            .mismatches
+            .iter()
+            .rev()
+            .take_while(|mismatch| mismatch.timestamp.as_u64() / SECONDS_PER_MINUTE >= oldest_minute)
+            .cloned()
+            .collect();
+
+        ComparisonStats {
+            window_seconds: window_minutes * SECONDS_PER_MINUTE,
+            totals,
+            minutes,
+            recent_mismatches,
+        }
+    }
+}
+
+fn max_window_minutes() -> u64 {
+    EXTERNAL_COMPARISONS_MAX_WINDOW.as_secs() / SECONDS_PER_MINUTE
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::fake_first;
+
+    #[test]
+    fn external_comparisons_count_outcomes_in_window() {
+        let comparisons = ExternalComparisons::default();
+        let hash = fake_first::<Hash>();
+        let t0 = 1_000 * SECONDS_PER_MINUTE;
+
+        comparisons.record(BlockNumber::ONE, hash, ComparisonOutcome::Matched, None, UnixTime::from(t0));
+        comparisons.record(BlockNumber::ONE, hash, ComparisonOutcome::GasMismatch, Some("gas".into()), UnixTime::from(t0));
+        comparisons.record(
+            BlockNumber::from(2u64),
+            hash,
+            ComparisonOutcome::LogsMismatch,
+            Some("logs".into()),
+            UnixTime::from(t0 + 5 * SECONDS_PER_MINUTE),
+        );
+
+        // last minute only sees the last comparison
+        let stats = comparisons.stats(Duration::from_secs(60), UnixTime::from(t0 + 5 * SECONDS_PER_MINUTE));
+        assert_eq!(stats.totals.logs_mismatch, 1);
+        assert_eq!(stats.totals.matched, 0);
+        assert_eq!(stats.recent_mismatches.len(), 1);
+        assert_eq!(stats.recent_mismatches[0].reason, "logs");
+
+        // wider window sees all comparisons
+        let stats = comparisons.stats(Duration::from_secs(10 * 60), UnixTime::from(t0 + 5 * SECONDS_PER_MINUTE));
+        assert_eq!(
+            stats.totals,
+            ComparisonCounters {
+                matched: 1,
+                gas_mismatch: 1,
+                logs_mismatch: 1,
+                status_mismatch: 0
+            }
+        );
+        assert_eq!(stats.minutes.len(), 2);
+        assert_eq!(
+            stats.recent_mismatches.iter().map(|mismatch| mismatch.outcome).collect::<Vec<_>>(),
+            vec![ComparisonOutcome::LogsMismatch, ComparisonOutcome::GasMismatch]
+        );
+
+        // buckets older than the max window are dropped
+        comparisons.record(
+            BlockNumber::from(3u64),
+            hash,
+            ComparisonOutcome::Matched,
+            None,
+            UnixTime::from(t0 + 2 * max_window_minutes() * SECONDS_PER_MINUTE),
+        );
+        assert_eq!(comparisons.history.lock_or_clear("test").buckets.len(), 1);
+    }
+}
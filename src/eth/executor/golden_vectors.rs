@@ -0,0 +1,301 @@
+//! Golden vectors asserting exact gas usage and state changes of a curated set of transactions.
+//!
+//! The expected values were produced by executing the same transactions in a plain `revm` instance configured with the same hardfork
+//! and block environment. A failure here means that a change in the EVM or in the storage silently altered execution results, so the
+//! expected values must only be updated when the change in behavior is intentional.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use ethereum_types::U256;
+use ethers_core::utils::get_create2_address;
+use hex_literal::hex;
+use revm::primitives::SpecId;
+
+use crate::eth::executor::Evm;
+use crate::eth::executor::EvmConfig;
+use crate::eth::executor::EvmInput;
+use crate::eth::executor::EvmOverlay;
+use crate::eth::primitives::Account;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::EvmExecution;
+use crate::eth::primitives::Gas;
+use crate::eth::primitives::LogTopic;
+use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::SlotValue;
+use crate::eth::primitives::UnixTime;
+use crate::eth::primitives::Wei;
+use crate::eth::storage::InMemoryPermanentStorage;
+use crate::eth::storage::InMemoryTemporaryStorage;
+use crate::eth::storage::StoragePointInTime;
+use crate::eth::storage::StratusStorage;
+
+// -----------------------------------------------------------------------------
+// Contracts
+// -----------------------------------------------------------------------------
+
+/// ERC-20 style token with `transfer(address,uint256)` and `balanceOf(address)`.
+///
+/// Balances are stored in a mapping at slot 0 and the constructor mints the supply appended to the init code to the deployer.
+const TOKEN_INIT_CODE: &[u8] = &hex!(
+    "60206100d26000396000513360005260006020526040600020556100ab806100276000396000f360003560e01c8063a9059cbb1461003a57806370a0"
+    "82311461002057600080fd5b600435600052600060205260406000205460005260206000f35b33600052600060205260406000208054602435808210"
+    "6100a6579003905560043560005260406000208054602435019055602435600052600435337fddf252ad1be2c89b69c2b068fc378daa952ba7f163c4"
+    "a11628f55a4df523b3ef60206000a3600160005260206000f35b600080fd"
+);
+
+/// Uniswap-style constant product pair between the native token and a token with `swap()`.
+///
+/// Slot 0 is the native reserve, slot 1 is the token reserve and slot 2 is the token address. The constructor takes the token address
+/// and the token reserve appended to the init code, and the native reserve from the transferred value. Swaps charge a 0.3% fee.
+const PAIR_INIT_CODE: &[u8] = &hex!(
+    "60406100d1600039600051600255602051600155346000556100ac806100256000396000f360003560e01c638119c0651461001457600080fd5b3480"
+    "156100a757806103e50280600154026000546103e80282019004905080156100a7578160005401600055806001540360015563a9059cbb60e01b6000"
+    "523360045280602452602060006044600060006002545af1156100a7578160005280602052337f77f92a1b6a1a11de8ca49515ad4c1fad45632dd344"
+    "2167d74b90b304a3c7a75860406000a260005260206000f35b600080fd"
+);
+
+/// Factory with `deploy(bytes32)` that deploys [`CHILD_INIT_CODE`] with `CREATE2` and emits `Deployed(address)`.
+const FACTORY_INIT_CODE: &[u8] = &hex!(
+    "61007f8061000d6000396000f360003560e01c632b85ba381461001457600080fd5b61001d806100626000396004359060006000f5801561005d5780"
+    "7ff40fcec21964ffb566044d083b4073f29f7f7929110ea19e1b3ebe375d89055e60006000a260005260206000f35b600080fd602a60005561000b80"
+    "6100126000396000f360005460005260206000f3"
+);
+
+/// Contract that stores `42` at slot 0 when deployed and returns it when called.
+const CHILD_INIT_CODE: &[u8] = &hex!("602a60005561000b806100126000396000f360005460005260206000f3");
+
+const TRANSFER_SELECTOR: [u8; 4] = hex!("a9059cbb");
+const SWAP_SELECTOR: [u8; 4] = hex!("8119c065");
+const DEPLOY_SELECTOR: [u8; 4] = hex!("2b85ba38");
+
+const TRANSFER_TOPIC: [u8; 32] = hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+const SWAP_TOPIC: [u8; 32] = hex!("77f92a1b6a1a11de8ca49515ad4c1fad45632dd3442167d74b90b304a3c7a758");
+const DEPLOYED_TOPIC: [u8; 32] = hex!("f40fcec21964ffb566044d083b4073f29f7f7929110ea19e1b3ebe375d89055e");
+
+const ALICE: Address = Address::new(hex!("f39fd6e51aad88f6f4ce6ab8827279cfffb92266"));
+const BOB: Address = Address::new(hex!("70997970c51812dc3a010c7d01b50e0d17dc79c8"));
+
+// -----------------------------------------------------------------------------
+// Harness
+// -----------------------------------------------------------------------------
+
+/// Executes transactions in sequence, carrying state between them without committing anything to the storage.
+struct GoldenChain {
+    evm: Evm,
+    overlay: EvmOverlay,
+    nonces: HashMap<Address, u64>,
+}
+
+impl GoldenChain {
+    fn new() -> Self {
+        let storage = StratusStorage::new(Box::<InMemoryTemporaryStorage>::default(), Box::<InMemoryPermanentStorage>::default()).unwrap();
+        storage
+            .save_accounts(vec![Account::new_with_balance(ALICE, Wei::from(U256::exp10(20)))])
+            .unwrap();
+
+        let config = EvmConfig {
+            chain_id: 2008u64.into(),
+            spec: SpecId::LONDON,
+            disable_gas_price_check: false,
+            contract_size_limit: None,
+            reject_not_contract: true,
+        };
+        Self {
+            evm: Evm::new(Arc::new(storage), config),
+            overlay: EvmOverlay::default(),
+            nonces: HashMap::new(),
+        }
+    }
+
+    fn execute(&mut self, from: Address, to: Option<Address>, value: U256, data: Vec<u8>) -> EvmExecution {
+        let nonce = self.nonces.entry(from).or_default();
+        let mut input = EvmInput::builder(from, to, value.into(), data.into())
+            .nonce((*nonce).into())
+            .unmetered_gas()
+            .block_env(1u64.into(), UnixTime::from(1702568764u64), StoragePointInTime::Pending)
+            .build();
+        input.overlay = Some(Arc::new(self.overlay.clone()));
+        *nonce += 1;
+
+        let execution = self.evm.execute(input).unwrap().execution;
+        self.overlay.apply(&execution);
+        execution
+    }
+
+    fn slot(&self, address: Address, index: SlotIndex) -> SlotValue {
+        self.overlay.read_slot(&address, &index).map(|slot| slot.value).unwrap_or_default()
+    }
+
+    fn deploy_token(&mut self, supply: U256) -> Address {
+        let execution = self.execute(ALICE, None, U256::zero(), [TOKEN_INIT_CODE, &word(supply)].concat());
+        execution.deployed_contract_address.unwrap()
+    }
+
+    fn transfer(&mut self, token: Address, from: Address, to: Address, amount: U256) -> EvmExecution {
+        self.execute(
+            from,
+            Some(token),
+            U256::zero(),
+            [&TRANSFER_SELECTOR[..], &address_word(to), &word(amount)].concat(),
+        )
+    }
+}
+
+fn word(value: U256) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    bytes
+}
+
+fn address_word(address: Address) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes[12..].copy_from_slice(address.0.as_bytes());
+    bytes
+}
+
+fn balance_index(address: Address) -> SlotIndex {
+    SlotIndex::ZERO.to_mapping_index(address.0.as_bytes().to_vec())
+}
+
+fn ether(value: u64) -> U256 {
+    U256::from(value) * U256::exp10(18)
+}
+
+fn dec(value: &str) -> SlotValue {
+    U256::from_dec_str(value).unwrap().into()
+}
+
+// -----------------------------------------------------------------------------
+// Vectors
+// -----------------------------------------------------------------------------
+
+#[test]
+fn golden_erc20_transfer() {
+    let mut chain = GoldenChain::new();
+
+    // deploy
+    let token = chain.deploy_token(ether(1_000_000));
+    assert_eq!(token, Address::from_str("0x5fbdb2315678afecb367f032d93f642f64180aa3").unwrap());
+
+    // first transfer to bob writes a zero slot
+    let execution = chain.transfer(token, ALICE, BOB, ether(1_000));
+    assert!(execution.is_success());
+    assert_eq!(execution.gas, Gas::from(50_765u64));
+    assert_eq!(execution.output, Bytes::from(word(U256::one())));
+    assert_eq!(execution.logs.len(), 1);
+    assert_eq!(execution.logs[0].topic0, Some(LogTopic::from(TRANSFER_TOPIC)));
+    assert_eq!(execution.logs[0].data, Bytes::from(word(ether(1_000))));
+    assert_eq!(chain.slot(token, balance_index(ALICE)), dec("999000000000000000000000"));
+    assert_eq!(chain.slot(token, balance_index(BOB)), dec("1000000000000000000000"));
+
+    // transfer above balance reverts
+    let execution = chain.transfer(token, BOB, ALICE, ether(2_000));
+    assert!(execution.is_failure());
+    assert_eq!(execution.gas, Gas::from(23_888u64));
+    assert!(execution.logs.is_empty());
+    assert_eq!(chain.slot(token, balance_index(BOB)), dec("1000000000000000000000"));
+
+    // second transfer to bob only updates non-zero slots
+    let execution = chain.transfer(token, ALICE, BOB, ether(1_000));
+    assert!(execution.is_success());
+    assert_eq!(execution.gas, Gas::from(33_665u64));
+    assert_eq!(chain.slot(token, balance_index(ALICE)), dec("998000000000000000000000"));
+    assert_eq!(chain.slot(token, balance_index(BOB)), dec("2000000000000000000000"));
+}
+
+#[test]
+fn golden_deploy_gas() {
+    let mut chain = GoldenChain::new();
+
+    let execution = chain.execute(ALICE, None, U256::zero(), [TOKEN_INIT_CODE, &word(ether(1_000_000))].concat());
+    assert!(execution.is_success());
+    assert_eq!(execution.gas, Gas::from(112_652u64));
+
+    let token = execution.deployed_contract_address.unwrap();
+    let execution = chain.execute(
+        ALICE,
+        None,
+        U256::exp10(19),
+        [PAIR_INIT_CODE, &address_word(token), &word(ether(500_000))].concat(),
+    );
+    assert!(execution.is_success());
+    assert_eq!(execution.gas, Gas::from(157_419u64));
+
+    let execution = chain.execute(ALICE, None, U256::zero(), FACTORY_INIT_CODE.to_vec());
+    assert!(execution.is_success());
+    assert_eq!(execution.gas, Gas::from(80_370u64));
+}
+
+#[test]
+fn golden_uniswap_style_swap() {
+    let mut chain = GoldenChain::new();
+
+    // setup
+    let token = chain.deploy_token(ether(1_000_000));
+    let execution = chain.execute(
+        ALICE,
+        None,
+        U256::exp10(19),
+        [PAIR_INIT_CODE, &address_word(token), &word(ether(500_000))].concat(),
+    );
+    let pair = execution.deployed_contract_address.unwrap();
+    assert_eq!(pair, Address::from_str("0xe7f1725e7734ce288f8367e1bb143e90bb3f0512").unwrap());
+    let execution = chain.transfer(token, ALICE, pair, ether(500_000));
+    assert_eq!(execution.gas, Gas::from(50_777u64));
+
+    // swap 1 native for tokens
+    let execution = chain.execute(ALICE, Some(pair), U256::exp10(18), SWAP_SELECTOR.to_vec());
+    assert!(execution.is_success());
+    assert_eq!(execution.gas, Gas::from(49_888u64));
+    assert_eq!(execution.output, Bytes::from(word(U256::from_dec_str("45330544694007456579067").unwrap())));
+    assert_eq!(execution.logs.len(), 2);
+    assert_eq!(execution.logs[0].address, token);
+    assert_eq!(execution.logs[0].topic0, Some(LogTopic::from(TRANSFER_TOPIC)));
+    assert_eq!(execution.logs[1].address, pair);
+    assert_eq!(execution.logs[1].topic0, Some(LogTopic::from(SWAP_TOPIC)));
+
+    // reserves and balances
+    assert_eq!(chain.slot(pair, SlotIndex::ZERO), dec("11000000000000000000"));
+    assert_eq!(chain.slot(pair, SlotIndex::ONE), dec("454669455305992543420933"));
+    assert_eq!(chain.slot(token, balance_index(ALICE)), dec("545330544694007456579067"));
+    assert_eq!(chain.slot(token, balance_index(pair)), dec("454669455305992543420933"));
+    assert_eq!(
+        chain.overlay.read_account(&pair).unwrap().balance,
+        Wei::from(U256::from_dec_str("11000000000000000000").unwrap())
+    );
+
+    // swap without value reverts
+    let execution = chain.execute(ALICE, Some(pair), U256::zero(), SWAP_SELECTOR.to_vec());
+    assert!(execution.is_failure());
+    assert_eq!(execution.gas, Gas::from(21_124u64));
+}
+
+#[test]
+fn golden_create2_factory() {
+    let mut chain = GoldenChain::new();
+
+    // deploy factory
+    let execution = chain.execute(ALICE, None, U256::zero(), FACTORY_INIT_CODE.to_vec());
+    let factory = execution.deployed_contract_address.unwrap();
+    assert_eq!(factory, Address::from_str("0x5fbdb2315678afecb367f032d93f642f64180aa3").unwrap());
+
+    // deploy child with salt 1
+    let salt = word(U256::one());
+    let execution = chain.execute(ALICE, Some(factory), U256::zero(), [&DEPLOY_SELECTOR[..], &salt].concat());
+    assert!(execution.is_success());
+    assert_eq!(execution.gas, Gas::from(78_776u64));
+
+    // child address follows CREATE2 rules
+    let expected_child: Address = get_create2_address(factory.0, salt, CHILD_INIT_CODE).into();
+    assert_eq!(expected_child, Address::from_str("0x1956f33cc4bf0736d6cce5d056a8c75df23616b0").unwrap());
+    assert_eq!(execution.output, Bytes::from(address_word(expected_child)));
+    assert_eq!(execution.logs.len(), 1);
+    assert_eq!(execution.logs[0].topic0, Some(LogTopic::from(DEPLOYED_TOPIC)));
+
+    // child was initialized
+    assert!(chain.overlay.read_account(&expected_child).unwrap().bytecode.is_some());
+    assert_eq!(chain.slot(expected_child, SlotIndex::ZERO), SlotValue::from(42u64));
+}
@@ -0,0 +1,193 @@
+//! Impersonation of accounts in dev mode.
+//!
+//! Accounts are impersonated on behalf of the client application that requested it, so clients sharing a dev or staging node can only
+//! send unsigned transactions from the accounts they impersonated themselves. Every change of impersonation and every impersonated
+//! transaction is recorded in an audit log.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::sync::RwLock;
+
+use display_json::DebugAsJson;
+
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::UnixTime;
+use crate::ext::MutexExt;
+
+/// Max number of audit entries kept.
+const MAX_AUDIT_ENTRIES: usize = 1024;
+
+/// Action recorded in the impersonation audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImpersonationAction {
+    /// Client started impersonating an account.
+    #[strum(to_string = "start")]
+    Start,
+
+    /// Client stopped impersonating an account.
+    #[strum(to_string = "stop")]
+    Stop,
+
+    /// Client sent a transaction from an impersonated account.
+    #[strum(to_string = "transaction")]
+    Transaction,
+}
+
+#[derive(DebugAsJson, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpersonationAuditEntry {
+    pub action: ImpersonationAction,
+
+    /// Impersonated account.
+    pub address: Address,
+
+    /// Client application that performed the action.
+    pub client: String,
+
+    /// Hash of the impersonated transaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<Hash>,
+
+    /// Indicates if the impersonated transaction was executed successfully.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+
+    pub at: UnixTime,
+}
+
+/// Accounts impersonated by each client application and the audit log of impersonations.
+#[derive(Debug, Default)]
+pub struct Impersonation {
+    /// Impersonated accounts and the clients that impersonated them.
+    accounts: RwLock<HashMap<Address, HashSet<String>>>,
+
+    /// Recent impersonation actions from the oldest to the newest.
+    audit: Mutex<VecDeque<ImpersonationAuditEntry>>,
+}
+
+impl Impersonation {
+    /// Allows an account to send transactions without signature on behalf of the client.
+    ///
+    /// Returns `false` if the account was already impersonated by the client.
+    pub fn start(&self, address: Address, client: &str) -> bool {
+        tracing::info!(%address, %client, "impersonating account");
+        let started = self.write_accounts().entry(address).or_default().insert(client.to_owned());
+        self.record(ImpersonationAction::Start, address, client, None, None);
+        started
+    }
+
+    /// Stops allowing an account to send transactions without signature on behalf of the client.
+    ///
+    /// Returns `false` if the account was not impersonated by the client.
+    pub fn stop(&self, address: &Address, client: &str) -> bool {
+        tracing::info!(%address, %client, "stopping account impersonation");
+        let mut accounts = self.write_accounts();
+        let Some(clients) = accounts.get_mut(address) else {
+            return false;
+        };
+        let stopped = clients.remove(client);
+        if clients.is_empty() {
+            accounts.remove(address);
+        }
+        drop(accounts);
+
+        if stopped {
+            self.record(ImpersonationAction::Stop, *address, client, None, None);
+        }
+        stopped
+    }
+
+    /// Checks if an account is impersonated by any client.
+    pub fn is_impersonated(&self, address: &Address) -> bool {
+        self.read_accounts().contains_key(address)
+    }
+
+    /// Checks if an account is impersonated by the client.
+    pub fn is_impersonated_by(&self, address: &Address, client: &str) -> bool {
+        self.read_accounts().get(address).is_some_and(|clients| clients.contains(client))
+    }
+
+    /// Records a transaction sent from an impersonated account.
+    pub fn record_transaction(&self, address: Address, client: &str, tx_hash: Hash, success: bool) {
+        tracing::info!(%address, %client, %tx_hash, %success, "recording impersonated transaction");
+        self.record(ImpersonationAction::Transaction, address, client, Some(tx_hash), Some(success));
+    }
+
+    /// Lists recorded actions from the oldest to the newest.
+    pub fn audit(&self) -> Vec<ImpersonationAuditEntry> {
+        self.audit.lock_or_clear("impersonation audit lock was poisoned").iter().cloned().collect()
+    }
+
+    fn record(&self, action: ImpersonationAction, address: Address, client: &str, tx_hash: Option<Hash>, success: Option<bool>) {
+        let mut audit = self.audit.lock_or_clear("impersonation audit lock was poisoned");
+        if audit.len() >= MAX_AUDIT_ENTRIES {
+            audit.pop_front();
+        }
+        audit.push_back(ImpersonationAuditEntry {
+            action,
+            address,
+            client: client.to_owned(),
+            tx_hash,
+            success,
+            at: UnixTime::now(),
+        });
+    }
+
+    fn read_accounts(&self) -> std::sync::RwLockReadGuard<'_, HashMap<Address, HashSet<String>>> {
+        self.accounts.read().unwrap_or_else(|poison_err| poison_err.into_inner())
+    }
+
+    fn write_accounts(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<Address, HashSet<String>>> {
+        self.accounts.write().unwrap_or_else(|poison_err| {
+            tracing::error!("impersonated accounts lock was poisoned");
+            self.accounts.clear_poison();
+            poison_err.into_inner()
+        })
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn impersonation_is_scoped_by_client() {
+        let impersonation = Impersonation::default();
+        let address = Address::new([1; 20]);
+
+        assert!(impersonation.start(address, "alice"));
+        assert!(impersonation.is_impersonated_by(&address, "alice"));
+        assert!(!impersonation.is_impersonated_by(&address, "bob"));
+
+        // other clients cannot stop the impersonation
+        assert!(!impersonation.stop(&address, "bob"));
+        assert!(impersonation.is_impersonated(&address));
+
+        assert!(impersonation.stop(&address, "alice"));
+        assert!(!impersonation.is_impersonated(&address));
+    }
+
+    #[test]
+    fn impersonation_actions_are_audited() {
+        let impersonation = Impersonation::default();
+        let address = Address::new([1; 20]);
+
+        impersonation.start(address, "alice");
+        impersonation.record_transaction(address, "alice", Hash::new([2; 32]), true);
+        impersonation.stop(&address, "alice");
+
+        let actions = impersonation.audit().into_iter().map(|entry| entry.action).collect::<Vec<_>>();
+        assert_eq!(
+            actions,
+            vec![ImpersonationAction::Start, ImpersonationAction::Transaction, ImpersonationAction::Stop]
+        );
+    }
+}
@@ -1,13 +1,64 @@
+mod call_cache;
+mod call_tracer;
+mod determinism;
 mod evm;
+mod evm_config;
 mod evm_input;
+mod evm_overlay;
+mod evm_queue;
 mod evm_result;
+mod execution_hooks;
 #[allow(clippy::module_inception)]
 mod executor;
 mod executor_config;
+mod external_comparisons;
+#[cfg(test)]
+mod golden_vectors;
+#[cfg(feature = "dev")]
+mod impersonation;
+mod nonce_parking;
+mod transaction_replay;
 
+pub use call_cache::CallCache;
+pub use call_cache::CallCacheKey;
+pub use call_tracer::CallTracer;
+pub use determinism::assert_no_wall_clock_read;
+pub use determinism::order_independent;
+pub use determinism::ExecutionScope;
+pub use determinism::DETERMINISM_GUARDS_ENABLED;
 pub use evm::Evm;
+pub use evm_config::evm_spec_name;
+pub use evm_config::parse_evm_spec;
+pub use evm_config::serialize_evm_spec;
+pub use evm_config::EvmConfig;
 pub use evm_input::EvmInput;
+pub use evm_input::EvmInputBuilder;
+pub use evm_overlay::EvmOverlay;
+pub use evm_queue::EvmQueue;
+pub use evm_queue::EvmQueueRejection;
+pub use evm_queue::EvmQueueStrategy;
 pub use evm_result::EvmExecutionResult;
+pub use execution_hooks::ExecutionHook;
+pub use execution_hooks::ExecutionHooks;
+pub use executor::EvmExecutor;
+pub use executor::EvmRoute;
 pub use executor::Executor;
 pub use executor::ExecutorStrategy;
 pub use executor_config::ExecutorConfig;
+pub use external_comparisons::ComparisonCounters;
+pub use external_comparisons::ComparisonMinute;
+pub use external_comparisons::ComparisonMismatch;
+pub use external_comparisons::ComparisonStats;
+pub use external_comparisons::ExternalComparisons;
+pub use external_comparisons::EXTERNAL_COMPARISONS_MAX_WINDOW;
+#[cfg(feature = "dev")]
+pub use impersonation::Impersonation;
+#[cfg(feature = "dev")]
+pub use impersonation::ImpersonationAction;
+#[cfg(feature = "dev")]
+pub use impersonation::ImpersonationAuditEntry;
+pub use nonce_parking::NonceParking;
+pub use transaction_replay::diff_executions;
+pub use transaction_replay::replay_transaction;
+pub use transaction_replay::ReplayDifference;
+pub use transaction_replay::TransactionReplay;
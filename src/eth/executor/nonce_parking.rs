@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use ethereum_types::U256;
+
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::Nonce;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TransactionInput;
+use crate::eth::primitives::Wei;
+use crate::ext::MutexExt;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
+
+/// Max time a parked transaction waits before checking the sender nonce again without being notified.
+///
+/// Nonces can also change without local executions (mined external blocks, dev cheatcodes), so parked transactions cannot rely only on
+/// notifications.
+const RECHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Minimum gas price increase, in percent, for a transaction to replace a parked transaction with the same sender and nonce.
+const REPLACEMENT_PRICE_BUMP_PERCENT: u64 = 10;
+
+/// Place where local transactions with nonces ahead of the sender account nonce wait until the nonce gap is filled.
+#[derive(Default)]
+pub struct NonceParking {
+    /// Number of transactions parked right now.
+    parked: AtomicUsize,
+
+    /// Parked transactions by sender and nonce, used to detect duplicates and replacements.
+    ///
+    /// Also used to wait for notifications.
+    transactions: Mutex<HashMap<(Address, Nonce), ParkedTransaction>>,
+
+    /// Notified every time a local transaction is executed, so parked transactions can check the sender nonce again.
+    executed: Condvar,
+}
+
+impl NonceParking {
+    /// Parks a transaction until the returned guard is dropped.
+    ///
+    /// Fails if the same transaction is already parked, or if it replaces a parked transaction with the same sender and nonce without
+    /// paying enough gas price. Replacements paying enough are parked alongside the original transaction, and the first one executed
+    /// consumes the nonce.
+    pub fn enter(&self, tx: &TransactionInput) -> Result<NonceParkingGuard<'_>, StratusError> {
+        let key = (tx.signer, tx.nonce);
+        let mut transactions = self.transactions.lock_or_clear("nonce parking lock was poisoned");
+        if let Some(parked) = transactions.get(&key) {
+            if parked.hash == tx.hash {
+                return Err(StratusError::TransactionAlreadyKnown { hash: tx.hash });
+            }
+            let min_gas_price = parked.min_replacement_gas_price();
+            if tx.gas_price < min_gas_price {
+                return Err(StratusError::TransactionReplacementUnderpriced {
+                    expected: min_gas_price,
+                    provided: tx.gas_price,
+                });
+            }
+        }
+        transactions.insert(
+            key,
+            ParkedTransaction {
+                hash: tx.hash,
+                gas_price: tx.gas_price,
+            },
+        );
+        drop(transactions);
+
+        let _parked = self.parked.fetch_add(1, Ordering::Relaxed) + 1;
+        #[cfg(feature = "metrics")]
+        metrics::set_executor_nonce_parked_transactions(_parked as u64);
+        Ok(NonceParkingGuard {
+            parking: self,
+            key,
+            hash: tx.hash,
+        })
+    }
+
+    /// Blocks the current thread until a local transaction is executed, the recheck interval elapses or the deadline is reached.
+    pub fn wait(&self, deadline: Instant) {
+        let timeout = deadline.saturating_duration_since(Instant::now()).min(RECHECK_INTERVAL);
+        let lock = self.transactions.lock_or_clear("nonce parking lock was poisoned");
+        let _ = self.executed.wait_timeout(lock, timeout);
+    }
+
+    /// Wakes all parked transactions to check their sender nonce again.
+    pub fn notify_executed(&self) {
+        self.executed.notify_all();
+    }
+
+    /// Number of transactions parked right now.
+    pub fn count(&self) -> usize {
+        self.parked.load(Ordering::Relaxed)
+    }
+}
+
+struct ParkedTransaction {
+    hash: Hash,
+    gas_price: Wei,
+}
+
+impl ParkedTransaction {
+    /// Minimum gas price a transaction must pay to replace this one.
+    fn min_replacement_gas_price(&self) -> Wei {
+        let bumped = self.gas_price.0.saturating_mul(U256::from(100 + REPLACEMENT_PRICE_BUMP_PERCENT)) / U256::from(100);
+        Wei(bumped)
+    }
+}
+
+/// Keeps a transaction counted as parked while alive.
+pub struct NonceParkingGuard<'a> {
+    parking: &'a NonceParking,
+    key: (Address, Nonce),
+    hash: Hash,
+}
+
+impl Drop for NonceParkingGuard<'_> {
+    fn drop(&mut self) {
+        // a replacement may have taken the slot, so only remove it if it still belongs to this transaction
+        let mut transactions = self.parking.transactions.lock_or_clear("nonce parking lock was poisoned");
+        if transactions.get(&self.key).is_some_and(|parked| parked.hash == self.hash) {
+            transactions.remove(&self.key);
+        }
+        drop(transactions);
+
+        let _parked = self.parking.parked.fetch_sub(1, Ordering::Relaxed) - 1;
+        #[cfg(feature = "metrics")]
+        metrics::set_executor_nonce_parked_transactions(_parked as u64);
+    }
+}
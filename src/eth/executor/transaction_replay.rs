@@ -0,0 +1,161 @@
+//! Replay of a single mined transaction to debug divergences from its stored receipt.
+//!
+//! The transaction is executed again against the state before its block, after the transactions that precede it in the block, exactly as
+//! it was executed when the block was mined or imported. The replayed execution is returned with the calls it performed and the fields
+//! that differ from the stored execution, which for imported blocks reflects the receipt of the external chain.
+
+use display_json::DebugAsJson;
+
+use crate::alias::JsonValue;
+use crate::eth::executor::Executor;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::CallTrace;
+use crate::eth::primitives::EvmExecution;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::Index;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TransactionStage;
+use crate::eth::storage::StratusStorage;
+use crate::ext::not;
+use crate::ext::to_json_value;
+use crate::infra::operations::Operation;
+
+/// Result of replaying a mined transaction.
+#[derive(DebugAsJson, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionReplay {
+    pub tx_hash: Hash,
+    pub block_number: BlockNumber,
+    pub transaction_index: Index,
+
+    /// Execution produced by the replay.
+    pub execution: EvmExecution,
+
+    /// Calls, contract creations and self-destructs performed by the replay.
+    pub calls: Vec<CallTrace>,
+
+    /// Fields of the replayed execution that differ from the stored execution. Empty when the replay matches.
+    pub differences: Vec<ReplayDifference>,
+}
+
+/// Field of the replayed execution that differs from the stored execution.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ReplayDifference {
+    /// Path of the field, like `logs[1].data`.
+    pub field: String,
+    pub stored: JsonValue,
+    pub replayed: JsonValue,
+}
+
+/// Replays a mined transaction. Returns `None` if the transaction is not mined.
+pub fn replay_transaction(
+    storage: &StratusStorage,
+    executor: &dyn Executor,
+    tx_hash: Hash,
+    operation: Option<&Operation>,
+) -> Result<Option<TransactionReplay>, StratusError> {
+    let Some(TransactionStage::Mined(tx)) = storage.read_transaction(&tx_hash)? else {
+        return Ok(None);
+    };
+    let Some(mut block) = storage.read_block(&BlockFilter::Number(tx.block_number))? else {
+        return Ok(None);
+    };
+    tracing::info!(%tx_hash, block_number = %tx.block_number, transaction_index = %tx.transaction_index, "replaying transaction");
+
+    // transactions after the replayed one do not affect it
+    block.transactions.truncate(tx.transaction_index.0 as usize + 1);
+    let Some((_, execution)) = executor.trace_block(&block, operation)?.pop().filter(|(hash, _)| *hash == tx_hash) else {
+        return Ok(None);
+    };
+    let calls = executor.trace_block_calls(&block, operation)?.pop().map(|(_, calls)| calls).unwrap_or_default();
+
+    let differences = diff_executions(&tx.execution, &execution);
+    if not(differences.is_empty()) {
+        tracing::warn!(%tx_hash, differences = %differences.len(), "replayed transaction differs from stored execution");
+    }
+
+    Ok(Some(TransactionReplay {
+        tx_hash,
+        block_number: tx.block_number,
+        transaction_index: tx.transaction_index,
+        execution,
+        calls,
+        differences,
+    }))
+}
+
+/// Lists the fields of the replayed execution that differ from the stored execution.
+///
+/// Compares the same fields `compare_with_receipt` compares during import, plus the gas, output and deployed contract.
+pub fn diff_executions(stored: &EvmExecution, replayed: &EvmExecution) -> Vec<ReplayDifference> {
+    let mut differences = Vec::new();
+    let mut diff = |field: String, stored: JsonValue, replayed: JsonValue| {
+        if stored != replayed {
+            differences.push(ReplayDifference { field, stored, replayed });
+        }
+    };
+
+    diff("status".into(), to_json_value(&stored.result), to_json_value(&replayed.result));
+    diff("gas".into(), to_json_value(stored.gas), to_json_value(replayed.gas));
+    diff("output".into(), to_json_value(&stored.output), to_json_value(&replayed.output));
+    diff(
+        "deployedContractAddress".into(),
+        to_json_value(stored.deployed_contract_address),
+        to_json_value(replayed.deployed_contract_address),
+    );
+
+    // logs are compared one by one, so the first diverging log is easy to find
+    diff("logs.length".into(), to_json_value(stored.logs.len()), to_json_value(replayed.logs.len()));
+    for (index, (stored_log, replayed_log)) in stored.logs.iter().zip(&replayed.logs).enumerate() {
+        diff(
+            format!("logs[{}].address", index),
+            to_json_value(stored_log.address),
+            to_json_value(replayed_log.address),
+        );
+        diff(
+            format!("logs[{}].topics", index),
+            to_json_value(stored_log.topics()),
+            to_json_value(replayed_log.topics()),
+        );
+        diff(
+            format!("logs[{}].data", index),
+            to_json_value(&stored_log.data),
+            to_json_value(&replayed_log.data),
+        );
+    }
+
+    differences
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::primitives::Bytes;
+    use crate::eth::primitives::Gas;
+    use crate::eth::primitives::Log;
+    use crate::utils::test_utils::fake_first;
+
+    #[test]
+    fn diff_executions_lists_diverging_fields() {
+        let mut stored = fake_first::<EvmExecution>();
+        stored.gas = Gas::from(21_000u64);
+        stored.logs = vec![Log::default(), Log::default()];
+        assert!(diff_executions(&stored, &stored).is_empty());
+
+        let mut replayed = stored.clone();
+        replayed.gas = Gas::from(22_000u64);
+        replayed.logs[1].data = Bytes(vec![1]);
+        replayed.logs.push(Log::default());
+
+        let fields = diff_executions(&stored, &replayed)
+            .into_iter()
+            .map(|difference| difference.field)
+            .collect::<Vec<_>>();
+        assert_eq!(fields, vec!["gas", "logs.length", "logs[1].data"]);
+    }
+}
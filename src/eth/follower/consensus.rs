@@ -2,14 +2,41 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 
+use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::Bytes;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TransactionOptions;
 use crate::eth::rpc::RpcClientApp;
 #[cfg(feature = "metrics")]
 use crate::infra::metrics;
 use crate::infra::BlockchainClient;
 
+/// Progress of a follower catching up with the leader, as reported by `eth_syncing`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncStatus {
+    /// Block where the follower started syncing.
+    pub starting_block: BlockNumber,
+
+    /// Last block persisted by the follower.
+    pub current_block: BlockNumber,
+
+    /// Last block known to be mined by the leader.
+    pub highest_block: BlockNumber,
+}
+
+impl SyncStatus {
+    /// Creates the status only if the follower is behind the leader.
+    pub fn behind(starting_block: BlockNumber, current_block: BlockNumber, highest_block: BlockNumber) -> Option<Self> {
+        (current_block < highest_block).then_some(Self {
+            starting_block: starting_block.min(current_block),
+            current_block,
+            highest_block,
+        })
+    }
+}
+
 #[async_trait]
 pub trait Consensus: Send + Sync {
     /// Whether this node should serve requests.
@@ -32,13 +59,13 @@ pub trait Consensus: Send + Sync {
     }
 
     /// Forwards a transaction to leader.
-    async fn forward_to_leader(&self, tx_hash: Hash, tx_data: Bytes, rpc_client: RpcClientApp) -> Result<Hash, StratusError> {
+    async fn forward_to_leader(&self, tx_hash: Hash, tx_data: Bytes, tx_options: TransactionOptions, rpc_client: RpcClientApp) -> Result<Hash, StratusError> {
         #[cfg(feature = "metrics")]
         let start = metrics::now();
 
         tracing::info!(%tx_hash, %rpc_client, "forwarding transaction to leader");
 
-        let hash = self.get_chain()?.send_raw_transaction_to_leader(tx_data.into(), rpc_client).await?;
+        let hash = self.get_chain()?.send_raw_transaction_to_leader(tx_data.into(), tx_options, rpc_client).await?;
 
         #[cfg(feature = "metrics")]
         metrics::inc_consensus_forward(start.elapsed());
@@ -48,6 +75,16 @@ pub trait Consensus: Send + Sync {
 
     fn get_chain(&self) -> anyhow::Result<&Arc<BlockchainClient>>;
 
+    /// Leader address that clients must send transactions to, if transactions are rejected instead of forwarded to the leader.
+    fn transactions_redirect(&self) -> Option<&str> {
+        None
+    }
+
     /// Get the lag between this node and the leader.
     async fn lag(&self) -> anyhow::Result<u64>;
+
+    /// Sync progress, or `None` if the node is caught up with the leader or the leader head is not known yet.
+    async fn sync_status(&self) -> anyhow::Result<Option<SyncStatus>> {
+        Ok(None)
+    }
 }
@@ -9,13 +9,16 @@ use futures::try_join;
 use futures::StreamExt;
 use serde::Deserialize;
 use tokio::sync::mpsc;
+use tokio::sync::Semaphore;
 use tokio::task::yield_now;
 use tokio::time::timeout;
 use tracing::Span;
 
 use crate::eth::executor::Executor;
 use crate::eth::follower::consensus::Consensus;
+use crate::eth::follower::consensus::SyncStatus;
 use crate::eth::miner::Miner;
+use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::ExternalBlock;
 use crate::eth::primitives::ExternalReceipt;
@@ -30,6 +33,8 @@ use crate::globals::IMPORTER_ONLINE_TASKS_SEMAPHORE;
 use crate::if_else;
 #[cfg(feature = "metrics")]
 use crate::infra::metrics;
+use crate::infra::operations::OperationKind;
+use crate::infra::operations::OPERATIONS;
 use crate::infra::tracing::warn_task_rx_closed;
 use crate::infra::tracing::warn_task_tx_closed;
 use crate::infra::tracing::SpanExt;
@@ -47,6 +52,14 @@ use crate::GlobalState;
 /// Current block number of the external RPC blockchain.
 static EXTERNAL_RPC_CURRENT_BLOCK: AtomicU64 = AtomicU64::new(0);
 
+/// Current block number of the external RPC blockchain, if already known by the importer.
+pub fn external_rpc_current_block() -> Option<BlockNumber> {
+    match EXTERNAL_RPC_CURRENT_BLOCK.load(Ordering::Relaxed) {
+        0 => None,
+        number => Some(BlockNumber::from(number)),
+    }
+}
+
 /// Only sets the external RPC current block number if it is equals or greater than the current one.
 fn set_external_rpc_current_block(new_number: BlockNumber) {
     let new_number_u64 = new_number.as_u64();
@@ -58,20 +71,36 @@ fn set_external_rpc_current_block(new_number: BlockNumber) {
 // -----------------------------------------------------------------------------
 // Constants
 // -----------------------------------------------------------------------------
-/// Number of blocks that are downloaded in parallel.
-const PARALLEL_BLOCKS: usize = 3;
-
-/// Number of receipts that are downloaded in parallel.
-const PARALLEL_RECEIPTS: usize = 100;
-
 /// Timeout awaiting for newHeads event before fallback to polling.
 const TIMEOUT_NEW_HEADS: Duration = Duration::from_millis(2000);
 
 /// Interval before we starting retrieving receipts because they are not immediately available after the block is retrieved.
 const INTERVAL_FETCH_RECEIPTS: Duration = Duration::from_millis(50);
 
+/// Limits of the stage that downloads blocks and receipts ahead of the executor.
+#[derive(Debug, Clone, Copy)]
+pub struct ImporterPrefetch {
+    /// Number of blocks downloaded concurrently, which is also the number of downloaded blocks queued for the executor.
+    pub blocks: usize,
+
+    /// Number of block and receipt requests in flight at the same time.
+    pub requests: usize,
+}
+
+impl ImporterPrefetch {
+    pub const DEFAULT: Self = Self { blocks: 10, requests: 100 };
+
+    /// Creates the limits ensuring at least one block and one request can be in flight.
+    pub fn new(blocks: usize, requests: usize) -> Self {
+        Self {
+            blocks: blocks.max(1),
+            requests: requests.max(1),
+        }
+    }
+}
+
 pub struct Importer {
-    executor: Arc<Executor>,
+    executor: Arc<dyn Executor>,
 
     miner: Arc<Miner>,
 
@@ -80,10 +109,28 @@ pub struct Importer {
     chain: Arc<BlockchainClient>,
 
     sync_interval: Duration,
+
+    max_reorg_depth: u64,
+
+    prefetch: ImporterPrefetch,
+
+    /// Last mined block when the importer started, reported as the starting block of the sync.
+    starting_block: AtomicU64,
 }
 
 impl Importer {
-    pub fn new(executor: Arc<Executor>, miner: Arc<Miner>, storage: Arc<StratusStorage>, chain: Arc<BlockchainClient>, sync_interval: Duration) -> Self {
+    /// Default maximum number of blocks that can be reverted when the external chain is reorganized.
+    pub const DEFAULT_MAX_REORG_DEPTH: u64 = 64;
+
+    pub fn new(
+        executor: Arc<dyn Executor>,
+        miner: Arc<Miner>,
+        storage: Arc<StratusStorage>,
+        chain: Arc<BlockchainClient>,
+        sync_interval: Duration,
+        max_reorg_depth: u64,
+        prefetch: ImporterPrefetch,
+    ) -> Self {
         tracing::info!("creating importer");
         Self {
             executor,
@@ -91,6 +138,9 @@ impl Importer {
             storage,
             chain,
             sync_interval,
+            max_reorg_depth,
+            prefetch,
+            starting_block: AtomicU64::new(0),
         }
     }
 
@@ -112,14 +162,25 @@ impl Importer {
 
         let storage = &self.storage;
         let number = storage.read_block_number_to_resume_import()?;
+        self.starting_block.store(storage.read_mined_block_number()?.as_u64(), Ordering::Relaxed);
 
-        let (backlog_tx, backlog_rx) = mpsc::unbounded_channel();
+        let (backlog_tx, backlog_rx) = mpsc::channel(self.prefetch.blocks);
+        let (reorg_tx, reorg_rx) = mpsc::unbounded_channel();
 
         // spawn block executor:
         // it executes and mines blocks and expects to receive them via channel in the correct order.
+        // when it detects a reorg, it reverts the storage and notifies the block fetcher from which block it should restart.
         let task_executor = spawn_named(
             "importer::executor",
-            Importer::start_block_executor(Arc::clone(&self.executor), Arc::clone(&self.miner), backlog_rx),
+            Importer::start_block_executor(
+                Arc::clone(&self.executor),
+                Arc::clone(&self.miner),
+                Arc::clone(&self.storage),
+                Arc::clone(&self.chain),
+                self.max_reorg_depth,
+                backlog_rx,
+                reorg_tx,
+            ),
         );
 
         // spawn block number:
@@ -131,12 +192,12 @@ impl Importer {
         );
 
         // spawn block fetcher:
-        // it fetches blocks and receipts in parallel and sends them to the executor in the correct order.
+        // it prefetches blocks and receipts in parallel and queues them to the executor in the correct order.
         // it uses the number fetcher current block to determine if should keep downloading more blocks or not.
         let block_fetcher_chain = Arc::clone(&self.chain);
         let task_block_fetcher = spawn_named(
             "importer::block-fetcher",
-            Importer::start_block_fetcher(block_fetcher_chain, backlog_tx, number),
+            Importer::start_block_fetcher(block_fetcher_chain, self.prefetch, backlog_tx, reorg_rx, number),
         );
 
         // await all tasks
@@ -152,17 +213,34 @@ impl Importer {
 
     // Executes external blocks and persist them to storage.
     async fn start_block_executor(
-        executor: Arc<Executor>,
+        executor: Arc<dyn Executor>,
         miner: Arc<Miner>,
-        mut backlog_rx: mpsc::UnboundedReceiver<(ExternalBlock, Vec<ExternalReceipt>)>,
+        storage: Arc<StratusStorage>,
+        chain: Arc<BlockchainClient>,
+        max_reorg_depth: u64,
+        mut backlog_rx: mpsc::Receiver<(ExternalBlock, Vec<ExternalReceipt>)>,
+        reorg_tx: mpsc::UnboundedSender<BlockNumber>,
     ) -> anyhow::Result<()> {
         const TASK_NAME: &str = "block-executor";
         let _permit = IMPORTER_ONLINE_TASKS_SEMAPHORE.acquire().await;
 
+        // last imported block, used to check that the next block is its child
+        let mut last_imported = storage.read_block(&BlockFilter::Latest)?.map(|block| (block.number(), block.hash()));
+
+        // register as a cancellable operation, cancelling it shuts down the whole importer
+        let operation = OPERATIONS.start(
+            OperationKind::ImporterOnline,
+            format!("importing blocks after {:?}", last_imported.map(|(number, _)| number)),
+        );
+
         loop {
             if Self::should_shutdown(TASK_NAME) {
                 return Ok(());
             }
+            if operation.is_cancelled() {
+                GlobalState::shutdown_importer_from(TASK_NAME, "operation was cancelled");
+                return Ok(());
+            }
 
             let (block, receipts) = match timeout(Duration::from_secs(2), backlog_rx.recv()).await {
                 Ok(Some(inner)) => inner,
@@ -173,8 +251,43 @@ impl Importer {
                 }
             };
 
+            let (block_number, block_hash) = (block.number(), block.hash());
+
             #[cfg(feature = "metrics")]
-            let (start, block_number, block_tx_len) = (metrics::now(), block.number(), block.transactions.len());
+            metrics::set_importer_online_prefetched_blocks(backlog_rx.len() as u64);
+
+            // check the block follows the last imported block
+            if let Some((last_number, last_hash)) = last_imported {
+                // discard blocks that were fetched before a reorg was handled
+                if block_number != last_number.next_block_number() {
+                    tracing::warn!(%block_number, expected = %last_number.next_block_number(), "discarding external block that does not follow the last imported block");
+                    continue;
+                }
+
+                // parent-hash mismatch means the external chain was reorganized
+                let parent_hash: Hash = block.parent_hash.into();
+                if parent_hash != last_hash {
+                    tracing::warn!(%block_number, %parent_hash, %last_hash, "external block parent does not match the last imported block, handling reorg");
+                    let ancestor = match revert_to_common_ancestor(&storage, &chain, last_number, max_reorg_depth).await {
+                        Ok(ancestor) => ancestor,
+                        Err(e) => {
+                            let message = GlobalState::shutdown_from(TASK_NAME, "failed to handle external chain reorg");
+                            return log_and_err!(reason = e, message);
+                        }
+                    };
+                    last_imported = Some(ancestor);
+
+                    // restart fetching from the first block after the common ancestor
+                    if reorg_tx.send(ancestor.0.next_block_number()).is_err() {
+                        warn_task_rx_closed(TASK_NAME);
+                        return Ok(());
+                    }
+                    continue;
+                }
+            }
+
+            #[cfg(feature = "metrics")]
+            let (start, block_tx_len) = (metrics::now(), block.transactions.len());
 
             // execute and mine
             let mut receipts = ExternalReceipts::from(receipts);
@@ -204,6 +317,7 @@ impl Importer {
                 let message = GlobalState::shutdown_from(TASK_NAME, "failed to mine external block");
                 return log_and_err!(reason = e, message);
             };
+            last_imported = Some((block_number, block_hash));
 
             #[cfg(feature = "metrics")]
             {
@@ -330,20 +444,32 @@ impl Importer {
     // Block fetcher
     // -----------------------------------------------------------------------------
 
-    /// Retrieves blocks and receipts.
+    /// Retrieves blocks and receipts ahead of the executor.
+    ///
+    /// Up to `prefetch.blocks` blocks are downloaded concurrently with all their receipts, and the number of requests in flight is
+    /// bounded by `prefetch.requests`. Downloaded blocks are queued in order and the fetcher waits when the queue is full.
     async fn start_block_fetcher(
         chain: Arc<BlockchainClient>,
-        backlog_tx: mpsc::UnboundedSender<(ExternalBlock, Vec<ExternalReceipt>)>,
+        prefetch: ImporterPrefetch,
+        backlog_tx: mpsc::Sender<(ExternalBlock, Vec<ExternalReceipt>)>,
+        mut reorg_rx: mpsc::UnboundedReceiver<BlockNumber>,
         mut importer_block_number: BlockNumber,
     ) -> anyhow::Result<()> {
         const TASK_NAME: &str = "external-block-fetcher";
         let _permit = IMPORTER_ONLINE_TASKS_SEMAPHORE.acquire().await;
+        let requests = Arc::new(Semaphore::new(prefetch.requests));
 
         loop {
             if Self::should_shutdown(TASK_NAME) {
                 return Ok(());
             }
 
+            // restart from the block informed by the executor after a reorg
+            while let Ok(restart_block_number) = reorg_rx.try_recv() {
+                tracing::warn!(%restart_block_number, "restarting block fetcher after external chain reorg");
+                importer_block_number = restart_block_number;
+            }
+
             // if we are ahead of current block number, await until we are behind again
             let external_rpc_current_block = EXTERNAL_RPC_CURRENT_BLOCK.load(Ordering::Relaxed);
             if importer_block_number.as_u64() > external_rpc_current_block {
@@ -359,14 +485,21 @@ impl Importer {
             let mut tasks = Vec::with_capacity(blocks_to_fetch as usize);
             while blocks_to_fetch > 0 {
                 blocks_to_fetch -= 1;
-                tasks.push(fetch_block_and_receipts(Arc::clone(&chain), importer_block_number));
+                tasks.push(fetch_block_and_receipts(Arc::clone(&chain), Arc::clone(&requests), importer_block_number));
                 importer_block_number = importer_block_number.next_block_number();
             }
 
             // keep fetching in order
-            let mut tasks = futures::stream::iter(tasks).buffered(PARALLEL_BLOCKS);
+            let mut tasks = futures::stream::iter(tasks).buffered(prefetch.blocks);
             while let Some((block, receipts)) = tasks.next().await {
-                if backlog_tx.send((block, receipts)).is_err() {
+                // stop sending blocks from the reorganized branch
+                if let Ok(restart_block_number) = reorg_rx.try_recv() {
+                    tracing::warn!(%restart_block_number, "restarting block fetcher after external chain reorg");
+                    importer_block_number = restart_block_number;
+                    break;
+                }
+
+                if backlog_tx.send((block, receipts)).await.is_err() {
                     warn_task_rx_closed(TASK_NAME);
                     return Ok(());
                 }
@@ -379,24 +512,77 @@ impl Importer {
 // Helpers
 // -----------------------------------------------------------------------------
 
+/// Finds the most recent block that is the same in the local storage and in the external chain, then resets the storage to it.
+///
+/// Returns the number and hash of the common ancestor.
+#[tracing::instrument(name = "importer::revert_to_common_ancestor", skip_all, fields(mined_number))]
+async fn revert_to_common_ancestor(
+    storage: &StratusStorage,
+    chain: &Arc<BlockchainClient>,
+    mined_number: BlockNumber,
+    max_reorg_depth: u64,
+) -> anyhow::Result<(BlockNumber, Hash)> {
+    Span::with(|s| {
+        s.rec_str("mined_number", &mined_number);
+    });
+
+    let mut number = mined_number;
+    let ancestor_hash = loop {
+        let depth = mined_number.as_u64() - number.as_u64();
+        if depth > max_reorg_depth {
+            return log_and_err!(payload = max_reorg_depth, "external chain reorg is deeper than the max allowed depth");
+        }
+
+        let Some(local_block) = storage.read_block(&BlockFilter::Number(number))? else {
+            return log_and_err!(payload = number, "local block not found while searching for reorg common ancestor");
+        };
+        let external_block = fetch_block(Arc::clone(chain), number).await;
+        if external_block.hash() == local_block.hash() {
+            break local_block.hash();
+        }
+
+        tracing::warn!(block_number = %number, local_hash = %local_block.hash(), external_hash = %external_block.hash(), "block diverges from external chain");
+        number = match number.prev() {
+            Some(prev) => prev,
+            None => return log_and_err!("genesis block diverges from external chain"),
+        };
+    };
+
+    let depth = mined_number.as_u64() - number.as_u64();
+    tracing::warn!(ancestor = %number, %depth, "external chain reorganized, reverting storage to common ancestor");
+    storage.reset_perm(number)?;
+
+    #[cfg(feature = "metrics")]
+    metrics::inc_importer_online_reorg_depth(depth as usize);
+
+    Ok((number, ancestor_hash))
+}
+
+/// Fetches a block and all its receipts, holding a permit of `requests` for each request in flight.
 #[tracing::instrument(name = "importer::fetch_block_and_receipts", skip_all, fields(block_number))]
-async fn fetch_block_and_receipts(chain: Arc<BlockchainClient>, block_number: BlockNumber) -> (ExternalBlock, Vec<ExternalReceipt>) {
+async fn fetch_block_and_receipts(chain: Arc<BlockchainClient>, requests: Arc<Semaphore>, block_number: BlockNumber) -> (ExternalBlock, Vec<ExternalReceipt>) {
     Span::with(|s| {
         s.rec_str("block_number", &block_number);
     });
 
     // fetch block
-    let block = fetch_block(Arc::clone(&chain), block_number).await;
+    let block = {
+        let _permit = requests.acquire().await;
+        fetch_block(Arc::clone(&chain), block_number).await
+    };
 
     // wait some time until receipts are available
     let _ = traced_sleep(INTERVAL_FETCH_RECEIPTS, SleepReason::SyncData).await;
 
     // fetch receipts in parallel
-    let mut receipts_tasks = Vec::with_capacity(block.transactions.len());
-    for hash in block.transactions.iter().map(|tx| tx.hash()) {
-        receipts_tasks.push(fetch_receipt(Arc::clone(&chain), block_number, hash));
-    }
-    let receipts = futures::stream::iter(receipts_tasks).buffer_unordered(PARALLEL_RECEIPTS).collect().await;
+    let receipts_tasks = block.transactions.iter().map(|tx| {
+        let (chain, requests, hash) = (Arc::clone(&chain), Arc::clone(&requests), tx.hash());
+        async move {
+            let _permit = requests.acquire().await;
+            fetch_receipt(chain, block_number, hash).await
+        }
+    });
+    let receipts = futures::future::join_all(receipts_tasks).await;
 
     (block, receipts)
 }
@@ -458,6 +644,14 @@ impl Consensus for Importer {
         Ok(EXTERNAL_RPC_CURRENT_BLOCK.load(Ordering::SeqCst) - self.storage.read_mined_block_number()?.as_u64())
     }
 
+    async fn sync_status(&self) -> anyhow::Result<Option<SyncStatus>> {
+        let Some(highest_block) = external_rpc_current_block() else {
+            return Ok(None);
+        };
+        let starting_block = BlockNumber::from(self.starting_block.load(Ordering::Relaxed));
+        Ok(SyncStatus::behind(starting_block, self.storage.read_mined_block_number()?, highest_block))
+    }
+
     fn get_chain(&self) -> anyhow::Result<&Arc<BlockchainClient>> {
         Ok(&self.chain)
     }
@@ -8,6 +8,8 @@ use serde_json::json;
 use crate::eth::executor::Executor;
 use crate::eth::follower::consensus::Consensus;
 use crate::eth::follower::importer::Importer;
+use crate::eth::follower::importer::ImporterPrefetch;
+use crate::eth::follower::replication::Replicator;
 use crate::eth::miner::Miner;
 use crate::eth::primitives::StratusError;
 use crate::eth::rpc::RpcContext;
@@ -16,6 +18,7 @@ use crate::ext::not;
 use crate::ext::parse_duration;
 use crate::ext::spawn_named;
 use crate::infra::BlockchainClient;
+use crate::log_and_err;
 use crate::GlobalState;
 use crate::NodeMode;
 
@@ -36,23 +39,53 @@ pub struct ImporterConfig {
 
     #[arg(long = "sync-interval", value_parser=parse_duration, env = "SYNC_INTERVAL", default_value = "100ms")]
     pub sync_interval: Duration,
+
+    /// Maximum number of blocks that can be reverted when the external chain is reorganized.
+    #[arg(long = "max-reorg-depth", env = "MAX_REORG_DEPTH", default_value_t = Importer::DEFAULT_MAX_REORG_DEPTH)]
+    pub max_reorg_depth: u64,
+
+    /// Number of blocks downloaded with their receipts ahead of the block being executed.
+    #[arg(long = "importer-prefetch-blocks", env = "IMPORTER_PREFETCH_BLOCKS", default_value_t = ImporterPrefetch::DEFAULT.blocks)]
+    pub importer_prefetch_blocks: usize,
+
+    /// Maximum number of block and receipt requests to the external RPC in flight at the same time.
+    #[arg(long = "importer-max-parallel-requests", env = "IMPORTER_MAX_PARALLEL_REQUESTS", default_value_t = ImporterPrefetch::DEFAULT.requests)]
+    pub importer_max_parallel_requests: usize,
+
+    /// Persists blocks streamed by the leader instead of re-executing them. Requires the external RPC WS endpoint.
+    ///
+    /// Transactions sent to the node are rejected with the leader address instead of being forwarded.
+    #[arg(long = "replication", env = "REPLICATION", default_value = "false")]
+    pub replication: bool,
 }
 
 impl ImporterConfig {
-    pub async fn init(&self, executor: Arc<Executor>, miner: Arc<Miner>, storage: Arc<StratusStorage>) -> anyhow::Result<Option<Arc<dyn Consensus>>> {
+    pub async fn init(&self, executor: Arc<dyn Executor>, miner: Arc<Miner>, storage: Arc<StratusStorage>) -> anyhow::Result<Option<Arc<dyn Consensus>>> {
         match GlobalState::get_node_mode() {
             NodeMode::Follower => self.init_follower(executor, miner, storage).await,
-            NodeMode::Leader => Ok(None),
+            NodeMode::Leader | NodeMode::Archive => Ok(None),
         }
     }
 
-    async fn init_follower(&self, executor: Arc<Executor>, miner: Arc<Miner>, storage: Arc<StratusStorage>) -> anyhow::Result<Option<Arc<dyn Consensus>>> {
+    async fn init_follower(&self, executor: Arc<dyn Executor>, miner: Arc<Miner>, storage: Arc<StratusStorage>) -> anyhow::Result<Option<Arc<dyn Consensus>>> {
         const TASK_NAME: &str = "importer::init";
         tracing::info!(config = ?self, "creating importer for follower node");
 
         let chain = Arc::new(BlockchainClient::new_http_ws(&self.external_rpc, self.external_rpc_ws.as_deref(), self.external_rpc_timeout).await?);
 
-        let importer = Importer::new(executor, Arc::clone(&miner), Arc::clone(&storage), Arc::clone(&chain), self.sync_interval);
+        if self.replication {
+            return self.init_replicator(miner, storage, chain);
+        }
+
+        let importer = Importer::new(
+            executor,
+            Arc::clone(&miner),
+            Arc::clone(&storage),
+            Arc::clone(&chain),
+            self.sync_interval,
+            self.max_reorg_depth,
+            ImporterPrefetch::new(self.importer_prefetch_blocks, self.importer_max_parallel_requests),
+        );
         let importer = Arc::new(importer);
 
         spawn_named(TASK_NAME, {
@@ -67,6 +100,25 @@ impl ImporterConfig {
         Ok(Some(importer))
     }
 
+    fn init_replicator(&self, miner: Arc<Miner>, storage: Arc<StratusStorage>, chain: Arc<BlockchainClient>) -> anyhow::Result<Option<Arc<dyn Consensus>>> {
+        const TASK_NAME: &str = "replicator::init";
+        if not(chain.supports_ws()) {
+            return log_and_err!("replication requires the external RPC WS endpoint");
+        }
+
+        let replicator = Arc::new(Replicator::new(miner, storage, chain));
+        spawn_named(TASK_NAME, {
+            let replicator = Arc::clone(&replicator);
+            async move {
+                if let Err(e) = replicator.run().await {
+                    tracing::error!(reason = ?e, "replicator failed");
+                }
+            }
+        });
+
+        Ok(Some(replicator))
+    }
+
     pub async fn init_follower_importer(&self, ctx: Arc<RpcContext>) -> Result<serde_json::Value, StratusError> {
         if not(GlobalState::is_follower()) {
             tracing::error!("node is currently not a follower");
@@ -80,7 +132,7 @@ impl ImporterConfig {
 
         GlobalState::set_importer_shutdown(false);
 
-        let consensus = match self.init(Arc::clone(&ctx.executor), Arc::clone(&ctx.miner), Arc::clone(&ctx.storage)).await {
+        let consensus = match self.init(Arc::clone(&ctx.executor), Arc::clone(ctx.miner()?), Arc::clone(&ctx.storage)).await {
             Ok(consensus) => consensus,
             Err(e) => {
                 tracing::error!(reason = ?e, "failed to initialize importer");
@@ -2,5 +2,7 @@
 mod importer;
 mod importer_config;
 
+pub use importer::external_rpc_current_block;
 pub use importer::Importer;
+pub use importer::ImporterPrefetch;
 pub use importer_config::ImporterConfig;
@@ -0,0 +1,214 @@
+//! Replication of mined blocks from the leader to followers.
+//!
+//! The leader streams its mined blocks through the `stratus_subscribeReplication` subscription and followers persist them as received,
+//! without re-executing their transactions. Replicas serve read-only RPC and reject transactions with the leader address.
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use jsonrpsee::SubscriptionMessage;
+use jsonrpsee::SubscriptionSink;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::timeout;
+
+use crate::eth::follower::consensus::Consensus;
+use crate::eth::follower::consensus::SyncStatus;
+use crate::eth::miner::Miner;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::storage::ChainHeadEvent;
+use crate::eth::storage::StratusStorage;
+use crate::ext::not;
+use crate::ext::traced_sleep;
+use crate::ext::SleepReason;
+use crate::infra::BlockchainClient;
+use crate::log_and_err;
+use crate::GlobalState;
+
+/// Interval between checks for new blocks when no block notification is received.
+const STREAM_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Delay before subscribing again after the replication stream fails.
+const RESUBSCRIBE_DELAY: Duration = Duration::from_millis(500);
+
+// -----------------------------------------------------------------------------
+// Leader
+// -----------------------------------------------------------------------------
+
+/// Streams mined blocks to a replication subscriber, starting from the specified block.
+///
+/// Blocks are always read from the permanent storage, so a subscriber that falls behind catches up before receiving new blocks. When the
+/// chain head is rolled back, the stream rewinds so blocks produced again after the rollback are also sent.
+pub async fn stream_replicated_blocks(storage: Arc<StratusStorage>, from: BlockNumber, sink: SubscriptionSink) {
+    const TASK_NAME: &str = "replication::stream";
+    tracing::info!(%from, "streaming replicated blocks");
+
+    let mut chain_head = storage.chain_head().subscribe();
+    let mut next = from;
+    loop {
+        if GlobalState::is_shutdown_warn(TASK_NAME) || sink.is_closed() {
+            return;
+        }
+
+        // send blocks mined since the last sent block
+        let mined_number = match storage.read_mined_block_number() {
+            Ok(number) => number,
+            Err(e) => {
+                tracing::error!(reason = ?e, "failed to read mined block number for replication");
+                return;
+            }
+        };
+        while next <= mined_number {
+            let block = match storage.read_block(&BlockFilter::Number(next)) {
+                Ok(Some(block)) => block,
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::error!(reason = ?e, block_number = %next, "failed to read block for replication");
+                    return;
+                }
+            };
+            let message = match SubscriptionMessage::from_json(&block) {
+                Ok(message) => message,
+                Err(e) => {
+                    tracing::error!(reason = ?e, block_number = %next, "failed to serialize block for replication");
+                    return;
+                }
+            };
+            if sink.send(message).await.is_err() {
+                tracing::info!(block_number = %next, "replication subscriber disconnected");
+                return;
+            }
+            next = next.next_block_number();
+        }
+
+        // await the next chain head change
+        match timeout(STREAM_POLL_INTERVAL, chain_head.recv()).await {
+            Ok(Ok(ChainHeadEvent::RolledBack { number, .. })) if number < next => {
+                tracing::warn!(%number, %next, "chain head rolled back. rewinding replication stream.");
+                next = number.next_block_number();
+            }
+            Ok(Err(RecvError::Closed)) => return,
+            _ => {}
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Follower
+// -----------------------------------------------------------------------------
+
+/// Persists blocks streamed by the leader.
+pub struct Replicator {
+    miner: Arc<Miner>,
+
+    storage: Arc<StratusStorage>,
+
+    chain: Arc<BlockchainClient>,
+
+    /// Last block received from the leader.
+    leader_block: AtomicU64,
+
+    /// Last mined block when the replicator started, reported as the starting block of the sync.
+    starting_block: AtomicU64,
+}
+
+impl Replicator {
+    pub fn new(miner: Arc<Miner>, storage: Arc<StratusStorage>, chain: Arc<BlockchainClient>) -> Self {
+        tracing::info!("creating replicator");
+        Self {
+            miner,
+            storage,
+            chain,
+            leader_block: AtomicU64::new(0),
+            starting_block: AtomicU64::new(0),
+        }
+    }
+
+    /// Checks if the replicator should shutdown.
+    fn should_shutdown(task_name: &str) -> bool {
+        GlobalState::is_shutdown_warn(task_name) || GlobalState::is_importer_shutdown_warn(task_name)
+    }
+
+    /// Subscribes to the leader replication stream and persists received blocks, subscribing again after failures.
+    pub async fn run(self: Arc<Self>) -> anyhow::Result<()> {
+        const TASK_NAME: &str = "replicator";
+        self.starting_block.store(self.storage.read_mined_block_number()?.as_u64(), Ordering::Relaxed);
+
+        loop {
+            if Self::should_shutdown(TASK_NAME) {
+                return Ok(());
+            }
+
+            let from = self.storage.read_mined_block_number()?.next_block_number();
+            let mut sub = match self.chain.subscribe_replication(from).await {
+                Ok(sub) => sub,
+                Err(e) => {
+                    tracing::error!(reason = ?e, %from, "failed to subscribe to replication. retrying with delay.");
+                    traced_sleep(RESUBSCRIBE_DELAY, SleepReason::RetryBackoff).await;
+                    continue;
+                }
+            };
+            tracing::info!(%from, "subscribed to replication");
+
+            while let Some(result) = sub.next().await {
+                if Self::should_shutdown(TASK_NAME) {
+                    return Ok(());
+                }
+
+                let block = match result {
+                    Ok(block) => block,
+                    Err(e) => {
+                        tracing::error!(reason = ?e, "failed to read replicated block. resubscribing.");
+                        break;
+                    }
+                };
+
+                let block_number = block.number();
+                self.leader_block.fetch_max(block_number.as_u64(), Ordering::Relaxed);
+                if let Err(e) = self.miner.commit_replicated(block) {
+                    let message = GlobalState::shutdown_from(TASK_NAME, "failed to commit replicated block");
+                    return log_and_err!(reason = e, message);
+                }
+                tracing::info!(%block_number, "committed replicated block");
+            }
+
+            if not(Self::should_shutdown(TASK_NAME)) {
+                tracing::warn!("replication stream closed by the leader. resubscribing.");
+                traced_sleep(RESUBSCRIBE_DELAY, SleepReason::RetryBackoff).await;
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Consensus for Replicator {
+    async fn lag(&self) -> anyhow::Result<u64> {
+        let mined_number = self.storage.read_mined_block_number()?.as_u64();
+        Ok(self.leader_block.load(Ordering::Relaxed).saturating_sub(mined_number))
+    }
+
+    async fn sync_status(&self) -> anyhow::Result<Option<SyncStatus>> {
+        let highest_block = self.leader_block.load(Ordering::Relaxed);
+        if highest_block == 0 {
+            return Ok(None);
+        }
+        let starting_block = BlockNumber::from(self.starting_block.load(Ordering::Relaxed));
+        Ok(SyncStatus::behind(
+            starting_block,
+            self.storage.read_mined_block_number()?,
+            BlockNumber::from(highest_block),
+        ))
+    }
+
+    fn get_chain(&self) -> anyhow::Result<&Arc<BlockchainClient>> {
+        Ok(&self.chain)
+    }
+
+    fn transactions_redirect(&self) -> Option<&str> {
+        Some(&self.chain.http_url)
+    }
+}
@@ -0,0 +1,220 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use clap::Parser;
+use display_json::DebugAsJson;
+use ethereum_types::U256;
+
+use crate::eth::primitives::FeeHistoryBlock;
+use crate::eth::primitives::Gas;
+use crate::eth::primitives::Wei;
+use crate::ext::not;
+
+/// Calculates the base fee of local blocks.
+///
+/// Local transactions are charged the base fee of the block where they are executed, so they pay for their gas only when it is not zero.
+#[derive(DebugAsJson, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct BaseFee {
+    mode: BaseFeeMode,
+
+    /// Base fee of all blocks in fixed mode, or of the first block in EIP-1559 mode.
+    initial: Wei,
+
+    /// Gas used by the parent block above which the base fee increases and below which it decreases.
+    target_gas: Gas,
+
+    /// Bounds the change of the base fee between two blocks to `1 / denominator`.
+    denominator: u64,
+}
+
+impl BaseFee {
+    /// Local blocks without base fee.
+    pub const DISABLED: BaseFee = BaseFee {
+        mode: BaseFeeMode::Disabled,
+        initial: Wei::ZERO,
+        target_gas: Gas::ZERO,
+        denominator: 1,
+    };
+
+    pub fn mode(&self) -> BaseFeeMode {
+        self.mode
+    }
+
+    /// Calculates the base fee of the block after the specified parent.
+    ///
+    /// In EIP-1559 mode, the first block and blocks whose parent has no base fee start from the initial base fee.
+    pub fn next(&self, parent: Option<&FeeHistoryBlock>) -> Wei {
+        match self.mode {
+            BaseFeeMode::Disabled => Wei::ZERO,
+            BaseFeeMode::Fixed => self.initial,
+            BaseFeeMode::Eip1559 => match parent {
+                Some(parent) if not(parent.base_fee_per_gas.is_zero()) => self.adjust(parent.base_fee_per_gas, parent.gas_used),
+                _ => self.initial,
+            },
+        }
+    }
+
+    /// Adjusts the parent base fee according to how far the parent gas used is from the target, as specified by EIP-1559.
+    fn adjust(&self, parent_base_fee: Wei, parent_gas_used: Gas) -> Wei {
+        let base_fee = parent_base_fee.0;
+        let gas_used = U256::from(parent_gas_used.as_u64());
+        let target_gas = U256::from(self.target_gas.as_u64());
+        let denominator = U256::from(self.denominator);
+
+        if gas_used == target_gas {
+            parent_base_fee
+        } else if gas_used > target_gas {
+            let delta = (base_fee * (gas_used - target_gas) / target_gas / denominator).max(U256::one());
+            Wei::new(base_fee.saturating_add(delta))
+        } else {
+            let delta = base_fee * (target_gas - gas_used) / target_gas / denominator;
+            Wei::new(base_fee - delta)
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Mode
+// -----------------------------------------------------------------------------
+
+/// Indicates how the base fee of local blocks is calculated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumIs, serde::Serialize)]
+pub enum BaseFeeMode {
+    /// Local blocks do not have a base fee.
+    #[serde(rename = "disabled")]
+    Disabled,
+
+    /// All local blocks have the same base fee.
+    #[serde(rename = "fixed")]
+    Fixed,
+
+    /// Base fee is adjusted after each block according to the gas used by the parent block.
+    #[serde(rename = "eip1559")]
+    Eip1559,
+}
+
+impl FromStr for BaseFeeMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self, Self::Err> {
+        match s {
+            "disabled" => Ok(Self::Disabled),
+            "fixed" => Ok(Self::Fixed),
+            "eip1559" => Ok(Self::Eip1559),
+            s => Err(anyhow!("unknown base fee mode: {}", s)),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Config
+// -----------------------------------------------------------------------------
+
+#[derive(DebugAsJson, Clone, Parser, serde::Serialize)]
+pub struct BaseFeeConfig {
+    /// How the base fee of local blocks is calculated: `disabled`, `fixed` or `eip1559`.
+    #[arg(long = "block-base-fee-mode", env = "BLOCK_BASE_FEE_MODE", default_value = "disabled")]
+    pub base_fee_mode: BaseFeeMode,
+
+    /// Base fee of all blocks in fixed mode, or of the first block in eip1559 mode.
+    #[arg(long = "block-base-fee", env = "BLOCK_BASE_FEE", default_value = "1000000000")]
+    pub base_fee: u64,
+
+    /// Gas used by a block above which the base fee of the next block increases. Defaults to the block gas limit divided by the elasticity.
+    #[arg(long = "block-base-fee-target-gas", env = "BLOCK_BASE_FEE_TARGET_GAS")]
+    pub base_fee_target_gas: Option<u64>,
+
+    /// Ratio between the block gas limit and the target gas.
+    #[arg(long = "block-base-fee-elasticity", env = "BLOCK_BASE_FEE_ELASTICITY", default_value = "2")]
+    pub base_fee_elasticity: u64,
+
+    /// Bounds the change of the base fee between two blocks to `1 / denominator`.
+    #[arg(long = "block-base-fee-denominator", env = "BLOCK_BASE_FEE_DENOMINATOR", default_value = "8")]
+    pub base_fee_denominator: u64,
+}
+
+impl BaseFeeConfig {
+    /// Creates the [`BaseFee`] calculator for blocks limited to the specified gas.
+    pub fn build(&self, block_gas_limit: u64) -> anyhow::Result<BaseFee> {
+        if self.base_fee_mode.is_disabled() {
+            return Ok(BaseFee::DISABLED);
+        }
+
+        if self.base_fee_elasticity == 0 {
+            return Err(anyhow!("block base fee elasticity must be greater than zero"));
+        }
+        if self.base_fee_denominator == 0 {
+            return Err(anyhow!("block base fee denominator must be greater than zero"));
+        }
+        let target_gas = self.base_fee_target_gas.unwrap_or(block_gas_limit / self.base_fee_elasticity);
+        if target_gas == 0 {
+            return Err(anyhow!("block base fee target gas must be greater than zero"));
+        }
+
+        Ok(BaseFee {
+            mode: self.base_fee_mode,
+            initial: Wei::from(self.base_fee),
+            target_gas: Gas::from(target_gas),
+            denominator: self.base_fee_denominator,
+        })
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::primitives::BlockNumber;
+
+    fn parent(base_fee: u64, gas_used: u64) -> FeeHistoryBlock {
+        FeeHistoryBlock {
+            number: BlockNumber::ZERO,
+            base_fee_per_gas: Wei::from(base_fee),
+            gas_used: Gas::from(gas_used),
+            gas_limit: Gas::from(30_000_000u64),
+            priority_fees: vec![],
+        }
+    }
+
+    #[test]
+    fn eip1559_base_fee_follows_parent_gas_used() {
+        let config = BaseFeeConfig {
+            base_fee_mode: BaseFeeMode::Eip1559,
+            base_fee: 1_000_000_000,
+            base_fee_target_gas: None,
+            base_fee_elasticity: 2,
+            base_fee_denominator: 8,
+        };
+        let base_fee = config.build(30_000_000).unwrap();
+
+        // first block and parents without base fee start from the initial base fee
+        assert_eq!(base_fee.next(None), Wei::from(1_000_000_000u64));
+        assert_eq!(base_fee.next(Some(&parent(0, 30_000_000))), Wei::from(1_000_000_000u64));
+
+        // at target, full and empty parents
+        assert_eq!(base_fee.next(Some(&parent(1_000_000_000, 15_000_000))), Wei::from(1_000_000_000u64));
+        assert_eq!(base_fee.next(Some(&parent(1_000_000_000, 30_000_000))), Wei::from(1_125_000_000u64));
+        assert_eq!(base_fee.next(Some(&parent(1_000_000_000, 0))), Wei::from(875_000_000u64));
+
+        // increases at least by one wei
+        assert_eq!(base_fee.next(Some(&parent(1, 15_000_001))), Wei::from(2u64));
+    }
+
+    #[test]
+    fn fixed_and_disabled_base_fee_ignore_parent() {
+        let mut config = BaseFeeConfig {
+            base_fee_mode: BaseFeeMode::Fixed,
+            base_fee: 7,
+            base_fee_target_gas: None,
+            base_fee_elasticity: 2,
+            base_fee_denominator: 8,
+        };
+        assert_eq!(config.build(30_000_000).unwrap().next(Some(&parent(100, 30_000_000))), Wei::from(7u64));
+
+        config.base_fee_mode = BaseFeeMode::Disabled;
+        assert_eq!(config.build(30_000_000).unwrap().next(Some(&parent(100, 30_000_000))), Wei::ZERO);
+    }
+}
@@ -0,0 +1,120 @@
+//! Per-block execution artifacts consumed by external proving systems.
+//!
+//! Each committed block produces a file named `<block_number>.bin` in the configured directory with the following layout:
+//!
+//! | Offset | Size | Content                                                  |
+//! |--------|------|----------------------------------------------------------|
+//! | 0      | 8    | Magic bytes `STRATUSA`.                                  |
+//! | 8      | 2    | Format version as little-endian `u16`.                   |
+//! | 10     | ..   | [`BlockArtifact`] encoded with `bincode` 1.x defaults.   |
+//!
+//! `bincode` defaults are little-endian fixed-size integers, `u64` length prefixes for sequences and maps, and fields encoded in
+//! declaration order. Accounts are sorted by address so the same block always produces the same bytes.
+
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockHeader;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::ExecutionAccountChanges;
+use crate::eth::primitives::ExecutionResult;
+use crate::eth::primitives::Gas;
+use crate::eth::primitives::Log;
+use crate::eth::primitives::TransactionInput;
+
+/// Magic bytes identifying an artifact file.
+pub const BLOCK_ARTIFACT_MAGIC: &[u8; 8] = b"STRATUSA";
+
+/// Current version of the artifact format. Must be incremented on any layout change.
+pub const BLOCK_ARTIFACT_VERSION: u16 = 2;
+
+/// Inputs, touched state and outputs of all transactions of a block.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BlockArtifact {
+    /// Header of the committed block, including the block environment used by the EVM.
+    pub header: BlockHeader,
+
+    /// Transactions in the order they were included.
+    pub transactions: Vec<TransactionArtifact>,
+}
+
+/// Inputs, touched state and outputs of a single transaction.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TransactionArtifact {
+    /// Transaction as received, including signature.
+    pub input: TransactionInput,
+
+    /// Accounts and slots read or written, with original and modified values, sorted by address.
+    pub touched: Vec<ExecutionAccountChanges>,
+
+    /// Status of the execution.
+    pub result: ExecutionResult,
+
+    /// Output returned by the execution.
+    pub output: Bytes,
+
+    /// Consumed gas.
+    pub gas: Gas,
+
+    /// Logs emitted by the execution.
+    pub logs: Vec<Log>,
+
+    /// Contract address if the transaction deployed a contract.
+    pub deployed_contract_address: Option<Address>,
+}
+
+impl BlockArtifact {
+    /// Encodes the artifact in the documented binary format.
+    pub fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        let mut buf = Vec::with_capacity(1024);
+        buf.extend_from_slice(BLOCK_ARTIFACT_MAGIC);
+        buf.extend_from_slice(&BLOCK_ARTIFACT_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut buf, self).context("failed to encode block artifact")?;
+        Ok(buf)
+    }
+
+    /// Writes the artifact to `<dir>/<block_number>.bin`.
+    ///
+    /// The file is written to a temporary path and renamed, so consumers never see a partially written artifact.
+    pub fn write_to(&self, dir: &Path) -> anyhow::Result<PathBuf> {
+        let path = dir.join(format!("{}.bin", self.header.number.as_u64()));
+        let tmp_path = path.with_extension("bin.tmp");
+
+        fs::write(&tmp_path, self.encode()?).with_context(|| format!("failed to write block artifact to {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, &path).with_context(|| format!("failed to rename block artifact to {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+impl From<&Block> for BlockArtifact {
+    fn from(block: &Block) -> Self {
+        let transactions = block
+            .transactions
+            .iter()
+            .map(|tx| {
+                let mut touched = tx.execution.changes.values().cloned().collect::<Vec<_>>();
+                touched.sort_by_key(|changes| changes.address.0);
+
+                TransactionArtifact {
+                    input: tx.input.clone(),
+                    touched,
+                    result: tx.execution.result.clone(),
+                    output: tx.execution.output.clone(),
+                    gas: tx.execution.gas,
+                    logs: tx.execution.logs.clone(),
+                    deployed_contract_address: tx.execution.deployed_contract_address,
+                }
+            })
+            .collect();
+
+        Self {
+            header: block.header.clone(),
+            transactions,
+        }
+    }
+}
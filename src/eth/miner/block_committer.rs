@@ -0,0 +1,255 @@
+//! Background commits of local blocks.
+//!
+//! When enabled, mining a local block only finishes the pending block in the temporary storage and queues it, so transactions that
+//! trigger mining in automine mode return without waiting for the permanent storage. A dedicated thread seals the queued blocks and
+//! commits them in order, taking all blocks queued while the previous batch was being written in a single pass.
+//!
+//! The executor keeps reading the state of queued blocks from the temporary storage, which keeps the state of recently finished blocks.
+//! Mined blocks, receipts and logs become visible only after their block is committed.
+//!
+//! Blocks failing to be committed are retried with backoff. If a block still cannot be committed, the committer pauses the miner and
+//! stops, discarding the blocks queued after it, and queueing new blocks fails until background commits are restarted.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::sync::mpsc::TryRecvError;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::anyhow;
+
+use crate::eth::miner::Miner;
+use crate::eth::primitives::Block;
+use crate::ext::not;
+use crate::ext::spawn_thread;
+use crate::GlobalState;
+
+/// Max number of blocks waiting to be committed, kept below the number of finished blocks whose state the temporary storage keeps.
+pub const MAX_QUEUED_BLOCKS: usize = 32;
+
+/// Max number of times a queued block is sealed and committed before the committer gives up.
+const MAX_COMMIT_ATTEMPTS: usize = 5;
+
+/// Delay before retrying to commit a queued block, doubled after each failure up to [`MAX_RETRY_DELAY`].
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(100);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Commits local blocks in a background thread.
+pub struct BlockCommitter {
+    queue: BlockCommitterQueue,
+    handle: JoinHandle<()>,
+
+    /// Indicates the committer is being stopped, so failing blocks are not retried.
+    stopping: Arc<AtomicBool>,
+}
+
+/// Queue of blocks waiting to be committed by a [`BlockCommitter`].
+///
+/// Cloned from the committer so callers do not hold any lock while waiting for space in the queue.
+#[derive(Clone)]
+pub struct BlockCommitterQueue {
+    tx: mpsc::SyncSender<CommitterMessage>,
+}
+
+enum CommitterMessage {
+    /// Local block to be sealed and committed.
+    Block(Block),
+
+    /// Acknowledged after all blocks queued before it are committed.
+    Flush(mpsc::Sender<()>),
+}
+
+impl BlockCommitter {
+    /// Spawns the committer thread. Mining waits when `max_queued` blocks are waiting to be committed.
+    pub fn spawn(miner: Arc<Miner>, max_queued: usize, max_batch: usize) -> Self {
+        const TASK_NAME: &str = "miner-committer";
+        tracing::info!(%max_queued, %max_batch, "spawning {}", TASK_NAME);
+
+        let (tx, rx) = mpsc::sync_channel(max_queued.clamp(1, MAX_QUEUED_BLOCKS));
+        let stopping = Arc::new(AtomicBool::new(false));
+        let handle = spawn_thread(TASK_NAME, {
+            let stopping = Arc::clone(&stopping);
+            move || run(miner, rx, max_batch.max(1), stopping)
+        });
+        Self {
+            queue: BlockCommitterQueue { tx },
+            handle,
+            stopping,
+        }
+    }
+
+    /// Queue used to send blocks to the committer thread.
+    pub fn queue(&self) -> BlockCommitterQueue {
+        self.queue.clone()
+    }
+
+    /// Checks if the committer thread is still running. It stops after a block cannot be committed.
+    pub fn is_running(&self) -> bool {
+        not(self.handle.is_finished())
+    }
+
+    /// Commits all queued blocks and stops the committer thread. Blocks failing to be committed are not retried.
+    pub fn stop(self) {
+        self.stopping.store(true, Ordering::Relaxed);
+        drop(self.queue);
+        if self.handle.join().is_err() {
+            tracing::error!("miner committer thread panicked");
+        }
+    }
+}
+
+impl BlockCommitterQueue {
+    /// Queues an unsealed local block to be committed after the blocks already queued. Waits if the queue is full.
+    pub fn enqueue(&self, block: Block) -> anyhow::Result<()> {
+        tracing::info!(block_number = %block.number(), transactions_len = %block.transactions.len(), "queueing block for background commit");
+        self.tx
+            .send(CommitterMessage::Block(block))
+            .map_err(|_| anyhow!("failed to queue block because the committer stopped"))
+    }
+
+    /// Waits until all blocks queued before the call are committed.
+    pub fn flush(&self) -> anyhow::Result<()> {
+        let (flushed_tx, flushed_rx) = mpsc::channel();
+        self.tx
+            .send(CommitterMessage::Flush(flushed_tx))
+            .map_err(|_| anyhow!("failed to flush queued blocks because the committer stopped"))?;
+        flushed_rx
+            .recv()
+            .map_err(|_| anyhow!("failed to flush queued blocks because the committer stopped"))
+    }
+}
+
+fn run(miner: Arc<Miner>, rx: mpsc::Receiver<CommitterMessage>, max_batch: usize, stopping: Arc<AtomicBool>) {
+    // blocks queued after the sender is dropped are still received, so stopping commits everything queued
+    while let Ok(message) = rx.recv() {
+        let mut blocks = Vec::new();
+        let mut flushes = Vec::new();
+        let mut next = Some(message);
+        while let Some(message) = next.take() {
+            match message {
+                CommitterMessage::Block(block) => blocks.push(block),
+                CommitterMessage::Flush(flushed_tx) => flushes.push(flushed_tx),
+            }
+            if blocks.len() < max_batch {
+                next = match rx.try_recv() {
+                    Ok(message) => Some(message),
+                    Err(TryRecvError::Empty | TryRecvError::Disconnected) => None,
+                };
+            }
+        }
+
+        // blocks queued after a failing block cannot be committed because they are sealed on top of it
+        if let Err(e) = commit_batch(&miner, blocks, &stopping) {
+            tracing::error!(reason = ?e, "miner committer stopped because a queued block could not be committed, pausing miner");
+            miner.pause();
+            let discarded = rx
+                .try_iter()
+                .filter_map(|message| match message {
+                    CommitterMessage::Block(block) => Some(block.number()),
+                    CommitterMessage::Flush(_) => None,
+                })
+                .collect::<Vec<_>>();
+            if not(discarded.is_empty()) {
+                tracing::error!(?discarded, "discarding blocks queued after the block that could not be committed");
+            }
+            return;
+        }
+
+        // flushes are acknowledged after all blocks received before them are committed
+        for flushed_tx in flushes {
+            let _ = flushed_tx.send(());
+        }
+    }
+    tracing::warn!("miner committer stopped after committing all queued blocks");
+}
+
+fn commit_batch(miner: &Miner, blocks: Vec<Block>, stopping: &AtomicBool) -> anyhow::Result<()> {
+    let (Some(first), Some(last)) = (blocks.first(), blocks.last()) else {
+        return Ok(());
+    };
+    tracing::info!(from = %first.number(), to = %last.number(), blocks = %blocks.len(), "committing queued blocks");
+    let last_number = last.number();
+
+    for mut block in blocks {
+        let mut attempt = 0;
+        let mut delay = INITIAL_RETRY_DELAY;
+        // blocks are sealed only now because their parent hash and state root depend on the previous block being committed
+        loop {
+            attempt += 1;
+            let result = miner.seal_local_block(&mut block).and_then(|_| miner.commit_or_quarantine(block.clone()));
+            let e = match result {
+                Ok(()) => break,
+                Err(e) => e,
+            };
+            tracing::error!(reason = ?e, block_number = %block.number(), %attempt, "failed to commit queued block even after quarantining its transactions");
+
+            let stopped = stopping.load(Ordering::Relaxed) || GlobalState::is_shutdown();
+            if attempt >= MAX_COMMIT_ATTEMPTS || stopped || not(wait_retry(delay, stopping)) {
+                tracing::error!(from = %block.number(), to = %last_number, %attempt, "giving up committing queued blocks");
+                return Err(e);
+            }
+            delay = (delay * 2).min(MAX_RETRY_DELAY);
+        }
+    }
+    Ok(())
+}
+
+/// Waits before retrying a failed commit. Returns `false` if the committer is stopped while waiting.
+fn wait_retry(delay: Duration, stopping: &AtomicBool) -> bool {
+    const CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+    let start = Instant::now();
+    while start.elapsed() < delay {
+        if stopping.load(Ordering::Relaxed) || GlobalState::is_shutdown() {
+            return false;
+        }
+        std::thread::sleep(CHECK_INTERVAL.min(delay.saturating_sub(start.elapsed())));
+    }
+    true
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::miner::MinerMode;
+    use crate::eth::primitives::BlockFilter;
+    use crate::eth::primitives::BlockNumber;
+    use crate::eth::primitives::Gas;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::InMemoryTemporaryStorage;
+    use crate::eth::storage::StratusStorage;
+
+    #[tokio::test]
+    async fn block_committer_commits_queued_blocks_in_order() {
+        let storage = Arc::new(StratusStorage::new(Box::<InMemoryTemporaryStorage>::default(), Box::<InMemoryPermanentStorage>::default()).unwrap());
+        let miner = Arc::new(Miner::new(Arc::clone(&storage), MinerMode::External, Gas::from(1_000_000u64), 3));
+        miner.start_background_commits(4, 2);
+        let base = storage.read_mined_block_number().unwrap();
+
+        // blocks are queued without being committed
+        for _ in 0..3 {
+            assert!(miner.mine_local_and_enqueue().unwrap());
+        }
+        miner.flush_commits().unwrap();
+        assert_eq!(storage.read_mined_block_number().unwrap(), BlockNumber::from(base.as_u64() + 3));
+
+        // each block is sealed on top of the previous committed block
+        let first = storage.read_block(&BlockFilter::Number(BlockNumber::from(base.as_u64() + 1))).unwrap().unwrap();
+        let second = storage.read_block(&BlockFilter::Number(BlockNumber::from(base.as_u64() + 2))).unwrap().unwrap();
+        assert_eq!(second.header.parent_hash, first.hash());
+        assert_eq!(second.hash(), second.header.compute_hash());
+
+        // stopping commits the remaining blocks and disables background commits
+        assert!(miner.mine_local_and_enqueue().unwrap());
+        miner.stop_background_commits();
+        assert_eq!(storage.read_mined_block_number().unwrap(), BlockNumber::from(base.as_u64() + 4));
+        assert!(!miner.mine_local_and_enqueue().unwrap());
+    }
+}
@@ -0,0 +1,199 @@
+//! Rolling window of transactions and gas per contract.
+//!
+//! Mined transactions are aggregated in one-minute buckets by the contract they called or deployed, so operators can find which
+//! application caused a traffic spike while it is happening. Only the most recent buckets are kept.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use display_json::DebugAsJson;
+
+use crate::eth::codegen;
+use crate::eth::codegen::ContractName;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::Gas;
+use crate::eth::primitives::UnixTime;
+use crate::ext::MutexExt;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
+
+/// Max window that can be queried, which is also the number of one-minute buckets kept.
+pub const CONTRACT_ACTIVITY_MAX_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+const SECONDS_PER_MINUTE: u64 = 60;
+
+/// Transactions and gas of one contract in a window.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractActivityStats {
+    pub address: Address,
+
+    /// Name of the contract if it is known by the node.
+    pub name: ContractName,
+
+    /// Number of transactions calling or deploying the contract.
+    pub transactions: u64,
+
+    /// Gas used by the transactions.
+    pub gas: Gas,
+
+    /// Number of transactions per minute on average in the window.
+    pub transactions_per_minute: u64,
+
+    /// Gas used per minute on average in the window.
+    pub gas_per_minute: Gas,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ContractCounters {
+    transactions: u64,
+    gas: u64,
+}
+
+#[derive(Debug)]
+struct ActivityBucket {
+    /// Minutes since the Unix epoch.
+    minute: u64,
+    contracts: HashMap<Address, ContractCounters>,
+}
+
+/// Transactions and gas per contract of recently mined blocks.
+#[derive(Debug, Default)]
+pub struct ContractActivity {
+    /// Buckets from the oldest to the newest minute.
+    buckets: Mutex<VecDeque<ActivityBucket>>,
+}
+
+impl ContractActivity {
+    /// Adds the transactions of a block mined at `now` to the current minute.
+    pub fn record_block(&self, block: &Block, now: UnixTime) {
+        if block.transactions.is_empty() {
+            return;
+        }
+
+        let minute = now.as_u64() / SECONDS_PER_MINUTE;
+        let mut buckets = self.buckets.lock_or_clear("contract activity lock was poisoned");
+        if buckets.back().map(|bucket| bucket.minute) != Some(minute) {
+            buckets.push_back(ActivityBucket {
+                minute,
+                contracts: HashMap::new(),
+            });
+        }
+        let oldest_minute = minute.saturating_sub(max_window_minutes() - 1);
+        while buckets.front().is_some_and(|bucket| bucket.minute < oldest_minute) {
+            buckets.pop_front();
+        }
+
+        let bucket = buckets.back_mut().expect("bucket was just pushed");
+        for tx in &block.transactions {
+            let Some(contract) = tx.input.to.or(tx.execution.deployed_contract_address) else {
+                continue;
+            };
+            let gas = tx.execution.gas.as_u64();
+            let counters = bucket.contracts.entry(contract).or_default();
+            counters.transactions += 1;
+            counters.gas += gas;
+
+            #[cfg(feature = "metrics")]
+            {
+                let name = codegen::contract_name_for_o11y(&Some(contract));
+                metrics::inc_miner_contract_transactions(name);
+                metrics::inc_n_miner_contract_gas(gas, name);
+            }
+        }
+    }
+
+    /// Lists the contracts with most gas used in the window ending at `now`, from the highest to the lowest.
+    pub fn top_contracts(&self, window: Duration, limit: usize, now: UnixTime) -> Vec<ContractActivityStats> {
+        let window_minutes = window.as_secs().div_ceil(SECONDS_PER_MINUTE).clamp(1, max_window_minutes());
+        let oldest_minute = (now.as_u64() / SECONDS_PER_MINUTE).saturating_sub(window_minutes - 1);
+
+        let mut totals: HashMap<Address, ContractCounters> = HashMap::new();
+        for bucket in self.buckets.lock_or_clear("contract activity lock was poisoned").iter() {
+            if bucket.minute < oldest_minute {
+                continue;
+            }
+            for (address, counters) in &bucket.contracts {
+                let total = totals.entry(*address).or_default();
+                total.transactions += counters.transactions;
+                total.gas += counters.gas;
+            }
+        }
+
+        let mut totals = totals.into_iter().collect::<Vec<_>>();
+        totals.sort_by(|(a_address, a), (b_address, b)| b.gas.cmp(&a.gas).then(b.transactions.cmp(&a.transactions)).then(a_address.0.cmp(&b_address.0)));
+        totals.truncate(limit);
+
+        totals
+            .into_iter()
+            .map(|(address, total)| ContractActivityStats {
+                address,
+                name: codegen::contract_name_for_o11y(&Some(address)),
+                transactions: total.transactions,
+                gas: total.gas.into(),
+                transactions_per_minute: total.transactions / window_minutes,
+                gas_per_minute: (total.gas / window_minutes).into(),
+            })
+            .collect()
+    }
+}
+
+fn max_window_minutes() -> u64 {
+    CONTRACT_ACTIVITY_MAX_WINDOW.as_secs() / SECONDS_PER_MINUTE
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::primitives::BlockNumber;
+    use crate::eth::primitives::TransactionMined;
+    use crate::utils::test_utils::fake_first;
+
+    fn block(calls: &[(Address, u64)]) -> Block {
+        let mut block = Block::new(BlockNumber::ONE, UnixTime::from(1));
+        for (to, gas) in calls {
+            let mut tx = fake_first::<TransactionMined>();
+            tx.input.to = Some(*to);
+            tx.execution.gas = (*gas).into();
+            block.transactions.push(tx);
+        }
+        block
+    }
+
+    #[test]
+    fn contract_activity_ranks_contracts_in_window() {
+        let activity = ContractActivity::default();
+        let a = Address::new([1; 20]);
+        let b = Address::new([2; 20]);
+        let t0 = 1_000 * SECONDS_PER_MINUTE;
+
+        activity.record_block(&block(&[(a, 100), (b, 50)]), UnixTime::from(t0));
+        activity.record_block(&block(&[(b, 50), (b, 50)]), UnixTime::from(t0 + 5 * SECONDS_PER_MINUTE));
+
+        // last minute only sees the second block
+        let top = activity.top_contracts(Duration::from_secs(60), 10, UnixTime::from(t0 + 5 * SECONDS_PER_MINUTE));
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].address, b);
+        assert_eq!(top[0].transactions, 2);
+
+        // wider window sees both blocks
+        let top = activity.top_contracts(Duration::from_secs(10 * 60), 10, UnixTime::from(t0 + 5 * SECONDS_PER_MINUTE));
+        assert_eq!(
+            top.iter().map(|stats| (stats.address, stats.transactions)).collect::<Vec<_>>(),
+            vec![(b, 3), (a, 1)]
+        );
+        assert_eq!(top[0].gas, Gas::from(150u64));
+        assert_eq!(top[0].gas_per_minute, Gas::from(15u64));
+
+        // buckets older than the max window are dropped
+        activity.record_block(&block(&[(a, 1)]), UnixTime::from(t0 + 2 * max_window_minutes() * SECONDS_PER_MINUTE));
+        assert_eq!(activity.buckets.lock_or_clear("test").len(), 1);
+    }
+}
@@ -0,0 +1,157 @@
+use std::sync::Mutex;
+
+use display_json::DebugAsJson;
+use indexmap::IndexMap;
+
+use crate::eth::primitives::ExecutionConflicts;
+use crate::eth::primitives::Gas;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::UnixTime;
+use crate::ext::not;
+use crate::ext::MutexExt;
+
+/// Max number of discarded attempts kept for a single transaction. Older attempts are dropped first.
+const MAX_ATTEMPTS_PER_TRANSACTION: usize = 64;
+
+/// Diagnostics of executions of local transactions that were discarded because they conflicted with the pending block or because the
+/// block containing them failed to be committed.
+///
+/// Allows finding out why a transaction took long to be included in a block. Disabled if the max number of transactions is zero.
+pub struct DiscardedAttempts {
+    /// Max number of transactions tracked. Older transactions are dropped first.
+    max_transactions: usize,
+
+    /// Discarded attempts of each transaction in the order the transactions were first discarded.
+    attempts: Mutex<IndexMap<Hash, Vec<DiscardedAttempt>>>,
+}
+
+/// An execution of a transaction that was discarded.
+#[derive(DebugAsJson, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscardedAttempt {
+    pub hash: Hash,
+    pub attempt: usize,
+    pub reason: DiscardReason,
+    pub discarded_at: UnixTime,
+    pub gas: Gas,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conflicts: Option<ExecutionConflicts>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Why an execution was discarded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, serde::Serialize)]
+pub enum DiscardReason {
+    /// Execution conflicted with the pending block state and the transaction was executed again.
+    #[serde(rename = "conflict")]
+    #[strum(to_string = "conflict")]
+    Conflict,
+
+    /// Block containing the execution failed to be committed.
+    #[serde(rename = "commit")]
+    #[strum(to_string = "commit")]
+    Commit,
+}
+
+impl DiscardedAttempts {
+    pub fn new(max_transactions: usize) -> Self {
+        Self {
+            max_transactions,
+            attempts: Mutex::new(IndexMap::new()),
+        }
+    }
+
+    /// Checks if discarded attempts are being recorded.
+    pub fn is_enabled(&self) -> bool {
+        self.max_transactions > 0
+    }
+
+    /// Records an execution discarded because of conflicts.
+    pub fn record_conflict(&self, hash: Hash, attempt: usize, gas: Gas, conflicts: &ExecutionConflicts) {
+        if not(self.is_enabled()) {
+            return;
+        }
+        self.record(DiscardedAttempt {
+            hash,
+            attempt,
+            reason: DiscardReason::Conflict,
+            discarded_at: UnixTime::now(),
+            gas,
+            conflicts: Some(conflicts.clone()),
+            error: None,
+        });
+    }
+
+    /// Records an execution discarded because its block failed to be committed.
+    pub fn record_commit_failure(&self, hash: Hash, attempt: usize, gas: Gas, error: &str) {
+        if not(self.is_enabled()) {
+            return;
+        }
+        self.record(DiscardedAttempt {
+            hash,
+            attempt,
+            reason: DiscardReason::Commit,
+            discarded_at: UnixTime::now(),
+            gas,
+            conflicts: None,
+            error: Some(error.to_owned()),
+        });
+    }
+
+    /// Lists the discarded attempts of a transaction from the oldest to the newest.
+    pub fn get(&self, hash: &Hash) -> Vec<DiscardedAttempt> {
+        self.attempts
+            .lock_or_clear("discarded attempts lock was poisoned")
+            .get(hash)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn record(&self, attempt: DiscardedAttempt) {
+        tracing::debug!(hash = %attempt.hash, attempt = %attempt.attempt, reason = %attempt.reason, "recording discarded attempt");
+
+        let mut attempts = self.attempts.lock_or_clear("discarded attempts lock was poisoned");
+        if not(attempts.contains_key(&attempt.hash)) && attempts.len() >= self.max_transactions {
+            attempts.shift_remove_index(0);
+        }
+        let tx_attempts = attempts.entry(attempt.hash).or_default();
+        if tx_attempts.len() >= MAX_ATTEMPTS_PER_TRANSACTION {
+            tx_attempts.remove(0);
+        }
+        tx_attempts.push(attempt);
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_attempts_of_most_recent_transactions() {
+        let attempts = DiscardedAttempts::new(2);
+        let (tx1, tx2, tx3) = (Hash::new([1; 32]), Hash::new([2; 32]), Hash::new([3; 32]));
+
+        attempts.record_commit_failure(tx1, 1, Gas::ZERO, "commit");
+        attempts.record_commit_failure(tx2, 1, Gas::ZERO, "commit");
+        attempts.record_commit_failure(tx2, 2, Gas::ZERO, "commit");
+        assert_eq!(attempts.get(&tx2).iter().map(|attempt| attempt.attempt).collect::<Vec<_>>(), vec![1, 2]);
+
+        attempts.record_commit_failure(tx3, 1, Gas::ZERO, "commit");
+        assert!(attempts.get(&tx1).is_empty());
+        assert_eq!(attempts.get(&tx3).len(), 1);
+    }
+
+    #[test]
+    fn disabled_does_not_record() {
+        let attempts = DiscardedAttempts::new(0);
+        let hash = Hash::new([1; 32]);
+        attempts.record_commit_failure(hash, 1, Gas::ZERO, "commit");
+        assert!(attempts.get(&hash).is_empty());
+    }
+}
@@ -0,0 +1,229 @@
+//! Hypothetical fees of mined blocks under a different fee configuration or gas schedule.
+//!
+//! Used to evaluate fee-model changes with the real history of the chain: each block is priced again with the base fee the configured
+//! calculator would have produced and, optionally, with the gas its transactions use when executed again under another EVM spec.
+
+use std::collections::HashMap;
+
+use display_json::DebugAsJson;
+use ethereum_types::U256;
+
+use crate::eth::miner::BaseFee;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::FeeHistoryBlock;
+use crate::eth::primitives::Gas;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::Wei;
+
+/// Accumulates original and hypothetical fees of a sequence of blocks.
+pub struct FeeRepricer {
+    base_fee: BaseFee,
+
+    /// Priority fee paid by all transactions instead of the one they originally paid.
+    priority_fee: Option<Wei>,
+
+    /// Hypothetical fee data of the previous block, used to calculate the base fee of the next one.
+    parent: Option<FeeHistoryBlock>,
+
+    report: FeeRepricingReport,
+    accounts: HashMap<Address, FeeTotals>,
+    contracts: HashMap<Address, FeeTotals>,
+}
+
+impl FeeRepricer {
+    pub fn new(base_fee: BaseFee, priority_fee: Option<Wei>) -> Self {
+        Self {
+            base_fee,
+            priority_fee,
+            parent: None,
+            report: FeeRepricingReport::default(),
+            accounts: HashMap::new(),
+            contracts: HashMap::new(),
+        }
+    }
+
+    /// Prices a block again. Blocks must be added in order.
+    ///
+    /// `repriced_gas` has the gas used by transactions executed again under another gas schedule. Transactions not present keep the gas
+    /// they used when mined.
+    pub fn add_block(&mut self, block: &Block, repriced_gas: &HashMap<Hash, Gas>) {
+        let base_fee = self.base_fee.next(self.parent.as_ref());
+
+        let mut block_gas_used = 0;
+        let mut priority_fees = Vec::with_capacity(block.transactions.len());
+        for tx in &block.transactions {
+            let original_gas = tx.execution.gas;
+            let gas = repriced_gas.get(&tx.input.hash).copied().unwrap_or(original_gas);
+            let priority_fee = match self.priority_fee {
+                Some(priority_fee) => priority_fee,
+                None => tx.input.gas_price.max(block.header.base_fee_per_gas) - block.header.base_fee_per_gas,
+            };
+
+            let tx_totals = FeeTotals {
+                transactions: 1,
+                original_gas: original_gas.as_u64(),
+                repriced_gas: gas.as_u64(),
+                original_fees: fee(original_gas, tx.input.gas_price),
+                repriced_fees: fee(gas, base_fee + priority_fee),
+            };
+            self.report.totals.add(&tx_totals);
+            self.accounts.entry(tx.input.signer).or_default().add(&tx_totals);
+            if let Some(contract) = tx.input.to.or(tx.execution.deployed_contract_address) {
+                self.contracts.entry(contract).or_default().add(&tx_totals);
+            }
+
+            block_gas_used += gas.as_u64();
+            priority_fees.push((priority_fee, gas));
+        }
+        priority_fees.sort_by_key(|(priority_fee, _)| *priority_fee);
+
+        // the next base fee follows the hypothetical gas used, not the original one
+        let mut parent = FeeHistoryBlock::from_block(block);
+        parent.base_fee_per_gas = base_fee;
+        parent.gas_used = Gas::from(block_gas_used);
+        parent.priority_fees = priority_fees;
+        self.parent = Some(parent);
+
+        let block_number = block.number();
+        self.report.block_start.get_or_insert(block_number);
+        self.report.block_end = Some(block_number);
+        self.report.blocks += 1;
+    }
+
+    /// Finishes the report with accounts and contracts sorted by the highest hypothetical fees.
+    pub fn finish(self) -> FeeRepricingReport {
+        let mut report = self.report;
+        report.accounts = sorted_entries(self.accounts);
+        report.contracts = sorted_entries(self.contracts);
+        report
+    }
+}
+
+fn fee(gas: Gas, price: Wei) -> Wei {
+    Wei::new(U256::from(gas.as_u64()).saturating_mul(price.0))
+}
+
+fn sorted_entries(totals: HashMap<Address, FeeTotals>) -> Vec<FeeRepricingEntry> {
+    let mut entries = totals
+        .into_iter()
+        .map(|(address, totals)| FeeRepricingEntry { address, totals })
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| b.totals.repriced_fees.cmp(&a.totals.repriced_fees).then(a.address.0.cmp(&b.address.0)));
+    entries
+}
+
+// -----------------------------------------------------------------------------
+// Report
+// -----------------------------------------------------------------------------
+
+/// Original and hypothetical fees of a range of blocks.
+#[derive(DebugAsJson, Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeRepricingReport {
+    pub block_start: Option<BlockNumber>,
+    pub block_end: Option<BlockNumber>,
+    pub blocks: u64,
+    pub totals: FeeTotals,
+
+    /// Fees by transaction signer.
+    pub accounts: Vec<FeeRepricingEntry>,
+
+    /// Fees by called or deployed contract.
+    pub contracts: Vec<FeeRepricingEntry>,
+}
+
+#[derive(DebugAsJson, Clone, serde::Serialize)]
+pub struct FeeRepricingEntry {
+    pub address: Address,
+
+    #[serde(flatten)]
+    pub totals: FeeTotals,
+}
+
+#[derive(DebugAsJson, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeTotals {
+    pub transactions: u64,
+    pub original_gas: u64,
+    pub repriced_gas: u64,
+    pub original_fees: Wei,
+    pub repriced_fees: Wei,
+}
+
+impl FeeTotals {
+    fn add(&mut self, other: &FeeTotals) {
+        self.transactions += other.transactions;
+        self.original_gas += other.original_gas;
+        self.repriced_gas += other.repriced_gas;
+        self.original_fees = Wei::new(self.original_fees.0.saturating_add(other.original_fees.0));
+        self.repriced_fees = Wei::new(self.repriced_fees.0.saturating_add(other.repriced_fees.0));
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::miner::BaseFeeConfig;
+    use crate::eth::miner::BaseFeeMode;
+    use crate::eth::primitives::TransactionMined;
+    use crate::eth::primitives::UnixTime;
+    use crate::utils::test_utils::fake_first;
+
+    fn block(number: u64, txs: &[(Address, Address, u64, u64)]) -> Block {
+        let mut block = Block::new(BlockNumber::from(number), UnixTime::from(number));
+        for (signer, to, gas, gas_price) in txs {
+            let mut tx = fake_first::<TransactionMined>();
+            tx.input.hash = fake_first::<Hash>();
+            tx.input.signer = *signer;
+            tx.input.to = Some(*to);
+            tx.input.gas_price = Wei::from(*gas_price);
+            tx.execution.gas = Gas::from(*gas);
+            block.transactions.push(tx);
+        }
+        block
+    }
+
+    #[test]
+    fn fee_repricer_applies_base_fee_and_repriced_gas() {
+        let config = BaseFeeConfig {
+            base_fee_mode: BaseFeeMode::Eip1559,
+            base_fee: 100,
+            base_fee_target_gas: Some(1_000),
+            base_fee_elasticity: 2,
+            base_fee_denominator: 8,
+        };
+        let alice = Address::new([1; 20]);
+        let bob = Address::new([2; 20]);
+        let contract = Address::new([3; 20]);
+
+        let mut repricer = FeeRepricer::new(config.build(2_000).unwrap(), Some(Wei::from(1u64)));
+
+        // first block starts from the initial base fee and uses twice the target gas
+        let first = block(1, &[(alice, contract, 2_000, 0)]);
+        repricer.add_block(&first, &HashMap::new());
+
+        // second block base fee increases by 1/8 and its transaction is executed again with less gas
+        let second = block(2, &[(bob, contract, 500, 10)]);
+        let repriced_gas = HashMap::from([(second.transactions[0].input.hash, Gas::from(400u64))]);
+        repricer.add_block(&second, &repriced_gas);
+
+        let report = repricer.finish();
+        assert_eq!(report.blocks, 2);
+        assert_eq!(report.block_start, Some(BlockNumber::from(1u64)));
+        assert_eq!(report.block_end, Some(BlockNumber::from(2u64)));
+        assert_eq!(report.totals.original_gas, 2_500);
+        assert_eq!(report.totals.repriced_gas, 2_400);
+        assert_eq!(report.totals.original_fees, Wei::from(5_000u64));
+        assert_eq!(report.totals.repriced_fees, Wei::from(2_000u64 * 101 + 400 * 113));
+
+        assert_eq!(report.accounts.iter().map(|entry| entry.address).collect::<Vec<_>>(), vec![alice, bob]);
+        assert_eq!(report.contracts.len(), 1);
+        assert_eq!(report.contracts[0].totals, report.totals);
+    }
+}
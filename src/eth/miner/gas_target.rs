@@ -0,0 +1,175 @@
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use clap::Parser;
+use display_json::DebugAsJson;
+
+use crate::eth::primitives::Gas;
+use crate::ext::parse_duration;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
+
+/// Gas used by a block, in percent of the target, above which the block is considered full.
+const FULL_BLOCK_PERCENT: u64 = 90;
+
+/// Number of steps the target takes to grow from the minimum to the block gas limit.
+const INCREASE_STEPS: u64 = 20;
+
+/// Adjusts the gas target of local blocks to keep block commits within a latency SLO.
+///
+/// Works as an AIMD controller: the target decreases by a quarter when a block takes longer than the SLO to commit or when too many
+/// executions conflicted while it was pending, and increases by a fixed step when full blocks commit comfortably within the SLO.
+#[derive(Debug)]
+pub struct GasTarget {
+    /// Max time to commit a local block.
+    slo: Duration,
+
+    /// Lowest gas target.
+    min: u64,
+
+    /// Highest gas target, which is the block gas limit.
+    max: u64,
+
+    /// Percentage of conflicting executions above which the target decreases.
+    max_conflict_percent: u64,
+
+    /// Current gas target.
+    current: AtomicU64,
+
+    /// Conflicts since the last committed block.
+    conflicts: AtomicU64,
+}
+
+impl GasTarget {
+    /// Current gas target of local blocks.
+    pub fn current(&self) -> Gas {
+        Gas::from(self.current.load(Ordering::Relaxed))
+    }
+
+    /// Records a local execution that conflicted with the pending block.
+    pub fn record_conflict(&self) {
+        self.conflicts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Adjusts the target after a local block is committed. Returns the new target.
+    pub fn record_block(&self, transactions: usize, gas_used: Gas, commit_latency: Duration) -> Gas {
+        let conflicts = self.conflicts.swap(0, Ordering::Relaxed);
+        let executions = transactions as u64 + conflicts;
+        let conflict_percent = if executions == 0 { 0 } else { conflicts * 100 / executions };
+
+        let current = self.current.load(Ordering::Relaxed);
+        let next = if commit_latency > self.slo || conflict_percent > self.max_conflict_percent {
+            (current - current / 4).max(self.min)
+        } else if gas_used.as_u64() >= current * FULL_BLOCK_PERCENT / 100 && commit_latency <= self.slo / 2 {
+            (current + self.increase_step()).min(self.max)
+        } else {
+            current
+        };
+
+        if next != current {
+            tracing::info!(%current, %next, commit_latency_ms = %commit_latency.as_millis(), %conflict_percent, "adjusting block gas target");
+            self.current.store(next, Ordering::Relaxed);
+        }
+        #[cfg(feature = "metrics")]
+        metrics::set_miner_block_gas_target(next);
+
+        Gas::from(next)
+    }
+
+    fn increase_step(&self) -> u64 {
+        ((self.max - self.min) / INCREASE_STEPS).max(1)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Config
+// -----------------------------------------------------------------------------
+
+#[derive(DebugAsJson, Clone, Parser, serde::Serialize)]
+pub struct GasTargetConfig {
+    /// Max time to commit a local block. When set, the gas target of local blocks is tuned automatically to keep commits within it.
+    #[arg(long = "block-gas-target-slo", env = "BLOCK_GAS_TARGET_SLO", value_parser = parse_duration)]
+    pub gas_target_slo: Option<Duration>,
+
+    /// Lowest gas target of local blocks when tuning is enabled.
+    #[arg(long = "block-gas-target-min", env = "BLOCK_GAS_TARGET_MIN", default_value = "1000000")]
+    pub gas_target_min: u64,
+
+    /// Percentage of local executions conflicting with the pending block above which the gas target decreases.
+    #[arg(long = "block-gas-target-max-conflicts", env = "BLOCK_GAS_TARGET_MAX_CONFLICTS", default_value = "10")]
+    pub gas_target_max_conflict_percent: u64,
+}
+
+impl GasTargetConfig {
+    /// Creates the [`GasTarget`] controller for blocks limited to the specified gas, if tuning is enabled.
+    ///
+    /// The target starts at the block gas limit.
+    pub fn build(&self, block_gas_limit: u64) -> anyhow::Result<Option<GasTarget>> {
+        let Some(slo) = self.gas_target_slo else {
+            return Ok(None);
+        };
+
+        if slo.is_zero() {
+            return Err(anyhow!("block gas target slo must be greater than zero"));
+        }
+        if self.gas_target_min == 0 || self.gas_target_min > block_gas_limit {
+            return Err(anyhow!("block gas target min must be greater than zero and at most the block gas limit"));
+        }
+
+        Ok(Some(GasTarget {
+            slo,
+            min: self.gas_target_min,
+            max: block_gas_limit,
+            max_conflict_percent: self.gas_target_max_conflict_percent,
+            current: AtomicU64::new(block_gas_limit),
+            conflicts: AtomicU64::new(0),
+        }))
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gas_target_follows_commit_latency_and_conflicts() {
+        let config = GasTargetConfig {
+            gas_target_slo: Some(Duration::from_millis(100)),
+            gas_target_min: 1_000_000,
+            gas_target_max_conflict_percent: 10,
+        };
+        let target = config.build(21_000_000).unwrap().unwrap();
+        assert_eq!(target.current(), Gas::from(21_000_000u64));
+
+        // slow commit decreases by a quarter
+        let next = target.record_block(100, Gas::from(21_000_000u64), Duration::from_millis(150));
+        assert_eq!(next, Gas::from(15_750_000u64));
+
+        // fast full block increases by one step
+        let next = target.record_block(100, Gas::from(15_000_000u64), Duration::from_millis(10));
+        assert_eq!(next, Gas::from(16_750_000u64));
+
+        // fast block that is not full keeps the target
+        let next = target.record_block(10, Gas::from(1_000_000u64), Duration::from_millis(10));
+        assert_eq!(next, Gas::from(16_750_000u64));
+
+        // too many conflicts decrease even when fast
+        for _ in 0..20 {
+            target.record_conflict();
+        }
+        let next = target.record_block(80, Gas::from(16_750_000u64), Duration::from_millis(10));
+        assert_eq!(next, Gas::from(12_562_500u64));
+
+        // never below the minimum
+        for _ in 0..20 {
+            target.record_block(1, Gas::ZERO, Duration::from_secs(1));
+        }
+        assert_eq!(target.current(), Gas::from(1_000_000u64));
+    }
+}
@@ -1,10 +1,14 @@
+#[cfg(feature = "artifacts")]
+use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::sync::mpsc;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::RwLock;
+use std::sync::Weak;
 use std::time::Duration;
+use std::time::Instant;
 
 use anyhow::anyhow;
 use itertools::Itertools;
@@ -15,12 +19,29 @@ use tokio::task::JoinSet;
 use tokio_util::sync::CancellationToken;
 use tracing::Span;
 
+use crate::eth::executor::ExecutionHooks;
+use crate::eth::executor::Executor;
+use crate::eth::miner::BaseFee;
+#[cfg(feature = "artifacts")]
+use crate::eth::miner::BlockArtifact;
+use crate::eth::miner::BlockCommitter;
+use crate::eth::miner::BlockCommitterQueue;
+use crate::eth::miner::ContractActivity;
+use crate::eth::miner::DiscardedAttempts;
+use crate::eth::miner::GasTarget;
 use crate::eth::miner::MinerMode;
+use crate::eth::miner::QuarantineReason;
+use crate::eth::miner::TransactionQuarantine;
+use crate::eth::primitives::logs_bloom::LogsBloom;
 use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::BlockHeader;
+use crate::eth::primitives::BlockHeaderCompact;
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::ExternalBlock;
 use crate::eth::primitives::ExternalTransactionExecution;
+use crate::eth::primitives::FeeHistoryBlock;
+use crate::eth::primitives::Gas;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::Index;
 use crate::eth::primitives::LocalTransactionExecution;
@@ -29,9 +50,14 @@ use crate::eth::primitives::Size;
 use crate::eth::primitives::StratusError;
 use crate::eth::primitives::TransactionExecution;
 use crate::eth::primitives::TransactionMined;
+use crate::eth::primitives::TransactionOptions;
 use crate::eth::primitives::UnixTime;
+use crate::eth::primitives::Wei;
+use crate::eth::storage::receipts_root;
+use crate::eth::storage::ChainHeadEvent;
 use crate::eth::storage::StratusStorage;
 use crate::ext::not;
+use crate::ext::spawn_thread;
 use crate::ext::DisplayExt;
 use crate::ext::MutexExt;
 use crate::ext::MutexResultExt;
@@ -57,15 +83,60 @@ pub struct Miner {
     /// Mode the block miner is running.
     mode: RwLock<MinerMode>,
 
+    /// Maximum cumulative gas of the transactions in a local block.
+    block_gas_limit: Gas,
+
+    /// Calculates the base fee of local blocks.
+    base_fee: BaseFee,
+
+    /// Fee data of the last mined local block.
+    ///
+    /// Blocks can be committed after the next block starts, so the base fee of the pending block is calculated from it instead of
+    /// the committed blocks.
+    last_mined_fees: Mutex<Option<FeeHistoryBlock>>,
+
+    /// Tunes the gas target of local blocks below the block gas limit, if enabled.
+    pub gas_target: Option<GasTarget>,
+
+    /// Transactions that repeatedly failed to be committed or executed.
+    pub quarantine: TransactionQuarantine,
+
+    /// Executions of local transactions discarded before being included in a block.
+    pub discarded_attempts: DiscardedAttempts,
+
+    /// Transactions and gas per contract of recently committed blocks.
+    pub contract_activity: ContractActivity,
+
+    /// Callbacks registered by embedding applications.
+    pub hooks: ExecutionHooks,
+
+    /// Directory where execution artifacts of committed blocks are written.
+    #[cfg(feature = "artifacts")]
+    artifacts_dir: Option<PathBuf>,
+
+    /// Commits local blocks in background, if enabled.
+    committer: Mutex<Option<BlockCommitter>>,
+
+    /// Executes again the transactions moved to the next block that conflict with the block they were removed from.
+    ///
+    /// Weak because the executor also holds the miner.
+    reexecutor: Mutex<Option<Weak<dyn Executor>>>,
+
     /// Broadcasts pending transactions events.
     pub notifier_pending_txs: broadcast::Sender<Hash>,
 
     /// Broadcasts new mined blocks events.
     pub notifier_blocks: broadcast::Sender<BlockHeader>,
 
+    /// Broadcasts new mined blocks events in compact binary format, sent before any other block notification.
+    pub notifier_blocks_compact: broadcast::Sender<BlockHeaderCompact>,
+
     /// Broadcasts transaction logs events.
     pub notifier_logs: broadcast::Sender<LogMined>,
 
+    /// Broadcasts mined transactions events.
+    pub notifier_transactions: broadcast::Sender<TransactionMined>,
+
     // -------------------------------------------------------------------------
     // Shutdown
     // -------------------------------------------------------------------------
@@ -86,21 +157,67 @@ pub struct MinerLocks {
 }
 
 impl Miner {
-    pub fn new(storage: Arc<StratusStorage>, mode: MinerMode) -> Self {
-        tracing::info!(?mode, "creating block miner");
+    pub fn new(storage: Arc<StratusStorage>, mode: MinerMode, block_gas_limit: Gas, quarantine_attempts: usize) -> Self {
+        tracing::info!(?mode, %block_gas_limit, %quarantine_attempts, "creating block miner");
         Self {
             locks: MinerLocks::default(),
             storage,
             is_paused: AtomicBool::new(false),
             mode: mode.into(),
+            block_gas_limit,
+            base_fee: BaseFee::DISABLED,
+            last_mined_fees: Mutex::new(None),
+            gas_target: None,
+            quarantine: TransactionQuarantine::new(quarantine_attempts),
+            discarded_attempts: DiscardedAttempts::new(0),
+            contract_activity: ContractActivity::default(),
+            hooks: ExecutionHooks::default(),
+            #[cfg(feature = "artifacts")]
+            artifacts_dir: None,
+            committer: Mutex::new(None),
+            reexecutor: Mutex::new(None),
             notifier_pending_txs: broadcast::channel(u16::MAX as usize).0,
             notifier_blocks: broadcast::channel(u16::MAX as usize).0,
+            notifier_blocks_compact: broadcast::channel(u16::MAX as usize).0,
             notifier_logs: broadcast::channel(u16::MAX as usize).0,
+            notifier_transactions: broadcast::channel(u16::MAX as usize).0,
             shutdown_signal: Mutex::new(STRATUS_SHUTDOWN_SIGNAL.child_token()),
             interval_joinset: AsyncMutex::new(None),
         }
     }
 
+    /// Calculates the base fee of local blocks with the specified calculator instead of leaving it zero.
+    pub fn with_base_fee(mut self, base_fee: BaseFee) -> Self {
+        tracing::info!(?base_fee, "configuring block base fee");
+        self.base_fee = base_fee;
+        self
+    }
+
+    /// Tunes the gas target of local blocks according to their commit latency and conflicts.
+    pub fn with_gas_target(mut self, gas_target: Option<GasTarget>) -> Self {
+        self.gas_target = gas_target;
+        self
+    }
+
+    /// Keeps the discarded execution attempts of the specified number of most recent transactions for diagnostics.
+    pub fn with_discarded_attempts(mut self, max_transactions: usize) -> Self {
+        self.discarded_attempts = DiscardedAttempts::new(max_transactions);
+        self
+    }
+
+    /// Writes an execution artifact of each committed block to the specified directory.
+    #[cfg(feature = "artifacts")]
+    pub fn with_artifacts_dir(mut self, dir: PathBuf) -> Self {
+        self.artifacts_dir = Some(dir);
+        self
+    }
+
+    /// Sets the executor used to execute again the transactions that exceed the block gas target and conflict when moved to the next
+    /// block.
+    pub fn set_reexecutor(&self, executor: &Arc<dyn Executor>) {
+        *self.reexecutor.lock_or_clear("miner reexecutor lock was poisoned") = Some(Arc::downgrade(executor));
+    }
+
     /// Spawns a new thread that keep mining blocks in the specified interval.
     ///
     /// Also unpauses `Miner` if it was paused.
@@ -131,6 +248,45 @@ impl Miner {
         *self.interval_joinset.lock().await = Some(joinset);
     }
 
+    /// Spawns a thread that seals and commits local blocks in background, so mining a local block does not wait for the permanent storage.
+    pub fn start_background_commits(self: &Arc<Self>, max_queued: usize, max_batch: usize) {
+        let mut committer = self.committer.lock_or_clear("miner committer lock was poisoned");
+        if committer.as_ref().is_some_and(BlockCommitter::is_running) {
+            tracing::warn!("tried to start background commits, but they are already running, skipping");
+            return;
+        }
+        // committers stopped by a commit failure are replaced
+        if let Some(stopped) = committer.take() {
+            stopped.stop();
+        }
+        *committer = Some(BlockCommitter::spawn(Arc::clone(self), max_queued, max_batch));
+    }
+
+    /// Waits until all local blocks queued for background commit are committed. Does nothing if background commits are disabled.
+    pub fn flush_commits(&self) -> anyhow::Result<()> {
+        match self.committer_queue() {
+            Some(queue) => queue.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Queue of the background committer, cloned so the committer lock is not held while waiting for the queue.
+    fn committer_queue(&self) -> Option<BlockCommitterQueue> {
+        self.committer
+            .lock_or_clear("miner committer lock was poisoned")
+            .as_ref()
+            .map(BlockCommitter::queue)
+    }
+
+    /// Commits all local blocks queued for background commit and stops committing in background.
+    pub fn stop_background_commits(&self) {
+        let committer = self.committer.lock_or_clear("miner committer lock was poisoned").take();
+        if let Some(committer) = committer {
+            tracing::warn!("stopping background commits");
+            committer.stop();
+        }
+    }
+
     /// Shuts down interval miner, set miner mode to External.
     pub async fn switch_to_external_mode(self: &Arc<Self>) {
         if self.mode().is_external() {
@@ -138,10 +294,36 @@ impl Miner {
             return;
         }
         self.shutdown_and_wait().await;
+        if let Err(e) = self.flush_commits() {
+            tracing::error!(reason = ?e, "failed to flush background commits before switching to external mode");
+        }
         self.set_mode(MinerMode::External);
         self.unpause();
     }
 
+    /// Switches between automine and manual mining, where executed transactions stay pending until a block is mined on demand with
+    /// [`Self::mine_local_and_commit`].
+    ///
+    /// Manual mining uses the external mode. Disabling automine when the interval miner is running does nothing, because transactions
+    /// already do not trigger mining.
+    pub async fn set_automine(self: &Arc<Self>, enabled: bool) {
+        match (enabled, self.mode()) {
+            (true, MinerMode::Automine) | (false, MinerMode::External | MinerMode::Interval(_)) => {
+                tracing::warn!(%enabled, mode = ?self.mode(), "trying to change automine, but it's already set, skipping");
+            }
+            (true, _) => {
+                tracing::info!("enabling automine");
+                self.shutdown_and_wait().await;
+                self.set_mode(MinerMode::Automine);
+                self.unpause();
+            }
+            (false, MinerMode::Automine) => {
+                tracing::info!("disabling automine");
+                self.set_mode(MinerMode::External);
+            }
+        }
+    }
+
     // Unpause interval miner (if in interval mode)
     pub fn unpause(&self) {
         self.is_paused.store(false, Ordering::Relaxed);
@@ -157,6 +339,19 @@ impl Miner {
         self.is_paused.load(Ordering::Relaxed)
     }
 
+    /// Maximum gas of the transactions included in each mined block.
+    pub fn block_gas_limit(&self) -> Gas {
+        self.block_gas_limit
+    }
+
+    /// Maximum gas of the transactions included in the next local block, which is below the block gas limit when it is being tuned.
+    pub fn block_gas_target(&self) -> Gas {
+        match self.gas_target {
+            Some(ref gas_target) => gas_target.current(),
+            None => self.block_gas_limit,
+        }
+    }
+
     pub fn mode(&self) -> MinerMode {
         *self.mode.read().unwrap_or_else(|poison_error| {
             tracing::error!("miner mode read lock was poisoned");
@@ -183,7 +378,7 @@ impl Miner {
     }
 
     /// Shutdown if miner is interval miner.
-    async fn shutdown_and_wait(&self) {
+    pub async fn shutdown_and_wait(&self) {
         // Note: we are intentionally holding this mutex till the end of the function, so that
         // subsequent calls wait for the first to finish, and `is_interval_miner_running` works too
         let mut joinset_lock = self.interval_joinset.lock().await;
@@ -192,7 +387,7 @@ impl Miner {
             return;
         };
 
-        tracing::warn!("Shutting down interval miner to switch to external mode");
+        tracing::warn!("shutting down interval miner");
 
         self.shutdown_signal.lock_or_clear("sending shutdown signal to interval miner").cancel();
 
@@ -255,8 +450,8 @@ impl Miner {
         // lock
         let _mine_lock = self.locks.mine.lock().map_lock_error("mine_external")?;
 
-        // mine block
-        let block = self.storage.finish_pending_block()?;
+        // mine block (external blocks are mined as received, so they are not limited by gas)
+        let (block, _) = self.storage.finish_pending_block(Gas::MAX)?;
         Span::with(|s| s.rec_str("block_number", &block.header.number));
         let Some(external_block) = block.external_block else {
             return log_and_err!("failed to mine external block because there is no external block being reexecuted");
@@ -278,26 +473,56 @@ impl Miner {
 
     /// Same as [`Self::mine_local`], but automatically commits the block instead of returning it.
     /// mainly used when is_automine is enabled.
+    ///
+    /// If background commits are enabled, the block is queued and committed later.
     pub fn mine_local_and_commit(&self) -> anyhow::Result<()> {
         let _mine_and_commit_lock = self.locks.mine_and_commit.lock().map_lock_error("mine_local_and_commit")?;
 
+        if self.mine_local_and_enqueue()? {
+            return Ok(());
+        }
         let block = self.mine_local()?;
-        self.commit(block)
+        self.commit_or_quarantine(block)
+    }
+
+    /// Mines local transactions and queues the block to be sealed and committed in background.
+    ///
+    /// Returns `false` without mining if background commits are disabled.
+    pub(super) fn mine_local_and_enqueue(&self) -> anyhow::Result<bool> {
+        let Some(queue) = self.committer_queue() else {
+            return Ok(false);
+        };
+        let block = self.mine_local_unsealed()?;
+        queue.enqueue(block)?;
+        Ok(true)
     }
 
     /// Mines local transactions.
     ///
     /// External transactions are not allowed to be part of the block.
+    ///
+    /// Transactions exceeding the block gas target are left pending for the next block.
     pub fn mine_local(&self) -> anyhow::Result<Block> {
+        let mut block = self.mine_local_unsealed()?;
+        self.seal_local_block(&mut block)?;
+        Ok(block)
+    }
+
+    /// Same as [`Self::mine_local`], but leaves the block to be sealed with [`Self::seal_local_block`] before it is committed.
+    fn mine_local_unsealed(&self) -> anyhow::Result<Block> {
         #[cfg(feature = "tracing")]
         let _span = info_span!("miner::mine_local", block_number = field::Empty).entered();
 
         // lock
         let _mine_lock = self.locks.mine.lock().map_lock_error("mine_local")?;
 
+        // the pending block number and the fee data of its parent change together, so executions never see one without the other
+        let mut last_mined_fees = self.last_mined_fees.lock_or_clear("miner last mined fees lock was poisoned");
+
         // mine block
-        let block = self.storage.finish_pending_block()?;
+        let (block, conflicting_txs) = self.storage.finish_pending_block(self.block_gas_target())?;
         Span::with(|s| s.rec_str("block_number", &block.header.number));
+        self.reexecute(conflicting_txs);
 
         // mine transactions
         let mut local_txs = Vec::with_capacity(block.transactions.len());
@@ -309,12 +534,160 @@ impl Miner {
             }
         }
 
-        block_from_local(block.header.number, local_txs)
+        let mut block = block_from_local(block.header.number, local_txs)?;
+        block.header.base_fee_per_gas = self.calculate_base_fee(block.number(), last_mined_fees.as_ref())?;
+        *last_mined_fees = Some(FeeHistoryBlock::from_block(&block));
+        Ok(block)
+    }
+
+    /// Executes again, in background and in order, transactions moved to the next block that conflict with the finished block.
+    ///
+    /// Runs in another thread because executing a transaction may wait for the mining that is in progress.
+    fn reexecute(&self, txs: Vec<TransactionExecution>) {
+        if txs.is_empty() {
+            return;
+        }
+        let executor = self
+            .reexecutor
+            .lock_or_clear("miner reexecutor lock was poisoned")
+            .as_ref()
+            .and_then(Weak::upgrade);
+        let Some(executor) = executor else {
+            tracing::error!(txs = %txs.len(), "discarding conflicting transactions moved to next block because there is no executor");
+            return;
+        };
+
+        spawn_thread("miner::reexecutor", move || {
+            for tx in txs {
+                let TransactionExecution::Local(tx) = tx else { continue };
+                let tx_hash = tx.input.hash;
+                if let Err(e) = executor.execute_local_transaction(tx.input, TransactionOptions::default()) {
+                    tracing::warn!(reason = ?e, %tx_hash, "failed to execute again transaction moved to next block");
+                }
+            }
+        });
+    }
+
+    /// Links a local block to its parent and calculates its state root and hash.
+    ///
+    /// Must be called after all other header fields are set and after the parent block is committed, because the hash is calculated
+    /// from the header and the other fields depend on the parent. Fails if the parent block cannot be read.
+    pub(super) fn seal_local_block(&self, block: &mut Block) -> anyhow::Result<()> {
+        if let Some(parent_number) = block.number().prev() {
+            let Some(parent) = self.storage.read_block(&BlockFilter::Number(parent_number))? else {
+                return log_and_err!(payload = block.number(), "failed to seal local block because its parent block was not found");
+            };
+            block.header.parent_hash = parent.hash();
+        }
+        if let Some(state_root) = self.storage.read_state_root_after(block)? {
+            block.header.state_root = state_root;
+        }
+        block.update_hash();
+        Ok(())
+    }
+
+    /// Calculator of the base fee of local blocks.
+    pub fn base_fee(&self) -> &BaseFee {
+        &self.base_fee
+    }
+
+    /// Returns the number and the base fee of the pending block, where local transactions are executed.
+    pub fn pending_base_fee(&self) -> anyhow::Result<(BlockNumber, Wei)> {
+        let last_mined_fees = self.last_mined_fees.lock_or_clear("miner last mined fees lock was poisoned");
+        let number = self.storage.read_pending_block_number()?.unwrap_or_default();
+        let base_fee = self.calculate_base_fee(number, last_mined_fees.as_ref())?;
+        Ok((number, base_fee))
+    }
+
+    /// Calculates the base fee of a local block from the fee data of its parent, which is the last mined block or a committed block.
+    fn calculate_base_fee(&self, number: BlockNumber, last_mined_fees: Option<&FeeHistoryBlock>) -> anyhow::Result<Wei> {
+        if not(self.base_fee.mode().is_eip1559()) {
+            return Ok(self.base_fee.next(None));
+        }
+        let Some(parent_number) = number.prev() else {
+            return Ok(self.base_fee.next(None));
+        };
+        if let Some(parent) = last_mined_fees.filter(|fees| fees.number == parent_number) {
+            return Ok(self.base_fee.next(Some(parent)));
+        }
+        let parent = self.storage.read_fee_history(parent_number, 1)?;
+        Ok(self.base_fee.next(parent.last()))
+    }
+
+    /// Same as [`Self::commit`], but retries failed commits and quarantines the transactions of blocks that cannot be committed.
+    ///
+    /// The miner is paused while the block is failing. After the configured number of attempts, the transactions of the block are
+    /// quarantined and an empty block is committed in its place, so a poison transaction does not wedge block production.
+    ///
+    /// Fails only if the empty block also cannot be committed, in which case the miner is left paused.
+    pub fn commit_or_quarantine(&self, mut block: Block) -> anyhow::Result<()> {
+        let start = Instant::now();
+        let mut paused_by_error = false;
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+
+            let e = match self.commit(block.clone()) {
+                Ok(()) => {
+                    if paused_by_error {
+                        tracing::warn!(block_number = %block.number(), "block committed after failures, resuming miner");
+                        self.unpause();
+                    }
+                    if let Some(ref gas_target) = self.gas_target {
+                        gas_target.record_block(block.transactions.len(), block.header.gas_used, start.elapsed());
+                    }
+                    return Ok(());
+                }
+                Err(e) => e,
+            };
+            tracing::error!(reason = ?e, block_number = %block.number(), %attempt, "failed to commit block");
+
+            // pause on error so the interval miner does not keep finishing blocks that cannot be committed
+            if not(self.is_paused()) {
+                self.pause();
+                paused_by_error = true;
+            }
+
+            // an empty block failing is not caused by transactions
+            if block.transactions.is_empty() {
+                return Err(e);
+            }
+            if self.discarded_attempts.is_enabled() {
+                let error = e.to_string();
+                for tx in &block.transactions {
+                    self.discarded_attempts.record_commit_failure(tx.input.hash, attempt, tx.execution.gas, &error);
+                }
+            }
+
+            // give up on the transactions and replace the block with an empty one
+            if attempt >= self.quarantine.max_attempts() {
+                let error = e.to_string();
+                for tx in &block.transactions {
+                    self.quarantine
+                        .quarantine(tx.input.hash, tx.input.signer, QuarantineReason::Commit, attempt, error.clone());
+                }
+                let base_fee_per_gas = block.header.base_fee_per_gas;
+                block = Block::new(block.number(), block.header.timestamp);
+                block.header.base_fee_per_gas = base_fee_per_gas;
+                self.seal_local_block(&mut block)?;
+            }
+        }
     }
 
     /// Persists a mined block to permanent storage and prepares new block.
     pub fn commit(&self, block: Block) -> anyhow::Result<()> {
+        self.do_commit(block, false)
+    }
+
+    /// Persists a block mined by the leader and received through replication, then notifies subscribers like a mined block.
+    pub fn commit_replicated(&self, block: Block) -> anyhow::Result<()> {
+        let _mine_and_commit_lock = self.locks.mine_and_commit.lock().map_lock_error("commit_replicated")?;
+        self.do_commit(block, true)
+    }
+
+    fn do_commit(&self, block: Block, replicated: bool) -> anyhow::Result<()> {
         let block_number = block.number();
+        let block_hash = block.hash();
 
         // track
         #[cfg(feature = "tracing")]
@@ -327,6 +700,11 @@ impl Miner {
         tracing::info!(%block_number, "miner acquired commit lock");
 
         // extract fields to use in notifications if have subscribers
+        let block_header_compact = if self.notifier_blocks_compact.receiver_count() > 0 {
+            Some(BlockHeaderCompact::from(&block.header))
+        } else {
+            None
+        };
         let block_header = if self.notifier_blocks.receiver_count() > 0 {
             Some(block.header.clone())
         } else {
@@ -337,17 +715,62 @@ impl Miner {
         } else {
             None
         };
+        let block_transactions = if self.notifier_transactions.receiver_count() > 0 {
+            Some(block.transactions.clone())
+        } else {
+            None
+        };
+
+        // write artifact before the block is moved to storage
+        // failures are logged but do not prevent the block from being committed
+        #[cfg(feature = "artifacts")]
+        if let Some(ref dir) = self.artifacts_dir {
+            match BlockArtifact::from(&block).write_to(dir) {
+                Ok(path) => tracing::info!(%block_number, path = %path.display(), "wrote block artifact"),
+                Err(e) => tracing::error!(reason = ?e, %block_number, "failed to write block artifact"),
+            }
+        }
+
+        // aggregate contract activity before the block is moved to storage
+        self.contract_activity.record_block(&block, UnixTime::now());
+
+        // keep a copy of the block only if some hook will receive it
+        let hooked_block = if self.hooks.count() > 0 { Some(block.clone()) } else { None };
 
         // save storage
-        self.storage.save_block(block)?;
-        self.storage.set_mined_block_number(block_number)?;
+        if replicated {
+            self.storage.apply_replicated_block(block)?;
+        } else {
+            self.storage.save_block(block)?;
+            self.storage.set_mined_block_number(block_number)?;
+        }
+
+        // hooks
+        if let Some(hooked_block) = hooked_block {
+            self.hooks.block_committed(&hooked_block);
+        }
 
         // notify
+        // chain head goes first so followers and filters see the new head before derived notifications
+        self.storage.chain_head().publish(ChainHeadEvent::Committed {
+            number: block_number,
+            hash: block_hash,
+        });
+
+        // compact headers go first among block notifications because they are consumed by latency-sensitive clients
+        if let Some(block_header_compact) = block_header_compact {
+            let _ = self.notifier_blocks_compact.send(block_header_compact);
+        }
         if let Some(block_logs) = block_logs {
             for log in block_logs {
                 let _ = self.notifier_logs.send(log);
             }
         }
+        if let Some(block_transactions) = block_transactions {
+            for tx in block_transactions {
+                let _ = self.notifier_transactions.send(tx);
+            }
+        }
         if let Some(block_header) = block_header {
             let _ = self.notifier_blocks.send(block_header);
         }
@@ -391,13 +814,16 @@ pub fn block_from_local(number: BlockNumber, txs: Vec<LocalTransactionExecution>
 
     // mine transactions and logs
     let mut log_index = Index::ZERO;
+    let mut cumulative_gas_used: u64 = 0;
     for (tx_idx, tx) in txs.into_iter().enumerate() {
         let transaction_index = Index::new(tx_idx as u64);
         // mine logs
         let mut mined_logs: Vec<LogMined> = Vec::with_capacity(tx.result.execution.logs.len());
+        let mut logs_bloom = LogsBloom::default();
         for mined_log in tx.result.execution.logs.clone() {
             // calculate bloom
             block.header.bloom.accrue_log(&mined_log);
+            logs_bloom.accrue_log(&mined_log);
 
             // mine log
             let mined_log = LogMined {
@@ -415,36 +841,32 @@ pub fn block_from_local(number: BlockNumber, txs: Vec<LocalTransactionExecution>
         }
 
         // mine transaction
+        cumulative_gas_used = cumulative_gas_used.saturating_add(tx.result.execution.gas.as_u64());
         let mined_transaction = TransactionMined {
             input: tx.input,
             execution: tx.result.execution,
             transaction_index,
+            cumulative_gas_used: cumulative_gas_used.into(),
             block_number: block.header.number,
             block_hash: block.header.hash,
             logs: mined_logs,
+            logs_bloom,
         };
 
         // add transaction to block
         block.transactions.push(mined_transaction);
     }
 
-    // calculate transactions hash
+    // calculate transactions and receipts roots
     if not(block.transactions.is_empty()) {
-        let transactions_hashes: Vec<&Hash> = block.transactions.iter().map(|x| &x.input.hash).collect();
-        block.header.transactions_root = triehash::ordered_trie_root::<KeccakHasher, _>(transactions_hashes).into();
+        let transactions = block.transactions.iter().map(|tx| tx.encode_transaction());
+        block.header.transactions_root = triehash::ordered_trie_root::<KeccakHasher, _>(transactions).into();
+        block.header.receipts_root = receipts_root(&block);
     }
 
-    // calculate final block hash
-
-    // replicate calculated block hash from header to transactions and logs
-    for transaction in block.transactions.iter_mut() {
-        transaction.block_hash = block.header.hash;
-        for log in transaction.logs.iter_mut() {
-            log.block_hash = block.header.hash;
-        }
-    }
+    // calculate block hash (parent hash and state root are set by the miner)
+    block.update_hash();
 
-    // TODO: calculate state_root, receipts_root and parent_hash
     Ok(block)
 }
 
@@ -499,6 +921,16 @@ mod interval_miner {
     fn mine_and_commit(miner: &Miner) {
         let _mine_and_commit_lock = miner.locks.mine_and_commit.lock_or_clear("mutex in mine_and_commit is poisoned");
 
+        // mine and queue for background commit, if enabled
+        match miner.mine_local_and_enqueue() {
+            Ok(true) => return,
+            Ok(false) => {}
+            Err(e) => {
+                tracing::error!(reason = ?e, "failed to mine block for background commit");
+                return;
+            }
+        }
+
         // mine
         let block = loop {
             match miner.mine_local() {
@@ -511,10 +943,10 @@ mod interval_miner {
 
         // commit
         loop {
-            match miner.commit(block.clone()) {
+            match miner.commit_or_quarantine(block.clone()) {
                 Ok(_) => break,
                 Err(e) => {
-                    tracing::error!(reason = ?e, "failed to commit block");
+                    tracing::error!(reason = ?e, "failed to commit block even after quarantining its transactions");
                     continue;
                 }
             }
@@ -1,11 +1,19 @@
+#[cfg(feature = "artifacts")]
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
+use anyhow::anyhow;
 use clap::Parser;
 use display_json::DebugAsJson;
 
+use crate::eth::miner::BaseFeeConfig;
+use crate::eth::miner::GasTargetConfig;
 use crate::eth::miner::Miner;
+use crate::eth::miner::MAX_QUEUED_BLOCKS;
+use crate::eth::primitives::BlockHeader;
+use crate::eth::primitives::Gas;
 use crate::eth::storage::StratusStorage;
 use crate::ext::not;
 use crate::ext::parse_duration;
@@ -21,6 +29,43 @@ pub struct MinerConfig {
     /// Target block time.
     #[arg(long = "block-mode", env = "BLOCK_MODE", default_value = "automine")]
     pub block_mode: MinerMode,
+
+    /// Maximum cumulative gas of the transactions in a mined block.
+    #[arg(long = "block-gas-limit", env = "BLOCK_GAS_LIMIT", default_value_t = BlockHeader::GAS_LIMIT)]
+    pub block_gas_limit: u64,
+
+    /// Number of failed block commits or EVM panics caused by a transaction before it is quarantined.
+    #[arg(long = "miner-quarantine-attempts", env = "MINER_QUARANTINE_ATTEMPTS", default_value = "3")]
+    pub quarantine_attempts: usize,
+
+    /// Number of most recent transactions whose discarded execution attempts (conflicts and commit failures) are kept for diagnostics.
+    /// Disabled if zero.
+    #[arg(long = "miner-discarded-attempts", env = "MINER_DISCARDED_ATTEMPTS", default_value = "0")]
+    pub discarded_attempts: usize,
+
+    /// Commits local blocks to the permanent storage in a background thread, so transactions mined in automine mode return before their
+    /// block is written. Mined blocks, receipts and logs become visible only after the background commit.
+    #[arg(long = "miner-background-commit", env = "MINER_BACKGROUND_COMMIT", default_value = "false")]
+    pub background_commit: bool,
+
+    /// Max number of local blocks waiting for a background commit. Mining waits while the queue is full.
+    #[arg(long = "miner-background-commit-queue", env = "MINER_BACKGROUND_COMMIT_QUEUE", default_value = "16")]
+    pub background_commit_queue: usize,
+
+    /// Max number of queued local blocks committed in a single pass of the background committer.
+    #[arg(long = "miner-background-commit-batch", env = "MINER_BACKGROUND_COMMIT_BATCH", default_value = "8")]
+    pub background_commit_batch: usize,
+
+    #[clap(flatten)]
+    pub base_fee: BaseFeeConfig,
+
+    #[clap(flatten)]
+    pub gas_target: GasTargetConfig,
+
+    /// Directory where an execution artifact of each committed block is written for external proving systems.
+    #[cfg(feature = "artifacts")]
+    #[arg(long = "block-artifacts-dir", env = "BLOCK_ARTIFACTS_DIR")]
+    pub block_artifacts_dir: Option<PathBuf>,
 }
 
 impl MinerConfig {
@@ -36,6 +81,7 @@ impl MinerConfig {
                 MinerMode::External
             }
             NodeMode::Leader => self.block_mode,
+            NodeMode::Archive => MinerMode::External,
         };
 
         self.init_with_mode(mode, storage).await
@@ -46,9 +92,29 @@ impl MinerConfig {
         tracing::info!(config = ?self, mode = ?mode, "creating block miner with specific mode");
 
         // create miner
-        let miner = Miner::new(Arc::clone(&storage), mode);
+        let base_fee = self.base_fee.build(self.block_gas_limit)?;
+        let gas_target = self.gas_target.build(self.block_gas_limit)?;
+        let miner = Miner::new(Arc::clone(&storage), mode, Gas::from(self.block_gas_limit), self.quarantine_attempts)
+            .with_base_fee(base_fee)
+            .with_gas_target(gas_target)
+            .with_discarded_attempts(self.discarded_attempts);
+        #[cfg(feature = "artifacts")]
+        let miner = match self.block_artifacts_dir {
+            Some(ref dir) => {
+                std::fs::create_dir_all(dir)?;
+                miner.with_artifacts_dir(dir.clone())
+            }
+            None => miner,
+        };
         let miner = Arc::new(miner);
 
+        if self.background_commit {
+            if self.background_commit_queue == 0 || self.background_commit_queue > MAX_QUEUED_BLOCKS {
+                return Err(anyhow!("miner background commit queue must be between 1 and {}", MAX_QUEUED_BLOCKS));
+            }
+            miner.start_background_commits(self.background_commit_queue, self.background_commit_batch);
+        }
+
         if let MinerMode::Interval(block_time) = mode {
             miner.start_interval_mining(block_time).await;
         }
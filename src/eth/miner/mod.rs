@@ -1,7 +1,44 @@
+mod base_fee;
+#[cfg(feature = "artifacts")]
+mod block_artifact;
+mod block_committer;
+mod contract_activity;
+mod discarded_attempts;
+mod fee_repricing;
+mod gas_target;
 #[allow(clippy::module_inception)]
 mod miner;
 mod miner_config;
+mod quarantine;
+mod watchdog;
 
+pub use base_fee::BaseFee;
+pub use base_fee::BaseFeeConfig;
+pub use base_fee::BaseFeeMode;
+#[cfg(feature = "artifacts")]
+pub use block_artifact::BlockArtifact;
+#[cfg(feature = "artifacts")]
+pub use block_artifact::TransactionArtifact;
+pub use block_committer::BlockCommitter;
+pub use block_committer::BlockCommitterQueue;
+pub use block_committer::MAX_QUEUED_BLOCKS;
+pub use contract_activity::ContractActivity;
+pub use contract_activity::ContractActivityStats;
+pub use contract_activity::CONTRACT_ACTIVITY_MAX_WINDOW;
+pub use discarded_attempts::DiscardReason;
+pub use discarded_attempts::DiscardedAttempt;
+pub use discarded_attempts::DiscardedAttempts;
+pub use fee_repricing::FeeRepricer;
+pub use fee_repricing::FeeRepricingEntry;
+pub use fee_repricing::FeeRepricingReport;
+pub use fee_repricing::FeeTotals;
+pub use gas_target::GasTarget;
+pub use gas_target::GasTargetConfig;
 pub use miner::Miner;
 pub use miner_config::MinerConfig;
 pub use miner_config::MinerMode;
+pub use quarantine::QuarantineReason;
+pub use quarantine::QuarantinedTransaction;
+pub use quarantine::TransactionQuarantine;
+pub use watchdog::BlockWatchdog;
+pub use watchdog::BlockWatchdogConfig;
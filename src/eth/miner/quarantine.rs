@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use display_json::DebugAsJson;
+use indexmap::IndexMap;
+
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::UnixTime;
+use crate::ext::MutexExt;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
+
+/// Dead-letter of transactions that repeatedly broke block production or EVM execution.
+///
+/// Quarantined transactions are rejected before execution, so they never enter a block again until released by an admin.
+pub struct TransactionQuarantine {
+    /// Number of failures after which a transaction is quarantined.
+    max_attempts: usize,
+
+    /// Number of failures of each transaction not quarantined yet.
+    failures: Mutex<HashMap<Hash, usize>>,
+
+    /// Quarantined transactions in the order they were quarantined.
+    quarantined: Mutex<IndexMap<Hash, QuarantinedTransaction>>,
+}
+
+/// A transaction moved to the quarantine.
+#[derive(DebugAsJson, Clone, serde::Serialize)]
+pub struct QuarantinedTransaction {
+    pub hash: Hash,
+    pub from: Address,
+    pub reason: QuarantineReason,
+    pub attempts: usize,
+    #[serde(rename = "quarantinedAt")]
+    pub quarantined_at: UnixTime,
+    pub error: String,
+}
+
+/// Failure that caused a transaction to be quarantined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, serde::Serialize)]
+pub enum QuarantineReason {
+    /// Commit of the block containing the transaction kept failing.
+    #[serde(rename = "commit")]
+    #[strum(to_string = "commit")]
+    Commit,
+
+    /// Execution of the transaction kept panicking the EVM.
+    #[serde(rename = "evm_panic")]
+    #[strum(to_string = "evm_panic")]
+    EvmPanic,
+}
+
+impl TransactionQuarantine {
+    pub fn new(max_attempts: usize) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            failures: Mutex::new(HashMap::new()),
+            quarantined: Mutex::new(IndexMap::new()),
+        }
+    }
+
+    /// Number of failures after which a transaction is quarantined.
+    pub fn max_attempts(&self) -> usize {
+        self.max_attempts
+    }
+
+    /// Registers a failure caused by a transaction, quarantining it if it reached the maximum number of attempts.
+    ///
+    /// Returns `true` if the transaction was quarantined.
+    pub fn record_failure(&self, hash: Hash, from: Address, reason: QuarantineReason, error: String) -> bool {
+        let attempts = {
+            let mut failures = self.failures.lock_or_clear("quarantine failures lock was poisoned");
+            let attempts = failures.entry(hash).or_default();
+            *attempts += 1;
+            if *attempts < self.max_attempts {
+                tracing::warn!(%hash, %reason, attempts = %*attempts, max_attempts = %self.max_attempts, "transaction failure recorded");
+                return false;
+            }
+            failures.remove(&hash).unwrap_or_default()
+        };
+        self.quarantine(hash, from, reason, attempts, error);
+        true
+    }
+
+    /// Moves a transaction to the quarantine, regardless of the number of failures.
+    pub fn quarantine(&self, hash: Hash, from: Address, reason: QuarantineReason, attempts: usize, error: String) {
+        tracing::error!(%hash, %from, %reason, %attempts, %error, "quarantining transaction");
+
+        #[cfg(feature = "metrics")]
+        metrics::inc_miner_quarantined_transactions(reason.to_string());
+
+        let tx = QuarantinedTransaction {
+            hash,
+            from,
+            reason,
+            attempts,
+            quarantined_at: UnixTime::now(),
+            error,
+        };
+        self.quarantined.lock_or_clear("quarantine lock was poisoned").insert(hash, tx);
+    }
+
+    /// Checks if a transaction is quarantined.
+    pub fn is_quarantined(&self, hash: &Hash) -> bool {
+        self.quarantined.lock_or_clear("quarantine lock was poisoned").contains_key(hash)
+    }
+
+    /// Lists all quarantined transactions.
+    pub fn list(&self) -> Vec<QuarantinedTransaction> {
+        self.quarantined.lock_or_clear("quarantine lock was poisoned").values().cloned().collect()
+    }
+
+    /// Releases a transaction from the quarantine, allowing it to be executed again.
+    ///
+    /// Returns `true` if the transaction was quarantined.
+    pub fn release(&self, hash: &Hash) -> bool {
+        self.failures.lock_or_clear("quarantine failures lock was poisoned").remove(hash);
+        let released = self.quarantined.lock_or_clear("quarantine lock was poisoned").shift_remove(hash).is_some();
+        if released {
+            tracing::warn!(%hash, "transaction released from quarantine");
+        }
+        released
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quarantines_after_max_attempts() {
+        let quarantine = TransactionQuarantine::new(3);
+        let hash = Hash::new([1; 32]);
+
+        assert!(!quarantine.record_failure(hash, Address::ZERO, QuarantineReason::EvmPanic, "panic".to_owned()));
+        assert!(!quarantine.record_failure(hash, Address::ZERO, QuarantineReason::EvmPanic, "panic".to_owned()));
+        assert!(!quarantine.is_quarantined(&hash));
+
+        assert!(quarantine.record_failure(hash, Address::ZERO, QuarantineReason::EvmPanic, "panic".to_owned()));
+        assert!(quarantine.is_quarantined(&hash));
+        assert_eq!(quarantine.list()[0].attempts, 3);
+    }
+
+    #[test]
+    fn release_allows_transaction_again() {
+        let quarantine = TransactionQuarantine::new(1);
+        let hash = Hash::new([2; 32]);
+
+        assert!(quarantine.record_failure(hash, Address::ZERO, QuarantineReason::Commit, "commit".to_owned()));
+        assert!(quarantine.release(&hash));
+        assert!(!quarantine.is_quarantined(&hash));
+        assert!(!quarantine.release(&hash));
+    }
+}
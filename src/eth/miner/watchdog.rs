@@ -0,0 +1,285 @@
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use clap::Parser;
+use display_json::DebugAsJson;
+
+use crate::eth::executor::Executor;
+use crate::eth::follower::importer::external_rpc_current_block;
+use crate::eth::miner::Miner;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::StratusError;
+use crate::eth::storage::StratusStorage;
+use crate::ext::parse_duration;
+use crate::ext::spawn_named;
+use crate::ext::traced_sleep;
+use crate::ext::SleepReason;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
+use crate::GlobalState;
+
+/// Max time waiting for the alert webhook to respond.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Alerts when no block is mined or imported for too long while there is work waiting to be included in a block.
+pub struct BlockWatchdog {
+    miner: Arc<Miner>,
+    executor: Arc<dyn Executor>,
+    storage: Arc<StratusStorage>,
+
+    /// Max time without new blocks before alerting.
+    max_block_interval: Duration,
+
+    /// URL that receives a POST with the diagnosis of each alert.
+    webhook_url: Option<String>,
+
+    http: reqwest::Client,
+}
+
+impl BlockWatchdog {
+    pub fn new(
+        miner: Arc<Miner>,
+        executor: Arc<dyn Executor>,
+        storage: Arc<StratusStorage>,
+        max_block_interval: Duration,
+        webhook_url: Option<String>,
+    ) -> Self {
+        Self {
+            miner,
+            executor,
+            storage,
+            max_block_interval,
+            webhook_url,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Spawns the watchdog task that checks block production periodically.
+    pub fn spawn(self: Arc<Self>) {
+        const TASK_NAME: &str = "miner::watchdog";
+        let check_interval = (self.max_block_interval / 4).max(Duration::from_millis(100));
+
+        spawn_named(TASK_NAME, async move {
+            let mut state = WatchdogState::new(Instant::now());
+            loop {
+                if GlobalState::is_shutdown_warn(TASK_NAME) {
+                    return;
+                }
+
+                if let Err(e) = self.check(&mut state).await {
+                    tracing::error!(reason = ?e, "failed to check block production");
+                }
+
+                traced_sleep(check_interval, SleepReason::Interval).await;
+            }
+        });
+    }
+
+    async fn check(&self, state: &mut WatchdogState) -> Result<(), StratusError> {
+        let mined_block_number = self.storage.read_mined_block_number()?;
+        let has_pending_work = self.has_pending_work(mined_block_number);
+
+        match state.observe(mined_block_number, Instant::now(), has_pending_work, self.max_block_interval) {
+            WatchdogEvent::Healthy => {}
+            WatchdogEvent::Recovered => {
+                tracing::info!(%mined_block_number, "block production recovered");
+                #[cfg(feature = "metrics")]
+                metrics::set_miner_watchdog_stalled(0);
+            }
+            WatchdogEvent::Stalled { stalled_for } => {
+                let diagnosis = self.diagnose(mined_block_number, stalled_for)?;
+                tracing::error!(%diagnosis, "no block produced or imported despite pending work");
+                #[cfg(feature = "metrics")]
+                {
+                    metrics::set_miner_watchdog_stalled(1);
+                    metrics::inc_miner_watchdog_alerts();
+                }
+                self.notify_webhook(&diagnosis).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks if there are transactions or external blocks waiting to be included in a block.
+    fn has_pending_work(&self, mined_block_number: BlockNumber) -> bool {
+        let pending_txs = self.storage.pending_transactions().len();
+        let queued_txs = self.executor.queued_transactions();
+        let importer_behind = external_rpc_current_block().is_some_and(|external| external > mined_block_number);
+        pending_txs > 0 || queued_txs > 0 || importer_behind
+    }
+
+    /// Collects a snapshot of the node state to help finding why blocks are not being produced.
+    fn diagnose(&self, mined_block_number: BlockNumber, stalled_for: Duration) -> Result<serde_json::Value, StratusError> {
+        // probe storage latency with the same read executed to mine and serve blocks
+        let start = Instant::now();
+        self.storage.read_block(&BlockFilter::Latest)?;
+        let storage_latency = start.elapsed();
+
+        Ok(serde_json::json!({
+            "minedBlockNumber": mined_block_number,
+            "pendingBlockNumber": self.storage.read_pending_block_number()?,
+            "externalBlockNumber": external_rpc_current_block(),
+            "stalledForMillis": stalled_for.as_millis(),
+            "maxBlockIntervalMillis": self.max_block_interval.as_millis(),
+            "nodeMode": GlobalState::get_node_mode().to_string(),
+            "minerMode": self.miner.mode(),
+            "minerPaused": self.miner.is_paused(),
+            "pendingTransactions": self.storage.pending_transactions().len(),
+            "queuedTransactions": self.executor.queued_transactions(),
+            "inFlightTransactions": self.executor.in_flight_transactions(),
+            "quarantinedTransactions": self.miner.quarantine.list().len(),
+            "storageLatencyMicros": storage_latency.as_micros(),
+        }))
+    }
+
+    async fn notify_webhook(&self, diagnosis: &serde_json::Value) {
+        let Some(ref url) = self.webhook_url else { return };
+        let result = self.http.post(url).timeout(WEBHOOK_TIMEOUT).json(diagnosis).send().await;
+        match result.and_then(|response| response.error_for_status()) {
+            Ok(_) => tracing::info!(%url, "sent block production alert to webhook"),
+            Err(e) => tracing::error!(reason = ?e, %url, "failed to send block production alert to webhook"),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// State
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, PartialEq, Eq)]
+enum WatchdogEvent {
+    Healthy,
+    Recovered,
+    Stalled { stalled_for: Duration },
+}
+
+/// Tracks when the mined block number last changed.
+struct WatchdogState {
+    last_block_number: Option<BlockNumber>,
+    last_block_at: Instant,
+    last_alert_at: Option<Instant>,
+}
+
+impl WatchdogState {
+    fn new(now: Instant) -> Self {
+        Self {
+            last_block_number: None,
+            last_block_at: now,
+            last_alert_at: None,
+        }
+    }
+
+    /// Alerts at most once per interval while production is stalled.
+    fn observe(&mut self, block_number: BlockNumber, now: Instant, has_pending_work: bool, max_block_interval: Duration) -> WatchdogEvent {
+        // new block
+        if self.last_block_number != Some(block_number) {
+            self.last_block_number = Some(block_number);
+            self.last_block_at = now;
+            return match self.last_alert_at.take() {
+                Some(_) => WatchdogEvent::Recovered,
+                None => WatchdogEvent::Healthy,
+            };
+        }
+
+        // no block, but nothing to include in a block
+        if !has_pending_work {
+            self.last_block_at = now;
+            return match self.last_alert_at.take() {
+                Some(_) => WatchdogEvent::Recovered,
+                None => WatchdogEvent::Healthy,
+            };
+        }
+
+        // no block with pending work
+        let stalled_for = now.duration_since(self.last_block_at);
+        if stalled_for < max_block_interval {
+            return WatchdogEvent::Healthy;
+        }
+        if self
+            .last_alert_at
+            .is_some_and(|last_alert_at| now.duration_since(last_alert_at) < max_block_interval)
+        {
+            return WatchdogEvent::Healthy;
+        }
+        self.last_alert_at = Some(now);
+        WatchdogEvent::Stalled { stalled_for }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Config
+// -----------------------------------------------------------------------------
+
+#[derive(DebugAsJson, Clone, Parser, serde::Serialize)]
+pub struct BlockWatchdogConfig {
+    /// Max time without a new block, while transactions or external blocks are pending, before alerting. Disabled if not set.
+    #[arg(long = "watchdog-block-interval", value_parser=parse_duration, env = "WATCHDOG_BLOCK_INTERVAL")]
+    pub watchdog_block_interval: Option<Duration>,
+
+    /// URL that receives a POST with a diagnosis snapshot each time the watchdog alerts.
+    #[arg(long = "watchdog-webhook-url", env = "WATCHDOG_WEBHOOK_URL", requires = "watchdog_block_interval")]
+    pub watchdog_webhook_url: Option<String>,
+}
+
+impl BlockWatchdogConfig {
+    /// Inits and spawns [`BlockWatchdog`] if enabled.
+    pub fn init(&self, miner: Arc<Miner>, executor: Arc<dyn Executor>, storage: Arc<StratusStorage>) -> Option<Arc<BlockWatchdog>> {
+        let max_block_interval = self.watchdog_block_interval?;
+        tracing::info!(config = ?self, "creating block production watchdog");
+
+        let watchdog = Arc::new(BlockWatchdog::new(
+            miner,
+            executor,
+            storage,
+            max_block_interval,
+            self.watchdog_webhook_url.clone(),
+        ));
+        Arc::clone(&watchdog).spawn();
+        Some(watchdog)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_secs(10);
+
+    #[test]
+    fn test_watchdog_alerts_only_with_pending_work() {
+        let t0 = Instant::now();
+        let mut state = WatchdogState::new(t0);
+        let block = BlockNumber::from(1u64);
+
+        assert_eq!(state.observe(block, t0, true, INTERVAL), WatchdogEvent::Healthy);
+
+        // idle node does not alert
+        assert_eq!(state.observe(block, t0 + INTERVAL * 2, false, INTERVAL), WatchdogEvent::Healthy);
+
+        // pending work without blocks alerts after the interval, once per interval
+        assert_eq!(
+            state.observe(block, t0 + INTERVAL * 2 + Duration::from_secs(1), true, INTERVAL),
+            WatchdogEvent::Healthy
+        );
+        assert_eq!(
+            state.observe(block, t0 + INTERVAL * 3, true, INTERVAL),
+            WatchdogEvent::Stalled { stalled_for: INTERVAL }
+        );
+        assert_eq!(
+            state.observe(block, t0 + INTERVAL * 3 + Duration::from_secs(1), true, INTERVAL),
+            WatchdogEvent::Healthy
+        );
+
+        // new block recovers
+        assert_eq!(
+            state.observe(block.next_block_number(), t0 + INTERVAL * 4, true, INTERVAL),
+            WatchdogEvent::Recovered
+        );
+    }
+}
@@ -3,5 +3,6 @@ pub mod executor;
 pub mod follower;
 pub mod miner;
 pub mod primitives;
+pub mod publisher;
 pub mod rpc;
 pub mod storage;
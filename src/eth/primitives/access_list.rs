@@ -0,0 +1,155 @@
+use display_json::DebugAsJson;
+use ethers_core::types::transaction::eip2930::AccessListItem as EthersAccessListItem;
+use itertools::Itertools;
+
+use crate::alias::EthersAccessList;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::EvmExecution;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::SlotIndex;
+use crate::ext::not;
+
+/// Accounts and storage slots accessed by a transaction, as specified by EIP-2930.
+#[derive(DebugAsJson, Clone, Default, PartialEq, Eq, fake::Dummy, serde::Serialize, serde::Deserialize)]
+pub struct AccessList(pub Vec<AccessListItem>);
+
+#[derive(DebugAsJson, Clone, PartialEq, Eq, fake::Dummy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListItem {
+    pub address: Address,
+    pub storage_keys: Vec<Hash>,
+}
+
+impl AccessList {
+    /// Creates the access list from the accounts and slots loaded by the EVM during an execution.
+    ///
+    /// The sender, the recipient (or deployed contract) and precompiles are always accessed by the transaction, so they are included
+    /// only if their storage was accessed.
+    pub fn from_execution(execution: &EvmExecution, from: Option<Address>, to: Option<Address>) -> Self {
+        let always_accessed =
+            |address: Address| Some(address) == from || Some(address) == to || Some(address) == execution.deployed_contract_address || address.is_precompile();
+        let items = execution
+            .changes
+            .values()
+            .filter(|account| not(account.slots.is_empty()) || not(always_accessed(account.address)))
+            .map(|account| AccessListItem {
+                address: account.address,
+                storage_keys: account.slots.keys().sorted().map(|index| Hash::new((*index).into())).collect(),
+            })
+            .sorted_by_key(|item| item.address.0)
+            .collect();
+        Self(items)
+    }
+
+    /// Storage slots declared in the access list with the address of the account they belong to.
+    pub fn slots(&self) -> impl Iterator<Item = (Address, SlotIndex)> + '_ {
+        self.0
+            .iter()
+            .flat_map(|item| item.storage_keys.iter().map(|key| (item.address, SlotIndex::from(key.0 .0))))
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Conversions: Other -> Self
+// -----------------------------------------------------------------------------
+
+impl From<EthersAccessList> for AccessList {
+    fn from(value: EthersAccessList) -> Self {
+        let items = value
+            .0
+            .into_iter()
+            .map(|item| AccessListItem {
+                address: item.address.into(),
+                storage_keys: item.storage_keys.into_iter().map_into().collect(),
+            })
+            .collect();
+        Self(items)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Conversions: Self -> Other
+// -----------------------------------------------------------------------------
+
+impl From<AccessList> for EthersAccessList {
+    fn from(value: AccessList) -> Self {
+        let items = value
+            .0
+            .into_iter()
+            .map(|item| EthersAccessListItem {
+                address: item.address.into(),
+                storage_keys: item.storage_keys.into_iter().map_into().collect(),
+            })
+            .collect();
+        Self(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use fake::Fake;
+    use fake::Faker;
+
+    use super::*;
+    use crate::eth::primitives::Account;
+    use crate::eth::primitives::ExecutionAccountChanges;
+    use crate::eth::primitives::ExecutionValueChange;
+    use crate::eth::primitives::Slot;
+
+    #[test]
+    fn access_list_includes_always_accessed_accounts_only_with_slots() {
+        let from = Address::new([1; 20]);
+        let to = Address::new([2; 20]);
+        let other = Address::new([3; 20]);
+        let precompile = Address::new([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+        let mut execution: EvmExecution = Faker.fake();
+        execution.changes = HashMap::new();
+        execution.deployed_contract_address = None;
+        for address in [from, to, other, precompile] {
+            let mut account = ExecutionAccountChanges::from_original_values(Account::new_empty(address));
+            if address == to {
+                account
+                    .slots
+                    .insert(SlotIndex::ONE, ExecutionValueChange::from_original(Slot::new_empty(SlotIndex::ONE)));
+            }
+            execution.changes.insert(address, account);
+        }
+
+        let access_list = AccessList::from_execution(&execution, Some(from), Some(to));
+        assert_eq!(
+            access_list,
+            AccessList(vec![
+                AccessListItem {
+                    address: to,
+                    storage_keys: vec![Hash::new(SlotIndex::ONE.into())],
+                },
+                AccessListItem {
+                    address: other,
+                    storage_keys: vec![],
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn access_list_slots_include_address_of_each_key() {
+        let address = Address::new([1; 20]);
+        let access_list = AccessList(vec![
+            AccessListItem {
+                address,
+                storage_keys: vec![Hash::new(SlotIndex::ONE.into()), Hash::new(SlotIndex::from(2u64).into())],
+            },
+            AccessListItem {
+                address: Address::new([2; 20]),
+                storage_keys: vec![],
+            },
+        ]);
+        assert_eq!(
+            access_list.slots().collect_vec(),
+            vec![(address, SlotIndex::ONE), (address, SlotIndex::from(2u64))]
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use display_json::DebugAsJson;
+use jsonrpsee::SubscriptionMessage;
+use serde_json::json;
+
+use crate::eth::primitives::Address;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::Index;
+use crate::eth::primitives::LogMined;
+use crate::eth::primitives::LogTopic;
+use crate::eth::primitives::TransactionMined;
+use crate::eth::primitives::Wei;
+
+/// Event related to a single account, derived from a mined transaction.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, serde::Serialize)]
+pub enum AccountActivity {
+    /// Transaction sent by or to the account.
+    Transaction {
+        address: Address,
+        direction: AccountActivityDirection,
+        hash: Hash,
+        from: Address,
+        to: Option<Address>,
+        value: Wei,
+        success: bool,
+        block_number: BlockNumber,
+        transaction_index: Index,
+    },
+
+    /// Balance of the account modified by a transaction.
+    BalanceChange {
+        address: Address,
+        transaction_hash: Hash,
+        previous: Option<Wei>,
+        current: Wei,
+        block_number: BlockNumber,
+        transaction_index: Index,
+    },
+
+    /// Log emitted by the account or that has the account as one of its topics.
+    Log { address: Address, log: LogMined },
+}
+
+/// Direction of a transaction relative to the account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, serde::Serialize)]
+pub enum AccountActivityDirection {
+    #[serde(rename = "incoming")]
+    #[strum(to_string = "incoming")]
+    Incoming,
+
+    #[serde(rename = "outgoing")]
+    #[strum(to_string = "outgoing")]
+    Outgoing,
+
+    /// Transaction sent by the account to itself.
+    #[serde(rename = "self")]
+    #[strum(to_string = "self")]
+    SelfTransfer,
+}
+
+impl AccountActivity {
+    /// Extracts all events related to an account from a mined transaction, in the order they happened.
+    pub fn from_transaction(address: Address, tx: &TransactionMined) -> Vec<AccountActivity> {
+        let mut activities = Vec::new();
+
+        // transaction
+        // contract deployments are incoming transactions to the deployed contract
+        let to = tx.input.to.or(tx.execution.deployed_contract_address);
+        let direction = match (tx.input.from == address, to == Some(address)) {
+            (true, true) => Some(AccountActivityDirection::SelfTransfer),
+            (true, false) => Some(AccountActivityDirection::Outgoing),
+            (false, true) => Some(AccountActivityDirection::Incoming),
+            (false, false) => None,
+        };
+        if let Some(direction) = direction {
+            activities.push(AccountActivity::Transaction {
+                address,
+                direction,
+                hash: tx.input.hash,
+                from: tx.input.from,
+                to,
+                value: tx.input.value,
+                success: tx.is_success(),
+                block_number: tx.block_number,
+                transaction_index: tx.transaction_index,
+            });
+        }
+
+        // balance
+        if let Some(changes) = tx.execution.changes.get(&address) {
+            if let Some(current) = changes.balance.take_modified_ref() {
+                let previous = changes.balance.take_original_ref().copied();
+                if previous != Some(*current) {
+                    activities.push(AccountActivity::BalanceChange {
+                        address,
+                        transaction_hash: tx.input.hash,
+                        previous,
+                        current: *current,
+                        block_number: tx.block_number,
+                        transaction_index: tx.transaction_index,
+                    });
+                }
+            }
+        }
+
+        // logs
+        let address_topic = LogTopic::from(address);
+        for log in &tx.logs {
+            if *log.address() == address || log.topics().contains(&address_topic) {
+                activities.push(AccountActivity::Log { address, log: log.clone() });
+            }
+        }
+
+        activities
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Conversions: Self -> Other
+// -----------------------------------------------------------------------------
+impl TryFrom<AccountActivity> for SubscriptionMessage {
+    type Error = serde_json::Error;
+
+    fn try_from(value: AccountActivity) -> Result<Self, Self::Error> {
+        let json = match value {
+            AccountActivity::Transaction {
+                address,
+                direction,
+                hash,
+                from,
+                to,
+                value,
+                success,
+                block_number,
+                transaction_index,
+            } => json!({
+                "type": "transaction",
+                "address": address,
+                "direction": direction,
+                "transactionHash": hash,
+                "from": from,
+                "to": to,
+                "value": value,
+                "status": if success { "0x1" } else { "0x0" },
+                "blockNumber": block_number,
+                "transactionIndex": transaction_index,
+            }),
+            AccountActivity::BalanceChange {
+                address,
+                transaction_hash,
+                previous,
+                current,
+                block_number,
+                transaction_index,
+            } => json!({
+                "type": "balanceChange",
+                "address": address,
+                "transactionHash": transaction_hash,
+                "previousBalance": previous,
+                "balance": current,
+                "blockNumber": block_number,
+                "transactionIndex": transaction_index,
+            }),
+            AccountActivity::Log { address, log } => json!({
+                "type": "log",
+                "address": address,
+                "log": log.to_json_rpc_log(),
+            }),
+        };
+        Self::from_json(&json)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use fake::Fake;
+    use fake::Faker;
+
+    use crate::eth::primitives::AccountActivity;
+    use crate::eth::primitives::AccountActivityDirection;
+    use crate::eth::primitives::Address;
+    use crate::eth::primitives::LogTopic;
+    use crate::eth::primitives::TransactionMined;
+
+    #[test]
+    fn account_activity_from_transaction() {
+        let sender = Address::new([1; 20]);
+        let receiver = Address::new([2; 20]);
+
+        let mut tx: TransactionMined = Faker.fake();
+        tx.input.from = sender;
+        tx.input.to = Some(receiver);
+        tx.execution.changes.clear();
+        tx.logs = vec![Faker.fake()];
+        tx.logs[0].log.address = Address::new([3; 20]);
+        tx.logs[0].log.topic1 = Some(LogTopic::from(receiver));
+
+        let sender_activities = AccountActivity::from_transaction(sender, &tx);
+        assert!(matches!(
+            sender_activities.as_slice(),
+            [AccountActivity::Transaction {
+                direction: AccountActivityDirection::Outgoing,
+                ..
+            }]
+        ));
+
+        let receiver_activities = AccountActivity::from_transaction(receiver, &tx);
+        assert!(matches!(
+            receiver_activities.as_slice(),
+            [
+                AccountActivity::Transaction {
+                    direction: AccountActivityDirection::Incoming,
+                    ..
+                },
+                AccountActivity::Log { .. }
+            ]
+        ));
+
+        assert!(AccountActivity::from_transaction(Address::new([4; 20]), &tx).is_empty());
+    }
+}
@@ -0,0 +1,47 @@
+use display_json::DebugAsJson;
+
+use crate::eth::primitives::Account;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::ExecutionAccountChanges;
+use crate::eth::primitives::Slot;
+
+/// State of an account changed by a block, as it was at the end of the block.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountStateDiff {
+    pub address: Address,
+
+    /// Nonce, balance and bytecode of the account. Present only if at least one of them changed.
+    pub account: Option<Account>,
+
+    /// Slots changed by the block.
+    pub slots: Vec<Slot>,
+}
+
+impl AccountStateDiff {
+    /// Creates the diff from the compacted changes of a block. Returns `None` if nothing was modified.
+    pub fn from_changes(changes: ExecutionAccountChanges) -> Option<Self> {
+        let account = match changes.is_account_modified() {
+            true => Some(Account {
+                address: changes.address,
+                nonce: changes.nonce.take_ref().copied().unwrap_or_default(),
+                balance: changes.balance.take_ref().copied().unwrap_or_default(),
+                bytecode: changes.bytecode.take_ref().cloned().flatten(),
+                code_hash: changes.code_hash,
+            }),
+            false => None,
+        };
+
+        let mut slots = changes.slots.values().filter_map(|slot| slot.take_modified_ref()).copied().collect::<Vec<_>>();
+        slots.sort_by_key(|slot| slot.index);
+
+        if account.is_none() && slots.is_empty() {
+            return None;
+        }
+        Some(Self {
+            address: changes.address,
+            account,
+            slots,
+        })
+    }
+}
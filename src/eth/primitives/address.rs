@@ -50,6 +50,12 @@ impl Address {
         self == &Self::COINBASE
     }
 
+    /// Checks if current address is one of the precompiled contracts (0x01 to 0x0a).
+    pub fn is_precompile(&self) -> bool {
+        let bytes = self.0.as_bytes();
+        bytes[..19].iter().all(|byte| *byte == 0) && (1..=10).contains(&bytes[19])
+    }
+
     /// Checks if current address should have their updates ignored.
     ///
     /// * Coinbase is ignored because we do not charge gas, otherwise it will have to be updated for every transaction.
@@ -0,0 +1,47 @@
+use display_json::DebugAsJson;
+
+use crate::eth::primitives::Address;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Pagination;
+use crate::eth::primitives::StratusError;
+use crate::eth::storage::StoragePointInTime;
+use crate::eth::storage::StratusStorage;
+
+/// JSON-RPC input used in the `stratus_getTransactionsByAddress` method.
+#[derive(DebugAsJson, Clone, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
+#[cfg_attr(test, derive(fake::Dummy))]
+pub struct AddressTransactionsInput {
+    pub address: Address,
+
+    #[serde(rename = "fromBlock", default)]
+    pub from_block: Option<BlockFilter>,
+
+    #[serde(rename = "toBlock", default)]
+    pub to_block: Option<BlockFilter>,
+
+    #[serde(default)]
+    pub offset: Option<usize>,
+
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl AddressTransactionsInput {
+    /// Parses itself into the block range (inclusive) and the pagination used to query the storage.
+    ///
+    /// The range starts at the genesis block and ends at the last mined block when not specified.
+    pub fn parse(&self, storage: &StratusStorage) -> Result<(BlockNumber, BlockNumber, Pagination), StratusError> {
+        let mined_number = storage.read_mined_block_number()?;
+        let to_number = |point_in_time| match point_in_time {
+            StoragePointInTime::Pending | StoragePointInTime::Mined => mined_number,
+            StoragePointInTime::MinedPast(number) => number,
+        };
+
+        let from = to_number(storage.translate_to_point_in_time(&self.from_block.unwrap_or(BlockFilter::Earliest))?);
+        let to = to_number(storage.translate_to_point_in_time(&self.to_block.unwrap_or(BlockFilter::Latest))?);
+        let pagination = Pagination::new(self.offset.unwrap_or_default(), self.limit.unwrap_or(Pagination::MAX_LIMIT));
+
+        Ok((from, to, pagination))
+    }
+}
@@ -1,17 +1,34 @@
 use std::collections::HashMap;
 
+#[cfg(feature = "alloy")]
+use alloy_rpc_types_eth::BlockTransactions;
 use display_json::DebugAsJson;
 use ethereum_types::H256;
 use itertools::Itertools;
+use rlp::Encodable;
+use rlp::RlpStream;
 use serde::Deserialize;
 
 use super::LogMined;
 use super::TransactionInput;
+#[cfg(feature = "alloy")]
+use crate::alias::AlloyBlockAlloyTransaction;
+#[cfg(feature = "alloy")]
+use crate::alias::AlloyBlockB256;
+#[cfg(feature = "alloy")]
+use crate::alias::AlloyTransaction;
 use crate::alias::EthersBlockEthersTransaction;
 use crate::alias::EthersBlockH256;
 use crate::alias::EthersTransaction;
+use crate::alias::JsonRpcBlockWithHashes;
+use crate::alias::JsonRpcBlockWithTransactions;
 use crate::alias::JsonValue;
+#[cfg(feature = "alloy")]
+use crate::alias::RevmB256;
+use crate::eth::executor::order_independent;
 use crate::eth::executor::EvmExecutionResult;
+use crate::eth::primitives::logs_bloom::LogsBloom;
+use crate::eth::primitives::AccountStateDiff;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::BlockHeader;
 use crate::eth::primitives::BlockNumber;
@@ -43,8 +60,14 @@ impl Block {
     }
 
     /// Pushes a single transaction execution to the blocks transactions.
+    ///
+    /// Log indexes and cumulative gas continue from the transactions already in the block.
     pub fn push_execution(&mut self, input: TransactionInput, evm_result: EvmExecutionResult) {
         let transaction_index = (self.transactions.len() as u64).into();
+        let first_log_index = self.transactions.iter().map(|tx| tx.logs.len() as u64).sum::<u64>();
+        let previous_cumulative_gas_used = self.transactions.last().map(|tx| tx.cumulative_gas_used.as_u64()).unwrap_or_default();
+        let logs_bloom = LogsBloom::from_logs(&evm_result.execution.logs);
+        self.header.bloom.accrue_bloom(&logs_bloom.0);
         self.transactions.push(TransactionMined {
             logs: evm_result
                 .execution
@@ -53,7 +76,7 @@ impl Block {
                 .cloned()
                 .enumerate()
                 .map(|(i, log)| LogMined {
-                    log_index: (i as u64).into(),
+                    log_index: (first_log_index + i as u64).into(),
                     log,
                     transaction_hash: input.hash,
                     transaction_index,
@@ -61,12 +84,14 @@ impl Block {
                     block_hash: self.header.hash,
                 })
                 .collect(),
+            logs_bloom,
             input,
+            cumulative_gas_used: previous_cumulative_gas_used.saturating_add(evm_result.execution.gas.as_u64()).into(),
             execution: evm_result.execution,
             transaction_index,
             block_number: self.header.number,
             block_hash: self.header.hash,
-        }); // TODO: update logs bloom
+        });
     }
 
     /// Calculates block size label by the number of transactions.
@@ -101,14 +126,14 @@ impl Block {
 
     /// Serializes itself to JSON-RPC block format with full transactions included.
     pub fn to_json_rpc_with_full_transactions(self) -> JsonValue {
-        let ethers_block: EthersBlockEthersTransaction = self.into();
-        to_json_value(ethers_block)
+        let json_rpc_block: JsonRpcBlockWithTransactions = self.into();
+        to_json_value(json_rpc_block)
     }
 
     /// Serializes itself to JSON-RPC block format with only transactions hashes included.
     pub fn to_json_rpc_with_transactions_hashes(self) -> JsonValue {
-        let ethers_block: EthersBlockH256 = self.into();
-        to_json_value(ethers_block)
+        let json_rpc_block: JsonRpcBlockWithHashes = self.into();
+        to_json_value(json_rpc_block)
     }
 
     /// Returns the block number.
@@ -121,7 +146,33 @@ impl Block {
         self.header.hash
     }
 
+    /// Recalculates the block hash from the header and replicates it to transactions and logs.
+    ///
+    /// Must be called after the header of a local block is changed. Blocks imported from external chains keep their original hashes.
+    pub fn update_hash(&mut self) {
+        self.header.hash = self.header.compute_hash();
+        for transaction in self.transactions.iter_mut() {
+            transaction.block_hash = self.header.hash;
+            for log in transaction.logs.iter_mut() {
+                log.block_hash = self.header.hash;
+            }
+        }
+    }
+
+    /// Computes the cumulative gas used by each transaction from the gas used by the transactions before it.
+    ///
+    /// Used by storages that do not persist the cumulative gas used.
+    pub fn derive_cumulative_gas_used(&mut self) {
+        let mut cumulative_gas_used: u64 = 0;
+        for tx in &mut self.transactions {
+            cumulative_gas_used = cumulative_gas_used.saturating_add(tx.execution.gas.as_u64());
+            tx.cumulative_gas_used = cumulative_gas_used.into();
+        }
+    }
+
     /// Compact accounts changes removing intermediate values, keeping only the last modified nonce, balance, bytecode and slots.
+    ///
+    /// Changes are sorted by address so storages apply them in the same order in every node.
     pub fn compact_account_changes(&self) -> Vec<ExecutionAccountChanges> {
         let mut block_compacted_changes: HashMap<Address, ExecutionAccountChanges> = HashMap::new();
         for transaction in &self.transactions {
@@ -151,7 +202,45 @@ impl Block {
             }
         }
 
-        block_compacted_changes.into_values().collect_vec()
+        let addresses = order_independent(&block_compacted_changes, |changes| {
+            changes.map(|(address, _)| *address).sorted_by_key(|address| address.0).collect_vec()
+        });
+        addresses
+            .into_iter()
+            .filter_map(|address| block_compacted_changes.remove(&address))
+            .collect_vec()
+    }
+
+    /// Accounts and slots modified by the block with their values at the end of it, sorted by address.
+    pub fn state_diff(&self) -> Vec<AccountStateDiff> {
+        self.compact_account_changes().into_iter().filter_map(AccountStateDiff::from_changes).collect()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Serialization / Deserialization
+// -----------------------------------------------------------------------------
+
+/// Canonical block encoding used by `geth export`, with the header, the signed transactions and no uncles.
+///
+/// Typed transactions are encoded as byte strings prefixed with their type as specified by EIP-2718. Decoded as [`ExternalBlock`] because
+/// executions are not part of the encoding.
+///
+/// [`ExternalBlock`]: crate::eth::primitives::ExternalBlock
+impl Encodable for Block {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(3);
+        s.append(&self.header);
+        s.begin_list(self.transactions.len());
+        for tx in &self.transactions {
+            let encoded = tx.encode_transaction();
+            if tx.is_typed() {
+                s.append(&encoded);
+            } else {
+                s.append_raw(&encoded, 1);
+            }
+        }
+        s.begin_list(0);
     }
 }
 
@@ -180,6 +269,30 @@ impl From<Block> for EthersBlockH256 {
     }
 }
 
+#[cfg(feature = "alloy")]
+impl From<Block> for AlloyBlockAlloyTransaction {
+    fn from(block: Block) -> Self {
+        let alloy_block = AlloyBlockAlloyTransaction::from(block.header.clone());
+        let alloy_block_transactions: Vec<AlloyTransaction> = block.transactions.into_iter().map_into().collect();
+        Self {
+            transactions: BlockTransactions::Full(alloy_block_transactions),
+            ..alloy_block
+        }
+    }
+}
+
+#[cfg(feature = "alloy")]
+impl From<Block> for AlloyBlockB256 {
+    fn from(block: Block) -> Self {
+        let alloy_block = AlloyBlockB256::from(block.header);
+        let alloy_block_transactions: Vec<RevmB256> = block.transactions.into_iter().map(|x| x.input.hash).map_into().collect();
+        Self {
+            transactions: BlockTransactions::Hashes(alloy_block_transactions),
+            ..alloy_block
+        }
+    }
+}
+
 impl TryFrom<JsonValue> for Block {
     type Error = anyhow::Error;
 
@@ -1,4 +1,10 @@
+#[cfg(feature = "alloy")]
+use alloy_rpc_types_eth::Block as AlloyBlock;
+#[cfg(feature = "alloy")]
+use alloy_rpc_types_eth::BlockTransactions;
 use display_json::DebugAsJson;
+use ethereum_types::Bloom;
+use ethereum_types::H160;
 use ethereum_types::H256;
 use ethereum_types::H64;
 use ethereum_types::U256;
@@ -9,9 +15,23 @@ use fake::Fake;
 use fake::Faker;
 use hex_literal::hex;
 use jsonrpsee::SubscriptionMessage;
+use revm::primitives::keccak256;
+use rlp::Encodable;
+use rlp::RlpStream;
 
-use crate::alias::EthersBlockVoid;
+#[cfg(feature = "alloy")]
+use crate::alias::AlloyB64;
+#[cfg(feature = "alloy")]
+use crate::alias::AlloyHeader;
 use crate::alias::EthersBytes;
+use crate::alias::JsonRpcHeader;
+use crate::alias::JsonValue;
+#[cfg(feature = "alloy")]
+use crate::alias::RevmB256;
+#[cfg(feature = "alloy")]
+use crate::alias::RevmBytes;
+#[cfg(feature = "alloy")]
+use crate::alias::RevmU256;
 use crate::eth::primitives::logs_bloom::LogsBloom;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::BlockNumber;
@@ -23,6 +43,9 @@ use crate::eth::primitives::Hash;
 use crate::eth::primitives::MinerNonce;
 use crate::eth::primitives::Size;
 use crate::eth::primitives::UnixTime;
+use crate::eth::primitives::Wei;
+use crate::ext::not;
+use crate::ext::to_json_value;
 use crate::ext::SerdeResultExt;
 
 /// Special hash used in block mining to indicate no uncle blocks.
@@ -38,6 +61,8 @@ pub struct BlockHeader {
     pub transactions_root: Hash,
     pub gas_used: Gas,
     pub gas_limit: Gas,
+    #[serde(default)]
+    pub base_fee_per_gas: Wei,
     pub bloom: LogsBloom,
     pub timestamp: UnixTime,
     pub parent_hash: Hash,
@@ -51,20 +76,40 @@ pub struct BlockHeader {
     pub state_root: Hash,
     pub total_difficulty: Difficulty, // is always 0x0
     pub nonce: MinerNonce,            // is always 0x0000000000000000
+    #[serde(default)]
+    pub mix_hash: Hash, // is always 0x0 for local blocks
+
+    // fields introduced after london, present only in external blocks and used to validate their hash (not persisted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub withdrawals_root: Option<Hash>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob_gas_used: Option<Gas>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excess_blob_gas: Option<Gas>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_beacon_block_root: Option<Hash>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_hash: Option<Hash>,
 }
 
 impl BlockHeader {
+    /// Gas limit reported in all block headers.
+    pub const GAS_LIMIT: u64 = 100_000_000;
+
     /// Creates a new block header with the given number.
+    ///
+    /// The header has no parent and its hash is calculated from its contents, so it must be recalculated after any field is changed.
     pub fn new(number: BlockNumber, timestamp: UnixTime) -> Self {
-        Self {
+        let mut header = Self {
             number,
-            hash: number.hash(),
+            hash: Hash::ZERO,
             transactions_root: HASH_EMPTY_TRIE,
             gas_used: Gas::ZERO,
             gas_limit: Gas::ZERO,
+            base_fee_per_gas: Wei::ZERO,
             bloom: LogsBloom::default(),
             timestamp,
-            parent_hash: number.prev().map(|n| n.hash()).unwrap_or(Hash::ZERO),
+            parent_hash: Hash::ZERO,
             author: Address::default(),
             extra_data: Bytes::default(),
             miner: Address::default(),
@@ -75,7 +120,20 @@ impl BlockHeader {
             state_root: HASH_EMPTY_TRIE,
             total_difficulty: Difficulty::default(),
             nonce: MinerNonce::default(),
-        }
+            mix_hash: Hash::ZERO,
+            withdrawals_root: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
+            requests_hash: None,
+        };
+        header.hash = header.compute_hash();
+        header
+    }
+
+    /// Calculates the block hash as the keccak256 of the canonical RLP encoding of the header.
+    pub fn compute_hash(&self) -> Hash {
+        Hash::new(keccak256(rlp::encode(self)).0)
     }
 }
 
@@ -90,6 +148,7 @@ impl Dummy<Faker> for BlockHeader {
             timestamp: faker.fake_with_rng(rng),
             parent_hash: faker.fake_with_rng(rng),
             gas_limit: faker.fake_with_rng(rng),
+            base_fee_per_gas: faker.fake_with_rng(rng),
             author: faker.fake_with_rng(rng),
             extra_data: faker.fake_with_rng(rng),
             miner: faker.fake_with_rng(rng),
@@ -100,6 +159,12 @@ impl Dummy<Faker> for BlockHeader {
             state_root: faker.fake_with_rng(rng),
             total_difficulty: faker.fake_with_rng(rng),
             nonce: faker.fake_with_rng(rng),
+            mix_hash: faker.fake_with_rng(rng),
+            withdrawals_root: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
+            requests_hash: None,
         }
     }
 }
@@ -112,11 +177,13 @@ where
     T: Default,
 {
     fn from(header: BlockHeader) -> Self {
+        let mut other = OtherFields::default();
+        other.extend(post_merge_fields(&header));
         Self {
             // block: identifiers
             hash: Some(header.hash.into()),
             number: Some(header.number.into()),
-            mix_hash: Some(H256::default()),
+            mix_hash: Some(header.mix_hash.into()),
 
             // block: relation with other blocks
             uncles_hash: HASH_EMPTY_UNCLES.into(),
@@ -134,18 +201,18 @@ where
             nonce: Some(H64::zero()),
 
             // mining: gas
-            gas_limit: Gas::from(100_000_000u64).into(),
+            gas_limit: Gas::from(BlockHeader::GAS_LIMIT).into(),
             gas_used: header.gas_used.into(),
-            base_fee_per_gas: Some(U256::zero()),
+            base_fee_per_gas: Some(header.base_fee_per_gas.into()),
             blob_gas_used: None,
             excess_blob_gas: None,
 
             // transactions
             transactions: vec![], // can't fill transactions from header, must be modified afterward
             transactions_root: header.transactions_root.into(),
-            receipts_root: HASH_EMPTY_TRIE.into(),
-            withdrawals_root: None,
-            withdrawals: None,
+            receipts_root: header.receipts_root.into(),
+            withdrawals_root: Some(HASH_EMPTY_TRIE.into()),
+            withdrawals: Some(Vec::new()),
 
             // data
             size: Some(u64::from(header.size).into()),
@@ -153,11 +220,82 @@ where
             extra_data: EthersBytes::default(),
             state_root: header.state_root.into(),
             seal_fields: Vec::default(),
-            other: OtherFields::default(),
+            other,
+        }
+    }
+}
+
+#[cfg(feature = "alloy")]
+impl From<BlockHeader> for AlloyHeader {
+    fn from(header: BlockHeader) -> Self {
+        Self {
+            // block: identifiers
+            hash: Some(header.hash.into()),
+            number: Some(header.number.as_u64()),
+            mix_hash: Some(header.mix_hash.into()),
+
+            // block: relation with other blocks
+            uncles_hash: RevmB256::from(HASH_EMPTY_UNCLES),
+            parent_beacon_block_root: None,
+            parent_hash: header.parent_hash.into(),
+
+            // mining: identifiers
+            timestamp: *header.timestamp,
+            miner: Address::COINBASE.into(),
+
+            // minining: difficulty
+            difficulty: RevmU256::ZERO,
+            total_difficulty: Some(RevmU256::ZERO),
+            nonce: Some(AlloyB64::ZERO),
+
+            // mining: gas
+            gas_limit: BlockHeader::GAS_LIMIT.into(),
+            gas_used: header.gas_used.as_u64().into(),
+            base_fee_per_gas: Some(header.base_fee_per_gas.0.low_u128()),
+            blob_gas_used: None,
+            excess_blob_gas: None,
+
+            // transactions
+            transactions_root: header.transactions_root.into(),
+            receipts_root: header.receipts_root.into(),
+            withdrawals_root: Some(RevmB256::from(HASH_EMPTY_TRIE)),
+            requests_root: None,
+
+            // data
+            logs_bloom: header.bloom.into(),
+            extra_data: RevmBytes::default(),
+            state_root: header.state_root.into(),
         }
     }
 }
 
+#[cfg(feature = "alloy")]
+impl<T> From<BlockHeader> for AlloyBlock<T>
+where
+    T: Default,
+{
+    fn from(header: BlockHeader) -> Self {
+        let other = post_merge_fields(&header);
+        let mut block = Self {
+            size: Some(RevmU256::from(u64::from(header.size))),
+            header: header.into(),
+            uncles: Vec::new(),
+            transactions: BlockTransactions::default(), // can't fill transactions from header, must be modified afterward
+            withdrawals: Some(Vec::new()),
+            ..Self::default()
+        };
+        block.other.extend(other);
+        block
+    }
+}
+
+/// Fields of post-merge blocks that are not part of the block types of the RPC libraries.
+///
+/// `prevRandao` replaced `mixHash` after the merge, so both are returned with the same value.
+fn post_merge_fields(header: &BlockHeader) -> Vec<(String, JsonValue)> {
+    vec![("prevRandao".to_owned(), to_json_value(header.mix_hash))]
+}
+
 // -----------------------------------------------------------------------------
 // Conversions: Other -> Self
 // -----------------------------------------------------------------------------
@@ -174,6 +312,7 @@ impl TryFrom<&ExternalBlock> for BlockHeader {
             timestamp: value.timestamp.into(),
             parent_hash: value.parent_hash.into(),
             gas_limit: value.gas_limit.try_into()?,
+            base_fee_per_gas: value.base_fee_per_gas.unwrap_or_default().into(),
             author: value.author(),
             extra_data: value.extra_data.clone().into(),
             miner: value.author.unwrap_or_default().into(),
@@ -184,14 +323,82 @@ impl TryFrom<&ExternalBlock> for BlockHeader {
             state_root: value.state_root.into(),
             total_difficulty: value.total_difficulty.unwrap_or_default().into(),
             nonce: value.nonce.unwrap_or_default().into(),
+            mix_hash: value.mix_hash.unwrap_or_default().into(),
+            withdrawals_root: value.withdrawals_root.map(Into::into),
+            blob_gas_used: value.blob_gas_used.map(Gas::try_from).transpose()?,
+            excess_blob_gas: value.excess_blob_gas.map(Gas::try_from).transpose()?,
+            parent_beacon_block_root: value.parent_beacon_block_root.map(Into::into),
+            // not modeled by ethers, so it is kept with the unknown fields
+            requests_hash: match value.other.get("requestsHash") {
+                Some(requests_hash) => Some(serde_json::from_value::<H256>(requests_hash.clone())?.into()),
+                None => None,
+            },
         })
     }
 }
 
+/// Canonical header encoding used to calculate the block hash, with fields in the order defined by the Ethereum yellow paper.
+///
+/// The base fee is encoded only when it is not zero because headers before London do not have it and local blocks do not charge it.
+/// Fields introduced after London (withdrawals, blobs, beacon root and requests) are appended in the order of their forks up to the last
+/// one present, and always include the base fee.
+impl Encodable for BlockHeader {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        let post_london_len = if self.requests_hash.is_some() {
+            5
+        } else if self.parent_beacon_block_root.is_some() {
+            4
+        } else if self.excess_blob_gas.is_some() {
+            3
+        } else if self.blob_gas_used.is_some() {
+            2
+        } else if self.withdrawals_root.is_some() {
+            1
+        } else {
+            0
+        };
+        let has_base_fee = post_london_len > 0 || not(self.base_fee_per_gas.is_zero());
+        s.begin_list(15 + usize::from(has_base_fee) + post_london_len);
+        s.append(&H256::from(self.parent_hash));
+        s.append(&H256::from(self.uncle_hash));
+        s.append(&H160::from(self.miner));
+        s.append(&H256::from(self.state_root));
+        s.append(&H256::from(self.transactions_root));
+        s.append(&H256::from(self.receipts_root));
+        s.append(&Bloom::from(self.bloom));
+        s.append(&self.difficulty.0);
+        s.append(&self.number.as_u64());
+        s.append(&self.gas_limit.as_u64());
+        s.append(&self.gas_used.as_u64());
+        s.append(&*self.timestamp);
+        s.append(&self.extra_data.0);
+        s.append(&H256::from(self.mix_hash));
+        s.append(&H64::from(self.nonce));
+        if has_base_fee {
+            s.append(&self.base_fee_per_gas.0);
+        }
+        if post_london_len >= 1 {
+            s.append(&H256::from(self.withdrawals_root.unwrap_or_default()));
+        }
+        if post_london_len >= 2 {
+            s.append(&self.blob_gas_used.unwrap_or_default().as_u64());
+        }
+        if post_london_len >= 3 {
+            s.append(&self.excess_blob_gas.unwrap_or_default().as_u64());
+        }
+        if post_london_len >= 4 {
+            s.append(&H256::from(self.parent_beacon_block_root.unwrap_or_default()));
+        }
+        if post_london_len >= 5 {
+            s.append(&H256::from(self.requests_hash.unwrap_or_default()));
+        }
+    }
+}
+
 impl From<BlockHeader> for SubscriptionMessage {
     fn from(value: BlockHeader) -> Self {
-        let ethers_block = EthersBlockVoid::from(value);
-        Self::from_json(&ethers_block).expect_infallible()
+        let json_rpc_header = JsonRpcHeader::from(value);
+        Self::from_json(&json_rpc_header).expect_infallible()
     }
 }
 
@@ -200,29 +407,104 @@ impl From<BlockHeader> for SubscriptionMessage {
 // -----------------------------------------------------------------------------
 #[cfg(test)]
 mod tests {
+    use ethereum_types::U256;
+    use hex_literal::hex;
+    use serde_json::json;
+
+    use super::HASH_EMPTY_TRIE;
+    use crate::eth::primitives::Address;
+    use crate::eth::primitives::Block;
     use crate::eth::primitives::BlockHeader;
     use crate::eth::primitives::BlockNumber;
+    use crate::eth::primitives::Bytes;
+    use crate::eth::primitives::Difficulty;
+    use crate::eth::primitives::Gas;
     use crate::eth::primitives::Hash;
+    use crate::eth::primitives::LogMined;
+    use crate::eth::primitives::MinerNonce;
+    use crate::eth::primitives::TransactionMined;
     use crate::eth::primitives::UnixTime;
+    use crate::eth::primitives::Wei;
+    use crate::utils::test_utils::fake_first;
 
     #[test]
     fn block_header_hash_calculation() {
         let header = BlockHeader::new(BlockNumber::ZERO, UnixTime::from(1234567890));
-        assert_eq!(header.hash.to_string(), "0x011b4d03dd8c01f1049143cf9c4c817e4b167f1d1b83e5c6f0f10d89ba1e7bce");
+        assert_eq!(header.hash, header.compute_hash());
+        assert_ne!(header.hash, BlockHeader::new(BlockNumber::ZERO, UnixTime::from(1234567891)).hash);
     }
 
     #[test]
-    fn block_header_parent_hash() {
-        let header = BlockHeader::new(BlockNumber::ONE, UnixTime::from(1234567891));
+    fn block_update_hash_replicates_hash() {
+        let mut block = Block::new(BlockNumber::ONE, UnixTime::from(1234567891));
+        block.transactions.push(fake_first::<TransactionMined>());
+        block.transactions[0].logs.push(fake_first::<LogMined>());
+        block.header.parent_hash = Block::genesis().hash();
+
+        block.update_hash();
+        assert_eq!(block.hash(), block.header.compute_hash());
+        assert_eq!(block.transactions[0].block_hash, block.hash());
+        assert_eq!(block.transactions[0].logs[0].block_hash, block.hash());
+    }
+
+    #[test]
+    fn block_header_compute_hash_of_mainnet_genesis() {
+        let header = BlockHeader {
+            difficulty: Difficulty(U256::from(0x400000000u64)),
+            gas_limit: Gas::from(5000u64),
+            state_root: Hash::new(hex!("d7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544")),
+            extra_data: Bytes(hex!("11bbe8db4e347b4e8c937c1c8370e4b5ed33adb3db69cbdb7a38e1e50b1b82fa").to_vec()),
+            nonce: MinerNonce::new(hex!("0000000000000042")),
+            ..BlockHeader::new(BlockNumber::ZERO, UnixTime::from(0))
+        };
         assert_eq!(
-            header.parent_hash.to_string(),
-            "0x011b4d03dd8c01f1049143cf9c4c817e4b167f1d1b83e5c6f0f10d89ba1e7bce"
+            header.compute_hash(),
+            Hash::new(hex!("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"))
         );
     }
 
+    #[test]
+    fn block_header_compute_hash_of_mainnet_block_1() {
+        let header = BlockHeader {
+            parent_hash: Hash::new(hex!("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3")),
+            miner: Address::new(hex!("05a56e2d52c817161883f50c441c3228cfe54d9f")),
+            difficulty: Difficulty(U256::from(0x3ff800000u64)),
+            gas_limit: Gas::from(5000u64),
+            state_root: Hash::new(hex!("d67e4d450343046425ae4271474353857ab860dbc0a1dde64b41b5cd3a532bf3")),
+            extra_data: Bytes(hex!("476574682f76312e302e302f6c696e75782f676f312e342e32").to_vec()),
+            mix_hash: Hash::new(hex!("969b900de27b6ac6a67742365dd65f55a0526c41fd18e1b16f1a1215c2e66f59")),
+            nonce: MinerNonce::new(hex!("539bd4979fef1ec4")),
+            ..BlockHeader::new(BlockNumber::ONE, UnixTime::from(1438269988))
+        };
+        assert_eq!(
+            header.compute_hash(),
+            Hash::new(hex!("88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6"))
+        );
+    }
+
+    #[test]
+    fn block_header_compute_hash_includes_base_fee() {
+        let header = BlockHeader::new(BlockNumber::ONE, UnixTime::from(1234567891));
+        let header_with_base_fee = BlockHeader {
+            base_fee_per_gas: Wei::from(7u64),
+            ..header.clone()
+        };
+        assert_ne!(header.compute_hash(), header_with_base_fee.compute_hash());
+    }
+
     #[test]
     fn block_header_genesis_parent_hash() {
         let header = BlockHeader::new(BlockNumber::ZERO, UnixTime::from(1234567890));
         assert_eq!(header.parent_hash, Hash::ZERO);
     }
+
+    #[test]
+    fn block_json_rpc_includes_post_merge_fields() {
+        let block = Block::new(BlockNumber::ONE, UnixTime::from(1234567891));
+        let json = block.to_json_rpc_with_transactions_hashes();
+        assert_eq!(json["withdrawals"], json!([]));
+        assert_eq!(json["withdrawalsRoot"], json!(HASH_EMPTY_TRIE));
+        assert_eq!(json["prevRandao"], json["mixHash"]);
+        assert_eq!(json["difficulty"], "0x0");
+    }
 }
@@ -0,0 +1,84 @@
+use jsonrpsee::SubscriptionMessage;
+
+use crate::eth::primitives::BlockHeader;
+use crate::ext::SerdeResultExt;
+
+/// Fixed-size binary encoding of the block header fields needed by latency-sensitive consumers.
+///
+/// Layout (all integers are big-endian):
+///
+/// | Offset | Size | Field        |
+/// |--------|------|--------------|
+/// | 0      | 1    | version      |
+/// | 1      | 8    | number       |
+/// | 9      | 32   | hash         |
+/// | 41     | 32   | parent hash  |
+/// | 73     | 8    | timestamp    |
+/// | 81     | 8    | gas used     |
+/// | 89     | 8    | transactions |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHeaderCompact([u8; BlockHeaderCompact::LEN]);
+
+impl BlockHeaderCompact {
+    /// Version of the frame layout.
+    pub const VERSION: u8 = 1;
+
+    /// Size of the frame in bytes.
+    pub const LEN: usize = 97;
+
+    /// Returns the encoded frame.
+    pub fn as_bytes(&self) -> &[u8; Self::LEN] {
+        &self.0
+    }
+}
+
+impl From<&BlockHeader> for BlockHeaderCompact {
+    fn from(header: &BlockHeader) -> Self {
+        let mut frame = [0u8; Self::LEN];
+        frame[0] = Self::VERSION;
+        frame[1..9].copy_from_slice(&header.number.as_u64().to_be_bytes());
+        frame[9..41].copy_from_slice(header.hash.as_fixed_bytes());
+        frame[41..73].copy_from_slice(header.parent_hash.as_fixed_bytes());
+        frame[73..81].copy_from_slice(&header.timestamp.as_u64().to_be_bytes());
+        frame[81..89].copy_from_slice(&header.gas_used.as_u64().to_be_bytes());
+        frame[89..97].copy_from_slice(&u64::from(header.size).to_be_bytes());
+        Self(frame)
+    }
+}
+
+impl From<BlockHeaderCompact> for SubscriptionMessage {
+    fn from(value: BlockHeaderCompact) -> Self {
+        // JSON-RPC notifications are text, so the frame is sent as a single hex string instead of a JSON object
+        Self::from_json(&const_hex::encode_prefixed(value.0)).expect_infallible()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use crate::eth::primitives::BlockHeader;
+    use crate::eth::primitives::BlockHeaderCompact;
+    use crate::eth::primitives::BlockNumber;
+    use crate::eth::primitives::Gas;
+    use crate::eth::primitives::Size;
+    use crate::eth::primitives::UnixTime;
+
+    #[test]
+    fn block_header_compact_layout() {
+        let mut header = BlockHeader::new(BlockNumber::from(7u64), UnixTime::from(1234567890));
+        header.gas_used = Gas::from(21_000u64);
+        header.size = Size::from(3u64);
+
+        let frame = BlockHeaderCompact::from(&header);
+        let bytes = frame.as_bytes();
+        assert_eq!(bytes[0], BlockHeaderCompact::VERSION);
+        assert_eq!(bytes[1..9], 7u64.to_be_bytes());
+        assert_eq!(&bytes[9..41], header.hash.as_fixed_bytes());
+        assert_eq!(&bytes[41..73], header.parent_hash.as_fixed_bytes());
+        assert_eq!(bytes[73..81], 1234567890u64.to_be_bytes());
+        assert_eq!(bytes[81..89], 21_000u64.to_be_bytes());
+        assert_eq!(bytes[89..97], 3u64.to_be_bytes());
+    }
+}
@@ -6,7 +6,6 @@ use std::str::FromStr;
 use anyhow::anyhow;
 use display_json::DebugAsJson;
 use ethereum_types::U64;
-use ethers_core::utils::keccak256;
 use fake::Dummy;
 use fake::Faker;
 use sqlx::encode::IsNull;
@@ -15,7 +14,6 @@ use sqlx::postgres::PgHasArrayType;
 use sqlx::types::BigDecimal;
 
 use crate::alias::RevmU256;
-use crate::eth::primitives::Hash;
 use crate::gen_newtype_from;
 
 #[derive(
@@ -42,11 +40,6 @@ impl BlockNumber {
     pub const ONE: BlockNumber = BlockNumber(U64::one());
     pub const MAX: BlockNumber = BlockNumber(U64([i64::MAX as u64])); // use i64 to avoid overflow PostgreSQL because its max limit is i64, not u64.
 
-    /// Calculates the keccak256 hash of the block number.
-    pub fn hash(&self) -> Hash {
-        Hash::new(keccak256(<[u8; 8]>::from(*self)))
-    }
-
     /// Returns the previous block number.
     pub fn prev(&self) -> Option<Self> {
         if self.is_zero() {
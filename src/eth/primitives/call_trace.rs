@@ -0,0 +1,185 @@
+use display_json::DebugAsJson;
+use serde_json::json;
+
+use crate::alias::JsonValue;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::Gas;
+use crate::eth::primitives::TransactionMined;
+use crate::eth::primitives::Wei;
+
+/// Kind of action performed by a contract during the execution of a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CallTraceKind {
+    #[strum(to_string = "call")]
+    Call,
+
+    #[strum(to_string = "callcode")]
+    CallCode,
+
+    #[strum(to_string = "delegatecall")]
+    DelegateCall,
+
+    #[strum(to_string = "staticcall")]
+    StaticCall,
+
+    #[strum(to_string = "create")]
+    Create,
+
+    #[strum(to_string = "create2")]
+    Create2,
+
+    #[strum(to_string = "suicide")]
+    Suicide,
+}
+
+impl CallTraceKind {
+    /// Type of the trace in the Parity trace format.
+    pub fn trace_type(&self) -> &'static str {
+        match self {
+            Self::Call | Self::CallCode | Self::DelegateCall | Self::StaticCall => "call",
+            Self::Create | Self::Create2 => "create",
+            Self::Suicide => "suicide",
+        }
+    }
+}
+
+/// Call, contract creation or self-destruct performed during the execution of a transaction.
+///
+/// Traces of a transaction are flat and ordered by the moment they started. The position of a trace in the tree of calls is given by
+/// its trace address, the indexes of its ancestors among the children of their parents.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct CallTrace {
+    pub kind: CallTraceKind,
+
+    /// Caller, creator or self-destructed contract.
+    pub from: Address,
+
+    /// Called contract, created contract or self-destruct beneficiary.
+    ///
+    /// Empty for contract creations that failed.
+    pub to: Option<Address>,
+
+    /// Value transferred, or balance sent to the beneficiary of a self-destruct.
+    pub value: Wei,
+
+    /// Gas limit of the call.
+    pub gas: Gas,
+    pub gas_used: Gas,
+
+    /// Call data or contract initialization code.
+    pub input: Bytes,
+
+    /// Returned data or deployed contract code.
+    pub output: Bytes,
+
+    /// Reason the call failed, if it failed.
+    pub error: Option<String>,
+
+    pub trace_address: Vec<usize>,
+
+    /// Number of direct children of the trace.
+    pub subtraces: usize,
+}
+
+impl CallTrace {
+    /// Serializes the trace in the Parity trace format.
+    pub fn to_json_parity_trace(&self, tx: &TransactionMined) -> JsonValue {
+        let (action, result) = match self.kind {
+            CallTraceKind::Call | CallTraceKind::CallCode | CallTraceKind::DelegateCall | CallTraceKind::StaticCall => (
+                json!({
+                    "callType": self.kind,
+                    "from": self.from,
+                    "to": self.to,
+                    "gas": self.gas,
+                    "input": self.input,
+                    "value": self.value,
+                }),
+                json!({"gasUsed": self.gas_used, "output": self.output}),
+            ),
+            CallTraceKind::Create | CallTraceKind::Create2 => (
+                json!({
+                    "creationMethod": self.kind,
+                    "from": self.from,
+                    "gas": self.gas,
+                    "init": self.input,
+                    "value": self.value,
+                }),
+                json!({"address": self.to, "code": self.output, "gasUsed": self.gas_used}),
+            ),
+            CallTraceKind::Suicide => (
+                json!({
+                    "address": self.from,
+                    "refundAddress": self.to,
+                    "balance": self.value,
+                }),
+                JsonValue::Null,
+            ),
+        };
+
+        let mut trace = json!({
+            "action": action,
+            "blockHash": tx.block_hash,
+            "blockNumber": tx.block_number.as_u64(),
+            "subtraces": self.subtraces,
+            "traceAddress": self.trace_address,
+            "transactionHash": tx.input.hash,
+            "transactionPosition": tx.transaction_index,
+            "type": self.kind.trace_type(),
+        });
+        match self.error {
+            Some(ref error) => trace["error"] = JsonValue::String(error.clone()),
+            None => trace["result"] = result,
+        }
+        trace
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use fake::Fake;
+    use fake::Faker;
+
+    use super::*;
+
+    fn call_trace(kind: CallTraceKind, error: Option<String>) -> CallTrace {
+        CallTrace {
+            kind,
+            from: Address::new([1; 20]),
+            to: Some(Address::new([2; 20])),
+            value: Wei::ZERO,
+            gas: 100_000u64.into(),
+            gas_used: 21_000u64.into(),
+            input: Bytes(vec![1, 2]),
+            output: Bytes(vec![3]),
+            error,
+            trace_address: vec![0, 1],
+            subtraces: 2,
+        }
+    }
+
+    #[test]
+    fn call_trace_is_serialized_in_parity_format() {
+        let tx: TransactionMined = Faker.fake();
+
+        let call = call_trace(CallTraceKind::DelegateCall, None).to_json_parity_trace(&tx);
+        assert_eq!(call["type"], "call");
+        assert_eq!(call["action"]["callType"], "delegatecall");
+        assert_eq!(call["traceAddress"], json!([0, 1]));
+        assert_eq!(call["subtraces"], 2);
+        assert_eq!(call["transactionHash"], json!(tx.input.hash));
+        assert!(call.get("error").is_none());
+        assert_eq!(call["result"]["output"], "0x03");
+
+        let create = call_trace(CallTraceKind::Create2, Some("Reverted".to_owned())).to_json_parity_trace(&tx);
+        assert_eq!(create["type"], "create");
+        assert_eq!(create["action"]["init"], "0x0102");
+        assert_eq!(create["error"], "Reverted");
+        assert!(create.get("result").is_none());
+    }
+}
@@ -8,11 +8,13 @@ use hex_literal::hex;
 use crate::eth::primitives::Account;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::Bytes;
+use crate::eth::primitives::ComparisonOutcome;
 use crate::eth::primitives::ExecutionAccountChanges;
 use crate::eth::primitives::ExecutionResult;
 use crate::eth::primitives::ExternalReceipt;
 use crate::eth::primitives::Gas;
 use crate::eth::primitives::Log;
+use crate::eth::primitives::ReceiptMismatch;
 use crate::eth::primitives::UnixTime;
 use crate::eth::primitives::Wei;
 use crate::ext::not;
@@ -105,68 +107,73 @@ impl EvmExecution {
     }
 
     /// Checks if current execution state matches the information present in the external receipt.
-    pub fn compare_with_receipt(&self, receipt: &ExternalReceipt) -> anyhow::Result<()> {
+    ///
+    /// Gas is not compared because it is fixed with the receipt gas before the comparison.
+    pub fn compare_with_receipt(&self, receipt: &ExternalReceipt) -> Result<(), ReceiptMismatch> {
         // compare execution status
         if self.is_success() != receipt.is_success() {
-            return log_and_err!(format!(
-                "transaction status mismatch | hash={} execution={:?} receipt={:?}",
-                receipt.hash(),
-                self.result,
-                receipt.status
-            ));
+            return Err(ReceiptMismatch {
+                outcome: ComparisonOutcome::StatusMismatch,
+                reason: format!(
+                    "transaction status mismatch | hash={} execution={:?} receipt={:?}",
+                    receipt.hash(),
+                    self.result,
+                    receipt.status
+                ),
+            });
         }
 
         // compare logs length
         if self.logs.len() != receipt.logs.len() {
             tracing::trace!(logs = ?self.logs, "execution logs");
             tracing::trace!(logs = ?receipt.logs, "receipt logs");
-            return log_and_err!(format!(
+            return Err(ReceiptMismatch::logs(format!(
                 "logs length mismatch | hash={} execution={} receipt={}",
                 receipt.hash(),
                 self.logs.len(),
                 receipt.logs.len()
-            ));
+            )));
         }
 
         // compare logs pairs
         for (log_index, (execution_log, receipt_log)) in self.logs.iter().zip(&receipt.logs).enumerate() {
             // compare log topics length
             if execution_log.topics().len() != receipt_log.topics.len() {
-                return log_and_err!(format!(
+                return Err(ReceiptMismatch::logs(format!(
                     "log topics length mismatch | hash={} log_index={} execution={} receipt={}",
                     receipt.hash(),
                     log_index,
                     execution_log.topics().len(),
                     receipt_log.topics.len(),
-                ));
+                )));
             }
 
             // compare log topics content
             for (topic_index, (execution_log_topic, receipt_log_topic)) in execution_log.topics().iter().zip(&receipt_log.topics).enumerate() {
                 if execution_log_topic.as_ref() != receipt_log_topic.as_ref() {
-                    return log_and_err!(format!(
+                    return Err(ReceiptMismatch::logs(format!(
                         "log topic content mismatch | hash={} log_index={} topic_index={} execution={} receipt={:#x}",
                         receipt.hash(),
                         log_index,
                         topic_index,
                         execution_log_topic,
                         receipt_log_topic,
-                    ));
+                    )));
                 }
             }
 
             // compare log data content
             if execution_log.data.as_ref() != receipt_log.data.as_ref() {
-                return log_and_err!(format!(
+                return Err(ReceiptMismatch::logs(format!(
                     "log data content mismatch | hash={} log_index={} execution={} receipt={:#x}",
                     receipt.hash(),
                     log_index,
                     execution_log.data,
                     receipt_log.data,
-                ));
+                )));
             }
         }
-        Ok(())
+        Result::Ok(())
     }
 
     /// External transactions are re-executed locally with max gas and zero gas price.
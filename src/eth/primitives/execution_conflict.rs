@@ -6,10 +6,24 @@ use crate::eth::primitives::Nonce;
 use crate::eth::primitives::SlotIndex;
 use crate::eth::primitives::SlotValue;
 use crate::eth::primitives::Wei;
+use crate::ext::not;
 
-#[derive(DebugAsJson, serde::Serialize)]
+#[derive(DebugAsJson, Clone, serde::Serialize)]
 pub struct ExecutionConflicts(pub NonEmpty<ExecutionConflict>);
 
+impl ExecutionConflicts {
+    /// Distinct addresses involved in the conflicts, in the order they were detected.
+    pub fn addresses(&self) -> Vec<Address> {
+        let mut addresses = Vec::new();
+        for address in self.0.iter().filter_map(ExecutionConflict::address) {
+            if not(addresses.contains(&address)) {
+                addresses.push(address);
+            }
+        }
+        addresses
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct ExecutionConflictsBuilder(Vec<ExecutionConflict>);
 
@@ -40,8 +54,9 @@ impl ExecutionConflictsBuilder {
     }
 }
 
-#[derive(DebugAsJson, serde::Serialize)]
+#[derive(DebugAsJson, Clone, serde::Serialize)]
 #[cfg_attr(test, derive(serde::Deserialize, fake::Dummy, PartialEq))]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum ExecutionConflict {
     /// Account nonce mismatch.
     Nonce { address: Address, expected: Nonce, actual: Nonce },
@@ -63,3 +78,13 @@ pub enum ExecutionConflict {
     /// Number of modified slots mismatch.
     SlotModifiedCount { expected: usize, actual: usize },
 }
+
+impl ExecutionConflict {
+    /// Address of the account or contract in conflict, if the conflict is related to a single address.
+    pub fn address(&self) -> Option<Address> {
+        match self {
+            Self::Nonce { address, .. } | Self::Balance { address, .. } | Self::Slot { address, .. } => Some(*address),
+            Self::AccountModifiedCount { .. } | Self::SlotModifiedCount { .. } => None,
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use display_json::DebugAsJson;
 
 #[derive(DebugAsJson, Clone, Copy, Default, PartialEq, Eq, derive_more::Add, derive_more::AddAssign, fake::Dummy, serde::Serialize, serde::Deserialize)]
@@ -7,4 +9,12 @@ pub struct EvmExecutionMetrics {
 
     /// Number of slot reads during EVM execution.
     pub slot_reads: usize,
+
+    /// Number of slot reads served by slots prefetched before the EVM execution.
+    #[serde(default)]
+    pub slot_prefetch_hits: usize,
+
+    /// Time spent reading accounts and slots from the storage during EVM execution.
+    #[serde(default)]
+    pub storage_read_time: Duration,
 }
@@ -1,15 +1,27 @@
+use ethereum_types::H256;
+use ethereum_types::U256;
+use ethereum_types::U64;
+use revm::primitives::keccak256;
+use rlp::Decodable;
+use rlp::DecoderError;
+use rlp::Rlp;
 use serde::Deserialize;
 
 use crate::alias::EthersBlockEthersTransaction;
 use crate::alias::EthersBlockExternalTransaction;
+use crate::alias::EthersBytes;
+use crate::alias::EthersTransaction;
 use crate::alias::JsonValue;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockHeader;
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::Bytes;
 use crate::eth::primitives::ExternalTransaction;
 use crate::eth::primitives::Hash;
+use crate::eth::primitives::StratusError;
 use crate::eth::primitives::UnixTime;
+use crate::ext::to_json_value;
 use crate::log_and_err;
 
 #[derive(Debug, Clone, derive_more::Deref, derive_more::DerefMut, serde::Deserialize, serde::Serialize)]
@@ -41,6 +53,22 @@ impl ExternalBlock {
     pub fn extra_data(&mut self) -> Bytes {
         std::mem::take(&mut self.0.extra_data).into()
     }
+
+    /// Checks the block hash matches the hash calculated from its header.
+    pub fn validate_hash(&self) -> Result<(), StratusError> {
+        let hash = self.hash();
+        let computed = BlockHeader::try_from(self)?.compute_hash();
+        if hash == computed {
+            return Ok(());
+        }
+
+        tracing::error!(number = %self.number(), %hash, %computed, "external block hash does not match its header");
+        Err(StratusError::ImporterBlockHashInvalid {
+            number: self.number(),
+            hash,
+            computed,
+        })
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -77,6 +105,73 @@ impl TryFrom<JsonValue> for ExternalBlock {
     }
 }
 
+/// Decodes the canonical block encoding produced by [`Block`] and by `geth export`.
+///
+/// The hash is calculated from the header and the size is the length of the encoded block. Transaction signers are recovered from
+/// their signatures.
+impl Decodable for ExternalBlock {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        let header = rlp.at(0)?;
+        let header_len = header.item_count()?;
+        if !(15..=21).contains(&header_len) {
+            return Err(DecoderError::RlpIncorrectListLen);
+        }
+        let hash = H256::from(keccak256(header.as_raw()).0);
+        let number = U64::from(header.val_at::<u64>(8)?);
+
+        // typed transactions are byte strings, legacy transactions are lists
+        let mut transactions = Vec::new();
+        for (index, tx_rlp) in rlp.at(1)?.iter().enumerate() {
+            let mut tx: EthersTransaction = if tx_rlp.is_list() {
+                rlp::decode(tx_rlp.as_raw())?
+            } else {
+                rlp::decode(tx_rlp.data()?)?
+            };
+            tx.from = tx.recover_from().map_err(|_| DecoderError::Custom("failed to recover transaction signer"))?;
+            tx.block_hash = Some(hash);
+            tx.block_number = Some(number);
+            tx.transaction_index = Some(U64::from(index));
+            transactions.push(ExternalTransaction::from(tx));
+        }
+
+        let mut block = EthersBlockExternalTransaction {
+            transactions,
+            hash: Some(hash),
+            parent_hash: header.val_at(0)?,
+            uncles_hash: header.val_at(1)?,
+            author: Some(header.val_at(2)?),
+            state_root: header.val_at(3)?,
+            transactions_root: header.val_at(4)?,
+            receipts_root: header.val_at(5)?,
+            number: Some(number),
+            gas_used: header.val_at(10)?,
+            gas_limit: header.val_at(9)?,
+            extra_data: EthersBytes::from(header.val_at::<Vec<u8>>(12)?),
+            logs_bloom: Some(header.val_at(6)?),
+            timestamp: header.val_at(11)?,
+            difficulty: header.val_at(7)?,
+            total_difficulty: None,
+            seal_fields: Vec::new(),
+            uncles: Vec::new(),
+            size: Some(U256::from(rlp.as_raw().len())),
+            mix_hash: Some(header.val_at(13)?),
+            nonce: Some(header.val_at(14)?),
+            base_fee_per_gas: if header_len > 15 { Some(header.val_at(15)?) } else { None },
+            blob_gas_used: if header_len > 17 { Some(header.val_at(17)?) } else { None },
+            excess_blob_gas: if header_len > 18 { Some(header.val_at(18)?) } else { None },
+            withdrawals: None,
+            withdrawals_root: if header_len > 16 { Some(header.val_at(16)?) } else { None },
+            parent_beacon_block_root: if header_len > 19 { Some(header.val_at(19)?) } else { None },
+            other: Default::default(),
+        };
+        if header_len > 20 {
+            let requests_hash: H256 = header.val_at(20)?;
+            block.other.insert("requestsHash".to_owned(), to_json_value(requests_hash));
+        }
+        Ok(ExternalBlock(block))
+    }
+}
+
 impl From<EthersBlockEthersTransaction> for ExternalBlock {
     fn from(value: EthersBlockEthersTransaction) -> Self {
         let txs: Vec<ExternalTransaction> = value.transactions.into_iter().map(ExternalTransaction::from).collect();
@@ -0,0 +1,127 @@
+use display_json::DebugAsJson;
+
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockHeader;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Gas;
+use crate::eth::primitives::Wei;
+
+/// Fee data of a mined block used to answer `eth_feeHistory` and suggest priority fees.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct FeeHistoryBlock {
+    pub number: BlockNumber,
+    pub base_fee_per_gas: Wei,
+    pub gas_used: Gas,
+    pub gas_limit: Gas,
+
+    /// Priority fee paid by each transaction and the gas it used, sorted by priority fee.
+    pub priority_fees: Vec<(Wei, Gas)>,
+}
+
+impl FeeHistoryBlock {
+    /// Calculates the fee data of a mined block.
+    pub fn from_block(block: &Block) -> Self {
+        let base_fee_per_gas = block.header.base_fee_per_gas;
+
+        let mut priority_fees = block
+            .transactions
+            .iter()
+            .map(|tx| {
+                let priority_fee = if tx.input.gas_price > base_fee_per_gas {
+                    tx.input.gas_price - base_fee_per_gas
+                } else {
+                    Wei::ZERO
+                };
+                (priority_fee, tx.execution.gas)
+            })
+            .collect::<Vec<_>>();
+        priority_fees.sort_by_key(|(priority_fee, _)| *priority_fee);
+
+        // local blocks do not fill gas fields in the header, so they are derived from transactions and defaults
+        let gas_used = priority_fees.iter().map(|(_, gas)| gas.as_u64()).sum::<u64>();
+        let gas_limit = match block.header.gas_limit.as_u64() {
+            0 => BlockHeader::GAS_LIMIT,
+            gas_limit => gas_limit,
+        };
+
+        Self {
+            number: block.number(),
+            base_fee_per_gas,
+            gas_used: Gas::from(gas_used),
+            gas_limit: Gas::from(gas_limit),
+            priority_fees,
+        }
+    }
+
+    /// Ratio between the gas used and the gas limit of the block.
+    pub fn gas_used_ratio(&self) -> f64 {
+        match self.gas_limit.as_u64() {
+            0 => 0.0,
+            gas_limit => self.gas_used.as_u64() as f64 / gas_limit as f64,
+        }
+    }
+
+    /// Priority fees at the specified percentiles, weighted by the gas used by each transaction.
+    ///
+    /// Percentiles must be between 0 and 100. Blocks without transactions have zero rewards.
+    pub fn rewards(&self, percentiles: &[f64]) -> Vec<Wei> {
+        if self.priority_fees.is_empty() {
+            return vec![Wei::ZERO; percentiles.len()];
+        }
+
+        let gas_used = self.gas_used.as_u64() as f64;
+        percentiles
+            .iter()
+            .map(|percentile| {
+                let threshold = gas_used * percentile / 100.0;
+                let mut cumulative_gas = 0.0;
+                for (priority_fee, gas) in &self.priority_fees {
+                    cumulative_gas += gas.as_u64() as f64;
+                    if cumulative_gas >= threshold {
+                        return *priority_fee;
+                    }
+                }
+                self.priority_fees.last().map(|(priority_fee, _)| *priority_fee).unwrap_or_default()
+            })
+            .collect()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use crate::eth::primitives::BlockNumber;
+    use crate::eth::primitives::FeeHistoryBlock;
+    use crate::eth::primitives::Gas;
+    use crate::eth::primitives::Wei;
+
+    fn fee_history_block(priority_fees: Vec<(u64, u64)>) -> FeeHistoryBlock {
+        let priority_fees = priority_fees.into_iter().map(|(fee, gas)| (Wei::from(fee), Gas::from(gas))).collect::<Vec<_>>();
+        FeeHistoryBlock {
+            number: BlockNumber::ONE,
+            base_fee_per_gas: Wei::ZERO,
+            gas_used: Gas::from(priority_fees.iter().map(|(_, gas)| gas.as_u64()).sum::<u64>()),
+            gas_limit: Gas::from(1_000u64),
+            priority_fees,
+        }
+    }
+
+    #[test]
+    fn rewards_are_weighted_by_gas_used() {
+        let block = fee_history_block(vec![(1, 100), (2, 100), (10, 800)]);
+        assert_eq!(
+            block.rewards(&[0.0, 10.0, 20.0, 50.0, 100.0]),
+            vec![Wei::from(1u64), Wei::from(1u64), Wei::from(2u64), Wei::from(10u64), Wei::from(10u64)]
+        );
+        assert_eq!(block.gas_used_ratio(), 1.0);
+    }
+
+    #[test]
+    fn rewards_of_empty_block_are_zero() {
+        let block = fee_history_block(vec![]);
+        assert_eq!(block.rewards(&[25.0, 75.0]), vec![Wei::ZERO, Wei::ZERO]);
+        assert_eq!(block.gas_used_ratio(), 0.0);
+    }
+}
@@ -9,6 +9,7 @@ use fake::Faker;
 use sqlx::encode::IsNull;
 use sqlx::postgres::PgHasArrayType;
 
+use crate::alias::RevmB256;
 use crate::gen_newtype_from;
 
 #[derive(DebugAsJson, Clone, Copy, Default, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize)]
@@ -123,3 +124,9 @@ impl From<Hash> for H256 {
         value.0
     }
 }
+
+impl From<Hash> for RevmB256 {
+    fn from(value: Hash) -> Self {
+        RevmB256::from(value.0 .0)
+    }
+}
@@ -16,6 +16,7 @@ pub struct Index(pub u64);
 impl Index {
     pub const ZERO: Index = Index(0u64);
     pub const ONE: Index = Index(1u64);
+    pub const MAX: Index = Index(u64::MAX);
 
     pub fn new(inner: u64) -> Self {
         Index(inner)
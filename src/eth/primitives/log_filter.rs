@@ -1,5 +1,6 @@
 use display_json::DebugAsJson;
 
+use crate::eth::primitives::logs_bloom::LogsBloom;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::LogFilterInput;
@@ -20,6 +21,33 @@ pub struct LogFilter {
 }
 
 impl LogFilter {
+    /// Checks if a block or receipt bloom may contain logs matching the filter.
+    ///
+    /// Blooms have false positives but no false negatives, so `false` means that no log can match the filter.
+    pub fn matches_bloom(&self, bloom: &LogsBloom) -> bool {
+        // not every block source fills the bloom, so an empty one cannot be used to discard logs
+        if bloom.is_empty() {
+            return true;
+        }
+
+        // filter address
+        if not(self.addresses.is_empty()) && not(self.addresses.iter().any(|address| bloom.contains_address(address))) {
+            return false;
+        }
+
+        // filter topics
+        for filter_topic in &self.original_input.topics {
+            if filter_topic.is_empty() || filter_topic.contains(&None) {
+                continue;
+            }
+            if not(filter_topic.iter().flatten().any(|topic| bloom.contains_topic(topic))) {
+                return false;
+            }
+        }
+
+        true
+    }
+
     /// Checks if a log matches the filter.
     pub fn matches(&self, log: &LogMined) -> bool {
         // filter block range
@@ -205,4 +233,28 @@ mod tests {
         assert!(filter.matches(&log_with_address(addresses[2])));
         assert!(filter.matches(&log_with_address(addresses[3])));
     }
+
+    #[test]
+    fn log_filtering_by_bloom() {
+        let addresses = fake_list::<Address>(2);
+        let topics = fake_list::<LogTopic>(2).into_iter().map(Some).collect_vec();
+
+        let mut log = log_with_address(addresses[0]).log;
+        log.topic0 = topics[0];
+        log.topic1 = None;
+        log.topic2 = None;
+        log.topic3 = None;
+        let bloom = LogsBloom::from_logs([&log]);
+
+        assert!(build_filter(vec![], vec![]).matches_bloom(&bloom));
+        assert!(build_filter(vec![addresses[0]], vec![]).matches_bloom(&bloom));
+        assert!(build_filter(vec![addresses[0], addresses[1]], vec![vec![topics[0]]]).matches_bloom(&bloom));
+        assert!(build_filter(vec![], vec![vec![topics[0]], vec![None]]).matches_bloom(&bloom));
+
+        assert!(not(build_filter(vec![addresses[1]], vec![]).matches_bloom(&bloom)));
+        assert!(not(build_filter(vec![], vec![vec![topics[1]]]).matches_bloom(&bloom)));
+
+        // empty blooms never discard logs
+        assert!(build_filter(vec![addresses[1]], vec![]).matches_bloom(&LogsBloom::default()));
+    }
 }
@@ -2,8 +2,13 @@ use display_json::DebugAsJson;
 use itertools::Itertools;
 use jsonrpsee::SubscriptionMessage;
 
+#[cfg(feature = "alloy")]
+use crate::alias::AlloyLog;
 use crate::alias::EthersLog;
+use crate::alias::JsonRpcLog;
 use crate::alias::JsonValue;
+#[cfg(feature = "alloy")]
+use crate::alias::RevmLog;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::Hash;
@@ -47,8 +52,8 @@ impl LogMined {
 
     /// Serializes itself to JSON-RPC log format.
     pub fn to_json_rpc_log(self) -> JsonValue {
-        let ethers_log: EthersLog = self.into();
-        to_json_value(ethers_log)
+        let json_rpc_log: JsonRpcLog = self.into();
+        to_json_value(json_rpc_log)
     }
 }
 
@@ -93,11 +98,34 @@ impl From<LogMined> for EthersLog {
     }
 }
 
+#[cfg(feature = "alloy")]
+impl From<LogMined> for AlloyLog {
+    fn from(value: LogMined) -> Self {
+        Self {
+            // log
+            inner: RevmLog::new_unchecked(
+                value.log.address.into(),
+                value.topics().into_iter().map_into().collect_vec(),
+                value.log.data.into(),
+            ),
+            log_index: Some(value.log_index.0),
+            removed: false,
+
+            // block / transaction
+            block_hash: Some(value.block_hash.into()),
+            block_number: Some(value.block_number.as_u64()),
+            block_timestamp: None,
+            transaction_hash: Some(value.transaction_hash.into()),
+            transaction_index: Some(value.transaction_index.0),
+        }
+    }
+}
+
 impl TryFrom<LogMined> for SubscriptionMessage {
     type Error = serde_json::Error;
 
     fn try_from(value: LogMined) -> Result<Self, Self::Error> {
-        let ethers_log = Into::<EthersLog>::into(value);
-        Self::from_json(&ethers_log)
+        let json_rpc_log = Into::<JsonRpcLog>::into(value);
+        Self::from_json(&json_rpc_log)
     }
 }
@@ -6,6 +6,7 @@ use fake::Dummy;
 use fake::Faker;
 
 use crate::alias::RevmB256;
+use crate::eth::primitives::Address;
 use crate::gen_newtype_from;
 
 /// Topic is part of a [`Log`](super::Log) emitted by the EVM during contract execution.
@@ -35,6 +36,15 @@ impl Dummy<Faker> for LogTopic {
 // -----------------------------------------------------------------------------
 gen_newtype_from!(self = LogTopic, other = H256, [u8; 32]);
 
+/// Topic holding an address, left-padded with zeros like indexed `address` parameters.
+impl From<Address> for LogTopic {
+    fn from(value: Address) -> Self {
+        let mut topic = [0u8; 32];
+        topic[12..].copy_from_slice(&<[u8; 20]>::from(value));
+        Self(H256(topic))
+    }
+}
+
 impl From<RevmB256> for LogTopic {
     fn from(value: RevmB256) -> Self {
         Self(value.0.into())
@@ -61,3 +71,9 @@ impl From<LogTopic> for [u8; 32] {
         value.0 .0
     }
 }
+
+impl From<LogTopic> for RevmB256 {
+    fn from(value: LogTopic) -> Self {
+        RevmB256::from(value.0 .0)
+    }
+}
@@ -2,8 +2,15 @@ use std::ops::Deref;
 use std::ops::DerefMut;
 
 use ethereum_types::Bloom;
+use ethereum_types::BloomInput;
+use fake::Dummy;
+use fake::Faker;
 
+#[cfg(feature = "alloy")]
+use crate::alias::AlloyBloom;
+use crate::eth::primitives::Address;
 use crate::eth::primitives::Log;
+use crate::eth::primitives::LogTopic;
 use crate::gen_newtype_from;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
@@ -19,12 +26,42 @@ impl LogsBloom {
         Self(Bloom::from_slice(bytes))
     }
 
+    /// Computes the bloom of all logs.
+    pub fn from_logs<'a>(logs: impl IntoIterator<Item = &'a Log>) -> Self {
+        let mut bloom = Self::default();
+        for log in logs {
+            bloom.accrue_log(log);
+        }
+        bloom
+    }
+
     pub fn accrue_log(&mut self, log: &Log) {
-        self.accrue(ethereum_types::BloomInput::Raw(log.address.as_ref()));
+        self.accrue(BloomInput::Raw(log.address.as_ref()));
         for topic in log.topics() {
-            self.accrue(ethereum_types::BloomInput::Raw(topic.as_ref()));
+            self.accrue(BloomInput::Raw(topic.as_ref()));
         }
     }
+
+    /// Checks if a log emitted by the address may have been accrued.
+    pub fn contains_address(&self, address: &Address) -> bool {
+        self.contains_input(BloomInput::Raw(address.as_ref()))
+    }
+
+    /// Checks if a log with the topic may have been accrued.
+    pub fn contains_topic(&self, topic: &LogTopic) -> bool {
+        self.contains_input(BloomInput::Raw(topic.as_ref()))
+    }
+
+    /// Checks if no log was accrued.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_zero()
+    }
+}
+
+impl Dummy<Faker> for LogsBloom {
+    fn dummy_with_rng<R: ethers_core::rand::prelude::Rng + ?Sized>(_: &Faker, rng: &mut R) -> Self {
+        Self(Bloom::random_using(rng))
+    }
 }
 
 impl Deref for LogsBloom {
@@ -62,6 +99,13 @@ impl From<LogsBloom> for [u8; 256] {
     }
 }
 
+#[cfg(feature = "alloy")]
+impl From<LogsBloom> for AlloyBloom {
+    fn from(value: LogsBloom) -> Self {
+        AlloyBloom::from(value.0 .0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use hex_literal::hex;
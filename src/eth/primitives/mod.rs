@@ -1,11 +1,17 @@
+mod access_list;
 mod account;
+mod account_activity;
+mod account_state_diff;
 mod address;
+mod address_transactions_input;
 mod block;
 mod block_filter;
 mod block_header;
+mod block_header_compact;
 mod block_number;
 pub mod bytes;
 mod call_input;
+mod call_trace;
 mod chain_id;
 mod code_hash;
 mod difficulty;
@@ -21,6 +27,7 @@ mod external_block;
 mod external_receipt;
 mod external_receipts;
 mod external_transaction;
+mod fee_history;
 mod gas;
 mod hash;
 mod index;
@@ -33,29 +40,42 @@ pub mod logs_bloom;
 mod miner_nonce;
 mod nonce;
 mod now;
+mod pagination;
 mod pending_block;
 mod pending_block_header;
+mod receipt_mismatch;
 mod size;
 mod slot;
 mod slot_index;
 mod slot_value;
 mod stratus_error;
+mod trace_filter_input;
 mod transaction_execution;
 mod transaction_input;
 mod transaction_mined;
+mod transaction_options;
 mod transaction_stage;
 mod unix_time;
 mod wei;
 
+pub use access_list::AccessList;
+pub use access_list::AccessListItem;
 pub use account::test_accounts;
 pub use account::Account;
+pub use account_activity::AccountActivity;
+pub use account_activity::AccountActivityDirection;
+pub use account_state_diff::AccountStateDiff;
 pub use address::Address;
+pub use address_transactions_input::AddressTransactionsInput;
 pub use block::Block;
 pub use block_filter::BlockFilter;
 pub use block_header::BlockHeader;
+pub use block_header_compact::BlockHeaderCompact;
 pub use block_number::BlockNumber;
 pub use bytes::Bytes;
 pub use call_input::CallInput;
+pub use call_trace::CallTrace;
+pub use call_trace::CallTraceKind;
 pub use chain_id::ChainId;
 pub use code_hash::CodeHash;
 pub use difficulty::Difficulty;
@@ -74,6 +94,7 @@ pub use external_block::ExternalBlock;
 pub use external_receipt::ExternalReceipt;
 pub use external_receipts::ExternalReceipts;
 pub use external_transaction::ExternalTransaction;
+pub use fee_history::FeeHistoryBlock;
 pub use gas::Gas;
 pub use hash::Hash;
 pub use index::Index;
@@ -86,18 +107,23 @@ pub use log_topic::LogTopic;
 pub use miner_nonce::MinerNonce;
 pub use nonce::Nonce;
 pub use now::DateTimeNow;
+pub use pagination::Pagination;
 pub use pending_block::PendingBlock;
 pub use pending_block_header::PendingBlockHeader;
+pub use receipt_mismatch::ComparisonOutcome;
+pub use receipt_mismatch::ReceiptMismatch;
 pub use size::Size;
 pub use slot::Slot;
 pub use slot_index::SlotIndex;
 pub use slot_value::SlotValue;
 pub use stratus_error::StratusError;
+pub use trace_filter_input::TraceFilterInput;
 pub use transaction_execution::ExternalTransactionExecution;
 pub use transaction_execution::LocalTransactionExecution;
 pub use transaction_execution::TransactionExecution;
 pub use transaction_input::TransactionInput;
 pub use transaction_mined::TransactionMined;
+pub use transaction_options::TransactionOptions;
 pub use transaction_stage::TransactionStage;
 pub use unix_time::UnixTime;
 pub use wei::Wei;
@@ -130,6 +156,7 @@ mod tests {
 
     gen_test_serde!(Account);
     gen_test_serde!(Address);
+    gen_test_serde!(AddressTransactionsInput);
     gen_test_serde!(Block);
     gen_test_serde!(BlockFilter);
     gen_test_serde!(BlockHeader);
@@ -170,6 +197,7 @@ mod tests {
     gen_test_serde!(TransactionExecutionValueChangeWei);
     gen_test_serde!(TransactionInput);
     gen_test_serde!(TransactionMined);
+    gen_test_serde!(TransactionOptions);
     gen_test_serde!(UnixTime);
     gen_test_serde!(Wei);
 }
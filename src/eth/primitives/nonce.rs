@@ -10,7 +10,7 @@ use fake::Faker;
 use crate::gen_newtype_from;
 use crate::gen_newtype_try_from;
 
-#[derive(DebugAsJson, derive_more::Display, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[derive(DebugAsJson, derive_more::Display, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
 pub struct Nonce(U64);
 
 impl Nonce {
@@ -0,0 +1,36 @@
+use display_json::DebugAsJson;
+
+/// Page of the results of a query, selected by the number of results to skip and the max number of results to return.
+#[derive(DebugAsJson, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct Pagination {
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl Pagination {
+    /// Max number of results returned in a single page.
+    pub const MAX_LIMIT: usize = 1_000;
+
+    pub fn new(offset: usize, limit: usize) -> Self {
+        Self {
+            offset,
+            limit: limit.min(Self::MAX_LIMIT),
+        }
+    }
+
+    /// Number of results that must be read to fill the page, including the skipped ones.
+    pub fn end(&self) -> usize {
+        self.offset.saturating_add(self.limit)
+    }
+
+    /// Selects the page from all results.
+    pub fn apply<T>(&self, results: impl IntoIterator<Item = T>) -> Vec<T> {
+        results.into_iter().skip(self.offset).take(self.limit).collect()
+    }
+}
+
+impl Default for Pagination {
+    fn default() -> Self {
+        Self::new(0, Self::MAX_LIMIT)
+    }
+}
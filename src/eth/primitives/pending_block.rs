@@ -3,12 +3,13 @@ use indexmap::IndexMap;
 
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::ExternalBlock;
+use crate::eth::primitives::Gas;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::PendingBlockHeader;
 use crate::eth::primitives::TransactionExecution;
 
 /// Block that is being mined and receiving updates.
-#[derive(DebugAsJson, Clone, Default, serde::Serialize)]
+#[derive(DebugAsJson, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct PendingBlock {
     pub header: PendingBlockHeader,
     pub transactions: IndexMap<Hash, TransactionExecution>,
@@ -29,4 +30,71 @@ impl PendingBlock {
     pub fn push_transaction(&mut self, tx: TransactionExecution) {
         self.transactions.insert(tx.hash(), tx);
     }
+
+    /// Cumulative gas used by the transactions in the block.
+    pub fn gas_used(&self) -> Gas {
+        let gas_used = self.transactions.values().map(|tx| tx.execution().gas.as_u64()).fold(0u64, u64::saturating_add);
+        Gas::from(gas_used)
+    }
+
+    /// Removes the transactions that do not fit in the specified gas limit, keeping the execution order.
+    ///
+    /// The first transaction is always kept, even if it alone exceeds the limit, so oversized transactions do not stall the chain.
+    pub fn split_off_over_gas_limit(&mut self, gas_limit: Gas) -> Vec<TransactionExecution> {
+        let gas_limit = gas_limit.as_u64();
+
+        let mut cumulative_gas: u64 = 0;
+        let mut split_at = self.transactions.len();
+        for (index, tx) in self.transactions.values().enumerate() {
+            cumulative_gas = cumulative_gas.saturating_add(tx.execution().gas.as_u64());
+            if index > 0 && cumulative_gas > gas_limit {
+                split_at = index;
+                break;
+            }
+        }
+
+        self.transactions.split_off(split_at).into_values().collect()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use fake::Fake;
+    use fake::Faker;
+
+    use super::*;
+    use crate::eth::primitives::LocalTransactionExecution;
+
+    fn tx_with_gas(gas: u64) -> TransactionExecution {
+        let mut tx: LocalTransactionExecution = Faker.fake();
+        tx.result.execution.gas = Gas::from(gas);
+        TransactionExecution::Local(tx)
+    }
+
+    #[test]
+    fn split_off_over_gas_limit_keeps_order_and_first_transaction() {
+        let txs = [tx_with_gas(60), tx_with_gas(30), tx_with_gas(20), tx_with_gas(5)];
+        let mut block = PendingBlock::new_at_now(BlockNumber::ONE);
+        for tx in txs.iter().cloned() {
+            block.push_transaction(tx);
+        }
+
+        // transactions after the first one over the limit are removed, even if later ones would fit
+        let remaining = block.split_off_over_gas_limit(Gas::from(100u64));
+        assert_eq!(block.transactions.keys().copied().collect::<Vec<_>>(), vec![txs[0].hash(), txs[1].hash()]);
+        assert_eq!(
+            remaining.iter().map(TransactionExecution::hash).collect::<Vec<_>>(),
+            vec![txs[2].hash(), txs[3].hash()]
+        );
+        assert_eq!(block.gas_used(), Gas::from(90u64));
+
+        // the first transaction is kept even if it alone exceeds the limit
+        let remaining = block.split_off_over_gas_limit(Gas::from(10u64));
+        assert_eq!(block.transactions.len(), 1);
+        assert_eq!(remaining.len(), 1);
+    }
 }
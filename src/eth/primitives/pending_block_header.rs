@@ -4,7 +4,7 @@ use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::UnixTime;
 
 /// Header of the pending block being mined.
-#[derive(DebugAsJson, Clone, Default, serde::Serialize)]
+#[derive(DebugAsJson, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct PendingBlockHeader {
     pub number: BlockNumber,
     pub timestamp: UnixTime,
@@ -0,0 +1,41 @@
+/// Outcome of comparing a re-executed external transaction with its receipt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, strum::IntoStaticStr, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ComparisonOutcome {
+    /// Status, gas and logs are the same as in the receipt.
+    Matched,
+
+    /// Gas used differs from the receipt. The receipt gas is kept and the transaction is still imported.
+    GasMismatch,
+
+    /// Number, topics or data of the logs differ from the receipt.
+    LogsMismatch,
+
+    /// Execution succeeded and the receipt failed, or the opposite.
+    StatusMismatch,
+}
+
+impl ComparisonOutcome {
+    /// Checks if the execution diverged from the receipt.
+    pub fn is_mismatch(&self) -> bool {
+        *self != Self::Matched
+    }
+}
+
+/// Part of a re-executed external transaction that does not match its receipt.
+#[derive(Debug, thiserror::Error)]
+#[error("{reason}")]
+pub struct ReceiptMismatch {
+    pub outcome: ComparisonOutcome,
+    pub reason: String,
+}
+
+impl ReceiptMismatch {
+    pub fn logs(reason: String) -> Self {
+        Self {
+            outcome: ComparisonOutcome::LogsMismatch,
+            reason,
+        }
+    }
+}
@@ -45,6 +45,36 @@ impl SlotIndex {
         let hashed_bytes = keccak256(mapping_index_bytes);
         Self::from(hashed_bytes)
     }
+
+    /// Computes the mapping index of a dynamic key (`string` or `bytes`).
+    ///
+    /// Unlike value type keys, dynamic keys are hashed as is, without padding.
+    pub fn to_dynamic_mapping_index(&self, key: &[u8]) -> SlotIndex {
+        let mut mapping_index_bytes = Vec::with_capacity(key.len() + 32);
+        mapping_index_bytes.extend_from_slice(key);
+        mapping_index_bytes.extend_from_slice(&<[u8; 32]>::from(*self));
+
+        let hashed_bytes = keccak256(mapping_index_bytes);
+        Self::from(hashed_bytes)
+    }
+
+    /// Computes the index where the elements of a dynamic array (or the data of a long `string` or `bytes`) stored at this index begin.
+    pub fn to_array_start_index(&self) -> SlotIndex {
+        let hashed_bytes = keccak256(<[u8; 32]>::from(*self));
+        Self::from(hashed_bytes)
+    }
+
+    /// Computes the index of an element of a dynamic array stored at this index.
+    ///
+    /// `element_slots` is the number of slots used by each element, which is `1` for value types that fill a whole slot.
+    pub fn to_array_element_index(&self, element: U256, element_slots: U256) -> SlotIndex {
+        self.to_array_start_index().offset(element.overflowing_mul(element_slots).0)
+    }
+
+    /// Computes the index of a struct field or static array element located `n` slots after this index.
+    pub fn offset(&self, n: U256) -> SlotIndex {
+        Self(self.0.overflowing_add(n).0)
+    }
 }
 
 impl Dummy<Faker> for SlotIndex {
@@ -170,6 +200,7 @@ impl PgHasArrayType for SlotIndex {
 
 #[cfg(test)]
 mod tests {
+    use ethereum_types::U256;
     use hex_literal::hex;
 
     use crate::eth::primitives::SlotIndex;
@@ -180,4 +211,19 @@ mod tests {
         let hashed = SlotIndex::ZERO.to_mapping_index(address);
         assert_eq!(hashed.to_string(), "0x215be5d23550ceb1beff54fb579a765903ba2ccc85b6f79bcf9bda4e8cb86034");
     }
+
+    #[test]
+    fn slot_index_to_array_element_index() {
+        let start = SlotIndex::ZERO.to_array_start_index();
+        assert_eq!(start.to_string(), "0x290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563");
+
+        let element = SlotIndex::ZERO.to_array_element_index(U256::from(1), U256::from(2));
+        assert_eq!(element.to_string(), "0x290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e565");
+    }
+
+    #[test]
+    fn slot_index_offset_wraps() {
+        let max = SlotIndex(U256::MAX);
+        assert_eq!(max.offset(U256::from(1)), SlotIndex::ZERO);
+    }
 }
@@ -4,6 +4,7 @@ use jsonrpsee::types::error::INVALID_PARAMS_CODE;
 use jsonrpsee::types::error::INVALID_REQUEST_CODE;
 use jsonrpsee::types::error::SERVER_IS_BUSY_CODE;
 use jsonrpsee::types::ErrorObjectOwned;
+use serde_json::json;
 use strum::EnumProperty;
 
 use crate::alias::JsonValue;
@@ -11,16 +12,29 @@ use crate::eth::primitives::Address;
 use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::Bytes;
+use crate::eth::primitives::ChainId;
 use crate::eth::primitives::ExecutionConflicts;
+use crate::eth::primitives::Hash;
 use crate::eth::primitives::Nonce;
+use crate::eth::primitives::Wei;
 use crate::ext::to_json_value;
 
+/// JSON-RPC error code for transactions rejected before execution (EIP-1474).
+pub const TRANSACTION_REJECTED_CODE: i32 = -32003;
+
+/// JSON-RPC error code for requests that exceeded a rate limit or concurrency cap (EIP-1474).
+pub const LIMIT_EXCEEDED_CODE: i32 = -32005;
+
 /// Valid  error catogories are:
-/// * client_request: request is invalid.
-/// * client_state:   request is valid, specific client rules rejects it.
-/// * server_state:   request is valid, global server rules rejects it.
-/// * execution:      request is valid, but failed in executor/evm.
-/// * internal:       request is valid, but a an internal component failed.
+/// * client_request:       request is invalid.
+/// * client_state:         request is valid, specific client rules rejects it.
+/// * server_state:         request is valid, global server rules rejects it.
+/// * transaction_rejected: request is valid, but the transaction cannot be executed against the sender state.
+/// * rate_limited:         request is valid, but exceeded a rate limit or concurrency cap.
+/// * execution:            request is valid, but failed in executor/evm.
+/// * internal:             request is valid, but a an internal component failed.
+///
+/// Each category is mapped to a stable JSON-RPC error code. See [`StratusError::rpc_code`].
 #[derive(Debug, thiserror::Error, strum::EnumProperty, strum::IntoStaticStr)]
 pub enum StratusError {
     // -------------------------------------------------------------------------
@@ -30,14 +44,42 @@ pub enum StratusError {
     #[strum(props(kind = "client_request"))]
     RpcBlockFilterInvalid { filter: BlockFilter },
 
+    #[error("header not found")]
+    #[strum(props(kind = "client_request"))]
+    RpcBlockNotFound { filter: BlockFilter },
+
     #[error("Denied because will fetch data from {actual} blocks, but the max allowed is {max}.")]
     #[strum(props(kind = "client_request"))]
     RpcBlockRangeInvalid { actual: u64, max: u64 },
 
+    #[error("Denied because bundle has {actual} calls, but the max allowed is {max}.")]
+    #[strum(props(kind = "client_request"))]
+    RpcCallManyLimit { actual: usize, max: usize },
+
+    #[error("Denied because reached maximum of {max} concurrent executions.")]
+    #[strum(props(kind = "rate_limited"))]
+    RpcConcurrencyLimit { max: usize },
+
     #[error("Denied because client did not identify itself.")]
     #[strum(props(kind = "client_request"))]
     RpcClientMissing,
 
+    #[error("EVM pool {route} cannot be resized to {size} EVMs.")]
+    #[strum(props(kind = "client_request"))]
+    RpcEvmPoolResizeInvalid { route: String, size: usize },
+
+    #[error("Denied because reached maximum filter limit of {max}.")]
+    #[strum(props(kind = "client_state"))]
+    RpcFilterLimit { max: u32 },
+
+    #[error("Filter {id} not found.")]
+    #[strum(props(kind = "client_state"))]
+    RpcFilterNotFound { id: String },
+
+    #[error("query returned more than {max} results")]
+    #[strum(props(kind = "client_request"))]
+    RpcLogsLimit { max: usize },
+
     #[error("Failed to decode {rust_type} parameter.")]
     #[strum(props(kind = "client_request"))]
     RpcParameterInvalid { rust_type: &'static str, decode_error: String },
@@ -46,6 +88,18 @@ pub enum StratusError {
     #[strum(props(kind = "client_request"))]
     RpcParameterMissing { rust_type: &'static str },
 
+    #[error("Denied because {limit} exceeded the rate limit of {rate} requests per second.")]
+    #[strum(props(kind = "rate_limited"))]
+    RpcRateLimited { limit: String, rate: u32 },
+
+    #[error("Account proofs are only available for the latest mined block.")]
+    #[strum(props(kind = "client_request"))]
+    RpcProofBlockUnsupported { filter: BlockFilter },
+
+    #[error("State trie is disabled, account proofs are not available.")]
+    #[strum(props(kind = "server_state"))]
+    RpcStateTrieDisabled,
+
     #[error("Invalid subscription event: {event}")]
     #[strum(props(kind = "client_request"))]
     RpcSubscriptionInvalid { event: String },
@@ -66,6 +120,10 @@ pub enum StratusError {
     #[strum(props(kind = "client_request"))]
     RpcTransactionInvalid { decode_error: String },
 
+    #[error("Denied because window has {actual} seconds, but the max allowed is {max}.")]
+    #[strum(props(kind = "client_request"))]
+    RpcWindowInvalid { actual: u64, max: u64 },
+
     // -------------------------------------------------------------------------
     // Transaction
     // -------------------------------------------------------------------------
@@ -77,14 +135,47 @@ pub enum StratusError {
     #[strum(props(kind = "execution"))]
     TransactionConflict(Box<ExecutionConflicts>),
 
-    #[error("Transaction nonce {transaction} does not match account nonce {account}.")]
-    #[strum(props(kind = "execution"))]
-    TransactionNonce { transaction: Nonce, account: Nonce },
+    #[error("Invalid chain id for signer: have {transaction} want {expected}.")]
+    #[strum(props(kind = "client_request"))]
+    TransactionInvalidChainId { transaction: ChainId, expected: ChainId },
+
+    // Messages of the errors below start with the same strings used by geth, because client libraries match them to decide how to retry.
+    #[error("nonce too low: next nonce {account}, tx nonce {transaction}")]
+    #[strum(props(kind = "transaction_rejected"))]
+    TransactionNonceTooLow { transaction: Nonce, account: Nonce },
+
+    #[error("nonce too high: next nonce {account}, tx nonce {transaction}")]
+    #[strum(props(kind = "transaction_rejected"))]
+    TransactionNonceTooHigh { transaction: Nonce, account: Nonce },
+
+    #[error("insufficient funds for gas * price + value: balance {balance}, cost {cost}")]
+    #[strum(props(kind = "transaction_rejected"))]
+    TransactionInsufficientFunds { balance: Wei, cost: Wei },
+
+    #[error("already known")]
+    #[strum(props(kind = "transaction_rejected"))]
+    TransactionAlreadyKnown { hash: Hash },
+
+    #[error("replacement transaction underpriced: gas price {provided}, min required {expected}")]
+    #[strum(props(kind = "transaction_rejected"))]
+    TransactionReplacementUnderpriced { expected: Wei, provided: Wei },
+
+    #[error("max fee per gas less than block base fee: maxFeePerGas: {gas_price}, baseFee: {base_fee}")]
+    #[strum(props(kind = "transaction_rejected"))]
+    TransactionGasPriceBelowBaseFee { gas_price: Wei, base_fee: Wei },
 
     #[error("Failed to executed transaction in EVM: {0:?}.")]
     #[strum(props(kind = "execution"))]
     TransactionEvmFailed(String), // split this in multiple errors
 
+    #[error("EVM panicked while executing transaction: {message}.")]
+    #[strum(props(kind = "internal"))]
+    TransactionEvmPanicked { message: String },
+
+    #[error("Execution cancelled after running for more than {timeout_millis}ms in the EVM.")]
+    #[strum(props(kind = "execution"))]
+    TransactionEvmTimeout { timeout_millis: u128 },
+
     #[error("Failed to execute transaction in leader: {0:?}.")]
     #[strum(props(kind = "execution"))]
     TransactionLeaderFailed(ErrorObjectOwned),
@@ -101,6 +192,33 @@ pub enum StratusError {
     #[strum(props(kind = "execution"))]
     TransactionFromZeroAddress,
 
+    #[error("Transaction from {from} is not signed and the account is not impersonated.")]
+    #[strum(props(kind = "client_request"))]
+    TransactionSignatureMissing { from: Address },
+
+    #[error("Transaction expired after waiting more than {ttl_millis}ms to be executed.")]
+    #[strum(props(kind = "server_state"))]
+    TransactionExpired { ttl_millis: u128 },
+
+    #[error("Transaction is valid until block {valid_until_block}, but pending block is {pending_block}.")]
+    #[strum(props(kind = "client_state"))]
+    TransactionValidUntilBlockExpired {
+        valid_until_block: BlockNumber,
+        pending_block: BlockNumber,
+    },
+
+    #[error("Transaction gave up after {attempts} attempts and {elapsed_millis}ms because of persistent conflicts.")]
+    #[strum(props(kind = "server_state"))]
+    TransactionRetryExhausted {
+        attempts: usize,
+        elapsed_millis: u128,
+        conflicts: Box<ExecutionConflicts>,
+    },
+
+    #[error("Transaction {hash} is quarantined because it repeatedly failed to be executed or mined.")]
+    #[strum(props(kind = "client_state"))]
+    TransactionQuarantined { hash: Hash },
+
     // -------------------------------------------------------------------------
     // Storage
     // -------------------------------------------------------------------------
@@ -116,6 +234,34 @@ pub enum StratusError {
     #[strum(props(kind = "internal"))]
     StoragePendingNumberConflict { new: BlockNumber, pending: BlockNumber },
 
+    #[error("Replicated block {number} has parent {parent_hash}, but the last mined block is {mined_hash}.")]
+    #[strum(props(kind = "internal"))]
+    StorageReplicatedBlockInvalid { number: BlockNumber, parent_hash: Hash, mined_hash: Hash },
+
+    #[error("Range from {from} to {to} crosses block {boundary} where the chain was rolled back and is not consistent yet, retry later.")]
+    #[strum(props(kind = "server_state"))]
+    StorageRangeInconsistent {
+        from: BlockNumber,
+        to: BlockNumber,
+        boundary: BlockNumber,
+    },
+
+    #[error("Snapshot is invalid: {reason}.")]
+    #[strum(props(kind = "internal"))]
+    StorageSnapshotInvalid { reason: String },
+
+    #[error("Snapshot cannot be imported because the permanent storage already has mined blocks up to {number}.")]
+    #[strum(props(kind = "internal"))]
+    StorageSnapshotImportNotEmpty { number: BlockNumber },
+
+    #[error("Quarantined blocks not found or already expired.")]
+    #[strum(props(kind = "client_request"))]
+    StorageQuarantineNotFound,
+
+    #[error("Quarantined blocks {id} can be restored only on top of block {reset_number}, but the last mined block is {mined}.")]
+    #[strum(props(kind = "client_state"))]
+    StorageQuarantineConflict { id: u64, reset_number: BlockNumber, mined: BlockNumber },
+
     #[error("There are ({pending_txs}) pending transactions.")]
     #[strum(props(kind = "internal"))]
     PendingTransactionsExist { pending_txs: usize },
@@ -162,6 +308,21 @@ pub enum StratusError {
     #[strum(props(kind = "internal"))]
     ImporterInitError,
 
+    #[error("External block {number} has hash {hash}, but its header hashes to {computed}.")]
+    #[strum(props(kind = "internal"))]
+    ImporterBlockHashInvalid { number: BlockNumber, hash: Hash, computed: Hash },
+
+    // -------------------------------------------------------------------------
+    // Operations
+    // -------------------------------------------------------------------------
+    #[error("Operation {id} was cancelled.")]
+    #[strum(props(kind = "server_state"))]
+    OperationCancelled { id: u64 },
+
+    #[error("Operation {id} not found.")]
+    #[strum(props(kind = "client_state"))]
+    OperationNotFound { id: u64 },
+
     // -------------------------------------------------------------------------
     // Consensus
     // -------------------------------------------------------------------------
@@ -206,6 +367,30 @@ pub enum StratusError {
     #[error("Stratus node is not a follower.")]
     #[strum(props(kind = "server_state"))]
     StratusNotFollower,
+
+    #[error("Stratus node is not a leader.")]
+    #[strum(props(kind = "server_state"))]
+    StratusNotLeader,
+
+    #[error("Stratus node is a read-only archive.")]
+    #[strum(props(kind = "server_state"))]
+    StratusArchiveReadOnly,
+
+    #[error("Stratus node is a read-only replica, send transactions to the leader at {leader}.")]
+    #[strum(props(kind = "server_state"))]
+    StratusReplicaReadOnly { leader: String },
+
+    #[error("Stratus node is lagging, block {required} was requested but the last mined block is {mined}.")]
+    #[strum(props(kind = "server_state"))]
+    StratusReplicaLagging { required: BlockNumber, mined: BlockNumber },
+
+    #[error("Stratus node is degraded, storage reads are too slow to execute the transaction. Retry later.")]
+    #[strum(props(kind = "server_state"))]
+    StratusDegraded,
+
+    #[error("Stratus node is overloaded, {route} EVM queue reached its capacity of {capacity} tasks.")]
+    #[strum(props(kind = "server_state"))]
+    StratusOverloaded { route: String, capacity: usize },
 }
 
 impl StratusError {
@@ -214,12 +399,31 @@ impl StratusError {
         self.rpc_code() == INTERNAL_ERROR_CODE
     }
 
+    /// Error category as documented in [`StratusError`].
+    pub fn kind(&self) -> &'static str {
+        self.get_str("kind").unwrap_or("internal")
+    }
+
     /// Error code to be used in JSON-RPC response.
+    ///
+    /// Codes are stable and depend only on the error category:
+    ///
+    /// | Category               | Code     |
+    /// |------------------------|----------|
+    /// | `client_request`       | `-32602` |
+    /// | `client_state`         | `-32600` |
+    /// | `server_state`         | `-32009` |
+    /// | `transaction_rejected` | `-32003` |
+    /// | `rate_limited`         | `-32005` |
+    /// | `execution`            | `-32000` |
+    /// | `internal`             | `-32603` |
     pub fn rpc_code(&self) -> i32 {
         match self.get_str("kind") {
             Some("client_request") => INVALID_PARAMS_CODE,
             Some("client_state") => INVALID_REQUEST_CODE,
             Some("server_state") => SERVER_IS_BUSY_CODE,
+            Some("transaction_rejected") => TRANSACTION_REJECTED_CODE,
+            Some("rate_limited") => LIMIT_EXCEEDED_CODE,
             Some("execution") => CALL_EXECUTION_FAILED_CODE,
             Some("internal") => INTERNAL_ERROR_CODE,
             Some(kind) => {
@@ -243,16 +447,50 @@ impl StratusError {
         match self {
             // RPC
             Self::RpcBlockFilterInvalid { filter } => to_json_value(filter),
+            Self::RpcBlockNotFound { filter } => to_json_value(filter),
+            Self::RpcLogsLimit { max } => json!({"max": max}),
             Self::RpcParameterInvalid { decode_error, .. } => to_json_value(decode_error),
+            Self::RpcProofBlockUnsupported { filter } => to_json_value(filter),
+
+            // Storage
+            Self::StorageRangeInconsistent { from, to, boundary } => json!({"from": from, "to": to, "boundary": boundary}),
+            Self::StorageQuarantineConflict { id, reset_number, mined } => json!({"id": id, "resetNumber": reset_number, "mined": mined}),
+
+            // Importer
+            Self::ImporterBlockHashInvalid { number, hash, computed } => json!({"number": number, "hash": hash, "computed": computed}),
 
             // Transaction
             Self::RpcTransactionInvalid { decode_error } => to_json_value(decode_error),
             Self::TransactionEvmFailed(e) => JsonValue::String(e.to_string()),
+            Self::TransactionAlreadyKnown { hash } => json!({"hash": hash}),
+            Self::TransactionInsufficientFunds { balance, cost } => json!({"expected": cost, "provided": balance}),
+            Self::TransactionInvalidChainId { transaction, expected } => json!({"transaction": transaction, "expected": expected}),
+            Self::TransactionNonceTooHigh { transaction, account } => json!({"expected": account, "provided": transaction}),
+            Self::TransactionNonceTooLow { transaction, account } => json!({"expected": account, "provided": transaction}),
+            Self::TransactionReplacementUnderpriced { expected, provided } => json!({"expected": expected, "provided": provided}),
+            Self::TransactionGasPriceBelowBaseFee { gas_price, base_fee } => json!({"expected": base_fee, "provided": gas_price}),
+            Self::TransactionEvmPanicked { message } => JsonValue::String(message.clone()),
+            Self::TransactionEvmTimeout { timeout_millis } => json!({"timeoutMillis": timeout_millis}),
+            Self::TransactionQuarantined { hash } => to_json_value(hash),
+            Self::TransactionConflict(conflicts) => json!({"conflicts": conflicts.0.iter().collect::<Vec<_>>()}),
+            Self::TransactionRetryExhausted {
+                attempts,
+                elapsed_millis,
+                conflicts,
+            } => json!({"attempts": attempts, "elapsedMillis": elapsed_millis, "conflicts": conflicts.0.iter().collect::<Vec<_>>()}),
             Self::TransactionReverted { output } => to_json_value(output),
+            Self::TransactionValidUntilBlockExpired {
+                valid_until_block,
+                pending_block,
+            } => json!({"validUntilBlock": valid_until_block, "pendingBlock": pending_block}),
 
             // Unexpected
             Self::Unexpected(e) => JsonValue::String(e.to_string()),
 
+            // Stratus state
+            Self::StratusReplicaReadOnly { leader } => json!({"leader": leader}),
+            Self::StratusReplicaLagging { required, mined } => json!({"required": required, "mined": mined}),
+
             _ => JsonValue::Null,
         }
     }
@@ -290,3 +528,79 @@ impl From<StratusError> for ErrorObjectOwned {
         Self::owned(value.rpc_code(), value.rpc_message(), Some(data))
     }
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::primitives::ExecutionConflictsBuilder;
+    use crate::eth::primitives::SlotIndex;
+    use crate::eth::primitives::SlotValue;
+
+    #[test]
+    fn stratus_error_rpc_codes_are_stable() {
+        let nonce = StratusError::TransactionNonceTooLow {
+            transaction: Nonce::ZERO,
+            account: Nonce::ZERO,
+        };
+        assert_eq!(nonce.rpc_code(), -32003);
+
+        let funds = StratusError::TransactionInsufficientFunds {
+            balance: Wei::ZERO,
+            cost: Wei::ONE,
+        };
+        assert_eq!(funds.rpc_code(), -32003);
+        assert_eq!(funds.rpc_data(), json!({"expected": Wei::ONE, "provided": Wei::ZERO}));
+
+        assert_eq!(StratusError::RpcClientMissing.rpc_code(), -32602);
+        assert_eq!(StratusError::RpcFilterLimit { max: 1 }.rpc_code(), -32600);
+        assert_eq!(StratusError::StratusShutdown.rpc_code(), -32009);
+        assert_eq!(StratusError::RpcConcurrencyLimit { max: 1 }.rpc_code(), -32005);
+        assert_eq!(StratusError::TransactionFromZeroAddress.rpc_code(), -32000);
+        assert_eq!(StratusError::MinerModeConflict.rpc_code(), -32603);
+        assert!(StratusError::MinerModeConflict.is_internal());
+    }
+
+    #[test]
+    fn stratus_error_rpc_messages_match_geth() {
+        let nonce = StratusError::TransactionNonceTooLow {
+            transaction: Nonce::from(1u64),
+            account: Nonce::from(2u64),
+        };
+        assert!(nonce.rpc_message().starts_with("nonce too low"));
+        assert_eq!(nonce.rpc_data(), json!({"expected": Nonce::from(2u64), "provided": Nonce::from(1u64)}));
+
+        let known = StratusError::TransactionAlreadyKnown { hash: Hash::default() };
+        assert_eq!(known.rpc_message(), "already known");
+
+        let underpriced = StratusError::TransactionReplacementUnderpriced {
+            expected: Wei::ONE,
+            provided: Wei::ZERO,
+        };
+        assert!(underpriced.rpc_message().starts_with("replacement transaction underpriced"));
+
+        let funds = StratusError::TransactionInsufficientFunds {
+            balance: Wei::ZERO,
+            cost: Wei::ONE,
+        };
+        assert!(funds.rpc_message().starts_with("insufficient funds"));
+    }
+
+    #[test]
+    fn stratus_error_rpc_data_contains_conflicts() {
+        let mut conflicts = ExecutionConflictsBuilder::default();
+        conflicts.add_slot(Address::ZERO, SlotIndex::ZERO, SlotValue::default(), SlotValue::from(1u64));
+        conflicts.add_nonce(Address::ZERO, Nonce::ZERO, Nonce::from(1u64));
+        let conflicts = conflicts.build().unwrap();
+        assert_eq!(conflicts.addresses(), vec![Address::ZERO]);
+
+        let data = StratusError::TransactionConflict(conflicts.into()).rpc_data();
+        assert_eq!(data["conflicts"][0]["type"], "slot");
+        assert_eq!(data["conflicts"][0]["address"], json!(Address::ZERO));
+        assert_eq!(data["conflicts"][0]["slot"], json!(SlotIndex::ZERO));
+        assert_eq!(data["conflicts"][1]["type"], "nonce");
+    }
+}
@@ -0,0 +1,60 @@
+use display_json::DebugAsJson;
+
+use crate::eth::primitives::Address;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::CallTrace;
+use crate::eth::primitives::StratusError;
+use crate::eth::storage::StoragePointInTime;
+use crate::eth::storage::StratusStorage;
+
+/// JSON-RPC input used in the `trace_filter` method.
+#[derive(DebugAsJson, Clone, Default, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
+#[cfg_attr(test, derive(fake::Dummy))]
+pub struct TraceFilterInput {
+    #[serde(rename = "fromBlock", default)]
+    pub from_block: Option<BlockFilter>,
+
+    #[serde(rename = "toBlock", default)]
+    pub to_block: Option<BlockFilter>,
+
+    /// Callers accepted by the filter. Empty accepts any caller.
+    #[serde(rename = "fromAddress", default)]
+    pub from_address: Vec<Address>,
+
+    /// Callees accepted by the filter. Empty accepts any callee.
+    #[serde(rename = "toAddress", default)]
+    pub to_address: Vec<Address>,
+
+    /// Number of matching traces to skip.
+    #[serde(default)]
+    pub after: Option<usize>,
+
+    /// Max number of matching traces to return.
+    #[serde(default)]
+    pub count: Option<usize>,
+}
+
+impl TraceFilterInput {
+    /// Parses itself into the block range (inclusive) to be traced.
+    ///
+    /// The range starts and ends at the last mined block when not specified.
+    pub fn parse(&self, storage: &StratusStorage) -> Result<(BlockNumber, BlockNumber), StratusError> {
+        let mined_number = storage.read_mined_block_number()?;
+        let to_number = |point_in_time| match point_in_time {
+            StoragePointInTime::Pending | StoragePointInTime::Mined => mined_number,
+            StoragePointInTime::MinedPast(number) => number,
+        };
+
+        let from = to_number(storage.translate_to_point_in_time(&self.from_block.unwrap_or(BlockFilter::Latest))?);
+        let to = to_number(storage.translate_to_point_in_time(&self.to_block.unwrap_or(BlockFilter::Latest))?);
+        Ok((from, to))
+    }
+
+    /// Checks if the trace matches the caller and callee of the filter.
+    pub fn matches(&self, trace: &CallTrace) -> bool {
+        let from_matches = self.from_address.is_empty() || self.from_address.contains(&trace.from);
+        let to_matches = self.to_address.is_empty() || trace.to.is_some_and(|to| self.to_address.contains(&to));
+        from_matches && to_matches
+    }
+}
@@ -9,7 +9,7 @@ use crate::eth::primitives::Hash;
 use crate::eth::primitives::TransactionInput;
 
 #[allow(clippy::large_enum_variant)]
-#[derive(DebugAsJson, Clone, strum::EnumIs, serde::Serialize)]
+#[derive(DebugAsJson, Clone, strum::EnumIs, serde::Serialize, serde::Deserialize)]
 pub enum TransactionExecution {
     /// Transaction that was sent directly to Stratus.
     Local(LocalTransactionExecution),
@@ -81,8 +81,8 @@ impl TransactionExecution {
     }
 }
 
-#[derive(DebugAsJson, Clone, derive_new::new, serde::Serialize)]
-#[cfg_attr(test, derive(serde::Deserialize, fake::Dummy, PartialEq))]
+#[derive(DebugAsJson, Clone, derive_new::new, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(test, derive(fake::Dummy, PartialEq))]
 pub struct LocalTransactionExecution {
     pub input: TransactionInput,
     pub result: EvmExecutionResult,
@@ -100,7 +100,7 @@ impl LocalTransactionExecution {
     }
 }
 
-#[derive(DebugAsJson, Clone, derive_new::new, serde::Serialize)]
+#[derive(DebugAsJson, Clone, derive_new::new, serde::Serialize, serde::Deserialize)]
 pub struct ExternalTransactionExecution {
     pub tx: ExternalTransaction,
     pub receipt: ExternalReceipt,
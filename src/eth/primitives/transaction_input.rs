@@ -4,14 +4,26 @@ use ethereum_types::U256;
 use ethereum_types::U64;
 use ethers_core::types::NameOrAddress;
 use ethers_core::types::TransactionRequest;
+use ethers_core::utils::keccak256;
 use fake::Dummy;
 use fake::Fake;
 use fake::Faker;
 use rlp::Decodable;
 use serde::Deserialize;
 
+#[cfg(feature = "alloy")]
+use crate::alias::AlloyAccessList;
+#[cfg(feature = "alloy")]
+use crate::alias::AlloyAccessListItem;
+#[cfg(feature = "alloy")]
+use crate::alias::AlloySignature;
+#[cfg(feature = "alloy")]
+use crate::alias::AlloyTransaction;
 use crate::alias::EthersTransaction;
 use crate::alias::JsonValue;
+#[cfg(feature = "alloy")]
+use crate::alias::RevmU256;
+use crate::eth::primitives::AccessList;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::Bytes;
 use crate::eth::primitives::ChainId;
@@ -43,11 +55,64 @@ pub struct TransactionInput {
     pub gas_limit: Gas,
     pub gas_price: Wei,
 
+    /// Max fee per gas of EIP-1559 transactions.
+    #[serde(default)]
+    pub max_fee_per_gas: Option<Wei>,
+
+    /// Max priority fee per gas of EIP-1559 transactions.
+    #[serde(default)]
+    pub max_priority_fee_per_gas: Option<Wei>,
+
+    /// Access list of EIP-2930 and EIP-1559 transactions.
+    #[serde(default)]
+    pub access_list: Option<AccessList>,
+
     pub v: U64,
     pub r: U256,
     pub s: U256,
 }
 
+impl TransactionInput {
+    /// Creates an unsigned transaction sent on behalf of an impersonated account with `eth_sendTransaction`.
+    ///
+    /// As there is no signature, the hash is derived from the unsigned RLP encoding of the request.
+    pub fn new_unsigned(request: TransactionRequest, nonce: Nonce, chain_id: ChainId) -> anyhow::Result<Self> {
+        let Some(from) = request.from else {
+            return Err(anyhow!("Transaction sender is missing."));
+        };
+        let request = request.nonce(nonce).chain_id(chain_id.0);
+        let hash = Hash::new(keccak256(request.rlp()));
+
+        Ok(Self {
+            tx_type: Some(U64::zero()),
+            chain_id: Some(chain_id),
+            hash,
+            nonce,
+            signer: from.into(),
+            from: from.into(),
+            to: request.to.map_into(),
+            value: request.value.unwrap_or_default().into(),
+            input: request.data.unwrap_or_default().into(),
+            gas_limit: match request.gas {
+                Some(gas) => gas.try_into()?,
+                None => Gas::MAX,
+            },
+            gas_price: request.gas_price.unwrap_or_default().into(),
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            access_list: None,
+            v: U64::zero(),
+            r: U256::zero(),
+            s: U256::zero(),
+        })
+    }
+
+    /// Checks if the transaction has no signature.
+    pub fn is_unsigned(&self) -> bool {
+        self.r.is_zero() && self.s.is_zero()
+    }
+}
+
 impl Dummy<Faker> for TransactionInput {
     fn dummy_with_rng<R: ethers_core::rand::prelude::Rng + ?Sized>(faker: &Faker, rng: &mut R) -> Self {
         Self {
@@ -62,6 +127,9 @@ impl Dummy<Faker> for TransactionInput {
             input: faker.fake_with_rng(rng),
             gas_limit: faker.fake_with_rng(rng),
             gas_price: faker.fake_with_rng(rng),
+            max_fee_per_gas: faker.fake_with_rng(rng),
+            max_priority_fee_per_gas: faker.fake_with_rng(rng),
+            access_list: faker.fake_with_rng(rng),
             v: rng.next_u64().into(),
             r: rng.next_u64().into(),
             s: rng.next_u64().into(),
@@ -129,6 +197,9 @@ fn try_from_ethers_transaction(value: EthersTransaction, compute_signer: bool) -
         input: value.input.clone().into(),
         gas_limit: value.gas.try_into()?,
         gas_price: value.gas_price.unwrap_or_default().into(),
+        max_fee_per_gas: value.max_fee_per_gas.map_into(),
+        max_priority_fee_per_gas: value.max_priority_fee_per_gas.map_into(),
+        access_list: value.access_list.map_into(),
         v: value.v,
         r: value.r,
         s: value.s,
@@ -151,6 +222,9 @@ impl From<TransactionInput> for EthersTransaction {
             input: value.input.clone().into(),
             gas: value.gas_limit.into(),
             gas_price: Some(value.gas_price.into()),
+            max_fee_per_gas: value.max_fee_per_gas.map_into(),
+            max_priority_fee_per_gas: value.max_priority_fee_per_gas.map_into(),
+            access_list: value.access_list.map_into(),
             v: value.v,
             r: value.r,
             s: value.s,
@@ -160,6 +234,45 @@ impl From<TransactionInput> for EthersTransaction {
     }
 }
 
+#[cfg(feature = "alloy")]
+impl From<TransactionInput> for AlloyTransaction {
+    fn from(value: TransactionInput) -> Self {
+        Self {
+            chain_id: value.chain_id.map_into(),
+            hash: value.hash.into(),
+            nonce: value.nonce.as_u64(),
+            from: value.signer.into(),
+            to: value.to.map_into(),
+            value: value.value.into(),
+            input: value.input.into(),
+            gas: value.gas_limit.as_u64().into(),
+            gas_price: Some(value.gas_price.0.low_u128()),
+            max_fee_per_gas: value.max_fee_per_gas.map(|fee| fee.0.low_u128()),
+            max_priority_fee_per_gas: value.max_priority_fee_per_gas.map(|fee| fee.0.low_u128()),
+            access_list: value.access_list.map(|access_list| {
+                AlloyAccessList(
+                    access_list
+                        .0
+                        .into_iter()
+                        .map(|item| AlloyAccessListItem {
+                            address: item.address.into(),
+                            storage_keys: item.storage_keys.into_iter().map(Into::into).collect(),
+                        })
+                        .collect(),
+                )
+            }),
+            signature: Some(AlloySignature {
+                r: RevmU256::from_limbs(value.r.0),
+                s: RevmU256::from_limbs(value.s.0),
+                v: RevmU256::from(value.v.as_u64()),
+                y_parity: None,
+            }),
+            transaction_type: value.tx_type.map(|tx_type| tx_type.low_u32() as u8),
+            ..Default::default()
+        }
+    }
+}
+
 impl From<TransactionInput> for TransactionRequest {
     fn from(value: TransactionInput) -> Self {
         let input = value;
@@ -186,3 +299,69 @@ impl TryFrom<JsonValue> for TransactionInput {
         }
     }
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use ethereum_types::H160;
+    use ethereum_types::H256;
+    use ethers_core::types::transaction::eip2930::AccessListItem;
+
+    use super::*;
+    use crate::alias::EthersAccessList;
+    use crate::ext::to_json_value;
+    use crate::if_else;
+
+    fn external_transaction(tx_type: u64) -> ExternalTransaction {
+        let access_list = EthersAccessList(vec![AccessListItem {
+            address: H160::repeat_byte(3),
+            storage_keys: vec![H256::repeat_byte(4), H256::repeat_byte(5)],
+        }]);
+        ExternalTransaction(EthersTransaction {
+            hash: H256::repeat_byte(1),
+            nonce: 7.into(),
+            block_hash: Some(H256::repeat_byte(2)),
+            block_number: Some(10.into()),
+            transaction_index: Some(1.into()),
+            from: H160::repeat_byte(6),
+            to: Some(H160::repeat_byte(7)),
+            value: 1_000.into(),
+            gas_price: Some(20.into()),
+            gas: 21_000.into(),
+            input: vec![1, 2, 3].into(),
+            v: 1.into(),
+            r: 11.into(),
+            s: 12.into(),
+            transaction_type: Some(tx_type.into()),
+            access_list: if_else!(tx_type >= 1, Some(access_list), None),
+            max_priority_fee_per_gas: if_else!(tx_type == 2, Some(2.into()), None),
+            max_fee_per_gas: if_else!(tx_type == 2, Some(30.into()), None),
+            chain_id: Some(2008.into()),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn external_transaction_conversion_is_lossless() {
+        for tx_type in [0, 1, 2] {
+            let external = external_transaction(tx_type);
+            let input = TransactionInput::try_from(external.clone()).unwrap();
+
+            // block fields are kept by the mined transaction, not by the input
+            let mut expected = external.0.clone();
+            expected.block_hash = None;
+            expected.block_number = None;
+            expected.transaction_index = None;
+
+            let converted = EthersTransaction::from(input.clone());
+            assert_eq!(converted, expected, "tx_type={}", tx_type);
+            assert_eq!(converted.rlp(), external.0.rlp(), "tx_type={}", tx_type);
+
+            // serialized input keeps all fields
+            assert_eq!(TransactionInput::try_from(to_json_value(&input)).unwrap(), input, "tx_type={}", tx_type);
+        }
+    }
+}
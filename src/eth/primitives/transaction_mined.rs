@@ -1,19 +1,36 @@
 use std::hash::Hash as HashTrait;
 
+#[cfg(feature = "alloy")]
+use alloy_rpc_types_eth::Receipt as AlloyConsensusReceipt;
+#[cfg(feature = "alloy")]
+use alloy_rpc_types_eth::ReceiptEnvelope;
+#[cfg(feature = "alloy")]
+use alloy_rpc_types_eth::ReceiptWithBloom;
 use display_json::DebugAsJson;
+use ethereum_types::Bloom;
+use ethereum_types::H160;
+use ethereum_types::H256;
 use itertools::Itertools;
+use rlp::RlpStream;
 
+#[cfg(feature = "alloy")]
+use crate::alias::AlloyReceipt;
+#[cfg(feature = "alloy")]
+use crate::alias::AlloyTransaction;
 use crate::alias::EthersReceipt;
 use crate::alias::EthersTransaction;
 use crate::eth::primitives::logs_bloom::LogsBloom;
+use crate::eth::primitives::Address;
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::EvmExecution;
 use crate::eth::primitives::ExternalReceipt;
 use crate::eth::primitives::ExternalTransaction;
+use crate::eth::primitives::Gas;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::Index;
 use crate::eth::primitives::LogMined;
 use crate::eth::primitives::TransactionInput;
+use crate::ext::not;
 use crate::ext::OptionExt;
 use crate::if_else;
 
@@ -29,9 +46,17 @@ pub struct TransactionMined {
     /// Logs added to the block.
     pub logs: Vec<LogMined>,
 
+    /// Bloom of the logs emitted by the transaction.
+    #[serde(default)]
+    pub logs_bloom: LogsBloom,
+
     /// Position of the transaction inside the block.
     pub transaction_index: Index,
 
+    /// Gas used by the transaction and all transactions before it in the block.
+    #[serde(default)]
+    pub cumulative_gas_used: Gas,
+
     /// Block number where the transaction was mined.
     pub block_number: BlockNumber,
 
@@ -68,6 +93,8 @@ impl TransactionMined {
             block_number: receipt.block_number(),
             block_hash: receipt.block_hash(),
             transaction_index: receipt.transaction_index.into(),
+            cumulative_gas_used: receipt.cumulative_gas_used.try_into()?,
+            logs_bloom: receipt.logs_bloom.into(),
             logs: receipt.0.logs.into_iter().map(LogMined::try_from).collect::<Result<Vec<LogMined>, _>>()?,
         })
     }
@@ -77,12 +104,62 @@ impl TransactionMined {
         self.execution.is_success()
     }
 
-    fn compute_bloom(&self) -> LogsBloom {
-        let mut bloom = LogsBloom::default();
-        for log_mined in self.logs.iter() {
-            bloom.accrue_log(&(log_mined.log));
+    /// Checks if the transaction is a typed transaction as specified by EIP-2718.
+    pub fn is_typed(&self) -> bool {
+        matches!(self.input.tx_type.map(|tx_type| tx_type.as_u64()), Some(1..=3))
+    }
+
+    /// Addresses the transaction is indexed by: the sender, the recipient and the deployed contract.
+    pub fn indexed_addresses(&self) -> Vec<Address> {
+        let mut addresses = vec![self.input.signer];
+        addresses.extend(self.input.to);
+        addresses.extend(self.execution.deployed_contract_address);
+        addresses.into_iter().unique().collect()
+    }
+
+    /// Computes the bloom of the logs emitted by the transaction.
+    pub fn compute_bloom(&self) -> LogsBloom {
+        LogsBloom::from_logs(self.logs.iter().map(|log_mined| &log_mined.log))
+    }
+
+    /// Encodes the signed transaction used as leaf of the block transactions trie.
+    ///
+    /// Typed transactions are prefixed with their type as specified by EIP-2718.
+    pub fn encode_transaction(&self) -> Vec<u8> {
+        EthersTransaction::from(self.input.clone()).rlp().to_vec()
+    }
+
+    /// Encodes the consensus receipt used as leaf of the block receipts trie.
+    ///
+    /// Typed transactions are prefixed with their type as specified by EIP-2718.
+    pub fn encode_receipt(&self, cumulative_gas_used: Gas) -> Vec<u8> {
+        let mut s = RlpStream::new_list(4);
+        s.append(&u8::from(self.is_success()));
+        s.append(&cumulative_gas_used.as_u64());
+        s.append(&Bloom::from(self.stored_or_computed_bloom()));
+        s.begin_list(self.logs.len());
+        for log_mined in &self.logs {
+            let log = &log_mined.log;
+            s.begin_list(3);
+            s.append(&H160::from(log.address));
+            s.append_list(&log.topics().into_iter().map(H256::from).collect_vec());
+            s.append(&log.data.0);
+        }
+        let payload = s.out().to_vec();
+
+        match self.input.tx_type.map(|tx_type| tx_type.as_u64()) {
+            Some(tx_type @ 1..=3) => [vec![tx_type as u8], payload].concat(),
+            _ => payload,
+        }
+    }
+
+    /// Returns the stored bloom, or computes it for transactions saved before blooms were stored.
+    fn stored_or_computed_bloom(&self) -> LogsBloom {
+        if self.logs_bloom.is_empty() && not(self.logs.is_empty()) {
+            self.compute_bloom()
+        } else {
+            self.logs_bloom
         }
-        bloom
     }
 }
 
@@ -116,12 +193,13 @@ impl From<TransactionMined> for EthersTransaction {
 
 impl From<TransactionMined> for EthersReceipt {
     fn from(value: TransactionMined) -> Self {
-        let logs_bloom = value.compute_bloom().into();
+        let logs_bloom = value.stored_or_computed_bloom();
         Self {
             // receipt specific
             status: Some(if_else!(value.is_success(), 1, 0).into()),
             contract_address: value.execution.contract_address().map_into(),
             gas_used: Some(value.execution.gas.into()),
+            cumulative_gas_used: value.cumulative_gas_used.into(),
 
             // transaction
             transaction_hash: value.input.hash.into(),
@@ -135,7 +213,7 @@ impl From<TransactionMined> for EthersReceipt {
 
             // logs
             logs: value.logs.into_iter().map_into().collect(),
-            logs_bloom, // TODO: save this to the database instead of computing it every time (could also be useful for eth_getLogs)
+            logs_bloom: logs_bloom.into(),
 
             // TODO: there are more fields to populate here
             ..Default::default()
@@ -143,6 +221,60 @@ impl From<TransactionMined> for EthersReceipt {
     }
 }
 
+#[cfg(feature = "alloy")]
+impl From<TransactionMined> for AlloyTransaction {
+    fn from(value: TransactionMined) -> Self {
+        Self {
+            block_hash: Some(value.block_hash.into()),
+            block_number: Some(value.block_number.as_u64()),
+            transaction_index: Some(value.transaction_index.0),
+            ..value.input.into()
+        }
+    }
+}
+
+#[cfg(feature = "alloy")]
+impl From<TransactionMined> for AlloyReceipt {
+    fn from(value: TransactionMined) -> Self {
+        let logs_bloom = value.stored_or_computed_bloom();
+        let receipt = ReceiptWithBloom {
+            receipt: AlloyConsensusReceipt {
+                status: value.is_success().into(),
+                cumulative_gas_used: value.cumulative_gas_used.as_u64().into(),
+                logs: value.logs.into_iter().map_into().collect(),
+            },
+            logs_bloom: logs_bloom.into(),
+        };
+        let envelope = match value.input.tx_type.map(|tx_type| tx_type.as_u64()) {
+            Some(1) => ReceiptEnvelope::Eip2930(receipt),
+            Some(2) => ReceiptEnvelope::Eip1559(receipt),
+            Some(3) => ReceiptEnvelope::Eip4844(receipt),
+            _ => ReceiptEnvelope::Legacy(receipt),
+        };
+
+        Self {
+            // receipt specific
+            inner: envelope,
+            contract_address: value.execution.contract_address().map_into(),
+            gas_used: value.execution.gas.as_u64().into(),
+            effective_gas_price: value.input.gas_price.0.low_u128(),
+            blob_gas_used: None,
+            blob_gas_price: None,
+            state_root: None,
+
+            // transaction
+            transaction_hash: value.input.hash.into(),
+            from: value.input.signer.into(),
+            to: value.input.to.map_into(),
+
+            // block
+            block_hash: Some(value.block_hash.into()),
+            block_number: Some(value.block_number.as_u64()),
+            transaction_index: Some(value.transaction_index.0),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use fake::Fake;
@@ -156,7 +288,9 @@ mod tests {
             input: Faker.fake(),
             execution: Faker.fake(),
             logs: vec![],
+            logs_bloom: LogsBloom::default(),
             transaction_index: transaction_index.into(),
+            cumulative_gas_used: Gas::ZERO,
             block_number: block_number.into(),
             block_hash: Hash::default(),
         }
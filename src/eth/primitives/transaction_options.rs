@@ -0,0 +1,21 @@
+use display_json::DebugAsJson;
+
+use crate::eth::primitives::BlockNumber;
+
+/// Optional client-supplied constraints sent alongside a raw transaction.
+#[derive(DebugAsJson, Clone, Copy, Default, PartialEq, Eq, fake::Dummy, serde::Serialize, serde::Deserialize)]
+pub struct TransactionOptions {
+    /// Last block number in which the transaction can be included. Transactions still queued after it are discarded.
+    #[serde(rename = "validUntilBlock", default, skip_serializing_if = "Option::is_none")]
+    pub valid_until_block: Option<BlockNumber>,
+}
+
+impl TransactionOptions {
+    /// Checks if the transaction can no longer be included in the specified block.
+    pub fn is_expired_at(&self, block_number: BlockNumber) -> bool {
+        match self.valid_until_block {
+            Some(valid_until_block) => block_number > valid_until_block,
+            None => false,
+        }
+    }
+}
@@ -1,5 +1,5 @@
-use crate::alias::EthersReceipt;
-use crate::alias::EthersTransaction;
+use crate::alias::JsonRpcReceipt;
+use crate::alias::JsonRpcTransaction;
 use crate::alias::JsonValue;
 use crate::eth::primitives::TransactionExecution;
 use crate::eth::primitives::TransactionMined;
@@ -21,18 +21,19 @@ impl TransactionStage {
     pub fn to_json_rpc_transaction(self) -> JsonValue {
         match self {
             TransactionStage::Executed(TransactionExecution::Local(tx)) => {
-                let json_rpc_payload: EthersTransaction = tx.input.into();
+                let json_rpc_payload: JsonRpcTransaction = tx.input.into();
                 to_json_value(json_rpc_payload)
             }
             TransactionStage::Executed(TransactionExecution::External(tx)) => {
                 // remove block information because we don't know to which local block the transaction will be added to.
+                // external transactions are always serialized using ethers types because they are received from the external node in that format.
                 let mut ethers_tx = tx.tx.0;
                 ethers_tx.block_number = None;
                 ethers_tx.block_hash = None;
                 to_json_value(ethers_tx)
             }
             TransactionStage::Mined(tx) => {
-                let json_rpc_payload: EthersTransaction = tx.into();
+                let json_rpc_payload: JsonRpcTransaction = tx.into();
                 to_json_value(json_rpc_payload)
             }
         }
@@ -43,7 +44,7 @@ impl TransactionStage {
         match self {
             TransactionStage::Executed(_) => JsonValue::Null,
             TransactionStage::Mined(tx) => {
-                let json_rpc_format: EthersReceipt = tx.into();
+                let json_rpc_format: JsonRpcReceipt = tx.into();
                 to_json_value(json_rpc_format)
             }
         }
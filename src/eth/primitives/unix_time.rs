@@ -9,6 +9,7 @@ use fake::Dummy;
 use fake::Faker;
 
 use crate::alias::RevmU256;
+use crate::eth::executor::assert_no_wall_clock_read;
 
 #[derive(DebugAsJson, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct UnixTime(u64);
@@ -18,11 +19,13 @@ impl UnixTime {
 
     #[cfg(not(feature = "dev"))]
     pub fn now() -> Self {
+        assert_no_wall_clock_read("UnixTime::now");
         Self(Utc::now().timestamp() as u64)
     }
 
     #[cfg(feature = "dev")]
     pub fn now() -> Self {
+        assert_no_wall_clock_read("UnixTime::now");
         offset::now()
     }
 
@@ -31,6 +34,12 @@ impl UnixTime {
         offset::set(timestamp, latest_timestamp)
     }
 
+    /// Shifts the clock forward by `seconds`, returning the total offset from the wall clock in seconds.
+    #[cfg(feature = "dev")]
+    pub fn increase_offset(seconds: u64) -> anyhow::Result<i64> {
+        offset::increase(seconds)
+    }
+
     pub fn to_i64(&self) -> i64 {
         self.0.try_into().expect("UNIX time is unrealistically high")
     }
@@ -132,6 +141,22 @@ mod offset {
         Ok(())
     }
 
+    pub fn increase(seconds: u64) -> anyhow::Result<i64> {
+        use crate::log_and_err;
+
+        let Ok(seconds) = i64::try_from(seconds) else {
+            return log_and_err!("time increase is too high");
+        };
+
+        // a pending next timestamp is shifted too, so the increase is not lost when it is consumed
+        let _ = NEXT_TIMESTAMP.fetch_update(SeqCst, SeqCst, |next| match next {
+            0 => None,
+            next => Some(next.saturating_add(seconds as u64)),
+        });
+        let previous = TIME_OFFSET.fetch_add(seconds, SeqCst);
+        Ok(previous.saturating_add(seconds))
+    }
+
     pub fn now() -> UnixTime {
         let offset_time = NEXT_TIMESTAMP.load(Acquire);
         let time_offset = TIME_OFFSET.load(Acquire);
@@ -0,0 +1,73 @@
+use async_trait::async_trait;
+use display_json::DebugAsJson;
+
+use crate::alias::JsonRpcHeader;
+use crate::alias::JsonValue;
+use crate::eth::primitives::BlockHeader;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::LogMined;
+use crate::ext::to_json_value;
+
+/// Destination of the events of mined blocks, like a Kafka cluster or a NATS server.
+#[async_trait]
+pub trait EventPublisher: Send + Sync + 'static {
+    /// Name of the destination, used to track which blocks were already delivered to it.
+    fn name(&self) -> &str;
+
+    /// Publishes events in order, returning only after the destination acknowledged all of them.
+    async fn publish(&self, events: &[PublisherEvent]) -> anyhow::Result<()>;
+}
+
+/// Kind of entity described by a published event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, strum::IntoStaticStr, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PublisherEventKind {
+    #[strum(to_string = "block")]
+    Block,
+
+    #[strum(to_string = "log")]
+    Log,
+}
+
+/// Mined block or log serialized to be published.
+#[derive(DebugAsJson, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublisherEvent {
+    pub kind: PublisherEventKind,
+
+    pub block_number: BlockNumber,
+
+    /// Key used by destinations to partition events.
+    ///
+    /// All events of a block share the same key, so they are delivered in the same order they were published.
+    #[serde(skip)]
+    pub key: String,
+
+    /// Block header or log in JSON-RPC format.
+    pub payload: JsonValue,
+}
+
+impl PublisherEvent {
+    /// Creates the events of a mined block, with the block header first followed by its logs.
+    pub fn from_block(header: BlockHeader, logs: Vec<LogMined>) -> Vec<Self> {
+        let block_number = header.number;
+        let key = block_number.to_string();
+
+        let mut events = Vec::with_capacity(1 + logs.len());
+        events.push(Self {
+            kind: PublisherEventKind::Block,
+            block_number,
+            key: key.clone(),
+            payload: to_json_value(JsonRpcHeader::from(header)),
+        });
+        for log in logs {
+            events.push(Self {
+                kind: PublisherEventKind::Log,
+                block_number,
+                key: key.clone(),
+                payload: log.to_json_rpc_log(),
+            });
+        }
+        events
+    }
+}
@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use futures::future::join_all;
+use itertools::Itertools;
+use rdkafka::config::ClientConfig;
+use rdkafka::producer::FutureProducer;
+use rdkafka::producer::FutureRecord;
+
+use crate::eth::publisher::EventPublisher;
+use crate::eth::publisher::PublisherEvent;
+use crate::eth::publisher::PublisherEventKind;
+use crate::ext::to_json_string;
+
+/// Max time an event waits in the producer queue before failing.
+const QUEUE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Publishes events to Kafka topics, one for blocks and one for logs.
+///
+/// The producer is idempotent and waits for all in-sync replicas, so events of a block keep their order inside their partition even
+/// when the producer retries.
+pub struct KafkaEventPublisher {
+    producer: FutureProducer,
+    blocks_topic: String,
+    logs_topic: String,
+}
+
+impl KafkaEventPublisher {
+    pub fn new(brokers: &str, blocks_topic: String, logs_topic: String) -> anyhow::Result<Self> {
+        tracing::info!(%brokers, %blocks_topic, %logs_topic, "creating kafka publisher");
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("enable.idempotence", "true")
+            .set("acks", "all")
+            .create()
+            .context("failed to create kafka producer")?;
+
+        Ok(Self {
+            producer,
+            blocks_topic,
+            logs_topic,
+        })
+    }
+
+    fn topic(&self, kind: PublisherEventKind) -> &str {
+        match kind {
+            PublisherEventKind::Block => &self.blocks_topic,
+            PublisherEventKind::Log => &self.logs_topic,
+        }
+    }
+}
+
+#[async_trait]
+impl EventPublisher for KafkaEventPublisher {
+    fn name(&self) -> &str {
+        "kafka"
+    }
+
+    async fn publish(&self, events: &[PublisherEvent]) -> anyhow::Result<()> {
+        // enqueue all events before awaiting acknowledgements, so a block is delivered in a single round-trip
+        let payloads = events.iter().map(to_json_string).collect_vec();
+        let deliveries = events.iter().zip(&payloads).map(|(event, payload)| {
+            let record = FutureRecord::to(self.topic(event.kind)).key(&event.key).payload(payload);
+            self.producer.send(record, QUEUE_TIMEOUT)
+        });
+
+        for delivery in join_all(deliveries).await {
+            if let Err((e, _)) = delivery {
+                return Err(e).context("failed to deliver event to kafka");
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,15 @@
+//! Publishing of mined blocks and logs to external event streams.
+
+mod event_publisher;
+#[cfg(feature = "kafka")]
+mod kafka;
+#[allow(clippy::module_inception)]
+mod publisher;
+
+pub use event_publisher::EventPublisher;
+pub use event_publisher::PublisherEvent;
+pub use event_publisher::PublisherEventKind;
+#[cfg(feature = "kafka")]
+pub use kafka::KafkaEventPublisher;
+pub use publisher::Publisher;
+pub use publisher::PublisherConfig;
@@ -0,0 +1,378 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use clap::Parser;
+use display_json::DebugAsJson;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::eth::miner::Miner;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockHeader;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::LogMined;
+use crate::eth::primitives::StratusError;
+use crate::eth::publisher::EventPublisher;
+#[cfg(feature = "kafka")]
+use crate::eth::publisher::KafkaEventPublisher;
+use crate::eth::publisher::PublisherEvent;
+use crate::eth::storage::StratusStorage;
+use crate::ext::parse_duration;
+use crate::ext::spawn_named;
+use crate::ext::traced_sleep;
+use crate::ext::SleepReason;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
+use crate::GlobalState;
+
+const TASK_NAME: &str = "publisher";
+
+/// Interval between shutdown checks when no block is mined.
+const IDLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Delay before the first retry of a failed delivery. Following retries double the delay up to the configured max.
+const RETRY_MIN_DELAY: Duration = Duration::from_millis(100);
+
+// -----------------------------------------------------------------------------
+// Publisher
+// -----------------------------------------------------------------------------
+
+/// Publishes the events of mined blocks to a destination with at-least-once delivery.
+///
+/// Events are built from the miner block and log notifications. The last block acknowledged by the destination is persisted in the
+/// permanent storage outbox, so blocks mined while the publisher was stopped, lagging or failing are read back from the storage and
+/// published in order. A block is published again if the node stops after the destination acknowledged it but before the outbox is
+/// updated, so consumers must tolerate duplicates. Blocks produced again after a rollback are only published once the chain passes the
+/// last published block or the publisher restarts.
+pub struct Publisher {
+    storage: Arc<StratusStorage>,
+
+    destination: Arc<dyn EventPublisher>,
+
+    /// First block published when the destination has no delivered blocks yet.
+    from_block: Option<BlockNumber>,
+
+    /// Max delay between delivery retries.
+    retry_max_delay: Duration,
+}
+
+impl Publisher {
+    pub fn new(storage: Arc<StratusStorage>, destination: Arc<dyn EventPublisher>, from_block: Option<BlockNumber>, retry_max_delay: Duration) -> Self {
+        Self {
+            storage,
+            destination,
+            from_block,
+            retry_max_delay,
+        }
+    }
+
+    /// Spawns the task that publishes blocks notified by the miner, restarting it from the outbox after failures.
+    pub fn spawn(self: Arc<Self>, miner: &Miner) {
+        let notifier_blocks = miner.notifier_blocks.clone();
+        let notifier_logs = miner.notifier_logs.clone();
+
+        spawn_named(TASK_NAME, async move {
+            loop {
+                // subscribe before reading the outbox, so blocks mined in between are not missed
+                let result = self.run(notifier_blocks.subscribe(), notifier_logs.subscribe()).await;
+                if GlobalState::is_shutdown_warn(TASK_NAME) {
+                    return;
+                }
+                if let Err(e) = result {
+                    tracing::error!(reason = ?e, publisher = %self.destination.name(), "publisher failed. restarting with delay.");
+                    traced_sleep(self.retry_max_delay, SleepReason::RetryBackoff).await;
+                }
+            }
+        });
+    }
+
+    async fn run(&self, mut blocks: broadcast::Receiver<BlockHeader>, mut logs: broadcast::Receiver<LogMined>) -> Result<(), StratusError> {
+        let publisher = self.destination.name();
+
+        // publish blocks mined while the publisher was stopped
+        let mut next = self.first_block()?;
+        let mined_number = self.storage.read_mined_block_number()?;
+        tracing::info!(%publisher, %next, %mined_number, "starting publisher");
+        next = self.publish_stored(next, mined_number).await?;
+
+        // logs are notified before their block header, so they are buffered until the header arrives
+        let mut pending_logs: BTreeMap<BlockNumber, Vec<LogMined>> = BTreeMap::new();
+        let mut logs_lagged = false;
+
+        loop {
+            if GlobalState::is_shutdown_warn(TASK_NAME) {
+                return Ok(());
+            }
+
+            tokio::select! {
+                // logs go first, so all logs of a block are buffered before its header is handled
+                biased;
+
+                log = logs.recv() => match log {
+                    Ok(log) => pending_logs.entry(log.block_number).or_default().push(log),
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!(%publisher, %skipped, "publisher lagged behind log notifications. reading next block from storage.");
+                        pending_logs.clear();
+                        logs_lagged = true;
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+
+                header = blocks.recv() => match header {
+                    Ok(header) => {
+                        let number = header.number;
+                        let block_logs = pending_logs.remove(&number).unwrap_or_default();
+                        pending_logs.retain(|log_block, _| *log_block > number);
+
+                        // block already published from storage
+                        if number < next {
+                            continue;
+                        }
+
+                        // blocks are missing or logs may be incomplete, so read them from storage
+                        if number > next || logs_lagged {
+                            next = self.publish_stored(next, number).await?;
+                            logs_lagged = false;
+                            continue;
+                        }
+
+                        self.deliver(number, PublisherEvent::from_block(header, block_logs)).await?;
+                        next = number.next_block_number();
+                    }
+                    // next header is ahead of the last published block, so skipped blocks are read from storage
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!(%publisher, %skipped, "publisher lagged behind block notifications. reading skipped blocks from storage.");
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+
+                _ = traced_sleep(IDLE_INTERVAL, SleepReason::Interval) => {}
+            }
+        }
+    }
+
+    /// Block following the last block delivered to the destination.
+    fn first_block(&self) -> Result<BlockNumber, StratusError> {
+        if let Some(cursor) = self.storage.read_publisher_cursor(self.destination.name())? {
+            return Ok(cursor.next_block_number());
+        }
+        match self.from_block {
+            Some(from_block) => Ok(from_block),
+            None => Ok(self.storage.read_mined_block_number()?.next_block_number()),
+        }
+    }
+
+    /// Publishes blocks in the range (inclusive) reading them from the storage.
+    ///
+    /// Returns the block following the last published block.
+    pub async fn publish_stored(&self, from: BlockNumber, to: BlockNumber) -> Result<BlockNumber, StratusError> {
+        let mut next = from;
+        while next <= to {
+            if GlobalState::is_shutdown() {
+                break;
+            }
+
+            // pruned blocks have no logs, but their headers are still published
+            if let Some(block) = self.storage.read_block(&BlockFilter::Number(next))? {
+                let logs = block.transactions.into_iter().flat_map(|tx| tx.logs).collect();
+                self.deliver(next, PublisherEvent::from_block(block.header, logs)).await?;
+            }
+            next = next.next_block_number();
+        }
+        Ok(next)
+    }
+
+    /// Publishes the events of a block, retrying until the destination acknowledges them, and moves the outbox cursor to the block.
+    async fn deliver(&self, number: BlockNumber, events: Vec<PublisherEvent>) -> Result<(), StratusError> {
+        let publisher = self.destination.name();
+        let mut retry_delay = RETRY_MIN_DELAY;
+
+        loop {
+            let start = Instant::now();
+            let result = self.destination.publish(&events).await;
+            #[cfg(feature = "metrics")]
+            metrics::inc_publisher_delivery(start.elapsed(), publisher, result.is_ok());
+
+            match result {
+                Ok(()) => break,
+                Err(e) => {
+                    tracing::error!(reason = ?e, %publisher, %number, elapsed = ?start.elapsed(), ?retry_delay, "failed to publish block events. retrying.");
+                    if GlobalState::is_shutdown() {
+                        return Err(StratusError::StratusShutdown);
+                    }
+                    traced_sleep(retry_delay, SleepReason::RetryBackoff).await;
+                    retry_delay = (retry_delay * 2).min(self.retry_max_delay);
+                }
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        {
+            for event in &events {
+                metrics::inc_publisher_events_published(publisher, <&'static str>::from(event.kind));
+            }
+            metrics::set_publisher_cursor(number.as_u64(), publisher);
+        }
+
+        // a failure only causes the block to be published again after a restart
+        if let Err(e) = self.storage.save_publisher_cursor(publisher, number) {
+            tracing::error!(reason = ?e, %publisher, %number, "failed to save publisher cursor");
+        }
+        Ok(())
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Config
+// -----------------------------------------------------------------------------
+
+/// Publishers of mined blocks and logs to external event streams. No publisher is started if no destination is configured.
+#[derive(DebugAsJson, Clone, Parser, serde::Serialize)]
+pub struct PublisherConfig {
+    /// First block published to destinations that have no delivered blocks yet. Defaults to the block after the last mined block.
+    #[arg(long = "publisher-from-block", env = "PUBLISHER_FROM_BLOCK")]
+    pub publisher_from_block: Option<u64>,
+
+    /// Max delay between retries when a destination fails to acknowledge events.
+    #[arg(long = "publisher-retry-max-delay", value_parser=parse_duration, env = "PUBLISHER_RETRY_MAX_DELAY", default_value = "10s")]
+    pub publisher_retry_max_delay: Duration,
+
+    /// Comma-separated Kafka bootstrap servers. Enables publishing to Kafka.
+    #[cfg(feature = "kafka")]
+    #[arg(long = "publisher-kafka-brokers", env = "PUBLISHER_KAFKA_BROKERS")]
+    pub publisher_kafka_brokers: Option<String>,
+
+    /// Kafka topic that receives block events.
+    #[cfg(feature = "kafka")]
+    #[arg(long = "publisher-kafka-blocks-topic", env = "PUBLISHER_KAFKA_BLOCKS_TOPIC", default_value = "stratus.blocks")]
+    pub publisher_kafka_blocks_topic: String,
+
+    /// Kafka topic that receives log events.
+    #[cfg(feature = "kafka")]
+    #[arg(long = "publisher-kafka-logs-topic", env = "PUBLISHER_KAFKA_LOGS_TOPIC", default_value = "stratus.logs")]
+    pub publisher_kafka_logs_topic: String,
+}
+
+impl PublisherConfig {
+    /// Creates the configured destinations.
+    pub fn destinations(&self) -> anyhow::Result<Vec<Arc<dyn EventPublisher>>> {
+        #[allow(unused_mut)]
+        let mut destinations: Vec<Arc<dyn EventPublisher>> = Vec::new();
+
+        #[cfg(feature = "kafka")]
+        if let Some(ref brokers) = self.publisher_kafka_brokers {
+            let kafka = KafkaEventPublisher::new(brokers, self.publisher_kafka_blocks_topic.clone(), self.publisher_kafka_logs_topic.clone())?;
+            destinations.push(Arc::new(kafka));
+        }
+
+        Ok(destinations)
+    }
+
+    /// Inits and spawns one [`Publisher`] for each configured destination.
+    pub fn init(&self, storage: Arc<StratusStorage>, miner: &Miner) -> anyhow::Result<Vec<Arc<Publisher>>> {
+        let destinations = self.destinations()?;
+        if destinations.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        tracing::info!(config = ?self, "creating publishers");
+
+        let from_block = self.publisher_from_block.map(BlockNumber::from);
+        let publishers = destinations
+            .into_iter()
+            .map(|destination| {
+                let publisher = Arc::new(Publisher::new(Arc::clone(&storage), destination, from_block, self.publisher_retry_max_delay));
+                Arc::clone(&publisher).spawn(miner);
+                publisher
+            })
+            .collect();
+        Ok(publishers)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::eth::primitives::Block;
+    use crate::eth::primitives::TransactionMined;
+    use crate::eth::primitives::UnixTime;
+    use crate::eth::publisher::PublisherEventKind;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::InMemoryTemporaryStorage;
+    use crate::ext::MutexExt;
+    use crate::utils::test_utils::fake_first;
+
+    /// Destination that fails the first attempt of each block.
+    #[derive(Default)]
+    struct FlakyPublisher {
+        attempts: Mutex<usize>,
+        published: Mutex<Vec<PublisherEvent>>,
+    }
+
+    #[async_trait]
+    impl EventPublisher for FlakyPublisher {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        async fn publish(&self, events: &[PublisherEvent]) -> anyhow::Result<()> {
+            let mut attempts = self.attempts.lock_or_clear("attempts lock was poisoned");
+            *attempts += 1;
+            if *attempts % 2 == 1 {
+                anyhow::bail!("destination unavailable");
+            }
+            self.published.lock_or_clear("published lock was poisoned").extend_from_slice(events);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn publisher_retries_and_moves_outbox_cursor() {
+        let storage = Arc::new(StratusStorage::new(Box::<InMemoryTemporaryStorage>::default(), Box::<InMemoryPermanentStorage>::default()).unwrap());
+        for number in 1..=3u64 {
+            let mut block = Block::new(number.into(), UnixTime::from(number));
+            let mut tx = fake_first::<TransactionMined>();
+            tx.logs = vec![fake_first::<LogMined>(); number as usize];
+            block.transactions.push(tx);
+            storage.set_pending_block_number(BlockNumber::from(number).next_block_number()).unwrap();
+            storage.save_block(block).unwrap();
+            storage.set_mined_block_number(number.into()).unwrap();
+        }
+
+        let destination = Arc::new(FlakyPublisher::default());
+        let publisher = Publisher::new(Arc::clone(&storage), Arc::clone(&destination) as Arc<dyn EventPublisher>, None, RETRY_MIN_DELAY);
+
+        let next = publisher.publish_stored(1u64.into(), 3u64.into()).await.unwrap();
+        assert_eq!(next, BlockNumber::from(4u64));
+        assert_eq!(storage.read_publisher_cursor("flaky").unwrap(), Some(BlockNumber::from(3u64)));
+
+        // each block is published once, with its header before its logs
+        let published = destination.published.lock_or_clear("published lock was poisoned");
+        let kinds = published.iter().map(|event| (event.block_number.as_u64(), event.kind)).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                (1, PublisherEventKind::Block),
+                (1, PublisherEventKind::Log),
+                (2, PublisherEventKind::Block),
+                (2, PublisherEventKind::Log),
+                (2, PublisherEventKind::Log),
+                (3, PublisherEventKind::Block),
+                (3, PublisherEventKind::Log),
+                (3, PublisherEventKind::Log),
+                (3, PublisherEventKind::Log),
+            ]
+        );
+        assert_eq!(*destination.attempts.lock_or_clear("attempts lock was poisoned"), 6);
+    }
+}
@@ -3,20 +3,34 @@
 mod rpc_client_app;
 mod rpc_config;
 mod rpc_context;
+mod rpc_filters;
+mod rpc_http_cache;
 mod rpc_http_middleware;
+mod rpc_limits;
+mod rpc_logs;
 mod rpc_method_wrapper;
 mod rpc_middleware;
 mod rpc_parser;
+mod rpc_pool;
+mod rpc_quantity_format;
 mod rpc_server;
 mod rpc_subscriptions;
+mod rpc_trace;
 
 pub use rpc_client_app::RpcClientApp;
 pub use rpc_config::RpcServerConfig;
 pub use rpc_context::RpcContext;
+pub use rpc_filters::RpcFilters;
+use rpc_http_cache::RpcHttpCache;
+use rpc_http_cache::RpcHttpCacheSlot;
+use rpc_http_middleware::RpcConsistencyToken;
 use rpc_http_middleware::RpcHttpMiddleware;
+pub use rpc_limits::RpcLimits;
 use rpc_middleware::RpcMiddleware;
 use rpc_parser::next_rpc_param;
 use rpc_parser::next_rpc_param_or_default;
 use rpc_parser::parse_rpc_rlp;
+pub use rpc_pool::RpcPool;
+pub use rpc_quantity_format::RpcQuantityFormat;
 pub use rpc_server::serve_rpc;
 pub use rpc_subscriptions::RpcSubscriptions;
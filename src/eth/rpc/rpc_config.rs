@@ -1,8 +1,16 @@
+use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::time::Duration;
 
 use clap::Parser;
 use display_json::DebugAsJson;
 
+use crate::eth::rpc::rpc_http_cache::parse_http_cache_namespaces;
+use crate::eth::rpc::rpc_limits::parse_method_rate_limits;
+use crate::eth::rpc::RpcQuantityFormat;
+use crate::ext::parse_duration;
+use crate::infra::profiling::ProfilingConfig;
+
 #[derive(Parser, DebugAsJson, Clone, serde::Serialize)]
 pub struct RpcServerConfig {
     /// JSON-RPC server binding address.
@@ -16,4 +24,85 @@ pub struct RpcServerConfig {
     /// JSON-RPC server max active subscriptions per client.
     #[arg(long = "max-subscriptions", env = "MAX_SUBSCRIPTIONS", default_value = "15")]
     pub rpc_max_subscriptions: u32,
+
+    /// JSON-RPC server max installed polling filters.
+    #[arg(long = "max-filters", env = "MAX_FILTERS", default_value = "1000")]
+    pub rpc_max_filters: u32,
+
+    /// Polling filters not polled during this time are uninstalled.
+    #[arg(long = "filter-timeout", value_parser=parse_duration, env = "FILTER_TIMEOUT", default_value = "5m")]
+    pub rpc_filter_timeout: Duration,
+
+    /// JSON-RPC server threads dedicated to cheap methods (block number, cached calls), so heavy methods do not delay them.
+    ///
+    /// If zero, cheap methods are served by the same pool as heavy methods.
+    #[arg(long = "rpc-hot-threads", env = "RPC_HOT_THREADS", default_value = "4")]
+    pub rpc_hot_threads: usize,
+
+    /// Formatting of quantities in JSON-RPC responses, for consumers that do not accept every valid encoding.
+    ///
+    /// Accepted values are `native` (responses are not rewritten), `canonical` and `legacy`.
+    #[arg(long = "rpc-quantity-format", env = "RPC_QUANTITY_FORMAT", default_value = "native")]
+    pub rpc_quantity_format: RpcQuantityFormat,
+
+    /// Requests per second allowed for each client IP, identified by the `X-Forwarded-For` or `X-Real-IP` headers.
+    ///
+    /// Requests above the limit are rejected with `429 Too Many Requests`. If zero, clients are not limited.
+    #[arg(long = "rpc-rate-limit-per-ip", env = "RPC_RATE_LIMIT_PER_IP", default_value = "0")]
+    pub rpc_rate_limit_per_ip: u32,
+
+    /// Requests per second allowed for each method across all clients, in the format `method=requests_per_second,...`.
+    ///
+    /// Requests above the limit are rejected with the JSON-RPC error `-32005`. Methods not listed are not limited.
+    #[arg(long = "rpc-method-rate-limits", value_parser=parse_method_rate_limits, env = "RPC_METHOD_RATE_LIMITS", default_value = "")]
+    pub rpc_method_rate_limits: HashMap<String, u32>,
+
+    /// Max number of calls and transactions executed concurrently by RPC methods.
+    ///
+    /// Executions above the limit are rejected with the JSON-RPC error `-32005`. If zero, executions are not limited.
+    #[arg(long = "rpc-max-concurrent-executions", env = "RPC_MAX_CONCURRENT_EXECUTIONS", default_value = "0")]
+    pub rpc_max_concurrent_executions: usize,
+
+    /// Max time a follower waits to mine the block of the consistency token sent in the `X-Stratus-Min-Block` header before rejecting
+    /// the request as lagging.
+    ///
+    /// If zero, requests are rejected without waiting when the follower is behind the token.
+    #[arg(long = "rpc-consistency-timeout", value_parser=parse_duration, env = "RPC_CONSISTENCY_TIMEOUT", default_value = "2s")]
+    pub rpc_consistency_timeout: Duration,
+
+    /// Max number of blocks scanned by a log query, or by each page of a paginated log query.
+    #[arg(long = "rpc-logs-max-block-range", env = "RPC_LOGS_MAX_BLOCK_RANGE", default_value = "5000")]
+    pub rpc_logs_max_block_range: u64,
+
+    /// Max number of logs returned by a log query, or by each page of a paginated log query.
+    ///
+    /// Queries matching more logs are rejected and must be narrowed or paginated. If zero, results are not limited.
+    #[arg(long = "rpc-logs-max-results", env = "RPC_LOGS_MAX_RESULTS", default_value = "10000")]
+    pub rpc_logs_max_results: usize,
+
+    /// Max size in bytes of responses of tracing methods. Larger traces are split in pages continued with a cursor.
+    #[arg(long = "rpc-trace-byte-budget", env = "RPC_TRACE_BYTE_BUDGET", default_value = "16777216")]
+    pub rpc_trace_byte_budget: usize,
+
+    /// Max-age of immutable HTTP responses by method namespace, in the format `namespace=duration,...`, like `eth=1d,debug=1h`.
+    ///
+    /// Responses of mined blocks and transactions requested by hash or explicit block number are sent with `Cache-Control` and `ETag`
+    /// headers, so CDNs and clients can cache them. Other responses are sent with `Cache-Control: no-store`. Namespaces not listed are not
+    /// cached. Must not be enabled in nodes where blocks can be reset, like development nodes.
+    #[arg(long = "rpc-http-cache", value_parser=parse_http_cache_namespaces, env = "RPC_HTTP_CACHE", default_value = "")]
+    pub rpc_http_cache: HashMap<String, Duration>,
+
+    #[clap(flatten)]
+    pub profiling: ProfilingConfig,
+}
+
+impl RpcServerConfig {
+    /// Max number of logs returned by a log query, which is unlimited when configured as zero.
+    pub fn logs_max_results(&self) -> usize {
+        if self.rpc_logs_max_results == 0 {
+            usize::MAX
+        } else {
+            self.rpc_logs_max_results
+        }
+    }
 }
@@ -7,9 +7,14 @@ use crate::eth::executor::Executor;
 use crate::eth::follower::consensus::Consensus;
 use crate::eth::miner::Miner;
 use crate::eth::primitives::ChainId;
+use crate::eth::primitives::StratusError;
+use crate::eth::rpc::rpc_filters::FilterManager;
 use crate::eth::rpc::rpc_subscriptions::RpcSubscriptionsConnected;
+use crate::eth::rpc::RpcLimits;
+use crate::eth::rpc::RpcPool;
 use crate::eth::rpc::RpcServerConfig;
 use crate::eth::storage::StratusStorage;
+use crate::infra::NodeIdentity;
 
 pub struct RpcContext {
     // app config
@@ -17,26 +22,40 @@ pub struct RpcContext {
 
     // blockchain config
     pub chain_id: ChainId,
-    pub client_version: &'static str,
+    pub node_identity: NodeIdentity,
 
     // gas config
     pub gas_price: usize,
 
-    // services
-    pub executor: Arc<Executor>,
-    pub miner: Arc<Miner>,
+    // services (archive nodes do not mine transactions, so they have no miner)
+    pub executor: Arc<dyn Executor>,
+    pub miner: Option<Arc<Miner>>,
     pub storage: Arc<StratusStorage>,
     pub consensus: RwLock<Option<Arc<dyn Consensus>>>,
     pub rpc_server: RpcServerConfig,
     pub subs: Arc<RpcSubscriptionsConnected>,
+    pub filters: Arc<FilterManager>,
+
+    // limits
+    pub limits: Arc<RpcLimits>,
+
+    // pools
+    pub hot_pool: RpcPool,
 }
 
 impl Debug for RpcContext {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RpcContext")
             .field("chain_id", &self.chain_id)
-            .field("client_version", &self.client_version)
+            .field("client_version", &self.node_identity.client_version())
             .field("gas_price", &self.gas_price)
             .finish_non_exhaustive()
     }
 }
+
+impl RpcContext {
+    /// Retrieves the miner, failing in archive nodes because they do not have one.
+    pub fn miner(&self) -> Result<&Arc<Miner>, StratusError> {
+        self.miner.as_ref().ok_or(StratusError::StratusArchiveReadOnly)
+    }
+}
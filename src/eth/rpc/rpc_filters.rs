@@ -0,0 +1,406 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+
+use ethereum_types::U256;
+use futures::join;
+use itertools::Itertools;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+use tokio::time::Duration;
+use tokio::time::Instant;
+
+use crate::alias::JsonValue;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::LogFilter;
+use crate::eth::primitives::LogMined;
+use crate::eth::primitives::StratusError;
+use crate::eth::rpc::RpcClientApp;
+use crate::eth::storage::ChainHeadEvent;
+use crate::ext::not;
+use crate::ext::spawn_named;
+use crate::ext::to_json_value;
+use crate::ext::traced_sleep;
+use crate::ext::MutexExt;
+use crate::ext::SleepReason;
+#[cfg(feature = "metrics")]
+use crate::infra::metrics;
+use crate::infra::tracing::warn_task_rx_closed;
+use crate::GlobalState;
+
+/// Frequency of uninstalling filters that are not polled anymore.
+const CLEANING_FREQUENCY: Duration = Duration::from_secs(10);
+
+/// Max wait since last checked shutdown in notifier.
+const NOTIFIER_SHUTDOWN_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Max number of changes buffered by a filter between polls. When reached, the oldest changes are discarded.
+const FILTER_MAX_CHANGES: usize = 10_000;
+
+mod label {
+    pub(super) const LOGS: &str = "logs";
+    pub(super) const NEW_BLOCKS: &str = "newBlocks";
+    pub(super) const NEW_PENDING_TXS: &str = "newPendingTransactions";
+}
+
+/// State of JSON-RPC polling filters.
+#[derive(Debug)]
+pub struct RpcFilters {
+    pub installed: Arc<FilterManager>,
+    pub handles: RpcFiltersHandles,
+}
+
+impl RpcFilters {
+    /// Creates a new filter manager that automatically spawns all necessary tasks in background.
+    pub fn spawn(
+        max_filters: u32,
+        filter_timeout: Duration,
+        rx_pending_txs: broadcast::Receiver<Hash>,
+        rx_chain_head: broadcast::Receiver<ChainHeadEvent>,
+        rx_logs: broadcast::Receiver<LogMined>,
+    ) -> Self {
+        let installed = Arc::new(FilterManager::new(max_filters));
+
+        let handles = RpcFiltersHandles {
+            cleaner: Self::spawn_filters_cleaner(Arc::clone(&installed), filter_timeout),
+            new_pending_txs: Self::spawn_new_pending_txs_buffer(Arc::clone(&installed), rx_pending_txs),
+            new_blocks: Self::spawn_new_blocks_buffer(Arc::clone(&installed), rx_chain_head),
+            logs: Self::spawn_logs_buffer(Arc::clone(&installed), rx_logs),
+        };
+
+        Self { installed, handles }
+    }
+
+    /// Spawns a new task to uninstall filters that were not polled for a while.
+    fn spawn_filters_cleaner(filters: Arc<FilterManager>, filter_timeout: Duration) -> JoinHandle<anyhow::Result<()>> {
+        const TASK_NAME: &str = "rpc::filter::cleaner";
+        spawn_named(TASK_NAME, async move {
+            loop {
+                if GlobalState::is_shutdown_warn(TASK_NAME) {
+                    return Ok(());
+                }
+
+                let uninstalled = filters.uninstall_expired(filter_timeout);
+                if not(uninstalled.is_empty()) {
+                    tracing::info!(amount = uninstalled.len(), filters = ?uninstalled, "uninstalled expired filters");
+                }
+
+                traced_sleep(CLEANING_FREQUENCY, SleepReason::Interval).await;
+            }
+        })
+    }
+
+    /// Spawns a new task that buffers new executed transactions for pending transaction filters.
+    fn spawn_new_pending_txs_buffer(filters: Arc<FilterManager>, mut rx_tx_hash: broadcast::Receiver<Hash>) -> JoinHandle<anyhow::Result<()>> {
+        const TASK_NAME: &str = "rpc::filter::newPendingTransactions";
+        spawn_named(TASK_NAME, async move {
+            loop {
+                if GlobalState::is_shutdown_warn(TASK_NAME) {
+                    return Ok(());
+                }
+
+                let tx_hash = match timeout(NOTIFIER_SHUTDOWN_CHECK_INTERVAL, rx_tx_hash.recv()).await {
+                    Ok(Ok(tx)) => tx,
+                    Ok(Err(_channel_closed)) => break,
+                    Err(_timed_out) => continue,
+                };
+
+                for filter in filters.lock().values_mut() {
+                    if let FilterKind::NewPendingTransactions = filter.kind {
+                        push_change(&mut filter.hashes, tx_hash);
+                    }
+                }
+            }
+            warn_task_rx_closed(TASK_NAME);
+            Ok(())
+        })
+    }
+
+    /// Spawns a new task that follows the chain head, buffering new mined blocks for block filters and discarding buffered changes of blocks
+    /// removed by rollbacks.
+    fn spawn_new_blocks_buffer(filters: Arc<FilterManager>, mut rx_chain_head: broadcast::Receiver<ChainHeadEvent>) -> JoinHandle<anyhow::Result<()>> {
+        const TASK_NAME: &str = "rpc::filter::newBlocks";
+        spawn_named(TASK_NAME, async move {
+            loop {
+                if GlobalState::is_shutdown_warn(TASK_NAME) {
+                    return Ok(());
+                }
+
+                let event = match timeout(NOTIFIER_SHUTDOWN_CHECK_INTERVAL, rx_chain_head.recv()).await {
+                    Ok(Ok(event)) => event,
+                    Ok(Err(_channel_closed)) => break,
+                    Err(_timed_out) => continue,
+                };
+
+                match event {
+                    ChainHeadEvent::Committed { number, hash } =>
+                        for filter in filters.lock().values_mut() {
+                            if let FilterKind::NewBlocks = filter.kind {
+                                push_change(&mut filter.blocks, (number, hash));
+                            }
+                        },
+                    ChainHeadEvent::RolledBack { number, .. } => filters.discard_after(number),
+                }
+            }
+            warn_task_rx_closed(TASK_NAME);
+            Ok(())
+        })
+    }
+
+    /// Spawns a new task that buffers new transactions logs for log filters.
+    fn spawn_logs_buffer(filters: Arc<FilterManager>, mut rx_log_mined: broadcast::Receiver<LogMined>) -> JoinHandle<anyhow::Result<()>> {
+        const TASK_NAME: &str = "rpc::filter::logs";
+        spawn_named(TASK_NAME, async move {
+            loop {
+                if GlobalState::is_shutdown_warn(TASK_NAME) {
+                    return Ok(());
+                }
+
+                let log = match timeout(NOTIFIER_SHUTDOWN_CHECK_INTERVAL, rx_log_mined.recv()).await {
+                    Ok(Ok(log)) => log,
+                    Ok(Err(_channel_closed)) => break,
+                    Err(_timed_out) => continue,
+                };
+
+                for filter in filters.lock().values_mut() {
+                    if let FilterKind::Logs(ref log_filter) = filter.kind {
+                        if log_filter.matches(&log) {
+                            push_change(&mut filter.logs, log.clone());
+                        }
+                    }
+                }
+            }
+            warn_task_rx_closed(TASK_NAME);
+            Ok(())
+        })
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Buffer handles
+// -----------------------------------------------------------------------------
+
+/// Handles of filter background tasks.
+#[derive(Debug)]
+pub struct RpcFiltersHandles {
+    cleaner: JoinHandle<anyhow::Result<()>>,
+    new_pending_txs: JoinHandle<anyhow::Result<()>>,
+    new_blocks: JoinHandle<anyhow::Result<()>>,
+    logs: JoinHandle<anyhow::Result<()>>,
+}
+
+impl RpcFiltersHandles {
+    pub fn stopped(self) {
+        let _ = join!(self.cleaner, self.new_pending_txs, self.new_blocks, self.logs);
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Installed filters
+// -----------------------------------------------------------------------------
+
+/// Identifier of an installed filter, serialized as a hex quantity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct FilterId(U256);
+
+impl FilterId {
+    /// Generates a new random filter id, so clients cannot guess filters installed by others.
+    fn new_random() -> Self {
+        Self(U256::from(rand::random::<u128>()))
+    }
+}
+
+impl fmt::Display for FilterId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+/// Kind of changes a filter is interested in.
+#[derive(Debug, Clone)]
+pub enum FilterKind {
+    /// Logs matching a filter, installed by `eth_newFilter`.
+    Logs(LogFilter),
+
+    /// Hashes of new mined blocks, installed by `eth_newBlockFilter`.
+    NewBlocks,
+
+    /// Hashes of new executed transactions, installed by `eth_newPendingTransactionFilter`.
+    NewPendingTransactions,
+}
+
+impl FilterKind {
+    /// Name of the filter kind used in logs and metrics.
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Logs(_) => label::LOGS,
+            Self::NewBlocks => label::NEW_BLOCKS,
+            Self::NewPendingTransactions => label::NEW_PENDING_TXS,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct InstalledFilter {
+    kind: FilterKind,
+    client: RpcClientApp,
+    last_polled_at: Instant,
+
+    /// Logs buffered since last poll.
+    logs: VecDeque<LogMined>,
+
+    /// Blocks buffered since last poll, with their numbers so blocks removed by a rollback can be discarded.
+    blocks: VecDeque<(BlockNumber, Hash)>,
+
+    /// Transactions hashes buffered since last poll.
+    hashes: VecDeque<Hash>,
+}
+
+/// Polling filters installed by clients that cannot use subscriptions.
+#[derive(Debug)]
+pub struct FilterManager {
+    max_filters: u32,
+    installed: Mutex<HashMap<FilterId, InstalledFilter>>,
+}
+
+impl FilterManager {
+    fn new(max_filters: u32) -> Self {
+        Self {
+            max_filters,
+            installed: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<FilterId, InstalledFilter>> {
+        self.installed.lock_or_clear("filters lock was poisoned")
+    }
+
+    /// Installs a new filter and returns its id.
+    pub fn install(&self, client: RpcClientApp, kind: FilterKind) -> Result<FilterId, StratusError> {
+        let mut installed = self.lock();
+        if installed.len() >= self.max_filters as usize {
+            return Err(StratusError::RpcFilterLimit { max: self.max_filters });
+        }
+
+        let id = FilterId::new_random();
+        tracing::info!(%id, %client, kind = kind.label(), "installing filter");
+        installed.insert(
+            id,
+            InstalledFilter {
+                kind,
+                client,
+                last_polled_at: Instant::now(),
+                logs: VecDeque::new(),
+                blocks: VecDeque::new(),
+                hashes: VecDeque::new(),
+            },
+        );
+
+        #[cfg(feature = "metrics")]
+        Self::set_filters_metric(&installed);
+
+        Ok(id)
+    }
+
+    /// Uninstalls a filter.
+    ///
+    /// Returns `true` if the filter was installed.
+    pub fn uninstall(&self, id: FilterId) -> bool {
+        let mut installed = self.lock();
+        let uninstalled = installed.remove(&id).is_some();
+        if uninstalled {
+            tracing::info!(%id, "uninstalled filter");
+        }
+
+        #[cfg(feature = "metrics")]
+        Self::set_filters_metric(&installed);
+
+        uninstalled
+    }
+
+    /// Retrieves all changes since the last poll in JSON-RPC format, clearing the buffered changes.
+    pub fn changes(&self, id: FilterId) -> Result<JsonValue, StratusError> {
+        let mut installed = self.lock();
+        let Some(filter) = installed.get_mut(&id) else {
+            return Err(StratusError::RpcFilterNotFound { id: id.to_string() });
+        };
+        filter.last_polled_at = Instant::now();
+
+        let changes = match filter.kind {
+            FilterKind::Logs(_) => JsonValue::Array(filter.logs.drain(..).map(LogMined::to_json_rpc_log).collect_vec()),
+            FilterKind::NewBlocks => to_json_value(filter.blocks.drain(..).map(|(_, hash)| hash).collect_vec()),
+            FilterKind::NewPendingTransactions => to_json_value(filter.hashes.drain(..).collect_vec()),
+        };
+        Ok(changes)
+    }
+
+    /// Retrieves the log filter of an installed filter, so all matching logs can be read from storage.
+    pub fn log_filter(&self, id: FilterId) -> Result<LogFilter, StratusError> {
+        let mut installed = self.lock();
+        match installed.get_mut(&id) {
+            Some(filter) => {
+                filter.last_polled_at = Instant::now();
+                match filter.kind {
+                    FilterKind::Logs(ref log_filter) => Ok(log_filter.clone()),
+                    _ => Err(StratusError::RpcFilterNotFound { id: id.to_string() }),
+                }
+            }
+            None => Err(StratusError::RpcFilterNotFound { id: id.to_string() }),
+        }
+    }
+
+    /// Discards buffered blocks and logs after the specified block, because they were removed by a rollback.
+    fn discard_after(&self, number: BlockNumber) {
+        tracing::info!(%number, "discarding filter changes of rolled back blocks");
+        for filter in self.lock().values_mut() {
+            filter.blocks.retain(|(block_number, _)| *block_number <= number);
+            filter.logs.retain(|log| log.block_number <= number);
+        }
+    }
+
+    /// Uninstalls filters that were not polled during the timeout.
+    ///
+    /// Returns the uninstalled filters and the clients that installed them.
+    fn uninstall_expired(&self, filter_timeout: Duration) -> Vec<(FilterId, RpcClientApp)> {
+        let mut installed = self.lock();
+        let mut uninstalled = Vec::new();
+        installed.retain(|id, filter| {
+            let should_keep = filter.last_polled_at.elapsed() < filter_timeout;
+            if !should_keep {
+                uninstalled.push((*id, filter.client.clone()));
+            }
+            should_keep
+        });
+
+        #[cfg(feature = "metrics")]
+        Self::set_filters_metric(&installed);
+
+        uninstalled
+    }
+
+    #[cfg(feature = "metrics")]
+    fn set_filters_metric(installed: &HashMap<FilterId, InstalledFilter>) {
+        let counts = installed.values().counts_by(|filter| filter.kind.label());
+        for label in [label::LOGS, label::NEW_BLOCKS, label::NEW_PENDING_TXS] {
+            metrics::set_rpc_filters_active(counts.get(label).copied().unwrap_or_default() as u64, label);
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+// -----------------------------------------------------------------------------
+
+/// Buffers a change in a filter, discarding the oldest change if the buffer is full.
+fn push_change<T>(buffer: &mut VecDeque<T>, change: T) {
+    if buffer.len() >= FILTER_MAX_CHANGES {
+        buffer.pop_front();
+    }
+    buffer.push_back(change);
+}
@@ -0,0 +1,209 @@
+//! HTTP caching headers of immutable JSON-RPC responses.
+//!
+//! Mined blocks and transactions never change, so responses that reference them by hash or by explicit block number can be cached by
+//! CDNs and clients. The HTTP middleware shares an [`RpcHttpCacheSlot`] with the RPC middleware through the request extensions; each call
+//! of the HTTP request records in the slot if its response is immutable, and the HTTP middleware emits `Cache-Control` and `ETag`
+//! headers only when all calls are.
+//!
+//! Caching is configured by namespace (the method prefix before `_`), with a max-age for each one.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use ethers_core::utils::keccak256;
+use jsonrpsee::types::Params;
+
+use crate::alias::JsonValue;
+use crate::ext::not;
+use crate::ext::parse_duration;
+use crate::ext::MutexExt;
+
+/// Methods whose responses are immutable when the result is mined, because the first parameter is a hash.
+const METHODS_BY_HASH: [&str; 5] = [
+    "eth_getBlockByHash",
+    "eth_getTransactionByHash",
+    "eth_getTransactionReceipt",
+    "debug_traceBlockByHash",
+    "stratus_getCodeByHash",
+];
+
+/// Methods whose responses are immutable when the first parameter is an explicit block number and the block is mined.
+const METHODS_BY_NUMBER: [&str; 5] = [
+    "eth_getBlockByNumber",
+    "eth_getBlockReceipts",
+    "debug_traceBlockByNumber",
+    "trace_block",
+    "stratus_getBlockStateDiff",
+];
+
+/// Parses per-namespace max-ages in the format `namespace=duration,...`.
+pub fn parse_http_cache_namespaces(s: &str) -> anyhow::Result<HashMap<String, Duration>> {
+    let mut namespaces = HashMap::new();
+    for entry in s.split(',').map(str::trim).filter(|entry| not(entry.is_empty())) {
+        let Some((namespace, max_age)) = entry.split_once('=') else {
+            return Err(anyhow!("invalid http cache namespace: {}", entry));
+        };
+        let max_age = parse_duration(max_age.trim()).map_err(|_| anyhow!("invalid http cache namespace: {}", entry))?;
+        namespaces.insert(namespace.trim().to_owned(), max_age);
+    }
+    Ok(namespaces)
+}
+
+/// Decides which JSON-RPC responses can be cached by HTTP caches.
+#[derive(Debug)]
+pub struct RpcHttpCache {
+    namespaces: HashMap<String, Duration>,
+}
+
+impl RpcHttpCache {
+    pub fn new(namespaces: HashMap<String, Duration>) -> Self {
+        Self {
+            namespaces: namespaces.into_iter().filter(|(_, max_age)| not(max_age.is_zero())).collect(),
+        }
+    }
+
+    /// Checks if caching headers are enabled for any namespace.
+    pub fn is_enabled(&self) -> bool {
+        not(self.namespaces.is_empty())
+    }
+
+    /// Max-age of the response of a call, if its response will be immutable once its result is mined.
+    pub fn max_age(&self, method: &str, params: &Params<'_>) -> Option<Duration> {
+        let namespace = method.split('_').next()?;
+        let max_age = self.namespaces.get(namespace)?;
+
+        if METHODS_BY_HASH.contains(&method) {
+            return Some(*max_age);
+        }
+        if METHODS_BY_NUMBER.contains(&method) && is_explicit_block_number(params) {
+            return Some(*max_age);
+        }
+        None
+    }
+}
+
+/// Checks if the first parameter is a block number instead of a tag like `latest`.
+fn is_explicit_block_number(params: &Params<'_>) -> bool {
+    match params.sequence().next::<JsonValue>() {
+        Ok(JsonValue::String(number)) => number.starts_with("0x"),
+        Ok(JsonValue::Number(_)) => true,
+        _ => false,
+    }
+}
+
+/// Checks if a result refers to mined data, as pending blocks and transactions have no block hash.
+pub fn is_mined_result(result: &JsonValue) -> bool {
+    match result {
+        JsonValue::Null => false,
+        JsonValue::Object(object) => ["hash", "blockHash"]
+            .iter()
+            .all(|field| object.get(*field).map(|value| not(value.is_null())).unwrap_or(true)),
+        _ => true,
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Slot
+// -----------------------------------------------------------------------------
+
+/// Caching decision of an HTTP request, shared by the HTTP middleware and the RPC middleware.
+#[derive(Debug, Clone, Default)]
+pub struct RpcHttpCacheSlot(Arc<Mutex<RpcHttpCacheDecision>>);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RpcHttpCacheDecision {
+    /// No call recorded yet.
+    #[default]
+    Empty,
+
+    /// All calls are immutable. The ETag is the combined hash of their responses.
+    Cacheable { max_age: Duration, etag: [u8; 32] },
+
+    /// At least one call is not immutable.
+    Uncacheable,
+}
+
+impl RpcHttpCacheSlot {
+    /// Records the response of a call, with the max-age if it is immutable.
+    pub fn record(&self, max_age: Option<Duration>, response: &str) {
+        let mut decision = self.0.lock_or_clear("rpc http cache slot lock was poisoned");
+        *decision = match (*decision, max_age) {
+            (RpcHttpCacheDecision::Uncacheable, _) | (_, None) => RpcHttpCacheDecision::Uncacheable,
+            (RpcHttpCacheDecision::Empty, Some(max_age)) => RpcHttpCacheDecision::Cacheable {
+                max_age,
+                etag: keccak256(response),
+            },
+            // calls of a batch may finish in any order, so their hashes are combined in an order-independent way
+            (RpcHttpCacheDecision::Cacheable { max_age: current, etag }, Some(max_age)) => {
+                let mut combined = keccak256(response);
+                for (byte, other) in combined.iter_mut().zip(etag) {
+                    *byte ^= other;
+                }
+                RpcHttpCacheDecision::Cacheable {
+                    max_age: current.min(max_age),
+                    etag: combined,
+                }
+            }
+        };
+    }
+
+    pub fn decision(&self) -> RpcHttpCacheDecision {
+        *self.0.lock_or_clear("rpc http cache slot lock was poisoned")
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn http_cache_accepts_only_immutable_calls() {
+        let namespaces = parse_http_cache_namespaces("eth=1d, debug=1h,").unwrap();
+        assert!(parse_http_cache_namespaces("eth").is_err());
+        let cache = RpcHttpCache::new(namespaces);
+
+        let day = Some(Duration::from_secs(24 * 60 * 60));
+        assert_eq!(cache.max_age("eth_getBlockByNumber", &Params::new(Some(r#"["0x1", false]"#))), day);
+        assert_eq!(cache.max_age("eth_getBlockByNumber", &Params::new(Some(r#"["latest", false]"#))), None);
+        assert_eq!(cache.max_age("eth_getTransactionReceipt", &Params::new(Some(r#"["0x01"]"#))), day);
+        assert_eq!(cache.max_age("eth_blockNumber", &Params::new(None)), None);
+        assert_eq!(cache.max_age("stratus_getCodeByHash", &Params::new(Some(r#"["0x01"]"#))), None);
+
+        assert!(is_mined_result(&json!({"hash": "0x01", "number": "0x1"})));
+        assert!(!is_mined_result(&json!({"hash": null, "number": "0x1"})));
+        assert!(!is_mined_result(&json!({"blockHash": null})));
+        assert!(!is_mined_result(&JsonValue::Null));
+    }
+
+    #[test]
+    fn http_cache_slot_requires_all_calls_cacheable() {
+        let slot = RpcHttpCacheSlot::default();
+        assert_eq!(slot.decision(), RpcHttpCacheDecision::Empty);
+
+        slot.record(Some(Duration::from_secs(60)), "a");
+        slot.record(Some(Duration::from_secs(30)), "b");
+        let RpcHttpCacheDecision::Cacheable { max_age, etag } = slot.decision() else {
+            panic!("slot should be cacheable");
+        };
+        assert_eq!(max_age, Duration::from_secs(30));
+
+        // batch order does not change the etag
+        let reversed = RpcHttpCacheSlot::default();
+        reversed.record(Some(Duration::from_secs(30)), "b");
+        reversed.record(Some(Duration::from_secs(60)), "a");
+        assert_eq!(reversed.decision(), RpcHttpCacheDecision::Cacheable { max_age, etag });
+
+        slot.record(None, "c");
+        slot.record(Some(Duration::from_secs(60)), "d");
+        assert_eq!(slot.decision(), RpcHttpCacheDecision::Uncacheable);
+    }
+}
@@ -1,23 +1,55 @@
 use std::collections::HashMap;
+use std::future;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
 
 use futures::TryFutureExt;
+use http::Method;
 use jsonrpsee::client_transport::ws::Uri;
 use jsonrpsee::core::BoxError;
 use jsonrpsee::server::HttpBody;
 use jsonrpsee::server::HttpRequest;
 use jsonrpsee::server::HttpResponse;
+use jsonrpsee::types::ErrorObjectOwned;
 use reqwest::header::HeaderMap;
 use reqwest::header::HeaderValue;
+use reqwest::header::CACHE_CONTROL;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::header::ETAG;
+use reqwest::header::IF_NONE_MATCH;
+use reqwest::StatusCode;
+use serde_json::json;
 use tower::Service;
 
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::StratusError;
+use crate::eth::rpc::rpc_http_cache::RpcHttpCacheDecision;
 use crate::eth::rpc::RpcClientApp;
+use crate::eth::rpc::RpcHttpCache;
+use crate::eth::rpc::RpcHttpCacheSlot;
+use crate::eth::rpc::RpcLimits;
 use crate::ext::not;
+use crate::ext::spawn_blocking_named;
+use crate::infra::profiling::Profiler;
+
+/// Path of the endpoint that collects execution profiles.
+const PROFILE_PATH: &str = "/debug/profile";
+
+/// Duration of execution profiles when not specified in the `seconds` query parameter.
+const PROFILE_DEFAULT_DURATION: Duration = Duration::from_secs(10);
+
+/// Last block number seen by the client, which a follower must have mined before serving the request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RpcConsistencyToken(pub BlockNumber);
 
 #[derive(Debug, Clone, derive_new::new)]
 pub struct RpcHttpMiddleware<S> {
     service: S,
+    limits: Arc<RpcLimits>,
+    cache: Arc<RpcHttpCache>,
+    profiler: Arc<Profiler>,
 }
 
 impl<S> Service<HttpRequest<HttpBody>> for RpcHttpMiddleware<S>
@@ -35,13 +67,139 @@ where
     }
 
     fn call(&mut self, mut request: HttpRequest<HttpBody>) -> Self::Future {
+        // reject clients above the rate limit before parsing the request
+        if let Some(ip) = parse_client_ip(request.headers()) {
+            if let Err(e) = self.limits.check_ip(&ip) {
+                tracing::warn!(%ip, reason = %e, "rejecting http request above rate limit");
+                return Box::pin(future::ready(Ok(too_many_requests(e))));
+            }
+        }
+
+        // profiles are served outside the json-rpc service because they are not json
+        if self.profiler.is_enabled() && request.method() == Method::GET && request.uri().path() == PROFILE_PATH {
+            return Box::pin(collect_profile(Arc::clone(&self.profiler), request.uri().clone()));
+        }
+
         let client_app = parse_client_app(request.headers(), request.uri());
         request.extensions_mut().insert(client_app);
 
+        if let Some(token) = parse_consistency_token(request.headers()) {
+            request.extensions_mut().insert(token);
+        }
+
+        // share the caching decision with the rpc middleware, which records it for each call
+        if self.cache.is_enabled() && request.method() == Method::POST {
+            let slot = RpcHttpCacheSlot::default();
+            request.extensions_mut().insert(slot.clone());
+            let if_none_match = request.headers().get(IF_NONE_MATCH).cloned();
+            let response = self.service.call(request).map_err(Into::into);
+            return Box::pin(response.map_ok(move |response| with_cache_headers(response, slot.decision(), if_none_match)));
+        }
+
         Box::pin(self.service.call(request).map_err(Into::into))
     }
 }
 
+/// Adds caching headers to a response according to the decision of all its calls.
+///
+/// Responses with an ETag matching the `If-None-Match` header are replaced by `304 Not Modified`.
+fn with_cache_headers(mut response: HttpResponse, decision: RpcHttpCacheDecision, if_none_match: Option<HeaderValue>) -> HttpResponse {
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+
+    let RpcHttpCacheDecision::Cacheable { max_age, etag } = decision else {
+        response.headers_mut().insert(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        return response;
+    };
+
+    let etag = format!("\"{}\"", const_hex::encode(etag));
+    let not_modified = if_none_match
+        .as_ref()
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').map(str::trim).any(|tag| tag == etag || tag == "*"));
+    if not_modified {
+        response = HttpResponse::new(HttpBody::from(String::new()));
+        *response.status_mut() = StatusCode::NOT_MODIFIED;
+    }
+
+    let cache_control = format!("public, max-age={}, immutable", max_age.as_secs());
+    let headers = response.headers_mut();
+    headers.insert(CACHE_CONTROL, HeaderValue::from_str(&cache_control).expect("cache control is ascii"));
+    headers.insert(ETAG, HeaderValue::from_str(&etag).expect("etag is ascii"));
+    response
+}
+
+/// Collects an execution profile during the seconds specified in the `seconds` query parameter and returns it as folded stacks.
+async fn collect_profile(profiler: Arc<Profiler>, uri: Uri) -> Result<HttpResponse, BoxError> {
+    let duration = uri
+        .query()
+        .and_then(|query| serde_urlencoded::from_str::<HashMap<String, String>>(query).ok())
+        .and_then(|params| params.get("seconds").and_then(|seconds| seconds.parse::<u64>().ok()))
+        .map(Duration::from_secs)
+        .unwrap_or(PROFILE_DEFAULT_DURATION);
+
+    let profile = spawn_blocking_named("rpc::profile", move || profiler.collect(duration)).await?;
+    let mut response = match profile {
+        Some(profile) => HttpResponse::new(HttpBody::from(profile.to_folded())),
+        None => {
+            let mut response = HttpResponse::new(HttpBody::from("another profile is being collected\n".to_owned()));
+            *response.status_mut() = StatusCode::CONFLICT;
+            response
+        }
+    };
+    let headers = response.headers_mut();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+    headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    Ok(response)
+}
+
+/// Extracts the client IP from the headers set by the load balancer in front of the server.
+fn parse_client_ip(headers: &HeaderMap<HeaderValue>) -> Option<String> {
+    if let Some(forwarded_for) = headers.get("x-forwarded-for").and_then(|value| value.to_str().ok()) {
+        // first address is the original client, the others are proxies
+        if let Some(ip) = forwarded_for.split(',').map(str::trim).find(|ip| not(ip.is_empty())) {
+            return Some(ip.to_owned());
+        }
+    }
+    headers
+        .get("x-real-ip")
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|ip| not(ip.is_empty()))
+        .map(str::to_owned)
+}
+
+/// Extracts the consistency token from the `x-stratus-min-block` header, accepting decimal or hexadecimal block numbers.
+fn parse_consistency_token(headers: &HeaderMap<HeaderValue>) -> Option<RpcConsistencyToken> {
+    let value = headers.get("x-stratus-min-block")?;
+    let Ok(value) = value.to_str() else {
+        tracing::warn!(value = ?value, "failed to parse consistency token header as ascii string");
+        return None;
+    };
+    let value = value.trim();
+    let parsed = match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse::<u64>(),
+    };
+    match parsed {
+        Ok(number) => Some(RpcConsistencyToken(BlockNumber::from(number))),
+        Err(e) => {
+            tracing::warn!(reason = ?e, %value, "failed to parse consistency token header");
+            None
+        }
+    }
+}
+
+/// Generates a `429 Too Many Requests` response with the JSON-RPC error in the body.
+fn too_many_requests(error: StratusError) -> HttpResponse {
+    let body = json!({"jsonrpc": "2.0", "error": ErrorObjectOwned::from(error), "id": null});
+    let mut response = HttpResponse::new(HttpBody::from(body.to_string()));
+    *response.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+    response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
 /// Extracts the client application name from the `app` query parameter.
 fn parse_client_app(headers: &HeaderMap<HeaderValue>, uri: &Uri) -> RpcClientApp {
     fn try_query_params(uri: &Uri) -> Option<RpcClientApp> {
@@ -0,0 +1,202 @@
+//! Rate limiting and concurrency caps of JSON-RPC requests.
+//!
+//! Requests are limited by token buckets that refill continuously at the configured rate and allow bursts of up to one second worth of
+//! requests. Clients are limited by the HTTP middleware, which rejects requests with `429 Too Many Requests`, and methods are limited by
+//! the RPC middleware, which rejects requests with the JSON-RPC error `-32005`. EVM executions are capped by a semaphore acquired before
+//! calls and transactions are executed.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use anyhow::anyhow;
+use tokio::sync::Semaphore;
+use tokio::sync::SemaphorePermit;
+
+use crate::eth::primitives::StratusError;
+use crate::eth::rpc::RpcServerConfig;
+use crate::ext::not;
+use crate::ext::MutexExt;
+
+/// Number of tracked clients above which buckets that are full again are discarded.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Parses per-method rate limits in the format `method=requests_per_second,...`.
+pub fn parse_method_rate_limits(s: &str) -> anyhow::Result<HashMap<String, u32>> {
+    let mut limits = HashMap::new();
+    for entry in s.split(',').map(str::trim).filter(|entry| not(entry.is_empty())) {
+        let Some((method, rate)) = entry.split_once('=') else {
+            return Err(anyhow!("invalid method rate limit: {}", entry));
+        };
+        let rate: u32 = rate.trim().parse().map_err(|_| anyhow!("invalid method rate limit: {}", entry))?;
+        limits.insert(method.trim().to_owned(), rate);
+    }
+    Ok(limits)
+}
+
+// -----------------------------------------------------------------------------
+// Token bucket
+// -----------------------------------------------------------------------------
+
+#[derive(Debug)]
+struct TokenBucket {
+    /// Tokens added per second, which is also the max number of tokens.
+    rate: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u32, now: Instant) -> Self {
+        Self {
+            rate: rate as f64,
+            tokens: rate as f64,
+            refilled_at: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.refilled_at = now;
+    }
+
+    /// Takes a token if available.
+    fn try_take(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+
+    fn is_full(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.tokens >= self.rate
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Limits
+// -----------------------------------------------------------------------------
+
+/// Rate limits and concurrency caps shared by the RPC middlewares and methods.
+#[derive(Debug)]
+pub struct RpcLimits {
+    /// Requests per second allowed for each client IP. Zero disables the limit.
+    rate_per_ip: u32,
+
+    /// Buckets of client IPs.
+    ip_buckets: Mutex<HashMap<String, TokenBucket>>,
+
+    /// Buckets of methods with a configured limit.
+    method_buckets: Mutex<HashMap<String, TokenBucket>>,
+
+    /// Permits of concurrent EVM executions, if limited.
+    executions: Option<Semaphore>,
+    max_executions: usize,
+}
+
+impl RpcLimits {
+    pub fn new(config: &RpcServerConfig) -> Self {
+        let now = Instant::now();
+        let method_buckets = config
+            .rpc_method_rate_limits
+            .iter()
+            .filter(|(_, rate)| **rate > 0)
+            .map(|(method, rate)| (method.clone(), TokenBucket::new(*rate, now)))
+            .collect();
+
+        Self {
+            rate_per_ip: config.rpc_rate_limit_per_ip,
+            ip_buckets: Mutex::new(HashMap::new()),
+            method_buckets: Mutex::new(method_buckets),
+            executions: (config.rpc_max_concurrent_executions > 0).then(|| Semaphore::new(config.rpc_max_concurrent_executions)),
+            max_executions: config.rpc_max_concurrent_executions,
+        }
+    }
+
+    /// Takes a token from the bucket of the client IP.
+    pub fn check_ip(&self, ip: &str) -> Result<(), StratusError> {
+        if self.rate_per_ip == 0 {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        let mut buckets = self.ip_buckets.lock_or_clear("rpc ip buckets lock was poisoned");
+        if buckets.len() >= MAX_TRACKED_CLIENTS {
+            buckets.retain(|_, bucket| not(bucket.is_full(now)));
+        }
+        let bucket = buckets.entry(ip.to_owned()).or_insert_with(|| TokenBucket::new(self.rate_per_ip, now));
+        if bucket.try_take(now) {
+            Ok(())
+        } else {
+            Err(StratusError::RpcRateLimited {
+                limit: format!("client {}", ip),
+                rate: self.rate_per_ip,
+            })
+        }
+    }
+
+    /// Takes a token from the bucket of the method, if the method is limited.
+    pub fn check_method(&self, method: &str) -> Result<(), StratusError> {
+        let now = Instant::now();
+        let mut buckets = self.method_buckets.lock_or_clear("rpc method buckets lock was poisoned");
+        let Some(bucket) = buckets.get_mut(method) else {
+            return Ok(());
+        };
+        if bucket.try_take(now) {
+            Ok(())
+        } else {
+            Err(StratusError::RpcRateLimited {
+                limit: format!("method {}", method),
+                rate: bucket.rate as u32,
+            })
+        }
+    }
+
+    /// Acquires a permit to execute a call or transaction in the EVM, released when the permit is dropped.
+    pub fn acquire_execution(&self) -> Result<Option<SemaphorePermit<'_>>, StratusError> {
+        let Some(ref executions) = self.executions else {
+            return Ok(None);
+        };
+        match executions.try_acquire() {
+            Ok(permit) => Ok(Some(permit)),
+            Err(_) => Err(StratusError::RpcConcurrencyLimit { max: self.max_executions }),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn token_bucket_refills_at_rate() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2, start);
+        assert!(bucket.try_take(start));
+        assert!(bucket.try_take(start));
+        assert!(!bucket.try_take(start));
+
+        let later = start + Duration::from_millis(500);
+        assert!(bucket.try_take(later));
+        assert!(!bucket.try_take(later));
+    }
+
+    #[test]
+    fn method_rate_limits_are_parsed() {
+        let limits = parse_method_rate_limits("eth_call=100, eth_getLogs=5,").unwrap();
+        assert_eq!(limits.get("eth_call"), Some(&100));
+        assert_eq!(limits.get("eth_getLogs"), Some(&5));
+        assert!(parse_method_rate_limits("").unwrap().is_empty());
+        assert!(parse_method_rate_limits("eth_call").is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! Paginated responses of log queries.
+//!
+//! `eth_getLogs` fails when a query matches more logs than the server allows. `stratus_getLogsPaginated` accepts the same filter, but
+//! returns the matching logs in pages: each page scans at most the max block range and returns at most the page limit, and the response
+//! includes a cursor that must be sent in the next request to continue from where the previous page stopped.
+
+use serde_json::json;
+
+use crate::alias::JsonValue;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Index;
+use crate::eth::primitives::LogMined;
+
+/// Pagination options accepted by `stratus_getLogsPaginated`.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct LogsPageOptions {
+    /// Max number of logs in the page. Limited by the server max results.
+    #[serde(default)]
+    pub limit: Option<usize>,
+
+    /// Position to continue from, as returned in a previous page.
+    #[serde(default)]
+    pub cursor: Option<LogsCursor>,
+}
+
+impl LogsPageOptions {
+    /// Max number of logs in the page, limited by the server max results.
+    pub fn limit(&self, server_max_results: usize) -> usize {
+        self.limit.unwrap_or(server_max_results).min(server_max_results).max(1)
+    }
+}
+
+/// Position of the first log of the next page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LogsCursor {
+    #[serde(rename = "blockNumber")]
+    pub block_number: BlockNumber,
+
+    #[serde(rename = "logIndex", default = "default_log_index")]
+    pub log_index: Index,
+}
+
+fn default_log_index() -> Index {
+    Index::ZERO
+}
+
+/// Page of logs matching a filter.
+#[derive(Debug)]
+pub struct LogsPage {
+    pub logs: Vec<LogMined>,
+
+    /// Position where the next page starts, if there are blocks or logs not returned yet.
+    pub cursor: Option<LogsCursor>,
+}
+
+impl LogsPage {
+    /// Selects the logs starting at the cursor that fit in the limit.
+    ///
+    /// `logs` are the logs found from the block of the cursor up to `scanned_to`, and `to_block` is the last block of the filter.
+    pub fn paginate(mut logs: Vec<LogMined>, cursor: Option<LogsCursor>, limit: usize, scanned_to: BlockNumber, to_block: BlockNumber) -> Self {
+        logs.sort_by_key(|log| (log.block_number, log.log_index));
+
+        // logs already sent are skipped only for the block the cursor points to
+        if let Some(cursor) = cursor {
+            logs.retain(|log| log.block_number != cursor.block_number || log.log_index >= cursor.log_index);
+        }
+
+        // more logs than the limit: continue from the first log not sent
+        if logs.len() > limit {
+            let next = &logs[limit];
+            let cursor = LogsCursor {
+                block_number: next.block_number,
+                log_index: next.log_index,
+            };
+            logs.truncate(limit);
+            return Self { logs, cursor: Some(cursor) };
+        }
+
+        // all logs fit: continue from the first block not scanned
+        let cursor = (scanned_to < to_block).then(|| LogsCursor {
+            block_number: scanned_to.next_block_number(),
+            log_index: Index::ZERO,
+        });
+        Self { logs, cursor }
+    }
+
+    /// Serializes the page to the `stratus_getLogsPaginated` response format.
+    pub fn to_json(self) -> JsonValue {
+        json!({
+            "logs": self.logs.into_iter().map(|log| log.to_json_rpc_log()).collect::<Vec<_>>(),
+            "cursor": self.cursor,
+        })
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_utils::fake_first;
+
+    fn logs(positions: &[(u64, u64)]) -> Vec<LogMined> {
+        positions
+            .iter()
+            .map(|(block_number, log_index)| {
+                let mut log = fake_first::<LogMined>();
+                log.block_number = BlockNumber::from(*block_number);
+                log.log_index = Index(*log_index);
+                log
+            })
+            .collect()
+    }
+
+    fn positions(page: &LogsPage) -> Vec<(u64, u64)> {
+        page.logs.iter().map(|log| (log.block_number.as_u64(), log.log_index.0)).collect()
+    }
+
+    #[test]
+    fn logs_page_continues_from_cursor() {
+        let found = logs(&[(1, 0), (1, 1), (2, 0), (2, 3), (3, 1)]);
+
+        // limit reached in the middle of a block
+        let page = LogsPage::paginate(found.clone(), None, 3, BlockNumber::from(3u64), BlockNumber::from(10u64));
+        assert_eq!(positions(&page), vec![(1, 0), (1, 1), (2, 0)]);
+        let cursor = page.cursor.unwrap();
+        assert_eq!(cursor.block_number, BlockNumber::from(2u64));
+        assert_eq!(cursor.log_index, Index(3));
+
+        // next page skips logs already sent and continues after the scanned blocks
+        let found = found.into_iter().filter(|log| log.block_number >= cursor.block_number).collect();
+        let page = LogsPage::paginate(found, Some(cursor), 3, BlockNumber::from(3u64), BlockNumber::from(10u64));
+        assert_eq!(positions(&page), vec![(2, 3), (3, 1)]);
+        assert_eq!(
+            page.cursor,
+            Some(LogsCursor {
+                block_number: BlockNumber::from(4u64),
+                log_index: Index::ZERO
+            })
+        );
+
+        // last page has no cursor
+        let page = LogsPage::paginate(vec![], page.cursor, 3, BlockNumber::from(10u64), BlockNumber::from(10u64));
+        assert!(page.logs.is_empty());
+        assert!(page.cursor.is_none());
+    }
+}
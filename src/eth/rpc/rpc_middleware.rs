@@ -1,7 +1,9 @@
 //! Track RPC requests and responses using metrics and traces.
 
 use std::future::Future;
+use std::sync::Arc;
 use std::task::Poll;
+use std::time::Duration;
 use std::time::Instant;
 
 use futures::future::BoxFuture;
@@ -9,9 +11,13 @@ use jsonrpsee::server::middleware::rpc::layer::ResponseFuture;
 use jsonrpsee::server::middleware::rpc::RpcService;
 use jsonrpsee::server::middleware::rpc::RpcServiceT;
 use jsonrpsee::types::error::INTERNAL_ERROR_CODE;
+use jsonrpsee::types::ErrorObjectOwned;
+use jsonrpsee::types::Id;
 use jsonrpsee::types::Params;
+use jsonrpsee::types::ResponsePayload;
 use jsonrpsee::MethodResponse;
 use pin_project::pin_project;
+use tokio::sync::broadcast::error::RecvError;
 use tracing::field;
 use tracing::info_span;
 use tracing::Level;
@@ -22,17 +28,27 @@ use crate::eth::codegen;
 use crate::eth::codegen::ContractName;
 use crate::eth::codegen::SoliditySignature;
 use crate::eth::primitives::Address;
+use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::Bytes;
 use crate::eth::primitives::CallInput;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::Nonce;
+use crate::eth::primitives::StratusError;
 use crate::eth::primitives::TransactionInput;
 use crate::eth::rpc::next_rpc_param;
 use crate::eth::rpc::parse_rpc_rlp;
+use crate::eth::rpc::rpc_http_cache::is_mined_result;
 use crate::eth::rpc::rpc_parser::RpcExtensionsExt;
 use crate::eth::rpc::RpcClientApp;
+use crate::eth::rpc::RpcConsistencyToken;
+use crate::eth::rpc::RpcHttpCache;
+use crate::eth::rpc::RpcHttpCacheSlot;
+use crate::eth::rpc::RpcLimits;
+use crate::eth::rpc::RpcQuantityFormat;
+use crate::eth::storage::StratusStorage;
 use crate::event_with;
 use crate::ext::from_json_str;
+use crate::ext::not;
 use crate::ext::to_json_string;
 #[cfg(feature = "metrics")]
 use crate::if_else;
@@ -40,6 +56,7 @@ use crate::infra::metrics;
 use crate::infra::tracing::new_cid;
 use crate::infra::tracing::SpanExt;
 use crate::infra::tracing::TracingExt;
+use crate::GlobalState;
 
 // -----------------------------------------------------------------------------
 // Request handling
@@ -48,11 +65,74 @@ use crate::infra::tracing::TracingExt;
 #[derive(Debug)]
 pub struct RpcMiddleware {
     service: RpcService,
+    quantity_format: RpcQuantityFormat,
+    limits: Arc<RpcLimits>,
+    http_cache: Arc<RpcHttpCache>,
+    storage: Arc<StratusStorage>,
+    consistency_timeout: Duration,
 }
 
 impl RpcMiddleware {
-    pub fn new(service: RpcService) -> Self {
-        Self { service }
+    pub fn new(
+        service: RpcService,
+        quantity_format: RpcQuantityFormat,
+        limits: Arc<RpcLimits>,
+        http_cache: Arc<RpcHttpCache>,
+        storage: Arc<StratusStorage>,
+        consistency_timeout: Duration,
+    ) -> Self {
+        Self {
+            service,
+            quantity_format,
+            limits,
+            http_cache,
+            storage,
+            consistency_timeout,
+        }
+    }
+
+    /// Calls the service only after the follower mines the block of the consistency token, failing if it takes too long.
+    fn call_at_block<'a>(&self, request: jsonrpsee::types::Request<'a>, min_block: BlockNumber) -> ResponseFuture<BoxFuture<'a, MethodResponse>> {
+        let service = self.service.clone();
+        let storage = Arc::clone(&self.storage);
+        let timeout = self.consistency_timeout;
+        ResponseFuture::future(Box::pin(async move {
+            if let Err(e) = wait_for_block(&storage, min_block, timeout).await {
+                let extensions = request.extensions().clone();
+                return MethodResponse::error(request.id, ErrorObjectOwned::from(e)).with_extensions(extensions);
+            }
+            service.call(request).await
+        }))
+    }
+}
+
+/// Waits until the last mined block is at least `min_block`.
+async fn wait_for_block(storage: &StratusStorage, min_block: BlockNumber, timeout: Duration) -> Result<(), StratusError> {
+    // subscribe before reading the mined block so no commit is missed between both
+    let mut chain_head = storage.chain_head().subscribe();
+    if storage.read_mined_block_number()? >= min_block {
+        return Ok(());
+    }
+
+    let wait = async {
+        loop {
+            match chain_head.recv().await {
+                Ok(event) if event.number() >= min_block => return Ok(()),
+                Ok(_) => continue,
+                // events were skipped, so the storage is the source of truth
+                Err(RecvError::Lagged(_)) if storage.read_mined_block_number()? >= min_block => return Ok(()),
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return Err(StratusError::UnexpectedChannelClosed { channel: "chain_head" }),
+            }
+        }
+    };
+    match tokio::time::timeout(timeout, wait).await {
+        Ok(result) => result,
+        Err(_) => {
+            let mined = storage.read_mined_block_number()?;
+            tracing::warn!(%min_block, %mined, "rejecting request because follower is lagging behind the consistency token");
+            Err(StratusError::StratusReplicaLagging { required: min_block, mined })
+        }
     }
 }
 
@@ -123,13 +203,39 @@ impl<'a> RpcServiceT<'a> for RpcMiddleware {
         // make span available to rpc-server
         request.extensions_mut().insert(span);
 
+        // check if the response can be cached by http caches before the request is moved to the service
+        let http_cache = request
+            .extensions()
+            .get::<RpcHttpCacheSlot>()
+            .cloned()
+            .map(|slot| (slot, self.http_cache.max_age(&method, &request.params())));
+
+        // reject methods above the rate limit
+        let future_response = match self.limits.check_method(&method) {
+            // followers serve the request only after reaching the block already seen by the client
+            Ok(()) => match request.extensions().get::<RpcConsistencyToken>() {
+                Some(RpcConsistencyToken(min_block)) if GlobalState::is_follower() => {
+                    let min_block = *min_block;
+                    self.call_at_block(request, min_block)
+                }
+                _ => self.service.call(request),
+            },
+            Err(e) => {
+                let extensions = request.extensions().clone();
+                ResponseFuture::ready(MethodResponse::error(request.id, ErrorObjectOwned::from(e)).with_extensions(extensions))
+            }
+        };
+
         RpcResponse {
             client,
             id: request.id.to_string(),
+            request_id: request.id.clone().into_owned(),
             method: method.to_string(),
             tx,
+            quantity_format: self.quantity_format,
+            http_cache,
             start: Instant::now(),
-            future_response: self.service.call(request),
+            future_response,
         }
     }
 }
@@ -144,9 +250,16 @@ pub struct RpcResponse<'a> {
     // identifiers
     client: RpcClientApp,
     id: String,
+    request_id: Id<'static>,
     method: String,
     tx: Option<TransactionTracingIdentifiers>,
 
+    // formatting
+    quantity_format: RpcQuantityFormat,
+
+    // http caching slot and max-age of the response if it is immutable
+    http_cache: Option<(RpcHttpCacheSlot, Option<Duration>)>,
+
     // data
     start: Instant,
     #[pin]
@@ -221,10 +334,39 @@ impl<'a> Future for RpcResponse<'a> {
             }
         }
 
+        // rewrite quantities when a compatibility format is configured
+        let response = match response {
+            Poll::Ready(response) if not(resp.quantity_format.is_native()) =>
+                Poll::Ready(format_quantities(response, resp.request_id.clone(), resp.method, *resp.quantity_format)),
+            response => response,
+        };
+
+        // record if the final response can be cached by http caches
+        if let (Poll::Ready(response), Some((slot, max_age))) = (&response, resp.http_cache.as_ref()) {
+            let max_age = max_age.filter(|_| response.is_success() && is_mined_result(&from_json_str::<JsonValue>(response.as_result())["result"]));
+            slot.record(max_age, response.as_result());
+        }
+
         response
     }
 }
 
+/// Rewrites the quantities of a successful method response according to the format.
+fn format_quantities(response: MethodResponse, id: Id<'static>, method: &str, format: RpcQuantityFormat) -> MethodResponse {
+    if not(response.is_success()) || response.is_subscription() {
+        return response;
+    }
+
+    let mut payload: JsonValue = from_json_str(response.as_result());
+    let Some(result) = payload.get_mut("result") else {
+        return response;
+    };
+    format.format_result(method, result);
+
+    let extensions = response.extensions().clone();
+    MethodResponse::response(id, ResponsePayload::success(result.take()), usize::MAX).with_extensions(extensions)
+}
+
 // -----------------------------------------------------------------------------
 // Helpers
 // -----------------------------------------------------------------------------
@@ -0,0 +1,83 @@
+//! Dedicated thread pool for cheap RPC methods.
+
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use anyhow::anyhow;
+use tokio::sync::oneshot;
+
+use crate::eth::primitives::StratusError;
+use crate::ext::spawn_blocking_named;
+use crate::ext::spawn_thread;
+use crate::ext::MutexExt;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Thread pool serving cheap read methods independently of the Tokio blocking pool that serves heavy methods.
+///
+/// Keeps latency-sensitive polling traffic (block number, cached calls) fast when heavy queries saturate the blocking pool. If created with
+/// zero threads, handlers run in the Tokio blocking pool like heavy methods.
+pub struct RpcPool {
+    sender: Option<mpsc::Sender<Job>>,
+}
+
+impl RpcPool {
+    /// Spawns a pool with the specified number of threads. Threads stop when the pool is dropped.
+    pub fn spawn(name: &str, threads: usize) -> Self {
+        if threads == 0 {
+            return Self { sender: None };
+        }
+        tracing::info!(%name, %threads, "spawning rpc pool");
+
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for index in 0..threads {
+            let receiver = Arc::clone(&receiver);
+            spawn_thread(&format!("{}-{}", name, index), move || loop {
+                let job = receiver.lock_or_clear("rpc pool lock was poisoned").recv();
+                match job {
+                    // a panicking handler drops its result sender, so the caller is notified and the thread keeps serving
+                    Ok(job) => {
+                        let _ = panic::catch_unwind(AssertUnwindSafe(job));
+                    }
+                    Err(_) => return,
+                }
+            });
+        }
+        Self { sender: Some(sender) }
+    }
+
+    /// Runs a blocking handler in the pool and awaits its result.
+    pub async fn run<F, T>(&self, name: &str, handler: F) -> Result<T, StratusError>
+    where
+        F: FnOnce() -> Result<T, StratusError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let Some(sender) = &self.sender else {
+            return Self::run_blocking(name, handler).await;
+        };
+
+        let (result_tx, result_rx) = oneshot::channel();
+        let job: Job = Box::new(move || {
+            let _ = result_tx.send(handler());
+        });
+        if sender.send(job).is_err() {
+            return Err(StratusError::Unexpected(anyhow!("rpc pool is stopped")));
+        }
+        result_rx.await.map_err(|_| StratusError::Unexpected(anyhow!("rpc handler panicked")))?
+    }
+
+    /// Runs a blocking handler in the Tokio blocking pool that serves heavy methods and awaits its result.
+    pub async fn run_blocking<F, T>(name: &str, handler: F) -> Result<T, StratusError>
+    where
+        F: FnOnce() -> Result<T, StratusError> + Send + 'static,
+        T: Send + 'static,
+    {
+        spawn_blocking_named(name, handler)
+            .await
+            .map_err(|_| StratusError::Unexpected(anyhow!("rpc handler panicked")))?
+    }
+}
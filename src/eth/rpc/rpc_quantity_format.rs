@@ -0,0 +1,219 @@
+//! Compatibility formatting of quantities in JSON-RPC responses.
+//!
+//! Some downstream consumers (older web3.js versions, certain explorers) do not accept every valid encoding of a quantity. Responses
+//! are produced in the native format and rewritten by the RPC middleware according to the configured [`RpcQuantityFormat`].
+
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use ethereum_types::U256;
+
+use crate::alias::JsonValue;
+use crate::ext::not;
+
+/// Methods whose result is a single quantity.
+const QUANTITY_METHODS: [&str; 8] = [
+    "eth_blobBaseFee",
+    "eth_blockNumber",
+    "eth_chainId",
+    "eth_estimateGas",
+    "eth_gasPrice",
+    "eth_getBalance",
+    "eth_getTransactionCount",
+    "eth_maxPriorityFeePerGas",
+];
+
+/// Fields of response objects that contain quantities, or lists of quantities.
+const QUANTITY_FIELDS: [&str; 27] = [
+    "baseFeePerGas",
+    "blockNumber",
+    "chainId",
+    "cumulativeGasUsed",
+    "currentBlock",
+    "difficulty",
+    "effectiveGasPrice",
+    "gas",
+    "gasLimit",
+    "gasPrice",
+    "gasUsed",
+    "highestBlock",
+    "logIndex",
+    "maxFeePerGas",
+    "maxPriorityFeePerGas",
+    "nonce",
+    "number",
+    "oldestBlock",
+    "reward",
+    "size",
+    "startingBlock",
+    "status",
+    "timestamp",
+    "totalDifficulty",
+    "transactionIndex",
+    "type",
+    "value",
+];
+
+/// Gas fields of Stratus extension methods (`stratus_*`), formatted as hex or decimal depending on the format.
+const EXTENSION_GAS_FIELDS: [&str; 4] = ["gas", "gasLimit", "gasPrice", "gasUsed"];
+
+/// Formatting of quantities in JSON-RPC responses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub enum RpcQuantityFormat {
+    /// Responses are sent as produced by the methods.
+    #[default]
+    #[serde(rename = "native")]
+    Native,
+
+    /// Quantities never have leading zeros, zero is always `0x0` and gas in extensions is always hex.
+    #[serde(rename = "canonical")]
+    Canonical,
+
+    /// Same as canonical, but gas in extensions is always a decimal number, as expected by older web3.js versions and explorers.
+    #[serde(rename = "legacy")]
+    Legacy,
+}
+
+impl FromStr for RpcQuantityFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "native" => Ok(Self::Native),
+            "canonical" => Ok(Self::Canonical),
+            "legacy" => Ok(Self::Legacy),
+            s => Err(anyhow!("unknown rpc quantity format: {}", s)),
+        }
+    }
+}
+
+impl RpcQuantityFormat {
+    /// Checks if responses must be rewritten.
+    pub fn is_native(&self) -> bool {
+        *self == Self::Native
+    }
+
+    /// Rewrites the result of a method according to the format.
+    pub fn format_result(&self, method: &str, result: &mut JsonValue) {
+        if self.is_native() {
+            return;
+        }
+
+        if QUANTITY_METHODS.contains(&method) {
+            self.format_quantity(result);
+        }
+        self.format_fields(method.starts_with("stratus_"), result);
+    }
+
+    fn format_fields(&self, extension: bool, value: &mut JsonValue) {
+        match value {
+            JsonValue::Array(values) =>
+                for value in values {
+                    self.format_fields(extension, value);
+                },
+            JsonValue::Object(fields) => {
+                // block nonces are 8-byte data, not quantities
+                let is_block = fields.contains_key("parentHash");
+
+                for (key, value) in fields.iter_mut() {
+                    if extension && EXTENSION_GAS_FIELDS.contains(&key.as_str()) {
+                        self.format_extension_gas(value);
+                    } else if QUANTITY_FIELDS.contains(&key.as_str()) && not_block_nonce(is_block, key) {
+                        self.format_quantity(value);
+                    } else {
+                        self.format_fields(extension, value);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Normalizes a quantity or a list of quantities.
+    fn format_quantity(&self, value: &mut JsonValue) {
+        match value {
+            JsonValue::String(s) =>
+                if let Some(quantity) = parse_quantity(s) {
+                    *s = format!("{:#x}", quantity);
+                },
+            JsonValue::Array(values) => values.iter_mut().for_each(|value| self.format_quantity(value)),
+            _ => {}
+        }
+    }
+
+    /// Formats gas of Stratus extensions as hex or decimal.
+    fn format_extension_gas(&self, value: &mut JsonValue) {
+        let quantity = match value {
+            JsonValue::String(s) => parse_quantity(s),
+            JsonValue::Number(n) => n.as_u64().map(U256::from),
+            _ => None,
+        };
+        let Some(quantity) = quantity else { return };
+
+        *value = match self {
+            Self::Legacy if quantity <= U256::from(u64::MAX) => JsonValue::from(quantity.as_u64()),
+            _ => JsonValue::String(format!("{:#x}", quantity)),
+        };
+    }
+}
+
+fn not_block_nonce(is_block: bool, key: &str) -> bool {
+    not(is_block && key == "nonce")
+}
+
+/// Parses a hex quantity, accepting leading zeros and the empty `0x` as zero.
+fn parse_quantity(s: &str) -> Option<U256> {
+    let digits = s.strip_prefix("0x")?;
+    if digits.is_empty() {
+        return Some(U256::zero());
+    }
+    if digits.len() > 64 {
+        return None;
+    }
+    U256::from_str_radix(digits, 16).ok()
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn canonical_format_normalizes_quantities() {
+        let mut result = json!({
+            "number": "0x0001",
+            "nonce": "0x0000000000000000",
+            "parentHash": "0x00",
+            "transactions": [{ "nonce": "0x", "input": "0x00" }],
+        });
+        RpcQuantityFormat::Canonical.format_result("eth_getBlockByNumber", &mut result);
+
+        assert_eq!(result["number"], "0x1");
+        assert_eq!(result["nonce"], "0x0000000000000000");
+        assert_eq!(result["parentHash"], "0x00");
+        assert_eq!(result["transactions"][0]["nonce"], "0x0");
+        assert_eq!(result["transactions"][0]["input"], "0x00");
+    }
+
+    #[test]
+    fn quantity_format_controls_extension_gas() {
+        let pending_block = json!({ "gasLimit": "0x64", "gasUsed": 10 });
+
+        let mut canonical = pending_block.clone();
+        RpcQuantityFormat::Canonical.format_result("stratus_getPendingBlock", &mut canonical);
+        assert_eq!(canonical, json!({ "gasLimit": "0x64", "gasUsed": "0xa" }));
+
+        let mut legacy = pending_block.clone();
+        RpcQuantityFormat::Legacy.format_result("stratus_getPendingBlock", &mut legacy);
+        assert_eq!(legacy, json!({ "gasLimit": 100, "gasUsed": 10 }));
+
+        let mut native = pending_block.clone();
+        RpcQuantityFormat::Native.format_result("stratus_getPendingBlock", &mut native);
+        assert_eq!(native, pending_block);
+    }
+}
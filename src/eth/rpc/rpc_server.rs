@@ -1,5 +1,6 @@
 //! RPC server for HTTP and WS.
 
+use std::cmp::min;
 use std::collections::HashMap;
 use std::ops::Deref;
 use std::str::FromStr;
@@ -8,6 +9,10 @@ use std::time::Duration;
 
 use anyhow::Result;
 use ethereum_types::U256;
+#[cfg(feature = "dev")]
+use ethereum_types::U64;
+#[cfg(feature = "dev")]
+use ethers_core::types::TransactionRequest;
 use futures::join;
 use http::Method;
 use itertools::Itertools;
@@ -20,9 +25,11 @@ use jsonrpsee::types::Params;
 use jsonrpsee::Extensions;
 use jsonrpsee::IntoSubscriptionCloseResponse;
 use jsonrpsee::PendingSubscriptionSink;
+use revm::primitives::SpecId;
 use serde_json::json;
 use tokio::runtime::Handle;
 use tokio::select;
+use tokio::sync::broadcast;
 use tower_http::cors::Any;
 use tower_http::cors::CorsLayer;
 use tracing::field;
@@ -32,41 +39,92 @@ use tracing::Span;
 
 use super::rpc_method_wrapper::call_error_metrics_wrapper;
 use crate::alias::JsonValue;
+use crate::eth::executor::evm_spec_name;
+use crate::eth::executor::replay_transaction;
+use crate::eth::executor::Evm;
+use crate::eth::executor::EvmRoute;
 use crate::eth::executor::Executor;
+use crate::eth::executor::EXTERNAL_COMPARISONS_MAX_WINDOW;
 use crate::eth::follower::consensus::Consensus;
+use crate::eth::follower::importer::Importer;
 use crate::eth::follower::importer::ImporterConfig;
+use crate::eth::follower::importer::ImporterPrefetch;
+use crate::eth::follower::replication::stream_replicated_blocks;
 use crate::eth::miner::Miner;
 use crate::eth::miner::MinerMode;
+use crate::eth::miner::CONTRACT_ACTIVITY_MAX_WINDOW;
 use crate::eth::primitives::Address;
+use crate::eth::primitives::AddressTransactionsInput;
+use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockHeader;
+use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::Bytes;
 use crate::eth::primitives::CallInput;
-use crate::eth::primitives::ChainId;
+use crate::eth::primitives::CallTrace;
+use crate::eth::primitives::CodeHash;
+use crate::eth::primitives::EvmExecution;
 use crate::eth::primitives::Hash;
+use crate::eth::primitives::LogFilter;
 use crate::eth::primitives::LogFilterInput;
+use crate::eth::primitives::LogMined;
+#[cfg(feature = "dev")]
+use crate::eth::primitives::Nonce;
+#[cfg(feature = "dev")]
+use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
+#[cfg(feature = "dev")]
+use crate::eth::primitives::SlotValue;
 use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TraceFilterInput;
+use crate::eth::primitives::TransactionExecution;
 use crate::eth::primitives::TransactionInput;
+use crate::eth::primitives::TransactionOptions;
+use crate::eth::primitives::TransactionStage;
+use crate::eth::primitives::UnixTime;
+use crate::eth::primitives::Wei;
 use crate::eth::rpc::next_rpc_param;
 use crate::eth::rpc::next_rpc_param_or_default;
 use crate::eth::rpc::parse_rpc_rlp;
+use crate::eth::rpc::rpc_filters::FilterId;
+use crate::eth::rpc::rpc_filters::FilterKind;
+use crate::eth::rpc::rpc_logs::LogsPage;
+use crate::eth::rpc::rpc_logs::LogsPageOptions;
 use crate::eth::rpc::rpc_parser::RpcExtensionsExt;
+use crate::eth::rpc::rpc_trace::TraceOptions;
+use crate::eth::rpc::rpc_trace::TracePage;
 use crate::eth::rpc::RpcClientApp;
 use crate::eth::rpc::RpcContext;
+use crate::eth::rpc::RpcFilters;
+use crate::eth::rpc::RpcHttpCache;
 use crate::eth::rpc::RpcHttpMiddleware;
+use crate::eth::rpc::RpcLimits;
 use crate::eth::rpc::RpcMiddleware;
+use crate::eth::rpc::RpcPool;
 use crate::eth::rpc::RpcServerConfig;
 use crate::eth::rpc::RpcSubscriptions;
+use crate::eth::storage::ChainTransition;
 use crate::eth::storage::StoragePointInTime;
 use crate::eth::storage::StratusStorage;
+use crate::eth::storage::FEE_HISTORY_MAX_BLOCKS;
 use crate::ext::not;
 use crate::ext::parse_duration;
+use crate::ext::spawn_named;
 use crate::ext::to_json_string;
 use crate::ext::to_json_value;
 use crate::ext::SerdeResultExt;
-use crate::infra::build_info;
+use crate::if_else;
 use crate::infra::metrics;
+use crate::infra::operations::Operation;
+use crate::infra::operations::OperationId;
+use crate::infra::operations::OperationInfo;
+use crate::infra::operations::OperationKind;
+use crate::infra::operations::OPERATIONS;
+use crate::infra::profiling::Profiler;
+use crate::infra::tracing::warn_task_cancellation;
 use crate::infra::tracing::SpanExt;
+use crate::infra::GracefulShutdown;
+use crate::infra::NodeIdentity;
 use crate::GlobalState;
 use crate::NodeMode;
 // -----------------------------------------------------------------------------
@@ -78,30 +136,69 @@ use crate::NodeMode;
 pub async fn serve_rpc(
     // services
     storage: Arc<StratusStorage>,
-    executor: Arc<Executor>,
-    miner: Arc<Miner>,
+    executor: Arc<dyn Executor>,
+    miner: Option<Arc<Miner>>,
     consensus: Option<Arc<dyn Consensus>>,
+    shutdown: GracefulShutdown,
 
     // config
     app_config: impl serde::Serialize,
     rpc_config: RpcServerConfig,
-    chain_id: ChainId,
+    node_identity: NodeIdentity,
 ) -> anyhow::Result<()> {
     const TASK_NAME: &str = "rpc-server";
     tracing::info!(%rpc_config.rpc_address, %rpc_config.rpc_max_connections, "creating {}", TASK_NAME);
 
+    // configure notifiers (archive nodes do not mine, so their notifiers never send, but are kept open until the server stops)
+    let (notifier_pending_txs, notifier_blocks, notifier_blocks_compact, notifier_logs, notifier_transactions) = match miner {
+        Some(ref miner) => (
+            miner.notifier_pending_txs.clone(),
+            miner.notifier_blocks.clone(),
+            miner.notifier_blocks_compact.clone(),
+            miner.notifier_logs.clone(),
+            miner.notifier_transactions.clone(),
+        ),
+        None => (
+            broadcast::channel(1).0,
+            broadcast::channel(1).0,
+            broadcast::channel(1).0,
+            broadcast::channel(1).0,
+            broadcast::channel(1).0,
+        ),
+    };
+
     // configure subscriptions
     let subs = RpcSubscriptions::spawn(
-        miner.notifier_pending_txs.subscribe(),
-        miner.notifier_blocks.subscribe(),
-        miner.notifier_logs.subscribe(),
+        notifier_pending_txs.subscribe(),
+        notifier_blocks.subscribe(),
+        notifier_blocks_compact.subscribe(),
+        notifier_logs.subscribe(),
+        notifier_transactions.subscribe(),
+    );
+
+    // configure filters
+    let filters = RpcFilters::spawn(
+        rpc_config.rpc_max_filters,
+        rpc_config.rpc_filter_timeout,
+        notifier_pending_txs.subscribe(),
+        storage.chain_head().subscribe(),
+        notifier_logs.subscribe(),
     );
 
+    // configure pools
+    let hot_pool = RpcPool::spawn("rpc-hot", rpc_config.rpc_hot_threads);
+
+    // configure limits
+    let limits = Arc::new(RpcLimits::new(&rpc_config));
+    let http_cache = Arc::new(RpcHttpCache::new(rpc_config.rpc_http_cache.clone()));
+    let profiler = Arc::new(Profiler::new(&rpc_config.profiling));
+    let middleware_storage = Arc::clone(&storage);
+
     // configure context
     let ctx = RpcContext {
         app_config: to_json_value(app_config),
-        chain_id,
-        client_version: "stratus",
+        chain_id: node_identity.chain_id,
+        node_identity,
         gas_price: 0,
 
         // services
@@ -113,6 +210,15 @@ pub async fn serve_rpc(
 
         // subscriptions
         subs: Arc::clone(&subs.connected),
+
+        // filters
+        filters: Arc::clone(&filters.installed),
+
+        // limits
+        limits: Arc::clone(&limits),
+
+        // pools
+        hot_pool,
     };
 
     // configure module
@@ -121,10 +227,23 @@ pub async fn serve_rpc(
 
     // configure middleware
     let cors = CorsLayer::new().allow_methods([Method::POST]).allow_origin(Any).allow_headers(Any);
-    let rpc_middleware = RpcServiceBuilder::new().layer_fn(RpcMiddleware::new);
+    let quantity_format = rpc_config.rpc_quantity_format;
+    let consistency_timeout = rpc_config.rpc_consistency_timeout;
+    let rpc_limits = Arc::clone(&limits);
+    let rpc_http_cache = Arc::clone(&http_cache);
+    let rpc_middleware = RpcServiceBuilder::new().layer_fn(move |service| {
+        RpcMiddleware::new(
+            service,
+            quantity_format,
+            Arc::clone(&rpc_limits),
+            Arc::clone(&rpc_http_cache),
+            Arc::clone(&middleware_storage),
+            consistency_timeout,
+        )
+    });
     let http_middleware = tower::ServiceBuilder::new()
         .layer(cors)
-        .layer_fn(RpcHttpMiddleware::new)
+        .layer_fn(move |service| RpcHttpMiddleware::new(service, Arc::clone(&limits), Arc::clone(&http_cache), Arc::clone(&profiler)))
         .layer(ProxyGetRequestLayer::new("/health", "stratus_health").unwrap())
         .layer(ProxyGetRequestLayer::new("/version", "stratus_version").unwrap())
         .layer(ProxyGetRequestLayer::new("/config", "stratus_config").unwrap())
@@ -147,113 +266,315 @@ pub async fn serve_rpc(
         _ = handle_rpc_server_watch.stopped() => {
             GlobalState::shutdown_from(TASK_NAME, "finished unexpectedly");
         },
-        _ = GlobalState::wait_shutdown_warn(TASK_NAME) => {
+        _ = shutdown.wait() => {
+            warn_task_cancellation(TASK_NAME);
             let _ = handle_rpc_server.stop();
         }
     }
 
     // await rpc server and subscriptions to finish
-    join!(handle_rpc_server.stopped(), subs.handles.stopped());
+    join!(handle_rpc_server.stopped(), subs.handles.stopped(), filters.handles.stopped());
 
     Ok(())
 }
 
 fn register_methods(mut module: RpcModule<RpcContext>) -> anyhow::Result<RpcModule<RpcContext>> {
+    // archive nodes are read-only and have no miner, so methods that change state or depend on the miner are not exposed
+    let read_only = GlobalState::is_archive();
+
     // dev mode methods
     #[cfg(feature = "dev")]
-    {
+    if not(read_only) {
         module.register_blocking_method("evm_setNextBlockTimestamp", evm_set_next_block_timestamp)?;
+        module.register_blocking_method("evm_increaseTime", evm_increase_time)?;
         module.register_blocking_method("evm_mine", evm_mine)?;
+        module.register_async_method("evm_setAutomine", evm_set_automine)?;
         module.register_blocking_method("hardhat_reset", stratus_reset)?;
         module.register_blocking_method("stratus_reset", stratus_reset)?;
+        module.register_blocking_method("evm_snapshot", evm_snapshot)?;
+        module.register_blocking_method("evm_revert", evm_revert)?;
+
+        // cheatcodes
+        module.register_blocking_method("hardhat_setBalance", stratus_set_balance)?;
+        module.register_blocking_method("anvil_setBalance", stratus_set_balance)?;
+        module.register_blocking_method("hardhat_setNonce", stratus_set_nonce)?;
+        module.register_blocking_method("anvil_setNonce", stratus_set_nonce)?;
+        module.register_blocking_method("hardhat_setCode", stratus_set_code)?;
+        module.register_blocking_method("anvil_setCode", stratus_set_code)?;
+        module.register_blocking_method("hardhat_setStorageAt", stratus_set_storage_at)?;
+        module.register_blocking_method("anvil_setStorageAt", stratus_set_storage_at)?;
+
+        // impersonation
+        module.register_method("hardhat_impersonateAccount", stratus_impersonate_account)?;
+        module.register_method("anvil_impersonateAccount", stratus_impersonate_account)?;
+        module.register_method("hardhat_stopImpersonatingAccount", stratus_stop_impersonating_account)?;
+        module.register_method("anvil_stopImpersonatingAccount", stratus_stop_impersonating_account)?;
+        module.register_method("stratus_getImpersonationAudit", stratus_get_impersonation_audit)?;
+        module.register_blocking_method("eth_sendTransaction", call_error_metrics_wrapper(eth_send_transaction))?;
     }
 
     // stratus status
     module.register_async_method("stratus_health", stratus_health)?;
 
     // stratus admin
-    module.register_method("stratus_enableTransactions", stratus_enable_transactions)?;
-    module.register_method("stratus_disableTransactions", stratus_disable_transactions)?;
-    module.register_method("stratus_enableMiner", stratus_enable_miner)?;
-    module.register_method("stratus_disableMiner", stratus_disable_miner)?;
     module.register_method("stratus_enableUnknownClients", stratus_enable_unknown_clients)?;
     module.register_method("stratus_disableUnknownClients", stratus_disable_unknown_clients)?;
-    module.register_async_method("stratus_changeToLeader", stratus_change_to_leader)?;
-    module.register_async_method("stratus_changeToFollower", stratus_change_to_follower)?;
-    module.register_async_method("stratus_initImporter", stratus_init_importer)?;
-    module.register_method("stratus_shutdownImporter", stratus_shutdown_importer)?;
-    module.register_async_method("stratus_changeMinerMode", stratus_change_miner_mode)?;
+    if not(read_only) {
+        module.register_method("stratus_enableTransactions", stratus_enable_transactions)?;
+        module.register_method("stratus_disableTransactions", stratus_disable_transactions)?;
+        module.register_method("stratus_enableMiner", stratus_enable_miner)?;
+        module.register_method("stratus_disableMiner", stratus_disable_miner)?;
+        module.register_method("stratus_getQuarantinedTransactions", stratus_get_quarantined_transactions)?;
+        module.register_method("stratus_releaseQuarantinedTransaction", stratus_release_quarantined_transaction)?;
+        module.register_method("stratus_getDiscardedAttempts", stratus_get_discarded_attempts)?;
+        module.register_blocking_method("stratus_restoreQuarantinedBlocks", stratus_restore_quarantined_blocks)?;
+        module.register_async_method("stratus_changeToLeader", stratus_change_to_leader)?;
+        module.register_async_method("stratus_changeToFollower", stratus_change_to_follower)?;
+        module.register_async_method("stratus_initImporter", stratus_init_importer)?;
+        module.register_method("stratus_shutdownImporter", stratus_shutdown_importer)?;
+        module.register_async_method("stratus_changeMinerMode", stratus_change_miner_mode)?;
+    }
+    module.register_method("stratus_getQuarantinedBlocks", stratus_get_quarantined_blocks)?;
+    module.register_method("stratus_resizeEvmPool", stratus_resize_evm_pool)?;
 
     // stratus state
     module.register_method("stratus_version", stratus_version)?;
     module.register_method("stratus_config", stratus_config)?;
+    module.register_method("stratus_getChainConfig", stratus_get_chain_config)?;
     module.register_method("stratus_state", stratus_state)?;
+    module.register_method("stratus_computeMappingSlot", stratus_compute_mapping_slot)?;
 
     module.register_async_method("stratus_getSubscriptions", stratus_get_subscriptions)?;
     module.register_method("stratus_pendingTransactionsCount", stratus_pending_transactions_count)?;
+    module.register_method("stratus_getOperations", stratus_get_operations)?;
+    module.register_method("stratus_cancelOperation", stratus_cancel_operation)?;
+    module.register_method("stratus_getPendingBlock", stratus_get_pending_block)?;
+    module.register_method("stratus_getTopContracts", stratus_get_top_contracts)?;
+    module.register_method("stratus_getComparisonStats", stratus_get_comparison_stats)?;
+    module.register_method("stratus_getChainTransitions", stratus_get_chain_transitions)?;
+
+    // txpool
+    module.register_method("txpool_status", txpool_status)?;
 
     // blockchain
     module.register_method("net_version", net_version)?;
     module.register_async_method("net_listening", net_listening)?;
     module.register_method("eth_chainId", eth_chain_id)?;
+    module.register_async_method("eth_syncing", eth_syncing)?;
+    module.register_method("eth_config", stratus_get_chain_config)?;
     module.register_method("web3_clientVersion", web3_client_version)?;
 
     // gas
-    module.register_method("eth_gasPrice", eth_gas_price)?;
+    module.register_blocking_method("eth_gasPrice", eth_gas_price)?;
+    module.register_blocking_method("eth_feeHistory", eth_fee_history)?;
+    module.register_blocking_method("eth_maxPriorityFeePerGas", eth_max_priority_fee_per_gas)?;
+    module.register_blocking_method("eth_blobBaseFee", eth_blob_base_fee)?;
 
     // block
-    module.register_blocking_method("eth_blockNumber", eth_block_number)?;
+    register_hot_method(&mut module, "eth_blockNumber", eth_block_number)?;
     module.register_blocking_method("eth_getBlockByNumber", eth_get_block_by_number)?;
     module.register_blocking_method("eth_getBlockByHash", eth_get_block_by_hash)?;
+    module.register_blocking_method("eth_getBlockReceipts", eth_get_block_receipts)?;
+    module.register_blocking_method("stratus_getBlockStateDiff", stratus_get_block_state_diff)?;
     module.register_method("eth_getUncleByBlockHashAndIndex", eth_get_uncle_by_block_hash_and_index)?;
 
     // transactions
     module.register_blocking_method("eth_getTransactionByHash", eth_get_transaction_by_hash)?;
     module.register_blocking_method("eth_getTransactionReceipt", eth_get_transaction_receipt)?;
+    module.register_blocking_method("stratus_getReceiptProof", stratus_get_receipt_proof)?;
+    module.register_blocking_method("stratus_getTransactionsByAddress", stratus_get_transactions_by_address)?;
     module.register_blocking_method("eth_estimateGas", eth_estimate_gas)?;
-    module.register_blocking_method("eth_call", call_error_metrics_wrapper(eth_call))?;
+    module.register_async_method("eth_call", eth_call_routed)?;
+    module.register_blocking_method("eth_callMany", eth_call_many)?;
+    module.register_blocking_method("eth_createAccessList", eth_create_access_list)?;
+    module.register_blocking_method("debug_traceCallMany", debug_trace_call_many)?;
+    module.register_blocking_method("debug_traceBlockByNumber", debug_trace_block_by_number)?;
+    module.register_blocking_method("debug_traceBlockByHash", debug_trace_block_by_hash)?;
+    module.register_blocking_method("debug_traceTransaction", debug_trace_transaction)?;
+    module.register_blocking_method("stratus_replayTransaction", stratus_replay_transaction)?;
+    module.register_blocking_method("trace_block", trace_block)?;
+    module.register_blocking_method("trace_transaction", trace_transaction)?;
+    module.register_blocking_method("trace_filter", trace_filter)?;
     module.register_blocking_method("eth_sendRawTransaction", call_error_metrics_wrapper(eth_send_raw_transaction))?;
 
     // logs
     module.register_blocking_method("eth_getLogs", eth_get_logs)?;
+    module.register_blocking_method("stratus_getLogsPaginated", stratus_get_logs_paginated)?;
+
+    // filters
+    module.register_blocking_method("eth_newFilter", eth_new_filter)?;
+    module.register_method("eth_newBlockFilter", eth_new_block_filter)?;
+    module.register_method("eth_newPendingTransactionFilter", eth_new_pending_transaction_filter)?;
+    module.register_method("eth_getFilterChanges", eth_get_filter_changes)?;
+    module.register_blocking_method("eth_getFilterLogs", eth_get_filter_logs)?;
+    module.register_method("eth_uninstallFilter", eth_uninstall_filter)?;
 
     // account
     module.register_method("eth_accounts", eth_accounts)?;
     module.register_blocking_method("eth_getTransactionCount", eth_get_transaction_count)?;
     module.register_blocking_method("eth_getBalance", eth_get_balance)?;
     module.register_blocking_method("eth_getCode", eth_get_code)?;
+    module.register_blocking_method("stratus_getCodeByHash", stratus_get_code_by_hash)?;
+    module.register_blocking_method("eth_getProof", eth_get_proof)?;
 
     // storage
     module.register_blocking_method("eth_getStorageAt", eth_get_storage_at)?;
 
     // subscriptions
     module.register_subscription("eth_subscribe", "eth_subscription", "eth_unsubscribe", eth_subscribe)?;
+    module.register_subscription(
+        "stratus_subscribeAccountActivity",
+        "stratus_accountActivity",
+        "stratus_unsubscribeAccountActivity",
+        stratus_subscribe_account_activity,
+    )?;
+    module.register_subscription(
+        "stratus_subscribeReplication",
+        "stratus_replication",
+        "stratus_unsubscribeReplication",
+        stratus_subscribe_replication,
+    )?;
 
     Ok(module)
 }
 
+/// Registers a cheap blocking method served by the hot pool instead of the blocking pool that serves heavy methods.
+fn register_hot_method<F, T>(module: &mut RpcModule<RpcContext>, method_name: &'static str, handler: F) -> anyhow::Result<()>
+where
+    F: Fn(Params<'static>, Arc<RpcContext>, Extensions) -> Result<T, StratusError> + Clone + Send + Sync + 'static,
+    T: serde::Serialize + Clone + Send + 'static,
+{
+    module.register_async_method(method_name, move |params, ctx, ext| {
+        let handler = handler.clone();
+        async move {
+            let handler_ctx = Arc::clone(&ctx);
+            ctx.hot_pool.run(method_name, move || handler(params, handler_ctx, ext)).await
+        }
+    })?;
+    Ok(())
+}
+
 // -----------------------------------------------------------------------------
 // Debug
 // -----------------------------------------------------------------------------
 
+/// Mines a block with the transactions pending in the temporary storage, optionally setting its timestamp first.
+#[cfg(feature = "dev")]
+fn evm_mine(params: Params<'_>, ctx: Arc<RpcContext>, _: Extensions) -> Result<JsonValue, StratusError> {
+    let (_, timestamp) = next_rpc_param_or_default::<Option<UnixTime>>(params.sequence())?;
+    if let Some(timestamp) = timestamp {
+        set_next_block_timestamp(&ctx, timestamp)?;
+    }
+    ctx.miner()?.mine_local_and_commit()?;
+    Ok(to_json_value(true))
+}
+
+/// Enables mining a block for each transaction, or disables it so blocks are mined only with `evm_mine`.
 #[cfg(feature = "dev")]
-fn evm_mine(_params: Params<'_>, ctx: Arc<RpcContext>, _: Extensions) -> Result<JsonValue, StratusError> {
-    ctx.miner.mine_local_and_commit()?;
+async fn evm_set_automine(params: Params<'_>, ctx: Arc<RpcContext>, _: Extensions) -> Result<JsonValue, StratusError> {
+    let (_, enabled) = next_rpc_param::<bool>(params.sequence())?;
+    ctx.miner()?.set_automine(enabled).await;
     Ok(to_json_value(true))
 }
 
 #[cfg(feature = "dev")]
 fn evm_set_next_block_timestamp(params: Params<'_>, ctx: Arc<RpcContext>, _: Extensions) -> Result<JsonValue, StratusError> {
-    use crate::eth::primitives::UnixTime;
+    let (_, timestamp) = next_rpc_param::<UnixTime>(params.sequence())?;
+    set_next_block_timestamp(&ctx, timestamp)?;
+    Ok(to_json_value(timestamp))
+}
+
+/// Shifts the timestamp of the next blocks forward, returning the total offset from the wall clock in seconds.
+#[cfg(feature = "dev")]
+fn evm_increase_time(params: Params<'_>, _: Arc<RpcContext>, _: Extensions) -> Result<JsonValue, StratusError> {
+    let (_, seconds) = next_rpc_param::<u64>(params.sequence())?;
+    let offset = UnixTime::increase_offset(seconds)?;
+    Ok(to_json_value(offset))
+}
+
+#[cfg(feature = "dev")]
+fn set_next_block_timestamp(ctx: &RpcContext, timestamp: UnixTime) -> Result<(), StratusError> {
     use crate::log_and_err;
 
-    let (_, timestamp) = next_rpc_param::<UnixTime>(params.sequence())?;
     let latest = ctx.storage.read_block(&BlockFilter::Latest)?;
     match latest {
         Some(block) => UnixTime::set_offset(timestamp, block.header.timestamp)?,
         None => return log_and_err!("reading latest block returned None")?,
     }
-    Ok(to_json_value(timestamp))
+    Ok(())
+}
+
+#[cfg(feature = "dev")]
+fn evm_snapshot(_: Params<'_>, ctx: Arc<RpcContext>, _: Extensions) -> Result<JsonValue, StratusError> {
+    let id = ctx.storage.snapshot()?;
+    Ok(to_json_value(hex_num(id)))
+}
+
+#[cfg(feature = "dev")]
+fn evm_revert(params: Params<'_>, ctx: Arc<RpcContext>, _: Extensions) -> Result<JsonValue, StratusError> {
+    let (_, id) = next_rpc_param::<U64>(params.sequence())?;
+    let reverted = ctx.storage.revert(id.as_u64())?;
+    Ok(to_json_value(reverted))
+}
+
+/// Allows an account to send transactions without signature on behalf of the client application.
+#[cfg(feature = "dev")]
+fn stratus_impersonate_account(params: Params<'_>, ctx: &RpcContext, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    let client = ext.rpc_client();
+    reject_unknown_client(client.clone())?;
+    let (_, address) = next_rpc_param::<Address>(params.sequence())?;
+    ctx.executor.impersonation().start(address, &client.to_string());
+    Ok(to_json_value(true))
+}
+
+/// Stops allowing an account to send transactions without signature on behalf of the client application.
+#[cfg(feature = "dev")]
+fn stratus_stop_impersonating_account(params: Params<'_>, ctx: &RpcContext, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    let client = ext.rpc_client();
+    reject_unknown_client(client.clone())?;
+    let (_, address) = next_rpc_param::<Address>(params.sequence())?;
+    ctx.executor.impersonation().stop(&address, &client.to_string());
+    Ok(to_json_value(true))
+}
+
+/// Returns recent impersonations and impersonated transactions.
+#[cfg(feature = "dev")]
+fn stratus_get_impersonation_audit(_: Params<'_>, ctx: &RpcContext, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    reject_unknown_client(ext.rpc_client())?;
+    Ok(to_json_value(ctx.executor.impersonation().audit()))
+}
+
+#[cfg(feature = "dev")]
+fn stratus_set_balance(params: Params<'_>, ctx: Arc<RpcContext>, _: Extensions) -> Result<JsonValue, StratusError> {
+    let (params, address) = next_rpc_param::<Address>(params.sequence())?;
+    let (_, balance) = next_rpc_param::<Wei>(params)?;
+    ctx.storage.set_balance(&address, balance)?;
+    Ok(to_json_value(true))
+}
+
+#[cfg(feature = "dev")]
+fn stratus_set_nonce(params: Params<'_>, ctx: Arc<RpcContext>, _: Extensions) -> Result<JsonValue, StratusError> {
+    let (params, address) = next_rpc_param::<Address>(params.sequence())?;
+    let (_, nonce) = next_rpc_param::<Nonce>(params)?;
+    ctx.storage.set_nonce(&address, nonce)?;
+    Ok(to_json_value(true))
+}
+
+#[cfg(feature = "dev")]
+fn stratus_set_code(params: Params<'_>, ctx: Arc<RpcContext>, _: Extensions) -> Result<JsonValue, StratusError> {
+    let (params, address) = next_rpc_param::<Address>(params.sequence())?;
+    let (_, code) = next_rpc_param::<Bytes>(params)?;
+    ctx.storage.set_code(&address, code)?;
+    Ok(to_json_value(true))
+}
+
+#[cfg(feature = "dev")]
+fn stratus_set_storage_at(params: Params<'_>, ctx: Arc<RpcContext>, _: Extensions) -> Result<JsonValue, StratusError> {
+    let (params, address) = next_rpc_param::<Address>(params.sequence())?;
+    let (params, index) = next_rpc_param::<SlotIndex>(params)?;
+    let (_, value) = next_rpc_param::<SlotValue>(params)?;
+    ctx.storage.set_storage(&address, Slot::new(index, value))?;
+    Ok(to_json_value(true))
 }
 
 // -----------------------------------------------------------------------------
@@ -267,7 +588,7 @@ async fn stratus_health(_: Params<'_>, context: Arc<RpcContext>, _: Extensions)
     }
 
     let should_serve = match GlobalState::get_node_mode() {
-        NodeMode::Leader => true,
+        NodeMode::Leader | NodeMode::Archive => true,
         NodeMode::Follower => {
             let consensus_lock = context.consensus.read().map_err(|_| {
                 tracing::error!("consensus read lock was poisoned");
@@ -339,6 +660,7 @@ async fn stratus_change_to_leader(_: Params<'_>, ctx: Arc<RpcContext>, ext: Exte
     tracing::info!("miner mode changed to interval(1s) successfully");
 
     GlobalState::set_node_mode(NodeMode::Leader);
+    ctx.storage.record_promotion()?;
     tracing::info!("node mode changed to leader successfully");
 
     Ok(json!(true))
@@ -365,7 +687,7 @@ async fn stratus_change_to_follower(params: Params<'_>, ctx: Arc<RpcContext>, ex
         });
     }
 
-    if not(ctx.miner.is_paused()) {
+    if not(ctx.miner()?.is_paused()) {
         tracing::error!("miner is currently not paused, cannot change node mode");
         return Err(StratusError::MinerEnabled);
     }
@@ -401,7 +723,8 @@ async fn stratus_init_importer(params: Params<'_>, ctx: Arc<RpcContext>, _: Exte
     let (params, external_rpc) = next_rpc_param::<String>(params.sequence())?;
     let (params, external_rpc_ws) = next_rpc_param::<String>(params)?;
     let (params, raw_external_rpc_timeout) = next_rpc_param::<String>(params)?;
-    let (_, raw_sync_interval) = next_rpc_param::<String>(params)?;
+    let (params, raw_sync_interval) = next_rpc_param::<String>(params)?;
+    let (_, replication) = next_rpc_param_or_default::<bool>(params)?;
 
     let external_rpc_timeout = parse_duration(&raw_external_rpc_timeout).map_err(|e| {
         tracing::error!(reason = ?e, "failed to parse external_rpc_timeout");
@@ -418,6 +741,10 @@ async fn stratus_init_importer(params: Params<'_>, ctx: Arc<RpcContext>, _: Exte
         external_rpc_ws: Some(external_rpc_ws),
         external_rpc_timeout,
         sync_interval,
+        max_reorg_depth: Importer::DEFAULT_MAX_REORG_DEPTH,
+        importer_prefetch_blocks: ImporterPrefetch::DEFAULT.blocks,
+        importer_max_parallel_requests: ImporterPrefetch::DEFAULT.requests,
+        replication,
     };
 
     importer_config.init_follower_importer(ctx).await
@@ -475,14 +802,15 @@ async fn change_miner_mode(new_mode: MinerMode, ctx: &RpcContext) -> Result<Json
         return Err(StratusError::RpcTransactionEnabled);
     }
 
-    let previous_mode = ctx.miner.mode();
+    let miner = ctx.miner()?;
+    let previous_mode = miner.mode();
 
     if previous_mode == new_mode {
         tracing::warn!(?new_mode, current = ?new_mode, "miner mode already set, skipping");
         return Ok(json!(false));
     }
 
-    if not(ctx.miner.is_paused()) && previous_mode.is_interval() {
+    if not(miner.is_paused()) && previous_mode.is_interval() {
         tracing::error!("cannot change miner mode from Interval Mode to another mode without pausing it first");
         return Err(StratusError::MinerEnabled);
     }
@@ -499,7 +827,7 @@ async fn change_miner_mode(new_mode: MinerMode, ctx: &RpcContext) -> Result<Json
                 });
             }
 
-            ctx.miner.switch_to_external_mode().await;
+            miner.switch_to_external_mode().await;
         }
         MinerMode::Interval(duration) => {
             tracing::info!(duration = ?duration, "changing miner mode to Interval");
@@ -516,7 +844,7 @@ async fn change_miner_mode(new_mode: MinerMode, ctx: &RpcContext) -> Result<Json
                 }
             }
 
-            ctx.miner.start_interval_mining(duration).await;
+            miner.start_interval_mining(duration).await;
         }
         MinerMode::Automine => {
             tracing::error!("automine mode is not supported");
@@ -547,14 +875,74 @@ fn stratus_disable_transactions(_: Params<'_>, _: &RpcContext, _: &Extensions) -
     GlobalState::is_transactions_enabled()
 }
 
-fn stratus_enable_miner(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> bool {
-    ctx.miner.unpause();
-    true
+fn stratus_enable_miner(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> Result<bool, StratusError> {
+    ctx.miner()?.unpause();
+    Ok(true)
+}
+
+fn stratus_disable_miner(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> Result<bool, StratusError> {
+    ctx.miner()?.pause();
+    Ok(false)
+}
+
+/// Returns the transactions quarantined because they repeatedly failed to be committed or executed.
+fn stratus_get_quarantined_transactions(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> Result<JsonValue, StratusError> {
+    Ok(to_json_value(ctx.miner()?.quarantine.list()))
+}
+
+/// Releases a transaction from the quarantine so it can be sent again. Returns false if it was not quarantined.
+fn stratus_release_quarantined_transaction(params: Params<'_>, ctx: &RpcContext, _: &Extensions) -> Result<bool, StratusError> {
+    let (_, hash) = next_rpc_param::<Hash>(params.sequence())?;
+    Ok(ctx.miner()?.quarantine.release(&hash))
+}
+
+/// Returns blocks removed by resets of the permanent storage that can still be restored.
+fn stratus_get_quarantined_blocks(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> JsonValue {
+    to_json_value(ctx.storage.quarantined_blocks())
+}
+
+/// Restores blocks removed by a reset of the permanent storage, by default the most recent one.
+fn stratus_restore_quarantined_blocks(params: Params<'_>, ctx: Arc<RpcContext>, _: Extensions) -> Result<JsonValue, StratusError> {
+    let (_, id) = next_rpc_param_or_default::<Option<u64>>(params.sequence())?;
+    ctx.storage.restore_quarantined_blocks(id).map(to_json_value)
+}
+
+/// Returns the executions of a transaction discarded because of conflicts or commit failures, if diagnostics are enabled.
+fn stratus_get_discarded_attempts(params: Params<'_>, ctx: &RpcContext, _: &Extensions) -> Result<JsonValue, StratusError> {
+    let (_, hash) = next_rpc_param::<Hash>(params.sequence())?;
+    Ok(to_json_value(ctx.miner()?.discarded_attempts.get(&hash)))
+}
+
+/// Changes the number of EVMs executing the tasks of a route (`parallel`, `call_present` or `call_past`).
+fn stratus_resize_evm_pool(params: Params<'_>, ctx: &RpcContext, _: &Extensions) -> Result<bool, StratusError> {
+    let (params, route) = next_rpc_param::<String>(params.sequence())?;
+    let (_, size) = next_rpc_param::<usize>(params)?;
+    let route = EvmRoute::from_str(&route).map_err(|e| StratusError::RpcParameterInvalid {
+        rust_type: "EvmRoute",
+        decode_error: e.to_string(),
+    })?;
+    ctx.executor.resize_evms(route, size)?;
+    Ok(true)
+}
+
+/// Returns recent promotions and rollbacks of the local chain.
+fn stratus_get_chain_transitions(_: Params<'_>, ctx: &RpcContext, ext: &Extensions) -> Result<Vec<ChainTransition>, StratusError> {
+    reject_unknown_client(ext.rpc_client())?;
+    Ok(ctx.storage.chain_transitions())
+}
+
+/// Returns long-running operations that can be cancelled.
+fn stratus_get_operations(_: Params<'_>, _: &RpcContext, ext: &Extensions) -> Result<Vec<OperationInfo>, StratusError> {
+    reject_unknown_client(ext.rpc_client())?;
+    Ok(OPERATIONS.list())
 }
 
-fn stratus_disable_miner(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> bool {
-    ctx.miner.pause();
-    false
+/// Requests the cancellation of a long-running operation.
+fn stratus_cancel_operation(params: Params<'_>, _: &RpcContext, ext: &Extensions) -> Result<bool, StratusError> {
+    reject_unknown_client(ext.rpc_client())?;
+    let (_, id) = next_rpc_param::<OperationId>(params.sequence())?;
+    OPERATIONS.cancel(id)?;
+    Ok(true)
 }
 
 /// Returns the count of executed transactions waiting to enter the next block.
@@ -562,18 +950,158 @@ fn stratus_pending_transactions_count(_: Params<'_>, ctx: &RpcContext, _: &Exten
     ctx.storage.pending_transactions().len()
 }
 
+/// Returns a preview of the block the next mining interval will produce and the amount of data held by the temporary storage.
+///
+/// Transactions that do not fit in the block gas limit are listed as deferred because they will be moved to the following block.
+fn stratus_get_pending_block(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> Result<JsonValue, StratusError> {
+    let Some(mut block) = ctx.storage.read_pending_block()? else {
+        return Ok(JsonValue::Null);
+    };
+
+    let gas_limit = ctx.miner()?.block_gas_limit();
+    let deferred_txs = block.split_off_over_gas_limit(gas_limit);
+
+    Ok(json!({
+        "number": block.header.number,
+        "timestamp": block.header.timestamp,
+        "gasLimit": gas_limit,
+        "gasUsed": block.gas_used(),
+        "transactions": block.transactions.keys().collect_vec(),
+        "deferredTransactions": deferred_txs.iter().map(TransactionExecution::hash).collect_vec(),
+        "temporaryStorage": ctx.storage.read_temp_stats(),
+    }))
+}
+
+/// Returns the contracts with most gas used by committed transactions in a recent window (like `5m` or `300000`), at most one hour.
+///
+/// Rates are averaged per minute over the whole window, so a spike can be compared with the baseline of previous minutes.
+fn stratus_get_top_contracts(params: Params<'_>, ctx: &RpcContext, _: &Extensions) -> Result<JsonValue, StratusError> {
+    const DEFAULT_LIMIT: usize = 10;
+
+    let (params, window) = next_rpc_param::<String>(params.sequence())?;
+    let (_, limit) = next_rpc_param_or_default::<Option<usize>>(params)?;
+    let window = parse_rpc_window(&window, CONTRACT_ACTIVITY_MAX_WINDOW)?;
+
+    let top = ctx
+        .miner
+        .contract_activity
+        .top_contracts(window, limit.unwrap_or(DEFAULT_LIMIT), UnixTime::now());
+    Ok(to_json_value(top))
+}
+
+fn stratus_get_comparison_stats(params: Params<'_>, ctx: &RpcContext, _: &Extensions) -> Result<JsonValue, StratusError> {
+    let (_, window) = next_rpc_param::<String>(params.sequence())?;
+    let window = parse_rpc_window(&window, EXTERNAL_COMPARISONS_MAX_WINDOW)?;
+
+    Ok(to_json_value(ctx.executor.external_comparison_stats(window)))
+}
+
+/// Parses a window of time like `5m` that must not be longer than `max`.
+fn parse_rpc_window(window: &str, max: Duration) -> Result<Duration, StratusError> {
+    let window = parse_duration(window).map_err(|e| StratusError::RpcParameterInvalid {
+        rust_type: "Duration",
+        decode_error: e.to_string(),
+    })?;
+    if window > max {
+        return Err(StratusError::RpcWindowInvalid {
+            actual: window.as_secs(),
+            max: max.as_secs(),
+        });
+    }
+    Ok(window)
+}
+
+// -----------------------------------------------------------------------------
+// Transaction pool
+// -----------------------------------------------------------------------------
+
+/// Returns the number of executed, queued and expired local transactions.
+fn txpool_status(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> JsonValue {
+    json!({
+        "pending": hex_num(ctx.storage.pending_transactions().len()),
+        "queued": hex_num(ctx.executor.queued_transactions()),
+        "expired": hex_num(ctx.executor.expired_transactions()),
+    })
+}
+
 // -----------------------------------------------------------------------------
 // Stratus - State
 // -----------------------------------------------------------------------------
 
-fn stratus_version(_: Params<'_>, _: &RpcContext, _: &Extensions) -> Result<JsonValue, StratusError> {
-    Ok(build_info::as_json())
+fn stratus_version(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> Result<JsonValue, StratusError> {
+    Ok(ctx.node_identity.as_json())
 }
 
 fn stratus_config(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> Result<JsonValue, StratusError> {
     Ok(ctx.app_config.clone())
 }
 
+fn stratus_get_chain_config(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> Result<JsonValue, StratusError> {
+    let evm_config = ctx.executor.evm_config();
+
+    // all forks up to the active spec are enabled since genesis
+    let fork_schedule = [
+        ("homestead", SpecId::HOMESTEAD),
+        ("tangerineWhistle", SpecId::TANGERINE),
+        ("spuriousDragon", SpecId::SPURIOUS_DRAGON),
+        ("byzantium", SpecId::BYZANTIUM),
+        ("constantinople", SpecId::CONSTANTINOPLE),
+        ("petersburg", SpecId::PETERSBURG),
+        ("istanbul", SpecId::ISTANBUL),
+        ("berlin", SpecId::BERLIN),
+        ("london", SpecId::LONDON),
+        ("shanghai", SpecId::SHANGHAI),
+        ("cancun", SpecId::CANCUN),
+    ]
+    .into_iter()
+    .filter(|(_, spec)| SpecId::enabled(evm_config.spec, *spec))
+    .map(|(fork, _)| (fork.to_owned(), json!(hex_num(0))))
+    .collect::<serde_json::Map<_, _>>();
+
+    // archive nodes do not mine, so they have no base fee mode and use the default block gas limit
+    let base_fee = next_base_fee(ctx)?;
+    let base_fee_mode = ctx.miner.as_ref().map(|miner| miner.base_fee().mode());
+    let block_gas_limit = ctx.miner.as_ref().map_or(BlockHeader::GAS_LIMIT, |miner| miner.block_gas_limit());
+
+    Ok(json!({
+        "chainId": hex_num(ctx.chain_id),
+        "hardfork": evm_spec_name(evm_config.spec),
+        "forkSchedule": fork_schedule,
+        "blockGasLimit": hex_num(block_gas_limit),
+        "transactionGasLimit": hex_num(Evm::TX_GAS_LIMIT),
+        "fees": {
+            "mode": base_fee_mode,
+            "gasPrice": hex_num(base_fee + suggest_priority_fee(ctx)?),
+            "baseFeePerGas": hex_num(base_fee),
+        },
+        // blocks are final as soon as they are mined
+        "finalityDepth": 0,
+    }))
+}
+
+/// Computes the storage index of a Solidity mapping entry, so clients reading storage directly do not need to implement the layout rules.
+///
+/// Value type keys are left-padded to 32 bytes, and dynamic keys (`string` or `bytes`) must set the third param to `true`.
+fn stratus_compute_mapping_slot(params: Params<'_>, _: &RpcContext, _: &Extensions) -> Result<String, StratusError> {
+    let (params, base) = next_rpc_param::<SlotIndex>(params.sequence())?;
+    let (params, key) = next_rpc_param::<Bytes>(params)?;
+    let (_, dynamic) = next_rpc_param_or_default::<bool>(params)?;
+
+    let index = if dynamic {
+        base.to_dynamic_mapping_index(&key)
+    } else {
+        if key.len() > 32 {
+            return Err(StratusError::RpcParameterInvalid {
+                rust_type: "Bytes",
+                decode_error: "value type mapping key must have at most 32 bytes".to_owned(),
+            });
+        }
+        base.to_mapping_index(key.to_vec())
+    };
+
+    Ok(hex_num_zero_padded(index.as_u256()))
+}
+
 fn stratus_state(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> Result<JsonValue, StratusError> {
     Ok(GlobalState::get_global_state_as_json(ctx))
 }
@@ -584,12 +1112,16 @@ async fn stratus_get_subscriptions(_: Params<'_>, ctx: Arc<RpcContext>, ext: Ext
     // NOTE: this is a workaround for holding only one lock at a time
     let pending_txs = serde_json::to_value(ctx.subs.new_heads.read().await.values().collect_vec()).expect_infallible();
     let new_heads = serde_json::to_value(ctx.subs.pending_txs.read().await.values().collect_vec()).expect_infallible();
+    let new_heads_compact = serde_json::to_value(ctx.subs.new_heads_compact.read().await.values().collect_vec()).expect_infallible();
     let logs = serde_json::to_value(ctx.subs.logs.read().await.values().flat_map(HashMap::values).collect_vec()).expect_infallible();
+    let account_activity = serde_json::to_value(ctx.subs.account_activity.read().await.values().flat_map(HashMap::values).collect_vec()).expect_infallible();
 
     let response = json!({
         "newPendingTransactions": pending_txs,
         "newHeads": new_heads,
+        "newHeadsCompact": new_heads_compact,
         "logs": logs,
+        "accountActivity": account_activity,
     });
     Ok(response)
 }
@@ -607,23 +1139,207 @@ async fn net_listening(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions
 }
 
 fn net_version(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> String {
-    ctx.chain_id.to_string()
+    ctx.node_identity.chain_id.to_string()
 }
 
 fn eth_chain_id(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> String {
-    hex_num(ctx.chain_id)
+    hex_num(ctx.node_identity.chain_id)
+}
+
+/// Returns the sync progress of a follower behind the leader, or `false` if the node is caught up or is not a follower.
+async fn eth_syncing(_: Params<'_>, ctx: Arc<RpcContext>, _: Extensions) -> Result<JsonValue, StratusError> {
+    if not(GlobalState::is_follower()) {
+        return Ok(JsonValue::Bool(false));
+    }
+
+    let consensus = {
+        let consensus_lock = ctx.consensus.read().map_err(|_| {
+            tracing::error!("consensus read lock was poisoned");
+            ctx.consensus.clear_poison();
+            StratusError::ConsensusLockFailed
+        })?;
+        consensus_lock.clone()
+    };
+    let Some(consensus) = consensus else {
+        return Ok(JsonValue::Bool(false));
+    };
+
+    match consensus.sync_status().await {
+        Ok(Some(status)) => Ok(to_json_value(status)),
+        Ok(None) => Ok(JsonValue::Bool(false)),
+        Err(e) => {
+            tracing::error!(reason = ?e, "failed to read sync status");
+            Err(StratusError::Unexpected(e))
+        }
+    }
 }
 
 fn web3_client_version(_: Params<'_>, ctx: &RpcContext, _: &Extensions) -> String {
-    ctx.client_version.to_owned()
+    ctx.node_identity.client_version().to_owned()
 }
 
 // -----------------------------------------------------------------------------
 // Gas
 // -----------------------------------------------------------------------------
 
-fn eth_gas_price(_: Params<'_>, _: &RpcContext, _: &Extensions) -> String {
-    hex_zero()
+/// Suggests a gas price that covers the base fee of the next block and the suggested priority fee.
+fn eth_gas_price(_: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<String, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::eth_gasPrice").entered();
+
+    // execute
+    let gas_price = next_base_fee(&ctx)? + suggest_priority_fee(&ctx)?;
+    Ok(hex_num(gas_price))
+}
+
+fn eth_fee_history(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::eth_feeHistory", block_count = field::Empty, newest_block = field::Empty).entered();
+
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let (params, block_count) = next_rpc_param::<JsonValue>(params.sequence())?;
+    let (params, newest_filter) = next_rpc_param::<BlockFilter>(params)?;
+    let (_, reward_percentiles) = next_rpc_param_or_default::<Option<Vec<f64>>>(params)?;
+
+    let block_count = match &block_count {
+        JsonValue::Number(number) => number.as_u64(),
+        JsonValue::String(hex) => u64::from_str_radix(hex.trim_start_matches("0x"), 16).ok(),
+        _ => None,
+    };
+    let Some(block_count) = block_count else {
+        return Err(StratusError::RpcParameterInvalid {
+            rust_type: "BlockCount",
+            decode_error: "block count must be a number or a hex string".to_owned(),
+        });
+    };
+    let block_count = block_count.min(FEE_HISTORY_MAX_BLOCKS as u64);
+
+    if let Some(ref percentiles) = reward_percentiles {
+        let in_range = percentiles.iter().all(|p| (0.0..=100.0).contains(p));
+        let ascending = percentiles.windows(2).all(|w| w[0] <= w[1]);
+        if not(in_range && ascending) {
+            return Err(StratusError::RpcParameterInvalid {
+                rust_type: "RewardPercentiles",
+                decode_error: "reward percentiles must be between 0 and 100 in ascending order".to_owned(),
+            });
+        }
+    }
+
+    // resolve newest block
+    let mined_number = ctx.storage.read_mined_block_number()?;
+    let newest = match newest_filter {
+        BlockFilter::Latest | BlockFilter::Pending => mined_number,
+        BlockFilter::Earliest => BlockNumber::ZERO,
+        BlockFilter::Number(number) if number <= mined_number => number,
+        BlockFilter::Hash(_) => match ctx.storage.read_block(&newest_filter)? {
+            Some(block) => block.number(),
+            None => return Err(StratusError::RpcBlockFilterInvalid { filter: newest_filter }),
+        },
+        BlockFilter::Number(_) => return Err(StratusError::RpcBlockFilterInvalid { filter: newest_filter }),
+    };
+    Span::with(|s| {
+        s.rec_str("block_count", &block_count);
+        s.rec_str("newest_block", &newest);
+    });
+
+    // execute
+    if block_count == 0 {
+        return Ok(json!({
+            "oldestBlock": hex_zero(),
+            "baseFeePerGas": [],
+            "gasUsedRatio": [],
+        }));
+    }
+    let fee_history = ctx.storage.read_fee_history(newest, block_count)?;
+    let oldest = fee_history.first().map(|block| block.number).unwrap_or(newest);
+
+    // base fees also include the next block, which keeps the base fee of the newest block unless the miner adjusts it between blocks
+    // (archive nodes do not mine, so their next base fee is the base fee of the newest block)
+    let next_base_fee = match (fee_history.last(), &ctx.miner) {
+        (Some(block), Some(miner)) if miner.base_fee().mode().is_eip1559() => miner.base_fee().next(Some(block)),
+        (Some(block), _) => block.base_fee_per_gas,
+        (None, _) => Wei::ZERO,
+    };
+    let mut base_fees = fee_history.iter().map(|block| hex_num(block.base_fee_per_gas)).collect_vec();
+    base_fees.push(hex_num(next_base_fee));
+    let gas_used_ratios = fee_history.iter().map(|block| block.gas_used_ratio()).collect_vec();
+
+    let mut response = json!({
+        "oldestBlock": hex_num(oldest.as_u64()),
+        "baseFeePerGas": base_fees,
+        "gasUsedRatio": gas_used_ratios,
+    });
+    if let Some(percentiles) = reward_percentiles {
+        let rewards = fee_history
+            .iter()
+            .map(|block| block.rewards(&percentiles).into_iter().map(hex_num).collect_vec())
+            .collect_vec();
+        response["reward"] = json!(rewards);
+    }
+    Ok(response)
+}
+
+fn eth_max_priority_fee_per_gas(_: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<String, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::eth_maxPriorityFeePerGas").entered();
+
+    // execute
+    Ok(hex_num(suggest_priority_fee(&ctx)?))
+}
+
+/// Base fee of the next block.
+///
+/// It is calculated by the miner when local blocks have a base fee, otherwise the next block keeps the base fee of the latest block.
+fn next_base_fee(ctx: &RpcContext) -> Result<Wei, StratusError> {
+    if let Some(miner) = ctx.miner.as_ref().filter(|miner| not(miner.base_fee().mode().is_disabled())) {
+        let (_, base_fee) = miner.pending_base_fee()?;
+        return Ok(base_fee);
+    }
+    let fee_history = ctx.storage.read_fee_history(ctx.storage.read_mined_block_number()?, 1)?;
+    Ok(fee_history.last().map(|block| block.base_fee_per_gas).unwrap_or_default())
+}
+
+/// Suggests a priority fee based on the median of the fees paid above the base fee in recent blocks.
+fn suggest_priority_fee(ctx: &RpcContext) -> Result<Wei, StratusError> {
+    const SAMPLE_BLOCKS: u64 = 20;
+    const SAMPLE_PERCENTILE: f64 = 60.0;
+
+    let newest = ctx.storage.read_mined_block_number()?;
+    let fee_history = ctx.storage.read_fee_history(newest, SAMPLE_BLOCKS)?;
+    let mut rewards = fee_history
+        .iter()
+        .filter(|block| not(block.priority_fees.is_empty()))
+        .flat_map(|block| block.rewards(&[SAMPLE_PERCENTILE]))
+        .collect_vec();
+    rewards.sort();
+
+    Ok(rewards.get(rewards.len() / 2).copied().unwrap_or_default())
+}
+
+/// Returns the base fee per blob gas of the next block.
+///
+/// Blob transactions are not accepted, so the excess blob gas is always zero and the fee is the EIP-4844 minimum, except in gas-free
+/// chains where all fees are zero.
+fn eth_blob_base_fee(_: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<String, StratusError> {
+    const MIN_BLOB_BASE_FEE: u64 = 1;
+
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::eth_blobBaseFee").entered();
+
+    // execute
+    let newest = ctx.storage.read_mined_block_number()?;
+    let fee_history = ctx.storage.read_fee_history(newest, 1)?;
+    let base_fee = fee_history.last().map(|block| block.base_fee_per_gas).unwrap_or_default();
+    if base_fee.is_zero() {
+        Ok(hex_zero())
+    } else {
+        Ok(hex_num(MIN_BLOB_BASE_FEE))
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -705,6 +1421,81 @@ fn eth_get_block_by_selector<const KIND: char>(params: Params<'_>, ctx: Arc<RpcC
     }
 }
 
+fn eth_get_block_receipts(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::eth_getBlockReceipts", filter = field::Empty, found = field::Empty).entered();
+
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let (_, filter) = next_rpc_param::<BlockFilter>(params.sequence())?;
+
+    // track
+    Span::with(|s| s.rec_str("filter", &filter));
+    tracing::info!(%filter, "reading block receipts");
+
+    // execute
+    let receipts = ctx.storage.read_block_receipts(&filter)?;
+    Span::with(|s| {
+        s.record("found", receipts.is_some());
+    });
+
+    match receipts {
+        Some(receipts) => {
+            tracing::info!(%filter, receipts = %receipts.len(), "block receipts found");
+            let receipts = receipts.into_iter().map(|tx| TransactionStage::Mined(tx).to_json_rpc_receipt()).collect_vec();
+            Ok(JsonValue::Array(receipts))
+        }
+        None => {
+            tracing::info!(%filter, "block receipts not found");
+            Ok(JsonValue::Null)
+        }
+    }
+}
+
+/// Returns the accounts and slots modified by a block with their values at the end of it.
+fn stratus_get_block_state_diff(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::stratus_getBlockStateDiff", filter = field::Empty, found = field::Empty).entered();
+
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let (_, filter) = next_rpc_param::<BlockFilter>(params.sequence())?;
+
+    // track
+    Span::with(|s| s.rec_str("filter", &filter));
+    tracing::info!(%filter, "reading block state diff");
+
+    // execute
+    let Some(block) = ctx.storage.read_block(&filter)? else {
+        tracing::info!(%filter, "block state diff not found");
+        Span::with(|s| {
+            s.record("found", false);
+        });
+        return Ok(JsonValue::Null);
+    };
+    let diff = ctx.storage.read_block_state_diff(block.number())?;
+    Span::with(|s| {
+        s.record("found", diff.is_some());
+    });
+
+    match diff {
+        Some(accounts) => {
+            tracing::info!(%filter, accounts = %accounts.len(), "block state diff found");
+            Ok(json!({
+                "blockNumber": block.number(),
+                "blockHash": block.hash(),
+                "accounts": accounts,
+            }))
+        }
+        None => {
+            tracing::info!(%filter, "block state diff not found");
+            Ok(JsonValue::Null)
+        }
+    }
+}
+
 fn eth_get_uncle_by_block_hash_and_index(_: Params<'_>, _: &RpcContext, _: &Extensions) -> Result<JsonValue, StratusError> {
     Ok(JsonValue::Null)
 }
@@ -775,24 +1566,100 @@ fn eth_get_transaction_receipt(params: Params<'_>, ctx: Arc<RpcContext>, ext: Ex
     }
 }
 
-fn eth_estimate_gas(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<String, StratusError> {
+fn stratus_get_receipt_proof(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
     // enter span
     let _middleware_enter = ext.enter_middleware_span();
-    let _method_enter = info_span!("rpc::eth_estimateGas", tx_from = field::Empty, tx_to = field::Empty).entered();
+    let _method_enter = info_span!("rpc::stratus_getReceiptProof", tx_hash = field::Empty, found = field::Empty).entered();
 
     // parse params
     reject_unknown_client(ext.rpc_client())?;
-    let (_, call) = next_rpc_param::<CallInput>(params.sequence())?;
+    let (_, tx_hash) = next_rpc_param::<Hash>(params.sequence())?;
 
     // track
+    Span::with(|s| s.rec_str("tx_hash", &tx_hash));
+    tracing::info!(%tx_hash, "reading receipt proof");
+
+    // execute
+    let proof = ctx.storage.read_receipt_proof(&tx_hash)?;
     Span::with(|s| {
-        s.rec_opt("tx_from", &call.from);
-        s.rec_opt("tx_to", &call.to);
+        s.record("found", proof.is_some());
     });
-    tracing::info!("executing eth_estimateGas");
 
-    // execute
-    match ctx.executor.execute_local_call(call, StoragePointInTime::Mined) {
+    match proof {
+        Some(proof) => Ok(to_json_value(proof)),
+        None => Ok(JsonValue::Null),
+    }
+}
+
+fn stratus_get_transactions_by_address(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
+    const MAX_BLOCK_RANGE: u64 = 100_000;
+
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!(
+        "rpc::stratus_getTransactionsByAddress",
+        address = field::Empty,
+        from = field::Empty,
+        to = field::Empty,
+        found = field::Empty
+    )
+    .entered();
+
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let (_, input) = next_rpc_param::<AddressTransactionsInput>(params.sequence())?;
+    let (from, to, pagination) = input.parse(&ctx.storage)?;
+    let blocks_in_range = from.count_to(&to);
+
+    // track
+    Span::with(|s| {
+        s.rec_str("address", &input.address);
+        s.rec_str("from", &from);
+        s.rec_str("to", &to);
+    });
+    tracing::info!(address = %input.address, %from, %to, ?pagination, "reading transactions by address");
+
+    // check range
+    if blocks_in_range > MAX_BLOCK_RANGE {
+        return Err(StratusError::RpcBlockRangeInvalid {
+            actual: blocks_in_range,
+            max: MAX_BLOCK_RANGE,
+        });
+    }
+
+    // execute
+    let txs = if_else!(
+        blocks_in_range == 0,
+        vec![],
+        ctx.storage.read_transactions_by_address(&input.address, from, to, pagination)?
+    );
+    Span::with(|s| {
+        s.record("found", txs.len());
+    });
+
+    let txs = txs.into_iter().map(|tx| TransactionStage::Mined(tx).to_json_rpc_transaction()).collect_vec();
+    Ok(JsonValue::Array(txs))
+}
+
+fn eth_estimate_gas(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<String, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::eth_estimateGas", tx_from = field::Empty, tx_to = field::Empty).entered();
+
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let (_, call) = next_rpc_param::<CallInput>(params.sequence())?;
+
+    // track
+    Span::with(|s| {
+        s.rec_opt("tx_from", &call.from);
+        s.rec_opt("tx_to", &call.to);
+    });
+    tracing::info!("executing eth_estimateGas");
+
+    // execute
+    let _execution_permit = ctx.limits.acquire_execution()?;
+    match ctx.executor.execute_local_call(call, StoragePointInTime::Mined) {
         // result is success
         Ok(result) if result.is_success() => {
             tracing::info!(tx_output = %result.output, "executed eth_estimateGas with success");
@@ -816,6 +1683,36 @@ fn eth_estimate_gas(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -
     }
 }
 
+/// Serves `eth_call` from the hot pool if its result is cached, otherwise from the blocking pool with the other heavy methods.
+async fn eth_call_routed(params: Params<'static>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<String, StratusError> {
+    let handler = call_error_metrics_wrapper(eth_call);
+    let handler_ctx = Arc::clone(&ctx);
+    if is_eth_call_cached(&params, &ctx) {
+        ctx.hot_pool.run("eth_call", move || handler(params, handler_ctx, ext)).await
+    } else {
+        RpcPool::run_blocking("eth_call", move || handler(params, handler_ctx, ext)).await
+    }
+}
+
+/// Checks if the result of an `eth_call` is cached without executing it.
+///
+/// Calls filtered by block hash are never considered cached because resolving the hash reads the block from storage.
+fn is_eth_call_cached(params: &Params<'_>, ctx: &RpcContext) -> bool {
+    let Ok((params, call)) = next_rpc_param::<CallInput>(params.sequence()) else {
+        return false;
+    };
+    let Ok((_, filter)) = next_rpc_param_or_default::<BlockFilter>(params) else {
+        return false;
+    };
+    if matches!(filter, BlockFilter::Hash(_)) {
+        return false;
+    }
+    match ctx.storage.translate_to_state_point_in_time(&filter) {
+        Ok(point_in_time) => ctx.executor.is_local_call_cached(&call, point_in_time),
+        Err(_) => false,
+    }
+}
+
 fn eth_call(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<String, StratusError> {
     // enter span
     let _middleware_enter = ext.enter_middleware_span();
@@ -835,24 +1732,423 @@ fn eth_call(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result
     tracing::info!(%filter, "executing eth_call");
 
     // execute
-    let point_in_time = ctx.storage.translate_to_point_in_time(&filter)?;
-    match ctx.executor.execute_local_call(call, point_in_time) {
-        // result is success
-        Ok(result) if result.is_success() => {
-            tracing::info!(tx_output = %result.output, "executed eth_call with success");
-            Ok(hex_data(result.output))
-        }
-
-        // result is failure
-        Ok(result) => {
-            tracing::warn!(tx_output = %result.output, "executed eth_call with failure");
-            Err(StratusError::TransactionReverted { output: result.output })
+    let point_in_time = ctx.storage.translate_to_state_point_in_time(&filter)?;
+    let _execution_permit = ctx.limits.acquire_execution()?;
+    match ctx.executor.execute_local_call(call, point_in_time) {
+        // result is success
+        Ok(result) if result.is_success() => {
+            tracing::info!(tx_output = %result.output, "executed eth_call with success");
+            Ok(hex_data(result.output))
+        }
+
+        // result is failure
+        Ok(result) => {
+            tracing::warn!(tx_output = %result.output, "executed eth_call with failure");
+            Err(StratusError::TransactionReverted { output: result.output })
+        }
+
+        // internal error
+        Err(e) => {
+            if e.is_internal() {
+                tracing::error!(reason = ?e, "failed to execute eth_call");
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Executes a call and returns the accounts and storage slots it accessed as an EIP-2930 access list, along with the gas it used.
+///
+/// Reverted calls still return the access list, with the revert reported in the `error` field.
+fn eth_create_access_list(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::eth_createAccessList", tx_from = field::Empty, tx_to = field::Empty, filter = field::Empty).entered();
+
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let (params, call) = next_rpc_param::<CallInput>(params.sequence())?;
+    let (_, filter) = next_rpc_param_or_default::<BlockFilter>(params)?;
+
+    // track
+    Span::with(|s| {
+        s.rec_opt("tx_from", &call.from);
+        s.rec_opt("tx_to", &call.to);
+        s.rec_str("filter", &filter);
+    });
+    tracing::info!(%filter, "executing eth_createAccessList");
+
+    // execute
+    let point_in_time = ctx.storage.translate_to_state_point_in_time(&filter)?;
+    let _execution_permit = ctx.limits.acquire_execution()?;
+    match ctx.executor.create_access_list(call, point_in_time) {
+        Ok((access_list, result)) => {
+            let mut response = json!({
+                "accessList": access_list,
+                "gasUsed": hex_num(result.gas.as_u64()),
+            });
+            if result.is_failure() {
+                tracing::warn!(tx_output = %result.output, "executed eth_createAccessList with failure");
+                response["error"] = json!("execution reverted");
+            }
+            Ok(response)
+        }
+        Err(e) => {
+            if e.is_internal() {
+                tracing::error!(reason = ?e, "failed to execute eth_createAccessList");
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Maximum number of calls allowed in a single `eth_callMany` or `debug_traceCallMany` bundle.
+const MAX_CALL_MANY_CALLS: usize = 100;
+
+fn eth_call_many(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::eth_callMany", calls = field::Empty, filter = field::Empty).entered();
+
+    // execute
+    let executions = execute_call_many(params, &ctx, &ext, None)?;
+
+    // each call reports its own output or revert reason, the bundle itself only fails on invalid input or internal errors
+    let results = executions
+        .into_iter()
+        .map(|execution| {
+            if execution.is_success() {
+                json!({ "value": hex_data(execution.output) })
+            } else {
+                let e = StratusError::TransactionReverted { output: execution.output };
+                json!({ "error": e.rpc_message(), "data": e.rpc_data() })
+            }
+        })
+        .collect_vec();
+    Ok(JsonValue::Array(results))
+}
+
+fn debug_trace_call_many(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::debug_traceCallMany", calls = field::Empty, filter = field::Empty).entered();
+
+    // execute
+    let operation = OPERATIONS.start(OperationKind::Trace, "debug_traceCallMany");
+    let executions = execute_call_many(params, &ctx, &ext, Some(&operation))?;
+    Ok(to_json_value(executions))
+}
+
+fn debug_trace_block_by_number(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::debug_traceBlockByNumber", filter = field::Empty, block_number = field::Empty).entered();
+    debug_trace_block(params, &ctx, &ext)
+}
+
+fn debug_trace_block_by_hash(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::debug_traceBlockByHash", filter = field::Empty, block_number = field::Empty).entered();
+    debug_trace_block(params, &ctx, &ext)
+}
+
+/// Executes again all transactions of a mined block shared by `debug_traceBlockByNumber` and `debug_traceBlockByHash`.
+///
+/// Tracer options other than the response byte budget and cursor are accepted for compatibility, but ignored.
+fn debug_trace_block(params: Params<'_>, ctx: &RpcContext, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let (params, filter) = next_rpc_param::<BlockFilter>(params.sequence())?;
+    let (_, options) = next_rpc_param_or_default::<TraceOptions>(params)?;
+
+    // track
+    Span::with(|s| s.rec_str("filter", &filter));
+    tracing::info!(%filter, ?options, "tracing block");
+
+    // read block
+    if filter == BlockFilter::Pending {
+        return Err(StratusError::RpcBlockFilterInvalid { filter });
+    }
+    let Some(block) = ctx.storage.read_block(&filter)? else {
+        return Err(StratusError::RpcBlockFilterInvalid { filter });
+    };
+    Span::with(|s| s.rec_str("block_number", &block.number()));
+
+    // execute
+    let operation = OPERATIONS.start(OperationKind::Trace, format!("debug_traceBlock {}", block.number()));
+    match ctx.executor.trace_block(&block, Some(&operation)) {
+        Ok(traces) => {
+            let budget = options.budget(ctx.rpc_server.rpc_trace_byte_budget);
+            let page = TracePage::paginate(traces, options.cursor.unwrap_or_default(), budget);
+            Ok(page.to_json_block_traces())
+        }
+        Err(e) => {
+            if e.is_internal() {
+                tracing::error!(reason = ?e, "failed to trace block");
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Executes again the transactions of a mined block up to the traced transaction.
+///
+/// Tracer options other than the response byte budget and cursor are accepted for compatibility, but ignored.
+fn debug_trace_transaction(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::debug_traceTransaction", tx_hash = field::Empty, block_number = field::Empty).entered();
+
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let (params, tx_hash) = next_rpc_param::<Hash>(params.sequence())?;
+    let (_, options) = next_rpc_param_or_default::<TraceOptions>(params)?;
+
+    // track
+    Span::with(|s| s.rec_str("tx_hash", &tx_hash));
+    tracing::info!(%tx_hash, ?options, "tracing transaction");
+
+    // read block
+    let Some(TransactionStage::Mined(tx)) = ctx.storage.read_transaction(&tx_hash)? else {
+        return Ok(JsonValue::Null);
+    };
+    let Some(mut block) = ctx.storage.read_block(&BlockFilter::Number(tx.block_number))? else {
+        return Ok(JsonValue::Null);
+    };
+    block.transactions.truncate(tx.transaction_index.0 as usize + 1);
+    Span::with(|s| s.rec_str("block_number", &block.number()));
+
+    // execute
+    let operation = OPERATIONS.start(OperationKind::Trace, format!("debug_traceTransaction {}", tx_hash));
+    match ctx.executor.trace_block(&block, Some(&operation)) {
+        Ok(mut traces) => {
+            let traces = traces.pop().into_iter().filter(|(hash, _)| *hash == tx_hash).collect_vec();
+            let budget = options.budget(ctx.rpc_server.rpc_trace_byte_budget);
+            let page = TracePage::paginate(traces, options.cursor.unwrap_or_default(), budget);
+            Ok(page.to_json_transaction_trace())
+        }
+        Err(e) => {
+            if e.is_internal() {
+                tracing::error!(reason = ?e, "failed to trace transaction");
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Executes a mined transaction again and returns the differences between the replay and the stored execution.
+fn stratus_replay_transaction(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::stratus_replayTransaction", tx_hash = field::Empty).entered();
+
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let (_, tx_hash) = next_rpc_param::<Hash>(params.sequence())?;
+
+    // track
+    Span::with(|s| s.rec_str("tx_hash", &tx_hash));
+
+    // execute
+    let operation = OPERATIONS.start(OperationKind::Trace, format!("stratus_replayTransaction {}", tx_hash));
+    match replay_transaction(&ctx.storage, ctx.executor.as_ref(), tx_hash, Some(&operation)) {
+        Ok(replay) => Ok(to_json_value(replay)),
+        Err(e) => {
+            if e.is_internal() {
+                tracing::error!(reason = ?e, "failed to replay transaction");
+            }
+            Err(e)
+        }
+    }
+}
+
+fn trace_block(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::trace_block", filter = field::Empty, block_number = field::Empty).entered();
+
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let (_, filter) = next_rpc_param::<BlockFilter>(params.sequence())?;
+
+    // track
+    Span::with(|s| s.rec_str("filter", &filter));
+    tracing::info!(%filter, "tracing calls of block");
+
+    // read block
+    if filter == BlockFilter::Pending {
+        return Err(StratusError::RpcBlockFilterInvalid { filter });
+    }
+    let Some(block) = ctx.storage.read_block(&filter)? else {
+        return Ok(JsonValue::Null);
+    };
+    Span::with(|s| s.rec_str("block_number", &block.number()));
+
+    // execute
+    let operation = OPERATIONS.start(OperationKind::Trace, format!("trace_block {}", block.number()));
+    match ctx.executor.trace_block_calls(&block, Some(&operation)) {
+        Ok(traces) => Ok(JsonValue::Array(to_json_parity_traces(&block, traces).collect())),
+        Err(e) => {
+            if e.is_internal() {
+                tracing::error!(reason = ?e, "failed to trace calls of block");
+            }
+            Err(e)
+        }
+    }
+}
+
+fn trace_transaction(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::trace_transaction", tx_hash = field::Empty, block_number = field::Empty).entered();
+
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let (_, tx_hash) = next_rpc_param::<Hash>(params.sequence())?;
+
+    // track
+    Span::with(|s| s.rec_str("tx_hash", &tx_hash));
+    tracing::info!(%tx_hash, "tracing calls of transaction");
+
+    // read block
+    let Some(TransactionStage::Mined(tx)) = ctx.storage.read_transaction(&tx_hash)? else {
+        return Ok(JsonValue::Null);
+    };
+    let Some(mut block) = ctx.storage.read_block(&BlockFilter::Number(tx.block_number))? else {
+        return Ok(JsonValue::Null);
+    };
+    block.transactions.truncate(tx.transaction_index.0 as usize + 1);
+    Span::with(|s| s.rec_str("block_number", &block.number()));
+
+    // execute
+    let operation = OPERATIONS.start(OperationKind::Trace, format!("trace_transaction {}", tx_hash));
+    match ctx.executor.trace_block_calls(&block, Some(&operation)) {
+        Ok(mut traces) => {
+            let tx_traces = traces
+                .pop()
+                .filter(|(hash, _)| *hash == tx_hash)
+                .map(|(_, tx_traces)| tx_traces)
+                .unwrap_or_default();
+            Ok(JsonValue::Array(tx_traces.iter().map(|trace| trace.to_json_parity_trace(&tx)).collect()))
+        }
+        Err(e) => {
+            if e.is_internal() {
+                tracing::error!(reason = ?e, "failed to trace calls of transaction");
+            }
+            Err(e)
+        }
+    }
+}
+
+fn trace_filter(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
+    const MAX_BLOCK_RANGE: u64 = 100;
+
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::trace_filter", from = field::Empty, to = field::Empty, found = field::Empty).entered();
+
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let (_, filter) = next_rpc_param_or_default::<TraceFilterInput>(params.sequence())?;
+    let (from, to) = filter.parse(&ctx.storage)?;
+    let blocks_in_range = from.count_to(&to);
+
+    // track
+    Span::with(|s| {
+        s.rec_str("from", &from);
+        s.rec_str("to", &to);
+    });
+    tracing::info!(%from, %to, ?filter, "filtering calls");
+
+    // check range
+    if blocks_in_range > MAX_BLOCK_RANGE {
+        return Err(StratusError::RpcBlockRangeInvalid {
+            actual: blocks_in_range,
+            max: MAX_BLOCK_RANGE,
+        });
+    }
+
+    // execute block by block, stopping when enough traces were found
+    let operation = OPERATIONS.start(OperationKind::Trace, format!("trace_filter {}..={}", from, to));
+    let mut skip = filter.after.unwrap_or_default();
+    let count = filter.count.unwrap_or(usize::MAX);
+    let mut found = Vec::new();
+    for number in from.as_u64()..=to.as_u64() {
+        if found.len() >= count {
+            break;
+        }
+        let Some(block) = ctx.storage.read_block(&BlockFilter::Number(number.into()))? else {
+            continue;
+        };
+        let traces = match ctx.executor.trace_block_calls(&block, Some(&operation)) {
+            Ok(traces) => traces,
+            Err(e) => {
+                if e.is_internal() {
+                    tracing::error!(reason = ?e, "failed to filter calls");
+                }
+                return Err(e);
+            }
+        };
+        for (tx, (_, tx_traces)) in block.transactions.iter().zip(traces) {
+            for trace in tx_traces.iter().filter(|trace| filter.matches(trace)) {
+                if skip > 0 {
+                    skip -= 1;
+                } else if found.len() < count {
+                    found.push(trace.to_json_parity_trace(tx));
+                }
+            }
+        }
+    }
+    Span::with(|s| {
+        s.record("found", found.len());
+    });
+
+    Ok(JsonValue::Array(found))
+}
+
+/// Serializes the calls traced from the transactions of a block in the Parity trace format.
+fn to_json_parity_traces(block: &Block, traces: Vec<(Hash, Vec<CallTrace>)>) -> impl Iterator<Item = JsonValue> + '_ {
+    block
+        .transactions
+        .iter()
+        .zip(traces)
+        .flat_map(|(tx, (_, tx_traces))| tx_traces.into_iter().map(move |trace| trace.to_json_parity_trace(tx)))
+}
+
+/// Parses and executes a bundle of calls shared by `eth_callMany` and `debug_traceCallMany`.
+fn execute_call_many(params: Params<'_>, ctx: &RpcContext, ext: &Extensions, operation: Option<&Operation>) -> Result<Vec<EvmExecution>, StratusError> {
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let (params, calls) = next_rpc_param::<Vec<CallInput>>(params.sequence())?;
+    let (_, filter) = next_rpc_param_or_default::<BlockFilter>(params)?;
+
+    // track
+    Span::with(|s| {
+        s.rec_str("calls", &calls.len());
+        s.rec_str("filter", &filter);
+    });
+    tracing::info!(calls = calls.len(), %filter, "executing call bundle");
+
+    // validate
+    if calls.len() > MAX_CALL_MANY_CALLS {
+        return Err(StratusError::RpcCallManyLimit {
+            actual: calls.len(),
+            max: MAX_CALL_MANY_CALLS,
+        });
+    }
+
+    // execute
+    let point_in_time = ctx.storage.translate_to_state_point_in_time(&filter)?;
+    let _execution_permit = ctx.limits.acquire_execution()?;
+    match ctx.executor.execute_local_call_many(calls, point_in_time, operation) {
+        Ok(executions) => {
+            tracing::info!(calls = executions.len(), "executed call bundle");
+            Ok(executions)
         }
-
-        // internal error
         Err(e) => {
             if e.is_internal() {
-                tracing::error!(reason = ?e, "failed to execute eth_call");
+                tracing::error!(reason = ?e, "failed to execute call bundle");
             }
             Err(e)
         }
@@ -872,8 +2168,11 @@ fn eth_send_raw_transaction(params: Params<'_>, ctx: Arc<RpcContext>, ext: Exten
     .entered();
 
     // parse params
+    // second param is the client identification used when forwarding to leader, third param are the optional transaction constraints
     reject_unknown_client(ext.rpc_client())?;
-    let (_, tx_data) = next_rpc_param::<Bytes>(params.sequence())?;
+    let (params, tx_data) = next_rpc_param::<Bytes>(params.sequence())?;
+    let (params, _) = next_rpc_param_or_default::<Option<JsonValue>>(params)?;
+    let (_, tx_options) = next_rpc_param_or_default::<TransactionOptions>(params)?;
     let tx = parse_rpc_rlp::<TransactionInput>(&tx_data)?;
     let tx_hash = tx.hash;
 
@@ -886,6 +2185,10 @@ fn eth_send_raw_transaction(params: Params<'_>, ctx: Arc<RpcContext>, ext: Exten
     });
 
     // check feature
+    if GlobalState::is_archive() {
+        tracing::warn!(%tx_hash, "failed to execute eth_sendRawTransaction because node is a read-only archive");
+        return Err(StratusError::StratusArchiveReadOnly);
+    }
     if not(GlobalState::is_transactions_enabled()) {
         tracing::warn!(%tx_hash, "failed to execute eth_sendRawTransaction because transactions are disabled");
         return Err(StratusError::RpcTransactionDisabled);
@@ -893,15 +2196,18 @@ fn eth_send_raw_transaction(params: Params<'_>, ctx: Arc<RpcContext>, ext: Exten
 
     // execute locally or forward to leader
     match GlobalState::get_node_mode() {
-        NodeMode::Leader => match ctx.executor.execute_local_transaction(tx) {
-            Ok(_) => Ok(hex_data(tx_hash)),
-            Err(e) => {
-                if e.is_internal() {
-                    tracing::error!(reason = ?e, "failed to execute eth_sendRawTransaction");
+        NodeMode::Leader => {
+            let _execution_permit = ctx.limits.acquire_execution()?;
+            match ctx.executor.execute_local_transaction(tx, tx_options) {
+                Ok(_) => Ok(hex_data(tx_hash)),
+                Err(e) => {
+                    if e.is_internal() {
+                        tracing::error!(reason = ?e, "failed to execute eth_sendRawTransaction");
+                    }
+                    Err(e)
                 }
-                Err(e)
             }
-        },
+        }
         NodeMode::Follower => {
             let consensus_lock = ctx.consensus.read().map_err(|_| {
                 tracing::error!("consensus read lock was poisoned");
@@ -909,9 +2215,16 @@ fn eth_send_raw_transaction(params: Params<'_>, ctx: Arc<RpcContext>, ext: Exten
                 StratusError::ConsensusLockFailed
             })?;
             match consensus_lock.as_ref() {
-                Some(consensus) => match Handle::current().block_on(consensus.forward_to_leader(tx_hash, tx_data, ext.rpc_client())) {
-                    Ok(hash) => Ok(hex_data(hash)),
-                    Err(e) => Err(e),
+                // replicas do not forward transactions, clients must send them to the leader
+                Some(consensus) => match consensus.transactions_redirect() {
+                    Some(leader) => {
+                        tracing::warn!(%tx_hash, %leader, "failed to execute eth_sendRawTransaction because node is a read-only replica");
+                        Err(StratusError::StratusReplicaReadOnly { leader: leader.to_owned() })
+                    }
+                    None => match Handle::current().block_on(consensus.forward_to_leader(tx_hash, tx_data, tx_options, ext.rpc_client())) {
+                        Ok(hash) => Ok(hex_data(hash)),
+                        Err(e) => Err(e),
+                    },
                 },
                 None => {
                     tracing::error!("unable to forward transaction because consensus is temporarily unavailable for follower node");
@@ -919,6 +2232,80 @@ fn eth_send_raw_transaction(params: Params<'_>, ctx: Arc<RpcContext>, ext: Exten
                 }
             }
         }
+        NodeMode::Archive => Err(StratusError::StratusArchiveReadOnly),
+    }
+}
+
+/// Executes an unsigned transaction on behalf of an impersonated account.
+#[cfg(feature = "dev")]
+fn eth_send_transaction(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<String, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!(
+        "rpc::eth_sendTransaction",
+        tx_hash = field::Empty,
+        tx_from = field::Empty,
+        tx_to = field::Empty,
+        tx_nonce = field::Empty
+    )
+    .entered();
+
+    // parse params
+    let client = ext.rpc_client();
+    reject_unknown_client(client.clone())?;
+    let client = client.to_string();
+    let (_, request) = next_rpc_param::<TransactionRequest>(params.sequence())?;
+    let Some(from) = request.from.map(Address::from) else {
+        return Err(StratusError::RpcParameterMissing { rust_type: "Address" });
+    };
+
+    // only accounts impersonated by the same client can send transactions without signature
+    if not(ctx.executor.impersonation().is_impersonated_by(&from, &client)) {
+        tracing::warn!(%from, %client, "failed to execute eth_sendTransaction because account is not impersonated by the client");
+        return Err(StratusError::TransactionSignatureMissing { from });
+    }
+
+    // fill missing nonce with the current account nonce
+    let nonce = match request.nonce {
+        Some(nonce) => Nonce::try_from(nonce).map_err(|e| StratusError::RpcParameterInvalid {
+            rust_type: "Nonce",
+            decode_error: e.to_string(),
+        })?,
+        None => ctx.storage.read_account(&from, &StoragePointInTime::Pending)?.nonce,
+    };
+    let tx = TransactionInput::new_unsigned(request, nonce, ctx.chain_id).map_err(|e| StratusError::RpcTransactionInvalid { decode_error: e.to_string() })?;
+    let tx_hash = tx.hash;
+
+    // track
+    Span::with(|s| {
+        s.rec_str("tx_hash", &tx_hash);
+        s.rec_str("tx_from", &tx.signer);
+        s.rec_opt("tx_to", &tx.to);
+        s.rec_str("tx_nonce", &tx.nonce);
+    });
+
+    // check feature
+    if not(GlobalState::is_transactions_enabled()) {
+        tracing::warn!(%tx_hash, "failed to execute eth_sendTransaction because transactions are disabled");
+        return Err(StratusError::RpcTransactionDisabled);
+    }
+    if GlobalState::get_node_mode() != NodeMode::Leader {
+        tracing::warn!(%tx_hash, "failed to execute eth_sendTransaction because impersonated transactions cannot be forwarded to leader");
+        return Err(StratusError::StratusNotLeader);
+    }
+
+    // execute
+    let _execution_permit = ctx.limits.acquire_execution()?;
+    let result = ctx.executor.execute_local_transaction(tx, TransactionOptions::default());
+    ctx.executor.impersonation().record_transaction(from, &client, tx_hash, result.is_ok());
+    match result {
+        Ok(_) => Ok(hex_data(tx_hash)),
+        Err(e) => {
+            if e.is_internal() {
+                tracing::error!(reason = ?e, "failed to execute eth_sendTransaction");
+            }
+            Err(e)
+        }
     }
 }
 
@@ -927,8 +2314,6 @@ fn eth_send_raw_transaction(params: Params<'_>, ctx: Arc<RpcContext>, ext: Exten
 // -----------------------------------------------------------------------------
 
 fn eth_get_logs(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
-    const MAX_BLOCK_RANGE: u64 = 5_000;
-
     // enter span
     let _middleware_enter = ext.enter_middleware_span();
     let _method_enter = info_span!(
@@ -946,33 +2331,245 @@ fn eth_get_logs(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Re
     let mut filter = filter_input.parse(&ctx.storage)?;
 
     // for this operation, the filter always need the end block specified to calculate the difference
-    if filter.to_block.is_none() {
-        filter.to_block = Some(ctx.storage.read_mined_block_number()?);
-    }
-    let blocks_in_range = filter.from_block.count_to(&filter.to_block.unwrap());
+    let to_block = match filter.to_block {
+        Some(to_block) => to_block,
+        None => ctx.storage.read_mined_block_number()?,
+    };
+    filter.to_block = Some(to_block);
+    let blocks_in_range = filter.from_block.count_to(&to_block);
 
     // track
     Span::with(|s| {
         s.rec_str("filter", &to_json_string(&filter));
         s.rec_str("filter_from", &filter.from_block);
-        s.rec_str("filter_to", &filter.to_block.unwrap());
+        s.rec_str("filter_to", &to_block);
         s.rec_str("filter_range", &blocks_in_range);
     });
     tracing::info!(?filter, "reading logs");
 
     // check range
-    if blocks_in_range > MAX_BLOCK_RANGE {
+    let max_block_range = ctx.rpc_server.rpc_logs_max_block_range;
+    if blocks_in_range > max_block_range {
         return Err(StratusError::RpcBlockRangeInvalid {
             actual: blocks_in_range,
-            max: MAX_BLOCK_RANGE,
+            max: max_block_range,
+        });
+    }
+
+    // execute
+    let operation = OPERATIONS.start(OperationKind::LogsScan, format!("eth_getLogs {}..={}", filter.from_block, to_block));
+    let rollback_version = ctx.storage.rollback_version();
+    let max_results = ctx.rpc_server.logs_max_results();
+    let logs = scan_logs(&ctx, &filter, to_block, &operation, max_results)?;
+    if logs.len() > max_results {
+        return Err(StratusError::RpcLogsLimit { max: max_results });
+    }
+
+    // chunks are validated individually, so also check the storage was not rolled back between them
+    ctx.storage.check_rollback_since(rollback_version, filter.from_block, to_block)?;
+    Ok(JsonValue::Array(logs.into_iter().map(|x| x.to_json_rpc_log()).collect()))
+}
+
+/// Reads logs in pages that are limited by the max block range and max results instead of failing when they are exceeded.
+fn stratus_get_logs_paginated(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!(
+        "rpc::stratus_getLogsPaginated",
+        filter = field::Empty,
+        filter_from = field::Empty,
+        filter_to = field::Empty
+    )
+    .entered();
+
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let (params, filter_input) = next_rpc_param_or_default::<LogFilterInput>(params.sequence())?;
+    let (_, options) = next_rpc_param_or_default::<LogsPageOptions>(params)?;
+    let mut filter = filter_input.parse(&ctx.storage)?;
+
+    // the page starts at the cursor and scans at most the max block range
+    let to_block = match filter.to_block {
+        Some(to_block) => to_block,
+        None => ctx.storage.read_mined_block_number()?,
+    };
+    if let Some(ref cursor) = options.cursor {
+        filter.from_block = filter.from_block.max(cursor.block_number);
+    }
+    let page_to = min(filter.from_block + (ctx.rpc_server.rpc_logs_max_block_range.max(1) - 1) as usize, to_block);
+    filter.to_block = Some(page_to);
+
+    // track
+    Span::with(|s| {
+        s.rec_str("filter", &to_json_string(&filter));
+        s.rec_str("filter_from", &filter.from_block);
+        s.rec_str("filter_to", &page_to);
+    });
+    tracing::info!(?filter, ?options, "reading logs page");
+
+    // execute
+    let operation = OPERATIONS.start(OperationKind::LogsScan, format!("stratus_getLogsPaginated {}..={}", filter.from_block, page_to));
+    let rollback_version = ctx.storage.rollback_version();
+    let limit = options.limit(ctx.rpc_server.logs_max_results());
+
+    // logs of the cursor block already sent are discarded by the page, so they do not count to the scan limit
+    let skipped = options.cursor.map(|cursor| cursor.log_index.0 as usize).unwrap_or_default();
+    let logs = scan_logs(&ctx, &filter, page_to, &operation, limit.saturating_add(skipped))?;
+    ctx.storage.check_rollback_since(rollback_version, filter.from_block, page_to)?;
+
+    Ok(LogsPage::paginate(logs, options.cursor, limit, page_to, to_block).to_json())
+}
+
+/// Reads the logs matching the filter up to `to_block` in chunks, so the scan can be cancelled between them.
+///
+/// Stops after the chunk where more than `max_results` logs are found.
+fn scan_logs(ctx: &RpcContext, filter: &LogFilter, to_block: BlockNumber, operation: &Operation, max_results: usize) -> Result<Vec<LogMined>, StratusError> {
+    const LOGS_SCAN_CHUNK: usize = 500;
+
+    let mut logs = Vec::new();
+    let mut chunk_from = filter.from_block;
+    while chunk_from <= to_block {
+        operation.check()?;
+
+        let chunk_to = min(chunk_from + (LOGS_SCAN_CHUNK - 1), to_block);
+        let chunk_filter = LogFilter {
+            from_block: chunk_from,
+            to_block: Some(chunk_to),
+            ..filter.clone()
+        };
+        logs.extend(ctx.storage.read_logs(&chunk_filter)?);
+        if logs.len() > max_results {
+            break;
+        }
+        chunk_from = chunk_to.next_block_number();
+    }
+    Ok(logs)
+}
+
+// -----------------------------------------------------------------------------
+// Filters
+// -----------------------------------------------------------------------------
+
+fn eth_new_filter(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<FilterId, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::eth_newFilter", filter = field::Empty, filter_id = field::Empty).entered();
+
+    // parse params
+    let client = ext.rpc_client();
+    reject_unknown_client(client.clone())?;
+    let (_, filter_input) = next_rpc_param_or_default::<LogFilterInput>(params.sequence())?;
+    let filter = filter_input.parse(&ctx.storage)?;
+    Span::with(|s| s.rec_str("filter", &to_json_string(&filter)));
+
+    // execute
+    let id = ctx.filters.install(client, FilterKind::Logs(filter))?;
+    Span::with(|s| s.rec_str("filter_id", &id));
+    Ok(id)
+}
+
+fn eth_new_block_filter(_: Params<'_>, ctx: &RpcContext, ext: &Extensions) -> Result<FilterId, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::eth_newBlockFilter", filter_id = field::Empty).entered();
+
+    // parse params
+    let client = ext.rpc_client();
+    reject_unknown_client(client.clone())?;
+
+    // execute
+    let id = ctx.filters.install(client, FilterKind::NewBlocks)?;
+    Span::with(|s| s.rec_str("filter_id", &id));
+    Ok(id)
+}
+
+fn eth_new_pending_transaction_filter(_: Params<'_>, ctx: &RpcContext, ext: &Extensions) -> Result<FilterId, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::eth_newPendingTransactionFilter", filter_id = field::Empty).entered();
+
+    // parse params
+    let client = ext.rpc_client();
+    reject_unknown_client(client.clone())?;
+
+    // execute
+    let id = ctx.filters.install(client, FilterKind::NewPendingTransactions)?;
+    Span::with(|s| s.rec_str("filter_id", &id));
+    Ok(id)
+}
+
+fn eth_get_filter_changes(params: Params<'_>, ctx: &RpcContext, ext: &Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::eth_getFilterChanges", filter_id = field::Empty).entered();
+
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let (_, id) = next_rpc_param::<FilterId>(params.sequence())?;
+    Span::with(|s| s.rec_str("filter_id", &id));
+
+    // execute
+    ctx.filters.changes(id)
+}
+
+fn eth_get_filter_logs(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::eth_getFilterLogs", filter_id = field::Empty, filter = field::Empty).entered();
+
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let (_, id) = next_rpc_param::<FilterId>(params.sequence())?;
+    let mut filter = ctx.filters.log_filter(id)?;
+
+    // same as eth_getLogs, the end block must be specified to calculate the difference
+    let to_block = match filter.to_block {
+        Some(to_block) => to_block,
+        None => ctx.storage.read_mined_block_number()?,
+    };
+    filter.to_block = Some(to_block);
+    let blocks_in_range = filter.from_block.count_to(&to_block);
+
+    // track
+    Span::with(|s| {
+        s.rec_str("filter_id", &id);
+        s.rec_str("filter", &to_json_string(&filter));
+    });
+    tracing::info!(%id, ?filter, "reading filter logs");
+
+    // check range
+    let max_block_range = ctx.rpc_server.rpc_logs_max_block_range;
+    if blocks_in_range > max_block_range {
+        return Err(StratusError::RpcBlockRangeInvalid {
+            actual: blocks_in_range,
+            max: max_block_range,
         });
     }
 
     // execute
-    let logs = ctx.storage.read_logs(&filter)?;
+    let operation = OPERATIONS.start(OperationKind::LogsScan, format!("eth_getLogs {}..={}", filter.from_block, to_block));
+    let max_results = ctx.rpc_server.logs_max_results();
+    let logs = scan_logs(&ctx, &filter, to_block, &operation, max_results)?;
+    if logs.len() > max_results {
+        return Err(StratusError::RpcLogsLimit { max: max_results });
+    }
     Ok(JsonValue::Array(logs.into_iter().map(|x| x.to_json_rpc_log()).collect()))
 }
 
+fn eth_uninstall_filter(params: Params<'_>, ctx: &RpcContext, ext: &Extensions) -> Result<bool, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::eth_uninstallFilter", filter_id = field::Empty).entered();
+
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let (_, id) = next_rpc_param::<FilterId>(params.sequence())?;
+    Span::with(|s| s.rec_str("filter_id", &id));
+
+    // execute
+    Ok(ctx.filters.uninstall(id))
+}
+
 // -----------------------------------------------------------------------------
 // Account
 // -----------------------------------------------------------------------------
@@ -998,7 +2595,7 @@ fn eth_get_transaction_count(params: Params<'_>, ctx: Arc<RpcContext>, ext: Exte
     });
     tracing::info!(%address, %filter, "reading account nonce");
 
-    let point_in_time = ctx.storage.translate_to_point_in_time(&filter)?;
+    let point_in_time = ctx.storage.translate_to_state_point_in_time(&filter)?;
     let account = ctx.storage.read_account(&address, &point_in_time)?;
     Ok(hex_num(account.nonce))
 }
@@ -1021,7 +2618,7 @@ fn eth_get_balance(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) ->
     tracing::info!(%address, %filter, "reading account native balance");
 
     // execute
-    let point_in_time = ctx.storage.translate_to_point_in_time(&filter)?;
+    let point_in_time = ctx.storage.translate_to_state_point_in_time(&filter)?;
     let account = ctx.storage.read_account(&address, &point_in_time)?;
     Ok(hex_num(account.balance))
 }
@@ -1043,12 +2640,57 @@ fn eth_get_code(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Re
     });
 
     // execute
-    let point_in_time = ctx.storage.translate_to_point_in_time(&filter)?;
+    let point_in_time = ctx.storage.translate_to_state_point_in_time(&filter)?;
     let account = ctx.storage.read_account(&address, &point_in_time)?;
 
     Ok(account.bytecode.map(hex_data).unwrap_or_else(hex_null))
 }
 
+/// Reads a mined bytecode by its hash, as returned in the `codeHash` of account proofs.
+fn stratus_get_code_by_hash(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::stratus_getCodeByHash", code_hash = field::Empty, found = field::Empty).entered();
+
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let (_, code_hash) = next_rpc_param::<CodeHash>(params.sequence())?;
+
+    // track
+    Span::with(|s| s.rec_str("code_hash", &hex_data(code_hash)));
+
+    // execute
+    let bytecode = ctx.storage.read_code(&code_hash)?;
+    Span::with(|s| {
+        s.record("found", bytecode.is_some());
+    });
+
+    Ok(bytecode.map(|bytecode| JsonValue::String(hex_data(bytecode))).unwrap_or(JsonValue::Null))
+}
+
+fn eth_get_proof(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions) -> Result<JsonValue, StratusError> {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let _method_enter = info_span!("rpc::eth_getProof", address = field::Empty, filter = field::Empty).entered();
+
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let (params, address) = next_rpc_param::<Address>(params.sequence())?;
+    let (params, indexes) = next_rpc_param::<Vec<SlotIndex>>(params)?;
+    let (_, filter) = next_rpc_param_or_default::<BlockFilter>(params)?;
+
+    // track
+    Span::with(|s| {
+        s.rec_str("address", &address);
+        s.rec_str("filter", &filter);
+    });
+    tracing::info!(%address, %filter, slots = %indexes.len(), "reading account proof");
+
+    // execute
+    let proof = ctx.storage.read_account_proof(&address, &indexes, &filter)?;
+    Ok(to_json_value(proof))
+}
+
 // -----------------------------------------------------------------------------
 // Subscriptions
 // -----------------------------------------------------------------------------
@@ -1094,6 +2736,11 @@ async fn eth_subscribe(params: Params<'_>, pending: PendingSubscriptionSink, ctx
             ctx.subs.add_new_heads(client, pending.accept().await?).instrument(method_span).await;
         }
 
+        "newHeadsCompact" => {
+            drop(method_enter);
+            ctx.subs.add_new_heads_compact(client, pending.accept().await?).instrument(method_span).await;
+        }
+
         "logs" => {
             let (_, filter) = next_rpc_param_or_default::<LogFilterInput>(params)?;
             let filter = filter.parse(&ctx.storage)?;
@@ -1113,6 +2760,86 @@ async fn eth_subscribe(params: Params<'_>, pending: PendingSubscriptionSink, ctx
     Ok(())
 }
 
+async fn stratus_subscribe_account_activity(
+    params: Params<'_>,
+    pending: PendingSubscriptionSink,
+    ctx: Arc<RpcContext>,
+    ext: Extensions,
+) -> impl IntoSubscriptionCloseResponse {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let method_span = info_span!("rpc::stratus_subscribeAccountActivity", address = field::Empty);
+    let method_enter = method_span.enter();
+
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let client = ext.rpc_client();
+    let address = match next_rpc_param::<Address>(params.sequence()) {
+        Ok((_, address)) => address,
+        Err(e) => {
+            drop(method_enter);
+            pending.reject(e).instrument(method_span).await;
+            return Ok(());
+        }
+    };
+
+    // check subscription limits
+    if let Err(e) = ctx.subs.check_client_subscriptions(ctx.rpc_server.rpc_max_subscriptions, &client).await {
+        drop(method_enter);
+        pending.reject(e).instrument(method_span).await;
+        return Ok(());
+    }
+
+    // track
+    Span::with(|s| s.rec_str("address", &address));
+    tracing::info!(%address, "subscribing to account activity");
+
+    // execute
+    drop(method_enter);
+    ctx.subs
+        .add_account_activity(client, address, pending.accept().await?)
+        .instrument(method_span)
+        .await;
+    Ok(())
+}
+
+/// Streams mined blocks starting from the specified block to a follower that replicates them.
+async fn stratus_subscribe_replication(
+    params: Params<'_>,
+    pending: PendingSubscriptionSink,
+    ctx: Arc<RpcContext>,
+    ext: Extensions,
+) -> impl IntoSubscriptionCloseResponse {
+    // enter span
+    let _middleware_enter = ext.enter_middleware_span();
+    let method_span = info_span!("rpc::stratus_subscribeReplication", from = field::Empty);
+    let method_enter = method_span.enter();
+
+    // parse params
+    reject_unknown_client(ext.rpc_client())?;
+    let from = match next_rpc_param::<BlockNumber>(params.sequence()) {
+        Ok((_, from)) => from,
+        Err(e) => {
+            drop(method_enter);
+            pending.reject(e).instrument(method_span).await;
+            return Ok(());
+        }
+    };
+
+    // track
+    Span::with(|s| s.rec_str("from", &from));
+    tracing::info!(%from, client = %ext.rpc_client(), "subscribing to replication");
+
+    // execute
+    drop(method_enter);
+    let sink = pending.accept().await?;
+    spawn_named(
+        "rpc::replication",
+        stream_replicated_blocks(Arc::clone(&ctx.storage), from, sink).instrument(method_span),
+    );
+    Ok(())
+}
+
 // -----------------------------------------------------------------------------
 // Storage
 // -----------------------------------------------------------------------------
@@ -1134,7 +2861,7 @@ fn eth_get_storage_at(params: Params<'_>, ctx: Arc<RpcContext>, ext: Extensions)
     });
 
     // execute
-    let point_in_time = ctx.storage.translate_to_point_in_time(&block_filter)?;
+    let point_in_time = ctx.storage.translate_to_state_point_in_time(&block_filter)?;
     let slot = ctx.storage.read_slot(&address, &index, &point_in_time)?;
 
     // It must be padded, even if it is zero.
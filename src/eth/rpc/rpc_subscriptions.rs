@@ -16,13 +16,17 @@ use tokio::task::JoinHandle;
 use tokio::time::timeout;
 use tokio::time::Duration;
 
+use crate::eth::primitives::AccountActivity;
+use crate::eth::primitives::Address;
 use crate::eth::primitives::BlockHeader;
+use crate::eth::primitives::BlockHeaderCompact;
 use crate::eth::primitives::DateTimeNow;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::LogFilter;
 use crate::eth::primitives::LogFilterInput;
 use crate::eth::primitives::LogMined;
 use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TransactionMined;
 use crate::eth::rpc::RpcClientApp;
 use crate::ext::not;
 use crate::ext::spawn_named;
@@ -48,7 +52,9 @@ const NOTIFIER_SHUTDOWN_CHECK_INTERVAL: Duration = Duration::from_secs(2);
 mod label {
     pub(super) const PENDING_TXS: &str = "newPendingTransactions";
     pub(super) const NEW_HEADS: &str = "newHeads";
+    pub(super) const NEW_HEADS_COMPACT: &str = "newHeadsCompact";
     pub(super) const LOGS: &str = "logs";
+    pub(super) const ACCOUNT_ACTIVITY: &str = "accountActivity";
 }
 
 /// State of JSON-RPC websocket subscriptions.
@@ -60,14 +66,22 @@ pub struct RpcSubscriptions {
 
 impl RpcSubscriptions {
     /// Creates a new subscription manager that automatically spawns all necessary tasks in background.
-    pub fn spawn(rx_pending_txs: broadcast::Receiver<Hash>, rx_blocks: broadcast::Receiver<BlockHeader>, rx_logs: broadcast::Receiver<LogMined>) -> Self {
+    pub fn spawn(
+        rx_pending_txs: broadcast::Receiver<Hash>,
+        rx_blocks: broadcast::Receiver<BlockHeader>,
+        rx_blocks_compact: broadcast::Receiver<BlockHeaderCompact>,
+        rx_logs: broadcast::Receiver<LogMined>,
+        rx_transactions: broadcast::Receiver<TransactionMined>,
+    ) -> Self {
         let connected = Arc::new(RpcSubscriptionsConnected::default());
 
         Self::spawn_subscriptions_cleaner(Arc::clone(&connected));
         let handles = RpcSubscriptionsHandles {
             new_pending_txs: Self::spawn_new_pending_txs_notifier(Arc::clone(&connected), rx_pending_txs),
             new_heads: Self::spawn_new_heads_notifier(Arc::clone(&connected), rx_blocks),
+            new_heads_compact: Self::spawn_new_heads_compact_notifier(Arc::clone(&connected), rx_blocks_compact),
             logs: Self::spawn_logs_notifier(Arc::clone(&connected), rx_logs),
+            account_activity: Self::spawn_account_activity_notifier(Arc::clone(&connected), rx_transactions),
         };
 
         Self { connected, handles }
@@ -85,7 +99,9 @@ impl RpcSubscriptions {
                 // store here which subscriptions were cleaned to later log them
                 let mut pending_txs_subs_cleaned = Vec::<RpcClientApp>::new();
                 let mut new_heads_subs_cleaned = Vec::<RpcClientApp>::new();
+                let mut new_heads_compact_subs_cleaned = Vec::<RpcClientApp>::new();
                 let mut logs_subs_cleaned = Vec::<(RpcClientApp, LogFilterInput)>::new();
+                let mut account_activity_subs_cleaned = Vec::<(RpcClientApp, Address)>::new();
 
                 // remove closed subscriptions
                 subs.pending_txs.write().await.retain(|_, sub| {
@@ -102,6 +118,13 @@ impl RpcSubscriptions {
                     }
                     should_keep
                 });
+                subs.new_heads_compact.write().await.retain(|_, sub| {
+                    let should_keep = not(sub.sink.is_closed());
+                    if !should_keep {
+                        new_heads_compact_subs_cleaned.push(sub.client.clone());
+                    }
+                    should_keep
+                });
                 subs.logs.write().await.retain(|_, connection_sub_map| {
                     // clear inner map first
                     connection_sub_map.retain(|_, sub| {
@@ -115,15 +138,31 @@ impl RpcSubscriptions {
                     // remove empty connection maps
                     not(connection_sub_map.is_empty())
                 });
+                subs.account_activity.write().await.retain(|_, connection_sub_map| {
+                    connection_sub_map.retain(|_, sub| {
+                        let should_keep = not(sub.inner.sink.is_closed());
+                        if !should_keep {
+                            account_activity_subs_cleaned.push((sub.inner.client.clone(), sub.address));
+                        }
+                        should_keep
+                    });
+                    not(connection_sub_map.is_empty())
+                });
 
                 // log cleaned subscriptions
-                let amount_cleaned = pending_txs_subs_cleaned.len() + new_heads_subs_cleaned.len() + logs_subs_cleaned.len();
+                let amount_cleaned = pending_txs_subs_cleaned.len()
+                    + new_heads_subs_cleaned.len()
+                    + new_heads_compact_subs_cleaned.len()
+                    + logs_subs_cleaned.len()
+                    + account_activity_subs_cleaned.len();
                 if amount_cleaned > 0 {
                     tracing::info!(
                         amount_cleaned,
                         pending_txs = ?pending_txs_subs_cleaned,
                         new_heads = ?new_heads_subs_cleaned,
+                        new_heads_compact = ?new_heads_compact_subs_cleaned,
                         logs = ?logs_subs_cleaned,
+                        account_activity = ?account_activity_subs_cleaned,
                         "cleaned subscriptions",
                     );
                 }
@@ -133,7 +172,9 @@ impl RpcSubscriptions {
                 {
                     metrics::set_rpc_subscriptions_active(subs.pending_txs.read().await.len() as u64, label::PENDING_TXS);
                     metrics::set_rpc_subscriptions_active(subs.new_heads.read().await.len() as u64, label::NEW_HEADS);
+                    metrics::set_rpc_subscriptions_active(subs.new_heads_compact.read().await.len() as u64, label::NEW_HEADS_COMPACT);
                     RpcSubscriptionsConnected::set_log_subs_metric(&(*subs.logs.read().await));
+                    RpcSubscriptionsConnected::set_account_activity_subs_metric(&(*subs.account_activity.read().await));
                 }
 
                 // await next iteration
@@ -190,6 +231,33 @@ impl RpcSubscriptions {
         })
     }
 
+    /// Spawns a new task that notifies subscribers about new created blocks using the compact binary header.
+    fn spawn_new_heads_compact_notifier(
+        subs: Arc<RpcSubscriptionsConnected>,
+        mut rx_block_compact: broadcast::Receiver<BlockHeaderCompact>,
+    ) -> JoinHandle<anyhow::Result<()>> {
+        const TASK_NAME: &str = "rpc::sub::newHeadsCompact";
+        spawn_named(TASK_NAME, async move {
+            loop {
+                if GlobalState::is_shutdown_warn(TASK_NAME) {
+                    return Ok(());
+                }
+
+                let block_header_compact = match timeout(NOTIFIER_SHUTDOWN_CHECK_INTERVAL, rx_block_compact.recv()).await {
+                    Ok(Ok(block)) => block,
+                    Ok(Err(_channel_closed)) => break,
+                    Err(_timed_out) => continue,
+                };
+
+                let interested_subs = subs.new_heads_compact.read().await;
+                let interested_subs = interested_subs.values().collect_vec();
+                Self::notify(interested_subs, block_header_compact);
+            }
+            warn_task_rx_closed(TASK_NAME);
+            Ok(())
+        })
+    }
+
     /// Spawns a new task that notifies subscribers about new transactions logs.
     fn spawn_logs_notifier(subs: Arc<RpcSubscriptionsConnected>, mut rx_log_mined: broadcast::Receiver<LogMined>) -> JoinHandle<anyhow::Result<()>> {
         const TASK_NAME: &str = "rpc::sub::logs";
@@ -219,6 +287,40 @@ impl RpcSubscriptions {
         })
     }
 
+    /// Spawns a new task that notifies subscribers about activity of the accounts they are interested in.
+    fn spawn_account_activity_notifier(
+        subs: Arc<RpcSubscriptionsConnected>,
+        mut rx_transaction: broadcast::Receiver<TransactionMined>,
+    ) -> JoinHandle<anyhow::Result<()>> {
+        const TASK_NAME: &str = "rpc::sub::accountActivity";
+        spawn_named(TASK_NAME, async move {
+            loop {
+                if GlobalState::is_shutdown_warn(TASK_NAME) {
+                    return Ok(());
+                }
+
+                let tx = match timeout(NOTIFIER_SHUTDOWN_CHECK_INTERVAL, rx_transaction.recv()).await {
+                    Ok(Ok(tx)) => tx,
+                    Ok(Err(_channel_closed)) => break,
+                    Err(_timed_out) => continue,
+                };
+
+                // group subscriptions by account so each account activity is extracted only once
+                let interested_subs = subs.account_activity.read().await;
+                let subs_by_address = interested_subs.values().flat_map(HashMap::values).into_group_map_by(|s| s.address);
+
+                for (address, address_subs) in subs_by_address {
+                    let address_subs = address_subs.into_iter().map(|s| &s.inner).collect_vec();
+                    for activity in AccountActivity::from_transaction(address, &tx) {
+                        Self::notify(address_subs.clone(), activity);
+                    }
+                }
+            }
+            warn_task_rx_closed(TASK_NAME);
+            Ok(())
+        })
+    }
+
     // -------------------------------------------------------------------------
     // Helpers
     // -------------------------------------------------------------------------
@@ -270,12 +372,14 @@ impl RpcSubscriptions {
 pub struct RpcSubscriptionsHandles {
     new_pending_txs: JoinHandle<anyhow::Result<()>>,
     new_heads: JoinHandle<anyhow::Result<()>>,
+    new_heads_compact: JoinHandle<anyhow::Result<()>>,
     logs: JoinHandle<anyhow::Result<()>>,
+    account_activity: JoinHandle<anyhow::Result<()>>,
 }
 
 impl RpcSubscriptionsHandles {
     pub async fn stopped(self) {
-        let _ = join!(self.new_pending_txs, self.new_heads, self.logs);
+        let _ = join!(self.new_pending_txs, self.new_heads, self.new_heads_compact, self.logs, self.account_activity);
     }
 }
 
@@ -331,12 +435,23 @@ pub struct SubscriptionWithFilter {
     filter: LogFilter,
 }
 
+#[derive(Debug, derive_more::Deref, derive_new::new, serde::Serialize)]
+pub struct SubscriptionWithAddress {
+    #[deref]
+    #[serde(flatten)]
+    inner: Subscription,
+
+    address: Address,
+}
+
 /// Active client subscriptions.
 #[derive(Debug, Default)]
 pub struct RpcSubscriptionsConnected {
     pub pending_txs: RwLock<HashMap<ConnectionId, Subscription>>,
     pub new_heads: RwLock<HashMap<ConnectionId, Subscription>>,
+    pub new_heads_compact: RwLock<HashMap<ConnectionId, Subscription>>,
     pub logs: RwLock<HashMap<ConnectionId, HashMap<LogFilter, SubscriptionWithFilter>>>,
+    pub account_activity: RwLock<HashMap<ConnectionId, HashMap<Address, SubscriptionWithAddress>>>,
 }
 
 impl RpcSubscriptionsConnected {
@@ -344,6 +459,7 @@ impl RpcSubscriptionsConnected {
     pub async fn check_client_subscriptions(&self, max_subscriptions: u32, client: &RpcClientApp) -> Result<(), StratusError> {
         let pending_txs = self.pending_txs.read().await.values().filter(|s| s.client == *client).count();
         let new_heads = self.new_heads.read().await.values().filter(|s| s.client == *client).count();
+        let new_heads_compact = self.new_heads_compact.read().await.values().filter(|s| s.client == *client).count();
         let logs = self
             .logs
             .read()
@@ -352,9 +468,17 @@ impl RpcSubscriptionsConnected {
             .flat_map(HashMap::values)
             .filter(|s| s.client == *client)
             .count();
-        tracing::info!(%pending_txs, %new_heads, %logs, "current client subscriptions");
+        let account_activity = self
+            .account_activity
+            .read()
+            .await
+            .values()
+            .flat_map(HashMap::values)
+            .filter(|s| s.client == *client)
+            .count();
+        tracing::info!(%pending_txs, %new_heads, %new_heads_compact, %logs, %account_activity, "current client subscriptions");
 
-        if pending_txs + new_heads + logs >= max_subscriptions as usize {
+        if pending_txs + new_heads + new_heads_compact + logs + account_activity >= max_subscriptions as usize {
             return Err(StratusError::RpcSubscriptionLimit { max: max_subscriptions });
         }
 
@@ -389,6 +513,20 @@ impl RpcSubscriptionsConnected {
         metrics::set_rpc_subscriptions_active(subs.len() as u64, label::NEW_HEADS);
     }
 
+    /// Adds a new subscriber to `newHeadsCompact` event.
+    pub async fn add_new_heads_compact(&self, rpc_client: RpcClientApp, sink: SubscriptionSink) {
+        tracing::info!(
+            id = sink.subscription_id().to_string_ext(),
+            %rpc_client,
+            "subscribing to newHeadsCompact event"
+        );
+        let mut subs = self.new_heads_compact.write().await;
+        subs.insert(sink.connection_id(), Subscription::new(rpc_client, sink.into()));
+
+        #[cfg(feature = "metrics")]
+        metrics::set_rpc_subscriptions_active(subs.len() as u64, label::NEW_HEADS_COMPACT);
+    }
+
     /// Adds a new subscriber to `logs` event.
     ///
     /// If the same connection is asking to subscribe with the same filter (which is redundant),
@@ -411,6 +549,31 @@ impl RpcSubscriptionsConnected {
         Self::set_log_subs_metric(&subs);
     }
 
+    /// Adds a new subscriber to the activity of an account.
+    ///
+    /// If the same connection is asking to subscribe to the same account, the new subscription overwrites the previous one.
+    pub async fn add_account_activity(&self, rpc_client: RpcClientApp, address: Address, sink: SubscriptionSink) {
+        tracing::info!(
+            id = sink.subscription_id().to_string_ext(), %address,
+            %rpc_client,
+            "subscribing to account activity"
+        );
+        let mut subs = self.account_activity.write().await;
+        let address_to_subscription_map = subs.entry(sink.connection_id()).or_default();
+
+        let inner = Subscription::new(rpc_client, sink.into());
+        address_to_subscription_map.insert(address, SubscriptionWithAddress::new(inner, address));
+
+        #[cfg(feature = "metrics")]
+        Self::set_account_activity_subs_metric(&subs);
+    }
+
+    #[cfg(feature = "metrics")]
+    fn set_account_activity_subs_metric(account_activity_subs: &HashMap<ConnectionId, HashMap<Address, SubscriptionWithAddress>>) {
+        let sub_count: usize = account_activity_subs.values().map(|value| value.len()).sum();
+        metrics::set_rpc_subscriptions_active(sub_count as u64, label::ACCOUNT_ACTIVITY);
+    }
+
     #[cfg(feature = "metrics")]
     fn set_log_subs_metric(log_subs: &HashMap<ConnectionId, HashMap<LogFilter, SubscriptionWithFilter>>) {
         let sub_count: usize = log_subs.values().map(|value| value.len()).sum();
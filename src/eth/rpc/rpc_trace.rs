@@ -0,0 +1,274 @@
+//! Byte-budgeted responses of tracing methods.
+//!
+//! Traces of pathological transactions (thousands of logs or storage changes) can produce responses too large to be serialized in
+//! memory or consumed by clients. Tracing methods accept a byte budget and split the traces in pages that fit in it: traces are added
+//! until the budget is exhausted, and the logs and storage changes of a trace that does not fit alone are split across pages. Partial
+//! traces are marked as truncated and the response includes a cursor that must be sent in the next request to continue from where the
+//! previous page stopped.
+
+use itertools::Itertools;
+use serde_json::json;
+
+use crate::alias::JsonValue;
+use crate::eth::primitives::EvmExecution;
+use crate::eth::primitives::ExecutionChanges;
+use crate::eth::primitives::Hash;
+use crate::ext::not;
+use crate::ext::to_json_string;
+use crate::ext::to_json_value;
+use crate::if_else;
+
+/// Tracer options accepted by tracing methods.
+///
+/// Other geth tracer options are accepted for compatibility, but ignored.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct TraceOptions {
+    /// Max size of the response in bytes. Limited by the server budget.
+    #[serde(rename = "responseByteBudget", default)]
+    pub byte_budget: Option<usize>,
+
+    /// Position to continue from, as returned in a previous truncated response.
+    #[serde(default)]
+    pub cursor: Option<TraceCursor>,
+}
+
+impl TraceOptions {
+    /// Byte budget of the response, limited by the server budget.
+    pub fn budget(&self, server_budget: usize) -> usize {
+        self.byte_budget.unwrap_or(server_budget).min(server_budget)
+    }
+}
+
+/// Position in a list of traces where the next page starts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TraceCursor {
+    /// Index of the transaction in the list of traces.
+    #[serde(rename = "transactionIndex", default)]
+    pub transaction: usize,
+
+    /// Index of the first log of the transaction not sent yet.
+    #[serde(rename = "logIndex", default)]
+    pub log: usize,
+
+    /// Index of the first storage change (ordered by address) of the transaction not sent yet.
+    #[serde(rename = "changeIndex", default)]
+    pub change: usize,
+}
+
+/// Page of traces that fits in the byte budget.
+#[derive(Debug)]
+pub struct TracePage {
+    /// Traces, or parts of traces, included in the page.
+    pub traces: Vec<(Hash, JsonValue)>,
+
+    /// Position where the next page starts, if the traces did not fit in the page.
+    pub cursor: Option<TraceCursor>,
+}
+
+impl TracePage {
+    /// Selects the traces starting at the cursor that fit in the budget.
+    ///
+    /// At least one log or storage change is always included, so clients make progress even when the budget is too small.
+    pub fn paginate(traces: Vec<(Hash, EvmExecution)>, cursor: TraceCursor, budget: usize) -> Self {
+        let mut page = Vec::new();
+        let mut used = 2; // array brackets
+
+        for (index, (hash, execution)) in traces.into_iter().enumerate().skip(cursor.transaction) {
+            // logs and changes already sent are skipped only for the transaction the cursor points to
+            let (log_offset, change_offset) = if_else!(index == cursor.transaction, (cursor.log, cursor.change), (0, 0));
+
+            // complete trace fits in the remaining budget
+            let trace = split_trace(&execution, log_offset, change_offset, usize::MAX);
+            let trace_len = entry_len(&hash, &trace.value);
+            if used + trace_len <= budget {
+                used += trace_len;
+                page.push((hash, trace.value));
+                continue;
+            }
+
+            // trace does not fit, but other traces were included, so it starts in the next page
+            if not(page.is_empty()) {
+                let next = TraceCursor {
+                    transaction: index,
+                    log: log_offset,
+                    change: change_offset,
+                };
+                return Self {
+                    traces: page,
+                    cursor: Some(next),
+                };
+            }
+
+            // trace does not fit alone, so it is split
+            let trace = split_trace(&execution, log_offset, change_offset, budget.saturating_sub(used));
+            let next = match trace.next {
+                Some((log, change)) => TraceCursor {
+                    transaction: index,
+                    log,
+                    change,
+                },
+                None => TraceCursor {
+                    transaction: index + 1,
+                    log: 0,
+                    change: 0,
+                },
+            };
+            page.push((hash, trace.value));
+            return Self {
+                traces: page,
+                cursor: Some(next),
+            };
+        }
+
+        Self { traces: page, cursor: None }
+    }
+
+    /// Serializes the page as the response of methods that trace multiple transactions.
+    ///
+    /// A truncated page ends with a marker containing the cursor of the next page.
+    pub fn to_json_block_traces(self) -> JsonValue {
+        let mut traces = self
+            .traces
+            .into_iter()
+            .map(|(tx_hash, trace)| json!({"txHash": tx_hash, "result": trace}))
+            .collect_vec();
+        if let Some(cursor) = self.cursor {
+            traces.push(json!({"truncated": true, "cursor": cursor}));
+        }
+        JsonValue::Array(traces)
+    }
+
+    /// Serializes the page as the response of methods that trace a single transaction.
+    ///
+    /// A truncated trace includes the cursor of the next page.
+    pub fn to_json_transaction_trace(self) -> JsonValue {
+        let Some((_, mut trace)) = self.traces.into_iter().next() else {
+            return JsonValue::Null;
+        };
+        if let (Some(cursor), Some(trace)) = (self.cursor, trace.as_object_mut()) {
+            trace.insert("cursor".to_owned(), to_json_value(cursor));
+        }
+        trace
+    }
+}
+
+/// Serialized size of a trace in the response, including the separator.
+fn entry_len(hash: &Hash, trace: &JsonValue) -> usize {
+    to_json_string(&json!({"txHash": hash, "result": trace})).len() + 1
+}
+
+/// Part of a trace that fits in the budget.
+struct SplitTrace {
+    value: JsonValue,
+
+    /// Offsets of the next log and change when the trace was truncated.
+    next: Option<(usize, usize)>,
+}
+
+/// Serializes a trace starting at the offsets and including as many logs and storage changes as fit in the budget.
+fn split_trace(execution: &EvmExecution, log_offset: usize, change_offset: usize, budget: usize) -> SplitTrace {
+    let logs = execution.logs.iter().skip(log_offset).map(to_json_value).collect_vec();
+    let changes = execution
+        .changes
+        .iter()
+        .sorted_by_key(|(address, _)| **address)
+        .skip(change_offset)
+        .map(|(address, changes)| (address.to_string(), to_json_value(changes)))
+        .collect_vec();
+
+    // base trace without logs and changes, reserving space for the entry wrapper and truncation markers
+    let mut value = to_json_value(EvmExecution {
+        block_timestamp: execution.block_timestamp,
+        receipt_applied: execution.receipt_applied,
+        result: execution.result.clone(),
+        output: execution.output.clone(),
+        logs: Vec::new(),
+        gas: execution.gas,
+        changes: ExecutionChanges::default(),
+        deployed_contract_address: execution.deployed_contract_address,
+    });
+    let mut used = to_json_string(&value).len() + 128;
+
+    // add logs, then changes, while they fit, but always at least one of them
+    let mut included_logs = 0;
+    for log in &logs {
+        let log_len = to_json_string(log).len() + 1;
+        if used.saturating_add(log_len) > budget && included_logs > 0 {
+            break;
+        }
+        used = used.saturating_add(log_len);
+        included_logs += 1;
+    }
+    let mut included_changes = 0;
+    if included_logs == logs.len() {
+        for (address, change) in &changes {
+            let change_len = address.len() + to_json_string(change).len() + 4;
+            if used.saturating_add(change_len) > budget && included_logs + included_changes > 0 {
+                break;
+            }
+            used = used.saturating_add(change_len);
+            included_changes += 1;
+        }
+    }
+    let truncated = included_logs < logs.len() || included_changes < changes.len();
+
+    value["logs"] = JsonValue::Array(logs.into_iter().take(included_logs).collect());
+    value["changes"] = JsonValue::Object(changes.into_iter().take(included_changes).collect());
+    if truncated {
+        value["truncated"] = JsonValue::Bool(true);
+    }
+
+    SplitTrace {
+        value,
+        next: truncated.then_some((log_offset + included_logs, change_offset + included_changes)),
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use fake::Fake;
+    use fake::Faker;
+
+    use super::*;
+    use crate::eth::primitives::Log;
+
+    fn execution_with_logs(logs: usize) -> EvmExecution {
+        let mut execution: EvmExecution = Faker.fake();
+        execution.changes.clear();
+        execution.logs = (0..logs).map(|_| Faker.fake::<Log>()).collect();
+        execution
+    }
+
+    #[test]
+    fn trace_page_includes_all_traces_within_budget() {
+        let traces = vec![(Hash::new([1; 32]), execution_with_logs(2)), (Hash::new([2; 32]), execution_with_logs(2))];
+        let page = TracePage::paginate(traces, TraceCursor::default(), usize::MAX);
+        assert_eq!(page.traces.len(), 2);
+        assert!(page.cursor.is_none());
+    }
+
+    #[test]
+    fn trace_page_splits_large_trace_with_cursor() {
+        let traces = vec![(Hash::new([1; 32]), execution_with_logs(50))];
+
+        let mut cursor = TraceCursor::default();
+        let mut logs = 0;
+        for _ in 0..50 {
+            let page = TracePage::paginate(traces.clone(), cursor, 2_000);
+            let (_, trace) = &page.traces[0];
+            logs += trace["logs"].as_array().unwrap().len();
+            match page.cursor {
+                Some(next) => {
+                    assert_eq!(trace["truncated"], true);
+                    cursor = next;
+                }
+                None => break,
+            }
+        }
+        assert_eq!(logs, 50);
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use display_json::DebugAsJson;
+
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::UnixTime;
+use crate::ext::not;
+use crate::ext::MutexExt;
+
+/// Blocks removed by resets of the permanent storage, kept for a retention window so an accidental rollback can be undone.
+///
+/// Quarantined blocks can be restored only on top of the block the storage was reset to, so they are discarded in practice once new
+/// blocks are mined after the reset. They are kept in memory and do not survive restarts.
+#[derive(Debug, Default)]
+pub struct BlockQuarantine {
+    /// How long quarantined blocks are kept. Zero disables the quarantine.
+    retention: Duration,
+
+    /// Max number of blocks removed by a single reset that are quarantined.
+    max_blocks: usize,
+
+    state: Mutex<BlockQuarantineState>,
+}
+
+#[derive(Debug, Default)]
+struct BlockQuarantineState {
+    next_id: u64,
+
+    /// Quarantined resets from the oldest to the newest.
+    entries: VecDeque<QuarantinedBlocks>,
+}
+
+/// Blocks removed by a single reset.
+#[derive(Debug)]
+pub struct QuarantinedBlocks {
+    pub id: u64,
+
+    /// Block the storage was reset to. Blocks can be restored only when it is the last mined block.
+    pub reset_number: BlockNumber,
+
+    /// Removed blocks in ascending order.
+    pub blocks: Vec<Block>,
+
+    pub quarantined_at: UnixTime,
+}
+
+/// Description of blocks removed by a single reset, without their contents.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantinedBlocksSummary {
+    pub id: u64,
+    pub reset_number: BlockNumber,
+    pub from_block: BlockNumber,
+    pub to_block: BlockNumber,
+    pub hashes: Vec<Hash>,
+    pub quarantined_at: UnixTime,
+    pub expires_at: UnixTime,
+}
+
+impl BlockQuarantine {
+    pub fn new(retention: Duration, max_blocks: usize) -> Self {
+        Self {
+            retention,
+            max_blocks,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Checks if blocks removed by resets must be quarantined.
+    pub fn is_enabled(&self) -> bool {
+        not(self.retention.is_zero()) && self.max_blocks > 0
+    }
+
+    /// Max number of blocks removed by a single reset that are quarantined.
+    pub fn max_blocks(&self) -> usize {
+        self.max_blocks
+    }
+
+    /// Quarantines the blocks removed by a reset to `reset_number`. Returns the id used to restore them.
+    pub fn quarantine(&self, reset_number: BlockNumber, blocks: Vec<Block>, now: UnixTime) -> u64 {
+        let mut state = self.state.lock_or_clear("block quarantine lock was poisoned");
+        self.expire(&mut state, now);
+
+        let id = state.next_id;
+        state.next_id += 1;
+        tracing::warn!(%id, %reset_number, blocks = %blocks.len(), retention = ?self.retention, "quarantining blocks removed by reset");
+        state.entries.push_back(QuarantinedBlocks {
+            id,
+            reset_number,
+            blocks,
+            quarantined_at: now,
+        });
+        id
+    }
+
+    /// Lists the quarantined resets that did not expire yet.
+    pub fn list(&self, now: UnixTime) -> Vec<QuarantinedBlocksSummary> {
+        let mut state = self.state.lock_or_clear("block quarantine lock was poisoned");
+        self.expire(&mut state, now);
+        state.entries.iter().map(|entry| self.summary(entry)).collect()
+    }
+
+    /// Removes blocks from the quarantine to restore them. Takes the most recent reset if `id` is not specified.
+    pub fn take(&self, id: Option<u64>, now: UnixTime) -> Option<QuarantinedBlocks> {
+        let mut state = self.state.lock_or_clear("block quarantine lock was poisoned");
+        self.expire(&mut state, now);
+        let position = match id {
+            Some(id) => state.entries.iter().position(|entry| entry.id == id)?,
+            None => state.entries.len().checked_sub(1)?,
+        };
+        state.entries.remove(position)
+    }
+
+    /// Returns blocks taken with `take` that could not be restored.
+    pub fn put_back(&self, entry: QuarantinedBlocks) {
+        let mut state = self.state.lock_or_clear("block quarantine lock was poisoned");
+        let position = state.entries.iter().position(|other| other.id > entry.id).unwrap_or(state.entries.len());
+        state.entries.insert(position, entry);
+    }
+
+    /// Describes quarantined blocks without their contents.
+    pub fn summary(&self, entry: &QuarantinedBlocks) -> QuarantinedBlocksSummary {
+        QuarantinedBlocksSummary {
+            id: entry.id,
+            reset_number: entry.reset_number,
+            from_block: entry.blocks.first().map(Block::number).unwrap_or(entry.reset_number),
+            to_block: entry.blocks.last().map(Block::number).unwrap_or(entry.reset_number),
+            hashes: entry.blocks.iter().map(Block::hash).collect(),
+            quarantined_at: entry.quarantined_at,
+            expires_at: UnixTime::from(entry.quarantined_at.as_u64() + self.retention.as_secs()),
+        }
+    }
+
+    fn expire(&self, state: &mut BlockQuarantineState, now: UnixTime) {
+        let retention = self.retention.as_secs();
+        while let Some(entry) = state.entries.front() {
+            if entry.quarantined_at.as_u64() + retention > now.as_u64() {
+                break;
+            }
+            tracing::info!(id = %entry.id, reset_number = %entry.reset_number, "discarding expired quarantined blocks");
+            state.entries.pop_front();
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::primitives::BlockFilter;
+    use crate::eth::primitives::StratusError;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::InMemoryTemporaryStorage;
+    use crate::eth::storage::StratusStorage;
+
+    fn blocks(numbers: std::ops::RangeInclusive<u64>) -> Vec<Block> {
+        numbers.map(|number| Block::new(number.into(), UnixTime::from(number))).collect()
+    }
+
+    #[test]
+    fn block_quarantine_takes_latest_and_expires() {
+        let quarantine = BlockQuarantine::new(Duration::from_secs(60), 10);
+        assert!(quarantine.is_enabled());
+        assert!(!BlockQuarantine::default().is_enabled());
+
+        let first = quarantine.quarantine(BlockNumber::from(5u64), blocks(6..=8), UnixTime::from(1_000));
+        let second = quarantine.quarantine(BlockNumber::from(5u64), blocks(6..=6), UnixTime::from(1_030));
+
+        let summaries = quarantine.list(UnixTime::from(1_030));
+        assert_eq!(summaries.len(), 2);
+        assert_eq!((summaries[0].from_block, summaries[0].to_block), (6u64.into(), 8u64.into()));
+        assert_eq!(summaries[0].expires_at, UnixTime::from(1_060));
+
+        // latest is taken by default and can be put back
+        let taken = quarantine.take(None, UnixTime::from(1_030)).unwrap();
+        assert_eq!(taken.id, second);
+        quarantine.put_back(taken);
+        assert_eq!(
+            quarantine.list(UnixTime::from(1_030)).iter().map(|summary| summary.id).collect::<Vec<_>>(),
+            vec![first, second]
+        );
+
+        // first expires before second
+        assert!(quarantine.take(Some(first), UnixTime::from(1_060)).is_none());
+        assert_eq!(quarantine.take(Some(second), UnixTime::from(1_060)).unwrap().blocks.len(), 1);
+        assert!(quarantine.take(None, UnixTime::from(1_060)).is_none());
+    }
+
+    #[test]
+    fn reset_blocks_are_restored_on_top_of_reset_block() {
+        let mut storage = StratusStorage::new(Box::<InMemoryTemporaryStorage>::default(), Box::<InMemoryPermanentStorage>::default()).unwrap();
+        storage.enable_reset_quarantine(Duration::from_secs(60), 10);
+
+        // mine blocks on top of the current head
+        let base = storage.read_mined_block_number().unwrap();
+        let mut parent_hash = storage
+            .read_block(&BlockFilter::Number(base))
+            .unwrap()
+            .map(|block| block.hash())
+            .unwrap_or_default();
+        for number in base.as_u64() + 1..=base.as_u64() + 3 {
+            let mut block = Block::new(number.into(), UnixTime::from(number));
+            block.header.parent_hash = parent_hash;
+            parent_hash = block.hash();
+            storage.apply_replicated_block(block).unwrap();
+        }
+
+        // reset quarantines removed blocks
+        storage.reset_perm(base).unwrap();
+        assert_eq!(storage.read_mined_block_number().unwrap(), base);
+        let summaries = storage.quarantined_blocks();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].hashes.len(), 3);
+
+        // restore brings them back
+        let restored = storage.restore_quarantined_blocks(None).unwrap();
+        assert_eq!(restored.to_block, BlockNumber::from(base.as_u64() + 3));
+        assert_eq!(storage.read_mined_block_number().unwrap(), restored.to_block);
+        assert_eq!(storage.read_block(&BlockFilter::Latest).unwrap().unwrap().hash(), parent_hash);
+        assert!(storage.quarantined_blocks().is_empty());
+        assert!(matches!(storage.restore_quarantined_blocks(None), Err(StratusError::StorageQuarantineNotFound)));
+    }
+}
@@ -0,0 +1,238 @@
+//! Block files used to move chain history between environments, similar to the files produced by `geth export`.
+//!
+//! A block file is a sequence of RLP records, one for each block:
+//!
+//! ```text
+//! [hash, size, [header, transactions, uncles], receipts]
+//! ```
+//!
+//! The block is in its canonical encoding and receipts are in their consensus encoding. The hash and size are kept because local blocks
+//! are not identified by the hash of their header. Imported blocks are re-executed like blocks received from the external RPC, so the
+//! importing storage must start from the same genesis state as the exporting one.
+
+use ethereum_types::Bloom;
+use ethereum_types::H160;
+use ethereum_types::H256;
+use ethereum_types::U256;
+use ethereum_types::U64;
+use rlp::DecoderError;
+use rlp::Rlp;
+use rlp::RlpStream;
+
+use crate::alias::EthersLog;
+use crate::alias::EthersReceipt;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::ExternalBlock;
+use crate::eth::primitives::ExternalReceipt;
+use crate::eth::primitives::ExternalTransaction;
+use crate::eth::primitives::Gas;
+
+/// Block read from a block file, ready to be re-executed.
+#[derive(Debug, Clone)]
+pub struct BlockRlpRecord {
+    pub block: ExternalBlock,
+    pub receipts: Vec<ExternalReceipt>,
+}
+
+impl BlockRlpRecord {
+    /// Encodes a block as a record of a block file.
+    pub fn encode(block: &Block) -> Vec<u8> {
+        let mut s = RlpStream::new_list(4);
+        s.append(&H256::from(block.hash()));
+        s.append(&u64::from(block.header.size));
+        s.append(block);
+
+        let mut cumulative_gas_used: u64 = 0;
+        s.begin_list(block.transactions.len());
+        for tx in &block.transactions {
+            cumulative_gas_used = cumulative_gas_used.saturating_add(tx.execution.gas.as_u64());
+            let encoded = tx.encode_receipt(Gas::from(cumulative_gas_used));
+            if tx.is_typed() {
+                s.append(&encoded);
+            } else {
+                s.append_raw(&encoded, 1);
+            }
+        }
+
+        s.out().to_vec()
+    }
+
+    /// Decodes all records of a block file.
+    pub fn decode_all(bytes: &[u8]) -> anyhow::Result<Vec<Self>> {
+        let mut records = Vec::new();
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let rlp = Rlp::new(&bytes[offset..]);
+            let payload = rlp.payload_info()?;
+            let record_len = payload.header_len + payload.value_len;
+            if offset + record_len > bytes.len() {
+                return Err(DecoderError::RlpIsTooShort.into());
+            }
+
+            let record = Self::decode(&Rlp::new(&bytes[offset..offset + record_len]))?;
+            records.push(record);
+            offset += record_len;
+        }
+        Ok(records)
+    }
+
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        if rlp.item_count()? != 4 {
+            return Err(DecoderError::RlpIncorrectListLen);
+        }
+        let hash: H256 = rlp.val_at(0)?;
+        let size: u64 = rlp.val_at(1)?;
+
+        // keep identifiers of the exported block
+        let mut block: ExternalBlock = rlp.val_at(2)?;
+        block.0.hash = Some(hash);
+        block.0.size = Some(U256::from(size));
+        for tx in &mut block.0.transactions {
+            tx.0.block_hash = Some(hash);
+        }
+
+        // typed receipts are byte strings, legacy receipts are lists
+        let receipts_rlp = rlp.at(3)?;
+        if receipts_rlp.item_count()? != block.transactions.len() {
+            return Err(DecoderError::Custom("number of receipts does not match number of transactions"));
+        }
+        let mut receipts = Vec::with_capacity(block.transactions.len());
+        let mut cumulative_gas_used = U256::zero();
+        let mut log_index = U256::zero();
+        for (tx, receipt_rlp) in block.transactions.iter().zip(receipts_rlp.iter()) {
+            let receipt = if receipt_rlp.is_list() {
+                decode_receipt(&receipt_rlp, tx, &mut cumulative_gas_used, &mut log_index)?
+            } else {
+                let data = receipt_rlp.data()?;
+                let Some(payload) = data.get(1..) else {
+                    return Err(DecoderError::RlpIsTooShort);
+                };
+                decode_receipt(&Rlp::new(payload), tx, &mut cumulative_gas_used, &mut log_index)?
+            };
+            receipts.push(receipt);
+        }
+
+        Ok(Self { block, receipts })
+    }
+
+    /// Returns the block number.
+    pub fn number(&self) -> BlockNumber {
+        self.block.number()
+    }
+}
+
+/// Decodes a consensus receipt, filling the fields that are not part of the encoding from the transaction and previous receipts.
+fn decode_receipt(rlp: &Rlp, tx: &ExternalTransaction, cumulative_gas_used: &mut U256, log_index: &mut U256) -> Result<ExternalReceipt, DecoderError> {
+    if rlp.item_count()? != 4 {
+        return Err(DecoderError::RlpIncorrectListLen);
+    }
+    let status: u64 = rlp.val_at(0)?;
+    let tx_cumulative_gas_used: U256 = rlp.val_at(1)?;
+    let logs_bloom: Bloom = rlp.val_at(2)?;
+    let Some(gas_used) = tx_cumulative_gas_used.checked_sub(*cumulative_gas_used) else {
+        return Err(DecoderError::Custom("cumulative gas used decreased between receipts"));
+    };
+    *cumulative_gas_used = tx_cumulative_gas_used;
+
+    let mut logs = Vec::new();
+    for log_rlp in rlp.at(3)?.iter() {
+        logs.push(EthersLog {
+            address: log_rlp.val_at::<H160>(0)?,
+            topics: log_rlp.list_at::<H256>(1)?,
+            data: log_rlp.val_at::<Vec<u8>>(2)?.into(),
+            block_hash: tx.0.block_hash,
+            block_number: tx.0.block_number,
+            transaction_hash: Some(tx.0.hash),
+            transaction_index: tx.0.transaction_index,
+            log_index: Some(*log_index),
+            removed: Some(false),
+            ..Default::default()
+        });
+        *log_index += U256::one();
+    }
+
+    Ok(ExternalReceipt(EthersReceipt {
+        transaction_hash: tx.0.hash,
+        transaction_index: tx.0.transaction_index.unwrap_or_default(),
+        block_hash: tx.0.block_hash,
+        block_number: tx.0.block_number,
+        from: tx.0.from,
+        to: tx.0.to,
+        cumulative_gas_used: tx_cumulative_gas_used,
+        gas_used: Some(gas_used),
+        logs,
+        status: Some(U64::from(status)),
+        logs_bloom,
+        transaction_type: tx.0.transaction_type,
+        ..Default::default()
+    }))
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::primitives::Address;
+    use crate::eth::primitives::BlockHeader;
+    use crate::eth::primitives::Bytes;
+    use crate::eth::primitives::Hash;
+    use crate::eth::primitives::UnixTime;
+    use crate::eth::primitives::Wei;
+
+    fn block(number: u64, base_fee: u64) -> Block {
+        let mut block = Block::new(BlockNumber::from(number), UnixTime::from(1_700_000_000 + number));
+        block.header.gas_limit = Gas::from(BlockHeader::GAS_LIMIT);
+        block.header.base_fee_per_gas = Wei::from(base_fee);
+        block.header.miner = Address::new([7; 20]);
+        block.header.author = Address::new([7; 20]);
+        block.header.extra_data = Bytes(vec![1, 2, 3]);
+        block.header.state_root = Hash::new([8; 32]);
+        block.update_hash();
+        block
+    }
+
+    #[test]
+    fn block_rlp_records_keep_headers() {
+        let blocks = [block(1, 0), block(2, 1_000_000_000)];
+        let bytes = blocks.iter().flat_map(BlockRlpRecord::encode).collect::<Vec<_>>();
+
+        let records = BlockRlpRecord::decode_all(&bytes).unwrap();
+        assert_eq!(records.len(), blocks.len());
+        for (record, block) in records.iter().zip(&blocks) {
+            assert_eq!(BlockHeader::try_from(&record.block).unwrap(), block.header);
+            assert!(record.block.validate_hash().is_ok());
+            assert!(record.receipts.is_empty());
+        }
+    }
+
+    #[test]
+    fn block_rlp_records_validate_headers_with_post_london_fields() {
+        let mut block = block(3, 0);
+        block.header.withdrawals_root = Some(Hash::new([9; 32]));
+        block.header.blob_gas_used = Some(Gas::from(131_072u64));
+        block.header.excess_blob_gas = Some(Gas::ZERO);
+        block.header.parent_beacon_block_root = Some(Hash::new([10; 32]));
+        block.header.requests_hash = Some(Hash::new([12; 32]));
+        block.update_hash();
+
+        let mut records = BlockRlpRecord::decode_all(&BlockRlpRecord::encode(&block)).unwrap();
+        let record = records.remove(0);
+        assert_eq!(BlockHeader::try_from(&record.block).unwrap(), block.header);
+        assert!(record.block.validate_hash().is_ok());
+
+        // post-london fields are covered by the hash
+        let mut tampered = record.block;
+        tampered.parent_beacon_block_root = Some(Hash::new([11; 32]).into());
+        assert!(tampered.validate_hash().is_err());
+    }
+
+    #[test]
+    fn block_rlp_records_reject_truncated_files() {
+        let bytes = BlockRlpRecord::encode(&block(1, 0));
+        assert!(BlockRlpRecord::decode_all(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
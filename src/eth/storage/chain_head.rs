@@ -0,0 +1,85 @@
+use tokio::sync::broadcast;
+
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Hash;
+
+/// Change of the last mined block of the local chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChainHeadEvent {
+    /// Block committed by the miner, either mined locally, imported from the external RPC or received through replication.
+    Committed { number: BlockNumber, hash: Hash },
+
+    /// Blocks after `number` up to `previous_mined` were removed by an importer reorg, a snapshot revert or a reset to genesis.
+    RolledBack { number: BlockNumber, previous_mined: BlockNumber },
+}
+
+impl ChainHeadEvent {
+    /// Last mined block after the event.
+    pub fn number(&self) -> BlockNumber {
+        match self {
+            Self::Committed { number, .. } => *number,
+            Self::RolledBack { number, .. } => *number,
+        }
+    }
+}
+
+/// Single source of chain head changes.
+///
+/// The miner publishes committed blocks and the storage publishes rollbacks, so notifiers, filters and followers see every change of the
+/// chain head in the same order, regardless of which path produced it.
+#[derive(Debug)]
+pub struct ChainHeadBus {
+    sender: broadcast::Sender<ChainHeadEvent>,
+}
+
+impl Default for ChainHeadBus {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(u16::MAX as usize).0,
+        }
+    }
+}
+
+impl ChainHeadBus {
+    /// Publishes a change of the chain head to all subscribers.
+    pub fn publish(&self, event: ChainHeadEvent) {
+        tracing::debug!(?event, "publishing chain head event");
+        let _ = self.sender.send(event);
+    }
+
+    /// Subscribes to changes of the chain head published after the subscription.
+    pub fn subscribe(&self) -> broadcast::Receiver<ChainHeadEvent> {
+        self.sender.subscribe()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chain_head_bus_delivers_events_in_order() {
+        let bus = ChainHeadBus::default();
+        let mut rx = bus.subscribe();
+
+        let committed = ChainHeadEvent::Committed {
+            number: BlockNumber::from(2),
+            hash: Hash::new([2; 32]),
+        };
+        let rolled_back = ChainHeadEvent::RolledBack {
+            number: BlockNumber::from(1),
+            previous_mined: BlockNumber::from(2),
+        };
+        bus.publish(committed);
+        bus.publish(rolled_back);
+
+        assert_eq!(rx.try_recv().unwrap(), committed);
+        assert_eq!(rx.try_recv().unwrap(), rolled_back);
+        assert_eq!(rolled_back.number(), BlockNumber::from(1));
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::VecDeque;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+
+use display_json::DebugAsJson;
+
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::UnixTime;
+use crate::ext::MutexExt;
+
+/// Max number of transitions kept.
+const MAX_TRANSITIONS: usize = 128;
+
+/// Change in how the local chain is produced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainTransitionKind {
+    /// Node stopped importing blocks from the leader and started mining its own blocks.
+    #[strum(to_string = "promotion")]
+    Promotion,
+
+    /// Blocks after a block number were removed and will be produced again.
+    #[strum(to_string = "rollback")]
+    Rollback,
+}
+
+#[derive(DebugAsJson, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainTransition {
+    pub kind: ChainTransitionKind,
+
+    /// Last block kept before the transition.
+    pub number: BlockNumber,
+
+    /// Last block mined before the transition. Greater than `number` only for rollbacks.
+    pub previous_mined: BlockNumber,
+
+    pub at: UnixTime,
+}
+
+/// Recent promotions and rollbacks, so reads spanning them can be validated.
+#[derive(Debug, Default)]
+pub struct ChainTransitions {
+    transitions: Mutex<VecDeque<ChainTransition>>,
+
+    /// Incremented on every rollback, so reads split in multiple steps can detect a rollback between them.
+    rollback_version: AtomicU64,
+}
+
+impl ChainTransitions {
+    /// Records the promotion of the node to leader after the specified block.
+    pub fn record_promotion(&self, number: BlockNumber) {
+        tracing::info!(%number, "recording chain promotion");
+        self.push(ChainTransition {
+            kind: ChainTransitionKind::Promotion,
+            number,
+            previous_mined: number,
+            at: UnixTime::now(),
+        });
+    }
+
+    /// Records the removal of the blocks after `number` up to `previous_mined`.
+    pub fn record_rollback(&self, number: BlockNumber, previous_mined: BlockNumber) {
+        tracing::warn!(%number, %previous_mined, "recording chain rollback");
+        self.rollback_version.fetch_add(1, Ordering::AcqRel);
+        self.push(ChainTransition {
+            kind: ChainTransitionKind::Rollback,
+            number,
+            previous_mined,
+            at: UnixTime::now(),
+        });
+    }
+
+    /// Current rollback version.
+    pub fn rollback_version(&self) -> u64 {
+        self.rollback_version.load(Ordering::Acquire)
+    }
+
+    /// Fails if a rollback happened since the specified version was read.
+    pub fn check_rollback_since(&self, version: u64, from: BlockNumber, to: BlockNumber) -> Result<(), StratusError> {
+        if self.rollback_version() == version {
+            return Ok(());
+        }
+        let transitions = self.transitions.lock_or_clear("chain transitions lock was poisoned");
+        let boundary = transitions
+            .iter()
+            .rev()
+            .find(|transition| transition.kind == ChainTransitionKind::Rollback)
+            .map(|rollback| rollback.number)
+            .unwrap_or(from);
+        Err(StratusError::StorageRangeInconsistent { from, to, boundary })
+    }
+
+    /// Lists recorded transitions from the oldest to the newest.
+    pub fn list(&self) -> Vec<ChainTransition> {
+        self.transitions.lock_or_clear("chain transitions lock was poisoned").iter().cloned().collect()
+    }
+
+    /// Fails if the range (inclusive) straddles a rollback whose removed blocks were not produced again yet.
+    pub fn check_range(&self, from: BlockNumber, to: BlockNumber, mined: BlockNumber) -> Result<(), StratusError> {
+        let transitions = self.transitions.lock_or_clear("chain transitions lock was poisoned");
+        let unresolved = transitions.iter().rev().find(|transition| {
+            transition.kind == ChainTransitionKind::Rollback && from <= transition.number && transition.number < to && mined < transition.previous_mined
+        });
+        match unresolved {
+            Some(rollback) => Err(StratusError::StorageRangeInconsistent {
+                from,
+                to,
+                boundary: rollback.number,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Blocks after which the node was promoted that are inside the range (inclusive), excluding the last block of the range.
+    pub fn promotions_in_range(&self, from: BlockNumber, to: BlockNumber) -> Vec<BlockNumber> {
+        let transitions = self.transitions.lock_or_clear("chain transitions lock was poisoned");
+        transitions
+            .iter()
+            .filter(|transition| transition.kind == ChainTransitionKind::Promotion && from <= transition.number && transition.number < to)
+            .map(|transition| transition.number)
+            .collect()
+    }
+
+    fn push(&self, transition: ChainTransition) {
+        let mut transitions = self.transitions.lock_or_clear("chain transitions lock was poisoned");
+        if transitions.len() >= MAX_TRANSITIONS {
+            transitions.pop_front();
+        }
+        transitions.push_back(transition);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_straddling_unresolved_rollback_is_rejected() {
+        let transitions = ChainTransitions::default();
+        transitions.record_rollback(BlockNumber::from(10u64), BlockNumber::from(15u64));
+
+        // re-producing the removed blocks
+        let mined = BlockNumber::from(12u64);
+        assert!(transitions.check_range(BlockNumber::from(5u64), BlockNumber::from(12u64), mined).is_err());
+        assert!(transitions.check_range(BlockNumber::from(5u64), BlockNumber::from(10u64), mined).is_ok());
+        assert!(transitions.check_range(BlockNumber::from(11u64), BlockNumber::from(12u64), mined).is_ok());
+
+        // removed blocks produced again
+        let mined = BlockNumber::from(15u64);
+        assert!(transitions.check_range(BlockNumber::from(5u64), BlockNumber::from(15u64), mined).is_ok());
+    }
+}
@@ -0,0 +1,55 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::FeeHistoryBlock;
+use crate::ext::MutexExt;
+
+/// Max number of recent blocks kept in the fee history.
+pub const FEE_HISTORY_MAX_BLOCKS: usize = 1024;
+
+/// Keeps fee data of the most recent mined blocks so `eth_feeHistory` does not need to read full blocks from storage.
+#[derive(Debug, Default)]
+pub struct FeeHistoryAccumulator {
+    blocks: Mutex<VecDeque<FeeHistoryBlock>>,
+}
+
+impl FeeHistoryAccumulator {
+    /// Tracks the fee data of a newly mined block.
+    pub fn push(&self, entry: FeeHistoryBlock) {
+        let mut blocks = self.blocks.lock_or_clear("fee history lock was poisoned");
+
+        // discard entries that are no longer valid or contiguous with the new block
+        while let Some(last) = blocks.back() {
+            if last.number < entry.number && last.number.next_block_number() == entry.number {
+                break;
+            }
+            if last.number < entry.number {
+                blocks.clear();
+                break;
+            }
+            blocks.pop_back();
+        }
+
+        if blocks.len() >= FEE_HISTORY_MAX_BLOCKS {
+            blocks.pop_front();
+        }
+        blocks.push_back(entry);
+    }
+
+    /// Retrieves the fee data of a block if it is still being tracked.
+    pub fn get(&self, number: BlockNumber) -> Option<FeeHistoryBlock> {
+        let blocks = self.blocks.lock_or_clear("fee history lock was poisoned");
+        let first = blocks.front()?.number;
+        if number < first {
+            return None;
+        }
+        let index = (number.as_u64() - first.as_u64()) as usize;
+        blocks.get(index).cloned()
+    }
+
+    /// Discards all tracked blocks.
+    pub fn clear(&self) {
+        self.blocks.lock_or_clear("fee history lock was poisoned").clear();
+    }
+}
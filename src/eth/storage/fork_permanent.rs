@@ -0,0 +1,568 @@
+//! Fork mode: permanent storage backed by the state of a remote chain.
+//!
+//! Accounts, bytecodes and slots not found in the local permanent storage are fetched from a remote RPC at a pinned block and cached in
+//! memory, so stratus can run as a local fork of another chain without importing its state. Blocks mined locally are saved only in the
+//! local storage, which takes precedence over the remote state.
+//!
+//! The caches keep only the most recently used entries, so evicted values are fetched again when needed. The pinned block must be
+//! specified once local blocks exist, because blocks mined on top of one remote state are not valid on top of another.
+//!
+//! Only state is forked. Blocks, transactions and logs of the remote chain are not available, and the local chain keeps its own block
+//! numbers, so every point in time reads the remote state at the pinned block. Listing all accounts or slots returns only local state.
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::hash::Hash as HashTrait;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use indexmap::IndexMap;
+use tokio::runtime::Handle;
+
+use crate::eth::primitives::Account;
+use crate::eth::primitives::AccountStateDiff;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::CodeHash;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::LogFilter;
+use crate::eth::primitives::LogMined;
+#[cfg(feature = "dev")]
+use crate::eth::primitives::Nonce;
+use crate::eth::primitives::Pagination;
+use crate::eth::primitives::Slot;
+use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::SlotValue;
+use crate::eth::primitives::TransactionMined;
+#[cfg(feature = "dev")]
+use crate::eth::primitives::Wei;
+use crate::eth::storage::PermanentStorage;
+use crate::eth::storage::RetentionTarget;
+use crate::eth::storage::StoragePointInTime;
+use crate::ext::not;
+use crate::ext::MutexExt;
+use crate::infra::BlockchainClient;
+use crate::log_and_err;
+
+#[derive(Debug)]
+pub struct ForkConfig {
+    pub url: String,
+
+    /// Block whose state is forked. Defaults to the current block of the remote chain.
+    pub block_number: Option<BlockNumber>,
+
+    pub timeout: Duration,
+
+    /// Max number of entries in each cache of remote state.
+    pub cache_capacity: usize,
+}
+
+/// Source of the forked state.
+trait ForkSource: Send + Sync + 'static {
+    /// Fetches an account with its bytecode at a block.
+    fn fetch_account(&self, address: &Address, block_number: BlockNumber) -> anyhow::Result<Account>;
+
+    /// Fetches a slot value at a block.
+    fn fetch_slot(&self, address: &Address, index: &SlotIndex, block_number: BlockNumber) -> anyhow::Result<SlotValue>;
+}
+
+pub struct ForkPermanentStorage {
+    inner: Box<dyn PermanentStorage>,
+    source: Box<dyn ForkSource>,
+
+    /// Pinned block of the remote chain.
+    block_number: BlockNumber,
+
+    /// Remote accounts already fetched. `None` when the account is empty in the remote chain.
+    accounts: ForkCache<Address, Option<Account>>,
+
+    /// Remote slots already fetched.
+    slots: ForkCache<(Address, SlotIndex), SlotValue>,
+
+    /// Bytecodes of remote accounts already fetched.
+    codes: ForkCache<CodeHash, Bytes>,
+}
+
+impl ForkPermanentStorage {
+    /// Creates a new [`ForkPermanentStorage`] that falls back to the remote chain when state is not found in `inner`.
+    ///
+    /// It must be called from inside a Tokio runtime.
+    pub fn new(inner: Box<dyn PermanentStorage>, config: ForkConfig) -> anyhow::Result<Self> {
+        tracing::info!(?config, "creating fork permanent storage");
+
+        let source = RpcForkSource::new(&config)?;
+        let block_number = match config.block_number {
+            Some(block_number) => block_number,
+            None => {
+                // the remote head changes between restarts, so it can only be used before local blocks are mined on top of it
+                let mined_number = inner.read_mined_block_number()?;
+                if mined_number > BlockNumber::ZERO {
+                    return log_and_err!(
+                        payload = mined_number,
+                        "fork block number must be specified because local blocks were already mined on top of the forked state"
+                    );
+                }
+                source.block_on(source.client.fetch_block_number())?
+            }
+        };
+        tracing::info!(url = %config.url, %block_number, "forking remote chain state");
+
+        Ok(Self::with_source(inner, Box::new(source), block_number, config.cache_capacity))
+    }
+
+    fn with_source(inner: Box<dyn PermanentStorage>, source: Box<dyn ForkSource>, block_number: BlockNumber, cache_capacity: usize) -> Self {
+        Self {
+            inner,
+            source,
+            block_number,
+            accounts: ForkCache::new(cache_capacity),
+            slots: ForkCache::new(cache_capacity),
+            codes: ForkCache::new(cache_capacity),
+        }
+    }
+
+    /// Pinned block of the remote chain.
+    pub fn fork_block_number(&self) -> BlockNumber {
+        self.block_number
+    }
+
+    /// Reads an account from the remote chain, fetching it only if not cached.
+    fn read_remote_account(&self, address: &Address) -> anyhow::Result<Option<Account>> {
+        if let Some(account) = self.accounts.get(address) {
+            return Ok(account);
+        }
+
+        tracing::debug!(%address, block_number = %self.block_number, "fetching account from forked chain");
+        let account = self.source.fetch_account(address, self.block_number)?;
+        let account = if account.is_empty() { None } else { Some(account) };
+
+        if let Some(Account {
+            bytecode: Some(ref bytecode),
+            code_hash,
+            ..
+        }) = account
+        {
+            self.codes.insert(code_hash, bytecode.clone());
+        }
+        self.accounts.insert(*address, account.clone());
+        Ok(account)
+    }
+
+    /// Reads a slot from the remote chain, fetching it only if not cached.
+    fn read_remote_slot(&self, address: &Address, index: &SlotIndex) -> anyhow::Result<Option<Slot>> {
+        // only contracts have slots, so accounts without bytecode are not queried
+        let is_contract = self.read_remote_account(address)?.is_some_and(|account| account.is_contract());
+        if not(is_contract) {
+            return Ok(None);
+        }
+
+        let value = match self.slots.get(&(*address, *index)) {
+            Some(value) => value,
+            None => {
+                tracing::debug!(%address, %index, block_number = %self.block_number, "fetching slot from forked chain");
+                let value = self.source.fetch_slot(address, index, self.block_number)?;
+                self.slots.insert((*address, *index), value);
+                value
+            }
+        };
+
+        if value.is_zero() {
+            return Ok(None);
+        }
+        Ok(Some(Slot::new(*index, value)))
+    }
+
+    /// Copies a remote account to the local storage before it is changed locally.
+    ///
+    /// Local storages persist only the fields that changed, so the unchanged fields of a remote account would be lost otherwise.
+    fn materialize_account(&self, address: &Address) -> anyhow::Result<()> {
+        if self.inner.read_account(address, &StoragePointInTime::Mined)?.is_some() {
+            return Ok(());
+        }
+        if let Some(account) = self.read_remote_account(address)? {
+            tracing::debug!(%address, "copying forked account to local storage");
+            self.inner.save_accounts(vec![account])?;
+        }
+        Ok(())
+    }
+}
+
+impl PermanentStorage for ForkPermanentStorage {
+    // -------------------------------------------------------------------------
+    // Block number
+    // -------------------------------------------------------------------------
+
+    fn set_mined_block_number(&self, number: BlockNumber) -> anyhow::Result<()> {
+        self.inner.set_mined_block_number(number)
+    }
+
+    fn read_mined_block_number(&self) -> anyhow::Result<BlockNumber> {
+        self.inner.read_mined_block_number()
+    }
+
+    // -------------------------------------------------------------------------
+    // Block
+    // -------------------------------------------------------------------------
+
+    fn save_block(&self, block: Block) -> anyhow::Result<()> {
+        let addresses: HashSet<Address> = block.transactions.iter().flat_map(|tx| tx.execution.changes.keys().copied()).collect();
+        for address in &addresses {
+            self.materialize_account(address)?;
+        }
+        self.inner.save_block(block)
+    }
+
+    fn read_block(&self, block_filter: &BlockFilter) -> anyhow::Result<Option<Block>> {
+        self.inner.read_block(block_filter)
+    }
+
+    fn read_block_receipts(&self, block_filter: &BlockFilter) -> anyhow::Result<Option<Vec<TransactionMined>>> {
+        self.inner.read_block_receipts(block_filter)
+    }
+
+    fn read_block_state_diff(&self, number: BlockNumber) -> anyhow::Result<Option<Vec<AccountStateDiff>>> {
+        self.inner.read_block_state_diff(number)
+    }
+
+    fn read_transaction(&self, hash: &Hash) -> anyhow::Result<Option<TransactionMined>> {
+        self.inner.read_transaction(hash)
+    }
+
+    fn read_logs(&self, filter: &LogFilter) -> anyhow::Result<Vec<LogMined>> {
+        self.inner.read_logs(filter)
+    }
+
+    fn read_transactions_by_address(&self, address: &Address, from: BlockNumber, to: BlockNumber, pagination: Pagination) -> anyhow::Result<Vec<Hash>> {
+        self.inner.read_transactions_by_address(address, from, to, pagination)
+    }
+
+    fn prune_blocks(&self, target: RetentionTarget, from: BlockNumber, to: BlockNumber) -> anyhow::Result<()> {
+        self.inner.prune_blocks(target, from, to)
+    }
+
+    // -------------------------------------------------------------------------
+    // Publisher outbox
+    // -------------------------------------------------------------------------
+
+    fn read_publisher_cursor(&self, publisher: &str) -> anyhow::Result<Option<BlockNumber>> {
+        self.inner.read_publisher_cursor(publisher)
+    }
+
+    fn save_publisher_cursor(&self, publisher: &str, number: BlockNumber) -> anyhow::Result<()> {
+        self.inner.save_publisher_cursor(publisher, number)
+    }
+
+    // -------------------------------------------------------------------------
+    // Account and slots
+    // -------------------------------------------------------------------------
+
+    fn save_accounts(&self, accounts: Vec<Account>) -> anyhow::Result<()> {
+        self.inner.save_accounts(accounts)
+    }
+
+    fn read_account(&self, address: &Address, point_in_time: &StoragePointInTime) -> anyhow::Result<Option<Account>> {
+        match self.inner.read_account(address, point_in_time)? {
+            Some(account) => Ok(Some(account)),
+            None => self.read_remote_account(address),
+        }
+    }
+
+    fn read_code(&self, code_hash: &CodeHash) -> anyhow::Result<Option<Bytes>> {
+        match self.inner.read_code(code_hash)? {
+            Some(code) => Ok(Some(code)),
+            None => Ok(self.codes.get(code_hash)),
+        }
+    }
+
+    fn read_slot(&self, address: &Address, index: &SlotIndex, point_in_time: &StoragePointInTime) -> anyhow::Result<Option<Slot>> {
+        match self.inner.read_slot(address, index, point_in_time)? {
+            Some(slot) => Ok(Some(slot)),
+            None => self.read_remote_slot(address, index),
+        }
+    }
+
+    fn read_slots(&self, slots: &[(Address, SlotIndex)], point_in_time: &StoragePointInTime) -> anyhow::Result<Vec<(Address, Slot)>> {
+        let mut found = self.inner.read_slots(slots, point_in_time)?;
+
+        let found_locally: HashSet<(Address, SlotIndex)> = found.iter().map(|(address, slot)| (*address, slot.index)).collect();
+        for (address, index) in slots {
+            if found_locally.contains(&(*address, *index)) {
+                continue;
+            }
+            if let Some(slot) = self.read_remote_slot(address, index)? {
+                found.push((*address, slot));
+            }
+        }
+        Ok(found)
+    }
+
+    fn read_all_accounts(&self) -> anyhow::Result<Vec<Account>> {
+        self.inner.read_all_accounts()
+    }
+
+    fn read_all_slots(&self) -> anyhow::Result<Vec<(Address, Slot)>> {
+        self.inner.read_all_slots()
+    }
+
+    fn save_slots(&self, slots: Vec<(Address, Slot)>) -> anyhow::Result<()> {
+        self.inner.save_slots(slots)
+    }
+
+    // -------------------------------------------------------------------------
+    // Account and slots (dev)
+    // -------------------------------------------------------------------------
+
+    #[cfg(feature = "dev")]
+    fn set_balance(&self, address: &Address, balance: Wei) -> anyhow::Result<()> {
+        self.materialize_account(address)?;
+        self.inner.set_balance(address, balance)
+    }
+
+    #[cfg(feature = "dev")]
+    fn set_nonce(&self, address: &Address, nonce: Nonce) -> anyhow::Result<()> {
+        self.materialize_account(address)?;
+        self.inner.set_nonce(address, nonce)
+    }
+
+    #[cfg(feature = "dev")]
+    fn set_code(&self, address: &Address, code: Bytes) -> anyhow::Result<()> {
+        self.materialize_account(address)?;
+        self.inner.set_code(address, code)
+    }
+
+    #[cfg(feature = "dev")]
+    fn set_storage(&self, address: &Address, slot: Slot) -> anyhow::Result<()> {
+        self.materialize_account(address)?;
+        self.inner.set_storage(address, slot)
+    }
+
+    // -------------------------------------------------------------------------
+    // Global state
+    // -------------------------------------------------------------------------
+
+    #[cfg(feature = "dev")]
+    fn reset(&self) -> anyhow::Result<()> {
+        // the remote state is immutable at the pinned block, so cached values are still valid
+        self.inner.reset()
+    }
+
+    fn reset_at(&self, number: BlockNumber) -> anyhow::Result<()> {
+        self.inner.reset_at(number)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Cache
+// -----------------------------------------------------------------------------
+
+/// LRU cache of remote state.
+struct ForkCache<K, V> {
+    capacity: usize,
+
+    /// Cached values ordered from the least to the most recently used.
+    entries: Mutex<IndexMap<K, V>>,
+}
+
+impl<K: HashTrait + Eq, V: Clone> ForkCache<K, V> {
+    /// Creates a cache holding at most `capacity` values. A capacity of zero disables the cache.
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::default(),
+        }
+    }
+
+    /// Retrieves a cached value, marking it as the most recently used.
+    fn get(&self, key: &K) -> Option<V> {
+        let mut entries = self.entries.lock_or_clear("fork cache lock was poisoned");
+        let index = entries.get_index_of(key)?;
+        let last = entries.len() - 1;
+        entries.move_index(index, last);
+        entries.get_index(last).map(|(_, value)| value.clone())
+    }
+
+    /// Caches a value, evicting the least recently used value if full.
+    fn insert(&self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock_or_clear("fork cache lock was poisoned");
+        if entries.len() >= self.capacity && not(entries.contains_key(&key)) {
+            entries.shift_remove_index(0);
+        }
+        entries.insert(key, value);
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Remote RPC
+// -----------------------------------------------------------------------------
+
+struct RpcForkSource {
+    client: BlockchainClient,
+
+    /// Runtime used to execute requests, because the storage interface is synchronous.
+    runtime: Handle,
+}
+
+impl RpcForkSource {
+    fn new(config: &ForkConfig) -> anyhow::Result<Self> {
+        let runtime = match Handle::try_current() {
+            Ok(runtime) => runtime,
+            Err(e) => return log_and_err!(reason = e, "fork permanent storage must be created inside a tokio runtime"),
+        };
+        let client = tokio::task::block_in_place(|| runtime.block_on(BlockchainClient::new_http(&config.url, config.timeout)))?;
+        Ok(Self { client, runtime })
+    }
+
+    /// Executes a request future from synchronous code, whether or not it is running inside the runtime.
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        match Handle::try_current() {
+            Ok(_) => tokio::task::block_in_place(|| self.runtime.block_on(future)),
+            Err(_) => self.runtime.block_on(future),
+        }
+    }
+}
+
+impl ForkSource for RpcForkSource {
+    fn fetch_account(&self, address: &Address, block_number: BlockNumber) -> anyhow::Result<Account> {
+        let block_number = Some(block_number);
+        let (balance, nonce, code) = self.block_on(async {
+            tokio::try_join!(
+                self.client.fetch_balance(address, block_number),
+                self.client.fetch_nonce(address, block_number),
+                self.client.fetch_code(address, block_number),
+            )
+        })?;
+
+        let bytecode = if code.is_empty() { None } else { Some(code) };
+        Ok(Account {
+            address: *address,
+            nonce,
+            balance,
+            code_hash: CodeHash::from_bytecode(bytecode.clone()),
+            bytecode,
+        })
+    }
+
+    fn fetch_slot(&self, address: &Address, index: &SlotIndex, block_number: BlockNumber) -> anyhow::Result<SlotValue> {
+        self.block_on(self.client.fetch_storage_at(address, index, Some(block_number)))
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::eth::primitives::Nonce;
+    use crate::eth::primitives::Wei;
+    use crate::eth::storage::InMemoryPermanentStorage;
+
+    /// Remote chain with a single contract, counting requests.
+    struct FakeSource {
+        contract: Account,
+        requests: Arc<AtomicUsize>,
+    }
+
+    impl ForkSource for FakeSource {
+        fn fetch_account(&self, address: &Address, _: BlockNumber) -> anyhow::Result<Account> {
+            self.requests.fetch_add(1, Ordering::Relaxed);
+            if *address == self.contract.address {
+                Ok(self.contract.clone())
+            } else {
+                Ok(Account::new_empty(*address))
+            }
+        }
+
+        fn fetch_slot(&self, address: &Address, index: &SlotIndex, _: BlockNumber) -> anyhow::Result<SlotValue> {
+            self.requests.fetch_add(1, Ordering::Relaxed);
+            if *address == self.contract.address && *index == SlotIndex::ONE {
+                Ok(SlotValue::from(7u64))
+            } else {
+                Ok(SlotValue::default())
+            }
+        }
+    }
+
+    #[test]
+    fn fork_storage_falls_back_to_remote_and_caches() {
+        let bytecode = Bytes(vec![0x60, 0x00]);
+        let contract = Account {
+            address: Address::from([1; 20]),
+            nonce: Nonce::from(1u64),
+            balance: Wei::from(100u64),
+            code_hash: CodeHash::from_bytecode(Some(bytecode.clone())),
+            bytecode: Some(bytecode.clone()),
+        };
+        let requests = Arc::new(AtomicUsize::new(0));
+        let source = FakeSource {
+            contract: contract.clone(),
+            requests: Arc::clone(&requests),
+        };
+        let storage = ForkPermanentStorage::with_source(Box::<InMemoryPermanentStorage>::default(), Box::new(source), BlockNumber::from(10u64), 100);
+        let mined = StoragePointInTime::Mined;
+
+        // remote account, bytecode and slots are fetched once
+        assert_eq!(storage.read_account(&contract.address, &mined).unwrap(), Some(contract.clone()));
+        assert_eq!(storage.read_code(&contract.code_hash).unwrap(), Some(bytecode));
+        assert_eq!(
+            storage.read_slot(&contract.address, &SlotIndex::ONE, &mined).unwrap().unwrap().value,
+            SlotValue::from(7u64)
+        );
+        assert_eq!(
+            storage.read_slot(&contract.address, &SlotIndex::ONE, &mined).unwrap().unwrap().value,
+            SlotValue::from(7u64)
+        );
+        assert!(storage.read_slot(&contract.address, &SlotIndex::ZERO, &mined).unwrap().is_none());
+        assert_eq!(requests.load(Ordering::Relaxed), 3);
+
+        // slots of accounts without bytecode are not fetched
+        let eoa = Address::from([2; 20]);
+        assert!(storage.read_account(&eoa, &mined).unwrap().is_none());
+        assert!(storage.read_slot(&eoa, &SlotIndex::ONE, &mined).unwrap().is_none());
+        assert_eq!(requests.load(Ordering::Relaxed), 4);
+
+        // local state takes precedence
+        storage
+            .save_slots(vec![(contract.address, Slot::new(SlotIndex::ONE, SlotValue::from(8u64)))])
+            .unwrap();
+        let slots = storage.read_slots(&[(contract.address, SlotIndex::ONE)], &mined).unwrap();
+        assert_eq!(slots, vec![(contract.address, Slot::new(SlotIndex::ONE, SlotValue::from(8u64)))]);
+    }
+
+    #[test]
+    fn fork_storage_evicts_least_recently_used() {
+        let contract = Account::new_empty(Address::from([1; 20]));
+        let requests = Arc::new(AtomicUsize::new(0));
+        let source = FakeSource {
+            contract,
+            requests: Arc::clone(&requests),
+        };
+        let storage = ForkPermanentStorage::with_source(Box::<InMemoryPermanentStorage>::default(), Box::new(source), BlockNumber::from(10u64), 2);
+        let mined = StoragePointInTime::Mined;
+
+        let [first, second, third] = [Address::from([2; 20]), Address::from([3; 20]), Address::from([4; 20])];
+        storage.read_account(&first, &mined).unwrap();
+        storage.read_account(&second, &mined).unwrap();
+        storage.read_account(&first, &mined).unwrap();
+        assert_eq!(requests.load(Ordering::Relaxed), 2);
+
+        // the least recently used account is evicted and fetched again
+        storage.read_account(&third, &mined).unwrap();
+        storage.read_account(&first, &mined).unwrap();
+        assert_eq!(requests.load(Ordering::Relaxed), 3);
+        storage.read_account(&second, &mined).unwrap();
+        assert_eq!(requests.load(Ordering::Relaxed), 4);
+    }
+}
@@ -0,0 +1,312 @@
+//! Genesis configuration loaded from a geth-style `genesis.json` file.
+//!
+//! Only the fields that are meaningful to Stratus are read, so files produced for geth or other clients can be used as is:
+//!
+//! ```json
+//! {
+//!   "config": { "chainId": 2008 },
+//!   "timestamp": "0x65c0e8bc",
+//!   "gasLimit": "0x3b9aca00",
+//!   "extraData": "0x",
+//!   "coinbase": "0x0000000000000000000000000000000000000000",
+//!   "alloc": {
+//!     "f39fd6e51aad88f6f4ce6ab8827279cfffb92266": { "balance": "0xffffffffffffffff" },
+//!     "0x5fbdb2315678afecb367f032d93f642f64180aa3": {
+//!       "balance": "0",
+//!       "nonce": "0x1",
+//!       "code": "0x6080...",
+//!       "storage": { "0x0": "0x2a" }
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! Quantities can be hexadecimal strings prefixed with `0x`, decimal strings or JSON numbers.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use anyhow::Context;
+use ethereum_types::U256;
+
+use crate::eth::primitives::Account;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::ChainId;
+use crate::eth::primitives::CodeHash;
+use crate::eth::primitives::Gas;
+use crate::eth::primitives::Slot;
+use crate::eth::primitives::UnixTime;
+
+/// Initial state and genesis block parameters of a chain.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GenesisConfig {
+    /// Chain ID declared in the file, if any.
+    pub chain_id: Option<ChainId>,
+
+    /// Timestamp of the genesis block.
+    pub timestamp: UnixTime,
+
+    /// Gas limit of the genesis block.
+    pub gas_limit: Gas,
+
+    /// Extra data of the genesis block.
+    pub extra_data: Bytes,
+
+    /// Miner of the genesis block.
+    pub coinbase: Address,
+
+    /// Accounts allocated at genesis.
+    pub accounts: Vec<Account>,
+
+    /// Slots allocated at genesis with the address of the account they belong to.
+    pub slots: Vec<(Address, Slot)>,
+}
+
+impl GenesisConfig {
+    /// Loads and validates a genesis file.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        tracing::info!(path = %path.display(), "loading genesis file");
+        let json = fs::read_to_string(path).with_context(|| format!("failed to read genesis file {}", path.display()))?;
+        Self::from_json(&json).with_context(|| format!("failed to parse genesis file {}", path.display()))
+    }
+
+    /// Parses and validates the contents of a genesis file.
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let file: GenesisFile = serde_json::from_str(json)?;
+
+        let mut accounts = Vec::with_capacity(file.alloc.len());
+        let mut slots = Vec::new();
+        for (address, alloc) in file.alloc {
+            let address = Address::from_str(&address).with_context(|| format!("invalid alloc address {}", address))?;
+
+            let bytecode = alloc.code.filter(|code| !code.is_empty());
+            accounts.push(Account {
+                address,
+                nonce: alloc.nonce.map(|nonce| nonce.to_u64()).transpose()?.unwrap_or_default().into(),
+                balance: alloc.balance.map(|balance| balance.to_u256()).transpose()?.unwrap_or_default().into(),
+                code_hash: CodeHash::from_bytecode(bytecode.clone()),
+                bytecode,
+            });
+
+            for (index, value) in alloc.storage {
+                let index = parse_u256(&index).with_context(|| format!("invalid storage index {} of {}", index, address))?;
+                let value = parse_u256(&value).with_context(|| format!("invalid storage value {} of {}", value, address))?;
+                slots.push((address, Slot::new(index.into(), value.into())));
+            }
+        }
+
+        Ok(Self {
+            chain_id: file.config.chain_id.map(ChainId::from),
+            timestamp: file.timestamp.map(|timestamp| timestamp.to_u64()).transpose()?.unwrap_or_default().into(),
+            gas_limit: file.gas_limit.map(|gas_limit| gas_limit.to_u64()).transpose()?.unwrap_or_default().into(),
+            extra_data: file.extra_data.unwrap_or_default(),
+            coinbase: file.coinbase.unwrap_or_default(),
+            accounts,
+            slots,
+        })
+    }
+
+    /// Creates the genesis block.
+    pub fn to_block(&self) -> Block {
+        let mut block = Block::new(BlockNumber::ZERO, self.timestamp);
+        block.header.gas_limit = self.gas_limit;
+        block.header.extra_data = self.extra_data.clone();
+        block.header.author = self.coinbase;
+        block.header.miner = self.coinbase;
+        block.update_hash();
+        block
+    }
+}
+
+// -----------------------------------------------------------------------------
+// File format
+// -----------------------------------------------------------------------------
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GenesisFile {
+    #[serde(default)]
+    config: GenesisFileChainConfig,
+
+    timestamp: Option<GenesisQuantity>,
+
+    gas_limit: Option<GenesisQuantity>,
+
+    extra_data: Option<Bytes>,
+
+    coinbase: Option<Address>,
+
+    #[serde(default)]
+    alloc: BTreeMap<String, GenesisFileAlloc>,
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GenesisFileChainConfig {
+    chain_id: Option<u64>,
+}
+
+#[derive(serde::Deserialize)]
+struct GenesisFileAlloc {
+    balance: Option<GenesisQuantity>,
+
+    nonce: Option<GenesisQuantity>,
+
+    code: Option<Bytes>,
+
+    #[serde(default)]
+    storage: BTreeMap<String, String>,
+}
+
+/// Numeric value that can be written as a JSON number or as a hexadecimal or decimal string.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum GenesisQuantity {
+    Number(u64),
+    String(String),
+}
+
+impl GenesisQuantity {
+    fn to_u256(&self) -> anyhow::Result<U256> {
+        match self {
+            Self::Number(value) => Ok(U256::from(*value)),
+            Self::String(value) => parse_u256(value),
+        }
+    }
+
+    fn to_u64(&self) -> anyhow::Result<u64> {
+        let value = self.to_u256()?;
+        if value > U256::from(u64::MAX) {
+            return Err(anyhow!("quantity {} does not fit in 64 bits", value));
+        }
+        Ok(value.as_u64())
+    }
+}
+
+/// Parses a hexadecimal string prefixed with `0x` or a decimal string.
+pub(super) fn parse_u256(value: &str) -> anyhow::Result<U256> {
+    let value = value.trim();
+    match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some("") => Ok(U256::zero()),
+        Some(hex) => Ok(U256::from_str_radix(hex, 16)?),
+        None => Ok(U256::from_dec_str(value)?),
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::primitives::BlockFilter;
+    use crate::eth::primitives::SlotIndex;
+    use crate::eth::primitives::SlotValue;
+    use crate::eth::primitives::Wei;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::InMemoryTemporaryStorage;
+    use crate::eth::storage::StoragePointInTime;
+    use crate::eth::storage::StratusStorage;
+
+    const GENESIS: &str = r#"{
+        "config": { "chainId": 2008, "londonBlock": 0 },
+        "nonce": "0x0",
+        "timestamp": "0x65c0e8bc",
+        "gasLimit": 1000000000,
+        "difficulty": "0x1",
+        "extraData": "0x1234",
+        "alloc": {
+            "f39fd6e51aad88f6f4ce6ab8827279cfffb92266": { "balance": "1000000000000000000" },
+            "0x5fbdb2315678afecb367f032d93f642f64180aa3": {
+                "balance": "0x0",
+                "nonce": "0x1",
+                "code": "0x602a60005260206000f3",
+                "storage": { "0x0": "0x2a", "0x0000000000000000000000000000000000000000000000000000000000000001": "7" }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn genesis_config_parses_geth_file() {
+        let genesis = GenesisConfig::from_json(GENESIS).unwrap();
+        assert_eq!(genesis.chain_id, Some(ChainId::from(2008u64)));
+        assert_eq!(genesis.timestamp, UnixTime::from(0x65c0e8bcu64));
+        assert_eq!(genesis.gas_limit, Gas::from(1_000_000_000u64));
+        assert_eq!(genesis.extra_data, Bytes(vec![0x12, 0x34]));
+
+        // accounts
+        let eoa = Address::from_str("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266").unwrap();
+        let contract = Address::from_str("0x5fbdb2315678afecb367f032d93f642f64180aa3").unwrap();
+        assert_eq!(genesis.accounts.len(), 2);
+
+        let eoa_account = genesis.accounts.iter().find(|account| account.address == eoa).unwrap();
+        assert_eq!(eoa_account.balance, Wei::from(U256::exp10(18)));
+        assert!(eoa_account.bytecode.is_none());
+
+        let contract_account = genesis.accounts.iter().find(|account| account.address == contract).unwrap();
+        assert_eq!(contract_account.nonce.as_u64(), 1);
+        assert_eq!(
+            contract_account.bytecode,
+            Some(Bytes(vec![0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3]))
+        );
+        assert_eq!(contract_account.code_hash, CodeHash::from_bytecode(contract_account.bytecode.clone()));
+
+        // slots
+        assert_eq!(genesis.slots.len(), 2);
+        assert!(genesis.slots.contains(&(contract, Slot::new(SlotIndex::ZERO, SlotValue::from(42u64)))));
+        assert!(genesis.slots.contains(&(contract, Slot::new(SlotIndex::ONE, SlotValue::from(7u64)))));
+
+        // block
+        let block = genesis.to_block();
+        assert_eq!(block.number(), BlockNumber::ZERO);
+        assert_eq!(block.header.timestamp, genesis.timestamp);
+        assert_eq!(block.header.gas_limit, genesis.gas_limit);
+    }
+
+    #[test]
+    fn genesis_config_rejects_invalid_values() {
+        assert!(GenesisConfig::from_json(r#"{ "alloc": { "0xinvalid": { "balance": "0x1" } } }"#).is_err());
+        assert!(GenesisConfig::from_json(r#"{ "alloc": { "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266": { "balance": "abc" } } }"#).is_err());
+        assert!(GenesisConfig::from_json(r#"{ "timestamp": "0x10000000000000000" }"#).is_err());
+    }
+
+    #[test]
+    fn genesis_config_creates_storage_genesis() {
+        let genesis = GenesisConfig::load(Path::new("config/genesis.local.json")).unwrap();
+        let contract = Address::from_str("0x5fbdb2315678afecb367f032d93f642f64180aa3").unwrap();
+
+        let storage = StratusStorage::new_with_options(
+            Some(Box::<InMemoryTemporaryStorage>::default()),
+            Box::<InMemoryPermanentStorage>::default(),
+            None,
+            Some(genesis.clone()),
+        )
+        .unwrap();
+
+        // block
+        let block = storage.read_block(&BlockFilter::Number(BlockNumber::ZERO)).unwrap().unwrap();
+        assert_eq!(block.header.timestamp, genesis.timestamp);
+        assert_eq!(block.header.gas_limit, genesis.gas_limit);
+        assert_eq!(storage.read_mined_block_number().unwrap(), BlockNumber::ZERO);
+
+        // state
+        for expected in &genesis.accounts {
+            let account = storage.read_account(&expected.address, &StoragePointInTime::Mined).unwrap();
+            assert_eq!(&account, expected);
+        }
+        let slot = storage.read_slot(&contract, &SlotIndex::ZERO, &StoragePointInTime::Mined).unwrap();
+        assert_eq!(slot.value, SlotValue::from(42u64));
+    }
+
+    #[test]
+    fn genesis_config_accepts_empty_file() {
+        assert_eq!(GenesisConfig::from_json("{}").unwrap(), GenesisConfig::default());
+    }
+}
@@ -1,6 +1,10 @@
 //! In-memory storage implementations.
 
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::hash::Hash as _;
+use std::hash::Hasher;
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
@@ -12,6 +16,7 @@ use indexmap::IndexMap;
 use itertools::Itertools;
 
 use crate::eth::primitives::Account;
+use crate::eth::primitives::AccountStateDiff;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockFilter;
@@ -19,32 +24,72 @@ use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::Bytes;
 use crate::eth::primitives::CodeHash;
 use crate::eth::primitives::Hash;
+use crate::eth::primitives::Index;
 use crate::eth::primitives::LogFilter;
 use crate::eth::primitives::LogMined;
 use crate::eth::primitives::Nonce;
+use crate::eth::primitives::Pagination;
 use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
 use crate::eth::primitives::TransactionMined;
 use crate::eth::primitives::Wei;
 use crate::eth::storage::inmemory::InMemoryHistory;
 use crate::eth::storage::PermanentStorage;
+use crate::eth::storage::RetentionTarget;
 use crate::eth::storage::StoragePointInTime;
+use crate::ext::not;
+
+/// Accounts of one partition of the in-memory permanent storage.
+type InMemoryAccounts = HashMap<Address, InMemoryPermanentAccount, hash_hasher::HashBuildHasher>;
+
+/// Bytecodes of all accounts indexed by their hash.
+type InMemoryCodes = HashMap<CodeHash, Bytes, hash_hasher::HashBuildHasher>;
 
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct InMemoryPermanentStorageState {
-    pub accounts: HashMap<Address, InMemoryPermanentAccount, hash_hasher::HashBuildHasher>,
     pub transactions: HashMap<Hash, Arc<Block>, hash_hasher::HashBuildHasher>,
     pub blocks_by_number: IndexMap<BlockNumber, Arc<Block>>,
     pub blocks_by_hash: IndexMap<Hash, Arc<Block>>,
+
+    /// Hashes of the transactions of each address, ordered by block and position in the block.
+    #[serde(default)]
+    pub transactions_by_address: HashMap<Address, BTreeMap<(BlockNumber, Index), Hash>, hash_hasher::HashBuildHasher>,
+
+    /// Last block delivered by each publisher.
+    #[serde(default)]
+    pub publisher_cursors: HashMap<String, BlockNumber>,
 }
 
+/// In-memory permanent storage.
+///
+/// Accounts and their slots are partitioned in shards by address hash, each one with its own lock, so reads of different accounts do not
+/// contend with each other. Blocks and transactions are kept behind a separate lock.
+///
+/// Accounts keep only the history of their code hash, and bytecodes are kept once by hash, so accounts with the same bytecode share it.
+///
+/// Changes of a block are applied to each shard separately, so reads of the mined state can briefly see only part of a block being saved.
 #[derive(Debug)]
 pub struct InMemoryPermanentStorage {
     state: RwLock<InMemoryPermanentStorageState>,
+    shards: Vec<RwLock<InMemoryAccounts>>,
+    codes: RwLock<InMemoryCodes>,
     block_number: AtomicU64,
 }
 
 impl InMemoryPermanentStorage {
+    /// Default number of account shards.
+    pub const DEFAULT_SHARDS: usize = 16;
+
+    pub fn new(shards: usize) -> Self {
+        tracing::info!(%shards, "creating inmemory permanent storage");
+        Self {
+            state: RwLock::new(InMemoryPermanentStorageState::default()),
+            shards: (0..shards.max(1)).map(|_| RwLock::new(InMemoryAccounts::default())).collect(),
+            codes: RwLock::new(InMemoryCodes::default()),
+            block_number: AtomicU64::default(),
+        }
+    }
+
     // -------------------------------------------------------------------------
     // Lock methods
     // -------------------------------------------------------------------------
@@ -59,6 +104,49 @@ impl InMemoryPermanentStorage {
         self.state.write().unwrap()
     }
 
+    /// Index of the shard where the account is stored.
+    fn shard_index(&self, address: &Address) -> usize {
+        let mut hasher = DefaultHasher::new();
+        address.hash(&mut hasher);
+        (hasher.finish() % self.shards.len() as u64) as usize
+    }
+
+    /// Locks the shard of the account for reading.
+    fn lock_shard_read(&self, address: &Address) -> RwLockReadGuard<'_, InMemoryAccounts> {
+        self.shards[self.shard_index(address)].read().unwrap()
+    }
+
+    /// Locks the shard of the account for writing.
+    fn lock_shard_write(&self, address: &Address) -> RwLockWriteGuard<'_, InMemoryAccounts> {
+        self.shards[self.shard_index(address)].write().unwrap()
+    }
+
+    /// Locks all shards for writing, always in the same order.
+    fn lock_shards_write(&self) -> Vec<RwLockWriteGuard<'_, InMemoryAccounts>> {
+        self.shards.iter().map(|shard| shard.write().unwrap()).collect()
+    }
+
+    // -------------------------------------------------------------------------
+    // Code methods
+    // -------------------------------------------------------------------------
+
+    /// Indexes a bytecode by its hash, returning the hash.
+    fn save_code(&self, bytecode: Bytes) -> CodeHash {
+        let code_hash = CodeHash::from_bytecode(Some(bytecode.clone()));
+        self.codes.write().unwrap().entry(code_hash).or_insert(bytecode);
+        code_hash
+    }
+
+    /// Fills the bytecode of an account read from a shard.
+    ///
+    /// Must be called after the shard lock is released, so the codes lock is never held together with it.
+    fn with_code(&self, mut account: Account) -> Account {
+        if account.code_hash != CodeHash::default() {
+            account.bytecode = self.codes.read().unwrap().get(&account.code_hash).cloned();
+        }
+        account
+    }
+
     // -------------------------------------------------------------------------
     // State methods
     // -------------------------------------------------------------------------
@@ -66,20 +154,21 @@ impl InMemoryPermanentStorage {
     /// Clears in-memory state.
     pub fn clear(&self) {
         let mut state = self.lock_write();
-        state.accounts.clear();
+        for mut shard in self.lock_shards_write() {
+            shard.clear();
+        }
         state.transactions.clear();
         state.blocks_by_hash.clear();
         state.blocks_by_number.clear();
+        state.transactions_by_address.clear();
+        state.publisher_cursors.clear();
+        self.codes.write().unwrap().clear();
     }
 }
 
 impl Default for InMemoryPermanentStorage {
     fn default() -> Self {
-        tracing::info!("creating inmemory permanent storage");
-        Self {
-            state: RwLock::new(InMemoryPermanentStorageState::default()),
-            block_number: AtomicU64::default(),
-        }
+        Self::new(Self::DEFAULT_SHARDS)
     }
 }
 
@@ -102,21 +191,18 @@ impl PermanentStorage for InMemoryPermanentStorage {
     // -------------------------------------------------------------------------
 
     fn read_account(&self, address: &Address, point_in_time: &StoragePointInTime) -> anyhow::Result<Option<Account>> {
-        let state = self.lock_read();
+        let account = self.lock_shard_read(address).get(address).map(|account| account.to_account(point_in_time));
+        Ok(account.map(|account| self.with_code(account)))
+    }
 
-        match state.accounts.get(address) {
-            Some(inmemory_account) => {
-                let account = inmemory_account.to_account(point_in_time);
-                Ok(Some(account))
-            }
-            None => Ok(None),
-        }
+    fn read_code(&self, code_hash: &CodeHash) -> anyhow::Result<Option<Bytes>> {
+        Ok(self.codes.read().unwrap().get(code_hash).cloned())
     }
 
     fn read_slot(&self, address: &Address, index: &SlotIndex, point_in_time: &StoragePointInTime) -> anyhow::Result<Option<Slot>> {
-        let state = self.lock_read();
+        let accounts = self.lock_shard_read(address);
 
-        let Some(account) = state.accounts.get(address) else {
+        let Some(account) = accounts.get(address) else {
             return Ok(None);
         };
 
@@ -129,6 +215,28 @@ impl PermanentStorage for InMemoryPermanentStorage {
         }
     }
 
+    fn read_all_accounts(&self) -> anyhow::Result<Vec<Account>> {
+        let mut accounts = Vec::new();
+        for shard in &self.shards {
+            let shard = shard.read().unwrap();
+            accounts.extend(shard.values().map(|account| account.to_account(&StoragePointInTime::Mined)));
+        }
+        Ok(accounts.into_iter().map(|account| self.with_code(account)).collect())
+    }
+
+    fn read_all_slots(&self) -> anyhow::Result<Vec<(Address, Slot)>> {
+        let mut slots = Vec::new();
+        for shard in &self.shards {
+            let shard = shard.read().unwrap();
+            slots.extend(
+                shard
+                    .values()
+                    .flat_map(|account| account.slots.values().map(|slot| (account.address, slot.get_current()))),
+            );
+        }
+        Ok(slots)
+    }
+
     fn read_block(&self, selection: &BlockFilter) -> anyhow::Result<Option<Block>> {
         let state_lock = self.lock_read();
         let block = match selection {
@@ -143,6 +251,22 @@ impl PermanentStorage for InMemoryPermanentStorage {
         }
     }
 
+    fn read_block_receipts(&self, selection: &BlockFilter) -> anyhow::Result<Option<Vec<TransactionMined>>> {
+        let state_lock = self.lock_read();
+        let block = match selection {
+            BlockFilter::Latest | BlockFilter::Pending => state_lock.blocks_by_number.values().last(),
+            BlockFilter::Earliest => state_lock.blocks_by_number.values().next(),
+            BlockFilter::Number(block_number) => state_lock.blocks_by_number.get(block_number),
+            BlockFilter::Hash(block_hash) => state_lock.blocks_by_hash.get(block_hash),
+        };
+        Ok(block.map(|block| block.transactions.clone()))
+    }
+
+    fn read_block_state_diff(&self, number: BlockNumber) -> anyhow::Result<Option<Vec<AccountStateDiff>>> {
+        // blocks are kept with the execution changes of their transactions
+        Ok(self.read_block(&BlockFilter::Number(number))?.map(|block| block.state_diff()))
+    }
+
     fn read_transaction(&self, hash: &Hash) -> anyhow::Result<Option<TransactionMined>> {
         let state_lock = self.lock_read();
         let Some(block) = state_lock.transactions.get(hash) else { return Ok(None) };
@@ -166,14 +290,88 @@ impl PermanentStorage for InMemoryPermanentStorage {
             let Some(block) = state.blocks_by_number.get(&block_number) else {
                 continue;
             };
+            if not(filter.matches_bloom(&block.header.bloom)) {
+                continue;
+            }
 
-            let tx_logs = block.transactions.iter().flat_map(|tx| &tx.logs).filter(|log| filter.matches(log));
+            let tx_logs = block
+                .transactions
+                .iter()
+                .filter(|tx| filter.matches_bloom(&tx.logs_bloom))
+                .flat_map(|tx| &tx.logs)
+                .filter(|log| filter.matches(log));
             filtered_logs.extend(tx_logs);
         }
 
         Ok(filtered_logs.into_iter().cloned().collect_vec())
     }
 
+    fn read_transactions_by_address(&self, address: &Address, from: BlockNumber, to: BlockNumber, pagination: Pagination) -> anyhow::Result<Vec<Hash>> {
+        if from > to {
+            return Ok(Vec::new());
+        }
+
+        let state = self.lock_read();
+        let Some(transactions) = state.transactions_by_address.get(address) else {
+            return Ok(Vec::new());
+        };
+        let in_range = transactions.range((from, Index::ZERO)..=(to, Index::MAX)).map(|(_, hash)| *hash);
+        Ok(pagination.apply(in_range))
+    }
+
+    fn prune_blocks(&self, target: RetentionTarget, from: BlockNumber, to: BlockNumber) -> anyhow::Result<()> {
+        let mut state = self.lock_write();
+
+        for block_number in from.as_u64()..=to.as_u64() {
+            let Some(block) = state.blocks_by_number.get(&BlockNumber::from(block_number)) else {
+                continue;
+            };
+            let mut block = Block::clone(block);
+            let tx_hashes = block.transactions.iter().map(|tx| tx.input.hash).collect_vec();
+            let tx_keys = block
+                .transactions
+                .iter()
+                .map(|tx| (tx.indexed_addresses(), (tx.block_number, tx.transaction_index)))
+                .collect_vec();
+            if not(target.prune_block(&mut block)) {
+                continue;
+            }
+
+            // pruned transactions are not indexed by address anymore
+            if target == RetentionTarget::Receipts {
+                for (addresses, key) in tx_keys {
+                    for address in addresses {
+                        if let Some(transactions) = state.transactions_by_address.get_mut(&address) {
+                            transactions.remove(&key);
+                        }
+                    }
+                }
+            }
+
+            // replace all references to the block
+            let block = Arc::new(block);
+            for tx_hash in tx_hashes {
+                match target {
+                    RetentionTarget::Receipts => state.transactions.remove(&tx_hash),
+                    RetentionTarget::Logs => state.transactions.insert(tx_hash, Arc::clone(&block)),
+                };
+            }
+            state.blocks_by_hash.insert(block.hash(), Arc::clone(&block));
+            state.blocks_by_number.insert(block.number(), block);
+        }
+
+        Ok(())
+    }
+
+    fn read_publisher_cursor(&self, publisher: &str) -> anyhow::Result<Option<BlockNumber>> {
+        Ok(self.lock_read().publisher_cursors.get(publisher).copied())
+    }
+
+    fn save_publisher_cursor(&self, publisher: &str, number: BlockNumber) -> anyhow::Result<()> {
+        self.lock_write().publisher_cursors.insert(publisher.to_owned(), number);
+        Ok(())
+    }
+
     fn save_block(&self, block: Block) -> anyhow::Result<()> {
         let mut state = self.lock_write();
 
@@ -186,12 +384,21 @@ impl PermanentStorage for InMemoryPermanentStorage {
         // save transactions
         for tx in &block.transactions {
             state.transactions.insert(tx.input.hash, Arc::clone(&block));
+            for address in tx.indexed_addresses() {
+                state
+                    .transactions_by_address
+                    .entry(address)
+                    .or_default()
+                    .insert((block_number, tx.transaction_index), tx.input.hash);
+            }
         }
 
         // save block account changes
+        // the block lock is released first, so reads of blocks and accounts are not blocked while shards are updated
+        drop(state);
         for changes in block.compact_account_changes() {
-            let account = state
-                .accounts
+            let mut accounts = self.lock_shard_write(&changes.address);
+            let account = accounts
                 .entry(changes.address)
                 .or_insert_with(|| InMemoryPermanentAccount::new_empty(changes.address));
 
@@ -205,7 +412,7 @@ impl PermanentStorage for InMemoryPermanentStorage {
 
             // bytecode
             if let Some(Some(bytecode)) = changes.bytecode.take_modified() {
-                account.bytecode.push(block_number, Some(bytecode));
+                account.code_hash.push(block_number, self.save_code(bytecode));
             }
 
             // slots
@@ -227,11 +434,63 @@ impl PermanentStorage for InMemoryPermanentStorage {
     }
 
     fn save_accounts(&self, accounts: Vec<Account>) -> anyhow::Result<()> {
-        let mut state = self.lock_write();
-        for account in accounts {
-            state
-                .accounts
-                .insert(account.address, InMemoryPermanentAccount::new_with_balance(account.address, account.balance));
+        for mut account in accounts {
+            if let Some(bytecode) = account.bytecode.take() {
+                account.code_hash = self.save_code(bytecode);
+            }
+            self.lock_shard_write(&account.address)
+                .insert(account.address, InMemoryPermanentAccount::new_from_account(account));
+        }
+        Ok(())
+    }
+
+    fn save_slots(&self, slots: Vec<(Address, Slot)>) -> anyhow::Result<()> {
+        for (address, slot) in slots {
+            let mut accounts = self.lock_shard_write(&address);
+            let account = accounts.entry(address).or_insert_with(|| InMemoryPermanentAccount::new_empty(address));
+            account.slots.insert(slot.index, InMemoryHistory::new_at_zero(slot));
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "dev")]
+    fn set_balance(&self, address: &Address, balance: Wei) -> anyhow::Result<()> {
+        let block_number = self.read_mined_block_number()?;
+        let mut accounts = self.lock_shard_write(address);
+        let account = accounts.entry(*address).or_insert_with(|| InMemoryPermanentAccount::new_empty(*address));
+        account.balance.push(block_number, balance);
+        Ok(())
+    }
+
+    #[cfg(feature = "dev")]
+    fn set_nonce(&self, address: &Address, nonce: Nonce) -> anyhow::Result<()> {
+        let block_number = self.read_mined_block_number()?;
+        let mut accounts = self.lock_shard_write(address);
+        let account = accounts.entry(*address).or_insert_with(|| InMemoryPermanentAccount::new_empty(*address));
+        account.nonce.push(block_number, nonce);
+        Ok(())
+    }
+
+    #[cfg(feature = "dev")]
+    fn set_code(&self, address: &Address, code: Bytes) -> anyhow::Result<()> {
+        let block_number = self.read_mined_block_number()?;
+        let code_hash = self.save_code(code);
+        let mut accounts = self.lock_shard_write(address);
+        let account = accounts.entry(*address).or_insert_with(|| InMemoryPermanentAccount::new_empty(*address));
+        account.code_hash.push(block_number, code_hash);
+        Ok(())
+    }
+
+    #[cfg(feature = "dev")]
+    fn set_storage(&self, address: &Address, slot: Slot) -> anyhow::Result<()> {
+        let block_number = self.read_mined_block_number()?;
+        let mut accounts = self.lock_shard_write(address);
+        let account = accounts.entry(*address).or_insert_with(|| InMemoryPermanentAccount::new_empty(*address));
+        match account.slots.get_mut(&slot.index) {
+            Some(slot_history) => slot_history.push(block_number, slot),
+            None => {
+                account.slots.insert(slot.index, InMemoryHistory::new(block_number, slot));
+            }
         }
         Ok(())
     }
@@ -242,7 +501,39 @@ impl PermanentStorage for InMemoryPermanentStorage {
 
         let mut state = self.lock_write();
         *state = InMemoryPermanentStorageState::default();
+        for mut shard in self.lock_shards_write() {
+            shard.clear();
+        }
+        self.codes.write().unwrap().clear();
+
+        Ok(())
+    }
+
+    fn reset_at(&self, number: BlockNumber) -> anyhow::Result<()> {
+        let mut state = self.lock_write();
+
+        // remove blocks and transactions after the target block
+        state.blocks_by_number.retain(|block_number, _| *block_number <= number);
+        state.blocks_by_hash.retain(|_, block| block.number() <= number);
+        state.transactions.retain(|_, block| block.number() <= number);
+        state.transactions_by_address.retain(|_, transactions| {
+            transactions.retain(|(block_number, _), _| *block_number <= number);
+            not(transactions.is_empty())
+        });
+
+        // publish blocks produced again after the target block
+        for cursor in state.publisher_cursors.values_mut() {
+            *cursor = (*cursor).min(number);
+        }
+
+        // remove account and slot changes after the target block
+        for mut shard in self.lock_shards_write() {
+            for account in shard.values_mut() {
+                account.reset_at(number);
+            }
+        }
 
+        self.block_number.store(number.as_u64(), Ordering::SeqCst);
         Ok(())
     }
 }
@@ -254,7 +545,6 @@ pub struct InMemoryPermanentAccount {
     pub address: Address,
     pub balance: InMemoryHistory<Wei>,
     pub nonce: InMemoryHistory<Nonce>,
-    pub bytecode: InMemoryHistory<Option<Bytes>>,
     pub code_hash: InMemoryHistory<CodeHash>,
     pub slots: HashMap<SlotIndex, InMemoryHistory<Slot>, hash_hasher::HashBuildHasher>,
 }
@@ -271,19 +561,49 @@ impl InMemoryPermanentAccount {
             address,
             balance: InMemoryHistory::new_at_zero(balance),
             nonce: InMemoryHistory::new_at_zero(Nonce::ZERO),
-            bytecode: InMemoryHistory::new_at_zero(None),
             code_hash: InMemoryHistory::new_at_zero(CodeHash::default()),
             slots: HashMap::default(),
         }
     }
 
-    /// Converts itself to an account at a point-in-time.
+    /// Creates a new permanent account with all initial values of an account, except the bytecode that is referenced by the code hash.
+    fn new_from_account(account: Account) -> Self {
+        Self {
+            address: account.address,
+            balance: InMemoryHistory::new_at_zero(account.balance),
+            nonce: InMemoryHistory::new_at_zero(account.nonce),
+            code_hash: InMemoryHistory::new_at_zero(account.code_hash),
+            slots: HashMap::default(),
+        }
+    }
+
+    /// Removes all changes after the specified block number.
+    fn reset_at(&mut self, block_number: BlockNumber) {
+        if let Some(balance) = self.balance.reset_at(block_number) {
+            self.balance = balance;
+        }
+        if let Some(nonce) = self.nonce.reset_at(block_number) {
+            self.nonce = nonce;
+        }
+        if let Some(code_hash) = self.code_hash.reset_at(block_number) {
+            self.code_hash = code_hash;
+        }
+        self.slots.retain(|_, slot_history| match slot_history.reset_at(block_number) {
+            Some(history) => {
+                *slot_history = history;
+                true
+            }
+            None => false,
+        });
+    }
+
+    /// Converts itself to an account at a point-in-time, without the bytecode that must be read by the code hash.
     pub fn to_account(&self, point_in_time: &StoragePointInTime) -> Account {
         Account {
             address: self.address,
             balance: self.balance.get_at_point(point_in_time).unwrap_or_default(),
             nonce: self.nonce.get_at_point(point_in_time).unwrap_or_default(),
-            bytecode: self.bytecode.get_at_point(point_in_time).unwrap_or_default(),
+            bytecode: None,
             code_hash: self.code_hash.get_at_point(point_in_time).unwrap_or_default(),
         }
     }
@@ -14,6 +14,7 @@ use crate::eth::primitives::EvmExecution;
 use crate::eth::primitives::ExecutionConflicts;
 use crate::eth::primitives::ExecutionConflictsBuilder;
 use crate::eth::primitives::ExternalBlock;
+use crate::eth::primitives::Gas;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::PendingBlock;
 use crate::eth::primitives::Slot;
@@ -21,6 +22,8 @@ use crate::eth::primitives::SlotIndex;
 use crate::eth::primitives::StratusError;
 use crate::eth::primitives::TransactionExecution;
 use crate::eth::storage::TemporaryStorage;
+use crate::eth::storage::TemporaryStorageStats;
+use crate::ext::not;
 use crate::log_and_err;
 
 /// Number of previous blocks to keep inmemory to detect conflicts between different blocks.
@@ -64,6 +67,9 @@ pub struct InMemoryTemporaryStorageState {
 
     /// Last state of accounts and slots. Can be recreated from the executions inside the pending block.
     pub accounts: HashMap<Address, InMemoryTemporaryAccount, hash_hasher::HashBuildHasher>,
+
+    /// Number of slots held by all accounts.
+    pub slots_len: usize,
 }
 
 impl InMemoryTemporaryStorageState {
@@ -88,6 +94,7 @@ impl InMemoryTemporaryStorageState {
     pub fn reset(&mut self) {
         self.block = None;
         self.accounts.clear();
+        self.slots_len = 0;
     }
 }
 
@@ -151,34 +158,7 @@ impl TemporaryStorage for InMemoryTemporaryStorage {
         }
 
         // save account changes
-        let changes = tx.execution().changes.values();
-        for change in changes {
-            let account = states
-                .head
-                .accounts
-                .entry(change.address)
-                .or_insert_with(|| InMemoryTemporaryAccount::new(change.address));
-
-            // account basic info
-            if let Some(nonce) = change.nonce.take_ref() {
-                account.info.nonce = *nonce;
-            }
-            if let Some(balance) = change.balance.take_ref() {
-                account.info.balance = *balance;
-            }
-
-            // bytecode (todo: where is code_hash?)
-            if let Some(Some(bytecode)) = change.bytecode.take_ref() {
-                account.info.bytecode = Some(bytecode.clone());
-            }
-
-            // slots
-            for slot in change.slots.values() {
-                if let Some(slot) = slot.take_ref() {
-                    account.slots.insert(slot.index, *slot);
-                }
-            }
-        }
+        do_save_account_changes(&mut states.head, tx.execution());
 
         // save execution
         states.head.require_pending_block_mut()?.push_transaction(tx);
@@ -195,10 +175,30 @@ impl TemporaryStorage for InMemoryTemporaryStorage {
             .unwrap_or_default()
     }
 
+    fn read_pending_block(&self) -> anyhow::Result<Option<PendingBlock>> {
+        Ok(self.lock_read().head.block.clone())
+    }
+
     /// TODO: we cannot allow more than one pending block. Where to put this check?
-    fn finish_pending_block(&self) -> anyhow::Result<PendingBlock> {
+    fn finish_pending_block(&self, gas_limit: Gas) -> anyhow::Result<(PendingBlock, Vec<TransactionExecution>)> {
         let mut states = self.lock_write();
-        let finished_block = states.head.require_pending_block()?.clone();
+        let mut finished_block = states.head.require_pending_block()?.clone();
+
+        // remove transactions that did not fit in the finished block and recreate its state without them
+        let remaining_txs = finished_block.split_off_over_gas_limit(gas_limit);
+        if not(remaining_txs.is_empty()) {
+            tracing::info!(
+                block_number = %finished_block.header.number,
+                remaining_txs = %remaining_txs.len(),
+                "block gas limit reached, moving remaining transactions to next block"
+            );
+            states.head.accounts.clear();
+            states.head.slots_len = 0;
+            for tx in finished_block.transactions.values() {
+                do_save_account_changes(&mut states.head, tx.execution());
+            }
+            states.head.block = Some(finished_block.clone());
+        }
 
         // remove last state if reached limit
         if states.len() + 1 >= MAX_BLOCKS {
@@ -209,7 +209,7 @@ impl TemporaryStorage for InMemoryTemporaryStorage {
         states.insert(0, InMemoryTemporaryStorageState::default());
         states.head.block = Some(PendingBlock::new_at_now(finished_block.header.number.next_block_number()));
 
-        Ok(finished_block)
+        Ok((finished_block, remaining_txs))
     }
 
     fn read_transaction(&self, hash: &Hash) -> anyhow::Result<Option<TransactionExecution>> {
@@ -244,11 +244,53 @@ impl TemporaryStorage for InMemoryTemporaryStorage {
         state.head.reset();
         Ok(())
     }
+
+    fn stats(&self) -> TemporaryStorageStats {
+        let states = self.lock_read();
+        TemporaryStorageStats {
+            pending_block_number: states.head.block.as_ref().map(|block| block.header.number),
+            pending_transactions: states.head.block.as_ref().map(|block| block.transactions.len()).unwrap_or_default(),
+            accounts: states.iter().map(|state| state.accounts.len()).sum(),
+            slots: states.iter().map(|state| state.slots_len).sum(),
+        }
+    }
 }
 
 // -----------------------------------------------------------------------------
 // Implementations without lock
 // -----------------------------------------------------------------------------
+fn do_save_account_changes(state: &mut InMemoryTemporaryStorageState, execution: &EvmExecution) {
+    let changes = execution.changes.values();
+    for change in changes {
+        let account = state
+            .accounts
+            .entry(change.address)
+            .or_insert_with(|| InMemoryTemporaryAccount::new(change.address));
+
+        // account basic info
+        if let Some(nonce) = change.nonce.take_ref() {
+            account.info.nonce = *nonce;
+        }
+        if let Some(balance) = change.balance.take_ref() {
+            account.info.balance = *balance;
+        }
+
+        // bytecode (todo: where is code_hash?)
+        if let Some(Some(bytecode)) = change.bytecode.take_ref() {
+            account.info.bytecode = Some(bytecode.clone());
+        }
+
+        // slots
+        for slot in change.slots.values() {
+            if let Some(slot) = slot.take_ref() {
+                if account.slots.insert(slot.index, *slot).is_none() {
+                    state.slots_len += 1;
+                }
+            }
+        }
+    }
+}
+
 fn do_read_account(states: &NonEmpty<InMemoryTemporaryStorageState>, address: &Address) -> Option<Account> {
     // search all
     for state in states.iter() {
@@ -1,19 +1,50 @@
 //! Ethereum / EVM storage.
 
+mod block_quarantine;
+mod block_rlp;
+mod chain_head;
+mod chain_transitions;
 mod external_rpc_storage;
+mod fee_history;
+mod fork_permanent;
+mod genesis_config;
 mod inmemory;
+#[cfg(any(test, feature = "storage-conformance"))]
+pub mod permanent_conformance;
 mod permanent_storage;
 mod postgres_external_rpc;
+mod postgres_permanent;
+mod receipt_trie;
 pub mod rocks;
 
 mod redis;
+mod retention;
+mod state_import;
+mod state_snapshot;
+mod state_trie;
+mod storage_encryption;
 mod storage_point_in_time;
 mod stratus_storage;
 mod temporary_storage;
+mod temporary_wal;
 
+pub use block_quarantine::BlockQuarantine;
+pub use block_quarantine::QuarantinedBlocks;
+pub use block_quarantine::QuarantinedBlocksSummary;
+pub use block_rlp::BlockRlpRecord;
+pub use chain_head::ChainHeadBus;
+pub use chain_head::ChainHeadEvent;
+pub use chain_transitions::ChainTransition;
+pub use chain_transitions::ChainTransitionKind;
+pub use chain_transitions::ChainTransitions;
 pub use external_rpc_storage::ExternalRpcStorage;
 pub use external_rpc_storage::ExternalRpcStorageConfig;
 pub use external_rpc_storage::ExternalRpcStorageKind;
+pub use fee_history::FeeHistoryAccumulator;
+pub use fee_history::FEE_HISTORY_MAX_BLOCKS;
+pub use fork_permanent::ForkConfig;
+pub use fork_permanent::ForkPermanentStorage;
+pub use genesis_config::GenesisConfig;
 pub use inmemory::InMemoryPermanentStorage;
 pub use inmemory::InMemoryPermanentStorageState;
 pub use inmemory::InMemoryTemporaryStorage;
@@ -22,10 +53,30 @@ pub use permanent_storage::PermanentStorageConfig;
 pub use permanent_storage::PermanentStorageKind;
 pub use postgres_external_rpc::PostgresExternalRpcStorage;
 pub use postgres_external_rpc::PostgresExternalRpcStorageConfig;
+pub use postgres_permanent::PostgresPermanentStorage;
+pub use postgres_permanent::PostgresPermanentStorageConfig;
+pub use receipt_trie::receipts_root;
+pub use receipt_trie::ReceiptProof;
+pub use retention::RetentionConfig;
+pub use retention::RetentionJanitor;
+pub use retention::RetentionReport;
+pub use retention::RetentionTarget;
 pub use rocks::rocks_permanent::RocksPermanentStorage;
+pub use state_import::StateImportBatch;
+pub use state_import::StateImportReader;
+pub use state_snapshot::StateSnapshot;
+pub use state_trie::AccountProof;
+pub use state_trie::SlotProof;
+pub use state_trie::StateTrie;
+pub use storage_encryption::StorageEncryption;
+pub use storage_encryption::StorageEncryptionConfig;
+pub use storage_encryption::ENCRYPTION_MAGIC;
 pub use storage_point_in_time::StoragePointInTime;
 pub use stratus_storage::StratusStorage;
 pub use stratus_storage::StratusStorageConfig;
 pub use temporary_storage::TemporaryStorage;
 pub use temporary_storage::TemporaryStorageConfig;
 pub use temporary_storage::TemporaryStorageKind;
+pub use temporary_storage::TemporaryStorageStats;
+pub use temporary_wal::TemporaryWal;
+pub use temporary_wal::TemporaryWalEntry;
@@ -0,0 +1,372 @@
+//! Conformance suite for [`PermanentStorage`] implementations.
+//!
+//! Each check receives an empty storage, writes the same blocks to it and returns an error describing the first behavior that differs
+//! from the expected one, so every backend is validated against the same scenarios. The suite runs in the unit tests of the backends
+//! that do not depend on external services, and is exposed with the `storage-conformance` feature to validate the others against a live
+//! instance.
+
+use anyhow::ensure;
+use anyhow::Context;
+use fake::Fake;
+use fake::Faker;
+
+use crate::eth::executor::EvmExecutionResult;
+use crate::eth::primitives::Account;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::CodeHash;
+use crate::eth::primitives::EvmExecution;
+use crate::eth::primitives::EvmExecutionMetrics;
+use crate::eth::primitives::ExecutionAccountChanges;
+use crate::eth::primitives::ExecutionResult;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::Log;
+use crate::eth::primitives::LogFilter;
+use crate::eth::primitives::LogFilterInput;
+use crate::eth::primitives::Slot;
+use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::TransactionInput;
+use crate::eth::primitives::UnixTime;
+use crate::eth::primitives::Wei;
+use crate::eth::storage::PermanentStorage;
+use crate::eth::storage::StoragePointInTime;
+
+/// Check that validates one behavior of an empty storage.
+pub type ConformanceCheck = fn(&dyn PermanentStorage) -> anyhow::Result<()>;
+
+/// All checks of the suite, identified by name.
+pub const CONFORMANCE_CHECKS: [(&str, ConformanceCheck); 7] = [
+    ("point_in_time_reads", check_point_in_time_reads),
+    ("reset_at", check_reset_at),
+    ("conflicting_changes", check_conflicting_changes),
+    ("blocks_and_logs", check_blocks_and_logs),
+    ("block_state_diff", check_block_state_diff),
+    ("contract_code", check_contract_code),
+    ("batch_slot_reads", check_batch_slot_reads),
+];
+
+/// Runs all checks, each one against a new empty storage created by `new_storage`.
+pub fn check_all(mut new_storage: impl FnMut() -> anyhow::Result<Box<dyn PermanentStorage>>) -> anyhow::Result<()> {
+    for (name, check) in CONFORMANCE_CHECKS {
+        tracing::info!(check = %name, "running permanent storage conformance check");
+        let storage = new_storage().with_context(|| format!("failed to create storage for conformance check {}", name))?;
+        check(storage.as_ref()).with_context(|| format!("permanent storage conformance check {} failed", name))?;
+    }
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Checks
+// -----------------------------------------------------------------------------
+
+/// Accounts and slots are read as they were at the end of each block.
+pub fn check_point_in_time_reads(storage: &dyn PermanentStorage) -> anyhow::Result<()> {
+    save_blocks(storage, vec![block(1, &[(10, 100, false)]), block(2, &[(20, 200, false)])])?;
+
+    for (point_in_time, balance, slot_value) in [
+        (StoragePointInTime::MinedPast(1.into()), 10, 100),
+        (StoragePointInTime::MinedPast(2.into()), 20, 200),
+        (StoragePointInTime::Mined, 20, 200),
+    ] {
+        expect_balance(storage, &point_in_time, balance)?;
+        expect_slot(storage, &point_in_time, slot_value)?;
+    }
+    Ok(())
+}
+
+/// Resetting to a block removes later blocks, transactions and changes, and mining continues from it.
+pub fn check_reset_at(storage: &dyn PermanentStorage) -> anyhow::Result<()> {
+    let blocks = vec![block(1, &[(10, 100, true)]), block(2, &[(20, 200, true)]), block(3, &[(30, 300, true)])];
+    let removed_tx = blocks[2].transactions[0].input.hash;
+    save_blocks(storage, blocks)?;
+
+    storage.reset_at(1.into())?;
+
+    ensure!(storage.read_mined_block_number()? == BlockNumber::from(1), "mined block number was not reset");
+    ensure!(
+        storage.read_block(&BlockFilter::Number(2.into()))?.is_none(),
+        "block after reset target still exists"
+    );
+    ensure!(storage.read_transaction(&removed_tx)?.is_none(), "transaction after reset target still exists");
+    ensure!(storage.read_logs(&log_filter(0, None))?.len() == 1, "logs after reset target still exist");
+    expect_balance(storage, &StoragePointInTime::Mined, 10)?;
+    expect_slot(storage, &StoragePointInTime::Mined, 100)?;
+
+    // mining again after the reset replaces the removed history
+    save_blocks(storage, vec![block(2, &[(25, 250, false)])])?;
+    expect_balance(storage, &StoragePointInTime::MinedPast(1.into()), 10)?;
+    expect_balance(storage, &StoragePointInTime::Mined, 25)?;
+    expect_slot(storage, &StoragePointInTime::Mined, 250)?;
+    Ok(())
+}
+
+/// When transactions of the same block change the same account and slot, the last change wins and previous blocks are not affected.
+pub fn check_conflicting_changes(storage: &dyn PermanentStorage) -> anyhow::Result<()> {
+    save_blocks(
+        storage,
+        vec![block(1, &[(10, 100, false)]), block(2, &[(20, 200, false), (21, 201, false), (22, 202, false)])],
+    )?;
+
+    expect_balance(storage, &StoragePointInTime::MinedPast(1.into()), 10)?;
+    expect_slot(storage, &StoragePointInTime::MinedPast(1.into()), 100)?;
+    expect_balance(storage, &StoragePointInTime::Mined, 22)?;
+    expect_slot(storage, &StoragePointInTime::Mined, 202)?;
+    Ok(())
+}
+
+/// Blocks, transactions and logs can be queried by number, hash, address and range.
+pub fn check_blocks_and_logs(storage: &dyn PermanentStorage) -> anyhow::Result<()> {
+    let blocks = vec![
+        block(1, &[(10, 100, true)]),
+        block(2, &[(20, 200, false)]),
+        block(3, &[(30, 300, true), (31, 301, true)]),
+    ];
+    let last_block = blocks[2].clone();
+    save_blocks(storage, blocks)?;
+
+    // blocks
+    for filter in [BlockFilter::Number(3.into()), BlockFilter::Hash(last_block.hash()), BlockFilter::Latest] {
+        let block = storage
+            .read_block(&filter)?
+            .with_context(|| format!("block not found with filter {}", filter))?;
+        ensure!(block.number() == last_block.number(), "wrong block read with filter {}", filter);
+        ensure!(block.transactions.len() == 2, "wrong number of transactions read with filter {}", filter);
+    }
+    ensure!(
+        storage.read_block(&BlockFilter::Number(4.into()))?.is_none(),
+        "block after last mined block was found"
+    );
+
+    // transactions
+    let expected_tx = &last_block.transactions[1];
+    let tx = storage.read_transaction(&expected_tx.input.hash)?.context("transaction not found")?;
+    ensure!(tx.block_number == expected_tx.block_number, "transaction read with wrong block number");
+    ensure!(tx.transaction_index == expected_tx.transaction_index, "transaction read with wrong index");
+
+    // logs
+    let logs = storage.read_logs(&log_filter(0, None))?;
+    ensure!(logs.len() == 3, "expected 3 logs in all blocks, found {}", logs.len());
+    let logs = storage.read_logs(&log_filter(2, Some(3)))?;
+    ensure!(logs.len() == 2, "expected 2 logs in blocks 2 to 3, found {}", logs.len());
+    ensure!(
+        logs.iter().map(|log| log.log_index.0).collect::<Vec<_>>() == vec![0, 1],
+        "logs of the last block read with wrong indexes"
+    );
+    let logs = storage.read_logs(&LogFilter {
+        addresses: vec![Address::new([0xee; 20])],
+        ..log_filter(0, None)
+    })?;
+    ensure!(logs.is_empty(), "logs found for an address that did not emit logs");
+    Ok(())
+}
+
+/// The state diff of a block has the last values of the accounts and slots it modified, and is removed with the block.
+pub fn check_block_state_diff(storage: &dyn PermanentStorage) -> anyhow::Result<()> {
+    save_blocks(storage, vec![block(1, &[(10, 100, false)]), block(2, &[(20, 200, false), (21, 201, false)])])?;
+
+    let diff = storage.read_block_state_diff(2.into())?.context("state diff of block 2 not found")?;
+    let addresses = diff.iter().map(|account| account.address).collect::<Vec<_>>();
+    ensure!(addresses == vec![ACCOUNT, CONTRACT], "state diff has wrong accounts: {:?}", addresses);
+
+    let balance = diff[0].account.as_ref().map(|account| account.balance);
+    ensure!(balance == Some(Wei::from(21u64)), "state diff has wrong balance: {:?}", balance);
+    ensure!(diff[0].slots.is_empty(), "state diff has slots of an account whose slots did not change");
+    ensure!(
+        diff[1].slots == vec![Slot::new(slot_index(), 201u64.into())],
+        "state diff has wrong slots: {:?}",
+        diff[1].slots
+    );
+
+    storage.reset_at(1.into())?;
+    ensure!(storage.read_block_state_diff(2.into())?.is_none(), "state diff after reset target still exists");
+    ensure!(storage.read_block_state_diff(1.into())?.is_some(), "state diff before reset target was removed");
+    Ok(())
+}
+
+/// Bytecodes are read with the account that deployed them and by their hash.
+pub fn check_contract_code(storage: &dyn PermanentStorage) -> anyhow::Result<()> {
+    let bytecode = Bytes(vec![0x60, 0x00, 0x60, 0x00, 0xf3]);
+    let code_hash = CodeHash::from_bytecode(Some(bytecode.clone()));
+
+    let mut deploy = block(1, &[(10, 100, false)]);
+    let contract = Account {
+        bytecode: Some(bytecode.clone()),
+        code_hash,
+        ..Account::new_empty(CONTRACT)
+    };
+    deploy.transactions[0]
+        .execution
+        .changes
+        .insert(CONTRACT, ExecutionAccountChanges::from_modified_values(contract, vec![]));
+    save_blocks(storage, vec![deploy])?;
+
+    for point_in_time in [StoragePointInTime::MinedPast(1.into()), StoragePointInTime::Mined] {
+        let account = storage.read_account(&CONTRACT, &point_in_time)?.context("contract not found")?;
+        ensure!(
+            account.bytecode.as_ref() == Some(&bytecode),
+            "expected bytecode at {}, found {:?}",
+            point_in_time,
+            account.bytecode
+        );
+    }
+    ensure!(storage.read_code(&code_hash)?.as_ref() == Some(&bytecode), "bytecode not found by hash");
+    ensure!(storage.read_code(&CodeHash::default())?.is_none(), "bytecode found for the empty code hash");
+    Ok(())
+}
+
+/// Batch slot reads return the same slots as individual reads and omit the slots not found.
+pub fn check_batch_slot_reads(storage: &dyn PermanentStorage) -> anyhow::Result<()> {
+    save_blocks(storage, vec![block(1, &[(10, 100, false)]), block(2, &[(20, 200, false)])])?;
+
+    let missing_index = SlotIndex::from([2, 0, 0, 0]);
+    let slots = [(CONTRACT, slot_index()), (CONTRACT, missing_index), (ACCOUNT, slot_index())];
+    for (point_in_time, expected) in [(StoragePointInTime::MinedPast(1.into()), 100u64), (StoragePointInTime::Mined, 200u64)] {
+        let found = storage.read_slots(&slots, &point_in_time)?;
+        ensure!(
+            found == vec![(CONTRACT, Slot::new(slot_index(), expected.into()))],
+            "expected only slot with value {} at {}, found {:?}",
+            expected,
+            point_in_time,
+            found
+        );
+    }
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Fixtures
+// -----------------------------------------------------------------------------
+
+/// Account whose balance is changed by all transactions.
+const ACCOUNT: Address = Address::new([0xaa; 20]);
+
+/// Contract whose slot is changed by all transactions and that emits their logs.
+const CONTRACT: Address = Address::new([0xcc; 20]);
+
+fn slot_index() -> SlotIndex {
+    SlotIndex::from([1, 0, 0, 0])
+}
+
+/// Creates a block where each transaction sets the balance of [`ACCOUNT`], sets a slot of [`CONTRACT`] and optionally emits a log.
+fn block(number: u64, transactions: &[(u64, u64, bool)]) -> Block {
+    let mut block = Block::new(number.into(), UnixTime::from(number));
+    for (index, (balance, slot_value, emits_log)) in transactions.iter().enumerate() {
+        let mut hash = [0; 32];
+        hash[..8].copy_from_slice(&number.to_be_bytes());
+        hash[8..16].copy_from_slice(&(index as u64).to_be_bytes());
+        let input = TransactionInput {
+            hash: Hash::new(hash),
+            signer: ACCOUNT,
+            from: ACCOUNT,
+            to: Some(CONTRACT),
+            ..TransactionInput::default()
+        };
+
+        let account = Account::new_with_balance(ACCOUNT, Wei::from(*balance));
+        let contract = Account::new_empty(CONTRACT);
+        let slot = Slot::new(slot_index(), (*slot_value).into());
+        let mut execution: EvmExecution = Faker.fake();
+        execution.block_timestamp = block.header.timestamp;
+        execution.result = ExecutionResult::Success;
+        execution.deployed_contract_address = None;
+        execution.gas = 21_000u64.into();
+        execution.logs = match emits_log {
+            true => vec![Log {
+                address: CONTRACT,
+                ..Log::default()
+            }],
+            false => vec![],
+        };
+        execution.changes = [
+            (ACCOUNT, ExecutionAccountChanges::from_modified_values(account, vec![])),
+            (CONTRACT, ExecutionAccountChanges::from_modified_values(contract, vec![slot])),
+        ]
+        .into_iter()
+        .collect();
+
+        block.push_execution(
+            input,
+            EvmExecutionResult {
+                execution,
+                metrics: EvmExecutionMetrics::default(),
+            },
+        );
+    }
+    block
+}
+
+/// Saves blocks the same way they are committed by the miner.
+fn save_blocks(storage: &dyn PermanentStorage, blocks: Vec<Block>) -> anyhow::Result<()> {
+    for block in blocks {
+        let number = block.number();
+        storage.save_block(block)?;
+        storage.set_mined_block_number(number)?;
+    }
+    Ok(())
+}
+
+fn log_filter(from: u64, to: Option<u64>) -> LogFilter {
+    LogFilter {
+        from_block: from.into(),
+        to_block: to.map(BlockNumber::from),
+        addresses: vec![],
+        original_input: LogFilterInput::default(),
+    }
+}
+
+fn expect_balance(storage: &dyn PermanentStorage, point_in_time: &StoragePointInTime, expected: u64) -> anyhow::Result<()> {
+    let account = storage.read_account(&ACCOUNT, point_in_time)?.context("account not found")?;
+    ensure!(
+        account.balance == Wei::from(expected),
+        "expected balance {} at {}, found {}",
+        expected,
+        point_in_time,
+        account.balance
+    );
+    Ok(())
+}
+
+fn expect_slot(storage: &dyn PermanentStorage, point_in_time: &StoragePointInTime, expected: u64) -> anyhow::Result<()> {
+    let slot = storage.read_slot(&CONTRACT, &slot_index(), point_in_time)?.context("slot not found")?;
+    ensure!(
+        slot.value == expected.into(),
+        "expected slot value {} at {}, found {}",
+        expected,
+        point_in_time,
+        slot.value
+    );
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::RocksPermanentStorage;
+
+    #[test]
+    fn inmemory_permanent_storage_conforms() {
+        check_all(|| Ok(Box::new(InMemoryPermanentStorage::default()))).unwrap();
+    }
+
+    #[test]
+    fn rocks_permanent_storage_conforms() {
+        let test_dir = tempfile::tempdir().unwrap();
+        let mut count = 0;
+        check_all(|| {
+            count += 1;
+            let prefix = test_dir.path().join(format!("conformance-{}", count)).display().to_string();
+            Ok(Box::new(RocksPermanentStorage::new(Some(prefix), Duration::ZERO)?))
+        })
+        .unwrap();
+    }
+}
@@ -6,22 +6,36 @@ use clap::Parser;
 use display_json::DebugAsJson;
 
 use crate::eth::primitives::Account;
+use crate::eth::primitives::AccountStateDiff;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::CodeHash;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::LogFilter;
 use crate::eth::primitives::LogMined;
+#[cfg(feature = "dev")]
+use crate::eth::primitives::Nonce;
+use crate::eth::primitives::Pagination;
 use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
 use crate::eth::primitives::TransactionMined;
+#[cfg(feature = "dev")]
+use crate::eth::primitives::Wei;
 use crate::eth::storage::redis::RedisPermanentStorage;
+use crate::eth::storage::ForkConfig;
+use crate::eth::storage::ForkPermanentStorage;
 use crate::eth::storage::InMemoryPermanentStorage;
+use crate::eth::storage::PostgresPermanentStorage;
+use crate::eth::storage::PostgresPermanentStorageConfig;
+use crate::eth::storage::RetentionTarget;
 use crate::eth::storage::RocksPermanentStorage;
 use crate::eth::storage::StoragePointInTime;
 use crate::ext::parse_duration;
 use crate::log_and_err;
+use crate::GlobalState;
 
 /// Permanent (committed) storage operations.
 pub trait PermanentStorage: Send + Sync + 'static {
@@ -45,12 +59,35 @@ pub trait PermanentStorage: Send + Sync + 'static {
     /// Retrieves a block from the storage.
     fn read_block(&self, block_filter: &BlockFilter) -> anyhow::Result<Option<Block>>;
 
+    /// Retrieves all transactions of a block with their receipt data. Returns Option when the block is not found.
+    fn read_block_receipts(&self, block_filter: &BlockFilter) -> anyhow::Result<Option<Vec<TransactionMined>>>;
+
+    /// Retrieves the accounts and slots modified by a block with their values at the end of it. Returns Option when the block is not found.
+    fn read_block_state_diff(&self, number: BlockNumber) -> anyhow::Result<Option<Vec<AccountStateDiff>>>;
+
     /// Retrieves a transaction from the storage.
     fn read_transaction(&self, hash: &Hash) -> anyhow::Result<Option<TransactionMined>>;
 
     /// Retrieves logs from the storage.
     fn read_logs(&self, filter: &LogFilter) -> anyhow::Result<Vec<LogMined>>;
 
+    /// Retrieves hashes of transactions sent from, sent to or deploying an address in the block range (inclusive), ordered by block and
+    /// position in the block.
+    fn read_transactions_by_address(&self, address: &Address, from: BlockNumber, to: BlockNumber, pagination: Pagination) -> anyhow::Result<Vec<Hash>>;
+
+    /// Removes the data covered by the retention target from blocks in the range (inclusive), keeping the block headers.
+    fn prune_blocks(&self, target: RetentionTarget, from: BlockNumber, to: BlockNumber) -> anyhow::Result<()>;
+
+    // -------------------------------------------------------------------------
+    // Publisher outbox
+    // -------------------------------------------------------------------------
+
+    /// Retrieves the last block whose events were delivered by the publisher.
+    fn read_publisher_cursor(&self, publisher: &str) -> anyhow::Result<Option<BlockNumber>>;
+
+    /// Persists the last block whose events were delivered by the publisher.
+    fn save_publisher_cursor(&self, publisher: &str, number: BlockNumber) -> anyhow::Result<()>;
+
     // -------------------------------------------------------------------------
     // Account and slots
     // -------------------------------------------------------------------------
@@ -61,16 +98,66 @@ pub trait PermanentStorage: Send + Sync + 'static {
     /// Retrieves an account from the storage. Returns Option when not found.
     fn read_account(&self, address: &Address, point_in_time: &StoragePointInTime) -> anyhow::Result<Option<Account>>;
 
+    /// Retrieves a bytecode by its hash from the storage. Returns Option when not found.
+    ///
+    /// Bytecodes are indexed by hash when saved, so accounts with the same bytecode share it.
+    fn read_code(&self, code_hash: &CodeHash) -> anyhow::Result<Option<Bytes>>;
+
     /// Retrieves an slot from the storage. Returns Option when not found.
     fn read_slot(&self, address: &Address, index: &SlotIndex, point_in_time: &StoragePointInTime) -> anyhow::Result<Option<Slot>>;
 
+    /// Retrieves multiple slots from the storage with the address of the account they belong to. Slots not found are omitted.
+    ///
+    /// Implementations should read all slots in a single round trip when the backend supports it.
+    fn read_slots(&self, slots: &[(Address, SlotIndex)], point_in_time: &StoragePointInTime) -> anyhow::Result<Vec<(Address, Slot)>> {
+        let mut found = Vec::with_capacity(slots.len());
+        for (address, index) in slots {
+            if let Some(slot) = self.read_slot(address, index, point_in_time)? {
+                found.push((*address, slot));
+            }
+        }
+        Ok(found)
+    }
+
+    /// Retrieves all accounts at the last mined block.
+    fn read_all_accounts(&self) -> anyhow::Result<Vec<Account>>;
+
+    /// Retrieves all slots at the last mined block with the address of the account they belong to.
+    fn read_all_slots(&self) -> anyhow::Result<Vec<(Address, Slot)>>;
+
+    /// Persists initial slots (snapshot imports).
+    fn save_slots(&self, slots: Vec<(Address, Slot)>) -> anyhow::Result<()>;
+
+    // -------------------------------------------------------------------------
+    // Account and slots (dev)
+    // -------------------------------------------------------------------------
+
+    #[cfg(feature = "dev")]
+    /// Overrides the balance of an account at the last mined block.
+    fn set_balance(&self, address: &Address, balance: Wei) -> anyhow::Result<()>;
+
+    #[cfg(feature = "dev")]
+    /// Overrides the nonce of an account at the last mined block.
+    fn set_nonce(&self, address: &Address, nonce: Nonce) -> anyhow::Result<()>;
+
+    #[cfg(feature = "dev")]
+    /// Overrides the bytecode of an account at the last mined block.
+    fn set_code(&self, address: &Address, code: Bytes) -> anyhow::Result<()>;
+
+    #[cfg(feature = "dev")]
+    /// Overrides a slot of an account at the last mined block.
+    fn set_storage(&self, address: &Address, slot: Slot) -> anyhow::Result<()>;
+
     // -------------------------------------------------------------------------
     // Global state
     // -------------------------------------------------------------------------
 
     #[cfg(feature = "dev")]
-    /// Resets all state to a specific block number.
+    /// Resets all state to the initial empty state.
     fn reset(&self) -> anyhow::Result<()>;
+
+    /// Resets all state to a specific block number, removing blocks and changes after it.
+    fn reset_at(&self, number: BlockNumber) -> anyhow::Result<()>;
 }
 
 // -----------------------------------------------------------------------------
@@ -85,9 +172,21 @@ pub struct PermanentStorageConfig {
     pub perm_storage_kind: PermanentStorageKind,
 
     /// Storage connection URL.
-    #[arg(long = "perm-storage-url", env = "PERM_STORAGE_URL", required_if_eq_any([("perm_storage_kind", "redis")]))]
+    #[arg(long = "perm-storage-url", env = "PERM_STORAGE_URL", required_if_eq_any([("perm_storage_kind", "redis"), ("perm_storage_kind", "postgres")]))]
     pub perm_storage_url: Option<String>,
 
+    /// Storage number of parallel open connections (Postgres only).
+    #[arg(long = "perm-storage-connections", env = "PERM_STORAGE_CONNECTIONS", default_value = "10")]
+    pub perm_storage_connections: u32,
+
+    /// Storage timeout when opening a connection (Postgres only).
+    #[arg(long = "perm-storage-timeout", value_parser=parse_duration, env = "PERM_STORAGE_TIMEOUT", default_value = "2s")]
+    pub perm_storage_timeout: Duration,
+
+    /// Number of partitions of accounts and slots, each one with its own lock (InMemory only).
+    #[arg(long = "inmemory-shards", env = "INMEMORY_SHARDS", default_value_t = InMemoryPermanentStorage::DEFAULT_SHARDS)]
+    pub inmemory_shards: usize,
+
     /// RocksDB storage path prefix to execute multiple local Stratus instances.
     #[arg(long = "rocks-path-prefix", env = "ROCKS_PATH_PREFIX")]
     pub rocks_path_prefix: Option<String>,
@@ -95,6 +194,22 @@ pub struct PermanentStorageConfig {
     /// The maximum time to wait for the RocksDB `wait_for_compaction` shutdown call.
     #[arg(long = "rocks-shutdown-timeout", env = "ROCKS_SHUTDOWN_TIMEOUT", value_parser=parse_duration, default_value = "4m")]
     pub rocks_shutdown_timeout: Duration,
+
+    /// RPC URL of a remote chain whose state is forked. Accounts and slots not found in the permanent storage are fetched from it.
+    #[arg(long = "fork-url", env = "FORK_URL")]
+    pub fork_url: Option<String>,
+
+    /// Block of the remote chain whose state is forked. Defaults to its current block, and is required once local blocks were mined.
+    #[arg(long = "fork-block-number", env = "FORK_BLOCK_NUMBER", requires = "fork_url")]
+    pub fork_block_number: Option<u64>,
+
+    /// Timeout of requests to the remote chain whose state is forked.
+    #[arg(long = "fork-timeout", value_parser=parse_duration, env = "FORK_TIMEOUT", default_value = "10s")]
+    pub fork_timeout: Duration,
+
+    /// Max number of accounts, slots and bytecodes of the remote chain cached in memory, for each kind.
+    #[arg(long = "fork-cache-capacity", env = "FORK_CACHE_CAPACITY", default_value = "100000")]
+    pub fork_cache_capacity: usize,
 }
 
 #[derive(DebugAsJson, Clone, serde::Serialize)]
@@ -102,6 +217,9 @@ pub enum PermanentStorageKind {
     #[serde(rename = "inmemory")]
     InMemory,
 
+    #[serde(rename = "postgres")]
+    Postgres,
+
     #[serde(rename = "redis")]
     Redis,
 
@@ -115,7 +233,19 @@ impl PermanentStorageConfig {
         tracing::info!(config = ?self, "creating permanent storage");
 
         let perm: Box<dyn PermanentStorage> = match self.perm_storage_kind {
-            PermanentStorageKind::InMemory => Box::<InMemoryPermanentStorage>::default(),
+            PermanentStorageKind::InMemory => Box::new(InMemoryPermanentStorage::new(self.inmemory_shards)),
+
+            PermanentStorageKind::Postgres => {
+                let Some(url) = self.perm_storage_url.as_deref() else {
+                    return log_and_err!("postgres connection url not provided when it was expected to be present");
+                };
+                let config = PostgresPermanentStorageConfig {
+                    url: url.to_owned(),
+                    connections: self.perm_storage_connections,
+                    acquire_timeout: self.perm_storage_timeout,
+                };
+                Box::new(PostgresPermanentStorage::new(config)?)
+            }
 
             PermanentStorageKind::Redis => {
                 let Some(url) = self.perm_storage_url.as_deref() else {
@@ -127,9 +257,25 @@ impl PermanentStorageConfig {
             PermanentStorageKind::Rocks => {
                 let prefix = self.rocks_path_prefix.clone();
                 let shutdown_timeout = self.rocks_shutdown_timeout;
-                Box::new(RocksPermanentStorage::new(prefix, shutdown_timeout)?)
+                // archive nodes only serve mined data, so they never write to it
+                if GlobalState::is_archive() {
+                    Box::new(RocksPermanentStorage::new_read_only(prefix, shutdown_timeout)?)
+                } else {
+                    Box::new(RocksPermanentStorage::new(prefix, shutdown_timeout)?)
+                }
             }
         };
+
+        // fork mode wraps any implementation
+        if let Some(url) = self.fork_url.as_deref() {
+            let config = ForkConfig {
+                url: url.to_owned(),
+                block_number: self.fork_block_number.map(BlockNumber::from),
+                timeout: self.fork_timeout,
+                cache_capacity: self.fork_cache_capacity,
+            };
+            return Ok(Box::new(ForkPermanentStorage::new(perm, config)?));
+        }
         Ok(perm)
     }
 }
@@ -140,6 +286,7 @@ impl FromStr for PermanentStorageKind {
     fn from_str(s: &str) -> anyhow::Result<Self, Self::Err> {
         match s {
             "inmemory" => Ok(Self::InMemory),
+            "postgres" => Ok(Self::Postgres),
             "redis" => Ok(Self::Redis),
             "rocks" => Ok(Self::Rocks),
             s => Err(anyhow!("unknown permanent storage: {}", s)),
@@ -0,0 +1,5 @@
+#[allow(clippy::module_inception)]
+mod postgres_permanent;
+
+pub use postgres_permanent::PostgresPermanentStorage;
+pub use postgres_permanent::PostgresPermanentStorageConfig;
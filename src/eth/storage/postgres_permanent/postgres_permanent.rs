@@ -0,0 +1,569 @@
+//! PostgreSQL permanent storage.
+//!
+//! Blocks, transactions and logs are stored once, while accounts and slots are stored as rows versioned by the block number
+//! that modified them, so point-in-time reads select the most recent row at or before the requested block.
+//!
+//! Queries are checked at runtime (not with `sqlx` macros), so the schema at `static/schema/002-schema-permanent.sql` must be
+//! applied before starting.
+
+use std::future::Future;
+use std::time::Duration;
+
+use itertools::Itertools;
+use sqlx::postgres::PgConnectOptions;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::types::Json;
+use sqlx::PgPool;
+use tokio::runtime::Handle;
+
+use crate::eth::primitives::Account;
+use crate::eth::primitives::AccountStateDiff;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::CodeHash;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::LogFilter;
+use crate::eth::primitives::LogMined;
+#[cfg(feature = "dev")]
+use crate::eth::primitives::Nonce;
+use crate::eth::primitives::Pagination;
+use crate::eth::primitives::Slot;
+use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::SlotValue;
+use crate::eth::primitives::TransactionMined;
+#[cfg(feature = "dev")]
+use crate::eth::primitives::Wei;
+use crate::eth::storage::PermanentStorage;
+use crate::eth::storage::RetentionTarget;
+use crate::eth::storage::StoragePointInTime;
+use crate::ext::to_json_value;
+use crate::log_and_err;
+
+#[derive(Debug)]
+pub struct PostgresPermanentStorageConfig {
+    pub url: String,
+    pub connections: u32,
+    pub acquire_timeout: Duration,
+}
+
+pub struct PostgresPermanentStorage {
+    pool: PgPool,
+
+    /// Runtime used to execute queries, because the storage interface is synchronous.
+    runtime: Handle,
+}
+
+impl PostgresPermanentStorage {
+    /// Creates a new [`PostgresPermanentStorage`].
+    ///
+    /// Connections are opened lazily, but it must be called from inside a Tokio runtime.
+    pub fn new(config: PostgresPermanentStorageConfig) -> anyhow::Result<Self> {
+        tracing::info!(?config, "creating postgres permanent storage");
+
+        let runtime = match Handle::try_current() {
+            Ok(runtime) => runtime,
+            Err(e) => return log_and_err!(reason = e, "postgres permanent storage must be created inside a tokio runtime"),
+        };
+
+        let options = match config.url.as_str().parse::<PgConnectOptions>() {
+            Ok(options) => options,
+            Err(e) => return log_and_err!(reason = e, "failed to parse postgres permanent storage url"),
+        };
+
+        let pool = PgPoolOptions::new()
+            .min_connections(config.connections)
+            .max_connections(config.connections)
+            .acquire_timeout(config.acquire_timeout)
+            .connect_lazy_with(options);
+
+        Ok(Self { pool, runtime })
+    }
+
+    /// Executes a query future from synchronous code, whether or not it is running inside the runtime.
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        match Handle::try_current() {
+            Ok(_) => tokio::task::block_in_place(|| self.runtime.block_on(future)),
+            Err(_) => self.runtime.block_on(future),
+        }
+    }
+
+    /// Reads a single JSON payload.
+    fn read_payload<T>(&self, query: sqlx::query::QueryScalar<'_, sqlx::Postgres, Json<T>, sqlx::postgres::PgArguments>) -> anyhow::Result<Option<T>>
+    where
+        T: serde::de::DeserializeOwned + Send + Unpin + 'static,
+    {
+        match self.block_on(query.fetch_optional(&self.pool)) {
+            Ok(payload) => Ok(payload.map(|payload| payload.0)),
+            Err(e) => log_and_err!(reason = e, "failed to read payload from postgres"),
+        }
+    }
+
+    /// Indexes bytecodes by their hash, keeping the ones already indexed.
+    fn save_codes(&self, bytecodes: Vec<Bytes>) -> anyhow::Result<()> {
+        if bytecodes.is_empty() {
+            return Ok(());
+        }
+
+        let (code_hashes, bytecodes) = split_codes(bytecodes);
+        let result = self.block_on(
+            sqlx::query(include_str!("sql/insert_account_codes.sql"))
+                .bind(code_hashes)
+                .bind(bytecodes)
+                .execute(&self.pool),
+        );
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to write account codes to postgres"),
+        }
+    }
+
+    /// Reads the current value of an account, falling back to an empty account.
+    #[cfg(feature = "dev")]
+    fn read_current_account(&self, address: &Address) -> anyhow::Result<Account> {
+        let account = self.read_account(address, &StoragePointInTime::Mined)?;
+        Ok(account.unwrap_or_else(|| Account::new_empty(*address)))
+    }
+
+    /// Overrides the current value of an account at the last mined block.
+    #[cfg(feature = "dev")]
+    fn save_account_override(&self, account: Account) -> anyhow::Result<()> {
+        let block_number = self.read_mined_block_number()?;
+        let result = self.block_on(
+            sqlx::query(include_str!("sql/insert_accounts.sql"))
+                .bind(vec![account.address])
+                .bind(vec![block_number.as_i64()])
+                .bind(vec![to_json_value(&account)])
+                .execute(&self.pool),
+        );
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to write account override to postgres"),
+        }
+    }
+}
+
+impl PermanentStorage for PostgresPermanentStorage {
+    fn set_mined_block_number(&self, number: BlockNumber) -> anyhow::Result<()> {
+        let result = self.block_on(
+            sqlx::query(include_str!("sql/upsert_mined_block_number.sql"))
+                .bind(number.as_i64())
+                .execute(&self.pool),
+        );
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to write mined number to postgres"),
+        }
+    }
+
+    fn read_mined_block_number(&self) -> anyhow::Result<BlockNumber> {
+        let result = self.block_on(sqlx::query_scalar::<_, i64>(include_str!("sql/select_mined_block_number.sql")).fetch_optional(&self.pool));
+        match result {
+            Ok(Some(number)) => Ok((number as u64).into()),
+            Ok(None) => Ok(BlockNumber::ZERO),
+            Err(e) => log_and_err!(reason = e, "failed to read mined block number from postgres"),
+        }
+    }
+
+    fn save_block(&self, block: Block) -> anyhow::Result<()> {
+        let number = block.number().as_i64();
+
+        // transactions and logs
+        let mut tx_hashes = vec![];
+        let mut tx_payloads = vec![];
+        let mut address_tx_addresses = vec![];
+        let mut address_tx_indexes = vec![];
+        let mut address_tx_hashes = vec![];
+        let mut log_indexes = vec![];
+        let mut log_addresses = vec![];
+        let mut log_payloads = vec![];
+        for tx in &block.transactions {
+            tx_hashes.push(tx.input.hash);
+            tx_payloads.push(to_json_value(tx));
+            for address in tx.indexed_addresses() {
+                address_tx_addresses.push(address);
+                address_tx_indexes.push(tx.transaction_index.0 as i64);
+                address_tx_hashes.push(tx.input.hash);
+            }
+            for log in &tx.logs {
+                log_indexes.push(log.log_index.0 as i64);
+                log_addresses.push(*log.address());
+                log_payloads.push(to_json_value(log));
+            }
+        }
+
+        // accounts and slots
+        let mut account_addresses = vec![];
+        let mut account_payloads = vec![];
+        let mut account_codes = vec![];
+        let mut slot_addresses = vec![];
+        let mut slot_indexes = vec![];
+        let mut slot_values = vec![];
+        for changes in block.compact_account_changes() {
+            if changes.is_account_modified() {
+                let mut account = Account {
+                    address: changes.address,
+                    code_hash: changes.code_hash,
+                    ..Account::default()
+                };
+                if let Some(nonce) = changes.nonce.take() {
+                    account.nonce = nonce;
+                }
+                if let Some(balance) = changes.balance.take() {
+                    account.balance = balance;
+                }
+                if let Some(bytecode) = changes.bytecode.take() {
+                    account_codes.extend(bytecode.clone());
+                    account.bytecode = bytecode;
+                }
+                account_addresses.push(account.address);
+                account_payloads.push(to_json_value(&account));
+            }
+
+            for slot in changes.slots.into_values() {
+                if let Some(slot) = slot.take() {
+                    slot_addresses.push(changes.address);
+                    slot_indexes.push(slot.index);
+                    slot_values.push(slot.value);
+                }
+            }
+        }
+
+        // execute all inserts in a single database transaction
+        let (code_hashes, code_bytecodes) = split_codes(account_codes);
+        let block_hash = block.hash();
+        let block_payload = to_json_value(&block);
+        let result: Result<(), sqlx::Error> = self.block_on(async {
+            let mut db_tx = self.pool.begin().await?;
+
+            sqlx::query(include_str!("sql/insert_block.sql"))
+                .bind(number)
+                .bind(block_hash)
+                .bind(block_payload)
+                .execute(&mut *db_tx)
+                .await?;
+
+            sqlx::query(include_str!("sql/insert_transactions.sql"))
+                .bind(&tx_hashes)
+                .bind(vec![number; tx_hashes.len()])
+                .bind(tx_payloads)
+                .execute(&mut *db_tx)
+                .await?;
+
+            sqlx::query(include_str!("sql/insert_address_transactions.sql"))
+                .bind(&address_tx_addresses)
+                .bind(vec![number; address_tx_addresses.len()])
+                .bind(address_tx_indexes)
+                .bind(address_tx_hashes)
+                .execute(&mut *db_tx)
+                .await?;
+
+            sqlx::query(include_str!("sql/insert_logs.sql"))
+                .bind(vec![number; log_indexes.len()])
+                .bind(log_indexes)
+                .bind(log_addresses)
+                .bind(log_payloads)
+                .execute(&mut *db_tx)
+                .await?;
+
+            sqlx::query(include_str!("sql/insert_accounts.sql"))
+                .bind(&account_addresses)
+                .bind(vec![number; account_addresses.len()])
+                .bind(account_payloads)
+                .execute(&mut *db_tx)
+                .await?;
+
+            sqlx::query(include_str!("sql/insert_account_codes.sql"))
+                .bind(code_hashes)
+                .bind(code_bytecodes)
+                .execute(&mut *db_tx)
+                .await?;
+
+            sqlx::query(include_str!("sql/insert_account_slots.sql"))
+                .bind(&slot_addresses)
+                .bind(slot_indexes)
+                .bind(vec![number; slot_addresses.len()])
+                .bind(slot_values)
+                .execute(&mut *db_tx)
+                .await?;
+
+            db_tx.commit().await
+        });
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to write block to postgres"),
+        }
+    }
+
+    fn read_block(&self, block_filter: &BlockFilter) -> anyhow::Result<Option<Block>> {
+        let query = match block_filter {
+            BlockFilter::Latest | BlockFilter::Pending => sqlx::query_scalar(include_str!("sql/select_latest_block.sql")),
+            BlockFilter::Earliest => sqlx::query_scalar(include_str!("sql/select_earliest_block.sql")),
+            BlockFilter::Hash(hash) => sqlx::query_scalar(include_str!("sql/select_block_by_hash.sql")).bind(*hash),
+            BlockFilter::Number(number) => sqlx::query_scalar(include_str!("sql/select_block_by_number.sql")).bind(number.as_i64()),
+        };
+        self.read_payload(query)
+    }
+
+    fn read_block_receipts(&self, block_filter: &BlockFilter) -> anyhow::Result<Option<Vec<TransactionMined>>> {
+        Ok(self.read_block(block_filter)?.map(|block| block.transactions))
+    }
+
+    fn read_block_state_diff(&self, number: BlockNumber) -> anyhow::Result<Option<Vec<AccountStateDiff>>> {
+        // block payload includes the execution changes of its transactions
+        Ok(self.read_block(&BlockFilter::Number(number))?.map(|block| block.state_diff()))
+    }
+
+    fn read_transaction(&self, hash: &Hash) -> anyhow::Result<Option<TransactionMined>> {
+        self.read_payload(sqlx::query_scalar(include_str!("sql/select_transaction.sql")).bind(*hash))
+    }
+
+    fn read_logs(&self, filter: &LogFilter) -> anyhow::Result<Vec<LogMined>> {
+        let to_block = match filter.to_block {
+            Some(number) => number,
+            None => self.read_mined_block_number()?,
+        };
+
+        let result = self.block_on(
+            sqlx::query_scalar::<_, Json<LogMined>>(include_str!("sql/select_logs.sql"))
+                .bind(filter.from_block.as_i64())
+                .bind(to_block.as_i64())
+                .bind(&filter.addresses)
+                .fetch_all(&self.pool),
+        );
+
+        // address and block range are filtered by the query, topics are filtered here
+        match result {
+            Ok(logs) => Ok(logs.into_iter().map(|log| log.0).filter(|log| filter.matches(log)).collect_vec()),
+            Err(e) => log_and_err!(reason = e, "failed to read logs from postgres"),
+        }
+    }
+
+    fn read_transactions_by_address(&self, address: &Address, from: BlockNumber, to: BlockNumber, pagination: Pagination) -> anyhow::Result<Vec<Hash>> {
+        let result = self.block_on(
+            sqlx::query_scalar::<_, Hash>(include_str!("sql/select_address_transactions.sql"))
+                .bind(*address)
+                .bind(from.as_i64())
+                .bind(to.as_i64())
+                .bind(pagination.offset as i64)
+                .bind(pagination.limit as i64)
+                .fetch_all(&self.pool),
+        );
+        match result {
+            Ok(hashes) => Ok(hashes),
+            Err(e) => log_and_err!(reason = e, "failed to read transactions by address from postgres"),
+        }
+    }
+
+    fn prune_blocks(&self, target: RetentionTarget, from: BlockNumber, to: BlockNumber) -> anyhow::Result<()> {
+        let query = match target {
+            RetentionTarget::Receipts => include_str!("sql/update_prune_receipts.sql"),
+            RetentionTarget::Logs => include_str!("sql/update_prune_logs.sql"),
+        };
+        let result = self.block_on(sqlx::query(query).bind(from.as_i64()).bind(to.as_i64()).execute(&self.pool));
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to prune blocks in postgres"),
+        }
+    }
+
+    fn read_publisher_cursor(&self, publisher: &str) -> anyhow::Result<Option<BlockNumber>> {
+        let result = self.block_on(
+            sqlx::query_scalar::<_, i64>(include_str!("sql/select_publisher_cursor.sql"))
+                .bind(publisher)
+                .fetch_optional(&self.pool),
+        );
+        match result {
+            Ok(number) => Ok(number.map(|number| (number as u64).into())),
+            Err(e) => log_and_err!(reason = e, "failed to read publisher cursor from postgres"),
+        }
+    }
+
+    fn save_publisher_cursor(&self, publisher: &str, number: BlockNumber) -> anyhow::Result<()> {
+        let result = self.block_on(
+            sqlx::query(include_str!("sql/upsert_publisher_cursor.sql"))
+                .bind(publisher)
+                .bind(number.as_i64())
+                .execute(&self.pool),
+        );
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to write publisher cursor to postgres"),
+        }
+    }
+
+    fn save_accounts(&self, accounts: Vec<Account>) -> anyhow::Result<()> {
+        // exit if no accounts
+        if accounts.is_empty() {
+            return Ok(());
+        }
+
+        self.save_codes(accounts.iter().filter_map(|account| account.bytecode.clone()).collect())?;
+
+        // initial accounts are saved at the genesis block so they are visible to all point-in-time reads
+        let addresses = accounts.iter().map(|account| account.address).collect_vec();
+        let payloads = accounts.iter().map(to_json_value).collect_vec();
+        let result = self.block_on(
+            sqlx::query(include_str!("sql/insert_accounts.sql"))
+                .bind(addresses)
+                .bind(vec![0i64; accounts.len()])
+                .bind(payloads)
+                .execute(&self.pool),
+        );
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to write accounts to postgres"),
+        }
+    }
+
+    fn save_slots(&self, slots: Vec<(Address, Slot)>) -> anyhow::Result<()> {
+        // exit if no slots
+        if slots.is_empty() {
+            return Ok(());
+        }
+
+        // initial slots are saved at the genesis block so they are visible to all point-in-time reads
+        let result = self.block_on(
+            sqlx::query(include_str!("sql/insert_account_slots.sql"))
+                .bind(slots.iter().map(|(address, _)| *address).collect_vec())
+                .bind(slots.iter().map(|(_, slot)| slot.index).collect_vec())
+                .bind(vec![0i64; slots.len()])
+                .bind(slots.iter().map(|(_, slot)| slot.value).collect_vec())
+                .execute(&self.pool),
+        );
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to write slots to postgres"),
+        }
+    }
+
+    fn read_account(&self, address: &Address, point_in_time: &StoragePointInTime) -> anyhow::Result<Option<Account>> {
+        let query = sqlx::query_scalar(include_str!("sql/select_account.sql"))
+            .bind(*address)
+            .bind(point_in_time_to_block_number(point_in_time));
+        self.read_payload(query)
+    }
+
+    fn read_code(&self, code_hash: &CodeHash) -> anyhow::Result<Option<Bytes>> {
+        let result = self.block_on(
+            sqlx::query_scalar::<_, Vec<u8>>(include_str!("sql/select_account_code.sql"))
+                .bind(code_hash.0.as_bytes().to_vec())
+                .fetch_optional(&self.pool),
+        );
+        match result {
+            Ok(bytecode) => Ok(bytecode.map(Bytes)),
+            Err(e) => log_and_err!(reason = e, "failed to read code from postgres"),
+        }
+    }
+
+    fn read_slot(&self, address: &Address, index: &SlotIndex, point_in_time: &StoragePointInTime) -> anyhow::Result<Option<Slot>> {
+        let result = self.block_on(
+            sqlx::query_scalar::<_, SlotValue>(include_str!("sql/select_account_slot.sql"))
+                .bind(*address)
+                .bind(*index)
+                .bind(point_in_time_to_block_number(point_in_time))
+                .fetch_optional(&self.pool),
+        );
+        match result {
+            Ok(value) => Ok(value.map(|value| Slot { index: *index, value })),
+            Err(e) => log_and_err!(reason = e, "failed to read slot from postgres"),
+        }
+    }
+
+    fn read_all_accounts(&self) -> anyhow::Result<Vec<Account>> {
+        let result = self.block_on(sqlx::query_scalar::<_, Json<Account>>(include_str!("sql/select_all_accounts.sql")).fetch_all(&self.pool));
+        match result {
+            Ok(payloads) => Ok(payloads.into_iter().map(|payload| payload.0).collect()),
+            Err(e) => log_and_err!(reason = e, "failed to read all accounts from postgres"),
+        }
+    }
+
+    fn read_all_slots(&self) -> anyhow::Result<Vec<(Address, Slot)>> {
+        let result =
+            self.block_on(sqlx::query_as::<_, (Address, SlotIndex, SlotValue)>(include_str!("sql/select_all_account_slots.sql")).fetch_all(&self.pool));
+        match result {
+            Ok(rows) => Ok(rows.into_iter().map(|(address, index, value)| (address, Slot { index, value })).collect()),
+            Err(e) => log_and_err!(reason = e, "failed to read all slots from postgres"),
+        }
+    }
+
+    #[cfg(feature = "dev")]
+    fn set_balance(&self, address: &Address, balance: Wei) -> anyhow::Result<()> {
+        let mut account = self.read_current_account(address)?;
+        account.balance = balance;
+        self.save_account_override(account)
+    }
+
+    #[cfg(feature = "dev")]
+    fn set_nonce(&self, address: &Address, nonce: Nonce) -> anyhow::Result<()> {
+        let mut account = self.read_current_account(address)?;
+        account.nonce = nonce;
+        self.save_account_override(account)
+    }
+
+    #[cfg(feature = "dev")]
+    fn set_code(&self, address: &Address, code: Bytes) -> anyhow::Result<()> {
+        let mut account = self.read_current_account(address)?;
+        account.code_hash = CodeHash::from_bytecode(Some(code.clone()));
+        self.save_codes(vec![code.clone()])?;
+        account.bytecode = Some(code);
+        self.save_account_override(account)
+    }
+
+    #[cfg(feature = "dev")]
+    fn set_storage(&self, address: &Address, slot: Slot) -> anyhow::Result<()> {
+        let block_number = self.read_mined_block_number()?;
+        let result = self.block_on(
+            sqlx::query(include_str!("sql/insert_account_slots.sql"))
+                .bind(vec![*address])
+                .bind(vec![slot.index])
+                .bind(vec![block_number.as_i64()])
+                .bind(vec![slot.value])
+                .execute(&self.pool),
+        );
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to write slot override to postgres"),
+        }
+    }
+
+    #[cfg(feature = "dev")]
+    fn reset(&self) -> anyhow::Result<()> {
+        let result = self.block_on(sqlx::query(include_str!("sql/delete_all.sql")).execute(&self.pool));
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to clear all postgres tables"),
+        }
+    }
+
+    fn reset_at(&self, number: BlockNumber) -> anyhow::Result<()> {
+        let result = self.block_on(
+            sqlx::query(include_str!("sql/delete_after_block.sql"))
+                .bind(number.as_i64())
+                .execute(&self.pool),
+        );
+        if let Err(e) = result {
+            return log_and_err!(reason = e, "failed to delete blocks from postgres");
+        }
+        self.set_mined_block_number(number)
+    }
+}
+
+/// Converts a point-in-time to the highest block number whose changes are visible.
+fn point_in_time_to_block_number(point_in_time: &StoragePointInTime) -> i64 {
+    match point_in_time {
+        StoragePointInTime::Mined | StoragePointInTime::Pending => i64::MAX,
+        StoragePointInTime::MinedPast(number) => number.as_i64(),
+    }
+}
+
+/// Splits bytecodes in the code hash and bytecode columns of the `account_codes` table.
+fn split_codes(bytecodes: Vec<Bytes>) -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
+    bytecodes
+        .into_iter()
+        .map(|bytecode| (CodeHash::from_bytecode(Some(bytecode.clone())).0.as_bytes().to_vec(), bytecode.0))
+        .unzip()
+}
@@ -0,0 +1,9 @@
+with
+    deleted_blocks as (delete from blocks where number > $1),
+    deleted_transactions as (delete from transactions where block_number > $1),
+    deleted_address_transactions as (delete from address_transactions where block_number > $1),
+    deleted_logs as (delete from logs where block_number > $1),
+    deleted_accounts as (delete from accounts where block_number > $1),
+    rewound_publisher_outbox as (update publisher_outbox set block_number = $1 where block_number > $1)
+delete from account_slots
+where block_number > $1;
@@ -0,0 +1 @@
+truncate mined_block_number, blocks, transactions, address_transactions, logs, accounts, account_codes, account_slots, publisher_outbox;
@@ -0,0 +1,3 @@
+insert into account_codes(code_hash, bytecode)
+select * from unnest($1::bytea[], $2::bytea[])
+on conflict (code_hash) do nothing;
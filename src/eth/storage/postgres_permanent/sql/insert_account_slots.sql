@@ -0,0 +1,3 @@
+insert into account_slots(address, idx, block_number, value)
+select * from unnest($1::bytea[], $2::bytea[], $3::bigint[], $4::bytea[])
+on conflict (address, idx, block_number) do update set value = excluded.value;
@@ -0,0 +1,3 @@
+insert into accounts(address, block_number, payload)
+select * from unnest($1::bytea[], $2::bigint[], $3::jsonb[])
+on conflict (address, block_number) do update set payload = excluded.payload;
@@ -0,0 +1,2 @@
+insert into address_transactions(address, block_number, transaction_index, hash)
+select * from unnest($1::bytea[], $2::bigint[], $3::bigint[], $4::bytea[]);
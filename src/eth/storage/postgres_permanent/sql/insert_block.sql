@@ -0,0 +1,2 @@
+insert into blocks(number, hash, payload)
+values ($1, $2, $3);
@@ -0,0 +1,2 @@
+insert into logs(block_number, log_index, address, payload)
+select * from unnest($1::bigint[], $2::bigint[], $3::bytea[], $4::jsonb[]);
@@ -0,0 +1,2 @@
+insert into transactions(hash, block_number, payload)
+select * from unnest($1::bytea[], $2::bigint[], $3::jsonb[]);
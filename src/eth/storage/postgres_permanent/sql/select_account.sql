@@ -0,0 +1,6 @@
+select payload
+from accounts
+where address = $1
+  and block_number <= $2
+order by block_number desc
+limit 1;
@@ -0,0 +1,3 @@
+select bytecode
+from account_codes
+where code_hash = $1;
@@ -0,0 +1,7 @@
+select value
+from account_slots
+where address = $1
+  and idx = $2
+  and block_number <= $3
+order by block_number desc
+limit 1;
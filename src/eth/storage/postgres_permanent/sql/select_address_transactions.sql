@@ -0,0 +1,8 @@
+select hash
+from address_transactions
+where address = $1
+  and block_number >= $2
+  and block_number <= $3
+order by block_number asc, transaction_index asc
+offset $4
+limit $5;
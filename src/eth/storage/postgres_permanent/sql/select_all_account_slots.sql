@@ -0,0 +1,3 @@
+select distinct on (address, idx) address, idx, value
+from account_slots
+order by address, idx, block_number desc;
@@ -0,0 +1,3 @@
+select distinct on (address) payload
+from accounts
+order by address, block_number desc;
@@ -0,0 +1,3 @@
+select payload
+from blocks
+where hash = $1;
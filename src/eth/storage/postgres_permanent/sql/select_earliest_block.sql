@@ -0,0 +1,4 @@
+select payload
+from blocks
+order by number asc
+limit 1;
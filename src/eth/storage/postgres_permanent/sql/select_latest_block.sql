@@ -0,0 +1,4 @@
+select payload
+from blocks
+order by number desc
+limit 1;
@@ -0,0 +1,6 @@
+select payload
+from logs
+where block_number >= $1
+  and block_number <= $2
+  and (cardinality($3::bytea[]) = 0 or address = any($3))
+order by block_number asc, log_index asc;
@@ -0,0 +1,3 @@
+select number
+from mined_block_number
+where id = 1;
@@ -0,0 +1,3 @@
+select block_number
+from publisher_outbox
+where publisher = $1;
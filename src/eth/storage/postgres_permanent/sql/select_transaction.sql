@@ -0,0 +1,3 @@
+select payload
+from transactions
+where hash = $1;
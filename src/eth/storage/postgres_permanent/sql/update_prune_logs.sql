@@ -0,0 +1,16 @@
+with
+    pruned_blocks as (
+        update blocks
+        set payload = jsonb_set(payload, '{transactions}', (
+            select coalesce(jsonb_agg(jsonb_set(tx, '{logs}', '[]'::jsonb) order by position), '[]'::jsonb)
+            from jsonb_array_elements(payload->'transactions') with ordinality as txs(tx, position)
+        ))
+        where number >= $1 and number <= $2
+    ),
+    pruned_transactions as (
+        update transactions
+        set payload = jsonb_set(payload, '{logs}', '[]'::jsonb)
+        where block_number >= $1 and block_number <= $2
+    )
+delete from logs
+where block_number >= $1 and block_number <= $2;
@@ -0,0 +1,10 @@
+with
+    pruned_blocks as (
+        update blocks
+        set payload = jsonb_set(payload, '{transactions}', '[]'::jsonb)
+        where number >= $1 and number <= $2
+    ),
+    deleted_transactions as (delete from transactions where block_number >= $1 and block_number <= $2),
+    deleted_address_transactions as (delete from address_transactions where block_number >= $1 and block_number <= $2)
+delete from logs
+where block_number >= $1 and block_number <= $2;
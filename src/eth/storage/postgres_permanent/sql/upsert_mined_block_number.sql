@@ -0,0 +1,3 @@
+insert into mined_block_number(id, number)
+values (1, $1)
+on conflict (id) do update set number = excluded.number;
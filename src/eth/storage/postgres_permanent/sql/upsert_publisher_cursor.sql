@@ -0,0 +1,3 @@
+insert into publisher_outbox(publisher, block_number)
+values ($1, $2)
+on conflict (publisher) do update set block_number = excluded.block_number;
@@ -0,0 +1,161 @@
+//! Merkle-Patricia trie of the receipts of a block.
+//!
+//! Leaves are keyed by the RLP encoding of the transaction index and hold the consensus receipt, so the root and proofs are the ones
+//! verified by cross-chain message systems against the `receiptsRoot` of the block header.
+
+use ethereum_types::U64;
+use itertools::Itertools;
+
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::Gas;
+use crate::eth::primitives::Hash;
+use crate::eth::storage::state_trie::prove;
+use crate::eth::storage::state_trie::to_nibbles;
+use crate::eth::storage::state_trie::trie_root;
+
+/// Proof of inclusion of a transaction receipt in a block.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptProof {
+    pub block_hash: Hash,
+    pub block_number: BlockNumber,
+    pub transaction_hash: Hash,
+    pub transaction_index: U64,
+    pub receipts_root: Hash,
+
+    /// Consensus encoding of the receipt, which is the value of the proven leaf.
+    pub receipt: Bytes,
+
+    /// Trie nodes in the path from the root to the receipt leaf.
+    pub proof: Vec<Bytes>,
+}
+
+impl ReceiptProof {
+    /// Generates the proof of the receipt of a transaction against the receipts root of the block it was mined in.
+    ///
+    /// Returns `None` if the transaction is not part of the block.
+    pub fn from_block(block: &Block, tx_hash: &Hash) -> Option<Self> {
+        let leaves = receipt_leaves(block);
+        let (index, (key, receipt)) = block
+            .transactions
+            .iter()
+            .sorted_by_key(|tx| tx.transaction_index)
+            .zip(&leaves)
+            .find(|(tx, _)| tx.input.hash == *tx_hash)
+            .map(|(tx, leaf)| (tx.transaction_index, leaf))?;
+
+        let mut entries = leaves.clone();
+        entries.sort_unstable();
+
+        Some(Self {
+            block_hash: block.hash(),
+            block_number: block.number(),
+            transaction_hash: *tx_hash,
+            transaction_index: index.into(),
+            receipts_root: trie_root(&entries).into(),
+            receipt: receipt.clone().into(),
+            proof: prove(&entries, key),
+        })
+    }
+}
+
+/// Computes the receipts root of a block.
+pub fn receipts_root(block: &Block) -> Hash {
+    let mut entries = receipt_leaves(block);
+    entries.sort_unstable();
+    trie_root(&entries).into()
+}
+
+/// Generates the trie leaves of the block receipts in transaction index order.
+fn receipt_leaves(block: &Block) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let mut cumulative_gas_used: u64 = 0;
+    block
+        .transactions
+        .iter()
+        .sorted_by_key(|tx| tx.transaction_index)
+        .map(|tx| {
+            cumulative_gas_used = cumulative_gas_used.saturating_add(tx.execution.gas.as_u64());
+            let key = to_nibbles(&rlp::encode(&tx.transaction_index.0));
+            (key, tx.encode_receipt(Gas::from(cumulative_gas_used)))
+        })
+        .collect()
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use ethers_core::utils::keccak256;
+    use fake::Fake;
+    use fake::Faker;
+    use keccak_hasher::KeccakHasher;
+
+    use super::*;
+    use crate::eth::primitives::TransactionMined;
+    use crate::eth::primitives::UnixTime;
+
+    fn create_block(transactions: usize) -> Block {
+        let mut block = Block::new(BlockNumber::ONE, UnixTime::ZERO);
+        block.transactions = (0..transactions)
+            .map(|index| {
+                let mut tx: TransactionMined = Faker.fake();
+                tx.transaction_index = (index as u64).into();
+                tx.execution.gas = Gas::from(21_000u64);
+                tx
+            })
+            .rev()
+            .collect();
+        block
+    }
+
+    #[test]
+    fn receipts_root_of_empty_block() {
+        let block = create_block(0);
+        assert_eq!(
+            receipts_root(&block),
+            Hash::from(triehash::ordered_trie_root::<KeccakHasher, Vec<Vec<u8>>>(vec![]))
+        );
+    }
+
+    #[test]
+    fn receipts_root_matches_triehash() {
+        for transactions in [1, 2, 17, 130, 300] {
+            let block = create_block(transactions);
+            let receipts = block
+                .transactions
+                .iter()
+                .sorted_by_key(|tx| tx.transaction_index)
+                .enumerate()
+                .map(|(i, tx)| tx.encode_receipt(Gas::from(21_000 * (i as u64 + 1))))
+                .collect_vec();
+
+            let expected = triehash::ordered_trie_root::<KeccakHasher, _>(receipts);
+            assert_eq!(receipts_root(&block), Hash::from(expected), "{} transactions", transactions);
+        }
+    }
+
+    #[test]
+    fn receipt_proof_starts_at_root_and_ends_at_receipt() {
+        let block = create_block(130);
+        for tx in &block.transactions {
+            let proof = ReceiptProof::from_block(&block, &tx.input.hash).unwrap();
+            assert_eq!(proof.transaction_index, U64::from(tx.transaction_index.0));
+            assert_eq!(proof.receipts_root, receipts_root(&block));
+            assert_eq!(Hash::new(keccak256(&proof.proof[0].0)), proof.receipts_root);
+
+            // the last node is the leaf holding the receipt
+            let leaf = rlp::Rlp::new(&proof.proof.last().unwrap().0);
+            assert_eq!(leaf.at(1).unwrap().data().unwrap(), &proof.receipt.0[..]);
+        }
+    }
+
+    #[test]
+    fn receipt_proof_of_unknown_transaction() {
+        let block = create_block(3);
+        assert!(ReceiptProof::from_block(&block, &Hash::default()).is_none());
+    }
+}
@@ -1,3 +1,5 @@
 mod redis_permanent;
+mod redis_temporary;
 
 pub use redis_permanent::RedisPermanentStorage;
+pub use redis_temporary::RedisTemporaryStorage;
@@ -4,20 +4,32 @@ use redis::Commands;
 use redis::Connection as RedisConnection;
 use redis::RedisResult;
 
+#[cfg(feature = "dev")]
+use crate::alias::JsonValue;
 use crate::eth::primitives::Account;
+use crate::eth::primitives::AccountStateDiff;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::CodeHash;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::LogFilter;
 use crate::eth::primitives::LogMined;
+#[cfg(feature = "dev")]
+use crate::eth::primitives::Nonce;
+use crate::eth::primitives::Pagination;
 use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
 use crate::eth::primitives::TransactionMined;
+#[cfg(feature = "dev")]
+use crate::eth::primitives::Wei;
 use crate::eth::storage::PermanentStorage;
+use crate::eth::storage::RetentionTarget;
 use crate::eth::storage::StoragePointInTime;
 use crate::ext::from_json_str;
+use crate::ext::not;
 use crate::ext::to_json_object;
 use crate::ext::to_json_string;
 use crate::ext::to_json_value;
@@ -48,6 +60,61 @@ impl RedisPermanentStorage {
             Err(e) => log_and_err!(reason = e, "failed to get redis connection"),
         }
     }
+
+    /// Reads all keys matching a pattern with their current values.
+    fn read_all_values(&self, conn: &mut RedisConnection, pattern: &str) -> RedisResult<Vec<(String, String)>> {
+        let keys: Vec<String> = redis::cmd("KEYS").arg(pattern).query(conn)?;
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let values: Vec<Option<String>> = conn.mget(&keys)?;
+        Ok(keys
+            .into_iter()
+            .zip(values)
+            .filter_map(|(key, value)| value.map(|value| (key, value)))
+            .collect())
+    }
+
+    /// Reads the current value of an account, falling back to an empty account.
+    #[cfg(feature = "dev")]
+    fn read_current_account(&self, address: &Address) -> anyhow::Result<Account> {
+        let account = self.read_account(address, &StoragePointInTime::Mined)?;
+        Ok(account.unwrap_or_else(|| Account::new_empty(*address)))
+    }
+
+    /// Overrides the current value of a key and appends it to its history at the last mined block.
+    #[cfg(feature = "dev")]
+    fn save_override(&self, key: String, key_history: String, value: JsonValue) -> anyhow::Result<()> {
+        let block_number = self.read_mined_block_number()?;
+
+        // add block number to force value modification
+        let mut value = value;
+        if let Some(object) = value.as_object_mut() {
+            object.insert("block".to_owned(), to_json_value(block_number));
+        }
+        let value = to_json_string(&value);
+
+        // execute set command
+        let mut conn = self.conn()?;
+        let set: RedisVoid = conn.set(key, value.clone());
+        if let Err(e) = set {
+            return log_and_err!(reason = e, "failed to write override set to redis");
+        }
+
+        // execute zadd command
+        let mut cmd = redis::cmd("ZADD");
+        cmd.arg(key_history).arg(block_number.as_u64()).arg(value);
+        let zadd: RedisVoid = cmd.exec(&mut conn);
+        match zadd {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to write override zadd to redis"),
+        }
+    }
+
+    #[cfg(feature = "dev")]
+    fn save_account_override(&self, account: Account) -> anyhow::Result<()> {
+        self.save_override(key_account(&account.address), key_account_history(&account.address), to_json_value(&account))
+    }
 }
 
 impl PermanentStorage for RedisPermanentStorage {
@@ -97,6 +164,9 @@ impl PermanentStorage for RedisPermanentStorage {
             let tx_key = key_tx(&tx.input.hash);
             let tx_value = to_json_string(&tx);
             mset_values.push((tx_key, tx_value));
+            for address in tx.indexed_addresses() {
+                zadd_values.push((key_address_txs(&address), member_address_tx(tx), block.number().as_u64()));
+            }
         }
 
         // changes
@@ -114,6 +184,11 @@ impl PermanentStorage for RedisPermanentStorage {
                     account.balance = balance;
                 }
                 if let Some(bytecode) = changes.bytecode.take() {
+                    // bytecodes are also indexed by hash, replacing identical ones
+                    if let Some(ref bytecode) = bytecode {
+                        account.code_hash = CodeHash::from_bytecode(Some(bytecode.clone()));
+                        mset_values.push((key_code(&account.code_hash), to_json_string(bytecode)));
+                    }
                     account.bytecode = bytecode;
                 }
 
@@ -182,6 +257,15 @@ impl PermanentStorage for RedisPermanentStorage {
         }
     }
 
+    fn read_block_receipts(&self, block_filter: &BlockFilter) -> anyhow::Result<Option<Vec<TransactionMined>>> {
+        Ok(self.read_block(block_filter)?.map(|block| block.transactions))
+    }
+
+    fn read_block_state_diff(&self, number: BlockNumber) -> anyhow::Result<Option<Vec<AccountStateDiff>>> {
+        // block json includes the execution changes of its transactions
+        Ok(self.read_block(&BlockFilter::Number(number))?.map(|block| block.state_diff()))
+    }
+
     fn read_transaction(&self, hash: &Hash) -> anyhow::Result<Option<TransactionMined>> {
         // prepare keys
         let tx_key = key_tx(hash);
@@ -225,7 +309,9 @@ impl PermanentStorage for RedisPermanentStorage {
         // filter
         let logs = blocks
             .into_iter()
+            .filter(|b| filter.matches_bloom(&b.header.bloom))
             .flat_map(|b| b.transactions)
+            .filter(|t| filter.matches_bloom(&t.logs_bloom))
             .flat_map(|t| t.logs)
             .filter(|log| filter.matches(log))
             .collect_vec();
@@ -233,6 +319,117 @@ impl PermanentStorage for RedisPermanentStorage {
         Ok(logs)
     }
 
+    fn read_transactions_by_address(&self, address: &Address, from: BlockNumber, to: BlockNumber, pagination: Pagination) -> anyhow::Result<Vec<Hash>> {
+        // execute command
+        let mut conn = self.conn()?;
+        let members: RedisVecString = conn.zrangebyscore_limit(
+            key_address_txs(address),
+            from.as_u64(),
+            to.as_u64(),
+            pagination.offset as isize,
+            pagination.limit as isize,
+        );
+
+        // parse
+        let members = match members {
+            Ok(members) => members,
+            Err(e) => return log_and_err!(reason = e, "failed to read transactions by address from redis"),
+        };
+        members
+            .iter()
+            .map(|member| match member.split_once("::") {
+                Some((_, hash)) => hash.parse::<Hash>(),
+                None => log_and_err!("invalid address transaction member in redis"),
+            })
+            .collect()
+    }
+
+    fn prune_blocks(&self, target: RetentionTarget, from: BlockNumber, to: BlockNumber) -> anyhow::Result<()> {
+        let mined_number = self.read_mined_block_number()?;
+
+        // rewrite pruned blocks and transactions
+        let mut mset_values = vec![];
+        let mut del_keys = vec![];
+        let mut zrem_values = vec![];
+        for block_number in from.as_u64()..=to.as_u64() {
+            let Some(mut block) = self.read_block(&BlockFilter::Number(block_number.into()))? else {
+                continue;
+            };
+            let tx_hashes = block.transactions.iter().map(|tx| tx.input.hash).collect_vec();
+            let address_tx_members = block.transactions.iter().flat_map(address_tx_members).collect_vec();
+            if not(target.prune_block(&mut block)) {
+                continue;
+            }
+
+            // blocks
+            let block_json = to_json_string(&block);
+            if block.number() == mined_number {
+                mset_values.push(("block::latest".to_owned(), block_json.clone()));
+            }
+            mset_values.push((key_block_by_hash(&block.hash()), block_json.clone()));
+            mset_values.push((key_block_by_number(block_number), block_json));
+
+            // transactions
+            match target {
+                RetentionTarget::Receipts => {
+                    del_keys.extend(tx_hashes.iter().map(key_tx));
+                    zrem_values.extend(address_tx_members);
+                }
+                RetentionTarget::Logs =>
+                    for tx in &block.transactions {
+                        mset_values.push((key_tx(&tx.input.hash), to_json_string(tx)));
+                    },
+            }
+        }
+
+        // execute commands
+        let mut conn = self.conn()?;
+        if not(mset_values.is_empty()) {
+            let set: RedisVoid = conn.mset(&mset_values);
+            if let Err(e) = set {
+                return log_and_err!(reason = e, "failed to write pruned blocks to redis");
+            }
+        }
+        if not(del_keys.is_empty()) {
+            let del: RedisVoid = conn.del(del_keys);
+            if let Err(e) = del {
+                return log_and_err!(reason = e, "failed to delete pruned transactions from redis");
+            }
+        }
+        for (key, member) in zrem_values {
+            let zrem: RedisVoid = conn.zrem(key, member);
+            if let Err(e) = zrem {
+                return log_and_err!(reason = e, "failed to delete pruned address transactions from redis");
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_publisher_cursor(&self, publisher: &str) -> anyhow::Result<Option<BlockNumber>> {
+        // execute command
+        let mut conn = self.conn()?;
+        let value: RedisOptUsize = conn.get(key_publisher_cursor(publisher));
+
+        // parse
+        match value {
+            Ok(value) => Ok(value.map(BlockNumber::from)),
+            Err(e) => log_and_err!(reason = e, "failed to read publisher cursor from redis"),
+        }
+    }
+
+    fn save_publisher_cursor(&self, publisher: &str, number: BlockNumber) -> anyhow::Result<()> {
+        // execute command
+        let mut conn = self.conn()?;
+        let set: RedisVoid = conn.set(key_publisher_cursor(publisher), number.to_string());
+
+        // parse
+        match set {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to write publisher cursor to redis"),
+        }
+    }
+
     fn save_accounts(&self, accounts: Vec<Account>) -> anyhow::Result<()> {
         // exit if no accounts
         if accounts.is_empty() {
@@ -241,23 +438,81 @@ impl PermanentStorage for RedisPermanentStorage {
 
         // prepare values
         let redis_accounts = accounts
-            .into_iter()
+            .iter()
             .map(|acc| {
                 let account_key = key_account(&acc.address);
                 let account_value = to_json_string(&acc);
                 (account_key, account_value)
             })
             .collect_vec();
+        let redis_codes = accounts
+            .iter()
+            .filter_map(|acc| acc.bytecode.as_ref())
+            .map(|bytecode| (key_code(&CodeHash::from_bytecode(Some(bytecode.clone()))), to_json_string(bytecode)))
+            .collect_vec();
 
         // execute command
         let mut conn = self.conn()?;
         let set: RedisVoid = conn.mset(&redis_accounts);
+        if let Err(e) = set {
+            return log_and_err!(reason = e, "failed to write accounts to redis");
+        }
+        if not(redis_codes.is_empty()) {
+            let set: RedisVoid = conn.mset(&redis_codes);
+            if let Err(e) = set {
+                return log_and_err!(reason = e, "failed to write account codes to redis");
+            }
+        }
 
-        // parse
-        match set {
-            Ok(_) => Ok(()),
-            Err(e) => log_and_err!(reason = e, "failed to write accounts to redis"),
+        // keep initial accounts in history so they can be restored when resetting to a previous block
+        for (account, (_, account_value)) in accounts.iter().zip(redis_accounts) {
+            let mut cmd = redis::cmd("ZADD");
+            cmd.arg(key_account_history(&account.address)).arg("NX").arg(0).arg(account_value);
+
+            let zadd: RedisVoid = cmd.exec(&mut conn);
+            if let Err(e) = zadd {
+                return log_and_err!(reason = e, "failed to write accounts zadd to redis");
+            }
+        }
+
+        Ok(())
+    }
+
+    fn save_slots(&self, slots: Vec<(Address, Slot)>) -> anyhow::Result<()> {
+        // exit if no slots
+        if slots.is_empty() {
+            return Ok(());
+        }
+
+        // prepare values
+        let redis_slots = slots
+            .iter()
+            .map(|(address, slot)| {
+                let mut slot_value = to_json_value(slot);
+                slot_value.as_object_mut().unwrap().insert("block".to_owned(), to_json_value(BlockNumber::ZERO));
+                (key_slot(address, &slot.index), to_json_string(&slot_value))
+            })
+            .collect_vec();
+
+        // execute command
+        let mut conn = self.conn()?;
+        let set: RedisVoid = conn.mset(&redis_slots);
+        if let Err(e) = set {
+            return log_and_err!(reason = e, "failed to write slots to redis");
         }
+
+        // keep initial slots in history so they can be restored when resetting to a previous block
+        for ((address, slot), (_, slot_value)) in slots.iter().zip(redis_slots) {
+            let mut cmd = redis::cmd("ZADD");
+            cmd.arg(key_slot_history(address, &slot.index)).arg("NX").arg(0).arg(slot_value);
+
+            let zadd: RedisVoid = cmd.exec(&mut conn);
+            if let Err(e) = zadd {
+                return log_and_err!(reason = e, "failed to write slots zadd to redis");
+            }
+        }
+
+        Ok(())
     }
 
     fn read_account(&self, address: &Address, point_in_time: &crate::eth::storage::StoragePointInTime) -> anyhow::Result<Option<Account>> {
@@ -305,6 +560,45 @@ impl PermanentStorage for RedisPermanentStorage {
         }
     }
 
+    fn read_code(&self, code_hash: &CodeHash) -> anyhow::Result<Option<Bytes>> {
+        // execute command
+        let mut conn = self.conn()?;
+        let redis_code: RedisOptString = conn.get(key_code(code_hash));
+
+        // parse
+        match redis_code {
+            Ok(Some(json)) => Ok(Some(from_json_str(&json))),
+            Ok(None) => Ok(None),
+            Err(e) => log_and_err!(reason = e, "failed to read code from redis"),
+        }
+    }
+
+    fn read_all_accounts(&self) -> anyhow::Result<Vec<Account>> {
+        let mut conn = self.conn()?;
+        let values = match self.read_all_values(&mut conn, "account::*") {
+            Ok(values) => values,
+            Err(e) => return log_and_err!(reason = e, "failed to read all accounts from redis"),
+        };
+        Ok(values.into_iter().map(|(_, json)| from_json_str(&json)).collect())
+    }
+
+    fn read_all_slots(&self) -> anyhow::Result<Vec<(Address, Slot)>> {
+        let mut conn = self.conn()?;
+        let values = match self.read_all_values(&mut conn, "slot::*") {
+            Ok(values) => values,
+            Err(e) => return log_and_err!(reason = e, "failed to read all slots from redis"),
+        };
+
+        let mut slots = Vec::with_capacity(values.len());
+        for (key, json) in values {
+            let Some(address) = key.split("::").nth(1) else {
+                return log_and_err!(payload = key, "failed to parse address from redis slot key");
+            };
+            slots.push((address.parse()?, from_json_str(&json)));
+        }
+        Ok(slots)
+    }
+
     fn read_slot(&self, address: &Address, index: &SlotIndex, point_in_time: &StoragePointInTime) -> anyhow::Result<Option<Slot>> {
         // execute command and parse
         let mut conn = self.conn()?;
@@ -351,6 +645,40 @@ impl PermanentStorage for RedisPermanentStorage {
         }
     }
 
+    #[cfg(feature = "dev")]
+    fn set_balance(&self, address: &Address, balance: Wei) -> anyhow::Result<()> {
+        let mut account = self.read_current_account(address)?;
+        account.balance = balance;
+        self.save_account_override(account)
+    }
+
+    #[cfg(feature = "dev")]
+    fn set_nonce(&self, address: &Address, nonce: Nonce) -> anyhow::Result<()> {
+        let mut account = self.read_current_account(address)?;
+        account.nonce = nonce;
+        self.save_account_override(account)
+    }
+
+    #[cfg(feature = "dev")]
+    fn set_code(&self, address: &Address, code: Bytes) -> anyhow::Result<()> {
+        let mut account = self.read_current_account(address)?;
+        account.code_hash = CodeHash::from_bytecode(Some(code.clone()));
+
+        let mut conn = self.conn()?;
+        let set: RedisVoid = conn.set(key_code(&account.code_hash), to_json_string(&code));
+        if let Err(e) = set {
+            return log_and_err!(reason = e, "failed to write code to redis");
+        }
+
+        account.bytecode = Some(code);
+        self.save_account_override(account)
+    }
+
+    #[cfg(feature = "dev")]
+    fn set_storage(&self, address: &Address, slot: Slot) -> anyhow::Result<()> {
+        self.save_override(key_slot(address, &slot.index), key_slot_history(address, &slot.index), to_json_value(slot))
+    }
+
     #[cfg(feature = "dev")]
     fn reset(&self) -> anyhow::Result<()> {
         let mut conn = self.conn()?;
@@ -360,6 +688,89 @@ impl PermanentStorage for RedisPermanentStorage {
             Err(e) => log_and_err!(reason = e, "failed to clear all redis keys"),
         }
     }
+
+    fn reset_at(&self, number: BlockNumber) -> anyhow::Result<()> {
+        let mined_number = self.read_mined_block_number()?;
+        let mut conn = self.conn()?;
+
+        // remove blocks and transactions after the target block
+        let mut del_keys = vec![];
+        let mut zrem_values = vec![];
+        for block_number in (number.as_u64() + 1)..=mined_number.as_u64() {
+            let Some(block) = self.read_block(&BlockFilter::Number(block_number.into()))? else {
+                continue;
+            };
+            del_keys.push(key_block_by_number(block_number));
+            del_keys.push(key_block_by_hash(&block.hash()));
+            del_keys.extend(block.transactions.iter().map(|tx| key_tx(&tx.input.hash)));
+            zrem_values.extend(block.transactions.iter().flat_map(address_tx_members));
+        }
+        if not(del_keys.is_empty()) {
+            let del: RedisVoid = conn.del(del_keys);
+            if let Err(e) = del {
+                return log_and_err!(reason = e, "failed to delete blocks from redis");
+            }
+        }
+        for (key, member) in zrem_values {
+            let zrem: RedisVoid = conn.zrem(key, member);
+            if let Err(e) = zrem {
+                return log_and_err!(reason = e, "failed to delete address transactions from redis");
+            }
+        }
+
+        // remove account and slot changes after the target block and restore current values from the remaining history
+        let history_keys: RedisVecString = redis::cmd("KEYS").arg("*_history::*").query(&mut conn);
+        let history_keys = match history_keys {
+            Ok(keys) => keys,
+            Err(e) => return log_and_err!(reason = e, "failed to read history keys from redis"),
+        };
+        for key_history in history_keys {
+            let zrem: RedisVoid = conn.zrembyscore(&key_history, format!("({}", number.as_u64()), "+inf");
+            if let Err(e) = zrem {
+                return log_and_err!(reason = e, "failed to delete history values from redis");
+            }
+
+            let last: RedisVecString = conn.zrange(&key_history, -1, -1);
+            let key = key_current_from_history(&key_history);
+            let restore: RedisVoid = match last {
+                Ok(values) => match values.into_iter().next() {
+                    Some(value) => conn.set(key, value),
+                    None => conn.del(key),
+                },
+                Err(e) => return log_and_err!(reason = e, "failed to read history values from redis"),
+            };
+            if let Err(e) = restore {
+                return log_and_err!(reason = e, "failed to restore current value in redis");
+            }
+        }
+
+        // publish blocks produced again after the target block
+        let cursor_keys: RedisVecString = redis::cmd("KEYS").arg(key_publisher_cursor("*")).query(&mut conn);
+        let cursor_keys = match cursor_keys {
+            Ok(keys) => keys,
+            Err(e) => return log_and_err!(reason = e, "failed to read publisher cursor keys from redis"),
+        };
+        for key in cursor_keys {
+            let cursor: RedisOptUsize = conn.get(&key);
+            let rewind: RedisVoid = match cursor {
+                Ok(Some(cursor)) if BlockNumber::from(cursor) > number => conn.set(key, number.to_string()),
+                Ok(_) => continue,
+                Err(e) => return log_and_err!(reason = e, "failed to read publisher cursor from redis"),
+            };
+            if let Err(e) = rewind {
+                return log_and_err!(reason = e, "failed to rewind publisher cursor in redis");
+            }
+        }
+
+        // update latest block and mined number
+        if let Some(block) = self.read_block(&BlockFilter::Number(number))? {
+            let set: RedisVoid = conn.set("block::latest", to_json_string(&block));
+            if let Err(e) = set {
+                return log_and_err!(reason = e, "failed to write latest block to redis");
+            }
+        }
+        self.set_mined_block_number(number)
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -386,6 +797,11 @@ fn key_account_history(address: &Address) -> String {
     format!("account_history::{}", address)
 }
 
+/// Generates a key for accessing a bytecode by its hash.
+fn key_code(code_hash: &CodeHash) -> String {
+    format!("code::{}", const_hex::encode_prefixed(code_hash))
+}
+
 /// Generates a key for accessing a slot.
 fn key_slot(address: &Address, index: &SlotIndex) -> String {
     format!("slot::{}::{}", address, index)
@@ -400,3 +816,33 @@ fn key_slot_history(address: &Address, index: &SlotIndex) -> String {
 fn key_tx(hash: &Hash) -> String {
     format!("tx::{}", hash)
 }
+
+/// Generates a key for accessing the transactions of an address, sorted by block number.
+fn key_address_txs(address: &Address) -> String {
+    format!("address_txs::{}", address)
+}
+
+/// Generates the member of a transaction in the transactions of an address.
+///
+/// Members with the same block number are sorted lexicographically, so the transaction index is padded to keep them in block order.
+fn member_address_tx(tx: &TransactionMined) -> String {
+    format!("{:020}::{}", tx.transaction_index.0, tx.input.hash)
+}
+
+/// Generates the keys and members of a transaction in the transactions of all addresses it is indexed by.
+fn address_tx_members(tx: &TransactionMined) -> Vec<(String, String)> {
+    tx.indexed_addresses()
+        .iter()
+        .map(|address| (key_address_txs(address), member_address_tx(tx)))
+        .collect()
+}
+
+/// Generates a key for accessing the last block delivered by a publisher.
+fn key_publisher_cursor(publisher: &str) -> String {
+    format!("publisher::cursor::{}", publisher)
+}
+
+/// Converts a history key (account or slot) to its current value key.
+fn key_current_from_history(key_history: &str) -> String {
+    key_history.replacen("_history::", "::", 1)
+}
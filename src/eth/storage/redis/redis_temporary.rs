@@ -0,0 +1,555 @@
+use std::sync::Mutex;
+
+use indexmap::IndexMap;
+use redis::Client as RedisClient;
+use redis::Commands;
+use redis::Connection as RedisConnection;
+use redis::RedisResult;
+
+use crate::eth::primitives::Account;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::EvmExecution;
+use crate::eth::primitives::ExecutionConflicts;
+use crate::eth::primitives::ExecutionConflictsBuilder;
+use crate::eth::primitives::ExternalBlock;
+use crate::eth::primitives::Gas;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::PendingBlock;
+use crate::eth::primitives::PendingBlockHeader;
+use crate::eth::primitives::Slot;
+use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TransactionExecution;
+use crate::eth::storage::TemporaryStorage;
+use crate::eth::storage::TemporaryStorageStats;
+use crate::ext::from_json_str;
+use crate::ext::not;
+use crate::ext::to_json_string;
+use crate::ext::MutexExt;
+use crate::log_and_err;
+
+type RedisVecOptString = RedisResult<Vec<Option<String>>>;
+type RedisVecOptU64 = RedisResult<Vec<Option<u64>>>;
+type RedisVecString = RedisResult<Vec<String>>;
+type RedisVecUsize = RedisResult<Vec<usize>>;
+type RedisOptString = RedisResult<Option<String>>;
+type RedisVoid = RedisResult<()>;
+
+/// Number of previous blocks to keep in Redis to detect conflicts between different blocks.
+const MAX_BLOCKS: u64 = 64;
+
+/// Temporary storage that keeps the pending block and the account/slot overlay in Redis.
+///
+/// The state survives restarts and can be inspected by other processes. Only one process should write to the same keyspace, because
+/// writes are serialized only inside the process.
+///
+/// Keyspace:
+/// * `temp::block::header`: header of the pending block.
+/// * `temp::block::external`: external block being re-executed.
+/// * `temp::block::tx_hashes`: list of hashes of the pending transactions in execution order.
+/// * `temp::block::txs`: hash of pending transactions by transaction hash.
+/// * `temp::state::head` and `temp::state::len`: id of the newest account/slot state and number of states kept.
+/// * `temp::state::{id}::accounts` and `temp::state::{id}::slots`: accounts and slots changed in each state.
+pub struct RedisTemporaryStorage {
+    client: redis::Client,
+
+    /// Serializes writes, so conflict checks and saves are atomic like in the in-memory implementation.
+    write_lock: Mutex<()>,
+}
+
+impl RedisTemporaryStorage {
+    pub fn new(url: &str) -> anyhow::Result<Self> {
+        tracing::info!("creating redis temporary storage");
+        let client = match RedisClient::open(url) {
+            Ok(client) => client,
+            Err(e) => return log_and_err!(reason = e, "failed to create redis client"),
+        };
+        Ok(Self {
+            client,
+            write_lock: Mutex::new(()),
+        })
+    }
+
+    fn conn(&self) -> anyhow::Result<RedisConnection> {
+        match self.client.get_connection() {
+            Ok(conn) => Ok(conn),
+            Err(e) => log_and_err!(reason = e, "failed to get redis connection"),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// States
+// -----------------------------------------------------------------------------
+
+/// Ids of the account/slot states kept to detect conflicts.
+#[derive(Debug, Clone, Copy)]
+struct RedisStates {
+    /// Id of the newest state.
+    head: u64,
+
+    /// Number of states kept.
+    len: u64,
+}
+
+impl RedisStates {
+    /// Ids from the newest to the oldest state.
+    fn ids(&self) -> impl Iterator<Item = u64> {
+        let head = self.head;
+        (0..self.len).map(move |offset| head - offset)
+    }
+
+    /// Id of the oldest state.
+    fn tail(&self) -> u64 {
+        self.head + 1 - self.len
+    }
+}
+
+impl TemporaryStorage for RedisTemporaryStorage {
+    // -------------------------------------------------------------------------
+    // Block number
+    // -------------------------------------------------------------------------
+
+    fn set_pending_block_number(&self, number: BlockNumber) -> anyhow::Result<()> {
+        let _lock = self.write_lock.lock_or_clear("redis temporary storage lock was poisoned");
+        let mut conn = self.conn()?;
+
+        let header = match do_read_header(&mut conn)? {
+            Some(mut header) => {
+                header.number = number;
+                header
+            }
+            None => PendingBlockHeader::new_at_now(number),
+        };
+
+        let set: RedisVoid = conn.set(KEY_HEADER, to_json_string(&header));
+        match set {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to write pending block header to redis"),
+        }
+    }
+
+    fn read_pending_block_number(&self) -> anyhow::Result<Option<BlockNumber>> {
+        let mut conn = self.conn()?;
+        Ok(do_read_header(&mut conn)?.map(|header| header.number))
+    }
+
+    // -------------------------------------------------------------------------
+    // Block and executions
+    // -------------------------------------------------------------------------
+
+    fn set_pending_external_block(&self, block: ExternalBlock) -> anyhow::Result<()> {
+        let _lock = self.write_lock.lock_or_clear("redis temporary storage lock was poisoned");
+        let mut conn = self.conn()?;
+        require_pending_block(&mut conn)?;
+
+        let set: RedisVoid = conn.set(KEY_EXTERNAL_BLOCK, to_json_string(&block));
+        match set {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to write pending external block to redis"),
+        }
+    }
+
+    fn save_execution(&self, tx: TransactionExecution, check_conflicts: bool) -> Result<(), StratusError> {
+        let _lock = self.write_lock.lock_or_clear("redis temporary storage lock was poisoned");
+        let mut conn = self.conn()?;
+        let states = do_read_states(&mut conn)?;
+
+        // check conflicts
+        if check_conflicts {
+            if let Some(conflicts) = do_check_conflicts(&mut conn, states, tx.execution())? {
+                return Err(StratusError::TransactionConflict(conflicts.into()));
+            }
+        }
+
+        // save account changes and execution
+        require_pending_block(&mut conn)?;
+        do_save_execution(&mut conn, states, &tx)?;
+
+        Ok(())
+    }
+
+    fn pending_transactions(&self) -> Vec<TransactionExecution> {
+        let Ok(mut conn) = self.conn() else { return Vec::new() };
+        match do_read_pending_block(&mut conn) {
+            Ok(Some(block)) => block.transactions.into_values().collect(),
+            Ok(None) | Err(_) => Vec::new(),
+        }
+    }
+
+    fn read_pending_block(&self) -> anyhow::Result<Option<PendingBlock>> {
+        let mut conn = self.conn()?;
+        do_read_pending_block(&mut conn)
+    }
+
+    fn finish_pending_block(&self, gas_limit: Gas) -> anyhow::Result<(PendingBlock, Vec<TransactionExecution>)> {
+        let _lock = self.write_lock.lock_or_clear("redis temporary storage lock was poisoned");
+        let mut conn = self.conn()?;
+
+        let Some(mut finished_block) = do_read_pending_block(&mut conn)? else {
+            return log_and_err!("no pending block being mined");
+        };
+        let mut states = do_read_states(&mut conn)?;
+
+        // remove transactions that did not fit in the finished block and recreate its state without them
+        let remaining_txs = finished_block.split_off_over_gas_limit(gas_limit);
+        if not(remaining_txs.is_empty()) {
+            tracing::info!(
+                block_number = %finished_block.header.number,
+                remaining_txs = %remaining_txs.len(),
+                "block gas limit reached, moving remaining transactions to next block"
+            );
+            let clear: RedisVoid = conn.del([key_state_accounts(states.head), key_state_slots(states.head)]);
+            if let Err(e) = clear {
+                return log_and_err!(reason = e, "failed to clear temporary state in redis");
+            }
+            for tx in finished_block.transactions.values() {
+                let mut pipe = redis::pipe();
+                pipe.atomic();
+                do_save_account_changes(&mut conn, &mut pipe, states, tx.execution())?;
+                let save: RedisVoid = pipe.query(&mut conn);
+                if let Err(e) = save {
+                    return log_and_err!(reason = e, "failed to write temporary state to redis");
+                }
+            }
+        }
+
+        // remove last state if reached limit and create new state
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        if states.len + 1 >= MAX_BLOCKS {
+            pipe.del(key_state_accounts(states.tail())).ignore();
+            pipe.del(key_state_slots(states.tail())).ignore();
+            states.len -= 1;
+        }
+        states.head += 1;
+        states.len += 1;
+
+        // start new block
+        let header = PendingBlockHeader::new_at_now(finished_block.header.number.next_block_number());
+        pipe.set(KEY_STATE_HEAD, states.head).ignore();
+        pipe.set(KEY_STATE_LEN, states.len).ignore();
+        pipe.set(KEY_HEADER, to_json_string(&header)).ignore();
+        pipe.del([KEY_EXTERNAL_BLOCK, KEY_TX_HASHES, KEY_TXS]).ignore();
+        let finish: RedisVoid = pipe.query(&mut conn);
+        if let Err(e) = finish {
+            return log_and_err!(reason = e, "failed to finish pending block in redis");
+        }
+
+        Ok((finished_block, remaining_txs))
+    }
+
+    fn read_transaction(&self, hash: &Hash) -> anyhow::Result<Option<TransactionExecution>> {
+        let mut conn = self.conn()?;
+        let redis_tx: RedisOptString = conn.hget(KEY_TXS, hash.to_string());
+        match redis_tx {
+            Ok(Some(json)) => Ok(Some(from_json_str(&json))),
+            Ok(None) => Ok(None),
+            Err(e) => log_and_err!(reason = e, "failed to read pending transaction from redis"),
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // Accounts and Slots
+    // -------------------------------------------------------------------------
+
+    fn read_account(&self, address: &Address) -> anyhow::Result<Option<Account>> {
+        let mut conn = self.conn()?;
+        let states = do_read_states(&mut conn)?;
+        do_read_account(&mut conn, states, address)
+    }
+
+    fn read_slot(&self, address: &Address, index: &SlotIndex) -> anyhow::Result<Option<Slot>> {
+        let mut conn = self.conn()?;
+        let states = do_read_states(&mut conn)?;
+        do_read_slot(&mut conn, states, address, index)
+    }
+
+    // -------------------------------------------------------------------------
+    // Global state
+    // -------------------------------------------------------------------------
+    fn reset(&self) -> anyhow::Result<()> {
+        let _lock = self.write_lock.lock_or_clear("redis temporary storage lock was poisoned");
+        let mut conn = self.conn()?;
+
+        let keys: RedisVecString = redis::cmd("KEYS").arg("temp::*").query(&mut conn);
+        let keys = match keys {
+            Ok(keys) => keys,
+            Err(e) => return log_and_err!(reason = e, "failed to read temporary keys from redis"),
+        };
+        if keys.is_empty() {
+            return Ok(());
+        }
+
+        let del: RedisVoid = conn.del(keys);
+        match del {
+            Ok(_) => Ok(()),
+            Err(e) => log_and_err!(reason = e, "failed to delete temporary keys from redis"),
+        }
+    }
+
+    fn stats(&self) -> TemporaryStorageStats {
+        let stats = || -> anyhow::Result<TemporaryStorageStats> {
+            let mut conn = self.conn()?;
+            let states = do_read_states(&mut conn)?;
+
+            let mut pipe = redis::pipe();
+            for id in states.ids() {
+                pipe.hlen(key_state_accounts(id));
+            }
+            for id in states.ids() {
+                pipe.hlen(key_state_slots(id));
+            }
+            let lens: RedisVecUsize = pipe.query(&mut conn);
+            let lens = match lens {
+                Ok(lens) => lens,
+                Err(e) => return log_and_err!(reason = e, "failed to read temporary storage stats from redis"),
+            };
+            let (accounts, slots) = lens.split_at(states.len as usize);
+
+            let pending_transactions: RedisResult<usize> = conn.llen(KEY_TX_HASHES);
+            let pending_transactions = match pending_transactions {
+                Ok(len) => len,
+                Err(e) => return log_and_err!(reason = e, "failed to read temporary storage stats from redis"),
+            };
+
+            Ok(TemporaryStorageStats {
+                pending_block_number: do_read_header(&mut conn)?.map(|header| header.number),
+                pending_transactions,
+                accounts: accounts.iter().sum(),
+                slots: slots.iter().sum(),
+            })
+        };
+        stats().unwrap_or_default()
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Implementations without lock
+// -----------------------------------------------------------------------------
+
+fn do_read_states(conn: &mut RedisConnection) -> anyhow::Result<RedisStates> {
+    let values: RedisVecOptU64 = conn.mget([KEY_STATE_HEAD, KEY_STATE_LEN]);
+    match values.as_deref() {
+        Ok([head, len]) => Ok(RedisStates {
+            head: head.unwrap_or_default(),
+            len: len.unwrap_or(1),
+        }),
+        Ok(_) => log_and_err!("unexpected number of values when reading temporary states from redis"),
+        Err(e) => log_and_err!(reason = e, "failed to read temporary states from redis"),
+    }
+}
+
+fn do_read_header(conn: &mut RedisConnection) -> anyhow::Result<Option<PendingBlockHeader>> {
+    let header: RedisOptString = conn.get(KEY_HEADER);
+    match header {
+        Ok(Some(json)) => Ok(Some(from_json_str(&json))),
+        Ok(None) => Ok(None),
+        Err(e) => log_and_err!(reason = e, "failed to read pending block header from redis"),
+    }
+}
+
+/// Validates there is a pending block being mined.
+fn require_pending_block(conn: &mut RedisConnection) -> anyhow::Result<PendingBlockHeader> {
+    match do_read_header(conn)? {
+        Some(header) => Ok(header),
+        None => log_and_err!("no pending block being mined"),
+    }
+}
+
+fn do_read_pending_block(conn: &mut RedisConnection) -> anyhow::Result<Option<PendingBlock>> {
+    let Some(header) = do_read_header(conn)? else { return Ok(None) };
+
+    // external block
+    let external_block: RedisOptString = conn.get(KEY_EXTERNAL_BLOCK);
+    let external_block = match external_block {
+        Ok(json) => json.map(|json| from_json_str::<ExternalBlock>(&json)),
+        Err(e) => return log_and_err!(reason = e, "failed to read pending external block from redis"),
+    };
+
+    // transactions in execution order
+    let hashes: RedisVecString = conn.lrange(KEY_TX_HASHES, 0, -1);
+    let hashes = match hashes {
+        Ok(hashes) => hashes,
+        Err(e) => return log_and_err!(reason = e, "failed to read pending transaction hashes from redis"),
+    };
+    let mut transactions = IndexMap::with_capacity(hashes.len());
+    if not(hashes.is_empty()) {
+        let txs: RedisVecOptString = redis::cmd("HMGET").arg(KEY_TXS).arg(&hashes).query(conn);
+        let txs = match txs {
+            Ok(txs) => txs,
+            Err(e) => return log_and_err!(reason = e, "failed to read pending transactions from redis"),
+        };
+        for json in txs.into_iter().flatten() {
+            let tx: TransactionExecution = from_json_str(&json);
+            transactions.insert(tx.hash(), tx);
+        }
+    }
+
+    Ok(Some(PendingBlock {
+        header,
+        transactions,
+        external_block,
+    }))
+}
+
+fn do_save_execution(conn: &mut RedisConnection, states: RedisStates, tx: &TransactionExecution) -> anyhow::Result<()> {
+    let mut pipe = redis::pipe();
+    pipe.atomic();
+
+    // save account changes
+    do_save_account_changes(conn, &mut pipe, states, tx.execution())?;
+
+    // save execution
+    let hash = tx.hash().to_string();
+    pipe.hset(KEY_TXS, &hash, to_json_string(tx)).ignore();
+    pipe.rpush(KEY_TX_HASHES, &hash).ignore();
+
+    let save: RedisVoid = pipe.query(conn);
+    match save {
+        Ok(_) => Ok(()),
+        Err(e) => log_and_err!(reason = e, "failed to write pending execution to redis"),
+    }
+}
+
+/// Adds the account changes of an execution to the newest state in the pipeline.
+fn do_save_account_changes(conn: &mut RedisConnection, pipe: &mut redis::Pipeline, states: RedisStates, execution: &EvmExecution) -> anyhow::Result<()> {
+    for change in execution.changes.values() {
+        let current: RedisOptString = conn.hget(key_state_accounts(states.head), change.address.to_string());
+        let mut account = match current {
+            Ok(Some(json)) => from_json_str::<Account>(&json),
+            Ok(None) => Account::new_empty(change.address),
+            Err(e) => return log_and_err!(reason = e, "failed to read temporary account from redis"),
+        };
+
+        // account basic info
+        if let Some(nonce) = change.nonce.take_ref() {
+            account.nonce = *nonce;
+        }
+        if let Some(balance) = change.balance.take_ref() {
+            account.balance = *balance;
+        }
+
+        // bytecode
+        if let Some(Some(bytecode)) = change.bytecode.take_ref() {
+            account.bytecode = Some(bytecode.clone());
+        }
+        pipe.hset(key_state_accounts(states.head), change.address.to_string(), to_json_string(&account))
+            .ignore();
+
+        // slots
+        for slot in change.slots.values() {
+            if let Some(slot) = slot.take_ref() {
+                pipe.hset(key_state_slots(states.head), field_slot(&change.address, &slot.index), to_json_string(slot))
+                    .ignore();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads the same field from all states, returning the value of the newest state that contains it.
+fn do_read_newest(conn: &mut RedisConnection, keys: impl Iterator<Item = String>, field: &str) -> anyhow::Result<Option<String>> {
+    let mut pipe = redis::pipe();
+    for key in keys {
+        pipe.hget(key, field);
+    }
+    let values: RedisVecOptString = pipe.query(conn);
+    match values {
+        Ok(values) => Ok(values.into_iter().flatten().next()),
+        Err(e) => log_and_err!(reason = e, "failed to read temporary state from redis"),
+    }
+}
+
+fn do_read_account(conn: &mut RedisConnection, states: RedisStates, address: &Address) -> anyhow::Result<Option<Account>> {
+    let keys = states.ids().map(key_state_accounts);
+    match do_read_newest(conn, keys, &address.to_string())? {
+        Some(json) => {
+            let account: Account = from_json_str(&json);
+            tracing::trace!(%address, ?account, "account found");
+            Ok(Some(account))
+        }
+        None => {
+            tracing::trace!(%address, "account not found");
+            Ok(None)
+        }
+    }
+}
+
+fn do_read_slot(conn: &mut RedisConnection, states: RedisStates, address: &Address, index: &SlotIndex) -> anyhow::Result<Option<Slot>> {
+    let keys = states.ids().map(key_state_slots);
+    match do_read_newest(conn, keys, &field_slot(address, index))? {
+        Some(json) => {
+            let slot: Slot = from_json_str(&json);
+            tracing::trace!(%address, %index, %slot, "slot found in temporary");
+            Ok(Some(slot))
+        }
+        None => {
+            tracing::trace!(%address, %index, "slot not found in temporary");
+            Ok(None)
+        }
+    }
+}
+
+fn do_check_conflicts(conn: &mut RedisConnection, states: RedisStates, execution: &EvmExecution) -> anyhow::Result<Option<ExecutionConflicts>> {
+    let mut conflicts = ExecutionConflictsBuilder::default();
+
+    for (address, change) in &execution.changes {
+        // check account info conflicts
+        if let Some(account) = do_read_account(conn, states, address)? {
+            if let Some(expected) = change.nonce.take_original_ref() {
+                let original = &account.nonce;
+                if expected != original {
+                    conflicts.add_nonce(*address, *original, *expected);
+                }
+            }
+            if let Some(expected) = change.balance.take_original_ref() {
+                let original = &account.balance;
+                if expected != original {
+                    conflicts.add_balance(*address, *original, *expected);
+                }
+            }
+        }
+
+        // check slots conflicts
+        for (slot_index, slot_change) in &change.slots {
+            if let Some(expected) = slot_change.take_original_ref() {
+                let Some(original) = do_read_slot(conn, states, address, slot_index)? else {
+                    continue;
+                };
+                if expected.value != original.value {
+                    conflicts.add_slot(*address, *slot_index, original.value, expected.value);
+                }
+            }
+        }
+    }
+
+    Ok(conflicts.build())
+}
+
+// -----------------------------------------------------------------------------
+// Keys helpers
+// -----------------------------------------------------------------------------
+
+const KEY_HEADER: &str = "temp::block::header";
+const KEY_EXTERNAL_BLOCK: &str = "temp::block::external";
+const KEY_TX_HASHES: &str = "temp::block::tx_hashes";
+const KEY_TXS: &str = "temp::block::txs";
+const KEY_STATE_HEAD: &str = "temp::state::head";
+const KEY_STATE_LEN: &str = "temp::state::len";
+
+/// Generates a key for accessing the accounts changed in a state.
+fn key_state_accounts(id: u64) -> String {
+    format!("temp::state::{}::accounts", id)
+}
+
+/// Generates a key for accessing the slots changed in a state.
+fn key_state_slots(id: u64) -> String {
+    format!("temp::state::{}::slots", id)
+}
+
+/// Generates a hash field for accessing a slot inside a state.
+fn field_slot(address: &Address, index: &SlotIndex) -> String {
+    format!("{}::{}", address, index)
+}
@@ -0,0 +1,353 @@
+//! Chain data retention policy.
+//!
+//! Old block data is pruned by a background janitor according to a declarative policy that configures how long each kind of data is
+//! kept. Block headers and account state are never pruned, so the chain can still be navigated and executed against after pruning.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use clap::Parser;
+use display_json::DebugAsJson;
+
+use crate::eth::primitives::Block;
+use crate::eth::primitives::BlockFilter;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::UnixTime;
+use crate::eth::storage::StratusStorage;
+use crate::ext::not;
+use crate::ext::parse_duration;
+use crate::ext::spawn_named;
+use crate::ext::traced_sleep;
+use crate::ext::MutexExt;
+use crate::ext::SleepReason;
+use crate::GlobalState;
+
+// -----------------------------------------------------------------------------
+// Target
+// -----------------------------------------------------------------------------
+
+/// Kind of block data that can be pruned by the retention policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::Display, strum::IntoStaticStr, serde::Serialize)]
+pub enum RetentionTarget {
+    /// Transactions with their receipts and logs. Blocks keep their headers but have no transactions.
+    #[strum(to_string = "receipts")]
+    #[serde(rename = "receipts")]
+    Receipts,
+
+    /// Logs emitted by transactions. Transactions and receipts are kept without logs.
+    #[strum(to_string = "logs")]
+    #[serde(rename = "logs")]
+    Logs,
+}
+
+impl RetentionTarget {
+    /// Removes the data covered by this target from a block, keeping its header.
+    ///
+    /// Returns `false` if the block had nothing to prune.
+    pub fn prune_block(&self, block: &mut Block) -> bool {
+        match self {
+            Self::Receipts => {
+                let modified = not(block.transactions.is_empty());
+                block.transactions.clear();
+                modified
+            }
+            Self::Logs => {
+                let mut modified = false;
+                for tx in &mut block.transactions {
+                    modified |= not(tx.logs.is_empty());
+                    tx.logs.clear();
+                }
+                modified
+            }
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Report
+// -----------------------------------------------------------------------------
+
+/// Summary of the data removed (or that would be removed in dry-run mode) by one janitor run for one target.
+#[derive(DebugAsJson, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct RetentionReport {
+    pub target: RetentionTarget,
+    pub dry_run: bool,
+
+    /// First block in the pruned range.
+    pub from_block: BlockNumber,
+
+    /// Last block in the pruned range (inclusive).
+    pub to_block: BlockNumber,
+
+    /// Number of blocks that had data removed.
+    pub blocks: u64,
+
+    /// Number of transactions removed.
+    pub transactions: u64,
+
+    /// Number of logs removed.
+    pub logs: u64,
+}
+
+// -----------------------------------------------------------------------------
+// Janitor
+// -----------------------------------------------------------------------------
+
+/// Background task that periodically prunes block data older than the configured retention of each target.
+pub struct RetentionJanitor {
+    storage: Arc<StratusStorage>,
+
+    /// How long the data of each target is kept. Targets not present are kept forever.
+    policy: Vec<(RetentionTarget, Duration)>,
+
+    /// Only reports what would be pruned without modifying the storage.
+    dry_run: bool,
+
+    /// First block of each target that was not pruned yet.
+    next_block: Mutex<HashMap<RetentionTarget, BlockNumber>>,
+}
+
+impl RetentionJanitor {
+    /// Creates a new janitor for the specified policy.
+    pub fn new(storage: Arc<StratusStorage>, policy: Vec<(RetentionTarget, Duration)>, dry_run: bool) -> Self {
+        Self {
+            storage,
+            policy,
+            dry_run,
+            next_block: Mutex::default(),
+        }
+    }
+
+    /// Evaluates the policy of all targets once, pruning blocks mined before `now` minus the retention of each target.
+    pub fn run_once(&self, now: UnixTime) -> Result<Vec<RetentionReport>, StratusError> {
+        let mut reports = Vec::with_capacity(self.policy.len());
+        for (target, retention) in &self.policy {
+            let cutoff = UnixTime::from(now.as_u64().saturating_sub(retention.as_secs()));
+            if let Some(report) = self.run_target(*target, cutoff)? {
+                reports.push(report);
+            }
+        }
+        Ok(reports)
+    }
+
+    /// Prunes the data of a target in all blocks mined at or before `cutoff`.
+    fn run_target(&self, target: RetentionTarget, cutoff: UnixTime) -> Result<Option<RetentionReport>, StratusError> {
+        let from_block = self
+            .next_block
+            .lock_or_clear("retention lock was poisoned")
+            .get(&target)
+            .copied()
+            .unwrap_or(BlockNumber::ZERO);
+        let Some(to_block) = self.find_last_block_before(from_block, cutoff)? else {
+            return Ok(None);
+        };
+
+        // count what will be removed
+        let mut report = RetentionReport {
+            target,
+            dry_run: self.dry_run,
+            from_block,
+            to_block,
+            blocks: 0,
+            transactions: 0,
+            logs: 0,
+        };
+        for number in from_block.as_u64()..=to_block.as_u64() {
+            let Some(mut block) = self.storage.read_block(&BlockFilter::Number(number.into()))? else {
+                continue;
+            };
+            let transactions = block.transactions.len() as u64;
+            let logs = block.transactions.iter().map(|tx| tx.logs.len() as u64).sum::<u64>();
+            if target.prune_block(&mut block) {
+                report.blocks += 1;
+                report.logs += logs;
+                if target == RetentionTarget::Receipts {
+                    report.transactions += transactions;
+                }
+            }
+        }
+
+        // prune
+        if not(self.dry_run) {
+            if report.blocks > 0 {
+                self.storage.prune_blocks(target, from_block, to_block)?;
+            }
+            self.next_block
+                .lock_or_clear("retention lock was poisoned")
+                .insert(target, to_block.next_block_number());
+        }
+
+        Ok(Some(report))
+    }
+
+    /// Finds the last mined block with timestamp at or before `cutoff`, starting the search at `from_block`.
+    ///
+    /// Block timestamps are non-decreasing, so it is a binary search.
+    fn find_last_block_before(&self, from_block: BlockNumber, cutoff: UnixTime) -> Result<Option<BlockNumber>, StratusError> {
+        let is_before_cutoff = |number: u64| -> Result<bool, StratusError> {
+            match self.storage.read_block(&BlockFilter::Number(number.into()))? {
+                Some(block) => Ok(block.header.timestamp.as_u64() <= cutoff.as_u64()),
+                // missing blocks (like an absent genesis) have no data to keep
+                None => Ok(true),
+            }
+        };
+
+        let mut low = from_block.as_u64();
+        let mut high = self.storage.read_mined_block_number()?.as_u64();
+        if low > high || not(is_before_cutoff(low)?) {
+            return Ok(None);
+        }
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if is_before_cutoff(mid)? {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        Ok(Some(low.into()))
+    }
+
+    /// Spawns the janitor task that evaluates the policy at the specified interval.
+    pub fn spawn(self: Arc<Self>, interval: Duration) {
+        const TASK_NAME: &str = "storage::retention";
+        spawn_named(TASK_NAME, async move {
+            loop {
+                if GlobalState::is_shutdown_warn(TASK_NAME) {
+                    return;
+                }
+
+                match self.run_once(UnixTime::now()) {
+                    Ok(reports) =>
+                        for report in reports {
+                            tracing::info!(?report, "evaluated retention policy");
+                        },
+                    Err(e) => tracing::error!(reason = ?e, "failed to evaluate retention policy"),
+                }
+
+                traced_sleep(interval, SleepReason::Interval).await;
+            }
+        });
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Config
+// -----------------------------------------------------------------------------
+
+/// Retention policy configuration. Data without a configured retention is kept forever.
+#[derive(DebugAsJson, Clone, Parser, serde::Serialize)]
+pub struct RetentionConfig {
+    /// How long transactions, receipts and logs are kept after their block is mined.
+    #[arg(long = "retention-receipts", value_parser=parse_duration, env = "RETENTION_RECEIPTS")]
+    pub retention_receipts: Option<Duration>,
+
+    /// How long logs are kept after their block is mined.
+    #[arg(long = "retention-logs", value_parser=parse_duration, env = "RETENTION_LOGS")]
+    pub retention_logs: Option<Duration>,
+
+    /// Interval between retention policy evaluations.
+    #[arg(long = "retention-interval", value_parser=parse_duration, env = "RETENTION_INTERVAL", default_value = "1h")]
+    pub retention_interval: Duration,
+
+    /// Only reports what would be pruned by the retention policy without modifying the storage.
+    #[arg(long = "retention-dry-run", env = "RETENTION_DRY_RUN")]
+    pub retention_dry_run: bool,
+}
+
+impl RetentionConfig {
+    /// Retention of each target that is not kept forever.
+    pub fn policy(&self) -> Vec<(RetentionTarget, Duration)> {
+        let mut policy = vec![];
+        if let Some(retention) = self.retention_receipts {
+            policy.push((RetentionTarget::Receipts, retention));
+        }
+        if let Some(retention) = self.retention_logs {
+            policy.push((RetentionTarget::Logs, retention));
+        }
+        policy
+    }
+
+    /// Spawns the retention janitor if any retention is configured.
+    pub fn init(&self, storage: Arc<StratusStorage>) -> Option<Arc<RetentionJanitor>> {
+        let policy = self.policy();
+        if policy.is_empty() {
+            return None;
+        }
+
+        // archive nodes are read-only, so they can only report
+        let dry_run = self.retention_dry_run || GlobalState::is_archive();
+        tracing::info!(config = ?self, %dry_run, "creating retention janitor");
+
+        let janitor = Arc::new(RetentionJanitor::new(storage, policy, dry_run));
+        Arc::clone(&janitor).spawn(self.retention_interval);
+        Some(janitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth::primitives::Hash;
+    use crate::eth::primitives::LogMined;
+    use crate::eth::primitives::TransactionMined;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::InMemoryTemporaryStorage;
+    use crate::utils::test_utils::fake_first;
+
+    /// Timestamp of the first block, after the genesis block timestamp.
+    const T0: u64 = 2_000_000_000;
+
+    fn storage_with_blocks(count: u64) -> Arc<StratusStorage> {
+        let storage = StratusStorage::new(Box::<InMemoryTemporaryStorage>::default(), Box::<InMemoryPermanentStorage>::default()).unwrap();
+        for number in 1..=count {
+            let mut block = Block::new(number.into(), UnixTime::from(T0 + number * 100));
+            let mut tx = fake_first::<TransactionMined>();
+            tx.input.hash = Hash::new([number as u8; 32]);
+            tx.block_number = number.into();
+            tx.logs = vec![fake_first::<LogMined>(), fake_first::<LogMined>()];
+            block.transactions.push(tx);
+            storage.set_pending_block_number(BlockNumber::from(number).next_block_number()).unwrap();
+            storage.save_block(block).unwrap();
+            storage.set_mined_block_number(number.into()).unwrap();
+        }
+        Arc::new(storage)
+    }
+
+    #[test]
+    fn retention_dry_run_reports_without_pruning() {
+        let storage = storage_with_blocks(5);
+        let janitor = RetentionJanitor::new(Arc::clone(&storage), vec![(RetentionTarget::Logs, Duration::from_secs(200))], true);
+
+        let reports = janitor.run_once(UnixTime::from(T0 + 500)).unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].to_block, BlockNumber::from(3));
+        assert_eq!(reports[0].blocks, 3);
+        assert_eq!(reports[0].logs, 6);
+
+        let block = storage.read_block(&BlockFilter::Number(1.into())).unwrap().unwrap();
+        assert_eq!(block.transactions[0].logs.len(), 2);
+    }
+
+    #[test]
+    fn retention_prunes_old_blocks_only() {
+        let storage = storage_with_blocks(5);
+        let janitor = RetentionJanitor::new(Arc::clone(&storage), vec![(RetentionTarget::Receipts, Duration::from_secs(200))], false);
+
+        let reports = janitor.run_once(UnixTime::from(T0 + 500)).unwrap();
+        assert_eq!(reports[0].transactions, 3);
+        for number in 1..=5u64 {
+            let block = storage.read_block(&BlockFilter::Number(number.into())).unwrap().unwrap();
+            assert_eq!(block.transactions.is_empty(), number <= 3);
+            assert_eq!(storage.read_transaction(&Hash::new([number as u8; 32])).unwrap().is_none(), number <= 3);
+        }
+
+        // already pruned blocks are not evaluated again
+        let reports = janitor.run_once(UnixTime::from(T0 + 600)).unwrap();
+        assert_eq!(reports[0].from_block, BlockNumber::from(4));
+        assert_eq!(reports[0].transactions, 1);
+    }
+}
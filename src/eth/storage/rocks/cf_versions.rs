@@ -14,12 +14,18 @@ use strum::IntoStaticStr;
 use strum::VariantNames;
 
 use super::types::AccountRocksdb;
+use super::types::AccountRocksdbV2;
 use super::types::BlockNumberRocksdb;
 use super::types::BlockRocksdb;
+use super::types::BlockRocksdbV2;
+use super::types::BlockRocksdbV3;
+use super::types::BytesRocksdb;
+use super::types::IndexRocksdb;
 use super::types::SlotValueRocksdb;
-use crate::eth::primitives::Account;
 use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::Index;
 use crate::eth::primitives::SlotValue;
 
 macro_rules! impl_single_version_cf_value {
@@ -75,14 +81,107 @@ macro_rules! impl_single_version_cf_value {
     };
 }
 
-impl_single_version_cf_value!(CfAccountsValue, AccountRocksdb, Account);
-impl_single_version_cf_value!(CfAccountsHistoryValue, AccountRocksdb, Account);
+/// Account values with two versions:
+///
+/// - `V1`: bytecode stored inline in the account.
+/// - `V2`: bytecode stored in the `account_codes` column family and referenced by its hash.
+macro_rules! impl_account_cf_value {
+    ($name:ident) => {
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, EnumCount, VariantNames, IntoStaticStr)]
+        pub enum $name {
+            V1(AccountRocksdb),
+            V2(AccountRocksdbV2),
+        }
+
+        // new values are always written in the latest version
+        impl From<AccountRocksdbV2> for $name {
+            fn from(v2: AccountRocksdbV2) -> Self {
+                Self::V2(v2)
+            }
+        }
+    };
+}
+
+impl_account_cf_value!(CfAccountsValue);
+impl_account_cf_value!(CfAccountsHistoryValue);
+
+impl From<CfAccountsHistoryValue> for CfAccountsValue {
+    fn from(value: CfAccountsHistoryValue) -> Self {
+        match value {
+            CfAccountsHistoryValue::V1(v1) => Self::V1(v1),
+            CfAccountsHistoryValue::V2(v2) => Self::V2(v2),
+        }
+    }
+}
+
+impl From<CfAccountsValue> for CfAccountsHistoryValue {
+    fn from(value: CfAccountsValue) -> Self {
+        match value {
+            CfAccountsValue::V1(v1) => Self::V1(v1),
+            CfAccountsValue::V2(v2) => Self::V2(v2),
+        }
+    }
+}
+
+/// Block values with three versions:
+///
+/// - `V1`: without the base fee.
+/// - `V2`: with the base fee.
+/// - `V3`: with the fee market fields and access list of the transactions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, EnumCount, VariantNames, IntoStaticStr)]
+pub enum CfBlocksByNumberValue {
+    V1(BlockRocksdb),
+    V2(BlockRocksdbV2),
+    V3(BlockRocksdbV3),
+}
+
+impl CfBlocksByNumberValue {
+    /// Converts the value to the latest version.
+    pub fn into_inner(self) -> BlockRocksdbV3 {
+        match self {
+            Self::V1(v1) => v1.into(),
+            Self::V2(v2) => v2.into(),
+            Self::V3(v3) => v3,
+        }
+    }
+}
+
+// new values are always written in the latest version
+impl From<Block> for CfBlocksByNumberValue {
+    fn from(value: Block) -> Self {
+        Self::V3(value.into())
+    }
+}
+
+/// Accounts modified by each block, with the bytecode referenced by its hash like in [`CfAccountsValue::V2`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, EnumCount, VariantNames, IntoStaticStr)]
+pub enum CfBlockAccountChangesValue {
+    V1(AccountRocksdbV2),
+}
+
+impl CfBlockAccountChangesValue {
+    pub fn into_inner(self) -> AccountRocksdbV2 {
+        match self {
+            Self::V1(v1) => v1,
+        }
+    }
+}
+
+impl From<AccountRocksdbV2> for CfBlockAccountChangesValue {
+    fn from(v1: AccountRocksdbV2) -> Self {
+        Self::V1(v1)
+    }
+}
+
+impl_single_version_cf_value!(CfAccountCodesValue, BytesRocksdb, Bytes);
 impl_single_version_cf_value!(CfAccountSlotsValue, SlotValueRocksdb, SlotValue);
 impl_single_version_cf_value!(CfAccountSlotsHistoryValue, SlotValueRocksdb, SlotValue);
 impl_single_version_cf_value!(CfTransactionsValue, BlockNumberRocksdb, BlockNumber);
-impl_single_version_cf_value!(CfBlocksByNumberValue, BlockRocksdb, Block);
 impl_single_version_cf_value!(CfBlocksByHashValue, BlockNumberRocksdb, BlockNumber);
 impl_single_version_cf_value!(CfLogsValue, BlockNumberRocksdb, BlockNumber);
+impl_single_version_cf_value!(CfAddressTransactionsValue, IndexRocksdb, Index);
+impl_single_version_cf_value!(CfPublisherOutboxValue, BlockNumberRocksdb, BlockNumber);
+impl_single_version_cf_value!(CfBlockSlotChangesValue, SlotValueRocksdb, SlotValue);
 
 #[cfg_attr(not(test), allow(dead_code))]
 trait ToCfName {
@@ -99,12 +198,17 @@ macro_rules! impl_to_cf_name {
 
 impl_to_cf_name!(CfAccountsValue, "accounts");
 impl_to_cf_name!(CfAccountsHistoryValue, "accounts_history");
+impl_to_cf_name!(CfAccountCodesValue, "account_codes");
 impl_to_cf_name!(CfAccountSlotsValue, "account_slots");
 impl_to_cf_name!(CfAccountSlotsHistoryValue, "account_slots_history");
 impl_to_cf_name!(CfTransactionsValue, "transactions");
 impl_to_cf_name!(CfBlocksByNumberValue, "blocks_by_number");
 impl_to_cf_name!(CfBlocksByHashValue, "blocks_by_hash");
 impl_to_cf_name!(CfLogsValue, "logs");
+impl_to_cf_name!(CfAddressTransactionsValue, "address_transactions");
+impl_to_cf_name!(CfPublisherOutboxValue, "publisher_outbox");
+impl_to_cf_name!(CfBlockAccountChangesValue, "block_account_changes");
+impl_to_cf_name!(CfBlockSlotChangesValue, "block_slot_changes");
 
 /// Test that deserialization works for each variant of the enum.
 ///
@@ -216,10 +320,10 @@ mod tests {
 
     /// Store snapshots of the current serialization format for each version.
     #[test]
-    fn test_snapshot_bincode_deserialization_for_single_version_enums() {
+    fn test_snapshot_bincode_deserialization_for_versioned_enums() {
         fn test_deserialization<CfValue, Inner, F>(inner_to_cf_value: F) -> Result<TestRunConfirmation<CfValue>>
         where
-            CfValue: From<Inner> + for<'de> Deserialize<'de> + Serialize + Clone + Debug + PartialEq + Into<&'static str> + ToCfName,
+            CfValue: for<'de> Deserialize<'de> + Serialize + Clone + Debug + PartialEq + Into<&'static str> + ToCfName + VariantNames,
             F: FnOnce(Inner) -> CfValue,
             Inner: Dummy<Faker>,
         {
@@ -241,24 +345,25 @@ mod tests {
                 if env::var("DANGEROUS_UPDATE_SNAPSHOTS").is_ok() {
                     let serialized = bincode::serialize(&expected)?;
                     fs::create_dir_all(&snapshot_parent_path)?;
-                    fs::write(snapshot_path, serialized)?;
+                    fs::write(&snapshot_path, serialized)?;
                 } else {
                     bail!("snapshot file at '{snapshot_path:?}' doesn't exist and GEN_NEW_VARIANT_SNAPSHOT is not set");
                 }
             }
 
             let snapshots = get_all_bincode_snapshots_from_folder(&snapshot_parent_path)?;
-
-            let [snapshot_path] = snapshots.as_slice() else {
-                bail!("expected 1 snapshot, found {}: {snapshots:?}", snapshots.len());
-            };
-
             ensure!(
-                snapshot_path == snapshot_path,
-                "snapshot path {snapshot_path:?} doesn't match the expected for v1: {snapshot_path:?}"
+                snapshots.len() == CfValue::VARIANTS.len(),
+                "expected {} snapshots, found {}: {snapshots:?}",
+                CfValue::VARIANTS.len(),
+                snapshots.len()
+            );
+            ensure!(
+                snapshots.contains(&snapshot_path),
+                "snapshot path {snapshot_path:?} wasn't found for {variant_name}: {snapshots:?}"
             );
 
-            let deserialized = bincode::deserialize::<CfValue>(&fs::read(snapshot_path)?)?;
+            let deserialized = bincode::deserialize::<CfValue>(&fs::read(&snapshot_path)?)?;
             ensure!(
                 expected == deserialized,
                 "deserialized value doesn't match expected\n deserialized = {deserialized:?}\n expected = {expected:?}",
@@ -269,20 +374,34 @@ mod tests {
 
         let mut accounts_checker = EnumCoverageDropBombChecker::<CfAccountsValue>::new();
         let mut accounts_history_checker = EnumCoverageDropBombChecker::<CfAccountsHistoryValue>::new();
+        let mut account_codes_checker = EnumCoverageDropBombChecker::<CfAccountCodesValue>::new();
         let mut account_slots_checker = EnumCoverageDropBombChecker::<CfAccountSlotsValue>::new();
         let mut account_slots_history_checker = EnumCoverageDropBombChecker::<CfAccountSlotsHistoryValue>::new();
         let mut transactions_checker = EnumCoverageDropBombChecker::<CfTransactionsValue>::new();
         let mut blocks_by_number_checker = EnumCoverageDropBombChecker::<CfBlocksByNumberValue>::new();
         let mut blocks_by_hash_checker = EnumCoverageDropBombChecker::<CfBlocksByHashValue>::new();
         let mut logs_checker = EnumCoverageDropBombChecker::<CfLogsValue>::new();
+        let mut address_transactions_checker = EnumCoverageDropBombChecker::<CfAddressTransactionsValue>::new();
+        let mut publisher_outbox_checker = EnumCoverageDropBombChecker::<CfPublisherOutboxValue>::new();
+        let mut block_account_changes_checker = EnumCoverageDropBombChecker::<CfBlockAccountChangesValue>::new();
+        let mut block_slot_changes_checker = EnumCoverageDropBombChecker::<CfBlockSlotChangesValue>::new();
 
         accounts_checker.add(test_deserialization::<_, AccountRocksdb, _>(CfAccountsValue::V1).unwrap());
+        accounts_checker.add(test_deserialization::<_, AccountRocksdbV2, _>(CfAccountsValue::V2).unwrap());
         accounts_history_checker.add(test_deserialization::<_, AccountRocksdb, _>(CfAccountsHistoryValue::V1).unwrap());
+        accounts_history_checker.add(test_deserialization::<_, AccountRocksdbV2, _>(CfAccountsHistoryValue::V2).unwrap());
+        account_codes_checker.add(test_deserialization::<_, BytesRocksdb, _>(CfAccountCodesValue::V1).unwrap());
         account_slots_checker.add(test_deserialization::<_, SlotValueRocksdb, _>(CfAccountSlotsValue::V1).unwrap());
         account_slots_history_checker.add(test_deserialization::<_, SlotValueRocksdb, _>(CfAccountSlotsHistoryValue::V1).unwrap());
         transactions_checker.add(test_deserialization::<_, BlockNumberRocksdb, _>(CfTransactionsValue::V1).unwrap());
         blocks_by_number_checker.add(test_deserialization::<_, BlockRocksdb, _>(CfBlocksByNumberValue::V1).unwrap());
+        blocks_by_number_checker.add(test_deserialization::<_, BlockRocksdbV2, _>(CfBlocksByNumberValue::V2).unwrap());
+        blocks_by_number_checker.add(test_deserialization::<_, BlockRocksdbV3, _>(CfBlocksByNumberValue::V3).unwrap());
         blocks_by_hash_checker.add(test_deserialization::<_, BlockNumberRocksdb, _>(CfBlocksByHashValue::V1).unwrap());
         logs_checker.add(test_deserialization::<_, BlockNumberRocksdb, _>(CfLogsValue::V1).unwrap());
+        address_transactions_checker.add(test_deserialization::<_, IndexRocksdb, _>(CfAddressTransactionsValue::V1).unwrap());
+        publisher_outbox_checker.add(test_deserialization::<_, BlockNumberRocksdb, _>(CfPublisherOutboxValue::V1).unwrap());
+        block_account_changes_checker.add(test_deserialization::<_, AccountRocksdbV2, _>(CfBlockAccountChangesValue::V1).unwrap());
+        block_slot_changes_checker.add(test_deserialization::<_, SlotValueRocksdb, _>(CfBlockSlotChangesValue::V1).unwrap());
     }
 }
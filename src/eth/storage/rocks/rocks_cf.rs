@@ -122,7 +122,6 @@ where
         self.deserialize_value_with_context(&value_bytes).map(Some)
     }
 
-    #[cfg_attr(not(test), allow(dead_code))]
     pub fn multi_get<I>(&self, keys: I) -> Result<Vec<(K, V)>>
     where
         I: IntoIterator<Item = K> + Clone,
@@ -229,23 +228,6 @@ where
         Ok(())
     }
 
-    // Custom method that combines entry and or_insert_with from a HashMap
-    pub fn get_or_insert_with<F>(&self, key: K, default: F) -> Result<V>
-    where
-        F: FnOnce() -> V,
-    {
-        let value = self.get(&key)?;
-
-        Ok(match value {
-            Some(value) => value,
-            None => {
-                let new_value = default();
-                self.insert(key, new_value.clone())?;
-                new_value
-            }
-        })
-    }
-
     pub fn iter_start(&self) -> RocksCfIter<K, V> {
         let cf = self.handle();
 
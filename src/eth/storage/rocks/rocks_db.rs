@@ -55,3 +55,20 @@ pub fn create_or_open_db(path: impl AsRef<Path>, cf_configs: &HashMap<&'static s
 
     Ok((Arc::new(db), db_opts))
 }
+
+/// Open an existing Database without allowing writes, so it can be read by archive nodes.
+///
+/// The returned `Options` **need** to be stored to refer to the DB metrics!
+#[tracing::instrument(skip_all, fields(path = ?path.as_ref()))]
+pub fn open_db_read_only(path: impl AsRef<Path>, cf_configs: &HashMap<&'static str, Options>) -> anyhow::Result<(Arc<DB>, Options)> {
+    let path = path.as_ref();
+
+    let cf_config_iter = cf_configs.iter().map(|(name, opts)| (*name, opts.clone()));
+    let db_opts = DbConfig::Default.to_options(CacheSetting::Disabled);
+
+    tracing::debug!("attempting to open RocksDB as read-only");
+    let db = DB::open_cf_with_opts_for_read_only(&db_opts, path, cf_config_iter, false).context("trying to open RocksDB as read-only")?;
+    tracing::info!(db_path = ?path, "successfully opened RocksDB as read-only");
+
+    Ok((Arc::new(db), db_opts))
+}
@@ -5,19 +5,29 @@ use std::time::Duration;
 
 use anyhow::bail;
 
+use super::rocks_state::AccountCodesMigrationReport;
 use super::rocks_state::RocksStorageState;
 use crate::eth::primitives::Account;
+use crate::eth::primitives::AccountStateDiff;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::CodeHash;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::LogFilter;
 use crate::eth::primitives::LogMined;
+#[cfg(feature = "dev")]
+use crate::eth::primitives::Nonce;
+use crate::eth::primitives::Pagination;
 use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
 use crate::eth::primitives::TransactionMined;
+#[cfg(feature = "dev")]
+use crate::eth::primitives::Wei;
 use crate::eth::storage::PermanentStorage;
+use crate::eth::storage::RetentionTarget;
 use crate::eth::storage::StoragePointInTime;
 
 #[derive(Debug)]
@@ -28,7 +38,16 @@ pub struct RocksPermanentStorage {
 
 impl RocksPermanentStorage {
     pub fn new(rocks_path_prefix: Option<String>, shutdown_timeout: Duration) -> anyhow::Result<Self> {
-        tracing::info!("setting up rocksdb storage");
+        Self::open(rocks_path_prefix, shutdown_timeout, false)
+    }
+
+    /// Opens an existing storage without allowing writes, so it can be served by archive nodes.
+    pub fn new_read_only(rocks_path_prefix: Option<String>, shutdown_timeout: Duration) -> anyhow::Result<Self> {
+        Self::open(rocks_path_prefix, shutdown_timeout, true)
+    }
+
+    fn open(rocks_path_prefix: Option<String>, shutdown_timeout: Duration, read_only: bool) -> anyhow::Result<Self> {
+        tracing::info!(%read_only, "setting up rocksdb storage");
 
         let path = if let Some(prefix) = rocks_path_prefix {
             // run some checks on the given prefix
@@ -48,7 +67,11 @@ impl RocksPermanentStorage {
             "data/rocksdb".to_string()
         };
 
-        let state = RocksStorageState::new(path, shutdown_timeout)?;
+        let state = if read_only {
+            RocksStorageState::new_read_only(path, shutdown_timeout)?
+        } else {
+            RocksStorageState::new(path, shutdown_timeout)?
+        };
         let block_number = state.preload_block_number()?;
 
         Ok(Self { state, block_number })
@@ -70,6 +93,11 @@ impl RocksPermanentStorage {
         // don't log here, this is binary-specific and will be logged in the binary
         self.state.revert_state_to_block(block_number.into())
     }
+
+    pub fn migrate_account_codes(&self) -> anyhow::Result<AccountCodesMigrationReport> {
+        // don't log here, this is binary-specific and will be logged in the binary
+        self.state.migrate_account_codes()
+    }
 }
 
 impl PermanentStorage for RocksPermanentStorage {
@@ -96,12 +124,36 @@ impl PermanentStorage for RocksPermanentStorage {
         })
     }
 
+    fn read_code(&self, code_hash: &CodeHash) -> anyhow::Result<Option<Bytes>> {
+        self.state.read_code(code_hash).inspect_err(|e| {
+            tracing::error!(reason = ?e, "failed to read code in RocksPermanent");
+        })
+    }
+
     fn read_slot(&self, address: &Address, index: &SlotIndex, point_in_time: &StoragePointInTime) -> anyhow::Result<Option<Slot>> {
         self.state.read_slot(address, index, point_in_time).inspect_err(|e| {
             tracing::error!(reason = ?e, "failed to read slot in RocksPermanent");
         })
     }
 
+    fn read_slots(&self, slots: &[(Address, SlotIndex)], point_in_time: &StoragePointInTime) -> anyhow::Result<Vec<(Address, Slot)>> {
+        self.state.read_slots(slots, point_in_time).inspect_err(|e| {
+            tracing::error!(reason = ?e, "failed to read slots in RocksPermanent");
+        })
+    }
+
+    fn read_all_accounts(&self) -> anyhow::Result<Vec<Account>> {
+        self.state.read_current_accounts().inspect_err(|e| {
+            tracing::error!(reason = ?e, "failed to read all accounts in RocksPermanent");
+        })
+    }
+
+    fn read_all_slots(&self) -> anyhow::Result<Vec<(Address, Slot)>> {
+        self.state.read_current_slots().inspect_err(|e| {
+            tracing::error!(reason = ?e, "failed to read all slots in RocksPermanent");
+        })
+    }
+
     fn read_block(&self, selection: &BlockFilter) -> anyhow::Result<Option<Block>> {
         let block = self.state.read_block(selection).inspect_err(|e| {
             tracing::error!(reason = ?e, "failed to read block in RocksPermanent");
@@ -112,6 +164,19 @@ impl PermanentStorage for RocksPermanentStorage {
         block
     }
 
+    fn read_block_receipts(&self, selection: &BlockFilter) -> anyhow::Result<Option<Vec<TransactionMined>>> {
+        let block = self.state.read_block(selection).inspect_err(|e| {
+            tracing::error!(reason = ?e, "failed to read block receipts in RocksPermanent");
+        })?;
+        Ok(block.map(|block| block.transactions))
+    }
+
+    fn read_block_state_diff(&self, number: BlockNumber) -> anyhow::Result<Option<Vec<AccountStateDiff>>> {
+        self.state.read_block_state_diff(number).inspect_err(|e| {
+            tracing::error!(reason = ?e, "failed to read block state diff in RocksPermanent");
+        })
+    }
+
     fn read_transaction(&self, hash: &Hash) -> anyhow::Result<Option<TransactionMined>> {
         self.state.read_transaction(hash).inspect_err(|e| {
             tracing::error!(reason = ?e, "failed to read transaction in RocksPermanent");
@@ -124,6 +189,30 @@ impl PermanentStorage for RocksPermanentStorage {
         })
     }
 
+    fn read_transactions_by_address(&self, address: &Address, from: BlockNumber, to: BlockNumber, pagination: Pagination) -> anyhow::Result<Vec<Hash>> {
+        self.state.read_transactions_by_address(address, from, to, pagination).inspect_err(|e| {
+            tracing::error!(reason = ?e, "failed to read transactions by address in RocksPermanent");
+        })
+    }
+
+    fn prune_blocks(&self, target: RetentionTarget, from: BlockNumber, to: BlockNumber) -> anyhow::Result<()> {
+        self.state.prune_blocks(target, from, to).inspect_err(|e| {
+            tracing::error!(reason = ?e, "failed to prune blocks in RocksPermanent");
+        })
+    }
+
+    fn read_publisher_cursor(&self, publisher: &str) -> anyhow::Result<Option<BlockNumber>> {
+        self.state.read_publisher_cursor(publisher).inspect_err(|e| {
+            tracing::error!(reason = ?e, "failed to read publisher cursor in RocksPermanent");
+        })
+    }
+
+    fn save_publisher_cursor(&self, publisher: &str, number: BlockNumber) -> anyhow::Result<()> {
+        self.state.save_publisher_cursor(publisher, number).inspect_err(|e| {
+            tracing::error!(reason = ?e, "failed to save publisher cursor in RocksPermanent");
+        })
+    }
+
     fn save_block(&self, block: Block) -> anyhow::Result<()> {
         #[cfg(feature = "metrics")]
         {
@@ -142,6 +231,46 @@ impl PermanentStorage for RocksPermanentStorage {
         })
     }
 
+    fn save_slots(&self, slots: Vec<(Address, Slot)>) -> anyhow::Result<()> {
+        self.state.save_slots(slots).inspect_err(|e| {
+            tracing::error!(reason = ?e, "failed to save slots in RocksPermanent");
+        })
+    }
+
+    #[cfg(feature = "dev")]
+    fn set_balance(&self, address: &Address, balance: Wei) -> anyhow::Result<()> {
+        self.state
+            .update_account(address, self.read_mined_block_number()?, |account| account.balance = balance.into())
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to set balance in RocksPermanent");
+            })
+    }
+
+    #[cfg(feature = "dev")]
+    fn set_nonce(&self, address: &Address, nonce: Nonce) -> anyhow::Result<()> {
+        self.state
+            .update_account(address, self.read_mined_block_number()?, |account| account.nonce = nonce.into())
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to set nonce in RocksPermanent");
+            })
+    }
+
+    #[cfg(feature = "dev")]
+    fn set_code(&self, address: &Address, code: Bytes) -> anyhow::Result<()> {
+        self.state
+            .update_account(address, self.read_mined_block_number()?, |account| account.bytecode = Some(code.into()))
+            .inspect_err(|e| {
+                tracing::error!(reason = ?e, "failed to set code in RocksPermanent");
+            })
+    }
+
+    #[cfg(feature = "dev")]
+    fn set_storage(&self, address: &Address, slot: Slot) -> anyhow::Result<()> {
+        self.state.update_slot(address, slot, self.read_mined_block_number()?).inspect_err(|e| {
+            tracing::error!(reason = ?e, "failed to set storage in RocksPermanent");
+        })
+    }
+
     #[cfg(feature = "dev")]
     fn reset(&self) -> anyhow::Result<()> {
         self.block_number.store(0u64, Ordering::SeqCst);
@@ -149,4 +278,12 @@ impl PermanentStorage for RocksPermanentStorage {
             tracing::error!(reason = ?e, "failed to reset in RocksPermanent");
         })
     }
+
+    fn reset_at(&self, number: BlockNumber) -> anyhow::Result<()> {
+        self.state.revert_state_to_block(number.into()).inspect_err(|e| {
+            tracing::error!(reason = ?e, "failed to reset_at in RocksPermanent");
+        })?;
+        self.block_number.store(number.as_u64(), Ordering::SeqCst);
+        Ok(())
+    }
 }
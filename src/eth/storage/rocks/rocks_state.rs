@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fmt;
 use std::fmt::Debug;
 use std::sync::atomic::AtomicU64;
@@ -9,6 +10,7 @@ use std::time::Instant;
 use anyhow::bail;
 use anyhow::Context;
 use anyhow::Result;
+use itertools::Itertools;
 use lazy_static::lazy_static;
 use rocksdb::Direction;
 use rocksdb::Options;
@@ -19,13 +21,18 @@ use serde::Deserialize;
 use serde::Serialize;
 use sugars::hmap;
 
+use super::cf_versions::CfAccountCodesValue;
 use super::cf_versions::CfAccountSlotsHistoryValue;
 use super::cf_versions::CfAccountSlotsValue;
 use super::cf_versions::CfAccountsHistoryValue;
 use super::cf_versions::CfAccountsValue;
+use super::cf_versions::CfAddressTransactionsValue;
+use super::cf_versions::CfBlockAccountChangesValue;
+use super::cf_versions::CfBlockSlotChangesValue;
 use super::cf_versions::CfBlocksByHashValue;
 use super::cf_versions::CfBlocksByNumberValue;
 use super::cf_versions::CfLogsValue;
+use super::cf_versions::CfPublisherOutboxValue;
 use super::cf_versions::CfTransactionsValue;
 use super::rocks_batch_writer::write_in_batch_for_multiple_cfs_impl;
 use super::rocks_batch_writer::BufferedBatchWriter;
@@ -33,25 +40,33 @@ use super::rocks_cf::RocksCfRef;
 use super::rocks_config::CacheSetting;
 use super::rocks_config::DbConfig;
 use super::rocks_db::create_or_open_db;
+use super::rocks_db::open_db_read_only;
 use super::types::AccountRocksdb;
+use super::types::AccountRocksdbV2;
 use super::types::AddressRocksdb;
 use super::types::BlockNumberRocksdb;
+use super::types::BytesRocksdb;
 use super::types::HashRocksdb;
 use super::types::IndexRocksdb;
 use super::types::SlotIndexRocksdb;
 use crate::eth::primitives::Account;
+use crate::eth::primitives::AccountStateDiff;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::CodeHash;
 use crate::eth::primitives::ExecutionAccountChanges;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::LogFilter;
 use crate::eth::primitives::LogMined;
+use crate::eth::primitives::Pagination;
 use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
 use crate::eth::primitives::TransactionMined;
 use crate::eth::storage::rocks::types::SlotValueRocksdb;
+use crate::eth::storage::RetentionTarget;
 use crate::eth::storage::StoragePointInTime;
 use crate::ext::MutexExt;
 use crate::ext::OptionExt;
@@ -74,12 +89,17 @@ lazy_static! {
     static ref CF_OPTIONS_MAP: HashMap<&'static str, Options> = hmap! {
         "accounts" => DbConfig::Default.to_options(CacheSetting::Enabled(15 * GIGABYTE)),
         "accounts_history" => DbConfig::FastWriteSST.to_options(CacheSetting::Disabled),
+        "account_codes" => DbConfig::Default.to_options(CacheSetting::Enabled(GIGABYTE)),
         "account_slots" => DbConfig::Default.to_options(CacheSetting::Enabled(45 * GIGABYTE)),
         "account_slots_history" => DbConfig::FastWriteSST.to_options(CacheSetting::Disabled),
         "transactions" => DbConfig::LargeSSTFiles.to_options(CacheSetting::Disabled),
         "blocks_by_number" => DbConfig::LargeSSTFiles.to_options(CacheSetting::Disabled),
         "blocks_by_hash" => DbConfig::LargeSSTFiles.to_options(CacheSetting::Disabled),
         "logs" => DbConfig::LargeSSTFiles.to_options(CacheSetting::Disabled),
+        "address_transactions" => DbConfig::LargeSSTFiles.to_options(CacheSetting::Disabled),
+        "publisher_outbox" => DbConfig::Default.to_options(CacheSetting::Disabled),
+        "block_account_changes" => DbConfig::FastWriteSST.to_options(CacheSetting::Disabled),
+        "block_slot_changes" => DbConfig::FastWriteSST.to_options(CacheSetting::Disabled),
     };
 }
 
@@ -107,12 +127,20 @@ pub struct RocksStorageState {
     db_path: String,
     accounts: RocksCfRef<AddressRocksdb, CfAccountsValue>,
     accounts_history: RocksCfRef<(AddressRocksdb, BlockNumberRocksdb), CfAccountsHistoryValue>,
+    account_codes: RocksCfRef<HashRocksdb, CfAccountCodesValue>,
     account_slots: RocksCfRef<(AddressRocksdb, SlotIndexRocksdb), CfAccountSlotsValue>,
     account_slots_history: RocksCfRef<(AddressRocksdb, SlotIndexRocksdb, BlockNumberRocksdb), CfAccountSlotsHistoryValue>,
     transactions: RocksCfRef<HashRocksdb, CfTransactionsValue>,
     blocks_by_number: RocksCfRef<BlockNumberRocksdb, CfBlocksByNumberValue>,
     blocks_by_hash: RocksCfRef<HashRocksdb, CfBlocksByHashValue>,
     logs: RocksCfRef<(HashRocksdb, IndexRocksdb), CfLogsValue>,
+    /// Transactions of each address, keyed by block so ranges of blocks can be iterated.
+    address_transactions: RocksCfRef<(AddressRocksdb, BlockNumberRocksdb, HashRocksdb), CfAddressTransactionsValue>,
+    /// Last block delivered by each publisher.
+    publisher_outbox: RocksCfRef<String, CfPublisherOutboxValue>,
+    /// Accounts and slots modified by each block, keyed by block so the state diff of a block can be iterated.
+    block_account_changes: RocksCfRef<(BlockNumberRocksdb, AddressRocksdb), CfBlockAccountChangesValue>,
+    block_slot_changes: RocksCfRef<(BlockNumberRocksdb, AddressRocksdb, SlotIndexRocksdb), CfBlockSlotChangesValue>,
     /// Last collected stats for a histogram
     #[cfg(feature = "metrics")]
     prev_stats: Mutex<HashMap<HistogramInt, (Sum, Count)>>,
@@ -123,13 +151,29 @@ pub struct RocksStorageState {
     #[cfg(feature = "metrics")]
     db_options: Options,
     shutdown_timeout: Duration,
+    /// Opened without allowing writes, so there is nothing to compact at shutdown.
+    read_only: bool,
 }
 
 impl RocksStorageState {
     pub fn new(path: String, shutdown_timeout: Duration) -> Result<Self> {
-        tracing::debug!("creating (or opening an existing) database with the specified column families");
+        Self::open(path, shutdown_timeout, false)
+    }
+
+    /// Opens an existing database without allowing writes.
+    pub fn new_read_only(path: String, shutdown_timeout: Duration) -> Result<Self> {
+        Self::open(path, shutdown_timeout, true)
+    }
+
+    fn open(path: String, shutdown_timeout: Duration, read_only: bool) -> Result<Self> {
         #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
-        let (db, db_options) = create_or_open_db(&path, &CF_OPTIONS_MAP).context("when trying to create (or open) rocksdb")?;
+        let (db, db_options) = if read_only {
+            tracing::debug!("opening an existing database as read-only with the specified column families");
+            open_db_read_only(&path, &CF_OPTIONS_MAP).context("when trying to open rocksdb as read-only")?
+        } else {
+            tracing::debug!("creating (or opening an existing) database with the specified column families");
+            create_or_open_db(&path, &CF_OPTIONS_MAP).context("when trying to create (or open) rocksdb")?
+        };
 
         if db.path().to_str().is_none() {
             bail!("db path doesn't isn't valid UTF-8: {:?}", db.path());
@@ -142,18 +186,24 @@ impl RocksStorageState {
             db_path: path,
             accounts: new_cf_ref(&db, "accounts")?,
             accounts_history: new_cf_ref(&db, "accounts_history")?,
+            account_codes: new_cf_ref(&db, "account_codes")?,
             account_slots: new_cf_ref(&db, "account_slots")?,
             account_slots_history: new_cf_ref(&db, "account_slots_history")?,
             transactions: new_cf_ref(&db, "transactions")?,
             blocks_by_number: new_cf_ref(&db, "blocks_by_number")?,
             blocks_by_hash: new_cf_ref(&db, "blocks_by_hash")?,
             logs: new_cf_ref(&db, "logs")?,
+            address_transactions: new_cf_ref(&db, "address_transactions")?,
+            publisher_outbox: new_cf_ref(&db, "publisher_outbox")?,
+            block_account_changes: new_cf_ref(&db, "block_account_changes")?,
+            block_slot_changes: new_cf_ref(&db, "block_slot_changes")?,
             #[cfg(feature = "metrics")]
             prev_stats: Mutex::default(),
             #[cfg(feature = "metrics")]
             db_options,
             db,
             shutdown_timeout,
+            read_only,
         };
 
         tracing::debug!("opened database successfully");
@@ -175,12 +225,17 @@ impl RocksStorageState {
     pub fn reset(&self) -> Result<()> {
         self.accounts.clear()?;
         self.accounts_history.clear()?;
+        self.account_codes.clear()?;
         self.account_slots.clear()?;
         self.account_slots_history.clear()?;
         self.transactions.clear()?;
         self.blocks_by_number.clear()?;
         self.blocks_by_hash.clear()?;
         self.logs.clear()?;
+        self.address_transactions.clear()?;
+        self.publisher_outbox.clear()?;
+        self.block_account_changes.clear()?;
+        self.block_slot_changes.clear()?;
         Ok(())
     }
 
@@ -195,7 +250,11 @@ impl RocksStorageState {
 
             if change.is_account_modified() {
                 let address: AddressRocksdb = change.address.into();
-                let mut account_info_entry = self.accounts.get_or_insert_with(address, || AccountRocksdb::default().into())?;
+                let mut account_info_entry = match self.accounts.get(&address)? {
+                    Some(CfAccountsValue::V1(account)) => self.prepare_batch_account_dedup(account, batch)?,
+                    Some(CfAccountsValue::V2(account)) => account,
+                    None => AccountRocksdbV2::default(),
+                };
 
                 if let Some(nonce) = change.nonce.take_modified() {
                     account_info_entry.nonce = nonce.into();
@@ -204,12 +263,18 @@ impl RocksStorageState {
                     account_info_entry.balance = balance.into();
                 }
                 if let Some(bytecode) = change.bytecode.take_modified() {
-                    account_info_entry.bytecode = bytecode.map_into();
+                    let account = AccountRocksdb {
+                        bytecode: bytecode.map_into(),
+                        ..Default::default()
+                    };
+                    account_info_entry.code_hash = self.prepare_batch_account_dedup(account, batch)?.code_hash;
                 }
 
-                self.accounts.prepare_batch_insertion([(address, account_info_entry.clone())], batch)?;
+                self.accounts.prepare_batch_insertion([(address, account_info_entry.clone().into())], batch)?;
+                self.block_account_changes
+                    .prepare_batch_insertion([((block_number, address), account_info_entry.clone().into())], batch)?;
                 self.accounts_history
-                    .prepare_batch_insertion([((address, block_number), account_info_entry.into_inner().into())], batch)?;
+                    .prepare_batch_insertion([((address, block_number), account_info_entry.into())], batch)?;
             }
 
             for (slot_index, slot_change) in &change.slots {
@@ -221,6 +286,8 @@ impl RocksStorageState {
                         .prepare_batch_insertion([((address, slot_index), slot_value.into())], batch)?;
                     self.account_slots_history
                         .prepare_batch_insertion([((address, slot_index, block_number), slot_value.into())], batch)?;
+                    self.block_slot_changes
+                        .prepare_batch_insertion([((block_number, address, slot_index), slot_value.into())], batch)?;
                 }
             }
         }
@@ -228,6 +295,36 @@ impl RocksStorageState {
         Ok(())
     }
 
+    /// Moves the bytecode of an account to the `account_codes` column family, returning the account referencing it.
+    fn prepare_batch_account_dedup(&self, account: AccountRocksdb, batch: &mut WriteBatch) -> Result<AccountRocksdbV2> {
+        let (account, code) = account.into_v2();
+        if let Some((code_hash, bytecode)) = code {
+            self.account_codes.prepare_batch_insertion([(code_hash, bytecode.into())], batch)?;
+        }
+        Ok(account)
+    }
+
+    /// Converts a stored account to the format with inline bytecode, reading the bytecode if necessary.
+    fn read_account_value(&self, value: CfAccountsValue) -> Result<AccountRocksdb> {
+        match value {
+            CfAccountsValue::V1(account) => Ok(account),
+            CfAccountsValue::V2(account) => {
+                let bytecode = match account.code_hash {
+                    Some(code_hash) => Some(self.read_account_code(code_hash)?),
+                    None => None,
+                };
+                Ok(account.into_v1(bytecode))
+            }
+        }
+    }
+
+    fn read_account_code(&self, code_hash: HashRocksdb) -> Result<BytesRocksdb> {
+        match self.account_codes.get(&code_hash)? {
+            Some(bytecode) => Ok(bytecode.into_inner()),
+            None => log_and_err!("rocks error, account code wasn't found where the account pointed at").with_context(|| format!("code_hash = {:?}", code_hash)),
+        }
+    }
+
     pub fn read_transaction(&self, tx_hash: &Hash) -> Result<Option<TransactionMined>> {
         let Some(block_number) = self.transactions.get(&(*tx_hash).into())? else {
             return Ok(None);
@@ -238,12 +335,14 @@ impl RocksStorageState {
                 .with_context(|| format!("block_number = {:?} tx_hash = {}", block_number, tx_hash));
         };
 
-        let transaction = block.into_inner().transactions.into_iter().find(|tx| &Hash::from(tx.input.hash) == tx_hash);
+        // converted as a block so fields derived from other transactions of the block are filled
+        let block = Block::from(block.into_inner());
+        let transaction = block.transactions.into_iter().find(|tx| &tx.input.hash == tx_hash);
 
         match transaction {
             Some(tx) => {
                 tracing::trace!(%tx_hash, "transaction found");
-                Ok(Some(tx.into()))
+                Ok(Some(tx))
             }
             None => log_and_err!("rocks error, transaction wasn't found in block where the index pointed at")
                 .with_context(|| format!("block_number = {:?} tx_hash = {}", block_number, tx_hash)),
@@ -269,12 +368,12 @@ impl RocksStorageState {
                 break;
             }
 
-            let logs = block
-                .into_inner()
-                .transactions
-                .into_iter()
-                .flat_map(|transaction| transaction.logs)
-                .map(LogMined::from);
+            let block = block.into_inner();
+            if !filter.matches_bloom(&block.header.bloom.clone().into()) {
+                continue;
+            }
+
+            let logs = block.transactions.into_iter().flat_map(|transaction| transaction.logs).map(LogMined::from);
 
             let filtered_logs = logs.filter(|log| filter.matches(log));
             logs_result.extend(filtered_logs);
@@ -282,6 +381,84 @@ impl RocksStorageState {
         Ok(logs_result)
     }
 
+    pub fn read_transactions_by_address(&self, address: &Address, from: BlockNumber, to: BlockNumber, pagination: Pagination) -> Result<Vec<Hash>> {
+        let address = AddressRocksdb::from(*address);
+        let to = BlockNumberRocksdb::from(to);
+        let iter = self
+            .address_transactions
+            .iter_from((address, from.into(), HashRocksdb::default()), Direction::Forward)?;
+
+        let mut transactions: Vec<(BlockNumberRocksdb, IndexRocksdb, HashRocksdb)> = vec![];
+        for next in iter {
+            let ((tx_address, block_number, hash), index) = next?;
+            if tx_address != address || block_number > to {
+                break;
+            }
+
+            // keys are ordered by hash inside a block, so the page can only be completed after reading all transactions of the last block
+            let page_filled = transactions.len() >= pagination.end();
+            if page_filled && transactions.last().is_some_and(|(last_block_number, _, _)| *last_block_number != block_number) {
+                break;
+            }
+            transactions.push((block_number, index.into_inner(), hash));
+        }
+
+        transactions.sort_by_key(|(block_number, index, _)| (*block_number, index.inner_value()));
+        Ok(pagination.apply(transactions.into_iter().map(|(_, _, hash)| hash.into())))
+    }
+
+    pub fn read_block_state_diff(&self, number: BlockNumber) -> Result<Option<Vec<AccountStateDiff>>> {
+        let number = BlockNumberRocksdb::from(number);
+        if self.blocks_by_number.get(&number)?.is_none() {
+            return Ok(None);
+        }
+
+        let mut diffs: HashMap<AddressRocksdb, AccountStateDiff> = HashMap::new();
+        for next in self.block_account_changes.iter_from((number, AddressRocksdb::default()), Direction::Forward)? {
+            let ((block_number, address), account) = next?;
+            if block_number != number {
+                break;
+            }
+            let account_address = Address::from(address);
+            let account = self.read_account_value(CfAccountsValue::V2(account.into_inner()))?.to_account(&account_address);
+            diffs.insert(
+                address,
+                AccountStateDiff {
+                    address: account_address,
+                    account: Some(account),
+                    slots: vec![],
+                },
+            );
+        }
+
+        let slots_iter = self
+            .block_slot_changes
+            .iter_from((number, AddressRocksdb::default(), SlotIndexRocksdb::default()), Direction::Forward)?;
+        for next in slots_iter {
+            let ((block_number, address, index), value) = next?;
+            if block_number != number {
+                break;
+            }
+            let diff = diffs.entry(address).or_insert_with(|| AccountStateDiff {
+                address: address.into(),
+                account: None,
+                slots: vec![],
+            });
+            diff.slots.push(Slot::new(index.into(), value.into_inner().into()));
+        }
+
+        let mut diffs = diffs.into_values().collect_vec();
+        diffs.sort_by_key(|diff| diff.address.0);
+        Ok(Some(diffs))
+    }
+
+    /// Reads a bytecode from the `account_codes` column family.
+    ///
+    /// Bytecodes of accounts stored before the `account_codes` migration are found only after it runs.
+    pub fn read_code(&self, code_hash: &CodeHash) -> Result<Option<Bytes>> {
+        Ok(self.account_codes.get(&(*code_hash).into())?.map(|bytecode| bytecode.into_inner().into()))
+    }
+
     pub fn read_slot(&self, address: &Address, index: &SlotIndex, point_in_time: &StoragePointInTime) -> Result<Option<Slot>> {
         if address.is_coinbase() {
             //XXX temporary, we will reload the database later without it
@@ -322,6 +499,40 @@ impl RocksStorageState {
         }
     }
 
+    pub fn read_slots(&self, slots: &[(Address, SlotIndex)], point_in_time: &StoragePointInTime) -> Result<Vec<(Address, Slot)>> {
+        match point_in_time {
+            StoragePointInTime::Mined | StoragePointInTime::Pending => {
+                let keys = slots
+                    .iter()
+                    .filter(|(address, _)| !address.is_coinbase())
+                    .map(|(address, index)| ((*address).into(), (*index).into()))
+                    .collect_vec();
+
+                let found = self.account_slots.multi_get(keys)?;
+                Ok(found
+                    .into_iter()
+                    .map(|((address, index), value)| {
+                        let slot = Slot {
+                            index: index.into(),
+                            value: value.into_inner().into(),
+                        };
+                        (address.into(), slot)
+                    })
+                    .collect())
+            }
+            // past slots are found by iterating the history, which cannot be batched
+            StoragePointInTime::MinedPast(_) => {
+                let mut found = Vec::with_capacity(slots.len());
+                for (address, index) in slots {
+                    if let Some(slot) = self.read_slot(address, index, point_in_time)? {
+                        found.push((*address, slot));
+                    }
+                }
+                Ok(found)
+            }
+        }
+    }
+
     pub fn read_account(&self, address: &Address, point_in_time: &StoragePointInTime) -> Result<Option<Account>> {
         if address.is_coinbase() || address.is_zero() {
             //XXX temporary, we will reload the database later without it
@@ -335,7 +546,7 @@ impl RocksStorageState {
                     return Ok(None);
                 };
 
-                let account = inner_account.to_account(address);
+                let account = self.read_account_value(inner_account)?.to_account(address);
                 tracing::trace!(%address, ?account, "account found");
                 Ok(Some(account))
             }
@@ -345,7 +556,7 @@ impl RocksStorageState {
                 if let Some(next) = self.accounts_history.iter_from(iterator_start, rocksdb::Direction::Reverse)?.next() {
                     let ((addr, _), account_info) = next?;
                     if addr == (*address).into() {
-                        return Ok(Some(account_info.to_account(address)));
+                        return Ok(Some(self.read_account_value(account_info.into())?.to_account(address)));
                     }
                 }
                 Ok(None)
@@ -372,12 +583,87 @@ impl RocksStorageState {
     }
 
     pub fn save_accounts(&self, accounts: Vec<Account>) -> Result<()> {
+        let mut batch = WriteBatch::default();
         for account in accounts {
             let (key, value) = account.into();
-            let value: CfAccountsValue = value.into();
-            self.accounts.insert(key, value.clone())?;
-            self.accounts_history.insert((key, 0u64.into()), value.into_inner().into())?;
+            let value = self.prepare_batch_account_dedup(value, &mut batch)?;
+            self.accounts.prepare_batch_insertion([(key, value.clone().into())], &mut batch)?;
+            self.accounts_history
+                .prepare_batch_insertion([((key, 0u64.into()), value.into())], &mut batch)?;
         }
+        self.write_in_batch_for_multiple_cfs(batch)
+    }
+
+    /// Writes initial slots to state and history at block zero.
+    pub fn save_slots(&self, slots: Vec<(Address, Slot)>) -> Result<()> {
+        let mut batch = WriteBatch::default();
+        for (address, slot) in slots {
+            let address: AddressRocksdb = address.into();
+            let slot_index: SlotIndexRocksdb = slot.index.into();
+            let slot_value: SlotValueRocksdb = slot.value.into();
+            self.account_slots
+                .prepare_batch_insertion([((address, slot_index), slot_value.into())], &mut batch)?;
+            self.account_slots_history
+                .prepare_batch_insertion([((address, slot_index, 0u64.into()), slot_value.into())], &mut batch)?;
+        }
+        self.write_in_batch_for_multiple_cfs(batch)
+    }
+
+    /// Reads all accounts at the last mined block.
+    pub fn read_current_accounts(&self) -> Result<Vec<Account>> {
+        self.accounts
+            .iter_start()
+            .map(|result| {
+                let (address, value) = result?;
+                Ok(self.read_account_value(value)?.to_account(&address.into()))
+            })
+            .collect()
+    }
+
+    /// Reads all slots at the last mined block.
+    pub fn read_current_slots(&self) -> Result<Vec<(Address, Slot)>> {
+        self.account_slots
+            .iter_start()
+            .map(|result| {
+                let ((address, index), value) = result?;
+                let slot = Slot {
+                    index: index.into(),
+                    value: value.into_inner().into(),
+                };
+                Ok((address.into(), slot))
+            })
+            .collect()
+    }
+
+    /// Overrides an account at the given block number, creating it if necessary.
+    pub fn update_account<F>(&self, address: &Address, block_number: BlockNumber, update: F) -> Result<()>
+    where
+        F: FnOnce(&mut AccountRocksdb),
+    {
+        let address: AddressRocksdb = (*address).into();
+        let mut account = match self.accounts.get(&address)? {
+            Some(value) => self.read_account_value(value)?,
+            None => AccountRocksdb::default(),
+        };
+        update(&mut account);
+
+        let mut batch = WriteBatch::default();
+        let account = self.prepare_batch_account_dedup(account, &mut batch)?;
+        self.accounts.prepare_batch_insertion([(address, account.clone().into())], &mut batch)?;
+        self.accounts_history
+            .prepare_batch_insertion([((address, block_number.into()), account.into())], &mut batch)?;
+        self.write_in_batch_for_multiple_cfs(batch)
+    }
+
+    /// Overrides a slot at the given block number.
+    pub fn update_slot(&self, address: &Address, slot: Slot, block_number: BlockNumber) -> Result<()> {
+        let address: AddressRocksdb = (*address).into();
+        let slot_index: SlotIndexRocksdb = slot.index.into();
+        let slot_value: SlotValueRocksdb = slot.value.into();
+
+        self.account_slots.insert((address, slot_index), slot_value.into())?;
+        self.account_slots_history
+            .insert((address, slot_index, block_number.into()), slot_value.into())?;
         Ok(())
     }
 
@@ -386,8 +672,15 @@ impl RocksStorageState {
 
         let mut txs_batch = vec![];
         let mut logs_batch = vec![];
+        let mut address_txs_batch = vec![];
         for transaction in block.transactions.iter().cloned() {
             txs_batch.push((transaction.input.hash.into(), transaction.block_number.into()));
+            for address in transaction.indexed_addresses() {
+                address_txs_batch.push((
+                    (address.into(), transaction.block_number.into(), transaction.input.hash.into()),
+                    transaction.transaction_index.into(),
+                ));
+            }
             for log in transaction.logs {
                 logs_batch.push(((transaction.input.hash.into(), log.log_index.into()), transaction.block_number.into()));
             }
@@ -396,6 +689,7 @@ impl RocksStorageState {
 
         self.transactions.prepare_batch_insertion(txs_batch, &mut batch)?;
         self.logs.prepare_batch_insertion(logs_batch, &mut batch)?;
+        self.address_transactions.prepare_batch_insertion(address_txs_batch, &mut batch)?;
 
         let number = block.number();
         let block_hash = block.hash();
@@ -443,24 +737,32 @@ impl RocksStorageState {
 
     #[cfg(test)]
     pub fn read_all_accounts(&self) -> Result<Vec<AccountRocksdb>> {
-        self.accounts.iter_start().map(|result| Ok(result?.1.into_inner())).collect()
+        self.accounts.iter_start().map(|result| self.read_account_value(result?.1)).collect()
     }
 
     #[cfg(test)]
     pub fn read_all_historical_accounts(&self) -> Result<Vec<AccountRocksdb>> {
-        self.accounts_history.iter_start().map(|result| Ok(result?.1.into_inner())).collect()
+        self.accounts_history
+            .iter_start()
+            .map(|result| self.read_account_value(result?.1.into()))
+            .collect()
     }
 
     /// Clears in-memory state.
     pub fn clear(&self) -> Result<()> {
         self.accounts.clear().context("when clearing accounts")?;
         self.accounts_history.clear().context("when clearing accounts_history")?;
+        self.account_codes.clear().context("when clearing account_codes")?;
         self.account_slots.clear().context("when clearing account_slots")?;
         self.account_slots_history.clear().context("when clearing account_slots_history")?;
         self.transactions.clear().context("when clearing transactions")?;
         self.blocks_by_hash.clear().context("when clearing blocks_by_hash")?;
         self.blocks_by_number.clear().context("when clearing blocks_by_number")?;
         self.logs.clear().context("when clearing logs")?;
+        self.address_transactions.clear().context("when clearing address_transactions")?;
+        self.publisher_outbox.clear().context("when clearing publisher_outbox")?;
+        self.block_account_changes.clear().context("when clearing block_account_changes")?;
+        self.block_slot_changes.clear().context("when clearing block_slot_changes")?;
         Ok(())
     }
 
@@ -478,7 +780,7 @@ impl RocksStorageState {
 
         struct LastHistoricalAccount {
             address: AddressRocksdb,
-            account: AccountRocksdb,
+            account: CfAccountsHistoryValue,
         }
 
         let mut history_accounts_count = 0_u64;
@@ -488,7 +790,6 @@ impl RocksStorageState {
         tracing::info!("starting iteration through historical accounts to clean values after target_block and reconstruct current accounts state");
         for next in self.accounts_history.iter_start() {
             let ((address, account_block_number), account) = next?;
-            let account = account.into_inner();
 
             // this can only be `Some` if account in last iteration was in valid range
             if let Some(last) = last_account {
@@ -594,6 +895,15 @@ impl RocksStorageState {
         }
         bufwriter.flush(&self.db)?;
 
+        tracing::info!("cleaning values in address_transactions column family");
+        for next in self.address_transactions.iter_start() {
+            let (key, _) = next?;
+            if should_delete_block(key.1) {
+                bufwriter.delete(&self.address_transactions, key)?;
+            }
+        }
+        bufwriter.flush(&self.db)?;
+
         tracing::info!("cleaning values in blocks_by_hash column family");
         for next in self.blocks_by_hash.iter_start() {
             let (hash, block) = next?;
@@ -609,8 +919,177 @@ impl RocksStorageState {
         }
         bufwriter.flush(&self.db)?;
 
+        tracing::info!("cleaning values in block_account_changes column family");
+        for key in self
+            .block_account_changes
+            .iter_from((target_block + 1, AddressRocksdb::default()), Direction::Forward)?
+            .keys()
+        {
+            bufwriter.delete(&self.block_account_changes, key?)?;
+        }
+        bufwriter.flush(&self.db)?;
+
+        tracing::info!("cleaning values in block_slot_changes column family");
+        for key in self
+            .block_slot_changes
+            .iter_from((target_block + 1, AddressRocksdb::default(), SlotIndexRocksdb::default()), Direction::Forward)?
+            .keys()
+        {
+            bufwriter.delete(&self.block_slot_changes, key?)?;
+        }
+        bufwriter.flush(&self.db)?;
+
+        tracing::info!("rewinding cursors in publisher_outbox column family");
+        for next in self.publisher_outbox.iter_start() {
+            let (publisher, cursor) = next?;
+            if should_delete_block(cursor.into_inner()) {
+                bufwriter.insert(&self.publisher_outbox, publisher, target_block.into())?;
+            }
+        }
+        bufwriter.flush(&self.db)?;
+
+        Ok(())
+    }
+
+    pub fn read_publisher_cursor(&self, publisher: &str) -> Result<Option<BlockNumber>> {
+        let cursor = self.publisher_outbox.get(&publisher.to_owned())?;
+        Ok(cursor.map(|cursor| cursor.into_inner().into()))
+    }
+
+    pub fn save_publisher_cursor(&self, publisher: &str, number: BlockNumber) -> Result<()> {
+        self.publisher_outbox.insert(publisher.to_owned(), number.into())
+    }
+
+    /// Removes the data covered by the retention target from blocks in the range (inclusive), keeping the block headers.
+    pub fn prune_blocks(&self, target: RetentionTarget, from: BlockNumber, to: BlockNumber) -> Result<()> {
+        let to = BlockNumberRocksdb::from(to);
+        let mut bufwriter = BufferedBatchWriter::new(1024 * 2);
+
+        for next in self.blocks_by_number.iter_from(from.into(), Direction::Forward)? {
+            let (number, block) = next?;
+            if number > to {
+                break;
+            }
+
+            let mut block = Block::from(block.into_inner());
+            let tx_hashes = block.transactions.iter().map(|tx| tx.input.hash).collect_vec();
+            let address_tx_keys: Vec<(AddressRocksdb, BlockNumberRocksdb, HashRocksdb)> = block
+                .transactions
+                .iter()
+                .flat_map(|tx| {
+                    tx.indexed_addresses()
+                        .into_iter()
+                        .map(move |address| (address.into(), number, tx.input.hash.into()))
+                })
+                .collect();
+            let log_keys: Vec<(HashRocksdb, IndexRocksdb)> = block
+                .transactions
+                .iter()
+                .flat_map(|tx| tx.logs.iter().map(|log| (tx.input.hash.into(), log.log_index.into())))
+                .collect();
+            if !target.prune_block(&mut block) {
+                continue;
+            }
+
+            // both targets remove all logs of the block
+            for key in log_keys {
+                bufwriter.delete(&self.logs, key)?;
+            }
+            if target == RetentionTarget::Receipts {
+                for tx_hash in tx_hashes {
+                    bufwriter.delete(&self.transactions, tx_hash.into())?;
+                }
+                for key in address_tx_keys {
+                    bufwriter.delete(&self.address_transactions, key)?;
+                }
+            }
+            bufwriter.insert(&self.blocks_by_number, number, block.into())?;
+        }
+        bufwriter.flush(&self.db)?;
+
         Ok(())
     }
+
+    /// Rewrites accounts that store their bytecode inline so they reference it in the `account_codes` column family.
+    ///
+    /// Accounts already referencing their bytecode are kept as they are, so the migration can be interrupted and resumed.
+    pub fn migrate_account_codes(&self) -> Result<AccountCodesMigrationReport> {
+        let mut bufwriter = BufferedBatchWriter::new(1024 * 2);
+        let mut report = AccountCodesMigrationReport::default();
+        let mut migrated_codes = HashSet::new();
+
+        tracing::info!("starting iteration through current accounts to move bytecodes to account_codes");
+        for next in self.accounts.iter_start() {
+            let (address, CfAccountsValue::V1(account)) = next? else {
+                continue;
+            };
+            let (account, code) = account.into_v2();
+            if let Some((code_hash, bytecode)) = code {
+                report.record_code(&mut migrated_codes, code_hash, &bytecode);
+                bufwriter.insert(&self.account_codes, code_hash, bytecode.into())?;
+            }
+            bufwriter.insert(&self.accounts, address, account.into())?;
+            report.accounts += 1;
+        }
+        bufwriter.flush(&self.db)?;
+
+        tracing::info!("starting iteration through historical accounts to move bytecodes to account_codes");
+        for next in self.accounts_history.iter_start() {
+            let (key, CfAccountsHistoryValue::V1(account)) = next? else {
+                continue;
+            };
+            let (account, code) = account.into_v2();
+            if let Some((code_hash, bytecode)) = code {
+                report.record_code(&mut migrated_codes, code_hash, &bytecode);
+                bufwriter.insert(&self.account_codes, code_hash, bytecode.into())?;
+            }
+            bufwriter.insert(&self.accounts_history, key, account.into())?;
+            report.history_accounts += 1;
+        }
+        bufwriter.flush(&self.db)?;
+
+        tracing::info!(?report, "finished account codes migration");
+        Ok(report)
+    }
+}
+
+/// Summary of the bytecodes moved to the `account_codes` column family by a migration.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AccountCodesMigrationReport {
+    /// Current accounts rewritten.
+    pub accounts: u64,
+
+    /// Historical accounts rewritten.
+    pub history_accounts: u64,
+
+    /// Rewritten accounts that had a bytecode.
+    pub codes: u64,
+
+    /// Distinct bytecodes among the rewritten accounts.
+    pub unique_codes: u64,
+
+    /// Size of the bytecodes stored inline before the migration.
+    pub bytes_before: u64,
+
+    /// Size of the distinct bytecodes plus the code hashes referencing them after the migration.
+    pub bytes_after: u64,
+}
+
+impl AccountCodesMigrationReport {
+    fn record_code(&mut self, migrated_codes: &mut HashSet<HashRocksdb>, code_hash: HashRocksdb, bytecode: &BytesRocksdb) {
+        self.codes += 1;
+        self.bytes_before += bytecode.len() as u64;
+        self.bytes_after += 32;
+        if migrated_codes.insert(code_hash) {
+            self.unique_codes += 1;
+            self.bytes_after += bytecode.len() as u64;
+        }
+    }
+
+    /// Estimated space saved by the migration, realized after the column families are compacted.
+    pub fn bytes_saved(&self) -> u64 {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
 }
 
 #[cfg(feature = "metrics")]
@@ -672,9 +1151,13 @@ impl RocksStorageState {
         self.account_slots_history.export_metrics();
         self.accounts.export_metrics();
         self.accounts_history.export_metrics();
+        self.address_transactions.export_metrics();
+        self.block_account_changes.export_metrics();
+        self.block_slot_changes.export_metrics();
         self.blocks_by_hash.export_metrics();
         self.blocks_by_number.export_metrics();
         self.logs.export_metrics();
+        self.publisher_outbox.export_metrics();
         self.transactions.export_metrics();
         Ok(())
     }
@@ -701,6 +1184,10 @@ impl RocksStorageState {
 
 impl Drop for RocksStorageState {
     fn drop(&mut self) {
+        if self.read_only {
+            return;
+        }
+
         let mut options = WaitForCompactOptions::default();
         // if background jobs are paused, it makes no sense to keep waiting indefinitely
         options.set_abort_on_pause(true);
@@ -747,6 +1234,7 @@ mod tests {
     use tempfile::tempdir;
 
     use super::*;
+    use crate::eth::executor::EvmExecutionResult;
     use crate::eth::primitives::BlockHeader;
     use crate::eth::primitives::ExecutionValueChange;
     use crate::eth::primitives::SlotValue;
@@ -816,6 +1304,45 @@ mod tests {
         assert_eq!(state.read_logs(&filter).unwrap().len(), 200);
     }
 
+    #[test]
+    fn test_multi_transaction_block_receipt_fields_round_trip() {
+        let test_dir = tempdir().unwrap();
+        let state = RocksStorageState::new(test_dir.path().display().to_string(), Duration::ZERO).unwrap();
+
+        // block with 3 transactions using 100, 200 and 300 gas and emitting 2 logs each
+        let mut block = Block::new(1.into(), Faker.fake());
+        for gas in [100u64, 200, 300] {
+            let mut result: EvmExecutionResult = Faker.fake();
+            result.execution.gas = gas.into();
+            result.execution.logs = vec![Faker.fake(), Faker.fake()];
+            block.push_execution(Faker.fake(), result);
+        }
+        state.save_block(block.clone()).unwrap();
+
+        let read_block = state.read_block(&BlockFilter::Number(1.into())).unwrap().unwrap();
+        for (expected, read) in block.transactions.iter().zip(&read_block.transactions) {
+            assert_eq!(read.transaction_index, expected.transaction_index);
+            assert_eq!(read.cumulative_gas_used, expected.cumulative_gas_used);
+            assert_eq!(
+                read.logs.iter().map(|log| log.log_index).collect_vec(),
+                expected.logs.iter().map(|log| log.log_index).collect_vec()
+            );
+        }
+        assert_eq!(
+            read_block.transactions.iter().map(|tx| tx.cumulative_gas_used.as_u64()).collect_vec(),
+            vec![100, 300, 600]
+        );
+        assert_eq!(
+            read_block.transactions.iter().flat_map(|tx| &tx.logs).map(|log| log.log_index.0).collect_vec(),
+            (0..6).collect_vec()
+        );
+
+        let last_tx = &block.transactions[2];
+        let read_tx = state.read_transaction(&last_tx.input.hash).unwrap().unwrap();
+        assert_eq!(read_tx.transaction_index, last_tx.transaction_index);
+        assert_eq!(read_tx.cumulative_gas_used.as_u64(), 600);
+    }
+
     #[test]
     fn regression_test_saving_account_changes_for_accounts_that_didnt_change() {
         let test_dir = tempdir().unwrap();
@@ -862,4 +1389,65 @@ mod tests {
         let history = state.read_all_historical_accounts().unwrap();
         assert_eq!(history.len(), 3);
     }
+
+    #[test]
+    fn test_migrate_account_codes() {
+        let test_dir = tempdir().unwrap();
+        let state = RocksStorageState::new(test_dir.path().display().to_string(), Duration::ZERO).unwrap();
+
+        // two contracts sharing the same bytecode and one EOA, all stored in the legacy format
+        let bytecode = BytesRocksdb(vec![0x60; 1000]);
+        let contract = AccountRocksdb {
+            bytecode: Some(bytecode.clone()),
+            ..Faker.fake()
+        };
+        let eoa = AccountRocksdb {
+            bytecode: None,
+            ..Faker.fake()
+        };
+        let accounts: [(Address, AccountRocksdb); 3] = [(Faker.fake(), contract.clone()), (Faker.fake(), contract), (Faker.fake(), eoa)];
+        for (address, account) in &accounts {
+            state.accounts.insert((*address).into(), CfAccountsValue::V1(account.clone())).unwrap();
+            state
+                .accounts_history
+                .insert(((*address).into(), 0u64.into()), CfAccountsHistoryValue::V1(account.clone()))
+                .unwrap();
+        }
+
+        let report = state.migrate_account_codes().unwrap();
+        assert_eq!(report.accounts, 3);
+        assert_eq!(report.history_accounts, 3);
+        assert_eq!(report.codes, 4);
+        assert_eq!(report.unique_codes, 1);
+        assert_eq!(report.bytes_before, 4000);
+        assert_eq!(report.bytes_after, 1000 + 4 * 32);
+
+        // accounts are rewritten but read the same
+        for (address, account) in &accounts {
+            let stored = state.accounts.get(&(*address).into()).unwrap().unwrap();
+            assert!(matches!(stored, CfAccountsValue::V2(_)));
+            for point_in_time in [StoragePointInTime::Mined, StoragePointInTime::MinedPast(0.into())] {
+                assert_eq!(state.read_account(address, &point_in_time).unwrap(), Some(account.to_account(address)));
+            }
+        }
+
+        // running again is a no-op
+        assert_eq!(state.migrate_account_codes().unwrap(), AccountCodesMigrationReport::default());
+    }
+
+    #[test]
+    fn test_read_only_reads_existing_blocks_and_rejects_writes() {
+        let test_dir = tempdir().unwrap();
+        let path = test_dir.path().display().to_string();
+
+        {
+            let state = RocksStorageState::new(path.clone(), Duration::ZERO).unwrap();
+            state.save_block(Block::new(1.into(), Faker.fake())).unwrap();
+        }
+
+        let state = RocksStorageState::new_read_only(path, Duration::ZERO).unwrap();
+        let block = state.read_block(&BlockFilter::Number(1.into())).unwrap();
+        assert_eq!(block.map(|block| block.number()), Some(1.into()));
+        assert!(state.save_block(Block::new(2.into(), Faker.fake())).is_err());
+    }
 }
@@ -0,0 +1,43 @@
+use std::fmt::Debug;
+
+use super::address::AddressRocksdb;
+use super::hash::HashRocksdb;
+use crate::eth::primitives::AccessList;
+use crate::eth::primitives::AccessListItem;
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct AccessListRocksdb(pub Vec<AccessListItemRocksdb>);
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct AccessListItemRocksdb {
+    pub address: AddressRocksdb,
+    pub storage_keys: Vec<HashRocksdb>,
+}
+
+impl From<AccessList> for AccessListRocksdb {
+    fn from(item: AccessList) -> Self {
+        let items = item
+            .0
+            .into_iter()
+            .map(|item| AccessListItemRocksdb {
+                address: AddressRocksdb::from(item.address),
+                storage_keys: item.storage_keys.into_iter().map(HashRocksdb::from).collect(),
+            })
+            .collect();
+        Self(items)
+    }
+}
+
+impl From<AccessListRocksdb> for AccessList {
+    fn from(item: AccessListRocksdb) -> Self {
+        let items = item
+            .0
+            .into_iter()
+            .map(|item| AccessListItem {
+                address: item.address.into(),
+                storage_keys: item.storage_keys.into_iter().map(Into::into).collect(),
+            })
+            .collect();
+        Self(items)
+    }
+}
@@ -1,13 +1,16 @@
 use std::fmt::Debug;
 
+use ethers_core::utils::keccak256;
 use revm::primitives::KECCAK_EMPTY;
 
 use super::address::AddressRocksdb;
 use super::bytes::BytesRocksdb;
+use super::hash::HashRocksdb;
 use super::nonce::NonceRocksdb;
 use super::wei::WeiRocksdb;
 use crate::eth::primitives::Account;
 use crate::eth::primitives::Address;
+use crate::eth::primitives::CodeHash;
 use crate::ext::OptionExt;
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
@@ -27,6 +30,39 @@ impl AccountRocksdb {
             code_hash: KECCAK_EMPTY.into(),
         }
     }
+
+    /// Splits the bytecode from the account, returning the account referencing it by hash and the bytecode itself.
+    pub fn into_v2(self) -> (AccountRocksdbV2, Option<(HashRocksdb, BytesRocksdb)>) {
+        let code = self.bytecode.map(|bytecode| (CodeHash::from(keccak256(&*bytecode)).into(), bytecode));
+
+        let account = AccountRocksdbV2 {
+            balance: self.balance,
+            nonce: self.nonce,
+            code_hash: code.as_ref().map(|(code_hash, _)| *code_hash),
+        };
+        (account, code)
+    }
+}
+
+/// Account with its bytecode stored in the `account_codes` column family, referenced by the code hash.
+///
+/// Accounts that share the same bytecode (and historical versions of the same account) store it only once.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct AccountRocksdbV2 {
+    pub balance: WeiRocksdb,
+    pub nonce: NonceRocksdb,
+    pub code_hash: Option<HashRocksdb>,
+}
+
+impl AccountRocksdbV2 {
+    /// Joins the account with its bytecode, previously read from the `account_codes` column family.
+    pub fn into_v1(self, bytecode: Option<BytesRocksdb>) -> AccountRocksdb {
+        AccountRocksdb {
+            balance: self.balance,
+            nonce: self.nonce,
+            bytecode,
+        }
+    }
 }
 
 impl From<Account> for (AddressRocksdb, AccountRocksdb) {
@@ -1,10 +1,18 @@
 use std::fmt::Debug;
 
+use fake::Dummy;
+use fake::Fake;
+use fake::Faker;
+
+use super::access_list::AccessListItemRocksdb;
+use super::access_list::AccessListRocksdb;
 use super::address::AddressRocksdb;
 use super::block_header::BlockHeaderRocksdb;
 use super::block_number::BlockNumberRocksdb;
 use super::hash::HashRocksdb;
 use super::transaction_mined::TransactionMinedRocksdb;
+use super::transaction_mined::TransactionMinedRocksdbV2;
+use super::wei::WeiRocksdb;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockHeader;
@@ -18,9 +26,79 @@ pub struct BlockRocksdb {
     pub transactions: Vec<TransactionMinedRocksdb>,
 }
 
-impl From<Block> for BlockRocksdb {
+/// Block with the base fee of its header, which is not stored in [`BlockRocksdb`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct BlockRocksdbV2 {
+    pub header: BlockHeaderRocksdb,
+    pub transactions: Vec<TransactionMinedRocksdb>,
+    pub base_fee_per_gas: WeiRocksdb,
+}
+
+impl From<BlockRocksdb> for BlockRocksdbV2 {
+    fn from(item: BlockRocksdb) -> Self {
+        BlockRocksdbV2 {
+            header: item.header,
+            transactions: item.transactions,
+            base_fee_per_gas: WeiRocksdb::ZERO, // blocks stored before the base fee was persisted did not have it
+        }
+    }
+}
+
+// generated from the previous version, so both versions share the same fake data
+impl Dummy<Faker> for BlockRocksdbV2 {
+    fn dummy_with_rng<R: ethers_core::rand::prelude::Rng + ?Sized>(faker: &Faker, rng: &mut R) -> Self {
+        let block: BlockRocksdb = faker.fake_with_rng(rng);
+        Self {
+            base_fee_per_gas: WeiRocksdb::ONE,
+            ..block.into()
+        }
+    }
+}
+
+/// Block with the fee market fields and access list of its transactions, which are not stored in [`BlockRocksdbV2`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct BlockRocksdbV3 {
+    pub header: BlockHeaderRocksdb,
+    pub transactions: Vec<TransactionMinedRocksdbV2>,
+    pub base_fee_per_gas: WeiRocksdb,
+}
+
+impl From<BlockRocksdb> for BlockRocksdbV3 {
+    fn from(item: BlockRocksdb) -> Self {
+        BlockRocksdbV2::from(item).into()
+    }
+}
+
+impl From<BlockRocksdbV2> for BlockRocksdbV3 {
+    fn from(item: BlockRocksdbV2) -> Self {
+        BlockRocksdbV3 {
+            header: item.header,
+            transactions: item.transactions.into_iter().map(TransactionMinedRocksdbV2::from).collect(),
+            base_fee_per_gas: item.base_fee_per_gas,
+        }
+    }
+}
+
+// generated from the previous version, so all versions share the same fake data
+impl Dummy<Faker> for BlockRocksdbV3 {
+    fn dummy_with_rng<R: ethers_core::rand::prelude::Rng + ?Sized>(faker: &Faker, rng: &mut R) -> Self {
+        let block: BlockRocksdbV2 = faker.fake_with_rng(rng);
+        let mut block = Self::from(block);
+        for tx in &mut block.transactions {
+            tx.input.max_fee_per_gas = Some(WeiRocksdb::ONE);
+            tx.input.max_priority_fee_per_gas = Some(WeiRocksdb::ONE);
+            tx.input.access_list = Some(AccessListRocksdb(vec![AccessListItemRocksdb {
+                address: tx.input.signer,
+                storage_keys: vec![tx.input.hash],
+            }]));
+        }
+        block
+    }
+}
+
+impl From<Block> for BlockRocksdbV3 {
     fn from(item: Block) -> Self {
-        BlockRocksdb {
+        BlockRocksdbV3 {
             header: BlockHeaderRocksdb {
                 number: BlockNumberRocksdb::from(item.header.number),
                 hash: HashRocksdb::from(item.header.hash),
@@ -41,20 +119,22 @@ impl From<Block> for BlockRocksdb {
                 total_difficulty: item.header.total_difficulty.into(),
                 nonce: item.header.nonce.into(),
             },
-            transactions: item.transactions.into_iter().map(TransactionMinedRocksdb::from).collect(),
+            transactions: item.transactions.into_iter().map(TransactionMinedRocksdbV2::from).collect(),
+            base_fee_per_gas: item.header.base_fee_per_gas.into(),
         }
     }
 }
 
-impl From<BlockRocksdb> for Block {
-    fn from(item: BlockRocksdb) -> Self {
-        Block {
+impl From<BlockRocksdbV3> for Block {
+    fn from(item: BlockRocksdbV3) -> Self {
+        let mut block = Block {
             header: BlockHeader {
                 number: BlockNumber::from(item.header.number),
                 hash: Hash::from(item.header.hash),
                 transactions_root: Hash::from(item.header.transactions_root),
                 gas_used: item.header.gas_used.into(),
                 gas_limit: item.header.gas_limit.into(),
+                base_fee_per_gas: item.base_fee_per_gas.into(),
                 bloom: item.header.bloom.into(),
                 timestamp: item.header.timestamp.into(),
                 parent_hash: Hash::from(item.header.parent_hash),
@@ -68,8 +148,16 @@ impl From<BlockRocksdb> for Block {
                 state_root: Hash::from(item.header.state_root),
                 total_difficulty: item.header.total_difficulty.into(),
                 nonce: item.header.nonce.into(),
+                mix_hash: Hash::ZERO, // not persisted because local blocks do not have it
+                withdrawals_root: None,
+                blob_gas_used: None,
+                excess_blob_gas: None,
+                parent_beacon_block_root: None,
+                requests_hash: None,
             },
             transactions: item.transactions.into_iter().map(TransactionMined::from).collect(),
-        }
+        };
+        block.derive_cumulative_gas_used();
+        block
     }
 }
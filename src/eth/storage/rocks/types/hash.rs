@@ -1,5 +1,6 @@
 use std::fmt::Debug;
 
+use crate::eth::primitives::CodeHash;
 use crate::eth::primitives::Hash;
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize, fake::Dummy)]
@@ -16,3 +17,15 @@ impl From<HashRocksdb> for Hash {
         item.0.into()
     }
 }
+
+impl From<CodeHash> for HashRocksdb {
+    fn from(item: CodeHash) -> Self {
+        HashRocksdb(item.0.into())
+    }
+}
+
+impl From<HashRocksdb> for CodeHash {
+    fn from(item: HashRocksdb) -> Self {
+        item.0.into()
+    }
+}
@@ -1,3 +1,4 @@
+mod access_list;
 mod account;
 mod address;
 mod block;
@@ -24,9 +25,13 @@ mod unix_time;
 mod wei;
 
 pub use account::AccountRocksdb;
+pub use account::AccountRocksdbV2;
 pub use address::AddressRocksdb;
 pub use block::BlockRocksdb;
+pub use block::BlockRocksdbV2;
+pub use block::BlockRocksdbV3;
 pub use block_number::BlockNumberRocksdb;
+pub use bytes::BytesRocksdb;
 pub use hash::HashRocksdb;
 pub use index::IndexRocksdb;
 pub use slot::SlotIndexRocksdb;
@@ -34,8 +39,8 @@ pub use slot::SlotValueRocksdb;
 
 #[cfg(test)]
 mod tests {
+    use access_list::AccessListRocksdb;
     use block_header::BlockHeaderRocksdb;
-    use bytes::BytesRocksdb;
     use chain_id::ChainIdRocksdb;
     use difficulty::DifficultyRocksdb;
     use execution::ExecutionRocksdb;
@@ -47,7 +52,9 @@ mod tests {
     use nonce::NonceRocksdb;
     use size::SizeRocksdb;
     use transaction_input::TransactionInputRocksdb;
+    use transaction_input::TransactionInputRocksdbV2;
     use transaction_mined::TransactionMinedRocksdb;
+    use transaction_mined::TransactionMinedRocksdbV2;
     use unix_time::UnixTimeRocksdb;
     use wei::WeiRocksdb;
 
@@ -55,11 +62,15 @@ mod tests {
     use super::*;
     use crate::gen_test_bincode;
 
+    gen_test_bincode!(AccessListRocksdb);
     gen_test_bincode!(AccountRocksdb);
+    gen_test_bincode!(AccountRocksdbV2);
     gen_test_bincode!(AddressRocksdb);
     gen_test_bincode!(BlockHeaderRocksdb);
     gen_test_bincode!(BlockNumberRocksdb);
     gen_test_bincode!(BlockRocksdb);
+    gen_test_bincode!(BlockRocksdbV2);
+    gen_test_bincode!(BlockRocksdbV3);
     gen_test_bincode!(BytesRocksdb);
     gen_test_bincode!(ChainIdRocksdb);
     gen_test_bincode!(DifficultyRocksdb);
@@ -77,7 +88,9 @@ mod tests {
     gen_test_bincode!(SlotIndexRocksdb);
     gen_test_bincode!(SlotValueRocksdb);
     gen_test_bincode!(TransactionInputRocksdb);
+    gen_test_bincode!(TransactionInputRocksdbV2);
     gen_test_bincode!(TransactionMinedRocksdb);
+    gen_test_bincode!(TransactionMinedRocksdbV2);
     gen_test_bincode!(UnixTimeRocksdb);
     gen_test_bincode!(WeiRocksdb);
 }
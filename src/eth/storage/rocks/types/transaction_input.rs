@@ -2,6 +2,7 @@ use std::fmt::Debug;
 
 use ethereum_types::U256;
 
+use super::access_list::AccessListRocksdb;
 use super::address::AddressRocksdb;
 use super::bytes::BytesRocksdb;
 use super::chain_id::ChainIdRocksdb;
@@ -30,7 +31,54 @@ pub struct TransactionInputRocksdb {
     pub s: [u64; 4],
 }
 
-impl From<TransactionInput> for TransactionInputRocksdb {
+/// Transaction input with the fee market fields and access list, which are not stored in [`TransactionInputRocksdb`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct TransactionInputRocksdbV2 {
+    pub tx_type: Option<u64>,
+    pub chain_id: Option<ChainIdRocksdb>,
+    pub hash: HashRocksdb,
+    pub nonce: NonceRocksdb,
+    pub signer: AddressRocksdb,
+    pub from: AddressRocksdb,
+    pub to: Option<AddressRocksdb>,
+    pub value: WeiRocksdb,
+    pub input: BytesRocksdb,
+    pub gas_limit: GasRocksdb,
+    pub gas_price: WeiRocksdb,
+    pub max_fee_per_gas: Option<WeiRocksdb>,
+    pub max_priority_fee_per_gas: Option<WeiRocksdb>,
+    pub access_list: Option<AccessListRocksdb>,
+    pub v: u64,
+    pub r: [u64; 4],
+    pub s: [u64; 4],
+}
+
+impl From<TransactionInputRocksdb> for TransactionInputRocksdbV2 {
+    fn from(item: TransactionInputRocksdb) -> Self {
+        Self {
+            tx_type: item.tx_type,
+            chain_id: item.chain_id,
+            hash: item.hash,
+            nonce: item.nonce,
+            signer: item.signer,
+            from: item.from,
+            to: item.to,
+            value: item.value,
+            input: item.input,
+            gas_limit: item.gas_limit,
+            gas_price: item.gas_price,
+            // transactions stored before these fields were persisted did not have them
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            access_list: None,
+            v: item.v,
+            r: item.r,
+            s: item.s,
+        }
+    }
+}
+
+impl From<TransactionInput> for TransactionInputRocksdbV2 {
     fn from(item: TransactionInput) -> Self {
         Self {
             tx_type: item.tx_type.map(|inner| inner.as_u64()),
@@ -44,6 +92,9 @@ impl From<TransactionInput> for TransactionInputRocksdb {
             input: BytesRocksdb::from(item.input),
             gas_limit: GasRocksdb::from(item.gas_limit),
             gas_price: WeiRocksdb::from(item.gas_price),
+            max_fee_per_gas: item.max_fee_per_gas.map_into(),
+            max_priority_fee_per_gas: item.max_priority_fee_per_gas.map_into(),
+            access_list: item.access_list.map_into(),
             v: item.v.as_u64(),
             r: item.r.0,
             s: item.s.0,
@@ -51,8 +102,8 @@ impl From<TransactionInput> for TransactionInputRocksdb {
     }
 }
 
-impl From<TransactionInputRocksdb> for TransactionInput {
-    fn from(item: TransactionInputRocksdb) -> Self {
+impl From<TransactionInputRocksdbV2> for TransactionInput {
+    fn from(item: TransactionInputRocksdbV2) -> Self {
         Self {
             chain_id: item.chain_id.map_into(),
             hash: item.hash.into(),
@@ -64,6 +115,9 @@ impl From<TransactionInputRocksdb> for TransactionInput {
             input: item.input.into(),
             gas_limit: item.gas_limit.into(),
             gas_price: item.gas_price.into(),
+            max_fee_per_gas: item.max_fee_per_gas.map_into(),
+            max_priority_fee_per_gas: item.max_priority_fee_per_gas.map_into(),
+            access_list: item.access_list.map_into(),
             v: item.v.into(),
             r: U256(item.r),
             s: U256(item.s),
@@ -1,11 +1,16 @@
 use std::fmt::Debug;
 
+use itertools::Itertools;
+
 use super::block_number::BlockNumberRocksdb;
 use super::execution::ExecutionRocksdb;
 use super::hash::HashRocksdb;
 use super::index::IndexRocksdb;
 use super::log_mined::LogMinedRockdb;
 use super::transaction_input::TransactionInputRocksdb;
+use super::transaction_input::TransactionInputRocksdbV2;
+use crate::eth::primitives::logs_bloom::LogsBloom;
+use crate::eth::primitives::Gas;
 use crate::eth::primitives::LogMined;
 use crate::eth::primitives::TransactionMined;
 
@@ -19,7 +24,31 @@ pub struct TransactionMinedRocksdb {
     pub block_hash: HashRocksdb,
 }
 
-impl From<TransactionMined> for TransactionMinedRocksdb {
+/// Transaction with the fee market fields and access list of its input, which are not stored in [`TransactionMinedRocksdb`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, fake::Dummy)]
+pub struct TransactionMinedRocksdbV2 {
+    pub input: TransactionInputRocksdbV2,
+    pub execution: ExecutionRocksdb,
+    pub logs: Vec<LogMinedRockdb>,
+    pub transaction_index: IndexRocksdb,
+    pub block_number: BlockNumberRocksdb,
+    pub block_hash: HashRocksdb,
+}
+
+impl From<TransactionMinedRocksdb> for TransactionMinedRocksdbV2 {
+    fn from(item: TransactionMinedRocksdb) -> Self {
+        Self {
+            input: item.input.into(),
+            execution: item.execution,
+            logs: item.logs,
+            transaction_index: item.transaction_index,
+            block_number: item.block_number,
+            block_hash: item.block_hash,
+        }
+    }
+}
+
+impl From<TransactionMined> for TransactionMinedRocksdbV2 {
     fn from(item: TransactionMined) -> Self {
         Self {
             input: item.input.into(),
@@ -32,13 +61,18 @@ impl From<TransactionMined> for TransactionMinedRocksdb {
     }
 }
 
-impl From<TransactionMinedRocksdb> for TransactionMined {
-    fn from(item: TransactionMinedRocksdb) -> Self {
+impl From<TransactionMinedRocksdbV2> for TransactionMined {
+    fn from(item: TransactionMinedRocksdbV2) -> Self {
+        let logs = item.logs.into_iter().map(LogMined::from).collect_vec();
         Self {
             input: item.input.into(),
             execution: item.execution.into(),
-            logs: item.logs.into_iter().map(LogMined::from).collect(),
+            // not persisted to keep the block format, it is derived from the stored logs instead
+            logs_bloom: LogsBloom::from_logs(logs.iter().map(|log| &log.log)),
+            logs,
             transaction_index: item.transaction_index.into(),
+            // not persisted to keep the block format, it is derived from the gas used by the block transactions instead
+            cumulative_gas_used: Gas::ZERO,
             block_number: item.block_number.into(),
             block_hash: item.block_hash.into(),
         }
@@ -0,0 +1,224 @@
+//! Reader of state files used to bootstrap the permanent storage with the state of an existing chain.
+//!
+//! A state file is a CSV file where each line is an account or a slot:
+//!
+//! ```csv
+//! kind,address,nonce_or_index,balance_or_value,bytecode
+//! account,0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266,0x1,1000000000000000000,
+//! account,0x5fbdb2315678afecb367f032d93f642f64180aa3,1,0x0,0x602a60005260206000f3
+//! slot,0x5fbdb2315678afecb367f032d93f642f64180aa3,0x0,0x2a
+//! ```
+//!
+//! The header line, empty lines and lines starting with `#` are ignored. Quantities can be hexadecimal strings prefixed with `0x` or
+//! decimal strings, and the bytecode column can be left empty for accounts that are not contracts.
+
+use std::io::BufRead;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use anyhow::Context;
+
+use crate::eth::primitives::Account;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::CodeHash;
+use crate::eth::primitives::Slot;
+use crate::eth::storage::genesis_config::parse_u256;
+
+/// Accounts and slots read from a state file to be saved together.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateImportBatch {
+    pub accounts: Vec<Account>,
+    pub slots: Vec<(Address, Slot)>,
+}
+
+impl StateImportBatch {
+    /// Number of accounts and slots in the batch.
+    pub fn len(&self) -> usize {
+        self.accounts.len() + self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Reads batches of at most `batch_size` accounts and slots from a state file.
+pub struct StateImportReader<R> {
+    reader: R,
+    batch_size: usize,
+    line_number: usize,
+    line: String,
+}
+
+impl<R: BufRead> StateImportReader<R> {
+    pub fn new(reader: R, batch_size: usize) -> Self {
+        Self {
+            reader,
+            batch_size: batch_size.max(1),
+            line_number: 0,
+            line: String::new(),
+        }
+    }
+
+    /// Parses the next line of the file. Returns `None` at the end of the file.
+    fn next_record(&mut self) -> anyhow::Result<Option<StateImportRecord>> {
+        loop {
+            self.line.clear();
+            if self.reader.read_line(&mut self.line)? == 0 {
+                return Ok(None);
+            }
+            self.line_number += 1;
+
+            let record = parse_line(&self.line).with_context(|| format!("invalid state file line {}", self.line_number))?;
+            if record.is_some() {
+                return Ok(record);
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for StateImportReader<R> {
+    type Item = anyhow::Result<StateImportBatch>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut batch = StateImportBatch::default();
+        while batch.len() < self.batch_size {
+            match self.next_record() {
+                Ok(Some(StateImportRecord::Account(account))) => batch.accounts.push(account),
+                Ok(Some(StateImportRecord::Slot(address, slot))) => batch.slots.push((address, slot)),
+                Ok(None) => break,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        if batch.is_empty() {
+            None
+        } else {
+            Some(Ok(batch))
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Parsing
+// -----------------------------------------------------------------------------
+
+enum StateImportRecord {
+    Account(Account),
+    Slot(Address, Slot),
+}
+
+/// Parses a line of the state file. Returns `None` for lines that must be ignored.
+fn parse_line(line: &str) -> anyhow::Result<Option<StateImportRecord>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    let columns = line.split(',').map(str::trim).collect::<Vec<_>>();
+    match columns.as_slice() {
+        ["kind", ..] => Ok(None),
+        ["account", address, nonce, balance] => Ok(Some(parse_account(address, nonce, balance, "")?)),
+        ["account", address, nonce, balance, bytecode] => Ok(Some(parse_account(address, nonce, balance, bytecode)?)),
+        ["slot", address, index, value] | ["slot", address, index, value, ""] => {
+            let address = Address::from_str(address).with_context(|| format!("invalid address {}", address))?;
+            let index = parse_u256(index).with_context(|| format!("invalid slot index {}", index))?;
+            let value = parse_u256(value).with_context(|| format!("invalid slot value {}", value))?;
+            Ok(Some(StateImportRecord::Slot(address, Slot::new(index.into(), value.into()))))
+        }
+        [kind, ..] => Err(anyhow!("unexpected record kind {} or number of columns {}", kind, columns.len())),
+        [] => Ok(None),
+    }
+}
+
+fn parse_account(address: &str, nonce: &str, balance: &str, bytecode: &str) -> anyhow::Result<StateImportRecord> {
+    let address = Address::from_str(address).with_context(|| format!("invalid address {}", address))?;
+
+    let nonce = parse_u256(nonce).with_context(|| format!("invalid nonce {}", nonce))?;
+    if nonce > u64::MAX.into() {
+        return Err(anyhow!("nonce {} does not fit in 64 bits", nonce));
+    }
+    let balance = parse_u256(balance).with_context(|| format!("invalid balance {}", balance))?;
+
+    let bytecode = match bytecode {
+        "" | "0x" => None,
+        bytecode => Some(Bytes(const_hex::decode(bytecode).with_context(|| format!("invalid bytecode of {}", address))?)),
+    };
+
+    Ok(StateImportRecord::Account(Account {
+        address,
+        nonce: nonce.as_u64().into(),
+        balance: balance.into(),
+        code_hash: CodeHash::from_bytecode(bytecode.clone()),
+        bytecode,
+    }))
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use ethereum_types::U256;
+
+    use super::*;
+    use crate::eth::primitives::SlotIndex;
+    use crate::eth::primitives::SlotValue;
+    use crate::eth::primitives::Wei;
+
+    const STATE: &str = "kind,address,nonce_or_index,balance_or_value,bytecode
+# externally owned accounts
+account,0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266,0x1,1000000000000000000,
+
+account,0x5fbdb2315678afecb367f032d93f642f64180aa3,1,0x0,0x602a60005260206000f3
+slot,0x5fbdb2315678afecb367f032d93f642f64180aa3,0x0,0x2a
+slot,0x5fbdb2315678afecb367f032d93f642f64180aa3,1,7
+";
+
+    #[test]
+    fn state_import_reads_accounts_and_slots() {
+        let batches = StateImportReader::new(STATE.as_bytes(), 100).collect::<anyhow::Result<Vec<_>>>().unwrap();
+        assert_eq!(batches.len(), 1);
+
+        let batch = &batches[0];
+        assert_eq!(batch.accounts.len(), 2);
+        assert_eq!(batch.slots.len(), 2);
+
+        let eoa = &batch.accounts[0];
+        assert_eq!(eoa.address, Address::from_str("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266").unwrap());
+        assert_eq!(eoa.nonce.as_u64(), 1);
+        assert_eq!(eoa.balance, Wei::from(U256::exp10(18)));
+        assert!(eoa.bytecode.is_none());
+
+        let contract = &batch.accounts[1];
+        assert_eq!(contract.bytecode, Some(Bytes(vec![0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3])));
+        assert_eq!(contract.code_hash, CodeHash::from_bytecode(contract.bytecode.clone()));
+
+        assert_eq!(batch.slots[0], (contract.address, Slot::new(SlotIndex::ZERO, SlotValue::from(42u64))));
+        assert_eq!(batch.slots[1], (contract.address, Slot::new(SlotIndex::ONE, SlotValue::from(7u64))));
+    }
+
+    #[test]
+    fn state_import_splits_batches() {
+        let batches = StateImportReader::new(STATE.as_bytes(), 3).collect::<anyhow::Result<Vec<_>>>().unwrap();
+        assert_eq!(batches.iter().map(StateImportBatch::len).collect::<Vec<_>>(), vec![3, 1]);
+    }
+
+    #[test]
+    fn state_import_rejects_invalid_lines() {
+        let invalid = [
+            "account,0xinvalid,0,0,",
+            "account,0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266,0x10000000000000000,0,",
+            "account,0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266,0,abc,",
+            "account,0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266,0,0,0xzz",
+            "slot,0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266,0x0",
+            "storage,0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266,0x0,0x0",
+        ];
+        for line in invalid {
+            let mut reader = StateImportReader::new(line.as_bytes(), 10);
+            let error = reader.next().unwrap().unwrap_err();
+            assert!(error.to_string().contains("line 1"), "{}", line);
+        }
+    }
+}
@@ -0,0 +1,386 @@
+//! Snapshot of the full chain state used to bootstrap new nodes without replaying every block.
+//!
+//! A snapshot file has the following layout:
+//!
+//! | Offset | Size | Content                                            |
+//! |--------|------|----------------------------------------------------|
+//! | 0      | 8    | Magic bytes `STRATUSS`.                            |
+//! | 8      | 2    | Format version as little-endian `u16`.             |
+//! | 10     | 8    | Last mined block number as little-endian `u64`.    |
+//! | 18     | ..   | Sequence of sections.                              |
+//!
+//! Each section has the following layout:
+//!
+//! | Size | Content                                                                    |
+//! |------|----------------------------------------------------------------------------|
+//! | 1    | Section kind: `1` for accounts, `2` for slots, `0` for the end section.    |
+//! | 4    | Number of entries as little-endian `u32`.                                  |
+//! | 4    | Payload length as little-endian `u32`.                                     |
+//! | ..   | Payload with the entries encoded with `bincode` 1.x defaults.              |
+//! | 32   | Keccak256 checksum of the payload.                                         |
+//!
+//! Entries are split in sections of at most [`SNAPSHOT_SECTION_ENTRIES`] so corruption is detected close to where it happened. The end
+//! section payload contains the total number of accounts and slots, so a truncated file is never accepted as a valid snapshot.
+
+use ethereum_types::H160;
+use ethereum_types::H256;
+use ethereum_types::U256;
+use ethers_core::utils::keccak256;
+
+use crate::eth::primitives::Account;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::CodeHash;
+use crate::eth::primitives::Slot;
+use crate::eth::primitives::StratusError;
+
+/// Magic bytes identifying a snapshot file.
+pub const SNAPSHOT_MAGIC: &[u8; 8] = b"STRATUSS";
+
+/// Current version of the snapshot format. Must be incremented on any layout change.
+pub const SNAPSHOT_VERSION: u16 = 1;
+
+/// Maximum number of entries in a single section.
+pub const SNAPSHOT_SECTION_ENTRIES: usize = 10_000;
+
+const SECTION_END: u8 = 0;
+const SECTION_ACCOUNTS: u8 = 1;
+const SECTION_SLOTS: u8 = 2;
+
+/// All accounts and slots at a specific block.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateSnapshot {
+    /// Last mined block number when the snapshot was taken.
+    pub block_number: BlockNumber,
+
+    /// Accounts at the block.
+    pub accounts: Vec<Account>,
+
+    /// Slots at the block with the address of the account they belong to.
+    pub slots: Vec<(Address, Slot)>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SnapshotAccount {
+    address: [u8; 20],
+    nonce: u64,
+    balance: [u8; 32],
+    code_hash: [u8; 32],
+    bytecode: Option<Vec<u8>>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SnapshotSlot {
+    address: [u8; 20],
+    index: [u8; 32],
+    value: [u8; 32],
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SnapshotEnd {
+    accounts: u64,
+    slots: u64,
+}
+
+impl StateSnapshot {
+    // -------------------------------------------------------------------------
+    // Encoding
+    // -------------------------------------------------------------------------
+
+    /// Encodes the snapshot in the documented binary format.
+    pub fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        let mut buf = Vec::with_capacity(18 + self.accounts.len() * 128 + self.slots.len() * 96);
+        buf.extend_from_slice(SNAPSHOT_MAGIC);
+        buf.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+        buf.extend_from_slice(&self.block_number.as_u64().to_le_bytes());
+
+        for chunk in self.accounts.chunks(SNAPSHOT_SECTION_ENTRIES) {
+            let entries = chunk.iter().map(SnapshotAccount::from).collect::<Vec<_>>();
+            encode_section(&mut buf, SECTION_ACCOUNTS, entries.len(), &entries)?;
+        }
+        for chunk in self.slots.chunks(SNAPSHOT_SECTION_ENTRIES) {
+            let entries = chunk.iter().map(SnapshotSlot::from).collect::<Vec<_>>();
+            encode_section(&mut buf, SECTION_SLOTS, entries.len(), &entries)?;
+        }
+
+        let end = SnapshotEnd {
+            accounts: self.accounts.len() as u64,
+            slots: self.slots.len() as u64,
+        };
+        encode_section(&mut buf, SECTION_END, 0, &end)?;
+
+        Ok(buf)
+    }
+
+    /// Decodes a snapshot, validating its magic bytes, version and checksums.
+    pub fn decode(bytes: &[u8]) -> Result<Self, StratusError> {
+        let mut reader = SnapshotReader { bytes, offset: 0 };
+
+        // header
+        if reader.take(SNAPSHOT_MAGIC.len())? != SNAPSHOT_MAGIC {
+            return Err(invalid("magic bytes do not match"));
+        }
+        let version = u16::from_le_bytes(reader.take_array()?);
+        if version != SNAPSHOT_VERSION {
+            return Err(invalid(format!("unsupported version {} (expected {})", version, SNAPSHOT_VERSION)));
+        }
+        let mut snapshot = StateSnapshot {
+            block_number: u64::from_le_bytes(reader.take_array()?).into(),
+            ..Default::default()
+        };
+
+        // sections
+        loop {
+            let section_offset = reader.offset;
+            let kind = reader.take_array::<1>()?[0];
+            let entries = u32::from_le_bytes(reader.take_array()?) as usize;
+            let payload_len = u32::from_le_bytes(reader.take_array()?) as usize;
+            let payload = reader.take(payload_len)?;
+            let checksum = reader.take(32)?;
+            if keccak256(payload) != checksum {
+                return Err(invalid(format!("checksum mismatch in section at offset {}", section_offset)));
+            }
+
+            match kind {
+                SECTION_ACCOUNTS => {
+                    let accounts: Vec<SnapshotAccount> = decode_payload(payload, entries)?;
+                    snapshot.accounts.extend(accounts.into_iter().map(Account::from));
+                }
+                SECTION_SLOTS => {
+                    let slots: Vec<SnapshotSlot> = decode_payload(payload, entries)?;
+                    snapshot.slots.extend(slots.into_iter().map(<(Address, Slot)>::from));
+                }
+                SECTION_END => {
+                    let end: SnapshotEnd = bincode::deserialize(payload).map_err(|e| invalid(format!("failed to decode end section: {}", e)))?;
+                    if end.accounts != snapshot.accounts.len() as u64 || end.slots != snapshot.slots.len() as u64 {
+                        return Err(invalid("number of entries does not match the end section"));
+                    }
+                    if reader.offset != bytes.len() {
+                        return Err(invalid("unexpected data after the end section"));
+                    }
+                    return Ok(snapshot);
+                }
+                kind => return Err(invalid(format!("unknown section kind {}", kind))),
+            }
+        }
+    }
+}
+
+fn encode_section<T: serde::Serialize + ?Sized>(buf: &mut Vec<u8>, kind: u8, entries: usize, value: &T) -> anyhow::Result<()> {
+    let payload = bincode::serialize(value)?;
+    buf.push(kind);
+    buf.extend_from_slice(&u32::try_from(entries)?.to_le_bytes());
+    buf.extend_from_slice(&u32::try_from(payload.len())?.to_le_bytes());
+    buf.extend_from_slice(&payload);
+    buf.extend_from_slice(&keccak256(&payload));
+    Ok(())
+}
+
+fn decode_payload<T: serde::de::DeserializeOwned>(payload: &[u8], entries: usize) -> Result<Vec<T>, StratusError> {
+    let values: Vec<T> = bincode::deserialize(payload).map_err(|e| invalid(format!("failed to decode section: {}", e)))?;
+    if values.len() != entries {
+        return Err(invalid(format!("section has {} entries but {} were expected", values.len(), entries)));
+    }
+    Ok(values)
+}
+
+fn invalid(reason: impl Into<String>) -> StratusError {
+    StratusError::StorageSnapshotInvalid { reason: reason.into() }
+}
+
+/// Cursor over the snapshot bytes that fails when the file is truncated.
+struct SnapshotReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> SnapshotReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], StratusError> {
+        let end = self.offset.checked_add(len).filter(|end| *end <= self.bytes.len());
+        let Some(end) = end else {
+            return Err(invalid(format!("unexpected end of file at offset {}", self.offset)));
+        };
+        let value = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(value)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], StratusError> {
+        let mut value = [0u8; N];
+        value.copy_from_slice(self.take(N)?);
+        Ok(value)
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Conversions
+// -----------------------------------------------------------------------------
+
+impl From<&Account> for SnapshotAccount {
+    fn from(value: &Account) -> Self {
+        Self {
+            address: value.address.0.to_fixed_bytes(),
+            nonce: value.nonce.as_u64(),
+            balance: u256_to_bytes(value.balance.0),
+            code_hash: value.code_hash.0.to_fixed_bytes(),
+            bytecode: value.bytecode.as_ref().map(|bytecode| bytecode.0.clone()),
+        }
+    }
+}
+
+impl From<SnapshotAccount> for Account {
+    fn from(value: SnapshotAccount) -> Self {
+        Self {
+            address: H160(value.address).into(),
+            nonce: value.nonce.into(),
+            balance: U256::from_big_endian(&value.balance).into(),
+            bytecode: value.bytecode.map(Bytes),
+            code_hash: CodeHash::new(H256(value.code_hash)),
+        }
+    }
+}
+
+impl From<&(Address, Slot)> for SnapshotSlot {
+    fn from((address, slot): &(Address, Slot)) -> Self {
+        Self {
+            address: address.0.to_fixed_bytes(),
+            index: u256_to_bytes(slot.index.0),
+            value: u256_to_bytes(slot.value.0),
+        }
+    }
+}
+
+impl From<SnapshotSlot> for (Address, Slot) {
+    fn from(value: SnapshotSlot) -> Self {
+        let slot = Slot::new(U256::from_big_endian(&value.index).into(), U256::from_big_endian(&value.value).into());
+        (H160(value.address).into(), slot)
+    }
+}
+
+fn u256_to_bytes(value: U256) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    bytes
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::eth::primitives::BlockFilter;
+    use crate::eth::primitives::Nonce;
+    use crate::eth::primitives::SlotIndex;
+    use crate::eth::primitives::SlotValue;
+    use crate::eth::primitives::Wei;
+    use crate::eth::storage::InMemoryPermanentStorage;
+    use crate::eth::storage::InMemoryTemporaryStorage;
+    use crate::eth::storage::StorageEncryption;
+    use crate::eth::storage::StoragePointInTime;
+    use crate::eth::storage::StratusStorage;
+    use crate::eth::storage::ENCRYPTION_MAGIC;
+
+    fn snapshot() -> StateSnapshot {
+        let eoa = Account::new_with_balance(Address::new([1; 20]), Wei::from(1_000u64));
+        let contract = Account {
+            address: Address::new([2; 20]),
+            nonce: Nonce::from(1u64),
+            balance: Wei::ZERO,
+            bytecode: Some(Bytes(vec![0x60, 0x00])),
+            code_hash: CodeHash::from_bytecode(Some(Bytes(vec![0x60, 0x00]))),
+        };
+        StateSnapshot {
+            block_number: 42u64.into(),
+            accounts: vec![eoa, contract],
+            slots: vec![(Address::new([2; 20]), Slot::new(SlotIndex::ONE, SlotValue::from(7u64)))],
+        }
+    }
+
+    #[test]
+    fn state_snapshot_roundtrip() {
+        let snapshot = snapshot();
+        let decoded = StateSnapshot::decode(&snapshot.encode().unwrap()).unwrap();
+        assert_eq!(decoded, snapshot);
+    }
+
+    #[test]
+    fn state_snapshot_roundtrip_many_sections() {
+        let mut snapshot = snapshot();
+        snapshot.slots = (0..SNAPSHOT_SECTION_ENTRIES as u64 * 2 + 1)
+            .map(|i| (Address::new([3; 20]), Slot::new(i.into(), (i + 1).into())))
+            .collect();
+        let decoded = StateSnapshot::decode(&snapshot.encode().unwrap()).unwrap();
+        assert_eq!(decoded, snapshot);
+    }
+
+    #[test]
+    fn state_snapshot_export_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.snapshot");
+        let snapshot = snapshot();
+        std::fs::write(&path, snapshot.encode().unwrap()).unwrap();
+
+        // import into an empty storage
+        let source = StratusStorage::new(Box::<InMemoryTemporaryStorage>::default(), Box::<InMemoryPermanentStorage>::default()).unwrap();
+        source.import_snapshot(&path).unwrap();
+        assert_eq!(source.read_mined_block_number().unwrap(), snapshot.block_number);
+        assert_eq!(source.read_pending_block_number().unwrap(), Some(snapshot.block_number.next_block_number()));
+        assert!(source.read_block(&BlockFilter::Number(snapshot.block_number)).unwrap().is_some());
+        for account in &snapshot.accounts {
+            assert_eq!(&source.read_account(&account.address, &StoragePointInTime::Mined).unwrap(), account);
+        }
+        for (address, slot) in &snapshot.slots {
+            assert_eq!(&source.read_slot(address, &slot.index, &StoragePointInTime::Mined).unwrap(), slot);
+        }
+
+        // export contains the imported state
+        let exported = source.export_snapshot(&dir.path().join("exported.snapshot")).unwrap();
+        assert_eq!(exported.block_number, snapshot.block_number);
+        for account in &snapshot.accounts {
+            assert!(exported.accounts.contains(account));
+        }
+        assert_eq!(exported.slots, snapshot.slots);
+
+        // cannot import into a storage with mined blocks
+        assert!(matches!(source.import_snapshot(&path), Err(StratusError::StorageSnapshotImportNotEmpty { .. })));
+    }
+
+    #[test]
+    fn state_snapshot_export_import_encrypted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.snapshot");
+        let encryption = Arc::new(StorageEncryption::new(&[1; 32]).unwrap());
+
+        let new_storage = || StratusStorage::new(Box::<InMemoryTemporaryStorage>::default(), Box::<InMemoryPermanentStorage>::default()).unwrap();
+        let mut source = new_storage();
+        source.enable_encryption(Arc::clone(&encryption));
+        source.export_snapshot(&path).unwrap();
+        assert!(std::fs::read(&path).unwrap().starts_with(ENCRYPTION_MAGIC));
+
+        // encrypted snapshot requires the key
+        assert!(new_storage().import_snapshot(&path).is_err());
+        let mut target = new_storage();
+        target.enable_encryption(encryption);
+        target.import_snapshot(&path).unwrap();
+    }
+
+    #[test]
+    fn state_snapshot_rejects_corrupted_payload() {
+        let mut bytes = snapshot().encode().unwrap();
+        bytes[30] ^= 0xff;
+        assert!(matches!(StateSnapshot::decode(&bytes), Err(StratusError::StorageSnapshotInvalid { .. })));
+    }
+
+    #[test]
+    fn state_snapshot_rejects_truncated_file() {
+        let bytes = snapshot().encode().unwrap();
+        for len in [0, 10, 18, bytes.len() - 1] {
+            assert!(matches!(StateSnapshot::decode(&bytes[..len]), Err(StratusError::StorageSnapshotInvalid { .. })));
+        }
+    }
+}
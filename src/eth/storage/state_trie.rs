@@ -0,0 +1,773 @@
+//! Merkle-Patricia state trie.
+//!
+//! Keeps the latest committed state of accounts and slots in memory to compute state roots and account proofs (EIP-1186).
+//!
+//! Tries are updated incrementally: nodes are immutable and shared between versions, so changing a key recreates only the nodes in its
+//! path and only those are encoded again when the root is requested. Sharing nodes also makes copies of the trie cheap, which is used
+//! to compute the state root of a block before it is saved.
+//!
+//! The trie is not persisted. It is rebuilt from the permanent storage when the storage is initialized and after resets.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::sync::RwLock;
+
+use ethereum_types::H256;
+use ethereum_types::U256;
+use ethers_core::utils::keccak256;
+use rlp::RlpStream;
+
+use crate::eth::primitives::Account;
+use crate::eth::primitives::Address;
+use crate::eth::primitives::Block;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::CodeHash;
+use crate::eth::primitives::Hash;
+use crate::eth::primitives::Nonce;
+use crate::eth::primitives::Slot;
+use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::SlotValue;
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::Wei;
+use crate::ext::MutexResultExt;
+
+/// RLP encoding of an empty trie node.
+const EMPTY_NODE: [u8; 1] = [0x80];
+
+/// In-memory state trie tracking accounts and slots committed to the permanent storage.
+#[derive(Debug, Default)]
+pub struct StateTrie {
+    state: RwLock<StateTrieState>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct StateTrieState {
+    accounts: HashMap<Address, StateTrieAccount>,
+
+    /// Trie of account leaves.
+    trie: MerkleTrie,
+}
+
+#[derive(Debug, Clone, Default)]
+struct StateTrieAccount {
+    nonce: Nonce,
+    balance: Wei,
+    code_hash: CodeHash,
+
+    /// Trie of non-zero slots.
+    storage: MerkleTrie,
+}
+
+/// Account changes of a block not tracked yet.
+#[derive(Debug)]
+pub struct StateTrieUpdate {
+    accounts: Vec<(Address, StateTrieAccount)>,
+
+    /// State root after the changes are tracked.
+    pub state_root: Hash,
+}
+
+/// Account proof as specified by EIP-1186.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountProof {
+    pub address: Address,
+    pub account_proof: Vec<Bytes>,
+    pub balance: Wei,
+    pub code_hash: CodeHash,
+    pub nonce: Nonce,
+    pub storage_hash: Hash,
+    pub storage_proof: Vec<SlotProof>,
+}
+
+/// Slot proof as specified by EIP-1186.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SlotProof {
+    pub key: SlotIndex,
+    pub value: SlotValue,
+    pub proof: Vec<Bytes>,
+}
+
+impl StateTrie {
+    // -------------------------------------------------------------------------
+    // Writes
+    // -------------------------------------------------------------------------
+
+    /// Tracks initial accounts (test accounts or genesis accounts).
+    pub fn save_accounts(&self, accounts: &[Account]) -> Result<(), StratusError> {
+        let mut state = self.state.write().map_lock_error("StateTrie::save_accounts")?;
+        for account in accounts {
+            let mut entry = state.accounts.get(&account.address).cloned().unwrap_or_default();
+            entry.nonce = account.nonce;
+            entry.balance = account.balance;
+            entry.code_hash = account.code_hash;
+            state.save_account(account.address, entry);
+        }
+        Ok(())
+    }
+
+    /// Overrides a single slot of an account.
+    pub fn save_slot(&self, address: &Address, slot: Slot) -> Result<(), StratusError> {
+        let mut state = self.state.write().map_lock_error("StateTrie::save_slot")?;
+        let mut entry = state.accounts.get(address).cloned().unwrap_or_default();
+        entry.save_slot(slot);
+        state.save_account(*address, entry);
+        Ok(())
+    }
+
+    /// Computes the account changes of a block and the resulting state root, without tracking them.
+    pub fn prepare_block(&self, block: &Block) -> Result<StateTrieUpdate, StratusError> {
+        let state = self.state.read().map_lock_error("StateTrie::prepare_block")?;
+        let accounts = state.block_changes(block);
+
+        let mut trie = state.trie.clone();
+        for (address, entry) in &accounts {
+            trie.insert(&account_key(address), entry.encode());
+        }
+        Ok(StateTrieUpdate {
+            accounts,
+            state_root: trie.root_hash().into(),
+        })
+    }
+
+    /// Tracks the account changes of a block prepared with `prepare_block`, after the block is saved.
+    pub fn apply(&self, update: StateTrieUpdate) -> Result<(), StratusError> {
+        let mut state = self.state.write().map_lock_error("StateTrie::apply")?;
+        for (address, entry) in update.accounts {
+            state.save_account(address, entry);
+        }
+        Ok(())
+    }
+
+    /// Replaces all tracked state with the specified accounts and slots.
+    pub fn rebuild(&self, accounts: &[Account], slots: &[(Address, Slot)]) -> Result<(), StratusError> {
+        let mut rebuilt = StateTrieState::default();
+        let mut entries: HashMap<Address, StateTrieAccount> = HashMap::with_capacity(accounts.len());
+        for account in accounts {
+            let entry = entries.entry(account.address).or_default();
+            entry.nonce = account.nonce;
+            entry.balance = account.balance;
+            entry.code_hash = account.code_hash;
+        }
+        for (address, slot) in slots {
+            entries.entry(*address).or_default().save_slot(*slot);
+        }
+        for (address, entry) in entries {
+            rebuilt.save_account(address, entry);
+        }
+
+        *self.state.write().map_lock_error("StateTrie::rebuild")? = rebuilt;
+        Ok(())
+    }
+
+    /// Removes all tracked accounts.
+    pub fn reset(&self) -> Result<(), StratusError> {
+        *self.state.write().map_lock_error("StateTrie::reset")? = StateTrieState::default();
+        Ok(())
+    }
+
+    /// Creates an independent copy of all tracked accounts.
+    pub fn snapshot(&self) -> Result<StateTrie, StratusError> {
+        let state = self.state.read().map_lock_error("StateTrie::snapshot")?;
+        Ok(StateTrie {
+            state: RwLock::new(state.clone()),
+        })
+    }
+
+    /// Replaces all tracked accounts with the accounts of a snapshot.
+    pub fn restore(&self, snapshot: &StateTrie) -> Result<(), StratusError> {
+        let snapshot_state = snapshot.state.read().map_lock_error("StateTrie::restore")?.clone();
+        *self.state.write().map_lock_error("StateTrie::restore")? = snapshot_state;
+        Ok(())
+    }
+
+    // -------------------------------------------------------------------------
+    // Reads
+    // -------------------------------------------------------------------------
+
+    /// Computes the current state root.
+    pub fn state_root(&self) -> Result<Hash, StratusError> {
+        let state = self.state.read().map_lock_error("StateTrie::state_root")?;
+        Ok(state.trie.root_hash().into())
+    }
+
+    /// Computes the state root after applying the account changes of a block, without tracking them.
+    pub fn state_root_after(&self, block: &Block) -> Result<Hash, StratusError> {
+        self.prepare_block(block).map(|update| update.state_root)
+    }
+
+    /// Generates the proof of an account and some of its slots against the current state root.
+    pub fn read_proof(&self, address: &Address, indexes: &[SlotIndex]) -> Result<AccountProof, StratusError> {
+        let state = self.state.read().map_lock_error("StateTrie::read_proof")?;
+
+        let empty = StateTrieAccount::default();
+        let account = state.accounts.get(address).unwrap_or(&empty);
+
+        let storage_proof = indexes
+            .iter()
+            .map(|index| {
+                let key = slot_key(index);
+                SlotProof {
+                    key: *index,
+                    value: account.storage.get(&key).map(decode_slot_value).unwrap_or_default(),
+                    proof: account.storage.prove(&key),
+                }
+            })
+            .collect();
+
+        Ok(AccountProof {
+            address: *address,
+            account_proof: state.trie.prove(&account_key(address)),
+            balance: account.balance,
+            code_hash: account.code_hash,
+            nonce: account.nonce,
+            storage_hash: account.storage.root_hash().into(),
+            storage_proof,
+        })
+    }
+}
+
+impl StateTrieState {
+    /// Tracks the new state of an account, updating its leaf.
+    fn save_account(&mut self, address: Address, entry: StateTrieAccount) {
+        self.trie.insert(&account_key(&address), entry.encode());
+        self.accounts.insert(address, entry);
+    }
+
+    /// Computes the new state of the accounts changed by a block, without tracking it.
+    fn block_changes(&self, block: &Block) -> Vec<(Address, StateTrieAccount)> {
+        block
+            .compact_account_changes()
+            .into_iter()
+            .map(|changes| {
+                let mut entry = self.accounts.get(&changes.address).cloned().unwrap_or_default();
+                if let Some(nonce) = changes.nonce.take_ref() {
+                    entry.nonce = *nonce;
+                }
+                if let Some(balance) = changes.balance.take_ref() {
+                    entry.balance = *balance;
+                }
+                entry.code_hash = changes.code_hash;
+                for slot in changes.slots.values().filter_map(|slot| slot.take_modified_ref()) {
+                    entry.save_slot(*slot);
+                }
+                (changes.address, entry)
+            })
+            .collect()
+    }
+}
+
+impl StateTrieAccount {
+    /// Sets a slot value, removing it from the storage trie when it is zero.
+    fn save_slot(&mut self, slot: Slot) {
+        let key = slot_key(&slot.index);
+        if slot.value.is_zero() {
+            self.storage.remove(&key);
+        } else {
+            self.storage.insert(&key, rlp::encode(&slot.value.as_u256()).to_vec());
+        }
+    }
+
+    /// Encodes the account leaf.
+    fn encode(&self) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(4);
+        stream.append(&U256::from(self.nonce));
+        stream.append(&U256::from(self.balance));
+        stream.append(&self.storage.root_hash());
+        stream.append(&self.code_hash.0);
+        stream.out().to_vec()
+    }
+}
+
+/// Trie key of an account.
+fn account_key(address: &Address) -> Vec<u8> {
+    to_nibbles(&keccak256(<[u8; 20]>::from(*address)))
+}
+
+/// Trie key of a slot.
+fn slot_key(index: &SlotIndex) -> Vec<u8> {
+    to_nibbles(&keccak256(<[u8; 32]>::from(*index)))
+}
+
+fn decode_slot_value(encoded: &[u8]) -> SlotValue {
+    rlp::decode::<U256>(encoded).map(SlotValue::from).unwrap_or_default()
+}
+
+// -----------------------------------------------------------------------------
+// Incremental trie
+// -----------------------------------------------------------------------------
+
+/// Merkle-Patricia trie with prefix-free keys, updated incrementally.
+#[derive(Debug, Clone, Default)]
+struct MerkleTrie {
+    root: Option<Arc<TrieNode>>,
+}
+
+#[derive(Debug)]
+struct TrieNode {
+    kind: TrieNodeKind,
+
+    /// RLP encoding of the node, computed when first requested.
+    encoded: OnceLock<Vec<u8>>,
+}
+
+#[derive(Debug)]
+enum TrieNodeKind {
+    Leaf {
+        path: Vec<u8>,
+        value: Vec<u8>,
+    },
+    Extension {
+        path: Vec<u8>,
+        child: Arc<TrieNode>,
+    },
+
+    /// Keys are prefix-free, so branches never hold values.
+    Branch {
+        children: [Option<Arc<TrieNode>>; 16],
+    },
+}
+
+impl MerkleTrie {
+    /// Inserts or replaces the value of a key.
+    fn insert(&mut self, key: &[u8], value: Vec<u8>) {
+        self.root = Some(insert_node(self.root.as_ref(), key, value));
+    }
+
+    /// Removes a key if present.
+    fn remove(&mut self, key: &[u8]) {
+        if let Some(ref root) = self.root {
+            if let Some(root) = remove_node(root, key) {
+                self.root = root;
+            }
+        }
+    }
+
+    /// Retrieves the value of a key.
+    fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        let mut node = self.root.as_ref()?;
+        let mut path = key;
+        loop {
+            match &node.kind {
+                TrieNodeKind::Leaf { path: leaf_path, value } => return (leaf_path.as_slice() == path).then_some(value.as_slice()),
+                TrieNodeKind::Extension { path: extension_path, child } => {
+                    path = path.strip_prefix(extension_path.as_slice())?;
+                    node = child;
+                }
+                TrieNodeKind::Branch { children } => {
+                    node = children[*path.first()? as usize].as_ref()?;
+                    path = &path[1..];
+                }
+            }
+        }
+    }
+
+    fn root_hash(&self) -> H256 {
+        match self.root {
+            Some(ref root) => H256(keccak256(root.encoded())),
+            None => H256(keccak256(EMPTY_NODE)),
+        }
+    }
+
+    /// Collects the nodes in the path from the root to the given key.
+    ///
+    /// Nodes embedded in their parents (encoded in less than 32 bytes) are not included, except for the root node.
+    fn prove(&self, key: &[u8]) -> Vec<Bytes> {
+        let Some(mut node) = self.root.as_ref() else {
+            return vec![EMPTY_NODE.to_vec().into()];
+        };
+        let mut proof = Vec::new();
+        let mut path = key;
+        loop {
+            let encoded = node.encoded();
+            if proof.is_empty() || encoded.len() >= 32 {
+                proof.push(encoded.to_vec().into());
+            }
+            match &node.kind {
+                TrieNodeKind::Leaf { .. } => return proof,
+                TrieNodeKind::Extension { path: extension_path, child } => {
+                    let Some(remaining) = path.strip_prefix(extension_path.as_slice()) else {
+                        return proof;
+                    };
+                    path = remaining;
+                    node = child;
+                }
+                TrieNodeKind::Branch { children } => {
+                    let Some(child) = path.first().and_then(|nibble| children[*nibble as usize].as_ref()) else {
+                        return proof;
+                    };
+                    path = &path[1..];
+                    node = child;
+                }
+            }
+        }
+    }
+}
+
+impl TrieNode {
+    fn new(kind: TrieNodeKind) -> Arc<Self> {
+        Arc::new(Self {
+            kind,
+            encoded: OnceLock::new(),
+        })
+    }
+
+    fn leaf(path: Vec<u8>, value: Vec<u8>) -> Arc<Self> {
+        Self::new(TrieNodeKind::Leaf { path, value })
+    }
+
+    fn branch(children: [Option<Arc<TrieNode>>; 16]) -> Arc<Self> {
+        Self::new(TrieNodeKind::Branch { children })
+    }
+
+    /// Prepends a path to a node, merging it with the node path when possible.
+    fn join(prefix: &[u8], node: Arc<TrieNode>) -> Arc<Self> {
+        if prefix.is_empty() {
+            return node;
+        }
+        match &node.kind {
+            TrieNodeKind::Leaf { path, value } => Self::leaf([prefix, path.as_slice()].concat(), value.clone()),
+            TrieNodeKind::Extension { path, child } => Self::new(TrieNodeKind::Extension {
+                path: [prefix, path.as_slice()].concat(),
+                child: Arc::clone(child),
+            }),
+            TrieNodeKind::Branch { .. } => Self::new(TrieNodeKind::Extension {
+                path: prefix.to_vec(),
+                child: node,
+            }),
+        }
+    }
+
+    /// RLP encoding of the node, computed only once.
+    fn encoded(&self) -> &[u8] {
+        self.encoded.get_or_init(|| match &self.kind {
+            TrieNodeKind::Leaf { path, value } => {
+                let mut stream = RlpStream::new_list(2);
+                stream.append(&hex_prefix(path, true));
+                stream.append(value);
+                stream.out().to_vec()
+            }
+            TrieNodeKind::Extension { path, child } => {
+                let mut stream = RlpStream::new_list(2);
+                stream.append(&hex_prefix(path, false));
+                append_child(&mut stream, child.encoded());
+                stream.out().to_vec()
+            }
+            TrieNodeKind::Branch { children } => {
+                let mut stream = RlpStream::new_list(17);
+                for child in children {
+                    match child {
+                        Some(child) => append_child(&mut stream, child.encoded()),
+                        None => {
+                            stream.append_empty_data();
+                        }
+                    }
+                }
+                stream.append_empty_data();
+                stream.out().to_vec()
+            }
+        })
+    }
+}
+
+/// Inserts a value into a subtrie, returning the new subtrie. Only nodes in the path of the key are recreated.
+fn insert_node(node: Option<&Arc<TrieNode>>, path: &[u8], value: Vec<u8>) -> Arc<TrieNode> {
+    let Some(node) = node else {
+        return TrieNode::leaf(path.to_vec(), value);
+    };
+
+    match &node.kind {
+        TrieNodeKind::Leaf {
+            path: leaf_path,
+            value: leaf_value,
+        } => {
+            if leaf_path.as_slice() == path {
+                return TrieNode::leaf(path.to_vec(), value);
+            }
+            // keys are prefix-free, so both paths continue after the shared prefix
+            let prefix = common_prefix(leaf_path, path);
+            let mut children: [Option<Arc<TrieNode>>; 16] = Default::default();
+            children[leaf_path[prefix] as usize] = Some(TrieNode::leaf(leaf_path[prefix + 1..].to_vec(), leaf_value.clone()));
+            children[path[prefix] as usize] = Some(TrieNode::leaf(path[prefix + 1..].to_vec(), value));
+            TrieNode::join(&path[..prefix], TrieNode::branch(children))
+        }
+        TrieNodeKind::Extension { path: extension_path, child } => {
+            let prefix = common_prefix(extension_path, path);
+            if prefix == extension_path.len() {
+                return TrieNode::join(extension_path, insert_node(Some(child), &path[prefix..], value));
+            }
+            let mut children: [Option<Arc<TrieNode>>; 16] = Default::default();
+            children[extension_path[prefix] as usize] = Some(TrieNode::join(&extension_path[prefix + 1..], Arc::clone(child)));
+            children[path[prefix] as usize] = Some(TrieNode::leaf(path[prefix + 1..].to_vec(), value));
+            TrieNode::join(&path[..prefix], TrieNode::branch(children))
+        }
+        TrieNodeKind::Branch { children } => {
+            let mut children = children.clone();
+            let nibble = path[0] as usize;
+            children[nibble] = Some(insert_node(children[nibble].as_ref(), &path[1..], value));
+            TrieNode::branch(children)
+        }
+    }
+}
+
+/// Removes a key from a subtrie, returning the new subtrie (`None` if it became empty), or `None` if the key was not found.
+fn remove_node(node: &Arc<TrieNode>, path: &[u8]) -> Option<Option<Arc<TrieNode>>> {
+    match &node.kind {
+        TrieNodeKind::Leaf { path: leaf_path, .. } => (leaf_path.as_slice() == path).then_some(None),
+        TrieNodeKind::Extension { path: extension_path, child } => {
+            let remaining = path.strip_prefix(extension_path.as_slice())?;
+            let child = remove_node(child, remaining)?;
+            Some(child.map(|child| TrieNode::join(extension_path, child)))
+        }
+        TrieNodeKind::Branch { children } => {
+            let nibble = *path.first()? as usize;
+            let child = remove_node(children[nibble].as_ref()?, &path[1..])?;
+            let mut children = children.clone();
+            children[nibble] = child;
+
+            // branches with a single child are collapsed into it
+            let mut remaining = children.iter().enumerate().filter(|(_, child)| child.is_some());
+            match (remaining.next(), remaining.next()) {
+                (None, _) => Some(None),
+                (Some((nibble, Some(child))), None) => Some(Some(TrieNode::join(&[nibble as u8], Arc::clone(child)))),
+                _ => Some(Some(TrieNode::branch(children))),
+            }
+        }
+    }
+}
+
+/// Length of the prefix shared by two paths.
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+// -----------------------------------------------------------------------------
+// Trie encoding
+// -----------------------------------------------------------------------------
+
+/// Computes the root hash of a trie built from sorted leaves with prefix-free keys.
+pub(super) fn trie_root(entries: &[(Vec<u8>, Vec<u8>)]) -> H256 {
+    H256(keccak256(encode_node(entries, 0)))
+}
+
+/// Collects the nodes in the path from the root to the given key.
+///
+/// Nodes embedded in their parents (encoded in less than 32 bytes) are not included, except for the root node.
+pub(super) fn prove(entries: &[(Vec<u8>, Vec<u8>)], key: &[u8]) -> Vec<Bytes> {
+    let mut proof = Vec::new();
+    let mut entries = entries;
+    let mut depth = 0;
+
+    loop {
+        let node = encode_node(entries, depth);
+        if proof.is_empty() || node.len() >= 32 {
+            proof.push(node.into());
+        }
+
+        if entries.len() <= 1 {
+            return proof;
+        }
+
+        let prefix = common_prefix_len(entries, depth);
+        if prefix > 0 {
+            // extension node: continue to its branch if the key shares the extension path
+            if key[depth..depth + prefix] != entries[0].0[depth..depth + prefix] {
+                return proof;
+            }
+            depth += prefix;
+        } else {
+            // branch node: continue to the child the key points to
+            let children = children_at(entries, depth, key[depth]);
+            if children.is_empty() {
+                return proof;
+            }
+            entries = children;
+            depth += 1;
+        }
+    }
+}
+
+/// Encodes the node holding the given leaves, considering only key nibbles starting at `depth`.
+fn encode_node(entries: &[(Vec<u8>, Vec<u8>)], depth: usize) -> Vec<u8> {
+    match entries {
+        [] => EMPTY_NODE.to_vec(),
+
+        // leaf node
+        [(key, value)] => {
+            let mut stream = RlpStream::new_list(2);
+            stream.append(&hex_prefix(&key[depth..], true));
+            stream.append(value);
+            stream.out().to_vec()
+        }
+
+        _ => {
+            let prefix = common_prefix_len(entries, depth);
+
+            // extension node
+            if prefix > 0 {
+                let mut stream = RlpStream::new_list(2);
+                stream.append(&hex_prefix(&entries[0].0[depth..depth + prefix], false));
+                append_child(&mut stream, &encode_node(entries, depth + prefix));
+                return stream.out().to_vec();
+            }
+
+            // branch node (keys are prefix-free, so branches never hold values)
+            let mut stream = RlpStream::new_list(17);
+            for nibble in 0..16 {
+                let children = children_at(entries, depth, nibble);
+                if children.is_empty() {
+                    stream.append_empty_data();
+                } else {
+                    append_child(&mut stream, &encode_node(children, depth + 1));
+                }
+            }
+            stream.append_empty_data();
+            stream.out().to_vec()
+        }
+    }
+}
+
+/// Appends a child node reference: embedded when small, hashed otherwise.
+fn append_child(stream: &mut RlpStream, node: &[u8]) {
+    if node.len() < 32 {
+        stream.append_raw(node, 1);
+    } else {
+        stream.append(&H256(keccak256(node)));
+    }
+}
+
+/// Returns the contiguous range of sorted leaves whose key has `nibble` at `depth`.
+fn children_at(entries: &[(Vec<u8>, Vec<u8>)], depth: usize, nibble: u8) -> &[(Vec<u8>, Vec<u8>)] {
+    let start = entries.partition_point(|(key, _)| key[depth] < nibble);
+    let end = entries.partition_point(|(key, _)| key[depth] <= nibble);
+    &entries[start..end]
+}
+
+/// Length of the key prefix shared by all sorted leaves starting at `depth`.
+fn common_prefix_len(entries: &[(Vec<u8>, Vec<u8>)], depth: usize) -> usize {
+    let (Some((first, _)), Some((last, _))) = (entries.first(), entries.last()) else {
+        return 0;
+    };
+    first[depth..].iter().zip(&last[depth..]).take_while(|(a, b)| a == b).count()
+}
+
+/// Splits bytes into nibbles.
+pub(super) fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]).collect()
+}
+
+/// Hex-prefix encoding of a nibble path.
+fn hex_prefix(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+    let flag = if leaf { 2 } else { 0 };
+    let mut encoded = Vec::with_capacity(nibbles.len() / 2 + 1);
+
+    let remaining = if nibbles.len() % 2 == 1 {
+        encoded.push(((flag + 1) << 4) | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        encoded.push(flag << 4);
+        nibbles
+    };
+    for pair in remaining.chunks(2) {
+        encoded.push((pair[0] << 4) | pair[1]);
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+    use keccak_hasher::KeccakHasher;
+
+    use super::*;
+
+    #[test]
+    fn empty_trie_root() {
+        let trie = StateTrie::default();
+        let expected = Hash::new(hex!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"));
+        assert_eq!(trie.state_root().unwrap(), expected);
+    }
+
+    #[test]
+    fn trie_root_matches_triehash() {
+        let leaves = (0u64..200).map(|i| (keccak256(i.to_be_bytes()).to_vec(), rlp::encode(&U256::from(i + 1)).to_vec()));
+
+        let expected = triehash::trie_root::<KeccakHasher, _, _, _>(leaves.clone());
+
+        let mut entries = leaves.map(|(key, value)| (to_nibbles(&key), value)).collect::<Vec<_>>();
+        entries.sort_unstable();
+        assert_eq!(Hash::from(trie_root(&entries)), Hash::from(expected));
+    }
+
+    #[test]
+    fn incremental_trie_matches_batch_trie() {
+        let leaf = |i: u64| (to_nibbles(&keccak256(i.to_be_bytes())), rlp::encode(&U256::from(i + 1)).to_vec());
+
+        // insert, replace and remove keys in any order
+        let mut trie = MerkleTrie::default();
+        for i in (0u64..300).rev() {
+            let (key, value) = leaf(i);
+            trie.insert(&key, value);
+        }
+        for i in (0u64..300).step_by(3) {
+            trie.remove(&leaf(i).0);
+        }
+        trie.remove(&leaf(1_000).0);
+        let (key, _) = leaf(7);
+        trie.insert(&key, vec![0x01]);
+
+        let mut entries = (0u64..300).filter(|i| i % 3 != 0).map(leaf).collect::<Vec<_>>();
+        entries.iter_mut().find(|(entry_key, _)| *entry_key == key).unwrap().1 = vec![0x01];
+        entries.sort_unstable();
+
+        assert_eq!(trie.root_hash(), trie_root(&entries));
+        assert_eq!(trie.get(&key), Some([0x01].as_slice()));
+        assert_eq!(trie.get(&leaf(3).0), None);
+        for probe in [leaf(1).0, leaf(3).0, leaf(1_000).0] {
+            assert_eq!(trie.prove(&probe), prove(&entries, &probe));
+        }
+
+        // removing all keys empties the trie
+        for i in 0u64..300 {
+            trie.remove(&leaf(i).0);
+        }
+        assert_eq!(trie.root_hash(), trie_root(&[]));
+    }
+
+    #[test]
+    fn state_trie_rebuild_matches_incremental_updates() {
+        let trie = StateTrie::default();
+        let accounts = (1u8..=20)
+            .map(|i| Account::new_with_balance(Address::new([i; 20]), Wei::from(i as u64)))
+            .collect::<Vec<_>>();
+        let slots = (1u8..=20)
+            .map(|i| (Address::new([i % 4 + 1; 20]), Slot::new(SlotIndex::from(i as u64), SlotValue::from(i as u64))))
+            .collect::<Vec<_>>();
+        trie.save_accounts(&accounts).unwrap();
+        for (address, slot) in &slots {
+            trie.save_slot(address, *slot).unwrap();
+        }
+        trie.save_slot(&slots[0].0, Slot::new(slots[0].1.index, SlotValue::default())).unwrap();
+
+        let rebuilt = StateTrie::default();
+        rebuilt.rebuild(&accounts, &slots[1..]).unwrap();
+        assert_eq!(rebuilt.state_root().unwrap(), trie.state_root().unwrap());
+
+        let proof = rebuilt.read_proof(&slots[1].0, &[slots[1].1.index]).unwrap();
+        assert_eq!(proof.storage_proof[0].value, slots[1].1.value);
+    }
+
+    #[test]
+    fn proof_starts_at_root() {
+        let trie = StateTrie::default();
+        let accounts = (1u8..=50)
+            .map(|i| Account::new_with_balance(Address::new([i; 20]), Wei::ONE))
+            .collect::<Vec<_>>();
+        trie.save_accounts(&accounts).unwrap();
+
+        let root = trie.state_root().unwrap();
+        let proof = trie.read_proof(&accounts[0].address, &[]).unwrap();
+        assert_eq!(Hash::new(keccak256(&proof.account_proof[0].0)), root);
+    }
+}
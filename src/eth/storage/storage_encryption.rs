@@ -0,0 +1,193 @@
+//! Encryption at rest of files written by the storage.
+//!
+//! When enabled, state snapshots and the write-ahead log of the temporary storage are encrypted with AES-256-GCM, so deployments with
+//! regulated data do not depend on an encrypted filesystem to protect them. Databases of the permanent storage backends are not encrypted
+//! by Stratus and must rely on the encryption of the database itself or of the volume.
+//!
+//! An encrypted payload has the following layout:
+//!
+//! | Size | Content                                  |
+//! |------|------------------------------------------|
+//! | 8    | Magic bytes `STRATUSE`.                  |
+//! | 12   | Random nonce.                            |
+//! | ..   | Ciphertext followed by the 16-byte tag.  |
+//!
+//! The key is read from an environment variable or from the output of a command, which can be used to fetch it from a KMS.
+
+use std::process::Command;
+
+use aes_gcm::aead::Aead;
+use aes_gcm::aead::KeyInit;
+use aes_gcm::aead::OsRng;
+use aes_gcm::AeadCore;
+use aes_gcm::Aes256Gcm;
+use aes_gcm::Key;
+use aes_gcm::Nonce;
+use anyhow::anyhow;
+use anyhow::Context;
+use clap::Parser;
+use display_json::DebugAsJson;
+
+use crate::ext::not;
+
+/// Magic bytes identifying an encrypted payload.
+pub const ENCRYPTION_MAGIC: &[u8; 8] = b"STRATUSE";
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// Encrypts and decrypts files written by the storage.
+pub struct StorageEncryption {
+    cipher: Aes256Gcm,
+}
+
+impl std::fmt::Debug for StorageEncryption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StorageEncryption").finish_non_exhaustive()
+    }
+}
+
+impl StorageEncryption {
+    /// Creates the encryption with a 256-bit key.
+    pub fn new(key: &[u8]) -> anyhow::Result<Self> {
+        if key.len() != KEY_LEN {
+            return Err(anyhow!("storage encryption key must have {} bytes, but has {}", KEY_LEN, key.len()));
+        }
+        Ok(Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+        })
+    }
+
+    /// Encrypts a payload with a random nonce.
+    pub fn encrypt(&self, plaintext: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| anyhow!("failed to encrypt storage payload"))?;
+
+        let mut encrypted = Vec::with_capacity(ENCRYPTION_MAGIC.len() + NONCE_LEN + ciphertext.len());
+        encrypted.extend_from_slice(ENCRYPTION_MAGIC);
+        encrypted.extend_from_slice(&nonce);
+        encrypted.extend_from_slice(&ciphertext);
+        Ok(encrypted)
+    }
+
+    /// Decrypts a payload created with `encrypt`, failing if it was not encrypted or was encrypted with another key.
+    pub fn decrypt(&self, encrypted: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let Some(encrypted) = encrypted.strip_prefix(ENCRYPTION_MAGIC.as_slice()) else {
+            return Err(anyhow!("storage payload is not encrypted"));
+        };
+        if encrypted.len() < NONCE_LEN {
+            return Err(anyhow!("encrypted storage payload is truncated"));
+        }
+        let (nonce, ciphertext) = encrypted.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("failed to decrypt storage payload: wrong key or corrupted data"))
+    }
+
+    /// Encrypts a line of a text file, keeping the result in a single hex-encoded line.
+    pub fn encrypt_line(&self, line: &str) -> anyhow::Result<String> {
+        let encrypted = self.encrypt(line.trim_end_matches('\n').as_bytes())?;
+        Ok(const_hex::encode(encrypted) + "\n")
+    }
+
+    /// Decrypts a line created with `encrypt_line`.
+    pub fn decrypt_line(&self, line: &str) -> anyhow::Result<String> {
+        let encrypted = const_hex::decode(line.trim()).context("encrypted storage line is not hex-encoded")?;
+        let decrypted = self.decrypt(&encrypted)?;
+        String::from_utf8(decrypted).context("decrypted storage line is not valid utf-8")
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Config
+// -----------------------------------------------------------------------------
+
+#[derive(DebugAsJson, Clone, Parser, serde::Serialize)]
+pub struct StorageEncryptionConfig {
+    /// Hex-encoded 256-bit key used to encrypt state snapshots and the temporary storage write-ahead log.
+    #[arg(
+        long = "storage-encryption-key",
+        env = "STORAGE_ENCRYPTION_KEY",
+        conflicts_with = "storage_encryption_key_command"
+    )]
+    #[serde(skip_serializing)]
+    pub storage_encryption_key: Option<String>,
+
+    /// Command whose output is the hex-encoded 256-bit encryption key, used to fetch the key from a KMS at startup.
+    #[arg(long = "storage-encryption-key-command", env = "STORAGE_ENCRYPTION_KEY_COMMAND")]
+    pub storage_encryption_key_command: Option<String>,
+}
+
+impl StorageEncryptionConfig {
+    /// Creates the encryption if a key source is configured.
+    pub fn init(&self) -> anyhow::Result<Option<StorageEncryption>> {
+        let key = match (&self.storage_encryption_key, &self.storage_encryption_key_command) {
+            (Some(key), _) => key.clone(),
+            (None, Some(command)) => run_key_command(command)?,
+            (None, None) => return Ok(None),
+        };
+
+        tracing::info!("enabling storage encryption at rest");
+        let key = const_hex::decode(key.trim()).context("storage encryption key is not hex-encoded")?;
+        StorageEncryption::new(&key).map(Some)
+    }
+}
+
+fn run_key_command(command: &str) -> anyhow::Result<String> {
+    tracing::info!(%command, "fetching storage encryption key");
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .output()
+        .with_context(|| format!("failed to run storage encryption key command: {}", command))?;
+    if not(output.status.success()) {
+        return Err(anyhow!("storage encryption key command failed with {}", output.status));
+    }
+    String::from_utf8(output.stdout).context("storage encryption key command output is not valid utf-8")
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn storage_encryption_roundtrip() {
+        let encryption = StorageEncryption::new(&[1; KEY_LEN]).unwrap();
+        let encrypted = encryption.encrypt(b"stratus").unwrap();
+        assert!(encrypted.starts_with(ENCRYPTION_MAGIC));
+        assert_eq!(encryption.decrypt(&encrypted).unwrap(), b"stratus");
+
+        // nonces are random, so the same payload is never encrypted twice the same way
+        assert_ne!(encryption.encrypt(b"stratus").unwrap(), encrypted);
+
+        // lines
+        let line = encryption.encrypt_line("{\"a\":1}\n").unwrap();
+        assert!(line.ends_with('\n') && line.matches('\n').count() == 1);
+        assert_eq!(encryption.decrypt_line(&line).unwrap(), "{\"a\":1}");
+
+        // wrong key, plaintext and invalid keys are rejected
+        let other = StorageEncryption::new(&[2; KEY_LEN]).unwrap();
+        assert!(other.decrypt(&encrypted).is_err());
+        assert!(encryption.decrypt(b"stratus").is_err());
+        assert!(StorageEncryption::new(&[1; 16]).is_err());
+    }
+
+    #[test]
+    fn storage_encryption_key_from_command() {
+        let config = StorageEncryptionConfig {
+            storage_encryption_key: None,
+            storage_encryption_key_command: Some(format!("echo {}", const_hex::encode([3; KEY_LEN]))),
+        };
+        let encryption = config.init().unwrap().unwrap();
+
+        let expected = StorageEncryption::new(&[3; KEY_LEN]).unwrap();
+        assert_eq!(expected.decrypt(&encryption.encrypt(b"kms").unwrap()).unwrap(), b"kms");
+    }
+}
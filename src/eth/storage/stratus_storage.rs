@@ -1,33 +1,80 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+#[cfg(feature = "dev")]
+use std::sync::Mutex;
+use std::time::Duration;
 
+use anyhow::Context;
 use clap::Parser;
 use display_json::DebugAsJson;
 use tracing::Span;
 
 use crate::eth::primitives::Account;
+use crate::eth::primitives::AccountStateDiff;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockFilter;
 use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Bytes;
+use crate::eth::primitives::CodeHash;
 use crate::eth::primitives::ExternalBlock;
+use crate::eth::primitives::FeeHistoryBlock;
+use crate::eth::primitives::Gas;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::LogFilter;
 use crate::eth::primitives::LogMined;
+#[cfg(feature = "dev")]
+use crate::eth::primitives::Nonce;
+use crate::eth::primitives::Pagination;
 use crate::eth::primitives::PendingBlock;
 use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
 use crate::eth::primitives::StratusError;
 use crate::eth::primitives::TransactionExecution;
+use crate::eth::primitives::TransactionMined;
 use crate::eth::primitives::TransactionStage;
+use crate::eth::primitives::UnixTime;
+#[cfg(feature = "dev")]
+use crate::eth::primitives::Wei;
+use crate::eth::storage::AccountProof;
+use crate::eth::storage::BlockQuarantine;
+use crate::eth::storage::ChainHeadBus;
+use crate::eth::storage::ChainHeadEvent;
+use crate::eth::storage::ChainTransition;
+use crate::eth::storage::ChainTransitions;
+use crate::eth::storage::FeeHistoryAccumulator;
+use crate::eth::storage::GenesisConfig;
 use crate::eth::storage::PermanentStorage;
 use crate::eth::storage::PermanentStorageConfig;
+use crate::eth::storage::QuarantinedBlocksSummary;
+use crate::eth::storage::ReceiptProof;
+use crate::eth::storage::RetentionTarget;
+use crate::eth::storage::StateImportBatch;
+use crate::eth::storage::StateSnapshot;
+use crate::eth::storage::StateTrie;
+use crate::eth::storage::StorageEncryption;
+use crate::eth::storage::StorageEncryptionConfig;
 use crate::eth::storage::StoragePointInTime;
 use crate::eth::storage::TemporaryStorage;
 use crate::eth::storage::TemporaryStorageConfig;
+use crate::eth::storage::TemporaryStorageStats;
+use crate::eth::storage::TemporaryWal;
+use crate::eth::storage::ENCRYPTION_MAGIC;
 use crate::ext::not;
+use crate::ext::parse_duration;
+#[cfg(feature = "dev")]
+use crate::ext::MutexExt;
 use crate::infra::metrics;
 use crate::infra::metrics::timed;
+use crate::infra::profiling::profile_scope;
 use crate::infra::tracing::SpanExt;
+use crate::log_and_err;
+use crate::GlobalState;
 
 mod label {
     pub(super) const TEMP: &str = "temporary";
@@ -38,8 +85,40 @@ mod label {
 ///
 /// Additionaly it tracks metrics that are independent of the storage implementation.
 pub struct StratusStorage {
-    temp: Box<dyn TemporaryStorage>,
+    /// Temporary storage of the pending block. Absent in archive nodes, which serve only mined data.
+    temp: Option<Box<dyn TemporaryStorage>>,
     perm: Box<dyn PermanentStorage>,
+
+    /// Optional write-ahead log of executions saved in the temporary storage.
+    temp_wal: Option<TemporaryWal>,
+
+    /// Optional encryption of files written by the storage, like state snapshots.
+    encryption: Option<Arc<StorageEncryption>>,
+
+    /// Optional state trie used to compute state roots and account proofs.
+    state_trie: Option<StateTrie>,
+
+    /// Fee data of the most recent mined blocks.
+    fee_history: FeeHistoryAccumulator,
+
+    /// Optional genesis state loaded from a genesis file. When absent, dev-mode uses the hard-coded genesis block and test accounts.
+    genesis: Option<GenesisConfig>,
+
+    /// Incremented every time the mined state changes, so results computed against it can be invalidated.
+    mined_state_version: AtomicU64,
+
+    /// Recent promotions and rollbacks used to validate reads of block ranges.
+    transitions: ChainTransitions,
+
+    /// Changes of the chain head, published by the miner for committed blocks and by the storage for rollbacks.
+    chain_head: ChainHeadBus,
+
+    /// Blocks removed by resets of the permanent storage that can still be restored.
+    quarantine: BlockQuarantine,
+
+    /// Snapshots created with `snapshot` that can be reverted to.
+    #[cfg(feature = "dev")]
+    snapshots: Mutex<StorageSnapshots>,
 }
 
 impl StratusStorage {
@@ -49,22 +128,95 @@ impl StratusStorage {
 
     /// Creates a new storage with the specified temporary and permanent implementations.
     pub fn new(temp: Box<dyn TemporaryStorage>, perm: Box<dyn PermanentStorage>) -> Result<Self, StratusError> {
-        let this = Self { temp, perm };
+        Self::new_with_options(Some(temp), perm, None, None)
+    }
+
+    /// Creates a new storage with the specified temporary and permanent implementations, optionally maintaining a state trie and
+    /// creating the genesis state from a genesis file.
+    ///
+    /// Without a temporary storage, pending data is not available and operations that change it fail. The storage is also considered a
+    /// read-only archive, so the genesis state is not created.
+    pub fn new_with_options(
+        temp: Option<Box<dyn TemporaryStorage>>,
+        perm: Box<dyn PermanentStorage>,
+        state_trie: Option<StateTrie>,
+        genesis: Option<GenesisConfig>,
+    ) -> Result<Self, StratusError> {
+        let this = Self {
+            temp,
+            perm,
+            temp_wal: None,
+            encryption: None,
+            state_trie,
+            fee_history: FeeHistoryAccumulator::default(),
+            genesis,
+            mined_state_version: AtomicU64::new(0),
+            transitions: ChainTransitions::default(),
+            chain_head: ChainHeadBus::default(),
+            quarantine: BlockQuarantine::default(),
+            #[cfg(feature = "dev")]
+            snapshots: Mutex::default(),
+        };
 
         // create genesis block and accounts if necessary
-        #[cfg(feature = "dev")]
-        {
-            let genesis = this.read_block(&crate::eth::primitives::BlockFilter::Number(crate::eth::primitives::BlockNumber::ZERO))?;
+        let read_only = this.temp.is_none();
+        if not(read_only) && (cfg!(feature = "dev") || this.genesis.is_some()) {
+            let genesis = this.read_block(&BlockFilter::Number(BlockNumber::ZERO))?;
             if genesis.is_none() {
+                #[cfg(feature = "dev")]
                 this.reset_to_genesis()?;
+                #[cfg(not(feature = "dev"))]
+                this.save_genesis()?;
             }
         }
 
         this.set_pending_block_number_as_next_if_not_set()?;
 
+        // the state trie is not persisted, so it is rebuilt from the existing state
+        this.rebuild_state_trie()?;
+
         Ok(this)
     }
 
+    /// Enables encryption of files written by the storage. Must be called before the storage is used by other components.
+    pub fn enable_encryption(&mut self, encryption: Arc<StorageEncryption>) {
+        self.encryption = Some(encryption);
+    }
+
+    /// Enables the quarantine of blocks removed by resets. Must be called before the storage is used by other components.
+    pub fn enable_reset_quarantine(&mut self, retention: Duration, max_blocks: usize) {
+        self.quarantine = BlockQuarantine::new(retention, max_blocks);
+    }
+
+    /// Enables the write-ahead log of the temporary storage, replaying the executions of blocks not committed yet.
+    ///
+    /// Must be called before the storage is used by other components. Returns the number of replayed executions.
+    pub fn enable_temp_wal(&mut self, wal: TemporaryWal) -> Result<usize, StratusError> {
+        let mined_number = self.read_mined_block_number()?;
+        let entries = wal.read()?;
+        tracing::info!(%mined_number, entries = %entries.len(), "replaying temporary storage wal");
+
+        // executions of finished blocks that were not committed are replayed into the pending block, so they are mined again
+        let pending_number = self.read_pending_block_number()?.unwrap_or(mined_number.next_block_number());
+        let mut replayed = Vec::new();
+        for entry in entries.into_iter().filter(|entry| entry.block_number > mined_number) {
+            let tx_hash = entry.tx.hash();
+            let line = TemporaryWal::encode(pending_number, &entry.tx, entry.check_conflicts);
+            match self.temp()?.save_execution(entry.tx, entry.check_conflicts) {
+                Ok(()) => replayed.push(line),
+                Err(e) => tracing::warn!(reason = ?e, %tx_hash, "failed to replay execution from temporary storage wal"),
+            }
+        }
+
+        // entries are rewritten with the block they were replayed to, so they are not replayed again after the block is committed
+        wal.rewrite(&replayed)?;
+        self.temp_wal = Some(wal);
+        self.export_temp_metrics();
+
+        tracing::info!(replayed = %replayed.len(), "replayed temporary storage wal");
+        Ok(replayed.len())
+    }
+
     // -------------------------------------------------------------------------
     // Block number
     // -------------------------------------------------------------------------
@@ -107,7 +259,11 @@ impl StratusStorage {
         let _span = tracing::info_span!("storage::read_pending_block_number").entered();
         tracing::debug!(storage = %label::TEMP, "reading pending block number");
 
-        timed(|| self.temp.read_pending_block_number())
+        // archive nodes do not have a pending block, so the block after the last mined one is considered pending
+        let Some(ref temp) = self.temp else {
+            return Ok(Some(self.read_mined_block_number()?.next_block_number()));
+        };
+        timed(|| temp.read_pending_block_number())
             .with(|m| {
                 metrics::inc_storage_read_pending_block_number(m.elapsed, label::TEMP, m.result.is_ok());
                 if let Err(ref e) = m.result {
@@ -137,13 +293,15 @@ impl StratusStorage {
         let _span = tracing::info_span!("storage::set_pending_block_number", %block_number).entered();
         tracing::debug!(storage = &label::TEMP, %block_number, "setting pending block number");
 
-        timed(|| self.temp.set_pending_block_number(block_number))
+        let temp = self.temp()?;
+        timed(|| temp.set_pending_block_number(block_number))
             .with(|m| {
                 metrics::inc_storage_set_pending_block_number(m.elapsed, label::TEMP, m.result.is_ok());
                 if let Err(ref e) = m.result {
                     tracing::error!(reason = ?e, "failed to set pending block number");
                 }
             })
+            .inspect(|_| self.export_temp_metrics())
             .map_err(Into::into)
     }
 
@@ -151,6 +309,11 @@ impl StratusStorage {
         #[cfg(feature = "tracing")]
         let _span = tracing::info_span!("storage::set_pending_block_number_as_next").entered();
 
+        // archive nodes do not have a pending block
+        if self.temp.is_none() {
+            return Ok(());
+        }
+
         let last_mined_block = self.read_mined_block_number()?;
         self.set_pending_block_number(last_mined_block.next_block_number())?;
         Ok(())
@@ -186,7 +349,8 @@ impl StratusStorage {
     pub fn set_pending_external_block(&self, block: ExternalBlock) -> Result<(), StratusError> {
         tracing::debug!(storage = %label::TEMP, block_number = %block.number(), "setting pending external block");
 
-        timed(|| self.temp.set_pending_external_block(block))
+        let temp = self.temp()?;
+        timed(|| temp.set_pending_external_block(block))
             .with(|m| {
                 metrics::inc_storage_set_pending_external_block(m.elapsed, label::TEMP, m.result.is_ok());
                 if let Err(ref e) = m.result {
@@ -209,6 +373,10 @@ impl StratusStorage {
             }
         }
 
+        if let Some(ref state_trie) = self.state_trie {
+            state_trie.save_accounts(&missing_accounts)?;
+        }
+
         tracing::debug!(storage = %label::PERM, accounts = ?missing_accounts, "saving initial accounts");
         timed(|| self.perm.save_accounts(missing_accounts))
             .with(|m| {
@@ -217,17 +385,19 @@ impl StratusStorage {
                     tracing::error!(reason = ?e, "failed to save accounts");
                 }
             })
+            .inspect(|_| self.increment_mined_state_version())
             .map_err(Into::into)
     }
 
     pub fn read_account(&self, address: &Address, point_in_time: &StoragePointInTime) -> Result<Account, StratusError> {
         #[cfg(feature = "tracing")]
         let _span = tracing::debug_span!("storage::read_account", %address, %point_in_time).entered();
+        let _profile_scope = profile_scope("storage::read_account");
 
         // read from temp only if requested
-        if point_in_time.is_pending() {
+        if let Some(temp) = self.temp_at(point_in_time) {
             tracing::debug!(storage = %label::TEMP, %address, "reading account");
-            let temp_account = timed(|| self.temp.read_account(address)).with(|m| {
+            let temp_account = timed(|| temp.read_account(address)).with(|m| {
                 metrics::inc_storage_read_account(m.elapsed, label::TEMP, point_in_time, m.result.is_ok());
                 if let Err(ref e) = m.result {
                     tracing::error!(reason = ?e, "failed to read account from temporary storage");
@@ -262,11 +432,12 @@ impl StratusStorage {
     pub fn read_slot(&self, address: &Address, index: &SlotIndex, point_in_time: &StoragePointInTime) -> Result<Slot, StratusError> {
         #[cfg(feature = "tracing")]
         let _span = tracing::debug_span!("storage::read_slot", %address, %index, %point_in_time).entered();
+        let _profile_scope = profile_scope("storage::read_slot");
 
         // read from temp only if requested
-        if point_in_time.is_pending() {
+        if let Some(temp) = self.temp_at(point_in_time) {
             tracing::debug!(storage = %label::TEMP, %address, %index, "reading slot");
-            let temp_slot = timed(|| self.temp.read_slot(address, index)).with(|m| {
+            let temp_slot = timed(|| temp.read_slot(address, index)).with(|m| {
                 metrics::inc_storage_read_slot(m.elapsed, label::TEMP, point_in_time, m.result.is_ok());
                 if let Err(ref e) = m.result {
                     tracing::error!(reason = ?e, "failed to read slot from temporary storage");
@@ -298,6 +469,124 @@ impl StratusStorage {
         }
     }
 
+    /// Reads multiple slots, reading the ones not found in the temporary storage from the permanent storage in a single batch.
+    ///
+    /// Like [`StratusStorage::read_slot`], slots not found are returned with default values.
+    pub fn read_slots(&self, slots: &[(Address, SlotIndex)], point_in_time: &StoragePointInTime) -> Result<HashMap<(Address, SlotIndex), Slot>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("storage::read_slots", slots = %slots.len(), %point_in_time).entered();
+        let _profile_scope = profile_scope("storage::read_slots");
+
+        let mut found = HashMap::with_capacity(slots.len());
+
+        // read from temp only if requested
+        let missing = if let Some(temp) = self.temp_at(point_in_time) {
+            tracing::debug!(storage = %label::TEMP, slots = %slots.len(), "reading slots");
+            let temp_slots = timed(|| {
+                slots
+                    .iter()
+                    .map(|(address, index)| temp.read_slot(address, index))
+                    .collect::<anyhow::Result<Vec<_>>>()
+            })
+            .with(|m| {
+                metrics::inc_storage_read_slots(m.elapsed, label::TEMP, point_in_time, m.result.is_ok());
+                if let Err(ref e) = m.result {
+                    tracing::error!(reason = ?e, "failed to read slots from temporary storage");
+                }
+            })?;
+
+            let mut missing = Vec::with_capacity(slots.len());
+            for ((address, index), temp_slot) in slots.iter().zip(temp_slots) {
+                match temp_slot {
+                    Some(slot) => {
+                        found.insert((*address, *index), slot);
+                    }
+                    None => missing.push((*address, *index)),
+                }
+            }
+            missing
+        } else {
+            slots.to_vec()
+        };
+
+        // always read from perm if necessary
+        if not(missing.is_empty()) {
+            tracing::debug!(storage = %label::PERM, slots = %missing.len(), %point_in_time, "reading slots");
+            let perm_slots = timed(|| self.perm.read_slots(&missing, point_in_time)).with(|m| {
+                metrics::inc_storage_read_slots(m.elapsed, label::PERM, point_in_time, m.result.is_ok());
+                if let Err(ref e) = m.result {
+                    tracing::error!(reason = ?e, "failed to read slots from permanent storage");
+                }
+            })?;
+            for (address, slot) in perm_slots {
+                found.insert((address, slot.index), slot);
+            }
+        }
+
+        // slots not found in any storage have default values
+        for (address, index) in missing {
+            found.entry((address, index)).or_insert_with(|| Slot::new_empty(index));
+        }
+        Ok(found)
+    }
+
+    #[cfg(feature = "dev")]
+    /// Overrides the balance of an account.
+    pub fn set_balance(&self, address: &Address, balance: Wei) -> Result<(), StratusError> {
+        tracing::debug!(storage = %label::PERM, %address, %balance, "setting balance");
+        self.override_account(address, |perm| perm.set_balance(address, balance))
+    }
+
+    #[cfg(feature = "dev")]
+    /// Overrides the nonce of an account.
+    pub fn set_nonce(&self, address: &Address, nonce: Nonce) -> Result<(), StratusError> {
+        tracing::debug!(storage = %label::PERM, %address, %nonce, "setting nonce");
+        self.override_account(address, |perm| perm.set_nonce(address, nonce))
+    }
+
+    #[cfg(feature = "dev")]
+    /// Overrides the bytecode of an account.
+    pub fn set_code(&self, address: &Address, code: Bytes) -> Result<(), StratusError> {
+        tracing::debug!(storage = %label::PERM, %address, code_len = %code.len(), "setting code");
+        self.override_account(address, |perm| perm.set_code(address, code))
+    }
+
+    #[cfg(feature = "dev")]
+    /// Overrides a slot of an account.
+    pub fn set_storage(&self, address: &Address, slot: Slot) -> Result<(), StratusError> {
+        tracing::debug!(storage = %label::PERM, %address, index = %slot.index, value = %slot.value, "setting storage");
+        self.override_account(address, |perm| perm.set_storage(address, slot))?;
+        if let Some(ref state_trie) = self.state_trie {
+            state_trie.save_slot(address, slot)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "dev")]
+    /// Applies a state override to the permanent storage, keeping the state trie in sync.
+    fn override_account<F>(&self, address: &Address, apply: F) -> Result<(), StratusError>
+    where
+        F: FnOnce(&dyn PermanentStorage) -> anyhow::Result<()>,
+    {
+        // pending transactions were executed against the previous state
+        let pending_txs = self.pending_transactions().len();
+        if pending_txs > 0 {
+            tracing::error!(%pending_txs, "cannot override state with pending transactions");
+            return Err(StratusError::PendingTransactionsExist { pending_txs });
+        }
+
+        apply(self.perm.as_ref()).inspect_err(|e| {
+            tracing::error!(reason = ?e, %address, "failed to override account in permanent storage");
+        })?;
+        self.increment_mined_state_version();
+
+        if let Some(ref state_trie) = self.state_trie {
+            let account = self.read_account(address, &StoragePointInTime::Mined)?;
+            state_trie.save_accounts(&[account])?;
+        }
+        Ok(())
+    }
+
     // -------------------------------------------------------------------------
     // Blocks
     // -------------------------------------------------------------------------
@@ -305,42 +594,122 @@ impl StratusStorage {
     pub fn save_execution(&self, tx: TransactionExecution, check_conflicts: bool) -> Result<(), StratusError> {
         #[cfg(feature = "tracing")]
         let _span = tracing::info_span!("storage::save_execution", tx_hash = %tx.hash()).entered();
+        let _profile_scope = profile_scope("storage::save_execution");
         tracing::debug!(storage = %label::TEMP, tx_hash = %tx.hash(), "saving execution");
+        let temp = self.temp()?;
 
-        timed(|| self.temp.save_execution(tx, check_conflicts))
+        // serialize before the execution is moved to the temporary storage
+        let wal_line = match self.temp_wal {
+            Some(_) => {
+                let pending_number = self.read_pending_block_number()?.unwrap_or_default();
+                Some(TemporaryWal::encode(pending_number, &tx, check_conflicts))
+            }
+            None => None,
+        };
+
+        timed(|| temp.save_execution(tx, check_conflicts))
             .with(|m| {
                 metrics::inc_storage_save_execution(m.elapsed, label::TEMP, m.result.is_ok());
                 if let Err(ref e) = m.result {
                     tracing::error!(reason = ?e, "failed to save execution");
                 }
             })
-            .map_err(Into::into)
+            .inspect(|_| self.export_temp_metrics())?;
+
+        // the execution is already saved, so failing to log it only reduces durability
+        if let (Some(wal), Some(line)) = (&self.temp_wal, wal_line) {
+            if let Err(e) = wal.append(&line) {
+                tracing::error!(reason = ?e, "failed to append execution to temporary storage wal");
+            }
+        }
+
+        Ok(())
     }
 
     /// Retrieves pending transactions being mined.
     pub fn pending_transactions(&self) -> Vec<TransactionExecution> {
-        self.temp.pending_transactions()
+        match self.temp {
+            Some(ref temp) => temp.pending_transactions(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Retrieves the pending block being mined.
+    pub fn read_pending_block(&self) -> Result<Option<PendingBlock>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_pending_block").entered();
+        tracing::debug!(storage = %label::TEMP, "reading pending block");
+
+        let Some(ref temp) = self.temp else { return Ok(None) };
+        temp.read_pending_block().map_err(Into::into)
     }
 
-    pub fn finish_pending_block(&self) -> Result<PendingBlock, StratusError> {
+    /// Retrieves the amount of data held by the temporary storage.
+    pub fn read_temp_stats(&self) -> TemporaryStorageStats {
+        match self.temp {
+            Some(ref temp) => temp.stats(),
+            None => TemporaryStorageStats::default(),
+        }
+    }
+
+    /// Finishes the pending block and starts a new block.
+    ///
+    /// Transactions exceeding the gas limit are moved to the new block in execution order. The ones that conflict with the state of
+    /// the finished block are returned, so they can be executed again.
+    pub fn finish_pending_block(&self, gas_limit: Gas) -> Result<(PendingBlock, Vec<TransactionExecution>), StratusError> {
         #[cfg(feature = "tracing")]
         let _span = tracing::info_span!("storage::finish_pending_block", block_number = tracing::field::Empty).entered();
         tracing::debug!(storage = %label::TEMP, "finishing pending block");
 
-        let result = timed(|| self.temp.finish_pending_block())
-            .with(|m| {
-                metrics::inc_storage_finish_pending_block(m.elapsed, label::TEMP, m.result.is_ok());
-                if let Err(ref e) = m.result {
-                    tracing::error!(reason = ?e, "failed to finish pending block");
-                }
-            })
-            .map_err(Into::into);
+        let temp = self.temp()?;
+        let (block, remaining_txs) = timed(|| temp.finish_pending_block(gas_limit)).with(|m| {
+            metrics::inc_storage_finish_pending_block(m.elapsed, label::TEMP, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to finish pending block");
+            }
+        })?;
+        Span::with(|s| s.rec_str("block_number", &block.header.number));
+
+        // moved transactions are saved like new executions, so they are also logged to the wal with the new block number
+        let mut conflicting_txs = Vec::new();
+        for tx in remaining_txs {
+            if let Err(e) = self.save_execution(tx.clone(), true) {
+                tracing::warn!(reason = ?e, tx_hash = %tx.hash(), "failed to move transaction to next block, it will be executed again");
+                conflicting_txs.push(tx);
+            }
+        }
+        self.export_temp_metrics();
 
-        if let Ok(ref block) = result {
-            Span::with(|s| s.rec_str("block_number", &block.header.number));
+        Ok((block, conflicting_txs))
+    }
+
+    /// Saves a block mined by the leader and received through replication, without re-executing its transactions.
+    ///
+    /// The block must be the child of the last mined block. The pending block number is moved after it.
+    pub fn apply_replicated_block(&self, block: Block) -> Result<(), StratusError> {
+        let block_number = block.number();
+
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::apply_replicated_block", %block_number).entered();
+        tracing::debug!(%block_number, transactions_len = %block.transactions.len(), "applying replicated block");
+
+        // check parent
+        let mined_number = self.read_mined_block_number()?;
+        if let Some(mined_block) = self.read_block(&BlockFilter::Number(mined_number))? {
+            if not(block_number.is_zero()) && block.header.parent_hash != mined_block.hash() {
+                tracing::error!(%block_number, parent_hash = %block.header.parent_hash, mined_hash = %mined_block.hash(), "failed to apply replicated block because parent does not match the last mined block");
+                return Err(StratusError::StorageReplicatedBlockInvalid {
+                    number: block_number,
+                    parent_hash: block.header.parent_hash,
+                    mined_hash: mined_block.hash(),
+                });
+            }
         }
 
-        result
+        // save block
+        self.set_pending_block_number(block_number.next_block_number())?;
+        self.save_block(block)?;
+        self.set_mined_block_number(block_number)
     }
 
     pub fn save_block(&self, block: Block) -> Result<(), StratusError> {
@@ -378,16 +747,70 @@ impl StratusStorage {
             return Err(StratusError::StorageBlockConflict { number: block_number });
         }
 
+        // verify state root
+        // local blocks are sealed with the root computed by the trie, while external blocks keep the root of the external chain
+        let state_trie_update = match self.state_trie {
+            Some(ref state_trie) => {
+                let update = state_trie.prepare_block(&block)?;
+                if update.state_root != block.header.state_root {
+                    tracing::warn!(%block_number, header_state_root = %block.header.state_root, computed_state_root = %update.state_root, "block state root does not match the state trie");
+                }
+                Some(update)
+            }
+            None => None,
+        };
+
         // save block
         let (label_size_by_tx, label_size_by_gas) = (block.label_size_by_transactions(), block.label_size_by_gas());
-        timed(|| self.perm.save_block(block))
-            .with(|m| {
-                metrics::inc_storage_save_block(m.elapsed, label::PERM, label_size_by_tx, label_size_by_gas, m.result.is_ok());
-                if let Err(ref e) = m.result {
-                    tracing::error!(reason = ?e, %block_number, "failed to save block");
-                }
-            })
-            .map_err(Into::into)
+        let fee_history_block = FeeHistoryBlock::from_block(&block);
+        timed(|| self.perm.save_block(block)).with(|m| {
+            metrics::inc_storage_save_block(m.elapsed, label::PERM, label_size_by_tx, label_size_by_gas, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, %block_number, "failed to save block");
+            }
+        })?;
+
+        // track state only after the block is saved, so the trie is not ahead of the permanent storage
+        if let (Some(state_trie), Some(update)) = (&self.state_trie, state_trie_update) {
+            state_trie.apply(update)?;
+        }
+
+        // track fees
+        self.fee_history.push(fee_history_block);
+        self.increment_mined_state_version();
+
+        // discard executions of committed blocks from the write-ahead log
+        if let Some(ref wal) = self.temp_wal {
+            if let Err(e) = wal.truncate_if_due(block_number) {
+                tracing::error!(reason = ?e, %block_number, "failed to truncate temporary storage wal");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Retrieves the fee data of `count` blocks ending at `newest` (inclusive), sorted from oldest to newest.
+    ///
+    /// Recent blocks are served from memory and older ones are read from the permanent storage.
+    pub fn read_fee_history(&self, newest: BlockNumber, count: u64) -> Result<Vec<FeeHistoryBlock>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_fee_history", %newest, %count).entered();
+
+        let oldest = newest.as_u64().saturating_sub(count.saturating_sub(1));
+        let mut fee_history = Vec::with_capacity(count as usize);
+        for number in oldest..=newest.as_u64() {
+            let number = BlockNumber::from(number);
+            if let Some(fee_history_block) = self.fee_history.get(number) {
+                fee_history.push(fee_history_block);
+                continue;
+            }
+            let filter = BlockFilter::Number(number);
+            let Some(block) = self.read_block(&filter)? else {
+                return Err(StratusError::RpcBlockFilterInvalid { filter });
+            };
+            fee_history.push(FeeHistoryBlock::from_block(&block));
+        }
+        Ok(fee_history)
     }
 
     pub fn read_block(&self, filter: &BlockFilter) -> Result<Option<Block>, StratusError> {
@@ -405,20 +828,72 @@ impl StratusStorage {
             .map_err(Into::into)
     }
 
+    /// Retrieves all mined transactions of a block with their receipt data, sorted by transaction index.
+    pub fn read_block_receipts(&self, filter: &BlockFilter) -> Result<Option<Vec<TransactionMined>>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_block_receipts", %filter).entered();
+        tracing::debug!(storage = %label::PERM, ?filter, "reading block receipts");
+
+        let receipts = timed(|| self.perm.read_block_receipts(filter)).with(|m| {
+            metrics::inc_storage_read_block_receipts(m.elapsed, label::PERM, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to read block receipts");
+            }
+        })?;
+        Ok(receipts.map(|mut receipts| {
+            receipts.sort_by_key(|tx| tx.transaction_index);
+            receipts
+        }))
+    }
+
+    /// Retrieves the accounts and slots modified by a mined block with their values at the end of it.
+    pub fn read_block_state_diff(&self, number: BlockNumber) -> Result<Option<Vec<AccountStateDiff>>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_block_state_diff", %number).entered();
+        tracing::debug!(storage = %label::PERM, %number, "reading block state diff");
+
+        timed(|| self.perm.read_block_state_diff(number))
+            .with(|m| {
+                metrics::inc_storage_read_block_state_diff(m.elapsed, label::PERM, m.result.is_ok());
+                if let Err(ref e) = m.result {
+                    tracing::error!(reason = ?e, "failed to read block state diff");
+                }
+            })
+            .map_err(Into::into)
+    }
+
+    /// Retrieves a mined bytecode by its hash.
+    pub fn read_code(&self, code_hash: &CodeHash) -> Result<Option<Bytes>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_code", ?code_hash).entered();
+        tracing::debug!(storage = %label::PERM, ?code_hash, "reading code");
+
+        timed(|| self.perm.read_code(code_hash))
+            .with(|m| {
+                metrics::inc_storage_read_code(m.elapsed, label::PERM, m.result.is_ok());
+                if let Err(ref e) = m.result {
+                    tracing::error!(reason = ?e, "failed to read code");
+                }
+            })
+            .map_err(Into::into)
+    }
+
     pub fn read_transaction(&self, tx_hash: &Hash) -> Result<Option<TransactionStage>, StratusError> {
         #[cfg(feature = "tracing")]
         let _span = tracing::info_span!("storage::read_transaction", %tx_hash).entered();
 
         // read from temp
-        tracing::debug!(storage = %label::TEMP, %tx_hash, "reading transaction");
-        let temp_tx = timed(|| self.temp.read_transaction(tx_hash)).with(|m| {
-            metrics::inc_storage_read_transaction(m.elapsed, label::TEMP, m.result.is_ok());
-            if let Err(ref e) = m.result {
-                tracing::error!(reason = ?e, "failed to read transaction from temporary storage");
+        if let Some(ref temp) = self.temp {
+            tracing::debug!(storage = %label::TEMP, %tx_hash, "reading transaction");
+            let temp_tx = timed(|| temp.read_transaction(tx_hash)).with(|m| {
+                metrics::inc_storage_read_transaction(m.elapsed, label::TEMP, m.result.is_ok());
+                if let Err(ref e) = m.result {
+                    tracing::error!(reason = ?e, "failed to read transaction from temporary storage");
+                }
+            })?;
+            if let Some(tx_temp) = temp_tx {
+                return Ok(Some(TransactionStage::new_executed(tx_temp)));
             }
-        })?;
-        if let Some(tx_temp) = temp_tx {
-            return Ok(Some(TransactionStage::new_executed(tx_temp)));
         }
 
         // read from perm
@@ -440,33 +915,366 @@ impl StratusStorage {
         let _span = tracing::info_span!("storage::read_logs", ?filter).entered();
         tracing::debug!(storage = %label::PERM, ?filter, "reading logs");
 
-        timed(|| self.perm.read_logs(filter))
+        // validate before and after reading, so a rollback in the middle of the read is also detected
+        let rollback_version = self.rollback_version();
+        let to = match filter.to_block {
+            Some(to) => to,
+            None => self.read_mined_block_number()?,
+        };
+        self.check_range_consistency(filter.from_block, to)?;
+
+        let logs = timed(|| self.perm.read_logs(filter)).with(|m| {
+            metrics::inc_storage_read_logs(m.elapsed, label::PERM, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to read logs");
+            }
+        })?;
+
+        self.check_rollback_since(rollback_version, filter.from_block, to)?;
+        Ok(logs)
+    }
+
+    pub fn read_transactions_by_address(
+        &self,
+        address: &Address,
+        from: BlockNumber,
+        to: BlockNumber,
+        pagination: Pagination,
+    ) -> Result<Vec<TransactionMined>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_transactions_by_address", %address, %from, %to).entered();
+        tracing::debug!(storage = %label::PERM, %address, %from, %to, ?pagination, "reading transactions by address");
+
+        // validate before and after reading, so a rollback in the middle of the read is also detected
+        let rollback_version = self.rollback_version();
+        self.check_range_consistency(from, to)?;
+
+        let hashes = timed(|| self.perm.read_transactions_by_address(address, from, to, pagination)).with(|m| {
+            metrics::inc_storage_read_transactions_by_address(m.elapsed, label::PERM, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to read transactions by address");
+            }
+        })?;
+
+        // transactions can be missing when receipts are pruned between reading the index and reading the transactions
+        let mut txs = Vec::with_capacity(hashes.len());
+        for hash in hashes {
+            if let Some(tx) = self.perm.read_transaction(&hash)? {
+                txs.push(tx);
+            }
+        }
+
+        self.check_rollback_since(rollback_version, from, to)?;
+        Ok(txs)
+    }
+
+    /// Checks the block range (inclusive) does not straddle an unresolved rollback and that blocks produced before and after a promotion
+    /// are linked.
+    pub fn check_range_consistency(&self, from: BlockNumber, to: BlockNumber) -> Result<(), StratusError> {
+        let mined = self.read_mined_block_number()?;
+        self.transitions.check_range(from, to, mined)?;
+
+        for number in self.transitions.promotions_in_range(from, to) {
+            let Some(block) = self.read_block(&BlockFilter::Number(number))? else {
+                continue;
+            };
+            let Some(next) = self.read_block(&BlockFilter::Number(number.next_block_number()))? else {
+                continue;
+            };
+            if next.header.parent_hash != block.hash() {
+                tracing::error!(%number, "blocks produced before and after promotion are not linked");
+                return Err(StratusError::StorageRangeInconsistent { from, to, boundary: number });
+            }
+        }
+        Ok(())
+    }
+
+    /// Version incremented on every rollback of the permanent storage.
+    pub fn rollback_version(&self) -> u64 {
+        self.transitions.rollback_version()
+    }
+
+    /// Fails if the permanent storage was rolled back since the specified version was read.
+    pub fn check_rollback_since(&self, version: u64, from: BlockNumber, to: BlockNumber) -> Result<(), StratusError> {
+        self.transitions.check_rollback_since(version, from, to)
+    }
+
+    /// Records the promotion of this node to leader after the last mined block.
+    pub fn record_promotion(&self) -> Result<(), StratusError> {
+        let mined = self.read_mined_block_number()?;
+        self.transitions.record_promotion(mined);
+        Ok(())
+    }
+
+    /// Lists recent promotions and rollbacks.
+    pub fn chain_transitions(&self) -> Vec<ChainTransition> {
+        self.transitions.list()
+    }
+
+    /// Bus where changes of the chain head are published.
+    pub fn chain_head(&self) -> &ChainHeadBus {
+        &self.chain_head
+    }
+
+    /// Invalidates results computed against the removed blocks and notifies chain head subscribers.
+    fn rolled_back(&self, number: BlockNumber, previous_mined: BlockNumber) {
+        self.increment_mined_state_version();
+        if number < previous_mined {
+            self.chain_head.publish(ChainHeadEvent::RolledBack { number, previous_mined });
+        }
+    }
+
+    /// Removes the data covered by the retention target from blocks in the range (inclusive), keeping the block headers.
+    pub fn prune_blocks(&self, target: RetentionTarget, from: BlockNumber, to: BlockNumber) -> Result<(), StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::prune_blocks", %target, %from, %to).entered();
+        tracing::debug!(storage = %label::PERM, %target, %from, %to, "pruning blocks");
+
+        timed(|| self.perm.prune_blocks(target, from, to))
             .with(|m| {
-                metrics::inc_storage_read_logs(m.elapsed, label::PERM, m.result.is_ok());
+                metrics::inc_storage_prune_blocks(m.elapsed, label::PERM, <&'static str>::from(target), m.result.is_ok());
                 if let Err(ref e) = m.result {
-                    tracing::error!(reason = ?e, "failed to read logs");
+                    tracing::error!(reason = ?e, %target, "failed to prune blocks");
                 }
             })
             .map_err(Into::into)
     }
 
+    // -------------------------------------------------------------------------
+    // Publisher outbox
+    // -------------------------------------------------------------------------
+
+    /// Retrieves the last block whose events were delivered by the publisher.
+    pub fn read_publisher_cursor(&self, publisher: &str) -> Result<Option<BlockNumber>, StratusError> {
+        tracing::debug!(storage = %label::PERM, %publisher, "reading publisher cursor");
+        self.perm.read_publisher_cursor(publisher).map_err(Into::into)
+    }
+
+    /// Persists the last block whose events were delivered by the publisher.
+    pub fn save_publisher_cursor(&self, publisher: &str, number: BlockNumber) -> Result<(), StratusError> {
+        tracing::debug!(storage = %label::PERM, %publisher, %number, "saving publisher cursor");
+        self.perm.save_publisher_cursor(publisher, number).map_err(Into::into)
+    }
+
+    // -------------------------------------------------------------------------
+    // State trie
+    // -------------------------------------------------------------------------
+
+    /// Retrieves the current state root if the state trie is enabled.
+    pub fn read_state_root(&self) -> Result<Option<Hash>, StratusError> {
+        match self.state_trie {
+            Some(ref state_trie) => state_trie.state_root().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Rebuilds the state trie from all accounts and slots at the last mined block if the state trie is enabled.
+    ///
+    /// Warns if the resulting state root does not match the state root of the last mined block.
+    fn rebuild_state_trie(&self) -> Result<(), StratusError> {
+        let Some(ref state_trie) = self.state_trie else {
+            return Ok(());
+        };
+        let accounts = self.perm.read_all_accounts()?;
+        let slots = self.perm.read_all_slots()?;
+        tracing::info!(accounts = %accounts.len(), slots = %slots.len(), "rebuilding state trie");
+        state_trie.rebuild(&accounts, &slots)?;
+
+        let state_root = state_trie.state_root()?;
+        if let Some(block) = self.read_block(&BlockFilter::Latest)? {
+            if block.header.state_root != state_root {
+                tracing::warn!(block_number = %block.number(), header_state_root = %block.header.state_root, computed_state_root = %state_root, "rebuilt state trie does not match the state root of the last mined block");
+            }
+        }
+        Ok(())
+    }
+
+    /// Retrieves the state root the block will have when saved if the state trie is enabled, so it can be part of the block hash.
+    pub fn read_state_root_after(&self, block: &Block) -> Result<Option<Hash>, StratusError> {
+        match self.state_trie {
+            Some(ref state_trie) => state_trie.state_root_after(block).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Generates an account proof (EIP-1186) for the account and slots at the specified block.
+    ///
+    /// The state trie only tracks the latest mined state, so proofs for other blocks are rejected.
+    pub fn read_account_proof(&self, address: &Address, indexes: &[SlotIndex], filter: &BlockFilter) -> Result<AccountProof, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_account_proof", %address, %filter).entered();
+
+        let Some(ref state_trie) = self.state_trie else {
+            return Err(StratusError::RpcStateTrieDisabled);
+        };
+
+        let is_latest = match self.translate_to_point_in_time(filter)? {
+            StoragePointInTime::Mined => true,
+            StoragePointInTime::MinedPast(number) => number == self.read_mined_block_number()?,
+            StoragePointInTime::Pending => false,
+        };
+        if not(is_latest) {
+            return Err(StratusError::RpcProofBlockUnsupported { filter: *filter });
+        }
+
+        state_trie.read_proof(address, indexes)
+    }
+
+    /// Generates the inclusion proof of a transaction receipt against the receipts root of the block it was mined in.
+    ///
+    /// Returns `None` if the transaction is not found or was not mined yet.
+    pub fn read_receipt_proof(&self, tx_hash: &Hash) -> Result<Option<ReceiptProof>, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::read_receipt_proof", %tx_hash).entered();
+
+        let Some(TransactionStage::Mined(tx)) = self.read_transaction(tx_hash)? else {
+            return Ok(None);
+        };
+        let Some(block) = self.read_block(&BlockFilter::Number(tx.block_number))? else {
+            return Ok(None);
+        };
+        Ok(ReceiptProof::from_block(&block, tx_hash))
+    }
+
     // -------------------------------------------------------------------------
     // General state
     // -------------------------------------------------------------------------
 
-    #[cfg(feature = "dev")]
-    /// Resets the storage to the genesis state used in dev-mode.
+    /// Resets the permanent storage to a specific block number, removing blocks and changes after it.
     ///
-    /// TODO: For now it uses the dev genesis block and test accounts, but it should be refactored to support genesis.json files.
-    pub fn reset_to_genesis(&self) -> Result<(), StratusError> {
-        use crate::eth::primitives::test_accounts;
+    /// Pending transactions are discarded because they were executed on top of the removed blocks.
+    pub fn reset_perm(&self, number: BlockNumber) -> Result<(), StratusError> {
+        tracing::info!(%number, "reseting permanent storage to block");
+
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::reset_perm", %number).entered();
+
+        // read blocks to be removed before they are destroyed
+        let previous_mined = self.read_mined_block_number()?;
+        let removed_blocks = self.read_blocks_to_quarantine(number, previous_mined)?;
+
+        // reset perm
+        tracing::debug!(storage = %label::PERM, %number, "reseting permanent storage");
+        timed(|| self.perm.reset_at(number)).with(|m| {
+            metrics::inc_storage_reset(m.elapsed, label::PERM, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to reset permanent storage");
+            }
+        })?;
+
+        // the state trie only keeps the latest state, so it is rebuilt from the state at the reset block
+        self.rebuild_state_trie()?;
+
+        if number < previous_mined {
+            self.transitions.record_rollback(number, previous_mined);
+        }
+        self.rolled_back(number, previous_mined);
+        if let Some(removed_blocks) = removed_blocks {
+            self.quarantine.quarantine(number, removed_blocks, UnixTime::now());
+        }
+
+        // reset fee history
+        self.fee_history.clear();
+
+        // discard pending transactions
+        self.reset_temp()?;
+        self.set_pending_block_number_as_next()?;
+
+        Ok(())
+    }
+
+    /// Reads the blocks after `number` up to `previous_mined` if they must be quarantined before a reset.
+    fn read_blocks_to_quarantine(&self, number: BlockNumber, previous_mined: BlockNumber) -> Result<Option<Vec<Block>>, StratusError> {
+        if not(self.quarantine.is_enabled()) || number >= previous_mined {
+            return Ok(None);
+        }
+        let count = (previous_mined.as_u64() - number.as_u64()) as usize;
+        if count > self.quarantine.max_blocks() {
+            tracing::warn!(%number, %previous_mined, max_blocks = %self.quarantine.max_blocks(), "not quarantining blocks removed by reset because they are too many");
+            return Ok(None);
+        }
 
+        let mut blocks = Vec::with_capacity(count);
+        let mut block_number = number.next_block_number();
+        while block_number <= previous_mined {
+            let Some(block) = self.read_block(&BlockFilter::Number(block_number))? else {
+                tracing::warn!(%block_number, "not quarantining blocks removed by reset because a block is missing");
+                return Ok(None);
+            };
+            blocks.push(block);
+            block_number = block_number.next_block_number();
+        }
+        Ok(Some(blocks))
+    }
+
+    /// Lists blocks removed by resets of the permanent storage that can still be restored.
+    pub fn quarantined_blocks(&self) -> Vec<QuarantinedBlocksSummary> {
+        self.quarantine.list(UnixTime::now())
+    }
+
+    /// Restores blocks removed by a reset on top of the block the storage was reset to. Restores the most recent reset if `id` is not
+    /// specified.
+    ///
+    /// Pending transactions are discarded because they were executed on top of the state the blocks are restored over.
+    pub fn restore_quarantined_blocks(&self, id: Option<u64>) -> Result<QuarantinedBlocksSummary, StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::restore_quarantined_blocks", ?id).entered();
+
+        let Some(mut entry) = self.quarantine.take(id, UnixTime::now()) else {
+            return Err(StratusError::StorageQuarantineNotFound);
+        };
+        let summary = self.quarantine.summary(&entry);
+
+        // blocks can only follow the block they were removed from
+        let mined = self.read_mined_block_number()?;
+        if mined != entry.reset_number {
+            tracing::error!(id = %entry.id, reset_number = %entry.reset_number, %mined, "failed to restore quarantined blocks because new blocks were mined after the reset");
+            let e = StratusError::StorageQuarantineConflict {
+                id: entry.id,
+                reset_number: entry.reset_number,
+                mined,
+            };
+            self.quarantine.put_back(entry);
+            return Err(e);
+        }
+        tracing::warn!(id = %entry.id, from = %summary.from_block, to = %summary.to_block, "restoring quarantined blocks");
+        self.reset_temp()?;
+
+        // restore blocks keeping the ones not restored yet if one fails
+        for index in 0..entry.blocks.len() {
+            let block = entry.blocks[index].clone();
+            let (block_number, block_hash) = (block.number(), block.hash());
+            if let Err(e) = self.apply_replicated_block(block) {
+                tracing::error!(reason = ?e, %block_number, "failed to restore quarantined block");
+                entry.blocks.drain(..index);
+                entry.reset_number = BlockNumber::from(entry.reset_number.as_u64() + index as u64);
+                self.quarantine.put_back(entry);
+                return Err(e);
+            }
+            self.chain_head.publish(ChainHeadEvent::Committed {
+                number: block_number,
+                hash: block_hash,
+            });
+        }
+
+        Ok(summary)
+    }
+
+    /// Genesis state loaded from a genesis file, if configured.
+    pub fn genesis(&self) -> Option<&GenesisConfig> {
+        self.genesis.as_ref()
+    }
+
+    #[cfg(feature = "dev")]
+    /// Resets the storage to the genesis state.
+    pub fn reset_to_genesis(&self) -> Result<(), StratusError> {
         tracing::info!("reseting storage to genesis state");
 
         #[cfg(feature = "tracing")]
         let _span = tracing::info_span!("storage::reset").entered();
 
         // reset perm
+        let previous_mined = self.read_mined_block_number()?;
         tracing::debug!(storage = %label::PERM, "reseting permanent storage");
         timed(|| self.perm.reset()).with(|m| {
             metrics::inc_storage_reset(m.elapsed, label::PERM, m.result.is_ok());
@@ -474,21 +1282,61 @@ impl StratusStorage {
                 tracing::error!(reason = ?e, "failed to reset permanent storage");
             }
         })?;
+        self.rolled_back(BlockNumber::ZERO, previous_mined);
+
+        // reset state trie
+        if let Some(ref state_trie) = self.state_trie {
+            state_trie.reset()?;
+        }
+
+        // reset fee history
+        self.fee_history.clear();
 
         // reset temp
-        tracing::debug!(storage = %label::TEMP, "reseting temporary storage");
-        timed(|| self.temp.reset()).with(|m| {
-            metrics::inc_storage_reset(m.elapsed, label::TEMP, m.result.is_ok());
-            if let Err(ref e) = m.result {
-                tracing::error!(reason = ?e, "failed to reset temporary storage");
+        self.reset_temp()?;
+
+        self.save_genesis()
+    }
+
+    /// Saves the genesis block and state into an empty storage.
+    ///
+    /// Uses the genesis file if configured, otherwise the dev-mode genesis block and test accounts.
+    fn save_genesis(&self) -> Result<(), StratusError> {
+        match self.genesis {
+            Some(ref genesis) => {
+                tracing::info!(accounts = %genesis.accounts.len(), slots = %genesis.slots.len(), "saving genesis state from genesis file");
+
+                // genesis state must be saved before the block so the state root includes it
+                self.save_accounts(genesis.accounts.clone())?;
+                if let Some(ref state_trie) = self.state_trie {
+                    for (address, slot) in &genesis.slots {
+                        state_trie.save_slot(address, *slot)?;
+                    }
+                }
+                tracing::debug!(storage = %label::PERM, slots = %genesis.slots.len(), "saving genesis slots");
+                self.perm.save_slots(genesis.slots.clone())?;
+
+                // genesis block (state root is part of its hash)
+                let mut block = genesis.to_block();
+                if let Some(state_root) = self.read_state_root_after(&block)? {
+                    block.header.state_root = state_root;
+                    block.update_hash();
+                }
+                self.save_block(block)?;
             }
-        })?;
+            None => {
+                #[cfg(feature = "dev")]
+                {
+                    use crate::eth::primitives::test_accounts;
 
-        // genesis block
-        self.save_block(Block::genesis())?;
+                    // genesis block
+                    self.save_block(Block::genesis())?;
 
-        // test accounts
-        self.save_accounts(test_accounts())?;
+                    // test accounts
+                    self.save_accounts(test_accounts())?;
+                }
+            }
+        }
 
         // block number
         self.set_mined_block_number(BlockNumber::ZERO)?;
@@ -497,23 +1345,308 @@ impl StratusStorage {
         Ok(())
     }
 
+    /// Exports all accounts, slots and the last mined block number to a snapshot file.
+    ///
+    /// The file is written to a temporary path and renamed, so an interrupted export never leaves a partial snapshot behind.
+    pub fn export_snapshot(&self, path: &Path) -> Result<StateSnapshot, StratusError> {
+        const MAX_ATTEMPTS: usize = 3;
+
+        tracing::info!(path = %path.display(), "exporting state snapshot");
+
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::export_snapshot").entered();
+
+        // read state, retrying if a block is mined while reading so accounts and slots are consistent with the block number
+        let mut attempt = 0;
+        let snapshot = loop {
+            attempt += 1;
+            let block_number = self.read_mined_block_number()?;
+            let accounts = self.perm.read_all_accounts()?;
+            let slots = self.perm.read_all_slots()?;
+            if self.read_mined_block_number()? == block_number {
+                break StateSnapshot { block_number, accounts, slots };
+            }
+            if attempt >= MAX_ATTEMPTS {
+                return log_and_err!("blocks were mined during all attempts to export the state snapshot").map_err(Into::into);
+            }
+            tracing::warn!(%attempt, "block mined while exporting state snapshot, retrying");
+        };
+
+        // write file
+        let mut bytes = snapshot.encode()?;
+        if let Some(ref encryption) = self.encryption {
+            bytes = encryption.encrypt(&bytes)?;
+        }
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, bytes).with_context(|| format!("failed to write state snapshot to {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, path).with_context(|| format!("failed to rename state snapshot to {}", path.display()))?;
+
+        tracing::info!(block_number = %snapshot.block_number, accounts = %snapshot.accounts.len(), slots = %snapshot.slots.len(), "exported state snapshot");
+        Ok(snapshot)
+    }
+
+    /// Imports accounts, slots and the last mined block number from a snapshot file created with `export_snapshot`.
+    ///
+    /// Blocks are not part of the snapshot, so it can only be imported into a storage without mined blocks. An empty block is saved at
+    /// the snapshot block number as the parent of the next mined block, and mining continues from the block after it.
+    pub fn import_snapshot(&self, path: &Path) -> Result<StateSnapshot, StratusError> {
+        tracing::info!(path = %path.display(), "importing state snapshot");
+
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::import_snapshot").entered();
+
+        // only empty storages can be bootstrapped from a snapshot
+        let mined_number = self.read_mined_block_number()?;
+        if mined_number != BlockNumber::ZERO {
+            return Err(StratusError::StorageSnapshotImportNotEmpty { number: mined_number });
+        }
+
+        // read file
+        let mut bytes = fs::read(path).with_context(|| format!("failed to read state snapshot from {}", path.display()))?;
+        if bytes.starts_with(ENCRYPTION_MAGIC) {
+            let Some(ref encryption) = self.encryption else {
+                return log_and_err!("state snapshot is encrypted, but storage encryption is not configured").map_err(Into::into);
+            };
+            bytes = encryption.decrypt(&bytes)?;
+        }
+        let snapshot = StateSnapshot::decode(&bytes)?;
+
+        // save state
+        if let Some(ref state_trie) = self.state_trie {
+            state_trie.save_accounts(&snapshot.accounts)?;
+            for (address, slot) in &snapshot.slots {
+                state_trie.save_slot(address, *slot)?;
+            }
+        }
+        tracing::debug!(storage = %label::PERM, accounts = %snapshot.accounts.len(), slots = %snapshot.slots.len(), "saving snapshot state");
+        self.perm.save_accounts(snapshot.accounts.clone())?;
+        self.perm.save_slots(snapshot.slots.clone())?;
+        self.increment_mined_state_version();
+
+        // discard pending transactions executed on top of the previous state
+        self.fee_history.clear();
+        self.reset_temp()?;
+
+        // block number
+        if self.read_block(&BlockFilter::Number(snapshot.block_number))?.is_none() {
+            self.perm.save_block(Block::new(snapshot.block_number, UnixTime::now()))?;
+        }
+        self.set_mined_block_number(snapshot.block_number)?;
+        self.set_pending_block_number_as_next()?;
+
+        tracing::info!(block_number = %snapshot.block_number, accounts = %snapshot.accounts.len(), slots = %snapshot.slots.len(), "imported state snapshot");
+        Ok(snapshot)
+    }
+
+    /// Saves a batch of accounts and slots read from a state file, overwriting existing values.
+    ///
+    /// Batches can be saved concurrently. Blocks are not part of the state file, so it must be used with a storage without mined blocks and
+    /// followed by setting the mined block number to the block the state belongs to.
+    pub fn import_state_batch(&self, batch: StateImportBatch) -> Result<(), StratusError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::import_state_batch", accounts = %batch.accounts.len(), slots = %batch.slots.len()).entered();
+
+        // save state
+        if let Some(ref state_trie) = self.state_trie {
+            state_trie.save_accounts(&batch.accounts)?;
+            for (address, slot) in &batch.slots {
+                state_trie.save_slot(address, *slot)?;
+            }
+        }
+        tracing::debug!(storage = %label::PERM, accounts = %batch.accounts.len(), slots = %batch.slots.len(), "saving imported state");
+        timed(|| {
+            self.perm.save_accounts(batch.accounts)?;
+            self.perm.save_slots(batch.slots)
+        })
+        .with(|m| {
+            metrics::inc_storage_save_accounts(m.elapsed, label::PERM, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to save imported state");
+            }
+        })?;
+        self.increment_mined_state_version();
+
+        Ok(())
+    }
+
+    #[cfg(feature = "dev")]
+    /// Captures the current mined block number and state, returning an id that can be used to revert to it.
+    ///
+    /// Pending transactions are not captured and are discarded when reverting.
+    pub fn snapshot(&self) -> Result<u64, StratusError> {
+        let block_number = self.read_mined_block_number()?;
+        let state_trie = match self.state_trie {
+            Some(ref state_trie) => Some(state_trie.snapshot()?),
+            None => None,
+        };
+
+        let mut snapshots = self.snapshots.lock_or_clear("storage snapshots lock was poisoned");
+        snapshots.last_id += 1;
+        let id = snapshots.last_id;
+        snapshots.snapshots.push(StorageSnapshot { id, block_number, state_trie });
+
+        tracing::info!(%id, %block_number, "created storage snapshot");
+        Ok(id)
+    }
+
+    #[cfg(feature = "dev")]
+    /// Reverts the storage to the state captured by a snapshot, discarding it and all snapshots created after it.
+    ///
+    /// Returns `false` if the snapshot does not exist.
+    pub fn revert(&self, id: u64) -> Result<bool, StratusError> {
+        let mut snapshots = self.snapshots.lock_or_clear("storage snapshots lock was poisoned");
+        let Some(position) = snapshots.snapshots.iter().position(|snapshot| snapshot.id == id) else {
+            tracing::warn!(%id, "storage snapshot not found");
+            return Ok(false);
+        };
+        let Some(snapshot) = snapshots.snapshots.drain(position..).next() else {
+            return Ok(false);
+        };
+
+        tracing::info!(%id, block_number = %snapshot.block_number, "reverting storage to snapshot");
+
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("storage::revert", %id).entered();
+
+        // revert perm
+        let previous_mined = self.read_mined_block_number()?;
+        tracing::debug!(storage = %label::PERM, block_number = %snapshot.block_number, "reverting permanent storage");
+        timed(|| self.perm.reset_at(snapshot.block_number)).with(|m| {
+            metrics::inc_storage_reset(m.elapsed, label::PERM, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to revert permanent storage");
+            }
+        })?;
+        self.rolled_back(snapshot.block_number, previous_mined);
+
+        // revert state trie
+        if let (Some(ref state_trie), Some(ref snapshot_trie)) = (&self.state_trie, &snapshot.state_trie) {
+            state_trie.restore(snapshot_trie)?;
+        }
+
+        // reset fee history
+        self.fee_history.clear();
+
+        // discard pending transactions
+        self.reset_temp()?;
+        self.set_pending_block_number_as_next()?;
+
+        Ok(true)
+    }
+
     // -------------------------------------------------------------------------
     // Utils
     // -------------------------------------------------------------------------
 
+    /// Retrieves the version of the mined state, which changes every time a block is mined or the mined state is modified.
+    pub fn read_mined_state_version(&self) -> u64 {
+        self.mined_state_version.load(Ordering::Acquire)
+    }
+
+    fn increment_mined_state_version(&self) {
+        self.mined_state_version.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Discards all pending data of the temporary storage, including executions in the write-ahead log.
+    fn reset_temp(&self) -> Result<(), StratusError> {
+        let Some(ref temp) = self.temp else { return Ok(()) };
+        tracing::debug!(storage = %label::TEMP, "reseting temporary storage");
+        timed(|| temp.reset()).with(|m| {
+            metrics::inc_storage_reset(m.elapsed, label::TEMP, m.result.is_ok());
+            if let Err(ref e) = m.result {
+                tracing::error!(reason = ?e, "failed to reset temporary storage");
+            }
+        })?;
+        if let Some(ref wal) = self.temp_wal {
+            wal.clear()?;
+        }
+        Ok(())
+    }
+
+    /// Retrieves the temporary storage, failing in archive nodes because they do not have one.
+    fn temp(&self) -> Result<&dyn TemporaryStorage, StratusError> {
+        match self.temp {
+            Some(ref temp) => Ok(temp.as_ref()),
+            None => Err(StratusError::StratusArchiveReadOnly),
+        }
+    }
+
+    /// Retrieves the temporary storage if it must be read at the point-in-time, which happens only for pending reads.
+    fn temp_at(&self, point_in_time: &StoragePointInTime) -> Option<&dyn TemporaryStorage> {
+        match self.temp {
+            Some(ref temp) if point_in_time.is_pending() => Some(temp.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// Exports gauges with the amount of data held by the temporary storage.
+    fn export_temp_metrics(&self) {
+        #[cfg(feature = "metrics")]
+        {
+            let stats = self.read_temp_stats();
+            metrics::set_storage_temporary_pending_block_number(stats.pending_block_number.map(|number| number.as_u64()).unwrap_or_default());
+            metrics::set_storage_temporary_pending_transactions(stats.pending_transactions as u64);
+            metrics::set_storage_temporary_accounts(stats.accounts as u64);
+            metrics::set_storage_temporary_slots(stats.slots as u64);
+        }
+    }
+
     /// Translates a block filter to a specific storage point-in-time indicator.
+    ///
+    /// The earliest block and block hashes are resolved through the stored block, because the earliest block is not the genesis block
+    /// when old blocks were pruned.
     pub fn translate_to_point_in_time(&self, block_filter: &BlockFilter) -> Result<StoragePointInTime, StratusError> {
         match block_filter {
             BlockFilter::Pending => Ok(StoragePointInTime::Pending),
             BlockFilter::Latest => Ok(StoragePointInTime::Mined),
-            BlockFilter::Earliest => Ok(StoragePointInTime::MinedPast(BlockNumber::ZERO)),
             BlockFilter::Number(number) => Ok(StoragePointInTime::MinedPast(*number)),
-            BlockFilter::Hash(_) => match self.read_block(block_filter)? {
+            BlockFilter::Earliest | BlockFilter::Hash(_) => match self.read_block(block_filter)? {
                 Some(block) => Ok(StoragePointInTime::MinedPast(block.header.number)),
-                None => Err(StratusError::RpcBlockFilterInvalid { filter: *block_filter }),
+                None => Err(StratusError::RpcBlockNotFound { filter: *block_filter }),
             },
         }
     }
+
+    /// Translates a block filter to the point-in-time of the state accounts and slots are read from, like in `eth_call` or `eth_getBalance`.
+    ///
+    /// Unlike ranges of blocks, state can only be read from blocks that exist: the pending block number reads from the temporary storage,
+    /// and blocks after it are rejected instead of silently reading the latest state.
+    pub fn translate_to_state_point_in_time(&self, block_filter: &BlockFilter) -> Result<StoragePointInTime, StratusError> {
+        let number = match self.translate_to_point_in_time(block_filter)? {
+            StoragePointInTime::MinedPast(number) => number,
+            point_in_time => return Ok(point_in_time),
+        };
+
+        if number <= self.read_mined_block_number()? {
+            return Ok(StoragePointInTime::MinedPast(number));
+        }
+        if Some(number) == self.read_pending_block_number()? {
+            return Ok(StoragePointInTime::Pending);
+        }
+        Err(StratusError::RpcBlockNotFound { filter: *block_filter })
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Snapshots
+// -----------------------------------------------------------------------------
+
+/// State captured by `StratusStorage::snapshot`.
+#[cfg(feature = "dev")]
+struct StorageSnapshot {
+    id: u64,
+    block_number: BlockNumber,
+    state_trie: Option<StateTrie>,
+}
+
+#[cfg(feature = "dev")]
+#[derive(Default)]
+struct StorageSnapshots {
+    /// Last generated snapshot id. Ids are never reused.
+    last_id: u64,
+
+    /// Snapshots ordered by creation.
+    snapshots: Vec<StorageSnapshot>,
 }
 
 // -----------------------------------------------------------------------------
@@ -528,14 +1661,61 @@ pub struct StratusStorageConfig {
 
     #[clap(flatten)]
     pub perm_storage: PermanentStorageConfig,
+
+    /// Maintains a state trie to compute block state roots and serve account proofs.
+    #[arg(long = "state-trie", env = "STATE_TRIE")]
+    pub state_trie: bool,
+
+    /// Bootstraps an empty storage from a state snapshot file created with `StratusStorage::export_snapshot`.
+    #[arg(long = "import-snapshot", env = "IMPORT_SNAPSHOT")]
+    pub import_snapshot: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub encryption: StorageEncryptionConfig,
+
+    /// Creates the genesis block and initial state from a geth-style genesis file when the storage has no genesis block.
+    #[arg(long = "genesis-file", env = "GENESIS_FILE")]
+    pub genesis_file: Option<PathBuf>,
+
+    /// How long blocks removed by resets of the permanent storage are kept so they can be restored. Zero disables the quarantine and
+    /// destroys them immediately.
+    #[arg(long = "reset-quarantine-retention", value_parser=parse_duration, env = "RESET_QUARANTINE_RETENTION", default_value = "0s")]
+    pub reset_quarantine_retention: Duration,
+
+    /// Max number of blocks removed by a single reset that are quarantined. Resets removing more blocks are not quarantined.
+    #[arg(long = "reset-quarantine-max-blocks", env = "RESET_QUARANTINE_MAX_BLOCKS", default_value = "1000")]
+    pub reset_quarantine_max_blocks: usize,
 }
 
 impl StratusStorageConfig {
     /// Initializes Stratus storage.
     pub fn init(&self) -> Result<Arc<StratusStorage>, StratusError> {
-        let temp_storage = self.temp_storage.init()?;
+        // archive nodes serve only mined data, so they do not have a temporary storage
+        let temp_storage = if GlobalState::is_archive() { None } else { Some(self.temp_storage.init()?) };
         let perm_storage = self.perm_storage.init()?;
-        let storage = StratusStorage::new(temp_storage, perm_storage)?;
+        let state_trie = self.state_trie.then(StateTrie::default);
+        let genesis = self.genesis_file.as_deref().map(GenesisConfig::load).transpose()?;
+        let mut storage = StratusStorage::new_with_options(temp_storage, perm_storage, state_trie, genesis)?;
+        storage.enable_reset_quarantine(self.reset_quarantine_retention, self.reset_quarantine_max_blocks);
+
+        let encryption = self.encryption.init()?.map(Arc::new);
+        if let Some(ref encryption) = encryption {
+            storage.enable_encryption(Arc::clone(encryption));
+        }
+
+        if storage.temp.is_some() {
+            if let Some(wal) = self.temp_storage.init_wal(encryption.as_ref())? {
+                storage.enable_temp_wal(wal)?;
+            }
+        }
+
+        if let Some(ref path) = self.import_snapshot {
+            if storage.temp.is_some() {
+                storage.import_snapshot(path)?;
+            } else {
+                tracing::warn!(path = %path.display(), "skipping snapshot import because archive nodes do not write to the permanent storage");
+            }
+        }
 
         Ok(Arc::new(storage))
     }
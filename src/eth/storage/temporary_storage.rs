@@ -1,4 +1,7 @@
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::anyhow;
 use clap::Parser;
@@ -8,13 +11,19 @@ use crate::eth::primitives::Account;
 use crate::eth::primitives::Address;
 use crate::eth::primitives::BlockNumber;
 use crate::eth::primitives::ExternalBlock;
+use crate::eth::primitives::Gas;
 use crate::eth::primitives::Hash;
 use crate::eth::primitives::PendingBlock;
 use crate::eth::primitives::Slot;
 use crate::eth::primitives::SlotIndex;
 use crate::eth::primitives::StratusError;
 use crate::eth::primitives::TransactionExecution;
+use crate::eth::storage::redis::RedisTemporaryStorage;
 use crate::eth::storage::InMemoryTemporaryStorage;
+use crate::eth::storage::StorageEncryption;
+use crate::eth::storage::TemporaryWal;
+use crate::ext::parse_duration;
+use crate::log_and_err;
 
 /// Temporary storage (in-between blocks) operations.
 pub trait TemporaryStorage: Send + Sync + 'static {
@@ -41,8 +50,14 @@ pub trait TemporaryStorage: Send + Sync + 'static {
     /// Retrieves the pending transactions of the pending block.
     fn pending_transactions(&self) -> Vec<TransactionExecution>;
 
+    /// Retrieves the pending block being mined.
+    fn read_pending_block(&self) -> anyhow::Result<Option<PendingBlock>>;
+
     /// Finishes the mining of the pending block and starts a new block.
-    fn finish_pending_block(&self) -> anyhow::Result<PendingBlock>;
+    ///
+    /// Transactions exceeding the gas limit are removed from the finished block and returned in execution order, so they can be
+    /// saved to the new block.
+    fn finish_pending_block(&self, gas_limit: Gas) -> anyhow::Result<(PendingBlock, Vec<TransactionExecution>)>;
 
     /// Retrieves a transaction from the storage.
     fn read_transaction(&self, hash: &Hash) -> anyhow::Result<Option<TransactionExecution>>;
@@ -63,6 +78,26 @@ pub trait TemporaryStorage: Send + Sync + 'static {
 
     /// Resets to default empty state.
     fn reset(&self) -> anyhow::Result<()>;
+
+    /// Retrieves the amount of data held by the storage.
+    fn stats(&self) -> TemporaryStorageStats;
+}
+
+/// Amount of data held by a temporary storage.
+#[derive(DebugAsJson, Clone, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemporaryStorageStats {
+    /// Number of the block being mined.
+    pub pending_block_number: Option<BlockNumber>,
+
+    /// Number of transactions executed in the block being mined.
+    pub pending_transactions: usize,
+
+    /// Number of accounts held, including the ones changed in previous blocks kept to detect conflicts.
+    pub accounts: usize,
+
+    /// Number of slots held, including the ones changed in previous blocks kept to detect conflicts.
+    pub slots: usize,
 }
 
 // -----------------------------------------------------------------------------
@@ -75,12 +110,27 @@ pub struct TemporaryStorageConfig {
     /// Temporary storage implementation.
     #[arg(long = "temp-storage", env = "TEMP_STORAGE")]
     pub temp_storage_kind: TemporaryStorageKind,
+
+    /// Storage connection URL (Redis only).
+    #[arg(long = "temp-storage-url", env = "TEMP_STORAGE_URL", required_if_eq("temp_storage_kind", "redis"))]
+    pub temp_storage_url: Option<String>,
+
+    /// Write-ahead log file where executions saved in the temporary storage are appended and replayed from on startup.
+    #[arg(long = "temp-storage-wal", env = "TEMP_STORAGE_WAL")]
+    pub temp_storage_wal: Option<PathBuf>,
+
+    /// Minimum interval between truncations of the write-ahead log after blocks are committed.
+    #[arg(long = "temp-storage-wal-truncate-interval", value_parser=parse_duration, env = "TEMP_STORAGE_WAL_TRUNCATE_INTERVAL", default_value = "10s")]
+    pub temp_storage_wal_truncate_interval: Duration,
 }
 
 #[derive(DebugAsJson, Clone, serde::Serialize)]
 pub enum TemporaryStorageKind {
     #[serde(rename = "inmemory")]
     InMemory,
+
+    #[serde(rename = "redis")]
+    Redis,
 }
 
 impl TemporaryStorageConfig {
@@ -90,6 +140,25 @@ impl TemporaryStorageConfig {
 
         match self.temp_storage_kind {
             TemporaryStorageKind::InMemory => Ok(Box::<InMemoryTemporaryStorage>::default()),
+
+            TemporaryStorageKind::Redis => {
+                let Some(url) = self.temp_storage_url.as_deref() else {
+                    return log_and_err!("redis connection url not provided when it was expected to be present");
+                };
+                Ok(Box::new(RedisTemporaryStorage::new(url)?))
+            }
+        }
+    }
+
+    /// Opens the write-ahead log of the temporary storage, if configured, encrypting it if encryption is enabled.
+    pub fn init_wal(&self, encryption: Option<&Arc<StorageEncryption>>) -> anyhow::Result<Option<TemporaryWal>> {
+        let Some(ref path) = self.temp_storage_wal else {
+            return Ok(None);
+        };
+        let wal = TemporaryWal::open(path, self.temp_storage_wal_truncate_interval)?;
+        match encryption {
+            Some(encryption) => Ok(Some(wal.with_encryption(Arc::clone(encryption)))),
+            None => Ok(Some(wal)),
         }
     }
 }
@@ -100,6 +169,7 @@ impl FromStr for TemporaryStorageKind {
     fn from_str(s: &str) -> anyhow::Result<Self, Self::Err> {
         match s {
             "inmemory" => Ok(Self::InMemory),
+            "redis" => Ok(Self::Redis),
             s => Err(anyhow!("unknown temporary storage: {}", s)),
         }
     }
@@ -0,0 +1,260 @@
+//! Write-ahead log of the temporary storage.
+//!
+//! Executions saved in the temporary storage are lost if the node crashes before they are mined and committed. When enabled, every
+//! saved execution is appended to a file as a JSON line together with the number of the block it was saved to. On startup, the
+//! executions of blocks not committed yet are replayed into the temporary storage before the node serves traffic. After blocks are
+//! committed, the file is periodically rewritten without the executions of committed blocks.
+//!
+//! Entries are written directly to the file without buffering, so they survive crashes of the process, but not of the host. When storage
+//! encryption is enabled, each line is encrypted separately, so a partially written entry still affects only itself.
+
+use std::fs;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Context;
+
+use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::TransactionExecution;
+use crate::eth::storage::StorageEncryption;
+use crate::ext::to_json_string;
+use crate::ext::MutexExt;
+
+/// Execution saved in the temporary storage.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct TemporaryWalEntry<T = TransactionExecution> {
+    /// Pending block the execution was saved to.
+    pub block_number: BlockNumber,
+
+    /// Indicates if conflicts were checked when the execution was saved.
+    pub check_conflicts: bool,
+
+    pub tx: T,
+}
+
+/// Append-only file of executions saved in the temporary storage.
+#[derive(Debug)]
+pub struct TemporaryWal {
+    path: PathBuf,
+
+    /// Minimum interval between truncations.
+    truncate_interval: Duration,
+
+    /// Encryption of the lines, if enabled.
+    encryption: Option<Arc<StorageEncryption>>,
+
+    inner: Mutex<TemporaryWalInner>,
+}
+
+#[derive(Debug)]
+struct TemporaryWalInner {
+    file: File,
+    truncated_at: Instant,
+}
+
+impl TemporaryWal {
+    /// Opens the log file, creating it if it does not exist.
+    pub fn open(path: &Path, truncate_interval: Duration) -> anyhow::Result<Self> {
+        tracing::info!(path = %path.display(), ?truncate_interval, "opening temporary storage wal");
+        Ok(Self {
+            path: path.to_owned(),
+            truncate_interval,
+            encryption: None,
+            inner: Mutex::new(TemporaryWalInner {
+                file: open_append(path)?,
+                truncated_at: Instant::now(),
+            }),
+        })
+    }
+
+    /// Encrypts lines written to the log and decrypts lines read from it.
+    pub fn with_encryption(mut self, encryption: Arc<StorageEncryption>) -> Self {
+        self.encryption = Some(encryption);
+        self
+    }
+
+    /// Serializes an execution as a log line.
+    ///
+    /// Executions are serialized before being saved to the temporary storage, but only appended after they are saved successfully.
+    pub fn encode(block_number: BlockNumber, tx: &TransactionExecution, check_conflicts: bool) -> String {
+        let entry = TemporaryWalEntry {
+            block_number,
+            check_conflicts,
+            tx,
+        };
+        to_json_string(&entry) + "\n"
+    }
+
+    /// Appends a serialized execution to the log.
+    pub fn append(&self, line: &str) -> anyhow::Result<()> {
+        let line = self.encrypt_line(line)?;
+        let mut inner = self.inner.lock_or_clear("temporary wal lock was poisoned");
+        inner
+            .file
+            .write_all(line.as_bytes())
+            .with_context(|| format!("failed to append to temporary storage wal {}", self.path.display()))
+    }
+
+    /// Reads all executions from the log, from the oldest to the newest.
+    ///
+    /// Invalid lines, like the last one when the process crashed while appending it, are skipped.
+    pub fn read(&self) -> anyhow::Result<Vec<TemporaryWalEntry>> {
+        let _inner = self.inner.lock_or_clear("temporary wal lock was poisoned");
+        self.read_unlocked()
+    }
+
+    /// Removes the executions of committed blocks if the last truncation happened more than the truncate interval ago.
+    pub fn truncate_if_due(&self, committed: BlockNumber) -> anyhow::Result<()> {
+        let mut inner = self.inner.lock_or_clear("temporary wal lock was poisoned");
+        if inner.truncated_at.elapsed() < self.truncate_interval {
+            return Ok(());
+        }
+
+        let entries = self.read_unlocked()?;
+        let remaining = entries.iter().filter(|entry| entry.block_number > committed).map(to_line).collect::<Vec<_>>();
+        tracing::info!(%committed, removed = %(entries.len() - remaining.len()), remaining = %remaining.len(), "truncating temporary storage wal");
+        self.rewrite_unlocked(&mut inner, &remaining)
+    }
+
+    /// Replaces all executions in the log.
+    pub fn rewrite(&self, lines: &[String]) -> anyhow::Result<()> {
+        let mut inner = self.inner.lock_or_clear("temporary wal lock was poisoned");
+        self.rewrite_unlocked(&mut inner, lines)
+    }
+
+    /// Removes all executions from the log.
+    pub fn clear(&self) -> anyhow::Result<()> {
+        self.rewrite(&[])
+    }
+
+    fn read_unlocked(&self) -> anyhow::Result<Vec<TemporaryWalEntry>> {
+        let file = File::open(&self.path).with_context(|| format!("failed to open temporary storage wal {}", self.path.display()))?;
+        let mut entries = Vec::new();
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.with_context(|| format!("failed to read temporary storage wal {}", self.path.display()))?;
+            let line = match self.encryption {
+                Some(ref encryption) => match encryption.decrypt_line(&line) {
+                    Ok(line) => line,
+                    Err(e) => {
+                        tracing::warn!(reason = ?e, line = %(index + 1), "skipping undecryptable temporary storage wal entry");
+                        continue;
+                    }
+                },
+                None => line,
+            };
+            match serde_json::from_str::<TemporaryWalEntry>(&line) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => tracing::warn!(reason = ?e, line = %(index + 1), "skipping invalid temporary storage wal entry"),
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Writes the lines to a temporary file and renames it, so an interrupted rewrite never loses entries.
+    fn rewrite_unlocked(&self, inner: &mut TemporaryWalInner, lines: &[String]) -> anyhow::Result<()> {
+        let tmp_path = self.path.with_extension("tmp");
+        let lines = lines.iter().map(|line| self.encrypt_line(line)).collect::<anyhow::Result<Vec<_>>>()?;
+        fs::write(&tmp_path, lines.concat()).with_context(|| format!("failed to write temporary storage wal to {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, &self.path).with_context(|| format!("failed to rename temporary storage wal to {}", self.path.display()))?;
+        inner.file = open_append(&self.path)?;
+        inner.truncated_at = Instant::now();
+        Ok(())
+    }
+
+    fn encrypt_line(&self, line: &str) -> anyhow::Result<String> {
+        match self.encryption {
+            Some(ref encryption) => encryption.encrypt_line(line),
+            None => Ok(line.to_owned()),
+        }
+    }
+}
+
+fn open_append(path: &Path) -> anyhow::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open temporary storage wal {}", path.display()))
+}
+
+fn to_line(entry: &TemporaryWalEntry) -> String {
+    TemporaryWal::encode(entry.block_number, &entry.tx, entry.check_conflicts)
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use fake::Fake;
+    use fake::Faker;
+
+    use super::*;
+    use crate::eth::primitives::TransactionInput;
+
+    fn tx() -> TransactionExecution {
+        TransactionExecution::new_local(TransactionInput::default(), Faker.fake())
+    }
+
+    #[test]
+    fn wal_truncation_keeps_uncommitted_executions() {
+        let dir = tempfile::tempdir().unwrap();
+        let wal = TemporaryWal::open(&dir.path().join("temp.wal"), Duration::ZERO).unwrap();
+
+        for number in [1u64, 1, 2, 3] {
+            wal.append(&TemporaryWal::encode(number.into(), &tx(), true)).unwrap();
+        }
+        assert_eq!(wal.read().unwrap().len(), 4);
+
+        wal.truncate_if_due(1u64.into()).unwrap();
+        let numbers = wal.read().unwrap().into_iter().map(|entry| entry.block_number.as_u64()).collect::<Vec<_>>();
+        assert_eq!(numbers, vec![2, 3]);
+
+        // appends continue after truncation
+        wal.append(&TemporaryWal::encode(4u64.into(), &tx(), false)).unwrap();
+        assert_eq!(wal.read().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn wal_skips_partially_written_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("temp.wal");
+        let wal = TemporaryWal::open(&path, Duration::ZERO).unwrap();
+
+        let line = TemporaryWal::encode(1u64.into(), &tx(), true);
+        wal.append(&line).unwrap();
+        wal.append(&line[..line.len() / 2]).unwrap();
+
+        assert_eq!(wal.read().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn wal_encrypts_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("temp.wal");
+        let encryption = Arc::new(StorageEncryption::new(&[1; 32]).unwrap());
+        let wal = TemporaryWal::open(&path, Duration::ZERO).unwrap().with_encryption(Arc::clone(&encryption));
+
+        for number in [1u64, 2] {
+            wal.append(&TemporaryWal::encode(number.into(), &tx(), true)).unwrap();
+        }
+        wal.truncate_if_due(1u64.into()).unwrap();
+        assert_eq!(wal.read().unwrap().len(), 1);
+
+        // file content is not readable without the key
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("block_number"));
+        let plain = TemporaryWal::open(&path, Duration::ZERO).unwrap();
+        assert!(plain.read().unwrap().is_empty());
+    }
+}
@@ -1,5 +1,6 @@
 use std::fmt::Debug;
 use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU8;
 use std::sync::atomic::Ordering;
 
 use once_cell::sync::Lazy;
@@ -92,6 +93,20 @@ pub enum NodeMode {
 
     #[strum(to_string = "follower")]
     Follower,
+
+    /// Read-only node serving historical data from the permanent storage.
+    #[strum(to_string = "archive")]
+    Archive,
+}
+
+impl NodeMode {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            v if v == Self::Leader as u8 => Self::Leader,
+            v if v == Self::Archive as u8 => Self::Archive,
+            _ => Self::Follower,
+        }
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -113,7 +128,7 @@ static TRANSACTIONS_ENABLED: AtomicBool = AtomicBool::new(true);
 static UNKNOWN_CLIENT_ENABLED: AtomicBool = AtomicBool::new(true);
 
 /// Current node mode.
-static IS_LEADER: AtomicBool = AtomicBool::new(false);
+static NODE_MODE: AtomicU8 = AtomicU8::new(NodeMode::Follower as u8);
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GlobalState;
@@ -236,6 +251,9 @@ impl GlobalState {
         let mode = if config.follower {
             Self::set_importer_shutdown(false);
             NodeMode::Follower
+        } else if config.archive {
+            Self::set_transactions_enabled(false);
+            NodeMode::Archive
         } else {
             NodeMode::Leader
         };
@@ -244,26 +262,27 @@ impl GlobalState {
 
     /// Sets the current node mode.
     pub fn set_node_mode(mode: NodeMode) {
-        IS_LEADER.store(matches!(mode, NodeMode::Leader), Ordering::Relaxed);
+        NODE_MODE.store(mode as u8, Ordering::Relaxed);
     }
 
     /// Gets the current node mode.
     pub fn get_node_mode() -> NodeMode {
-        if IS_LEADER.load(Ordering::Relaxed) {
-            NodeMode::Leader
-        } else {
-            NodeMode::Follower
-        }
+        NodeMode::from_u8(NODE_MODE.load(Ordering::Relaxed))
     }
 
     /// Checks if the node is in follower mode.
     pub fn is_follower() -> bool {
-        !IS_LEADER.load(Ordering::Relaxed)
+        Self::get_node_mode() == NodeMode::Follower
     }
 
     /// Checks if the node is in leader mode.
     pub fn is_leader() -> bool {
-        IS_LEADER.load(Ordering::Relaxed)
+        Self::get_node_mode() == NodeMode::Leader
+    }
+
+    /// Checks if the node is in read-only archive mode.
+    pub fn is_archive() -> bool {
+        Self::get_node_mode() == NodeMode::Archive
     }
 
     // -------------------------------------------------------------------------
@@ -273,11 +292,12 @@ impl GlobalState {
     pub fn get_global_state_as_json(ctx: &RpcContext) -> JsonValue {
         json!({
             "is_leader": Self::is_leader(),
+            "is_archive": Self::is_archive(),
             "is_shutdown": Self::is_shutdown(),
             "is_importer_shutdown": Self::is_importer_shutdown(),
-            "is_interval_miner_running": ctx.miner.is_interval_miner_running(),
+            "is_interval_miner_running": ctx.miner.as_ref().is_some_and(|miner| miner.is_interval_miner_running()),
             "transactions_enabled": Self::is_transactions_enabled(),
-            "miner_paused": ctx.miner.is_paused(),
+            "miner_paused": ctx.miner.as_ref().is_some_and(|miner| miner.is_paused()),
             "unknown_client_enabled": Self::is_unknown_client_enabled(),
         })
     }
@@ -16,11 +16,17 @@ use crate::alias::EthersBytes;
 use crate::alias::EthersTransaction;
 use crate::alias::JsonValue;
 use crate::eth::primitives::Address;
+use crate::eth::primitives::Block;
 use crate::eth::primitives::BlockNumber;
+use crate::eth::primitives::Bytes;
 use crate::eth::primitives::ExternalBlock;
 use crate::eth::primitives::ExternalReceipt;
 use crate::eth::primitives::Hash;
+use crate::eth::primitives::Nonce;
+use crate::eth::primitives::SlotIndex;
+use crate::eth::primitives::SlotValue;
 use crate::eth::primitives::StratusError;
+use crate::eth::primitives::TransactionOptions;
 use crate::eth::primitives::Wei;
 use crate::eth::rpc::RpcClientApp;
 use crate::ext::to_json_value;
@@ -198,17 +204,66 @@ impl BlockchainClient {
         }
     }
 
+    /// Fetches account nonce by address and block number.
+    pub async fn fetch_nonce(&self, address: &Address, block_number: Option<BlockNumber>) -> anyhow::Result<Nonce> {
+        tracing::debug!(%address, block_number = %block_number.or_empty(), "fetching account nonce");
+
+        let address = to_json_value(address);
+        let number = to_json_value(block_number);
+        let result = self.http.request::<Nonce, _>("eth_getTransactionCount", [address, number]).await;
+
+        match result {
+            Ok(nonce) => Ok(nonce),
+            Err(e) => log_and_err!(reason = e, "failed to fetch account nonce"),
+        }
+    }
+
+    /// Fetches account bytecode by address and block number.
+    pub async fn fetch_code(&self, address: &Address, block_number: Option<BlockNumber>) -> anyhow::Result<Bytes> {
+        tracing::debug!(%address, block_number = %block_number.or_empty(), "fetching account code");
+
+        let address = to_json_value(address);
+        let number = to_json_value(block_number);
+        let result = self.http.request::<Bytes, _>("eth_getCode", [address, number]).await;
+
+        match result {
+            Ok(code) => Ok(code),
+            Err(e) => log_and_err!(reason = e, "failed to fetch account code"),
+        }
+    }
+
+    /// Fetches a slot value by address, slot index and block number.
+    pub async fn fetch_storage_at(&self, address: &Address, index: &SlotIndex, block_number: Option<BlockNumber>) -> anyhow::Result<SlotValue> {
+        tracing::debug!(%address, %index, block_number = %block_number.or_empty(), "fetching account slot");
+
+        let address = to_json_value(address);
+        let index = to_json_value(index);
+        let number = to_json_value(block_number);
+        let result = self.http.request::<SlotValue, _>("eth_getStorageAt", [address, index, number]).await;
+
+        match result {
+            Ok(value) => Ok(value),
+            Err(e) => log_and_err!(reason = e, "failed to fetch account slot"),
+        }
+    }
+
     // -------------------------------------------------------------------------
     // RPC mutations
     // -------------------------------------------------------------------------
 
     /// Forwards a transaction to leader.
-    pub async fn send_raw_transaction_to_leader(&self, tx: EthersBytes, rpc_client: RpcClientApp) -> Result<Hash, StratusError> {
+    pub async fn send_raw_transaction_to_leader(
+        &self,
+        tx: EthersBytes,
+        tx_options: TransactionOptions,
+        rpc_client: RpcClientApp,
+    ) -> Result<Hash, StratusError> {
         tracing::debug!("sending raw transaction to leader");
 
         let tx = to_json_value(tx);
         let rpc_client = to_json_value(rpc_client);
-        let result = self.http.request::<Hash, _>("eth_sendRawTransaction", [tx, rpc_client]).await;
+        let tx_options = to_json_value(tx_options);
+        let result = self.http.request::<Hash, _>("eth_sendRawTransaction", [tx, rpc_client, tx_options]).await;
 
         match result {
             Ok(hash) => Ok(hash),
@@ -225,8 +280,28 @@ impl BlockchainClient {
     // -------------------------------------------------------------------------
 
     pub async fn subscribe_new_heads(&self) -> anyhow::Result<Subscription<ExternalBlock>> {
-        const TASK_NAME: &str = "blockchain::subscribe_new_heads";
         tracing::debug!("subscribing to newHeads event");
+        self.subscribe("newHeads", "eth_subscribe", &[JsonValue::String("newHeads".to_owned())], "eth_unsubscribe")
+            .await
+    }
+
+    /// Subscribes to blocks mined by the leader starting from the specified block, including already mined ones.
+    pub async fn subscribe_replication(&self, from: BlockNumber) -> anyhow::Result<Subscription<Block>> {
+        tracing::debug!(%from, "subscribing to replication");
+        self.subscribe(
+            "replication",
+            "stratus_subscribeReplication",
+            &[to_json_value(from)],
+            "stratus_unsubscribeReplication",
+        )
+        .await
+    }
+
+    async fn subscribe<T>(&self, event: &str, method: &str, params: &[JsonValue], unsubscribe_method: &str) -> anyhow::Result<Subscription<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        const TASK_NAME: &str = "blockchain::subscribe";
 
         let mut first_attempt = true;
         loop {
@@ -235,9 +310,7 @@ impl BlockchainClient {
             };
 
             let ws_read = self.require_ws().await?;
-            let result = ws_read
-                .subscribe::<ExternalBlock, _>("eth_subscribe", [JsonValue::String("newHeads".to_owned())], "eth_unsubscribe")
-                .await;
+            let result = ws_read.subscribe::<T, _>(method, params, unsubscribe_method).await;
 
             match result {
                 // subscribed
@@ -247,10 +320,10 @@ impl BlockchainClient {
                 e @ Err(ClientError::RestartNeeded(_)) => {
                     // will try to reconnect websocket client only in first attempt
                     if first_attempt {
-                        tracing::error!(reason = ?e, %first_attempt, "failed to subscribe to newHeads event. trying to reconnect websocket client now.");
+                        tracing::error!(reason = ?e, %first_attempt, "failed to subscribe to {} event. trying to reconnect websocket client now.", event);
                     } else {
-                        tracing::error!(reason = ?e, %first_attempt, "failed to subscribe to newHeads event. will not try to reconnect websocket client.");
-                        return e.context("failed to subscribe to newHeads event");
+                        tracing::error!(reason = ?e, %first_attempt, "failed to subscribe to {} event. will not try to reconnect websocket client.", event);
+                        return e.context(format!("failed to subscribe to {} event", event));
                     }
                     first_attempt = false;
 
@@ -262,7 +335,7 @@ impl BlockchainClient {
                 }
 
                 // failed and cannot do anything
-                Err(e) => return log_and_err!(reason = e, "failed to subscribe to newHeads event"),
+                Err(e) => return log_and_err!(reason = e, format!("failed to subscribe to {} event", event)),
             }
         }
     }
@@ -0,0 +1,109 @@
+//! Coordinated shutdown of the services that execute and persist transactions.
+
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use tokio::time::timeout;
+use tokio_util::sync::CancellationToken;
+
+use crate::eth::executor::Executor;
+use crate::eth::miner::Miner;
+use crate::eth::storage::StratusStorage;
+use crate::ext::not;
+use crate::ext::traced_sleep;
+use crate::ext::SleepReason;
+use crate::globals::STRATUS_SHUTDOWN_SIGNAL;
+use crate::GlobalState;
+
+/// Interval between checks of in-flight executions while draining.
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Handle shared by services to observe the application shutdown and to drain their work before the process exits.
+///
+/// The shutdown is triggered by SIGTERM/SIGINT or by any task calling [`GlobalState::shutdown_from`].
+#[derive(Debug, Clone)]
+pub struct GracefulShutdown {
+    token: CancellationToken,
+
+    /// Max time spent in each drain step before giving up and exiting anyway.
+    drain_timeout: Duration,
+}
+
+impl GracefulShutdown {
+    pub fn new(drain_timeout: Duration) -> Self {
+        Self {
+            token: STRATUS_SHUTDOWN_SIGNAL.clone(),
+            drain_timeout,
+        }
+    }
+
+    /// Token cancelled when the application starts shutting down.
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Checks if the application is shutting down.
+    pub fn is_shutdown(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Waits until the application starts shutting down.
+    pub async fn wait(&self) {
+        self.token.cancelled().await;
+    }
+
+    /// Drains in-flight work after the RPC server stopped accepting requests.
+    ///
+    /// Steps:
+    /// 1. Waits the importer to finish the block being imported.
+    /// 2. Waits local transactions already accepted by the executor to finish.
+    /// 3. Stops the interval miner.
+    /// 4. Mines and commits transactions left in the pending block, so the temporary storage is empty when the process exits.
+    /// 5. Waits blocks queued for background commit to be committed.
+    pub async fn drain(&self, executor: &dyn Executor, miner: &Arc<Miner>, storage: &Arc<StratusStorage>) {
+        const TASK_NAME: &str = "graceful-shutdown";
+        tracing::info!(drain_timeout = ?self.drain_timeout, "draining services before shutdown");
+
+        // importer
+        if timeout(self.drain_timeout, GlobalState::wait_for_importer_to_finish()).await.is_err() {
+            tracing::error!(task = TASK_NAME, "importer did not finish in time");
+        }
+
+        // executor
+        let deadline = Instant::now() + self.drain_timeout;
+        while executor.in_flight_transactions() > 0 {
+            if Instant::now() >= deadline {
+                tracing::error!(task = TASK_NAME, in_flight = %executor.in_flight_transactions(), "local transactions did not finish in time");
+                break;
+            }
+            traced_sleep(DRAIN_POLL_INTERVAL, SleepReason::SyncData).await;
+        }
+
+        // miner
+        miner.shutdown_and_wait().await;
+
+        // pending block
+        // external blocks are committed only when fully imported, so a partial external block is discarded
+        let pending_txs = storage.pending_transactions().len();
+        if pending_txs > 0 && not(miner.mode().is_external()) {
+            tracing::info!(%pending_txs, "committing pending block before shutdown");
+            let miner = Arc::clone(miner);
+            match timeout(self.drain_timeout, tokio::task::spawn_blocking(move || miner.mine_local_and_commit())).await {
+                Ok(Ok(Ok(()))) => tracing::info!(%pending_txs, "committed pending block before shutdown"),
+                Ok(Ok(Err(e))) => tracing::error!(reason = ?e, %pending_txs, "failed to commit pending block before shutdown"),
+                Ok(Err(e)) => tracing::error!(reason = ?e, %pending_txs, "failed to commit pending block before shutdown"),
+                Err(_) => tracing::error!(%pending_txs, "pending block was not committed in time"),
+            }
+        }
+
+        // blocks queued for background commit
+        let miner = Arc::clone(miner);
+        if timeout(self.drain_timeout, tokio::task::spawn_blocking(move || miner.stop_background_commits()))
+            .await
+            .is_err()
+        {
+            tracing::error!(task = TASK_NAME, "blocks queued for background commit were not committed in time");
+        }
+    }
+}
@@ -4,20 +4,32 @@ use std::stringify;
 use clap::Parser;
 use display_json::DebugAsJson;
 
+use crate::infra::metrics::init_label_controls;
 use crate::infra::metrics::metrics_for_consensus;
 use crate::infra::metrics::metrics_for_evm;
 use crate::infra::metrics::metrics_for_executor;
 use crate::infra::metrics::metrics_for_importer_online;
 use crate::infra::metrics::metrics_for_json_rpc;
+use crate::infra::metrics::metrics_for_miner;
 use crate::infra::metrics::metrics_for_rocks;
 use crate::infra::metrics::metrics_for_storage_read;
+use crate::infra::metrics::metrics_for_storage_temporary;
 use crate::infra::metrics::metrics_for_storage_write;
+use crate::infra::metrics::MetricLabelControls;
 
 #[derive(DebugAsJson, Clone, Parser, serde::Serialize)]
 pub struct MetricsConfig {
     /// Metrics exporter binding address.
     #[arg(long = "metrics-exporter-address", env = "METRICS_EXPORTER_ADDRESS", default_value = "0.0.0.0:9000")]
     pub metrics_exporter_address: SocketAddr,
+
+    /// Labels removed from all metrics, like `contract` or `function`, to reduce the scrape size.
+    #[arg(long = "metrics-disabled-labels", env = "METRICS_DISABLED_LABELS", value_delimiter = ',')]
+    pub metrics_disabled_labels: Vec<String>,
+
+    /// Max number of distinct values recorded for each label. New values after the limit are recorded as `other`. Unlimited if zero.
+    #[arg(long = "metrics-max-label-values", env = "METRICS_MAX_LABEL_VALUES", default_value = "0")]
+    pub metrics_max_label_values: usize,
 }
 
 impl MetricsConfig {
@@ -29,13 +41,18 @@ impl MetricsConfig {
         let mut metrics = Vec::new();
         metrics.extend(metrics_for_importer_online());
         metrics.extend(metrics_for_json_rpc());
+        metrics.extend(metrics_for_miner());
         metrics.extend(metrics_for_executor());
         metrics.extend(metrics_for_evm());
         metrics.extend(metrics_for_storage_read());
         metrics.extend(metrics_for_storage_write());
+        metrics.extend(metrics_for_storage_temporary());
         metrics.extend(metrics_for_rocks());
         metrics.extend(metrics_for_consensus());
 
+        // init label controls before any metric is recorded
+        init_label_controls(MetricLabelControls::new(&self.metrics_disabled_labels, self.metrics_max_label_values));
+
         // init metric exporter
         init_metrics_exporter(self.metrics_exporter_address);
 
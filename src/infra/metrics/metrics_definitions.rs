@@ -14,7 +14,10 @@ metrics! {
     histogram_duration rpc_requests_finished{client, method, contract, function, result, result_code, success},
 
     "Number of JSON-RPC subscriptions active right now."
-    gauge rpc_subscriptions_active{subscription}
+    gauge rpc_subscriptions_active{subscription},
+
+    "Number of JSON-RPC polling filters installed right now."
+    gauge rpc_filters_active{filter}
 }
 
 // Storage reads.
@@ -33,12 +36,27 @@ metrics! {
     "Time executing storage read_block operation."
     histogram_duration storage_read_block{storage, success},
 
+    "Time executing storage read_block_receipts operation."
+    histogram_duration storage_read_block_receipts{storage, success},
+
+    "Time executing storage read_block_state_diff operation."
+    histogram_duration storage_read_block_state_diff{storage, success},
+
+    "Time executing storage read_code operation."
+    histogram_duration storage_read_code{storage, success},
+
     "Time executing storage read_logs operation."
     histogram_duration storage_read_logs{storage, success},
 
+    "Time executing storage read_transactions_by_address operation."
+    histogram_duration storage_read_transactions_by_address{storage, success},
+
     "Time executing storage read_slot operation."
     histogram_duration storage_read_slot{storage, point_in_time, success},
 
+    "Time executing storage read_slots operation."
+    histogram_duration storage_read_slots{storage, point_in_time, success},
+
     "Time executing storage read_transaction operation."
     histogram_duration storage_read_transaction{storage, success}
 }
@@ -69,7 +87,27 @@ metrics! {
     histogram_duration storage_save_block{storage, size_by_tx, size_by_gas, success},
 
     "Time executing storage reset operation."
-    histogram_duration storage_reset{storage, success}
+    histogram_duration storage_reset{storage, success},
+
+    "Time executing storage prune_blocks operation."
+    histogram_duration storage_prune_blocks{storage, target, success}
+}
+
+// Temporary storage state.
+metrics! {
+    group: storage_temporary,
+
+    "Number of the pending block being mined."
+    gauge storage_temporary_pending_block_number{},
+
+    "Number of transactions executed in the pending block."
+    gauge storage_temporary_pending_transactions{},
+
+    "Number of accounts held by the temporary storage."
+    gauge storage_temporary_accounts{},
+
+    "Number of slots held by the temporary storage."
+    gauge storage_temporary_slots{}
 }
 
 // Importer online metrics.
@@ -80,7 +118,50 @@ metrics! {
     histogram_duration import_online_mined_block{},
 
     "Number of transactions imported."
-    counter importer_online_transactions_total{}
+    counter importer_online_transactions_total{},
+
+    "Number of blocks reverted when the external chain was reorganized."
+    histogram_counter importer_online_reorg_depth{},
+
+    "Number of downloaded blocks waiting to be executed."
+    gauge importer_online_prefetched_blocks{}
+}
+
+// Miner metrics.
+metrics! {
+    group: miner,
+
+    "Number of transactions moved to quarantine."
+    counter miner_quarantined_transactions{reason},
+
+    "Number of alerts raised because no block was produced or imported despite pending work."
+    counter miner_watchdog_alerts{},
+
+    "Indicates if block production is stalled according to the watchdog (1) or not (0)."
+    gauge miner_watchdog_stalled{},
+
+    "Number of committed transactions by called or deployed contract."
+    counter miner_contract_transactions{contract},
+
+    "Gas used by committed transactions by called or deployed contract."
+    counter miner_contract_gas{contract},
+
+    "Gas target of local blocks when it is tuned by commit latency and conflicts."
+    gauge miner_block_gas_target{}
+}
+
+// Event publisher metrics.
+metrics! {
+    group: publisher,
+
+    "Time delivering the events of one block to a destination, including failed attempts."
+    histogram_duration publisher_delivery{publisher, success},
+
+    "Number of events acknowledged by a destination."
+    counter publisher_events_published{publisher, kind},
+
+    "Last block whose events were acknowledged by a destination."
+    gauge publisher_cursor{publisher}
 }
 
 // Execution metrics.
@@ -102,6 +183,9 @@ metrics! {
     "Gas spent executing an external transaction."
     histogram_counter executor_external_transaction_gas{function},
 
+    "Number of re-executed external transactions by outcome of the comparison with their receipts."
+    counter executor_external_comparisons{outcome},
+
     "Number of account reads when importing an external block."
     histogram_counter executor_external_block_account_reads{},
 
@@ -120,6 +204,33 @@ metrics! {
     "Gas spent executing a local transaction."
     histogram_counter executor_local_transaction_gas{success, function},
 
+    "Number of local transactions discarded because they expired before being executed."
+    counter executor_local_transaction_expired{reason},
+
+    "Number of local transactions that conflicted with the pending block, by conflicting contract or account address."
+    counter executor_transaction_conflicts{contract},
+
+    "Number of transactions executed and saved to the pending block."
+    counter executor_transactions_total{source},
+
+    "Number of tasks waiting in an EVM queue."
+    gauge executor_evm_queue_depth{route},
+
+    "Number of tasks rejected because an EVM queue was full."
+    counter executor_evm_queue_rejected{route},
+
+    "Number of EVMs consuming tasks from an EVM queue."
+    gauge executor_evm_pool_size{route},
+
+    "Number of local transactions aborted because they exceeded the storage latency budget."
+    counter executor_storage_latency_budget_exceeded{},
+
+    "Number of local transactions parked waiting for a nonce gap to be filled."
+    gauge executor_nonce_parked_transactions{},
+
+    "Number of parked local transactions evicted because the sender nonce or balance invalidated them."
+    counter executor_nonce_parking_evictions{reason},
+
     "Time executing a transaction received with eth_call or eth_estimateGas."
     histogram_duration executor_local_call{success, function},
 
@@ -132,6 +243,9 @@ metrics! {
     "Gas spent executing a local call."
     histogram_counter executor_local_call_gas{function},
 
+    "Number of local calls served from the call cache (hit) or executed (miss)."
+    counter executor_local_call_cache{hit},
+
     "Count types of errors when executing a transaction."
     counter executor_transaction_error_types{error_type}
 }
@@ -146,7 +260,13 @@ metrics! {
     histogram_counter evm_execution_account_reads{},
 
     "Number of slots read in a single EVM execution."
-    histogram_counter evm_execution_slot_reads{}
+    histogram_counter evm_execution_slot_reads{},
+
+    "Number of slots read in a single EVM execution that were prefetched before it."
+    histogram_counter evm_execution_slot_prefetch_hits{},
+
+    "Number of EVM executions cancelled because they exceeded the execution timeout."
+    counter evm_execution_timeouts{point_in_time}
 }
 
 metrics! {
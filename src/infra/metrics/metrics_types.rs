@@ -1,10 +1,14 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::RwLock;
 use std::time::Duration;
 
 use metrics::describe_counter;
 use metrics::describe_gauge;
 use metrics::describe_histogram;
 use metrics::Label;
+use once_cell::sync::OnceCell;
 
 pub type HistogramInt = u32;
 pub type Sum = u64;
@@ -26,6 +30,72 @@ pub const LABEL_UNKNOWN: &str = "unknown";
 /// Label value indicating an error happened.
 pub const LABEL_ERROR: &str = "error";
 
+/// Label value replacing new values of a label that reached its max number of distinct values.
+pub const LABEL_OTHER: &str = "other";
+
+/// Label controls applied to all metrics. Labels are recorded as they are if not initialized.
+static LABEL_CONTROLS: OnceCell<MetricLabelControls> = OnceCell::new();
+
+/// Initializes the label controls applied to all metrics. Can be initialized only once.
+pub(super) fn init_label_controls(controls: MetricLabelControls) {
+    if LABEL_CONTROLS.set(controls).is_err() {
+        tracing::warn!("metric label controls were already initialized");
+    }
+}
+
+/// Removes configured labels and bounds the number of distinct values of each label, so the scrape size is bounded.
+pub(super) struct MetricLabelControls {
+    /// Labels removed from all metrics.
+    disabled: HashSet<String>,
+
+    /// Max number of distinct values recorded for each label. Unlimited if zero.
+    max_values: usize,
+
+    /// Distinct values already recorded for each label.
+    values: RwLock<HashMap<&'static str, HashSet<String>>>,
+}
+
+impl MetricLabelControls {
+    pub(super) fn new(disabled: &[String], max_values: usize) -> Self {
+        Self {
+            disabled: disabled.iter().map(|label| label.trim().to_owned()).collect(),
+            max_values,
+            values: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the value to be recorded for the label, or `None` if the label is disabled.
+    fn apply(&self, key: &'static str, value: String) -> Option<String> {
+        if self.disabled.contains(key) {
+            return None;
+        }
+        if self.max_values == 0 {
+            return Some(value);
+        }
+
+        // most values were already recorded, so avoid the write lock
+        let values = self.values.read().unwrap_or_else(|poison_err| poison_err.into_inner());
+        if values.get(key).is_some_and(|label_values| label_values.contains(&value)) {
+            return Some(value);
+        }
+        drop(values);
+
+        let mut values = self.values.write().unwrap_or_else(|poison_err| poison_err.into_inner());
+        let label_values = values.entry(key).or_default();
+        if label_values.contains(&value) {
+            return Some(value);
+        }
+        if label_values.len() >= self.max_values {
+            return Some(LABEL_OTHER.to_owned());
+        }
+        label_values.insert(value.clone());
+        if label_values.len() == self.max_values {
+            tracing::warn!(label = %key, max_values = %self.max_values, "metric label reached max distinct values, new values will be recorded as other");
+        }
+        Some(value)
+    }
+}
+
 // -----------------------------------------------------------------------------
 // Metric
 // -----------------------------------------------------------------------------
@@ -114,13 +184,20 @@ impl From<i32> for MetricLabelValue {
 }
 
 /// Converts a list of label keys-value pairs to `metrics::Label`. Labels with missing values are filtered out.
+///
+/// Label controls are applied if initialized.
 pub(super) fn into_labels(labels: Vec<(&'static str, MetricLabelValue)>) -> Vec<Label> {
+    let controls = LABEL_CONTROLS.get();
     labels
         .into_iter()
         .filter_map(|(key, value)| match value {
             MetricLabelValue::Some(value) => Some((key, value)),
             MetricLabelValue::None => None,
         })
+        .filter_map(|(key, value)| match controls {
+            Some(controls) => controls.apply(key, value).map(|value| (key, value)),
+            None => Some((key, value)),
+        })
         .map(|(key, value)| Label::new(key, value))
         .collect()
 }
@@ -184,3 +261,26 @@ impl<T> Timed<T> {
         self.result
     }
 }
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn label_controls_remove_disabled_labels_and_bound_values() {
+        let controls = MetricLabelControls::new(&["contract".to_owned()], 2);
+
+        assert_eq!(controls.apply("contract", "Token".to_owned()), None);
+
+        assert_eq!(controls.apply("method", "eth_call".to_owned()).as_deref(), Some("eth_call"));
+        assert_eq!(controls.apply("method", "eth_getLogs".to_owned()).as_deref(), Some("eth_getLogs"));
+        assert_eq!(controls.apply("method", "eth_chainId".to_owned()).as_deref(), Some(LABEL_OTHER));
+
+        // values recorded before reaching the limit are kept
+        assert_eq!(controls.apply("method", "eth_call".to_owned()).as_deref(), Some("eth_call"));
+    }
+}
@@ -2,8 +2,14 @@
 
 pub mod blockchain_client;
 pub mod build_info;
+pub mod graceful_shutdown;
 pub mod metrics;
+pub mod node_identity;
+pub mod operations;
+pub mod profiling;
 pub mod sentry;
 pub mod tracing;
 
 pub use blockchain_client::BlockchainClient;
+pub use graceful_shutdown::GracefulShutdown;
+pub use node_identity::NodeIdentity;
@@ -0,0 +1,101 @@
+//! Identity of the node, used by tooling that fingerprints nodes through `web3_clientVersion`, `net_version`, `eth_chainId` and
+//! `stratus_version`.
+
+use serde_json::json;
+
+use crate::alias::JsonValue;
+use crate::eth::primitives::ChainId;
+use crate::eth::storage::PermanentStorageKind;
+use crate::eth::storage::StratusStorageConfig;
+use crate::eth::storage::TemporaryStorageKind;
+use crate::ext::not;
+use crate::ext::to_json_value;
+use crate::infra::build_info;
+
+/// Client version in the `name/version/commit/build-timestamp` format used by other Ethereum clients.
+pub const CLIENT_VERSION: &str = const_format::formatcp!(
+    "stratus/v{}/{}/{}",
+    env!("CARGO_PKG_VERSION"),
+    build_info::GIT_COMMIT,
+    build_info::BUILD_TIMESTAMP
+);
+
+#[derive(Debug, Clone)]
+pub struct NodeIdentity {
+    pub chain_id: ChainId,
+    pub temp_storage_kind: TemporaryStorageKind,
+    pub perm_storage_kind: PermanentStorageKind,
+}
+
+impl NodeIdentity {
+    pub fn new(chain_id: ChainId, storage: &StratusStorageConfig) -> Self {
+        Self {
+            chain_id,
+            temp_storage_kind: storage.temp_storage.temp_storage_kind.clone(),
+            perm_storage_kind: storage.perm_storage.perm_storage_kind.clone(),
+        }
+    }
+
+    /// Client version returned by `web3_clientVersion`.
+    pub fn client_version(&self) -> &'static str {
+        CLIENT_VERSION
+    }
+
+    /// Cargo features the binary was compiled with.
+    pub fn features(&self) -> Vec<&'static str> {
+        build_info::CARGO_FEATURES
+            .split(',')
+            .map(str::trim)
+            .filter(|feature| not(feature.is_empty()))
+            .collect()
+    }
+
+    /// Build info extended with the node identity, returned by `stratus_version`.
+    pub fn as_json(&self) -> JsonValue {
+        let mut info = build_info::as_json();
+        info["node"] = json!({
+            "chain_id": u64::from(self.chain_id),
+            "client_version": self.client_version(),
+            "features": self.features(),
+            "storage": {
+                "temporary": to_json_value(&self.temp_storage_kind),
+                "permanent": to_json_value(&self.perm_storage_kind),
+            },
+        });
+        info
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_identity_includes_build_and_storage() {
+        let identity = NodeIdentity {
+            chain_id: ChainId::from(2008u64),
+            temp_storage_kind: TemporaryStorageKind::InMemory,
+            perm_storage_kind: PermanentStorageKind::Rocks,
+        };
+
+        let client_version = identity.client_version();
+        assert!(client_version.starts_with("stratus/v"));
+        assert!(client_version.contains(build_info::GIT_COMMIT));
+        assert!(client_version.ends_with(build_info::BUILD_TIMESTAMP));
+
+        let info = identity.as_json();
+        assert_eq!(info["node"]["client_version"], client_version);
+        assert_eq!(info["node"]["storage"]["temporary"], "inmemory");
+        assert_eq!(info["node"]["storage"]["permanent"], "rocks");
+        assert!(info["node"]["features"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|feature| not(feature.as_str().unwrap().is_empty())));
+        assert_eq!(info["git"]["commit"], build_info::GIT_COMMIT);
+    }
+}
@@ -0,0 +1,173 @@
+//! Registry of long-running operations that can be listed and cancelled by admin RPCs.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use tokio_util::sync::CancellationToken;
+
+use crate::eth::primitives::StratusError;
+use crate::eth::primitives::UnixTime;
+use crate::ext::MutexExt;
+use crate::globals::STRATUS_SHUTDOWN_SIGNAL;
+
+/// Global registry of running operations.
+pub static OPERATIONS: Lazy<Operations> = Lazy::new(Operations::default);
+
+/// Unique identifier of an operation.
+pub type OperationId = u64;
+
+/// Long-running operations that support cancellation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationKind {
+    /// Online importer executing external blocks.
+    #[strum(to_string = "importer_online")]
+    ImporterOnline,
+
+    /// `eth_getLogs` scanning a range of blocks.
+    #[strum(to_string = "logs_scan")]
+    LogsScan,
+
+    /// Re-execution of transactions or calls to produce traces.
+    #[strum(to_string = "trace")]
+    Trace,
+}
+
+/// Description of a running operation.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationInfo {
+    pub id: OperationId,
+    pub kind: OperationKind,
+    pub description: String,
+    pub started_at: UnixTime,
+    pub cancelled: bool,
+}
+
+struct RunningOperation {
+    info: OperationInfo,
+    token: CancellationToken,
+}
+
+#[derive(Default)]
+pub struct Operations {
+    next_id: AtomicU64,
+    running: Mutex<BTreeMap<OperationId, RunningOperation>>,
+}
+
+impl Operations {
+    /// Registers a new running operation that is unregistered when the returned guard is dropped.
+    ///
+    /// The operation is also cancelled when the application is shutting down.
+    pub fn start(&'static self, kind: OperationKind, description: impl Into<String>) -> Operation {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let token = STRATUS_SHUTDOWN_SIGNAL.child_token();
+        let info = OperationInfo {
+            id,
+            kind,
+            description: description.into(),
+            started_at: UnixTime::now(),
+            cancelled: false,
+        };
+        tracing::info!(%id, %kind, description = %info.description, "starting operation");
+
+        let mut running = self.running.lock_or_clear("operations lock was poisoned");
+        running.insert(id, RunningOperation { info, token: token.clone() });
+        Operation {
+            id,
+            kind,
+            token,
+            registry: self,
+        }
+    }
+
+    /// Lists running operations ordered by id.
+    pub fn list(&self) -> Vec<OperationInfo> {
+        let running = self.running.lock_or_clear("operations lock was poisoned");
+        running
+            .values()
+            .map(|op| OperationInfo {
+                cancelled: op.token.is_cancelled(),
+                ..op.info.clone()
+            })
+            .collect()
+    }
+
+    /// Requests the cancellation of a running operation.
+    ///
+    /// The operation stops at its next cancellation check, so it may still be listed for a short time.
+    pub fn cancel(&self, id: OperationId) -> Result<(), StratusError> {
+        let running = self.running.lock_or_clear("operations lock was poisoned");
+        match running.get(&id) {
+            Some(op) => {
+                tracing::warn!(%id, kind = %op.info.kind, "cancelling operation");
+                op.token.cancel();
+                Ok(())
+            }
+            None => Err(StratusError::OperationNotFound { id }),
+        }
+    }
+}
+
+/// Handle held by a running operation. Unregisters the operation when dropped.
+pub struct Operation {
+    id: OperationId,
+    kind: OperationKind,
+    token: CancellationToken,
+    registry: &'static Operations,
+}
+
+impl Operation {
+    /// Operation identifier.
+    pub fn id(&self) -> OperationId {
+        self.id
+    }
+
+    /// Token cancelled when the operation is cancelled.
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Checks if the operation was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Fails if the operation was cancelled.
+    pub fn check(&self) -> Result<(), StratusError> {
+        if self.is_cancelled() {
+            tracing::warn!(id = %self.id, kind = %self.kind, "operation was cancelled");
+            return Err(StratusError::OperationCancelled { id: self.id });
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Operation {
+    fn drop(&mut self) {
+        self.registry.running.lock_or_clear("operations lock was poisoned").remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_operation() {
+        let operation = OPERATIONS.start(OperationKind::LogsScan, "test");
+        assert!(OPERATIONS.list().iter().any(|op| op.id == operation.id() && !op.cancelled));
+
+        OPERATIONS.cancel(operation.id()).unwrap();
+        assert!(operation.is_cancelled());
+        assert!(matches!(operation.check(), Err(StratusError::OperationCancelled { .. })));
+
+        let id = operation.id();
+        drop(operation);
+        assert!(OPERATIONS.list().iter().all(|op| op.id != id));
+        assert!(matches!(OPERATIONS.cancel(id), Err(StratusError::OperationNotFound { .. })));
+    }
+}
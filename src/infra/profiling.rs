@@ -0,0 +1,257 @@
+//! Opt-in sampling profiler of EVM executions.
+//!
+//! Code executed by EVMs is divided in named scopes, like the execution of a transaction or a storage read, entered with
+//! [`profile_scope`]. While a profile is being collected, each thread publishes the stack of scopes it is in and a sampler records the
+//! stacks of all threads at a fixed frequency. Stacks are sampled by wall-clock time, so time spent waiting for the storage is visible,
+//! unlike in CPU profilers.
+//!
+//! Profiles are exported in the folded stacks format (`thread;scope;scope samples`) accepted by flamegraph.pl, inferno and speedscope.
+//! Scopes cost a single atomic load when no profile is being collected.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::Weak;
+use std::time::Duration;
+use std::time::Instant;
+
+use clap::Parser;
+use display_json::DebugAsJson;
+use itertools::Itertools;
+
+use crate::ext::not;
+use crate::ext::parse_duration;
+use crate::ext::MutexExt;
+
+/// Indicates if a profile is being collected, so scopes are recorded.
+static SAMPLING: AtomicBool = AtomicBool::new(false);
+
+/// Scope stacks of all threads that entered a scope while a profile was being collected.
+static THREADS: Mutex<Vec<Weak<ThreadScopes>>> = Mutex::new(Vec::new());
+
+thread_local! {
+    static CURRENT_THREAD: RefCell<Option<Arc<ThreadScopes>>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug)]
+struct ThreadScopes {
+    /// Thread name without its index, so threads of the same pool are aggregated.
+    group: String,
+    scopes: Mutex<Vec<&'static str>>,
+}
+
+// -----------------------------------------------------------------------------
+// Scopes
+// -----------------------------------------------------------------------------
+
+/// Scope entered by the current thread. Left when dropped.
+#[must_use]
+pub struct ProfileScope {
+    recorded: bool,
+}
+
+/// Enters a named scope in the current thread, recorded only while a profile is being collected.
+pub fn profile_scope(name: &'static str) -> ProfileScope {
+    if not(SAMPLING.load(Ordering::Relaxed)) {
+        return ProfileScope { recorded: false };
+    }
+    with_current_thread(|thread| thread.scopes.lock_or_clear("profile scopes lock was poisoned").push(name));
+    ProfileScope { recorded: true }
+}
+
+impl Drop for ProfileScope {
+    fn drop(&mut self) {
+        if self.recorded {
+            with_current_thread(|thread| thread.scopes.lock_or_clear("profile scopes lock was poisoned").pop());
+        }
+    }
+}
+
+fn with_current_thread<T>(f: impl FnOnce(&ThreadScopes) -> T) -> T {
+    CURRENT_THREAD.with(|current| {
+        let mut current = current.borrow_mut();
+        let thread = current.get_or_insert_with(|| {
+            let thread = Arc::new(ThreadScopes {
+                group: thread_group(std::thread::current().name().unwrap_or("unnamed")),
+                scopes: Mutex::new(Vec::new()),
+            });
+            THREADS.lock_or_clear("profile threads lock was poisoned").push(Arc::downgrade(&thread));
+            thread
+        });
+        f(thread)
+    })
+}
+
+/// Removes the index from thread names like `evm-tx-parallel-3`.
+fn thread_group(name: &str) -> String {
+    match name.rsplit_once('-') {
+        Some((group, index)) if not(index.is_empty()) && index.chars().all(|c| c.is_ascii_digit()) => group.to_owned(),
+        _ => name.to_owned(),
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Profiler
+// -----------------------------------------------------------------------------
+
+/// Collects profiles on demand.
+#[derive(Debug)]
+pub struct Profiler {
+    enabled: bool,
+    frequency: u32,
+    max_duration: Duration,
+
+    /// Held while a profile is being collected, so only one is collected at a time.
+    collecting: Mutex<()>,
+}
+
+/// Stacks sampled during a profile, with the number of samples of each one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FoldedProfile {
+    pub stacks: BTreeMap<String, u64>,
+}
+
+impl FoldedProfile {
+    /// Formats the profile as folded stacks, one stack per line.
+    pub fn to_folded(&self) -> String {
+        let mut folded = String::new();
+        for (stack, samples) in &self.stacks {
+            let _ = writeln!(folded, "{} {}", stack, samples);
+        }
+        folded
+    }
+}
+
+impl Profiler {
+    pub fn new(config: &ProfilingConfig) -> Self {
+        Self {
+            enabled: config.profiling_enabled,
+            frequency: config.profiling_frequency.max(1),
+            max_duration: config.profiling_max_duration,
+            collecting: Mutex::new(()),
+        }
+    }
+
+    /// Checks if profiles can be collected.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Samples the scope stacks of all threads during the specified duration, limited to the max duration.
+    ///
+    /// Blocks the current thread until the profile is collected. Returns `None` if profiling is disabled or another profile is being
+    /// collected.
+    pub fn collect(&self, duration: Duration) -> Option<FoldedProfile> {
+        if not(self.enabled) {
+            return None;
+        }
+        let Ok(_collecting) = self.collecting.try_lock() else {
+            tracing::warn!("cannot collect profile because another profile is being collected");
+            return None;
+        };
+
+        let duration = duration.min(self.max_duration);
+        let interval = Duration::from_secs(1) / self.frequency;
+        tracing::info!(?duration, frequency = %self.frequency, "collecting execution profile");
+
+        let mut profile = FoldedProfile::default();
+        SAMPLING.store(true, Ordering::Relaxed);
+        let start = Instant::now();
+        while start.elapsed() < duration {
+            sample(&mut profile);
+            std::thread::sleep(interval);
+        }
+        SAMPLING.store(false, Ordering::Relaxed);
+
+        tracing::info!(stacks = %profile.stacks.len(), "collected execution profile");
+        Some(profile)
+    }
+}
+
+/// Records the current scope stack of each thread.
+fn sample(profile: &mut FoldedProfile) {
+    let mut threads = THREADS.lock_or_clear("profile threads lock was poisoned");
+    threads.retain(|thread| thread.strong_count() > 0);
+    for thread in threads.iter().filter_map(Weak::upgrade) {
+        let scopes = thread.scopes.lock_or_clear("profile scopes lock was poisoned");
+        if scopes.is_empty() {
+            continue;
+        }
+        let stack = std::iter::once(thread.group.as_str()).chain(scopes.iter().copied()).join(";");
+        *profile.stacks.entry(stack).or_default() += 1;
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Config
+// -----------------------------------------------------------------------------
+
+#[derive(DebugAsJson, Clone, Parser, serde::Serialize)]
+pub struct ProfilingConfig {
+    /// Enables the `/debug/profile` endpoint, which samples where EVMs spend time, including storage waits, and returns folded stacks
+    /// for flamegraph tools.
+    #[arg(long = "profiling-enabled", env = "PROFILING_ENABLED", default_value = "false")]
+    pub profiling_enabled: bool,
+
+    /// Number of samples per second collected while a profile is being collected.
+    #[arg(long = "profiling-frequency", env = "PROFILING_FREQUENCY", default_value = "100")]
+    pub profiling_frequency: u32,
+
+    /// Max duration of a profile.
+    #[arg(long = "profiling-max-duration", value_parser=parse_duration, env = "PROFILING_MAX_DURATION", default_value = "60s")]
+    pub profiling_max_duration: Duration,
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiler_samples_scope_stacks_by_thread_group() {
+        assert_eq!(thread_group("evm-tx-parallel-12"), "evm-tx-parallel");
+        assert_eq!(thread_group("rpc-hot"), "rpc-hot");
+
+        let profiler = Profiler::new(&ProfilingConfig {
+            profiling_enabled: true,
+            profiling_frequency: 1000,
+            profiling_max_duration: Duration::from_millis(200),
+        });
+
+        // thread waits inside a storage read while the profile is collected
+        let worker = std::thread::Builder::new()
+            .name("evm-test-1".into())
+            .spawn(|| {
+                while not(SAMPLING.load(Ordering::Relaxed)) {
+                    std::thread::yield_now();
+                }
+                let _execute = profile_scope("evm::execute");
+                let _read = profile_scope("storage::read_slot");
+                std::thread::sleep(Duration::from_millis(100));
+            })
+            .unwrap();
+
+        let profile = profiler.collect(Duration::from_secs(10)).unwrap();
+        worker.join().unwrap();
+        assert!(profile
+            .stacks
+            .get("evm-test;evm::execute;storage::read_slot")
+            .is_some_and(|samples| *samples > 0));
+        assert!(profile.to_folded().contains("evm-test;evm::execute;storage::read_slot "));
+
+        // disabled profiler does not collect
+        let disabled = Profiler::new(&ProfilingConfig {
+            profiling_enabled: false,
+            profiling_frequency: 100,
+            profiling_max_duration: Duration::from_secs(1),
+        });
+        assert!(disabled.collect(Duration::from_secs(1)).is_none());
+    }
+}
@@ -1,7 +1,10 @@
 use std::sync::Arc;
 
 use stratus::config::StratusConfig;
+use stratus::eth::primitives::ChainId;
 use stratus::eth::rpc::serve_rpc;
+use stratus::infra::GracefulShutdown;
+use stratus::infra::NodeIdentity;
 use stratus::GlobalServices;
 use stratus::GlobalState;
 #[cfg(all(not(target_env = "msvc"), any(feature = "jemalloc", feature = "jeprof")))]
@@ -21,33 +24,62 @@ async fn run(config: StratusConfig) -> anyhow::Result<()> {
     // Init services
     let storage = config.storage.init()?;
 
-    // Init miner
-    let miner = config.miner.init(Arc::clone(&storage)).await?;
+    // Validate genesis file against the configured chain
+    if let Some(genesis_chain_id) = storage.genesis().and_then(|genesis| genesis.chain_id) {
+        let chain_id = ChainId::from(config.executor.executor_chain_id);
+        if genesis_chain_id != chain_id {
+            anyhow::bail!("genesis file chain id {} does not match executor chain id {}", genesis_chain_id, chain_id);
+        }
+    }
 
-    // Init executor
-    let executor = config.executor.init(Arc::clone(&storage), Arc::clone(&miner));
+    // Init retention janitor
+    config.retention.init(Arc::clone(&storage));
 
-    // Init importer
-    let consensus = if let Some(importer_config) = &config.importer {
-        importer_config.init(Arc::clone(&executor), Arc::clone(&miner), Arc::clone(&storage)).await?
-    } else {
+    // Init miner (archive nodes serve only mined data, so they do not mine)
+    let miner = if GlobalState::is_archive() {
         None
+    } else {
+        Some(config.miner.init(Arc::clone(&storage)).await?)
     };
 
+    // Init executor (without a miner, it executes only calls and traces)
+    let executor = config.executor.init(Arc::clone(&storage), miner.clone());
+
+    // Init importer
+    let consensus = match (&config.importer, &miner) {
+        (Some(importer_config), Some(miner)) => importer_config.init(Arc::clone(&executor), Arc::clone(miner), Arc::clone(&storage)).await?,
+        _ => None,
+    };
+
+    // Init block production watchdog and event publishers
+    if let Some(ref miner) = miner {
+        config.watchdog.init(Arc::clone(miner), Arc::clone(&executor), Arc::clone(&storage));
+        config.publisher.init(Arc::clone(&storage), miner)?;
+    }
+
     // Init RPC server
+    let shutdown = GracefulShutdown::new(config.shutdown_drain_timeout);
     serve_rpc(
         // Services
         Arc::clone(&storage),
-        executor,
-        miner,
+        Arc::clone(&executor),
+        miner.clone(),
         consensus,
+        shutdown.clone(),
         // Config
         config.clone(),
         config.rpc_server,
-        config.executor.executor_chain_id.into(),
+        NodeIdentity::new(config.executor.executor_chain_id.into(), &config.storage),
     )
     .await?;
 
+    // Drain services after the RPC server stopped accepting requests
+    if let Some(ref miner) = miner {
+        shutdown.drain(&executor, miner, &storage).await;
+    }
+    drop(executor);
+    drop(miner);
+
     // Explicitly block the `main` thread to drop the storage.
     drop(storage);
 
@@ -0,0 +1,60 @@
+create table mined_block_number(
+    id int primary key not null default 1 check (id = 1),
+    number bigint not null check (number >= 0)
+);
+
+create table blocks(
+    number bigint primary key not null check (number >= 0),
+    hash bytea unique not null check (length(hash) = 32),
+    payload jsonb not null
+);
+
+create table transactions(
+    hash bytea primary key not null check (length(hash) = 32),
+    block_number bigint not null check (block_number >= 0),
+    payload jsonb not null
+);
+create index transactions_block_number on transactions(block_number);
+
+create table address_transactions(
+    address bytea not null check (length(address) = 20),
+    block_number bigint not null check (block_number >= 0),
+    transaction_index bigint not null check (transaction_index >= 0),
+    hash bytea not null check (length(hash) = 32),
+    primary key (address, block_number, transaction_index)
+);
+create index address_transactions_block_number on address_transactions(block_number);
+
+create table logs(
+    block_number bigint not null check (block_number >= 0),
+    log_index bigint not null check (log_index >= 0),
+    address bytea not null check (length(address) = 20),
+    payload jsonb not null,
+    primary key (block_number, log_index)
+);
+create index logs_address_block_number on logs(address, block_number);
+
+create table accounts(
+    address bytea not null check (length(address) = 20),
+    block_number bigint not null check (block_number >= 0),
+    payload jsonb not null,
+    primary key (address, block_number)
+);
+
+create table account_codes(
+    code_hash bytea primary key not null check (length(code_hash) = 32),
+    bytecode bytea not null
+);
+
+create table account_slots(
+    address bytea not null check (length(address) = 20),
+    idx bytea not null check (length(idx) = 32),
+    block_number bigint not null check (block_number >= 0),
+    value bytea not null check (length(value) = 32),
+    primary key (address, idx, block_number)
+);
+
+create table publisher_outbox(
+    publisher text primary key not null,
+    block_number bigint not null check (block_number >= 0)
+);